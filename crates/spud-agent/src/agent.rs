@@ -1,4 +1,4 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::Result;
 
@@ -52,6 +52,11 @@ impl Agent {
     pub fn mood(&self) -> Mood {
         self.engine.mood()
     }
+
+    /// Change how long each animation frame is shown (default 300 ms).
+    pub fn set_frame_interval(&mut self, interval: Duration) {
+        self.engine.set_frame_interval(interval);
+    }
 }
 
 #[cfg(test)]
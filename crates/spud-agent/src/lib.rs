@@ -22,3 +22,6 @@ mod types;
 
 pub use agent::Agent;
 pub use types::{AsciiFrame, FacePack, Mood};
+
+/// Version of this crate, as reported by `spud version`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
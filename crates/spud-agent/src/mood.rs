@@ -63,6 +63,12 @@ impl MoodEngine {
     pub fn mood(&self) -> Mood {
         self.mood
     }
+
+    /// Change the interval between animation frames. Zero is treated as
+    /// one millisecond.
+    pub fn set_frame_interval(&mut self, interval: Duration) {
+        self.frame_interval = interval.max(Duration::from_millis(1));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,147 @@
+//! Command-line interface for the `spud` binary.
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use spud_runtime::{version, IfRunning};
+
+/// SPUD — a DOOM-inspired terminal dashboard.
+#[derive(Debug, Parser)]
+#[command(name = "spud", version = version::SUMMARY, long_version = version::long())]
+pub(crate) struct Cli {
+    /// Directory holding SPUD config files such as `alerts.toml`
+    /// [default: <platform config dir>/spud]
+    #[arg(long = "config", value_name = "DIR", env = "SPUD_CONFIG_DIR")]
+    pub config_dir: Option<PathBuf>,
+
+    /// Named workspace under `<config dir>/workspaces/` with its own
+    /// settings, modules, and plugin roots
+    #[arg(long, value_name = "NAME", env = "SPUD_WORKSPACE")]
+    pub workspace: Option<String>,
+
+    /// Module to activate on startup (e.g. `stats`)
+    #[arg(long, value_name = "ID")]
+    pub module: Option<String>,
+
+    /// Directory to search for plugin manifests; repeatable, added to
+    /// `SPUD_PLUGIN_DIRS`
+    #[arg(long = "plugin-dir", value_name = "PATH")]
+    pub plugin_dirs: Vec<PathBuf>,
+
+    /// Run modules, plugins, and alerts without the terminal UI; logs go to
+    /// stderr
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Start without restoring the last session (console history and
+    /// visibility, active module, HUD size)
+    #[arg(long)]
+    pub fresh: bool,
+
+    /// Log filter (`error`..`trace` or a directive like
+    /// `spud_remote=debug`); overrides `SPUD_LOG`/`RUST_LOG`
+    #[arg(long, value_name = "FILTER")]
+    pub log_level: Option<String>,
+
+    /// Append every bus event (except ticks and spans) to FILE as JSON lines
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Seed for random behaviour such as idle animations, so a run can be
+    /// reproduced; logged at startup and written to `--record` files
+    /// [default: from the clock]
+    #[arg(long, value_name = "N", env = "SPUD_SEED")]
+    pub seed: Option<u64>,
+
+    /// What to do when SPUD already runs for this state directory:
+    /// `refuse`, `attach` to run commands in it, or `steal` to make it quit
+    /// and start in its place
+    #[arg(long, value_name = "ACTION", default_value = "refuse")]
+    pub if_running: IfRunningArg,
+
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+/// Subcommands that run instead of the dashboard.
+#[derive(Debug, Subcommand)]
+pub(crate) enum CliCommand {
+    /// Check the environment and configuration, then exit
+    Doctor,
+    /// Show the screen of the SPUD that is already running (started with
+    /// `serve`), or run console commands from stdin in it
+    Attach,
+    /// Run SPUD without a terminal for `attach` to show; it keeps running
+    /// when the attached terminal closes
+    Serve,
+}
+
+/// `--if-running` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum IfRunningArg {
+    Refuse,
+    Attach,
+    Steal,
+}
+
+impl From<IfRunningArg> for IfRunning {
+    fn from(arg: IfRunningArg) -> Self {
+        match arg {
+            IfRunningArg::Refuse => Self::Refuse,
+            IfRunningArg::Attach => Self::Attach,
+            IfRunningArg::Steal => Self::Steal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_repeatable_plugin_dirs_and_subcommand() {
+        let cli = Cli::try_parse_from([
+            "spud",
+            "--plugin-dir",
+            "a",
+            "--plugin-dir",
+            "b",
+            "--module",
+            "stats",
+            "--workspace",
+            "work",
+            "doctor",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.plugin_dirs,
+            vec![PathBuf::from("a"), PathBuf::from("b")]
+        );
+        assert_eq!(cli.module.as_deref(), Some("stats"));
+        assert_eq!(cli.workspace.as_deref(), Some("work"));
+        assert!(matches!(cli.command, Some(CliCommand::Doctor)));
+        assert_eq!(cli.seed, None);
+
+        assert!(!cli.fresh);
+
+        assert_eq!(cli.if_running, IfRunningArg::Refuse);
+
+        let cli = Cli::try_parse_from(["spud", "--seed", "42", "--fresh"]).unwrap();
+        assert_eq!(cli.seed, Some(42));
+        assert!(cli.fresh);
+
+        let cli = Cli::try_parse_from(["spud", "--if-running", "steal", "attach"]).unwrap();
+        assert_eq!(IfRunning::from(cli.if_running), IfRunning::Steal);
+        assert!(matches!(cli.command, Some(CliCommand::Attach)));
+
+        let cli = Cli::try_parse_from(["spud", "--fresh", "serve"]).unwrap();
+        assert!(matches!(cli.command, Some(CliCommand::Serve)));
+    }
+}
@@ -0,0 +1,327 @@
+//! Alert rule configuration loaded from `alerts.toml`.
+//!
+//! ```toml
+//! [[rule]]
+//! name = "hot-cpu"
+//! when = "stats.cpu > 90 for 30s"
+//! severity = "critical"
+//!
+//! [[rule]]
+//! name = "plugin-errors"
+//! when = "plugin.errors > 5/min"
+//! command = "plugins stats"
+//! ```
+//!
+//! A condition names a telemetry series as `source.key` (a bare `key` matches
+//! that key from any source), a comparison, and a threshold. A `/sec`,
+//! `/min`, or `/hour` suffix compares the series' increase over that period
+//! instead of its latest value. An optional `for <duration>` requires the
+//! condition to hold continuously before the alert fires.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Alert rules file schema.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertsConfig {
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<AlertRuleConfig>,
+}
+
+/// One alert rule as written in config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRuleConfig {
+    pub name: String,
+    /// Condition expression, e.g. `stats.cpu > 90 for 30s`.
+    pub when: String,
+    #[serde(default)]
+    pub severity: AlertSeverity,
+    /// Text shown when the alert fires; defaults to the condition.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Console command run once each time the alert fires.
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+/// How loudly an alert is surfaced.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    #[default]
+    Warn,
+    Critical,
+}
+
+impl AlertSeverity {
+    /// Lowercase name used in config and console output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// Comparison operator in an alert condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Comparison {
+    /// Apply the comparison as `value <op> threshold`.
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Self::Gt => value > threshold,
+            Self::Ge => value >= threshold,
+            Self::Lt => value < threshold,
+            Self::Le => value <= threshold,
+        }
+    }
+
+    /// Operator as written in config.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+        }
+    }
+}
+
+/// Parsed form of an [`AlertRuleConfig::when`] expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertCondition {
+    /// Telemetry source, or `None` to match the key from any source.
+    pub source: Option<String>,
+    pub key: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// Compare the increase over this period instead of the latest value.
+    pub rate_per: Option<Duration>,
+    /// How long the condition must hold before firing.
+    pub sustain: Duration,
+}
+
+impl AlertCondition {
+    /// Parse `<metric> <op> <threshold>[/<unit>] [for <duration>]`.
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        let (metric, op, threshold, sustain) = match tokens.as_slice() {
+            [metric, op, threshold] => (*metric, *op, *threshold, None),
+            [metric, op, threshold, "for", sustain] => (*metric, *op, *threshold, Some(*sustain)),
+            _ => bail!(
+                "expected `<source.key> <op> <threshold>[/unit] [for <duration>]`, got `{input}`"
+            ),
+        };
+
+        let (source, key) = match metric.split_once('.') {
+            Some((source, key)) if !source.is_empty() && !key.is_empty() => {
+                (Some(source.to_string()), key.to_string())
+            }
+            None if !metric.is_empty() => (None, metric.to_string()),
+            _ => bail!("invalid metric name `{metric}`"),
+        };
+
+        let comparison = match op {
+            ">" => Comparison::Gt,
+            ">=" => Comparison::Ge,
+            "<" => Comparison::Lt,
+            "<=" => Comparison::Le,
+            other => bail!("unsupported comparison `{other}` (use >, >=, <, <=)"),
+        };
+
+        let (threshold, rate_per) = match threshold.split_once('/') {
+            Some((value, unit)) => {
+                let per = match unit {
+                    "s" | "sec" => Duration::from_secs(1),
+                    "m" | "min" => Duration::from_secs(60),
+                    "h" | "hour" => Duration::from_secs(3_600),
+                    other => bail!("unsupported rate unit `{other}` (use sec, min, hour)"),
+                };
+                (value, Some(per))
+            }
+            None => (threshold, None),
+        };
+        let threshold: f64 = threshold
+            .parse()
+            .with_context(|| format!("invalid threshold `{threshold}`"))?;
+
+        let sustain = match sustain {
+            Some(raw) => parse_duration(raw)?,
+            None => Duration::ZERO,
+        };
+
+        Ok(Self {
+            source,
+            key,
+            comparison,
+            threshold,
+            rate_per,
+            sustain,
+        })
+    }
+}
+
+/// Parse a duration such as `500ms`, `30s`, `5m`, or `1h`.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (digits, unit) = input.split_at(split);
+    let value: u64 = digits
+        .parse()
+        .with_context(|| format!("invalid duration `{input}`"))?;
+    let secs = |per_unit: u64| {
+        value
+            .checked_mul(per_unit)
+            .with_context(|| format!("duration `{input}` is too long"))
+    };
+    let duration = match unit {
+        "ms" => Duration::from_millis(value),
+        "s" | "" => Duration::from_secs(value),
+        "m" => Duration::from_secs(secs(60)?),
+        "h" => Duration::from_secs(secs(3_600)?),
+        other => bail!("invalid duration unit `{other}` in `{input}` (use ms, s, m, h)"),
+    };
+    Ok(duration)
+}
+
+impl AlertsConfig {
+    /// Parse and validate alert rules TOML.
+    pub fn from_toml_str(input: &str) -> Result<Self> {
+        let config: Self = toml::from_str(input).context("failed to parse alerts TOML")?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load and validate alert rules from disk.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read alerts config at {}", path.display()))?;
+
+        Self::from_toml_str(&raw)
+            .with_context(|| format!("invalid alerts config at {}", path.display()))
+    }
+
+    /// Check rule names are unique and every condition parses.
+    pub fn validate(&self) -> Result<()> {
+        let mut names = BTreeSet::new();
+        for rule in &self.rules {
+            if rule.name.trim().is_empty() {
+                bail!("alert rule name must not be empty");
+            }
+            if !names.insert(rule.name.as_str()) {
+                bail!("duplicate alert rule name: {}", rule.name);
+            }
+            AlertCondition::parse(&rule.when)
+                .with_context(|| format!("alert rule {} has an invalid condition", rule.name))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_threshold_with_sustain() {
+        let condition = AlertCondition::parse("stats.cpu > 90 for 30s").unwrap();
+        assert_eq!(condition.source.as_deref(), Some("stats"));
+        assert_eq!(condition.key, "cpu");
+        assert_eq!(condition.comparison, Comparison::Gt);
+        assert_eq!(condition.threshold, 90.0);
+        assert_eq!(condition.rate_per, None);
+        assert_eq!(condition.sustain, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn parses_rate_and_bare_key() {
+        let condition = AlertCondition::parse("errors >= 5/min").unwrap();
+        assert_eq!(condition.source, None);
+        assert_eq!(condition.rate_per, Some(Duration::from_secs(60)));
+        assert_eq!(condition.sustain, Duration::ZERO);
+
+        let dotted = AlertCondition::parse("plugin.errors > 5/min").unwrap();
+        assert_eq!(dotted.source.as_deref(), Some("plugin"));
+    }
+
+    #[test]
+    fn rejects_malformed_conditions() {
+        for bad in [
+            "cpu 90",
+            "cpu == 90",
+            "cpu > hot",
+            "cpu > 5/day",
+            "cpu > 90 for soon",
+            "cpu > 90 until 5s",
+            ".cpu > 1",
+        ] {
+            assert!(AlertCondition::parse(bad).is_err(), "{bad} should fail");
+        }
+    }
+
+    #[test]
+    fn config_rejects_duplicate_names_and_bad_conditions() {
+        let config = AlertsConfig::from_toml_str(
+            r#"
+[[rule]]
+name = "hot"
+when = "stats.cpu > 90 for 30s"
+severity = "critical"
+command = "plugins stats"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.rules[0].severity, AlertSeverity::Critical);
+        assert_eq!(config.rules[0].command.as_deref(), Some("plugins stats"));
+
+        let duplicate = AlertsConfig::from_toml_str(
+            r#"
+[[rule]]
+name = "hot"
+when = "cpu > 1"
+
+[[rule]]
+name = "hot"
+when = "cpu > 2"
+"#,
+        );
+        assert!(duplicate.is_err());
+
+        let invalid = AlertsConfig::from_toml_str("[[rule]]\nname = \"x\"\nwhen = \"cpu ~ 1\"\n");
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7_200));
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn parse_duration_rejects_overflow() {
+        let err = parse_duration("99999999999999999h").unwrap_err();
+        assert_eq!(err.to_string(), "duration `99999999999999999h` is too long");
+        assert!(parse_duration("99999999999999999999s").is_err());
+    }
+}
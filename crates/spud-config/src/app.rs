@@ -0,0 +1,1355 @@
+//! Application settings loaded from `spud.toml`.
+//!
+//! ```toml
+//! log_level = "info"        # tracing filter, same syntax as SPUD_LOG
+//! locale = "de"             # UI language; read at startup
+//! profile = "balanced"      # battery | balanced | performance
+//! tick_interval_ms = 100    # overrides the profile's tick rate
+//! transcript = false        # append console sessions to a transcript file
+//!
+//! [log]
+//! max_file_mb = 10          # start a new log file past this size; 0 rotates daily only
+//! max_files = 20            # log files kept in all; the oldest go first
+//! retention_days = 7        # delete log files older than this
+//! max_total_mb = 500        # delete the oldest log files past this total; 0 is no limit
+//! compress = "gzip"         # none | gzip | zstd; for files no longer written to
+//!
+//! [hud]
+//! height = 9
+//! face_width = 18
+//!
+//! [face]
+//! scale = "integer"         # none | integer | fit; sizing of pixel faces in the face panel
+//! filter = "lanczos"        # nearest | bilinear | lanczos
+//! letterbox = true          # keep the aspect ratio; false stretches to fill the panel
+//! graphics = "half-block"   # half-block | quadrant | braille
+//!
+//! [console]
+//! height = 50               # percent of the screen; 100 is full height
+//! anchor = "top"            # top | bottom
+//! background = "solid"      # solid | dim | transparent
+//! slide_ms = 250            # overrides the profile's slide length; 0 is instant
+//! easing = "ease-out-cubic" # linear | ease-out-quad | ease-out-cubic | ease-in-out-cubic
+//!
+//! [terminal]
+//! sync_output = "auto"      # auto | on | off; wrap frames in synchronized updates (DEC 2026)
+//!
+//! [idle]
+//! after_secs = 300          # attract mode after this long without a key
+//! cycle_secs = 20           # switch modules this often while in it
+//!
+//! [bus]
+//! max_queue = 4096          # events waiting to be dispatched
+//! overflow = "drop-oldest"  # drop-oldest | drop-newest | block
+//!
+//! [commands]
+//! destructive = "confirm"   # confirm | allow | deny
+//! disabled = ["plugins stop"]
+//!
+//! [plugins]
+//! trusted_keys = ["RWQ…"]   # minisign public keys that may sign plugins
+//! require_signatures = true # plugins outside local_roots must be signed
+//! local_roots = ["/home/me/src/spud-plugins"]
+//!
+//! [export]
+//! enabled = true            # write the agent frame and metrics to a shared file
+//! path = "/run/user/1000/spud-frame"  # optional; absolute
+//! interval_ms = 100         # time between writes
+//!
+//! [http]
+//! enabled = true            # read-only status page; needs the `http` feature
+//! bind = "127.0.0.1:7878"
+//!
+//! [mdns]
+//! enabled = true            # advertise the status page on the LAN; needs the `mdns` feature
+//! name = "den"              # optional; instance name, default the host name
+//!
+//! [modules.stats]        # per-module sections; see `modules`
+//! hide = ["cores"]
+//! ```
+//!
+//! Every field is optional. The app watches the file and applies changes
+//! while running.
+
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Error, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::modules::ModulesConfig;
+use crate::profile::Profile;
+
+/// File name of the settings file inside the config directory.
+pub const APP_CONFIG_FILE: &str = "spud.toml";
+
+const TICK_INTERVAL_MS: RangeInclusive<u64> = 10..=10_000;
+const HUD_HEIGHT: RangeInclusive<u16> = 5..=40;
+const HUD_FACE_WIDTH: RangeInclusive<u16> = 4..=80;
+const CONSOLE_HEIGHT: RangeInclusive<u16> = 20..=100;
+const CONSOLE_SLIDE_MS: RangeInclusive<u64> = 0..=2_000;
+const IDLE_AFTER_SECS: RangeInclusive<u64> = 5..=86_400;
+const IDLE_CYCLE_SECS: RangeInclusive<u64> = 2..=3_600;
+const BUS_MAX_QUEUE: RangeInclusive<usize> = 16..=1_000_000;
+const LOG_MAX_FILE_MB: RangeInclusive<u64> = 0..=4_096;
+const LOG_MAX_FILES: RangeInclusive<usize> = 1..=1_000;
+const LOG_RETENTION_DAYS: RangeInclusive<u64> = 1..=3_650;
+const LOG_MAX_TOTAL_MB: RangeInclusive<u64> = 0..=1_048_576;
+const EXPORT_INTERVAL_MS: RangeInclusive<u64> = 16..=60_000;
+/// Longest DNS label.
+const MDNS_NAME_MAX: usize = 63;
+
+/// `spud.toml` schema.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppConfig {
+    /// Log filter directive; `None` leaves the environment/CLI filter alone.
+    #[serde(default)]
+    pub log_level: Option<String>,
+    /// UI locale such as `de` or `pt-BR`; `None` detects it from the
+    /// environment. Only read at startup.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Timing trade-offs; see [`Profile`].
+    #[serde(default)]
+    pub profile: Profile,
+    /// Milliseconds between app ticks; `None` uses the profile's rate.
+    #[serde(default)]
+    pub tick_interval_ms: Option<u64>,
+    /// Append console commands and their output to a per-session
+    /// transcript file in the log directory.
+    #[serde(default)]
+    pub transcript: bool,
+    #[serde(default)]
+    pub log: LogConfig,
+    #[serde(default)]
+    pub hud: HudConfig,
+    #[serde(default)]
+    pub face: FaceConfig,
+    #[serde(default)]
+    pub console: ConsoleConfig,
+    #[serde(default)]
+    pub terminal: TerminalConfig,
+    #[serde(default)]
+    pub idle: IdleConfig,
+    #[serde(default)]
+    pub bus: BusConfig,
+    #[serde(default)]
+    pub commands: CommandsConfig,
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub http: HttpConfig,
+    #[serde(default)]
+    pub mdns: MdnsConfig,
+    /// Per-module sections, `[modules.<id>]`.
+    #[serde(default)]
+    pub modules: ModulesConfig,
+}
+
+/// Rotation and retention of the log files in the log directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct LogConfig {
+    /// MiB a log file may reach before the next one starts; 0 starts a new
+    /// file only when the day changes.
+    pub max_file_mb: u64,
+    /// Log files kept in all, counting the one being written; the oldest
+    /// are deleted first.
+    pub max_files: usize,
+    /// Days a log file is kept.
+    pub retention_days: u64,
+    /// MiB all log files may take together; the oldest are deleted first.
+    /// 0 is no limit.
+    pub max_total_mb: u64,
+    /// How files no longer being written to are compressed.
+    pub compress: LogCompression,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_file_mb: 10,
+            max_files: 20,
+            retention_days: 7,
+            max_total_mb: 0,
+            compress: LogCompression::default(),
+        }
+    }
+}
+
+impl LogConfig {
+    fn validate(&self) -> Result<()> {
+        if !LOG_MAX_FILE_MB.contains(&self.max_file_mb) {
+            bail!(
+                "log.max_file_mb must be between {} and {}",
+                LOG_MAX_FILE_MB.start(),
+                LOG_MAX_FILE_MB.end()
+            );
+        }
+        if !LOG_MAX_FILES.contains(&self.max_files) {
+            bail!(
+                "log.max_files must be between {} and {}",
+                LOG_MAX_FILES.start(),
+                LOG_MAX_FILES.end()
+            );
+        }
+        if !LOG_RETENTION_DAYS.contains(&self.retention_days) {
+            bail!(
+                "log.retention_days must be between {} and {}",
+                LOG_RETENTION_DAYS.start(),
+                LOG_RETENTION_DAYS.end()
+            );
+        }
+        if !LOG_MAX_TOTAL_MB.contains(&self.max_total_mb) {
+            bail!(
+                "log.max_total_mb must be between {} and {}",
+                LOG_MAX_TOTAL_MB.start(),
+                LOG_MAX_TOTAL_MB.end()
+            );
+        }
+        Ok(())
+    }
+
+    /// Size at which a log file is rotated, if size rotation is on.
+    pub fn max_file_bytes(&self) -> Option<u64> {
+        (self.max_file_mb > 0).then(|| self.max_file_mb * 1024 * 1024)
+    }
+
+    /// How long a log file is kept.
+    pub fn retention(&self) -> Duration {
+        Duration::from_secs(self.retention_days * 86_400)
+    }
+
+    /// Size all log files may take together, if limited.
+    pub fn max_total_bytes(&self) -> Option<u64> {
+        (self.max_total_mb > 0).then(|| self.max_total_mb * 1024 * 1024)
+    }
+}
+
+/// Compression of log files the writer has moved on from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogCompression {
+    /// Keep them as plain text.
+    #[default]
+    None,
+    /// `.gz`, readable with `zcat` and `zless`.
+    Gzip,
+    /// `.zst`, smaller and faster than gzip; read with `zstdcat`.
+    Zstd,
+}
+
+impl LogCompression {
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// How pixel-art faces are fitted into the HUD face panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct FaceConfig {
+    pub scale: FaceScale,
+    /// Resampling filter for sizes that are not whole multiples.
+    pub filter: FaceFilter,
+    /// Scale both axes alike and centre the face; otherwise each axis is
+    /// scaled to fill the panel.
+    pub letterbox: bool,
+    /// Characters the pixels are drawn with.
+    pub graphics: GraphicsMode,
+}
+
+impl Default for FaceConfig {
+    fn default() -> Self {
+        Self {
+            scale: FaceScale::default(),
+            filter: FaceFilter::default(),
+            letterbox: true,
+            graphics: GraphicsMode::default(),
+        }
+    }
+}
+
+/// How a face's size follows the panel's.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FaceScale {
+    /// Drawn pixel for pixel and clipped to the panel.
+    None,
+    /// Pixel-perfect: the largest whole multiple (or whole fraction) of
+    /// the face's size that fits.
+    #[default]
+    Integer,
+    /// As large as fits, at any ratio.
+    Fit,
+}
+
+impl FaceScale {
+    pub const ALL: [FaceScale; 3] = [FaceScale::None, FaceScale::Integer, FaceScale::Fit];
+
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Integer => "integer",
+            Self::Fit => "fit",
+        }
+    }
+}
+
+impl fmt::Display for FaceScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FaceScale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|scale| scale.name() == s) {
+            Some(scale) => Ok(scale),
+            None => bail!("unknown face scale {s:?} (expected none, integer, or fit)"),
+        }
+    }
+}
+
+/// Resampling filter for scaled faces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FaceFilter {
+    /// Nearest pixel: hard edges, no new colours.
+    Nearest,
+    /// Linear blend of the neighbouring pixels.
+    Bilinear,
+    /// Lanczos (a = 3): the sharpest when shrinking large faces.
+    #[default]
+    Lanczos,
+}
+
+impl FaceFilter {
+    pub const ALL: [FaceFilter; 3] = [
+        FaceFilter::Nearest,
+        FaceFilter::Bilinear,
+        FaceFilter::Lanczos,
+    ];
+
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Nearest => "nearest",
+            Self::Bilinear => "bilinear",
+            Self::Lanczos => "lanczos",
+        }
+    }
+}
+
+impl fmt::Display for FaceFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FaceFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|filter| filter.name() == s) {
+            Some(filter) => Ok(filter),
+            None => bail!("unknown face filter {s:?} (expected nearest, bilinear, or lanczos)"),
+        }
+    }
+}
+
+/// How pixels are drawn with character cells. More pixels per cell means
+/// finer detail, but a cell still shows at most two colours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GraphicsMode {
+    /// `▀`/`▄`: 1×2 pixels per cell, every pixel its own colour.
+    #[default]
+    HalfBlock,
+    /// `▚`, `▟`, …: 2×2 pixels per cell.
+    Quadrant,
+    /// Braille dots: 2×4 pixels per cell.
+    Braille,
+}
+
+impl GraphicsMode {
+    pub const ALL: [GraphicsMode; 3] = [
+        GraphicsMode::HalfBlock,
+        GraphicsMode::Quadrant,
+        GraphicsMode::Braille,
+    ];
+
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::HalfBlock => "half-block",
+            Self::Quadrant => "quadrant",
+            Self::Braille => "braille",
+        }
+    }
+
+    /// Pixels per cell, across and down.
+    pub fn cell_pixels(self) -> (usize, usize) {
+        match self {
+            Self::HalfBlock => (1, 2),
+            Self::Quadrant => (2, 2),
+            Self::Braille => (2, 4),
+        }
+    }
+}
+
+impl fmt::Display for GraphicsMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for GraphicsMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|mode| mode.name() == s) {
+            Some(mode) => Ok(mode),
+            None => {
+                bail!("unknown graphics mode {s:?} (expected half-block, quadrant, or braille)")
+            }
+        }
+    }
+}
+
+/// How frames are written to the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct TerminalConfig {
+    /// Wrap each frame in a synchronized update so it appears at once.
+    pub sync_output: SyncOutput,
+}
+
+/// Whether frames are sent as synchronized updates (DEC private mode
+/// 2026), which the terminal holds back until the whole frame is in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncOutput {
+    /// When the terminal is known to support them.
+    #[default]
+    Auto,
+    /// Always; terminals without support ignore the mode.
+    On,
+    Off,
+}
+
+impl SyncOutput {
+    pub const ALL: [SyncOutput; 3] = [SyncOutput::Auto, SyncOutput::On, SyncOutput::Off];
+
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::On => "on",
+            Self::Off => "off",
+        }
+    }
+
+    /// Whether to synchronize, given whether the terminal `supported` it.
+    pub fn enabled(self, supported: bool) -> bool {
+        match self {
+            Self::Auto => supported,
+            Self::On => true,
+            Self::Off => false,
+        }
+    }
+}
+
+impl fmt::Display for SyncOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SyncOutput {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|mode| mode.name() == s) {
+            Some(mode) => Ok(mode),
+            None => bail!("unknown sync output mode {s:?} (expected auto, on, or off)"),
+        }
+    }
+}
+
+/// Size of the HUD strip at the bottom of the shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HudConfig {
+    /// Rows, including borders.
+    #[serde(default = "default_hud_height")]
+    pub height: u16,
+    /// Columns of the centre agent-face panel.
+    #[serde(default = "default_hud_face_width")]
+    pub face_width: u16,
+}
+
+fn default_hud_height() -> u16 {
+    9
+}
+
+fn default_hud_face_width() -> u16 {
+    18
+}
+
+impl Default for HudConfig {
+    fn default() -> Self {
+        Self {
+            height: default_hud_height(),
+            face_width: default_hud_face_width(),
+        }
+    }
+}
+
+impl HudConfig {
+    /// Check that both sizes are within their ranges.
+    pub fn validate(&self) -> Result<()> {
+        if !HUD_HEIGHT.contains(&self.height) {
+            bail!(
+                "hud.height must be between {} and {}",
+                HUD_HEIGHT.start(),
+                HUD_HEIGHT.end()
+            );
+        }
+        if !HUD_FACE_WIDTH.contains(&self.face_width) {
+            bail!(
+                "hud.face_width must be between {} and {}",
+                HUD_FACE_WIDTH.start(),
+                HUD_FACE_WIDTH.end()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Size, position, and background of the console overlay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConsoleConfig {
+    /// Percent of the screen height the open console covers.
+    #[serde(default = "default_console_height")]
+    pub height: u16,
+    #[serde(default)]
+    pub anchor: ConsoleAnchor,
+    #[serde(default)]
+    pub background: ConsoleBackground,
+    /// Slide animation length in milliseconds; `None` uses the profile's.
+    #[serde(default)]
+    pub slide_ms: Option<u64>,
+    /// Curve the slide follows.
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+fn default_console_height() -> u16 {
+    50
+}
+
+impl Default for ConsoleConfig {
+    fn default() -> Self {
+        Self {
+            height: default_console_height(),
+            anchor: ConsoleAnchor::default(),
+            background: ConsoleBackground::default(),
+            slide_ms: None,
+            easing: Easing::default(),
+        }
+    }
+}
+
+impl ConsoleConfig {
+    /// Check that the height and slide length are within their ranges.
+    pub fn validate(&self) -> Result<()> {
+        if !CONSOLE_HEIGHT.contains(&self.height) {
+            bail!(
+                "console.height must be between {} and {}",
+                CONSOLE_HEIGHT.start(),
+                CONSOLE_HEIGHT.end()
+            );
+        }
+        if self
+            .slide_ms
+            .is_some_and(|ms| !CONSOLE_SLIDE_MS.contains(&ms))
+        {
+            bail!(
+                "console.slide_ms must be between {} and {}",
+                CONSOLE_SLIDE_MS.start(),
+                CONSOLE_SLIDE_MS.end()
+            );
+        }
+        Ok(())
+    }
+
+    /// Slide animation length under `profile`: `slide_ms` if set, else the
+    /// profile's.
+    pub fn slide_duration(&self, profile: Profile) -> Duration {
+        self.slide_ms
+            .map(Duration::from_millis)
+            .unwrap_or(profile.tuning().slide_duration)
+    }
+}
+
+/// Shape of an animation over time; see `spud_core::easing` for the math.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Fast start, gentle stop.
+    EaseOutQuad,
+    /// Faster start and softer stop than [`Easing::EaseOutQuad`].
+    #[default]
+    EaseOutCubic,
+    /// Gentle start and stop.
+    EaseInOutCubic,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseOutQuad,
+        Easing::EaseOutCubic,
+        Easing::EaseInOutCubic,
+    ];
+
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::EaseOutQuad => "ease-out-quad",
+            Self::EaseOutCubic => "ease-out-cubic",
+            Self::EaseInOutCubic => "ease-in-out-cubic",
+        }
+    }
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Easing {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|easing| easing.name() == s) {
+            Some(easing) => Ok(easing),
+            None => bail!(
+                "unknown easing {s:?} (expected linear, ease-out-quad, ease-out-cubic, or ease-in-out-cubic)"
+            ),
+        }
+    }
+}
+
+/// Screen edge the console slides in from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleAnchor {
+    #[default]
+    Top,
+    Bottom,
+}
+
+impl ConsoleAnchor {
+    pub const ALL: [ConsoleAnchor; 2] = [ConsoleAnchor::Top, ConsoleAnchor::Bottom];
+
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+        }
+    }
+}
+
+impl fmt::Display for ConsoleAnchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ConsoleAnchor {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|anchor| anchor.name() == s) {
+            Some(anchor) => Ok(anchor),
+            None => bail!("unknown console anchor {s:?} (expected top or bottom)"),
+        }
+    }
+}
+
+/// What shows behind the console log.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleBackground {
+    /// Black, hiding the shell.
+    #[default]
+    Solid,
+    /// The shell, greyed out on black.
+    Dim,
+    /// The shell as drawn.
+    Transparent,
+}
+
+impl ConsoleBackground {
+    pub const ALL: [ConsoleBackground; 3] = [
+        ConsoleBackground::Solid,
+        ConsoleBackground::Dim,
+        ConsoleBackground::Transparent,
+    ];
+
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Solid => "solid",
+            Self::Dim => "dim",
+            Self::Transparent => "transparent",
+        }
+    }
+}
+
+impl fmt::Display for ConsoleBackground {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ConsoleBackground {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL
+            .into_iter()
+            .find(|background| background.name() == s)
+        {
+            Some(background) => Ok(background),
+            None => bail!("unknown console background {s:?} (expected solid, dim, or transparent)"),
+        }
+    }
+}
+
+/// Attract mode: after a spell without key presses the shell dims, the
+/// agent shows off, and modules may cycle until the next key.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct IdleConfig {
+    /// Seconds without a key press before attract mode starts; `None`
+    /// never starts it.
+    pub after_secs: Option<u64>,
+    /// Seconds between module switches in attract mode; `None` stays on
+    /// the active module.
+    pub cycle_secs: Option<u64>,
+}
+
+impl IdleConfig {
+    /// Check that both periods are within their ranges.
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .after_secs
+            .is_some_and(|secs| !IDLE_AFTER_SECS.contains(&secs))
+        {
+            bail!(
+                "idle.after_secs must be between {} and {}",
+                IDLE_AFTER_SECS.start(),
+                IDLE_AFTER_SECS.end()
+            );
+        }
+        if self
+            .cycle_secs
+            .is_some_and(|secs| !IDLE_CYCLE_SECS.contains(&secs))
+        {
+            bail!(
+                "idle.cycle_secs must be between {} and {}",
+                IDLE_CYCLE_SECS.start(),
+                IDLE_CYCLE_SECS.end()
+            );
+        }
+        Ok(())
+    }
+
+    /// Time without input before attract mode, if it is on.
+    pub fn after(&self) -> Option<Duration> {
+        self.after_secs.map(Duration::from_secs)
+    }
+
+    /// Time between module switches in attract mode, if they cycle.
+    pub fn cycle(&self) -> Option<Duration> {
+        self.cycle_secs.map(Duration::from_secs)
+    }
+}
+
+/// Limits of the event bus queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BusConfig {
+    /// Events that may wait for dispatch before `overflow` applies.
+    #[serde(default = "default_bus_max_queue")]
+    pub max_queue: usize,
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+}
+
+fn default_bus_max_queue() -> usize {
+    4096
+}
+
+impl Default for BusConfig {
+    fn default() -> Self {
+        Self {
+            max_queue: default_bus_max_queue(),
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// What the event bus does with a publish when its queue is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued event to make room.
+    #[default]
+    DropOldest,
+    /// Discard the event being published.
+    DropNewest,
+    /// Keep every event and stop reading from plugins and modules until
+    /// the queue drains.
+    Block,
+}
+
+impl OverflowPolicy {
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DropOldest => "drop-oldest",
+            Self::DropNewest => "drop-newest",
+            Self::Block => "block",
+        }
+    }
+}
+
+/// Guards on console commands.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct CommandsConfig {
+    /// What destructive commands such as `quit` need before they run.
+    pub destructive: DestructivePolicy,
+    /// Commands refused outright, e.g. for a kiosk or demo. An entry is a
+    /// command name, optionally followed by leading arguments, so
+    /// `"plugins stop"` refuses only that subcommand.
+    pub disabled: Vec<String>,
+}
+
+impl CommandsConfig {
+    fn validate(&self) -> Result<()> {
+        if self.disabled.iter().any(|entry| entry.trim().is_empty()) {
+            bail!("commands.disabled entries must not be empty");
+        }
+        Ok(())
+    }
+}
+
+/// What a destructive command typed in the console needs before it runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DestructivePolicy {
+    /// Ask in the console first.
+    #[default]
+    Confirm,
+    /// Run at once.
+    Allow,
+    /// Refuse, wherever the command comes from.
+    Deny,
+}
+
+impl DestructivePolicy {
+    /// Name as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Confirm => "confirm",
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+/// Which plugin entrypoints the host trusts to start.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PluginsConfig {
+    /// Minisign public keys, the base64 line of a `minisign.pub` file.
+    pub trusted_keys: Vec<String>,
+    /// Refuse plugins outside `local_roots` unless their entrypoint carries
+    /// a signature from one of `trusted_keys`.
+    pub require_signatures: bool,
+    /// Absolute directories whose plugins are exempt from
+    /// `require_signatures`, such as a checkout you develop plugins in.
+    pub local_roots: Vec<PathBuf>,
+}
+
+impl PluginsConfig {
+    fn validate(&self) -> Result<()> {
+        if self.trusted_keys.iter().any(|key| key.trim().is_empty()) {
+            bail!("plugins.trusted_keys entries must not be empty");
+        }
+        if let Some(root) = self.local_roots.iter().find(|root| !root.is_absolute()) {
+            bail!(
+                "plugins.local_roots entries must be absolute: {}",
+                root.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Shared-file export of the agent frame and runtime metrics for external
+/// overlays and status bars.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct ExportConfig {
+    pub enabled: bool,
+    /// Absolute path of the export file; `None` uses the app's default.
+    pub path: Option<PathBuf>,
+    /// Milliseconds between writes.
+    pub interval_ms: u64,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            interval_ms: 100,
+        }
+    }
+}
+
+impl ExportConfig {
+    fn validate(&self) -> Result<()> {
+        if !EXPORT_INTERVAL_MS.contains(&self.interval_ms) {
+            bail!(
+                "export.interval_ms must be between {} and {}",
+                EXPORT_INTERVAL_MS.start(),
+                EXPORT_INTERVAL_MS.end()
+            );
+        }
+        if let Some(path) = self.path.as_ref().filter(|path| !path.is_absolute()) {
+            bail!("export.path must be absolute: {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Time between writes.
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms)
+    }
+}
+
+/// Read-only HTTP status page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct HttpConfig {
+    pub enabled: bool,
+    /// Address the server listens on; loopback unless changed.
+    pub bind: SocketAddr,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: SocketAddr::from(([127, 0, 0, 1], 7878)),
+        }
+    }
+}
+
+/// mDNS advertisement of the status page, so tools on the LAN can find
+/// running instances.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct MdnsConfig {
+    pub enabled: bool,
+    /// Instance name shown to browsers; `None` uses the host name.
+    pub name: Option<String>,
+}
+
+impl MdnsConfig {
+    fn validate(&self) -> Result<()> {
+        if let Some(name) = &self.name {
+            if name.is_empty() || name.len() > MDNS_NAME_MAX {
+                bail!("mdns.name must be 1 to {MDNS_NAME_MAX} bytes");
+            }
+            if name.contains('.') {
+                bail!("mdns.name must not contain '.'");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AppConfig {
+    /// Parse and validate settings TOML.
+    pub fn from_toml_str(input: &str) -> Result<Self> {
+        let config: Self = toml::from_str(input).context("failed to parse spud.toml")?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Load and validate settings from disk.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_toml_str(&raw).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// Check value ranges.
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .tick_interval_ms
+            .is_some_and(|ms| !TICK_INTERVAL_MS.contains(&ms))
+        {
+            bail!(
+                "tick_interval_ms must be between {} and {}",
+                TICK_INTERVAL_MS.start(),
+                TICK_INTERVAL_MS.end()
+            );
+        }
+        self.log.validate()?;
+        self.hud.validate()?;
+        self.console.validate()?;
+        self.idle.validate()?;
+        if !BUS_MAX_QUEUE.contains(&self.bus.max_queue) {
+            bail!(
+                "bus.max_queue must be between {} and {}",
+                BUS_MAX_QUEUE.start(),
+                BUS_MAX_QUEUE.end()
+            );
+        }
+        self.commands.validate()?;
+        self.plugins.validate()?;
+        self.export.validate()?;
+        self.mdns.validate()?;
+        self.modules.stats.validate()
+    }
+
+    /// Interval between ticks under `profile`: `tick_interval_ms` if set,
+    /// else the profile's rate.
+    pub fn tick_interval(&self, profile: Profile) -> Duration {
+        self.tick_interval_ms
+            .map(Duration::from_millis)
+            .unwrap_or(profile.tuning().tick_interval)
+    }
+
+    /// Names of the settings that differ from `other`, in file order.
+    pub fn changed_fields(&self, other: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.log_level != other.log_level {
+            changed.push("log_level");
+        }
+        if self.locale != other.locale {
+            changed.push("locale");
+        }
+        if self.profile != other.profile {
+            changed.push("profile");
+        }
+        if self.tick_interval_ms != other.tick_interval_ms {
+            changed.push("tick_interval_ms");
+        }
+        if self.transcript != other.transcript {
+            changed.push("transcript");
+        }
+        if self.log != other.log {
+            changed.push("log");
+        }
+        if self.hud.height != other.hud.height {
+            changed.push("hud.height");
+        }
+        if self.hud.face_width != other.hud.face_width {
+            changed.push("hud.face_width");
+        }
+        if self.face != other.face {
+            changed.push("face");
+        }
+        if self.console.height != other.console.height {
+            changed.push("console.height");
+        }
+        if self.console.anchor != other.console.anchor {
+            changed.push("console.anchor");
+        }
+        if self.console.background != other.console.background {
+            changed.push("console.background");
+        }
+        if self.console.slide_ms != other.console.slide_ms {
+            changed.push("console.slide_ms");
+        }
+        if self.console.easing != other.console.easing {
+            changed.push("console.easing");
+        }
+        if self.terminal.sync_output != other.terminal.sync_output {
+            changed.push("terminal.sync_output");
+        }
+        if self.idle.after_secs != other.idle.after_secs {
+            changed.push("idle.after_secs");
+        }
+        if self.idle.cycle_secs != other.idle.cycle_secs {
+            changed.push("idle.cycle_secs");
+        }
+        if self.bus.max_queue != other.bus.max_queue {
+            changed.push("bus.max_queue");
+        }
+        if self.bus.overflow != other.bus.overflow {
+            changed.push("bus.overflow");
+        }
+        if self.commands.destructive != other.commands.destructive {
+            changed.push("commands.destructive");
+        }
+        if self.commands.disabled != other.commands.disabled {
+            changed.push("commands.disabled");
+        }
+        if self.plugins.trusted_keys != other.plugins.trusted_keys {
+            changed.push("plugins.trusted_keys");
+        }
+        if self.plugins.require_signatures != other.plugins.require_signatures {
+            changed.push("plugins.require_signatures");
+        }
+        if self.plugins.local_roots != other.plugins.local_roots {
+            changed.push("plugins.local_roots");
+        }
+        if self.export != other.export {
+            changed.push("export");
+        }
+        if self.http != other.http {
+            changed.push("http");
+        }
+        if self.mdns != other.mdns {
+            changed.push("mdns");
+        }
+        if self.modules.stats != other.modules.stats {
+            changed.push("modules.stats");
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_uses_defaults() {
+        assert_eq!(AppConfig::from_toml_str("").unwrap(), AppConfig::default());
+    }
+
+    #[test]
+    fn parses_and_diffs_settings() {
+        let config = AppConfig::from_toml_str(
+            r#"
+log_level = "debug"
+locale = "de"
+tick_interval_ms = 250
+transcript = true
+
+[log]
+max_file_mb = 0
+compress = "zstd"
+
+[hud]
+height = 12
+
+[console]
+anchor = "bottom"
+easing = "linear"
+
+[idle]
+after_secs = 120
+
+[bus]
+overflow = "drop-newest"
+
+[commands]
+destructive = "deny"
+disabled = ["plugins stop"]
+
+[plugins]
+require_signatures = true
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.tick_interval(Profile::Battery),
+            Duration::from_millis(250)
+        );
+        assert_eq!(config.hud.face_width, 18);
+        assert_eq!(config.log.max_file_bytes(), None);
+        assert_eq!(config.log.max_files, 20);
+        assert_eq!(config.log.compress, LogCompression::Zstd);
+        assert_eq!(config.log.max_total_bytes(), None);
+        assert_eq!(
+            AppConfig::default().log.max_file_bytes(),
+            Some(10 * 1024 * 1024)
+        );
+        assert_eq!(config.bus.overflow, OverflowPolicy::DropNewest);
+        assert_eq!(config.bus.max_queue, 4096);
+        assert_eq!(config.idle.after(), Some(Duration::from_secs(120)));
+        assert_eq!(config.idle.cycle(), None);
+        assert_eq!(config.commands.destructive, DestructivePolicy::Deny);
+        assert_eq!(config.console.height, 50);
+        assert_eq!(config.console.anchor, ConsoleAnchor::Bottom);
+        assert_eq!(
+            config.console.slide_duration(Profile::Battery),
+            Duration::ZERO
+        );
+        assert_eq!(
+            config.changed_fields(&AppConfig::default()),
+            vec![
+                "log_level",
+                "locale",
+                "tick_interval_ms",
+                "transcript",
+                "log",
+                "hud.height",
+                "console.anchor",
+                "console.easing",
+                "idle.after_secs",
+                "bus.overflow",
+                "commands.destructive",
+                "commands.disabled",
+                "plugins.require_signatures"
+            ]
+        );
+    }
+
+    #[test]
+    fn profile_sets_tick_rate_unless_overridden() {
+        let config = AppConfig::from_toml_str("profile = \"battery\"").unwrap();
+        assert_eq!(config.profile, Profile::Battery);
+        assert_eq!(
+            config.tick_interval(config.profile),
+            Profile::Battery.tuning().tick_interval
+        );
+        assert_eq!(
+            config.changed_fields(&AppConfig::default()),
+            vec!["profile"]
+        );
+        assert!(AppConfig::from_toml_str("profile = \"turbo\"").is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_unknown_fields() {
+        let err = AppConfig::from_toml_str("tick_interval_ms = 0").unwrap_err();
+        assert!(err.to_string().contains("tick_interval_ms"));
+        assert!(AppConfig::from_toml_str("[hud]\nheight = 100").is_err());
+        assert!(AppConfig::from_toml_str("[log]\nmax_files = 0").is_err());
+        assert!(AppConfig::from_toml_str("[log]\nretention_days = 0").is_err());
+        assert!(AppConfig::from_toml_str("[log]\ncompress = \"xz\"").is_err());
+        assert!(AppConfig::from_toml_str("[console]\nheight = 10").is_err());
+        assert!(AppConfig::from_toml_str("[console]\nslide_ms = 5000").is_err());
+        assert!(AppConfig::from_toml_str("[console]\neasing = \"bounce\"").is_err());
+        assert!(AppConfig::from_toml_str("[console]\nbackground = \"blur\"").is_err());
+        assert!(AppConfig::from_toml_str("[idle]\nafter_secs = 1").is_err());
+        assert!(AppConfig::from_toml_str("[idle]\ncycle_secs = 0").is_err());
+        assert!(AppConfig::from_toml_str("[bus]\nmax_queue = 1").is_err());
+        assert!(AppConfig::from_toml_str("[bus]\noverflow = \"spill\"").is_err());
+        assert!(AppConfig::from_toml_str("theme = \"dark\"").is_err());
+        assert!(AppConfig::from_toml_str("[commands]\ndestructive = \"ask\"").is_err());
+        assert!(AppConfig::from_toml_str("[commands]\ndisabled = [\" \"]").is_err());
+        let err = AppConfig::from_toml_str("[plugins]\nlocal_roots = [\"plugins\"]").unwrap_err();
+        assert!(err.to_string().contains("must be absolute"));
+        let err =
+            AppConfig::from_toml_str("[modules.stats.thresholds]\ncpu = [95, 70]").unwrap_err();
+        assert!(format!("{err:#}").contains("modules.stats.thresholds.cpu"));
+    }
+
+    #[test]
+    fn export_is_off_by_default_and_checked() {
+        let config = AppConfig::from_toml_str("[export]\nenabled = true").unwrap();
+        assert!(config.export.enabled);
+        assert_eq!(config.export.interval(), Duration::from_millis(100));
+        assert_eq!(config.changed_fields(&AppConfig::default()), vec!["export"]);
+        assert!(AppConfig::from_toml_str("[export]\ninterval_ms = 1").is_err());
+        let err = AppConfig::from_toml_str("[export]\npath = \"spud.shm\"").unwrap_err();
+        assert!(err.to_string().contains("export.path"));
+    }
+
+    #[test]
+    fn http_listens_on_loopback_by_default() {
+        let config = AppConfig::default();
+        assert!(!config.http.enabled);
+        assert!(config.http.bind.ip().is_loopback());
+        let config =
+            AppConfig::from_toml_str("[http]\nenabled = true\nbind = \"0.0.0.0:9000\"").unwrap();
+        assert_eq!(config.http.bind.port(), 9000);
+        assert_eq!(config.changed_fields(&AppConfig::default()), vec!["http"]);
+        assert!(AppConfig::from_toml_str("[http]\nbind = \"localhost\"").is_err());
+    }
+
+    #[test]
+    fn mdns_names_must_be_one_label() {
+        let config = AppConfig::from_toml_str("[mdns]\nenabled = true\nname = \"den\"").unwrap();
+        assert_eq!(config.mdns.name.as_deref(), Some("den"));
+        assert_eq!(config.changed_fields(&AppConfig::default()), vec!["mdns"]);
+        assert!(AppConfig::from_toml_str("[mdns]\nname = \"den.local\"").is_err());
+        assert!(AppConfig::from_toml_str("[mdns]\nname = \"\"").is_err());
+    }
+
+    #[test]
+    fn faces_scale_in_whole_steps_by_default() {
+        let config = AppConfig::default();
+        assert_eq!(config.face.scale, FaceScale::Integer);
+        assert_eq!(config.face.filter, FaceFilter::Lanczos);
+        assert!(config.face.letterbox);
+        let config =
+            AppConfig::from_toml_str("[face]\nscale = \"fit\"\nfilter = \"bilinear\"").unwrap();
+        assert_eq!(config.face.scale, FaceScale::Fit);
+        assert_eq!(config.face.filter, FaceFilter::Bilinear);
+        assert_eq!(config.changed_fields(&AppConfig::default()), vec!["face"]);
+        assert!(AppConfig::from_toml_str("[face]\nfilter = \"bicubic\"").is_err());
+        assert_eq!("none".parse::<FaceScale>().unwrap(), FaceScale::None);
+        assert_eq!(config.face.graphics, GraphicsMode::HalfBlock);
+        let config = AppConfig::from_toml_str("[face]\ngraphics = \"braille\"").unwrap();
+        assert_eq!(config.face.graphics.cell_pixels(), (2, 4));
+        assert_eq!(
+            "quadrant".parse::<GraphicsMode>().unwrap(),
+            GraphicsMode::Quadrant
+        );
+        assert!(AppConfig::from_toml_str("[face]\ngraphics = \"sixel\"").is_err());
+    }
+
+    #[test]
+    fn sync_output_follows_the_terminal_unless_forced() {
+        let config = AppConfig::default();
+        assert_eq!(config.terminal.sync_output, SyncOutput::Auto);
+        assert!(config.terminal.sync_output.enabled(true));
+        assert!(!config.terminal.sync_output.enabled(false));
+        let config = AppConfig::from_toml_str("[terminal]\nsync_output = \"on\"").unwrap();
+        assert!(config.terminal.sync_output.enabled(false));
+        assert_eq!(
+            config.changed_fields(&AppConfig::default()),
+            vec!["terminal.sync_output"]
+        );
+        assert_eq!("off".parse::<SyncOutput>().unwrap(), SyncOutput::Off);
+        assert!(AppConfig::from_toml_str("[terminal]\nsync_output = \"yes\"").is_err());
+    }
+}
@@ -3,6 +3,30 @@
 //! This crate owns on-disk configuration schemas so runtime crates can share a
 //! single source of truth.
 
+pub mod alerts;
+pub mod app;
+pub mod modules;
+pub mod paths;
 pub mod plugin;
+pub mod profile;
+pub mod workspace;
 
-pub use plugin::{PluginCompatibility, PluginManifest, PluginPermissions, PluginRuntime};
+pub use alerts::{AlertCondition, AlertRuleConfig, AlertSeverity, AlertsConfig};
+pub use app::{
+    AppConfig, BusConfig, CommandsConfig, ConsoleAnchor, ConsoleBackground, ConsoleConfig,
+    DestructivePolicy, Easing, ExportConfig, FaceConfig, FaceFilter, FaceScale, GraphicsMode,
+    HttpConfig, HudConfig, IdleConfig, LogCompression, LogConfig, MdnsConfig, OverflowPolicy,
+    PluginsConfig, SyncOutput, TerminalConfig, APP_CONFIG_FILE,
+};
+pub use modules::{
+    GaugeStyle, ModulesConfig, StatsConfig, StatsSection, StatsThresholds, Thresholds,
+};
+pub use plugin::{
+    PluginCompatibility, PluginHealth, PluginIntegrity, PluginManifest, PluginModuleSpec,
+    PluginPermissions, PluginRuntime,
+};
+pub use profile::{Profile, ProfileTuning};
+pub use workspace::{Workspace, WorkspaceConfig};
+
+/// Version of this crate, as reported by `spud version`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,285 @@
+//! Per-module sections of `spud.toml`, under `[modules.<id>]`.
+//!
+//! ```toml
+//! [modules.stats]
+//! hide = ["cores"]      # cores | swap | process | gpu | battery
+//! gauge = "thick"       # normal | thick | double
+//!
+//! [modules.stats.thresholds]
+//! cpu = [70, 90]        # warn, crit percent
+//! mem = [80, 95]
+//! swap = [50, 80]
+//! gpu = [70, 90]
+//! ```
+//!
+//! Modules read their section in `Module::configure`, at startup and
+//! whenever the file changes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// The `[modules]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModulesConfig {
+    #[serde(default)]
+    pub stats: StatsConfig,
+}
+
+/// `[modules.stats]`: what the system stats module shows and when its
+/// gauges change color.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatsConfig {
+    #[serde(default)]
+    pub thresholds: StatsThresholds,
+    /// Hero-pane sections to leave out.
+    #[serde(default)]
+    pub hide: Vec<StatsSection>,
+    /// Line style of the hero-pane gauges.
+    #[serde(default)]
+    pub gauge: GaugeStyle,
+}
+
+impl StatsConfig {
+    /// Whether `section` is shown.
+    pub fn shows(&self, section: StatsSection) -> bool {
+        !self.hide.contains(&section)
+    }
+
+    /// Check every threshold pair.
+    pub fn validate(&self) -> Result<()> {
+        for (name, thresholds) in [
+            ("cpu", self.thresholds.cpu),
+            ("mem", self.thresholds.mem),
+            ("swap", self.thresholds.swap),
+            ("gpu", self.thresholds.gpu),
+        ] {
+            if let Err(err) = thresholds.validate() {
+                bail!("modules.stats.thresholds.{name}: {err}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Warn/crit pairs for each stats gauge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatsThresholds {
+    #[serde(default = "default_cpu_thresholds")]
+    pub cpu: Thresholds,
+    #[serde(default = "default_mem_thresholds")]
+    pub mem: Thresholds,
+    #[serde(default = "default_swap_thresholds")]
+    pub swap: Thresholds,
+    #[serde(default = "default_gpu_thresholds")]
+    pub gpu: Thresholds,
+}
+
+fn default_cpu_thresholds() -> Thresholds {
+    Thresholds::new(70, 90)
+}
+
+fn default_mem_thresholds() -> Thresholds {
+    Thresholds::new(80, 95)
+}
+
+fn default_swap_thresholds() -> Thresholds {
+    Thresholds::new(50, 80)
+}
+
+fn default_gpu_thresholds() -> Thresholds {
+    Thresholds::new(70, 90)
+}
+
+impl Default for StatsThresholds {
+    fn default() -> Self {
+        Self {
+            cpu: default_cpu_thresholds(),
+            mem: default_mem_thresholds(),
+            swap: default_swap_thresholds(),
+            gpu: default_gpu_thresholds(),
+        }
+    }
+}
+
+/// Percentages at which a gauge turns to its warning and critical colors.
+/// Written as a `[warn, crit]` array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "[u8; 2]", into = "[u8; 2]")]
+pub struct Thresholds {
+    pub warn: u8,
+    pub crit: u8,
+}
+
+impl Thresholds {
+    /// A `warn`/`crit` pair; not validated.
+    pub fn new(warn: u8, crit: u8) -> Self {
+        Self { warn, crit }
+    }
+
+    /// Both values must be percentages, with `warn <= crit`.
+    pub fn validate(&self) -> Result<()> {
+        if self.crit > 100 {
+            bail!("thresholds must be at most 100");
+        }
+        if self.warn > self.crit {
+            bail!("warn ({}) must not exceed crit ({})", self.warn, self.crit);
+        }
+        Ok(())
+    }
+}
+
+impl From<[u8; 2]> for Thresholds {
+    fn from([warn, crit]: [u8; 2]) -> Self {
+        Self::new(warn, crit)
+    }
+}
+
+impl From<Thresholds> for [u8; 2] {
+    fn from(thresholds: Thresholds) -> Self {
+        [thresholds.warn, thresholds.crit]
+    }
+}
+
+/// An optional part of the stats hero pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsSection {
+    /// Per-core CPU grid.
+    Cores,
+    /// Swap gauge.
+    Swap,
+    /// SPUD's own RSS and CPU.
+    Process,
+    /// GPU gauge, when built with the `gpu` feature and a GPU is found.
+    Gpu,
+    /// Battery gauge, when the machine has one.
+    Battery,
+}
+
+impl StatsSection {
+    /// Every section, in display order.
+    pub const ALL: [StatsSection; 5] = [
+        Self::Cores,
+        Self::Swap,
+        Self::Process,
+        Self::Gpu,
+        Self::Battery,
+    ];
+
+    /// Lowercase name, as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cores => "cores",
+            Self::Swap => "swap",
+            Self::Process => "process",
+            Self::Gpu => "gpu",
+            Self::Battery => "battery",
+        }
+    }
+}
+
+impl fmt::Display for StatsSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for StatsSection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL.into_iter().find(|section| section.name() == s) {
+            Some(section) => Ok(section),
+            None => bail!("unknown section {s:?} (expected cores, swap, process, gpu, or battery)"),
+        }
+    }
+}
+
+/// Line drawn by the stats hero-pane gauges.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GaugeStyle {
+    /// Thin line.
+    #[default]
+    Normal,
+    /// Heavy line.
+    Thick,
+    /// Double line.
+    Double,
+}
+
+impl GaugeStyle {
+    /// Every style.
+    pub const ALL: [GaugeStyle; 3] = [Self::Normal, Self::Thick, Self::Double];
+
+    /// Lowercase name, as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Thick => "thick",
+            Self::Double => "double",
+        }
+    }
+}
+
+impl fmt::Display for GaugeStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for GaugeStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match Self::ALL.into_iter().find(|style| style.name() == s) {
+            Some(style) => Ok(style),
+            None => bail!("unknown gauge style {s:?} (expected normal, thick, or double)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stats_section_with_defaults() {
+        let config: ModulesConfig = toml::from_str(
+            r#"
+[stats]
+hide = ["cores", "process"]
+gauge = "double"
+
+[stats.thresholds]
+cpu = [60, 85]
+"#,
+        )
+        .unwrap();
+        let stats = &config.stats;
+        assert_eq!(stats.thresholds.cpu, Thresholds::new(60, 85));
+        assert_eq!(stats.thresholds.mem, Thresholds::new(80, 95));
+        assert!(!stats.shows(StatsSection::Cores));
+        assert!(stats.shows(StatsSection::Swap));
+        assert_eq!(stats.gauge, GaugeStyle::Double);
+        stats.validate().unwrap();
+    }
+
+    #[test]
+    fn rejects_inverted_or_oversized_thresholds() {
+        assert!(Thresholds::new(90, 70).validate().is_err());
+        assert!(Thresholds::new(90, 101).validate().is_err());
+        let mut stats = StatsConfig::default();
+        stats.thresholds.swap = Thresholds::new(60, 50);
+        let err = stats.validate().unwrap_err();
+        assert!(err.to_string().contains("thresholds.swap"));
+        assert!("graph".parse::<StatsSection>().is_err());
+        assert_eq!("thick".parse::<GaugeStyle>().unwrap(), GaugeStyle::Thick);
+    }
+}
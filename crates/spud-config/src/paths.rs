@@ -0,0 +1,138 @@
+//! Where SPUD keeps its files.
+//!
+//! Every directory follows the same precedence: a `SPUD_*_DIR` env var, then
+//! the platform default from [`dirs`] with a `spud` subdirectory. Features
+//! that read or write files (logs, persisted state, face packs, plugin
+//! grants, history) resolve their locations here rather than inventing their
+//! own.
+//!
+//! | Kind   | Override          | Linux default                   | macOS default                        |
+//! |--------|-------------------|---------------------------------|--------------------------------------|
+//! | config | `SPUD_CONFIG_DIR` | `$XDG_CONFIG_HOME/spud`         | `~/Library/Application Support/spud` |
+//! | data   | `SPUD_DATA_DIR`   | `$XDG_DATA_HOME/spud`           | `~/Library/Application Support/spud` |
+//! | cache  | `SPUD_CACHE_DIR`  | `$XDG_CACHE_HOME/spud`          | `~/Library/Caches/spud`              |
+//! | state  | `SPUD_STATE_DIR`  | `$XDG_STATE_HOME/spud`          | data dir                             |
+//! | logs   | `SPUD_LOG_DIR`    | `<data>/logs`                   | `~/Library/Logs/spud`                |
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "spud";
+
+/// User configuration such as `alerts.toml`.
+pub fn config_dir() -> Option<PathBuf> {
+    resolve(DirKind::Config, &env)
+}
+
+/// Durable data the user would miss if deleted.
+pub fn data_dir() -> Option<PathBuf> {
+    resolve(DirKind::Data, &env)
+}
+
+/// Regenerable files, safe to delete.
+pub fn cache_dir() -> Option<PathBuf> {
+    resolve(DirKind::Cache, &env)
+}
+
+/// Session state such as history, kept across runs but not worth backing
+/// up. Falls back to the data dir on platforms without a state dir.
+pub fn state_dir() -> Option<PathBuf> {
+    resolve(DirKind::State, &env)
+}
+
+/// Log files. Unlike the other directories this always resolves, falling
+/// back to `./logs` when the platform has no home directory.
+pub fn log_dir() -> PathBuf {
+    resolve(DirKind::Log, &env).unwrap_or_else(|| PathBuf::from("logs"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirKind {
+    Config,
+    Data,
+    Cache,
+    State,
+    Log,
+}
+
+impl DirKind {
+    fn env_var(self) -> &'static str {
+        match self {
+            Self::Config => "SPUD_CONFIG_DIR",
+            Self::Data => "SPUD_DATA_DIR",
+            Self::Cache => "SPUD_CACHE_DIR",
+            Self::State => "SPUD_STATE_DIR",
+            Self::Log => "SPUD_LOG_DIR",
+        }
+    }
+
+    fn platform_default(self) -> Option<PathBuf> {
+        match self {
+            Self::Config => dirs::config_dir().map(|dir| dir.join(APP_DIR)),
+            Self::Data => dirs::data_dir().map(|dir| dir.join(APP_DIR)),
+            Self::Cache => dirs::cache_dir().map(|dir| dir.join(APP_DIR)),
+            Self::State => dirs::state_dir()
+                .map(|dir| dir.join(APP_DIR))
+                .or_else(|| Self::Data.platform_default()),
+            Self::Log => {
+                if cfg!(target_os = "macos") {
+                    dirs::home_dir().map(|home| home.join("Library").join("Logs").join(APP_DIR))
+                } else {
+                    Self::Data.platform_default().map(|dir| dir.join("logs"))
+                }
+            }
+        }
+    }
+}
+
+fn env(key: &str) -> Option<OsString> {
+    std::env::var_os(key)
+}
+
+/// Apply the env override for `kind`, ignoring empty values.
+fn resolve(kind: DirKind, env: &dyn Fn(&str) -> Option<OsString>) -> Option<PathBuf> {
+    env(kind.env_var())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| kind.platform_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_override_wins_and_empty_is_ignored() {
+        let env = |key: &str| match key {
+            "SPUD_CACHE_DIR" => Some(OsString::from("/tmp/spud-cache")),
+            "SPUD_LOG_DIR" => Some(OsString::new()),
+            _ => None,
+        };
+        assert_eq!(
+            resolve(DirKind::Cache, &env),
+            Some(PathBuf::from("/tmp/spud-cache"))
+        );
+        assert_eq!(resolve(DirKind::Log, &env), DirKind::Log.platform_default());
+    }
+
+    #[test]
+    fn defaults_live_under_spud() {
+        let none = |_: &str| None;
+        for kind in [
+            DirKind::Config,
+            DirKind::Data,
+            DirKind::Cache,
+            DirKind::State,
+        ] {
+            if let Some(dir) = resolve(kind, &none) {
+                assert!(dir.ends_with(APP_DIR), "{kind:?}: {}", dir.display());
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        assert_eq!(
+            resolve(DirKind::Log, &none),
+            dirs::home_dir().map(|home| home.join("Library/Logs/spud"))
+        );
+    }
+}
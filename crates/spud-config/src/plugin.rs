@@ -1,6 +1,7 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
 use semver::{Version, VersionReq};
@@ -16,6 +17,12 @@ pub struct PluginManifest {
     pub runtime: PluginRuntime,
     pub compatibility: PluginCompatibility,
     pub permissions: PluginPermissions,
+    #[serde(default)]
+    pub health: PluginHealth,
+    #[serde(default)]
+    pub integrity: PluginIntegrity,
+    #[serde(default)]
+    pub module: Option<PluginModuleSpec>,
 }
 
 /// Runtime entrypoint metadata for plugin startup.
@@ -27,6 +34,18 @@ pub struct PluginRuntime {
     pub command: Option<String>,
     #[serde(default)]
     pub args: Vec<String>,
+    /// Host environment variables (names or globs such as `LC_*`) passed
+    /// through on top of the baseline the host always passes. The rest of
+    /// the host environment is withheld.
+    #[serde(default)]
+    pub inherit_env: Vec<String>,
+    /// Variables set for the plugin process, overriding inherited ones.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Working directory relative to the manifest, using `/` separators.
+    /// Defaults to the manifest's directory.
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 /// Compatibility constraints for host API negotiation.
@@ -46,6 +65,148 @@ pub struct PluginPermissions {
     pub event_tags: Vec<String>,
     #[serde(default)]
     pub subscriptions: Vec<String>,
+    /// `source.key` globs of telemetry the plugin may see in snapshots,
+    /// `spud.state.get_telemetry`, and telemetry events. Absent means all.
+    #[serde(default)]
+    pub telemetry_keys: Option<Vec<String>>,
+}
+
+/// Startup deadline and liveness checks for a plugin session.
+///
+/// ```toml
+/// [health]
+/// handshake_timeout_ms = 2000  # spawn to completed spud.handshake
+/// ping_interval_ms = 5000      # send spud.plugin.health pings (off by default)
+/// ping_timeout_ms = 1000       # an unanswered ping fails after this long
+/// max_failures = 3             # failed pings in a row before Unhealthy
+/// restart = true               # restart the plugin once it is Unhealthy
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PluginHealth {
+    pub handshake_timeout_ms: u64,
+    pub ping_interval_ms: Option<u64>,
+    pub ping_timeout_ms: u64,
+    pub max_failures: u32,
+    pub restart: bool,
+}
+
+impl Default for PluginHealth {
+    fn default() -> Self {
+        Self {
+            handshake_timeout_ms: 2_000,
+            ping_interval_ms: None,
+            ping_timeout_ms: 1_000,
+            max_failures: 3,
+            restart: true,
+        }
+    }
+}
+
+impl PluginHealth {
+    /// Longest handshake deadline a manifest may ask for.
+    pub const MAX_HANDSHAKE_TIMEOUT_MS: u64 = 60_000;
+    /// Shortest ping interval a manifest may ask for.
+    pub const MIN_PING_INTERVAL_MS: u64 = 50;
+
+    /// Time allowed between spawn and a completed handshake.
+    pub fn handshake_timeout(&self) -> Duration {
+        Duration::from_millis(self.handshake_timeout_ms)
+    }
+
+    /// Time between pings, or `None` when pings are off.
+    pub fn ping_interval(&self) -> Option<Duration> {
+        self.ping_interval_ms.map(Duration::from_millis)
+    }
+
+    /// Time an unanswered ping takes to count as failed.
+    pub fn ping_timeout(&self) -> Duration {
+        Duration::from_millis(self.ping_timeout_ms)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(1..=Self::MAX_HANDSHAKE_TIMEOUT_MS).contains(&self.handshake_timeout_ms) {
+            bail!(
+                "health.handshake_timeout_ms must be between 1 and {}",
+                Self::MAX_HANDSHAKE_TIMEOUT_MS
+            );
+        }
+        if let Some(interval) = self.ping_interval_ms {
+            if interval < Self::MIN_PING_INTERVAL_MS {
+                bail!(
+                    "health.ping_interval_ms must be at least {}",
+                    Self::MIN_PING_INTERVAL_MS
+                );
+            }
+        }
+        if self.ping_timeout_ms == 0 {
+            bail!("health.ping_timeout_ms must be greater than 0");
+        }
+        if self.max_failures == 0 {
+            bail!("health.max_failures must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+/// A module the plugin adds to the registry, so it joins the Tab cycle.
+///
+/// ```toml
+/// [module]
+/// id = "weather"      # module id, unique among all modules
+/// title = "Weather"   # shown in the top bar and module list
+/// ```
+///
+/// The plugin fills the module's hero area and HUD with
+/// `spud.module.update`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginModuleSpec {
+    pub id: String,
+    pub title: String,
+}
+
+impl PluginModuleSpec {
+    fn validate(&self) -> Result<()> {
+        validate_nonempty("module.id", &self.id)?;
+        if self.id.contains(char::is_whitespace) {
+            bail!("module.id must be a single word: {:?}", self.id);
+        }
+        validate_nonempty("module.title", &self.title)?;
+        validate_no_surrounding_whitespace("module.title", &self.title)?;
+        Ok(())
+    }
+}
+
+/// Checks the host makes on the entrypoint file before every start.
+///
+/// ```toml
+/// [integrity]
+/// sha256 = "2cf24dba…"                    # hex digest of the entrypoint
+/// signature = "dist/index.js.minisig"     # minisign signature of the entrypoint
+/// ```
+///
+/// The signature path is relative to the manifest, using `/` separators.
+/// Which keys may sign is host configuration (`[plugins]` in `spud.toml`).
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PluginIntegrity {
+    pub sha256: Option<String>,
+    pub signature: Option<String>,
+}
+
+impl PluginIntegrity {
+    fn validate(&self) -> Result<()> {
+        if let Some(digest) = &self.sha256 {
+            if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                bail!("integrity.sha256 must be 64 hex digits");
+            }
+        }
+        if let Some(signature) = &self.signature {
+            validate_relative_path("integrity.signature", signature)?;
+        }
+        Ok(())
+    }
 }
 
 impl PluginManifest {
@@ -81,9 +242,22 @@ impl PluginManifest {
         }
 
         validate_runtime_args(&self.runtime.args)?;
+        validate_allowlist("runtime.inherit_env", &self.runtime.inherit_env)?;
+        validate_env(&self.runtime.env)?;
+        if let Some(cwd) = &self.runtime.cwd {
+            validate_relative_path("runtime.cwd", cwd)?;
+        }
         validate_allowlist("permissions.commands", &self.permissions.commands)?;
         validate_allowlist("permissions.event_tags", &self.permissions.event_tags)?;
         validate_allowlist("permissions.subscriptions", &self.permissions.subscriptions)?;
+        if let Some(keys) = &self.permissions.telemetry_keys {
+            validate_allowlist("permissions.telemetry_keys", keys)?;
+        }
+        self.health.validate()?;
+        self.integrity.validate()?;
+        if let Some(module) = &self.module {
+            module.validate()?;
+        }
 
         Version::parse(&self.version)
             .with_context(|| format!("manifest version must be valid semver: {}", self.version))?;
@@ -168,6 +342,31 @@ fn validate_runtime_args(args: &[String]) -> Result<()> {
     Ok(())
 }
 
+fn validate_env(env: &BTreeMap<String, String>) -> Result<()> {
+    for (name, value) in env {
+        if name.is_empty() || name.contains(['=', '\0']) {
+            bail!("runtime.env name {name:?} must be non-empty without '=' or NUL");
+        }
+        if value.contains('\0') {
+            bail!("runtime.env value for {name} must not contain NUL");
+        }
+    }
+    Ok(())
+}
+
+/// Paths such as the working directory must stay inside the plugin's
+/// directory.
+fn validate_relative_path(field: &str, path: &str) -> Result<()> {
+    validate_nonempty(field, path)?;
+    if path.starts_with('/') || path.contains('\\') || path.contains(':') {
+        bail!("{field} must be a relative path with / separators: {path:?}");
+    }
+    if path.split('/').any(|part| part == "..") {
+        bail!("{field} must not leave the plugin directory: {path:?}");
+    }
+    Ok(())
+}
+
 fn validate_allowlist(field: &str, values: &[String]) -> Result<()> {
     let mut seen = BTreeSet::new();
 
@@ -280,6 +479,126 @@ commands = ["help"]
         );
     }
 
+    #[test]
+    fn runtime_env_and_cwd_are_validated() {
+        let manifest = PluginManifest::from_toml_str(VALID_MANIFEST).unwrap();
+        assert!(manifest.runtime.inherit_env.is_empty());
+        assert_eq!(manifest.runtime.cwd, None);
+
+        let with = |extra: &str| {
+            VALID_MANIFEST.replace(
+                "args = [\"--enable-source-maps\"]",
+                &format!("args = []\n{extra}"),
+            )
+        };
+        let manifest = PluginManifest::from_toml_str(&with(
+            "inherit_env = [\"LC_*\"]\ncwd = \"data/run\"\nenv = { LOG_LEVEL = \"debug\" }",
+        ))
+        .unwrap();
+        assert_eq!(manifest.runtime.env["LOG_LEVEL"], "debug");
+        assert_eq!(manifest.runtime.cwd.as_deref(), Some("data/run"));
+
+        for (extra, message) in [
+            (
+                "cwd = \"../secrets\"",
+                "must not leave the plugin directory",
+            ),
+            ("cwd = \"/tmp\"", "must be a relative path"),
+            ("env = { \"A=B\" = \"x\" }", "runtime.env name"),
+            ("inherit_env = [\"PATH\", \"PATH\"]", "duplicate entry"),
+        ] {
+            let err = format!(
+                "{:#}",
+                PluginManifest::from_toml_str(&with(extra)).unwrap_err()
+            );
+            assert!(err.contains(message), "{extra}: {err}");
+        }
+    }
+
+    #[test]
+    fn integrity_fields_are_validated() {
+        let manifest = PluginManifest::from_toml_str(VALID_MANIFEST).unwrap();
+        assert_eq!(manifest.integrity, PluginIntegrity::default());
+
+        let digest = "ab".repeat(32);
+        let raw = format!(
+            "{VALID_MANIFEST}\n[integrity]\nsha256 = \"{digest}\"\nsignature = \"dist/index.js.minisig\"\n"
+        );
+        let manifest = PluginManifest::from_toml_str(&raw).unwrap();
+        assert_eq!(manifest.integrity.sha256, Some(digest));
+
+        for (section, message) in [
+            ("sha256 = \"abc\"", "64 hex digits"),
+            (
+                "signature = \"../key.minisig\"",
+                "must not leave the plugin directory",
+            ),
+        ] {
+            let raw = format!("{VALID_MANIFEST}\n[integrity]\n{section}\n");
+            let err = format!("{:#}", PluginManifest::from_toml_str(&raw).unwrap_err());
+            assert!(err.contains(message), "{section}: {err}");
+        }
+    }
+
+    #[test]
+    fn telemetry_keys_are_optional() {
+        let manifest = PluginManifest::from_toml_str(VALID_MANIFEST).unwrap();
+        assert_eq!(manifest.permissions.telemetry_keys, None);
+
+        let raw = VALID_MANIFEST.replace(
+            "subscriptions = [\"tick\", \"resize\"]",
+            "subscriptions = [\"tick\", \"resize\"]\ntelemetry_keys = [\"stats.cpu*\"]",
+        );
+        let manifest = PluginManifest::from_toml_str(&raw).unwrap();
+        assert_eq!(
+            manifest.permissions.telemetry_keys,
+            Some(vec!["stats.cpu*".to_string()])
+        );
+
+        let raw = raw.replace("[\"stats.cpu*\"]", "[\"\"]");
+        let err = PluginManifest::from_toml_str(&raw).unwrap_err().to_string();
+        assert!(err.contains("permissions.telemetry_keys entries must not be empty"));
+    }
+
+    #[test]
+    fn health_defaults_and_bounds() {
+        let manifest = PluginManifest::from_toml_str(VALID_MANIFEST).unwrap();
+        assert_eq!(manifest.health, PluginHealth::default());
+        assert_eq!(manifest.health.ping_interval(), None);
+
+        let raw = format!("{VALID_MANIFEST}\n[health]\nping_interval_ms = 500\nrestart = false\n");
+        let manifest = PluginManifest::from_toml_str(&raw).unwrap();
+        assert_eq!(
+            manifest.health.ping_interval(),
+            Some(Duration::from_millis(500))
+        );
+        assert!(!manifest.health.restart);
+
+        let raw = format!("{VALID_MANIFEST}\n[health]\nmax_failures = 0\n");
+        let err = PluginManifest::from_toml_str(&raw).unwrap_err().to_string();
+        assert!(err.contains("health.max_failures"));
+    }
+
+    #[test]
+    fn module_section_is_optional_and_validated() {
+        let manifest = PluginManifest::from_toml_str(VALID_MANIFEST).unwrap();
+        assert_eq!(manifest.module, None);
+
+        let raw = format!("{VALID_MANIFEST}\n[module]\nid = \"weather\"\ntitle = \"Weather\"\n");
+        let manifest = PluginManifest::from_toml_str(&raw).unwrap();
+        assert_eq!(
+            manifest.module,
+            Some(PluginModuleSpec {
+                id: "weather".to_string(),
+                title: "Weather".to_string(),
+            })
+        );
+
+        let raw = format!("{VALID_MANIFEST}\n[module]\nid = \"my weather\"\ntitle = \"Weather\"\n");
+        let err = PluginManifest::from_toml_str(&raw).unwrap_err().to_string();
+        assert!(err.contains("module.id must be a single word"), "{err}");
+    }
+
     #[test]
     fn id_with_surrounding_whitespace_is_rejected() {
         let raw = VALID_MANIFEST.replace("id = \"spud.test\"", "id = \" spud.test \"");
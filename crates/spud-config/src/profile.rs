@@ -0,0 +1,123 @@
+//! Runtime profiles trading smoothness for power use.
+//!
+//! A profile bundles the timing knobs that cost CPU wake-ups: how often the
+//! app ticks and polls for input, how often modules sample telemetry, and
+//! how much the UI animates. `battery` also turns off the console slide
+//! (reduced motion).
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{bail, Error};
+use serde::{Deserialize, Serialize};
+
+/// A named set of timing trade-offs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Profile {
+    /// Fewer wake-ups and no slide animation.
+    Battery,
+    /// The defaults.
+    #[default]
+    Balanced,
+    /// Faster ticks and sampling for a smoother display.
+    Performance,
+}
+
+/// Timing values for a [`Profile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProfileTuning {
+    /// Interval between app ticks, unless `tick_interval_ms` is set.
+    pub tick_interval: Duration,
+    /// How long each loop iteration waits for input.
+    pub poll_timeout: Duration,
+    /// How often modules sample system telemetry.
+    pub telemetry_interval: Duration,
+    /// Console slide animation length; zero opens and closes instantly.
+    pub slide_duration: Duration,
+    /// Interval between agent face animation frames.
+    pub face_frame_interval: Duration,
+}
+
+impl Profile {
+    /// Every profile, from lowest to highest power use.
+    pub const ALL: [Profile; 3] = [Profile::Battery, Profile::Balanced, Profile::Performance];
+
+    /// Lowercase name, as written in `spud.toml`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Battery => "battery",
+            Self::Balanced => "balanced",
+            Self::Performance => "performance",
+        }
+    }
+
+    /// Timing values for this profile.
+    pub fn tuning(self) -> ProfileTuning {
+        let ms = Duration::from_millis;
+        match self {
+            Self::Battery => ProfileTuning {
+                tick_interval: ms(250),
+                poll_timeout: ms(50),
+                telemetry_interval: ms(3_000),
+                slide_duration: Duration::ZERO,
+                face_frame_interval: ms(1_200),
+            },
+            Self::Balanced => ProfileTuning {
+                tick_interval: ms(100),
+                poll_timeout: ms(16),
+                telemetry_interval: ms(1_000),
+                slide_duration: ms(250),
+                face_frame_interval: ms(300),
+            },
+            Self::Performance => ProfileTuning {
+                tick_interval: ms(50),
+                poll_timeout: ms(8),
+                telemetry_interval: ms(500),
+                slide_duration: ms(250),
+                face_frame_interval: ms(200),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::ALL.into_iter().find(|profile| profile.name() == s) {
+            Some(profile) => Ok(profile),
+            None => bail!("unknown profile {s:?} (expected battery, balanced, or performance)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for profile in Profile::ALL {
+            assert_eq!(profile.name().parse::<Profile>().unwrap(), profile);
+        }
+        assert!("turbo".parse::<Profile>().is_err());
+    }
+
+    #[test]
+    fn profiles_order_by_power_use() {
+        let [battery, balanced, performance] = Profile::ALL.map(Profile::tuning);
+        assert!(battery.tick_interval > balanced.tick_interval);
+        assert!(balanced.tick_interval > performance.tick_interval);
+        assert!(battery.poll_timeout > performance.poll_timeout);
+        assert!(battery.telemetry_interval > performance.telemetry_interval);
+        assert!(battery.slide_duration.is_zero());
+    }
+}
@@ -0,0 +1,160 @@
+//! Named workspaces: separate setups under one config directory.
+//!
+//! A workspace is a directory `<config dir>/workspaces/<name>/` used in
+//! place of the config dir itself, so it has its own `spud.toml`,
+//! `alerts.toml`, and locale catalogs. An optional `workspace.toml` there
+//! picks the modules and adds plugin roots:
+//!
+//! ```toml
+//! modules = ["stats"]        # module ids to load, in tab order; all when omitted
+//! plugin_dirs = ["plugins"]  # relative to the workspace directory
+//! ```
+//!
+//! Each workspace also gets its own state directory,
+//! `<state dir>/workspaces/<name>/`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::paths;
+
+/// Directory under the config (and state) dir holding one directory per
+/// workspace.
+pub const WORKSPACES_DIR: &str = "workspaces";
+/// Optional per-workspace file choosing modules and plugin roots.
+pub const WORKSPACE_FILE: &str = "workspace.toml";
+
+/// `workspace.toml` schema.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    /// Module ids to load, in order; `None` loads every available module.
+    #[serde(default)]
+    pub modules: Option<Vec<String>>,
+    /// Extra plugin search roots, relative to the workspace directory.
+    #[serde(default)]
+    pub plugin_dirs: Vec<PathBuf>,
+}
+
+/// A resolved workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    /// Used as the config dir while the workspace is active.
+    pub dir: PathBuf,
+    pub config: WorkspaceConfig,
+}
+
+impl Workspace {
+    /// Load workspace `name` from under `config_dir`. The directory must
+    /// exist; `workspace.toml` is optional.
+    pub fn load(config_dir: &Path, name: &str) -> Result<Self> {
+        validate_name(name)?;
+        let dir = config_dir.join(WORKSPACES_DIR).join(name);
+        if !dir.is_dir() {
+            let known = list(config_dir);
+            if known.is_empty() {
+                bail!("no workspace {name:?}: create {} to add it", dir.display());
+            }
+            bail!("no workspace {name:?} (available: {})", known.join(", "));
+        }
+        let file = dir.join(WORKSPACE_FILE);
+        let config = if file.exists() {
+            let raw = fs::read_to_string(&file)
+                .with_context(|| format!("failed to read {}", file.display()))?;
+            toml::from_str(&raw).with_context(|| format!("invalid {}", file.display()))?
+        } else {
+            WorkspaceConfig::default()
+        };
+        Ok(Self {
+            name: name.to_string(),
+            dir,
+            config,
+        })
+    }
+
+    /// Plugin roots from `plugin_dirs`, resolved against the workspace
+    /// directory.
+    pub fn plugin_roots(&self) -> Vec<PathBuf> {
+        self.config
+            .plugin_dirs
+            .iter()
+            .map(|dir| self.dir.join(dir))
+            .collect()
+    }
+
+    /// Whether module `id` is part of this workspace.
+    pub fn includes_module(&self, id: &str) -> bool {
+        self.config
+            .modules
+            .as_ref()
+            .is_none_or(|modules| modules.iter().any(|module| module == id))
+    }
+
+    /// `<state dir>/workspaces/<name>`, for state kept per workspace.
+    pub fn state_dir(&self) -> Option<PathBuf> {
+        paths::state_dir().map(|dir| dir.join(WORKSPACES_DIR).join(&self.name))
+    }
+}
+
+/// Names of the workspaces under `config_dir`, sorted.
+pub fn list(config_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(config_dir.join(WORKSPACES_DIR)) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| validate_name(name).is_ok())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Workspace names are single path components: letters, digits, `-`, `_`.
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("invalid workspace name {name:?} (use letters, digits, '-' and '_')");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn loads_workspace_and_resolves_plugin_dirs() {
+        let root = TestDir::new("workspaces");
+        let work = root.join(WORKSPACES_DIR).join("work");
+        fs::create_dir_all(&work).unwrap();
+        fs::create_dir_all(root.join(WORKSPACES_DIR).join("homelab")).unwrap();
+        fs::write(
+            work.join(WORKSPACE_FILE),
+            "modules = [\"stats\"]\nplugin_dirs = [\"plugins\"]\n",
+        )
+        .unwrap();
+
+        let workspace = Workspace::load(&root, "work").unwrap();
+        let homelab = Workspace::load(&root, "homelab").unwrap();
+        let missing = Workspace::load(&root, "nope").unwrap_err();
+        let names = list(&root);
+
+        assert_eq!(workspace.plugin_roots(), vec![work.join("plugins")]);
+        assert!(workspace.includes_module("stats"));
+        assert!(!workspace.includes_module("hello"));
+        assert!(homelab.includes_module("hello"));
+        assert_eq!(names, vec!["homelab", "work"]);
+        assert!(missing.to_string().contains("available: homelab, work"));
+        assert!(Workspace::load(&root, "../etc").is_err());
+    }
+}
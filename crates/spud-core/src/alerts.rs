@@ -0,0 +1,332 @@
+//! Alert rules evaluated against the telemetry history store.
+//!
+//! Rules come from [`spud_config::AlertsConfig`]. The app calls
+//! [`AlertEngine::evaluate`] every tick and turns the returned
+//! [`AlertTransition`]s into console entries, agent moods, and commands.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use spud_config::alerts::{AlertCondition, AlertSeverity, AlertsConfig, Comparison};
+
+use crate::telemetry::TelemetryStore;
+
+/// A compiled alert rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    /// Condition as written in config, for display.
+    pub expression: String,
+    pub condition: AlertCondition,
+    pub severity: AlertSeverity,
+    pub message: String,
+    pub command: Option<String>,
+}
+
+/// Lifecycle state of one alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertStatus {
+    /// Condition does not hold.
+    Ok,
+    /// Condition holds but has not lasted the rule's `for` duration yet.
+    Pending,
+    /// Condition has held long enough; the alert is active.
+    Firing,
+    /// Firing, but acknowledged by the user.
+    Acknowledged,
+}
+
+impl AlertStatus {
+    /// Lowercase name used in console output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Pending => "pending",
+            Self::Firing => "firing",
+            Self::Acknowledged => "acked",
+        }
+    }
+}
+
+/// Notification-worthy change produced by [`AlertEngine::evaluate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertTransition {
+    /// An alert started firing.
+    Fired {
+        name: String,
+        severity: AlertSeverity,
+        message: String,
+        value: f64,
+        command: Option<String>,
+    },
+    /// A previously announced alert's condition cleared.
+    Resolved { name: String },
+}
+
+/// Runtime state for one rule.
+#[derive(Debug, Clone)]
+pub struct AlertState {
+    pub rule: AlertRule,
+    pub status: AlertStatus,
+    /// Most recent value the condition was checked against.
+    pub last_value: Option<f64>,
+    pub silenced_until: Option<Instant>,
+    pending_since: Option<Instant>,
+    /// Whether a `Fired` transition was emitted for the current episode.
+    announced: bool,
+}
+
+impl AlertState {
+    /// Whether notifications for this alert are currently suppressed.
+    pub fn is_silenced(&self, now: Instant) -> bool {
+        self.silenced_until.is_some_and(|until| now < until)
+    }
+}
+
+/// Evaluates alert rules and tracks their state.
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+    alerts: Vec<AlertState>,
+}
+
+impl AlertEngine {
+    /// Compile every rule in `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a rule condition fails to parse.
+    pub fn from_config(config: &AlertsConfig) -> Result<Self> {
+        let alerts = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let condition = AlertCondition::parse(&rule.when).with_context(|| {
+                    format!("alert rule {} has an invalid condition", rule.name)
+                })?;
+                Ok(AlertState {
+                    rule: AlertRule {
+                        name: rule.name.clone(),
+                        expression: rule.when.clone(),
+                        condition,
+                        severity: rule.severity,
+                        message: rule.message.clone().unwrap_or_else(|| rule.when.clone()),
+                        command: rule.command.clone(),
+                    },
+                    status: AlertStatus::Ok,
+                    last_value: None,
+                    silenced_until: None,
+                    pending_since: None,
+                    announced: false,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { alerts })
+    }
+
+    /// All alerts in config order.
+    pub fn alerts(&self) -> &[AlertState] {
+        &self.alerts
+    }
+
+    /// Return `true` if no rules are configured.
+    pub fn is_empty(&self) -> bool {
+        self.alerts.is_empty()
+    }
+
+    /// Check every rule against `store` and return state changes to announce.
+    ///
+    /// Silenced alerts still change state but produce no transitions.
+    pub fn evaluate(&mut self, store: &TelemetryStore, now: Instant) -> Vec<AlertTransition> {
+        let mut transitions = Vec::new();
+        for alert in &mut self.alerts {
+            let value = condition_value(&alert.rule.condition, store, now);
+            alert.last_value = value;
+            let condition = &alert.rule.condition;
+            let holds = value.is_some_and(|v| condition.comparison.holds(v, condition.threshold));
+
+            if !holds {
+                if alert.announced {
+                    transitions.push(AlertTransition::Resolved {
+                        name: alert.rule.name.clone(),
+                    });
+                }
+                alert.status = AlertStatus::Ok;
+                alert.pending_since = None;
+                alert.announced = false;
+                continue;
+            }
+
+            let since = *alert.pending_since.get_or_insert(now);
+            if alert.status == AlertStatus::Ok || alert.status == AlertStatus::Pending {
+                alert.status = if now.saturating_duration_since(since) >= condition.sustain {
+                    AlertStatus::Firing
+                } else {
+                    AlertStatus::Pending
+                };
+            }
+
+            if alert.status == AlertStatus::Firing && !alert.announced && !alert.is_silenced(now) {
+                alert.announced = true;
+                transitions.push(AlertTransition::Fired {
+                    name: alert.rule.name.clone(),
+                    severity: alert.rule.severity,
+                    message: alert.rule.message.clone(),
+                    value: value.unwrap_or_default(),
+                    command: alert.rule.command.clone(),
+                });
+            }
+        }
+        transitions
+    }
+
+    /// Acknowledge a firing alert so it stops counting as active.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alert is unknown or not firing.
+    pub fn acknowledge(&mut self, name: &str) -> Result<()> {
+        let alert = self.find_mut(name)?;
+        if alert.status != AlertStatus::Firing {
+            bail!("alert {} is not firing", name);
+        }
+        alert.status = AlertStatus::Acknowledged;
+        Ok(())
+    }
+
+    /// Suppress notifications for an alert until `now + duration`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alert is unknown.
+    pub fn silence(&mut self, name: &str, duration: Duration, now: Instant) -> Result<()> {
+        let until = now
+            .checked_add(duration)
+            .with_context(|| format!("cannot silence {name} for that long"))?;
+        self.find_mut(name)?.silenced_until = Some(until);
+        Ok(())
+    }
+
+    /// Highest severity among firing alerts that are neither acknowledged
+    /// nor silenced.
+    pub fn active_severity(&self, now: Instant) -> Option<AlertSeverity> {
+        self.alerts
+            .iter()
+            .filter(|alert| alert.status == AlertStatus::Firing && !alert.is_silenced(now))
+            .map(|alert| alert.rule.severity)
+            .max()
+    }
+
+    fn find_mut(&mut self, name: &str) -> Result<&mut AlertState> {
+        match self.alerts.iter_mut().find(|alert| alert.rule.name == name) {
+            Some(alert) => Ok(alert),
+            None => bail!("unknown alert: {}", name),
+        }
+    }
+}
+
+/// Value a condition is compared against, taking the worst case across
+/// sources when the rule names a bare key.
+fn condition_value(
+    condition: &AlertCondition,
+    store: &TelemetryStore,
+    now: Instant,
+) -> Option<f64> {
+    let sample = |source: &str| match condition.rate_per {
+        Some(per) => store.window_delta(source, &condition.key, per, now),
+        None => store
+            .numeric_last_n(source, &condition.key, 1)
+            .first()
+            .copied(),
+    };
+
+    let values: Vec<f64> = match &condition.source {
+        Some(source) => sample(source).into_iter().collect(),
+        None => store
+            .sources_with_key(&condition.key)
+            .filter_map(sample)
+            .collect(),
+    };
+
+    let worst = match condition.comparison {
+        Comparison::Gt | Comparison::Ge => f64::max,
+        Comparison::Lt | Comparison::Le => f64::min,
+    };
+    values.into_iter().reduce(worst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::TelemetryValue;
+
+    fn engine(toml: &str) -> AlertEngine {
+        AlertEngine::from_config(&AlertsConfig::from_toml_str(toml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn sustained_threshold_fires_then_resolves() {
+        let mut engine = engine(
+            "[[rule]]\nname = \"hot\"\nwhen = \"stats.cpu > 90 for 30s\"\nseverity = \"critical\"\n",
+        );
+        let mut store = TelemetryStore::default();
+        let start = Instant::now();
+
+        store.record("stats", "cpu", TelemetryValue::Float(95.0), start);
+        assert!(engine.evaluate(&store, start).is_empty());
+        assert_eq!(engine.alerts()[0].status, AlertStatus::Pending);
+
+        let later = start + Duration::from_secs(31);
+        store.record("stats", "cpu", TelemetryValue::Float(97.0), later);
+        let fired = engine.evaluate(&store, later);
+        assert!(matches!(
+            &fired[..],
+            [AlertTransition::Fired { name, value, .. }] if name == "hot" && *value == 97.0
+        ));
+        assert_eq!(engine.active_severity(later), Some(AlertSeverity::Critical));
+        assert!(engine.evaluate(&store, later).is_empty());
+
+        store.record("stats", "cpu", TelemetryValue::Float(20.0), later);
+        let resolved = engine.evaluate(&store, later);
+        assert_eq!(
+            resolved,
+            vec![AlertTransition::Resolved { name: "hot".into() }]
+        );
+        assert_eq!(engine.alerts()[0].status, AlertStatus::Ok);
+    }
+
+    #[test]
+    fn rate_rule_matches_bare_key_across_sources() {
+        let mut engine = engine("[[rule]]\nname = \"errs\"\nwhen = \"errors > 5/min\"\n");
+        let mut store = TelemetryStore::default();
+        let start = Instant::now();
+        store.record("plugin", "errors", TelemetryValue::Int(0), start);
+        store.record("other", "errors", TelemetryValue::Int(0), start);
+        let later = start + Duration::from_secs(20);
+        store.record("plugin", "errors", TelemetryValue::Int(3), later);
+        store.record("other", "errors", TelemetryValue::Int(9), later);
+
+        let fired = engine.evaluate(&store, later);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(engine.alerts()[0].last_value, Some(9.0));
+    }
+
+    #[test]
+    fn ack_and_silence_suppress_activity() {
+        let mut engine = engine("[[rule]]\nname = \"hot\"\nwhen = \"cpu > 1\"\n");
+        let mut store = TelemetryStore::default();
+        let now = Instant::now();
+        assert!(engine.acknowledge("hot").is_err());
+
+        engine.silence("hot", Duration::from_secs(60), now).unwrap();
+        store.record("stats", "cpu", TelemetryValue::Float(5.0), now);
+        assert!(engine.evaluate(&store, now).is_empty());
+        assert_eq!(engine.alerts()[0].status, AlertStatus::Firing);
+        assert_eq!(engine.active_severity(now), None);
+
+        let after = now + Duration::from_secs(61);
+        assert_eq!(engine.evaluate(&store, after).len(), 1);
+        engine.acknowledge("hot").unwrap();
+        assert_eq!(engine.active_severity(after), None);
+        assert!(engine.silence("nope", Duration::from_secs(1), now).is_err());
+    }
+}
@@ -1,6 +1,10 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Instant;
+
+use spud_config::{BusConfig, OverflowPolicy};
 
 use crate::event::Event;
+use crate::tap::{EventTap, DEFAULT_TAP_CAPACITY};
 
 /// A simple FIFO event queue.
 ///
@@ -8,8 +12,56 @@ use crate::event::Event;
 /// 1. **Publish** — input polling and timers push events into the queue.
 /// 2. **Drain** — all pending events are pulled out in order.
 /// 3. **Broadcast** — each event is dispatched to modules via the registry.
+///
+/// Queued events are also copied into an [`EventTap`] for the debug
+/// overlay; events the overflow policy discards on publish are not.
+///
+/// The queue holds at most `max_queue` events; past that the
+/// [`OverflowPolicy`] decides what gives. `Quit` is never dropped. Under
+/// [`OverflowPolicy::Block`] nothing is dropped and the app stops pulling
+/// from plugins and modules while [`EventBus::is_blocking`] is true.
 pub struct EventBus {
     queue: VecDeque<Event>,
+    tap: EventTap,
+    config: BusConfig,
+    counts: BTreeMap<&'static str, KindCounts>,
+    peak_depth: usize,
+    blocked: u64,
+}
+
+/// Publish and drop totals for one [`Event::kind`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KindCounts {
+    pub published: u64,
+    /// Evicted or discarded because the queue was full.
+    pub dropped: u64,
+}
+
+/// Point-in-time view of the bus queue and its counters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusStats {
+    pub depth: usize,
+    /// Deepest the queue has been.
+    pub peak_depth: usize,
+    pub max_queue: usize,
+    pub overflow: OverflowPolicy,
+    /// Publishes that found the queue full under [`OverflowPolicy::Block`]
+    /// and were queued past the limit.
+    pub blocked: u64,
+    /// Counters per event kind, ordered by kind.
+    pub kinds: Vec<(&'static str, KindCounts)>,
+}
+
+impl BusStats {
+    /// Events published, across kinds.
+    pub fn published(&self) -> u64 {
+        self.kinds.iter().map(|(_, counts)| counts.published).sum()
+    }
+
+    /// Events dropped, across kinds.
+    pub fn dropped(&self) -> u64 {
+        self.kinds.iter().map(|(_, counts)| counts.dropped).sum()
+    }
 }
 
 impl Default for EventBus {
@@ -19,16 +71,53 @@ impl Default for EventBus {
 }
 
 impl EventBus {
-    /// Create an empty event bus.
+    /// Create an empty event bus with the default limits.
     pub fn new() -> Self {
         Self {
             queue: VecDeque::new(),
+            tap: EventTap::new(Instant::now(), DEFAULT_TAP_CAPACITY),
+            config: BusConfig::default(),
+            counts: BTreeMap::new(),
+            peak_depth: 0,
+            blocked: 0,
         }
     }
 
-    /// Enqueue an event at the back of the queue.
+    /// Apply `[bus]` from `spud.toml`. Events already queued are kept even
+    /// if the new limit is lower.
+    pub fn configure(&mut self, config: BusConfig) {
+        self.config = config;
+    }
+
+    /// Enqueue an event at the back of the queue, applying the overflow
+    /// policy if the queue is full.
+    ///
+    /// Only queued events reach the tap. Under
+    /// [`OverflowPolicy::DropOldest`], a full queue holding nothing but
+    /// `Quit` has nothing to evict, so the new event is dropped instead.
     pub fn publish(&mut self, event: Event) {
+        self.counts.entry(event.kind()).or_default().published += 1;
+        if self.queue.len() >= self.config.max_queue && !matches!(event, Event::Quit) {
+            match self.config.overflow {
+                OverflowPolicy::DropOldest => {
+                    let position = self.queue.iter().position(|e| !matches!(e, Event::Quit));
+                    let evicted = position.and_then(|i| self.queue.remove(i));
+                    let dropped = evicted.as_ref().unwrap_or(&event);
+                    self.counts.entry(dropped.kind()).or_default().dropped += 1;
+                    if evicted.is_none() {
+                        return;
+                    }
+                }
+                OverflowPolicy::DropNewest => {
+                    self.counts.entry(event.kind()).or_default().dropped += 1;
+                    return;
+                }
+                OverflowPolicy::Block => self.blocked += 1,
+            }
+        }
+        self.tap.record(&event, Instant::now());
         self.queue.push_back(event);
+        self.peak_depth = self.peak_depth.max(self.queue.len());
     }
 
     /// Remove and return all pending events, preserving insertion order.
@@ -40,11 +129,44 @@ impl EventBus {
     pub fn has_pending(&self) -> bool {
         !self.queue.is_empty()
     }
+
+    /// Whether publishers that can wait should hold off: the queue is full
+    /// under [`OverflowPolicy::Block`].
+    pub fn is_blocking(&self) -> bool {
+        self.config.overflow == OverflowPolicy::Block && self.queue.len() >= self.config.max_queue
+    }
+
+    /// Queue depth and per-kind counters.
+    pub fn stats(&self) -> BusStats {
+        BusStats {
+            depth: self.queue.len(),
+            peak_depth: self.peak_depth,
+            max_queue: self.config.max_queue,
+            overflow: self.config.overflow,
+            blocked: self.blocked,
+            kinds: self
+                .counts
+                .iter()
+                .map(|(kind, counts)| (*kind, *counts))
+                .collect(),
+        }
+    }
+
+    /// History of recently published events.
+    pub fn tap(&self) -> &EventTap {
+        &self.tap
+    }
+
+    /// Mutable access to the history, to pause, scroll, or clear it.
+    pub fn tap_mut(&mut self) -> &mut EventTap {
+        &mut self.tap
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::event::ActivationReason;
     use std::time::Instant;
 
     #[test]
@@ -89,12 +211,139 @@ mod tests {
     #[test]
     fn preserves_order() {
         let mut bus = EventBus::new();
-        bus.publish(Event::ModuleActivated { id: "a".into() });
+        bus.publish(Event::ModuleActivated {
+            id: "a".into(),
+            reason: ActivationReason::Switch,
+        });
         bus.publish(Event::ModuleDeactivated { id: "b".into() });
         bus.publish(Event::Quit);
         let events = bus.drain();
-        assert!(matches!(&events[0], Event::ModuleActivated { id } if id == "a"));
+        assert!(matches!(&events[0], Event::ModuleActivated { id, .. } if id == "a"));
         assert!(matches!(&events[1], Event::ModuleDeactivated { id } if id == "b"));
         assert!(matches!(&events[2], Event::Quit));
     }
+
+    fn bounded(max_queue: usize, overflow: OverflowPolicy) -> EventBus {
+        let mut bus = EventBus::new();
+        bus.configure(BusConfig {
+            max_queue,
+            overflow,
+        });
+        bus
+    }
+
+    fn deactivated(id: &str) -> Event {
+        Event::ModuleDeactivated { id: id.into() }
+    }
+
+    #[test]
+    fn drop_oldest_evicts_but_keeps_quit() {
+        let mut bus = bounded(2, OverflowPolicy::DropOldest);
+        bus.publish(Event::Quit);
+        bus.publish(deactivated("a"));
+        bus.publish(deactivated("b"));
+        let events = bus.drain();
+        assert!(matches!(&events[0], Event::Quit));
+        assert!(matches!(&events[1], Event::ModuleDeactivated { id } if id == "b"));
+
+        let stats = bus.stats();
+        assert_eq!((stats.published(), stats.dropped()), (3, 1));
+        assert_eq!(stats.peak_depth, 2);
+        assert_eq!(
+            stats.kinds,
+            vec![
+                (
+                    "module_deactivated",
+                    KindCounts {
+                        published: 2,
+                        dropped: 1
+                    }
+                ),
+                (
+                    "quit",
+                    KindCounts {
+                        published: 1,
+                        dropped: 0
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_newest_discards_the_publish_unless_quit() {
+        let mut bus = bounded(1, OverflowPolicy::DropNewest);
+        bus.publish(deactivated("a"));
+        bus.publish(deactivated("b"));
+        bus.publish(Event::Quit);
+        let events = bus.drain();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], Event::ModuleDeactivated { id } if id == "a"));
+        assert!(matches!(&events[1], Event::Quit));
+        assert_eq!(bus.stats().dropped(), 1);
+    }
+
+    #[test]
+    fn drop_oldest_drops_the_new_event_when_only_quit_is_queued() {
+        let mut bus = bounded(2, OverflowPolicy::DropOldest);
+        bus.publish(Event::Quit);
+        bus.publish(Event::Quit);
+        bus.publish(deactivated("a"));
+        assert_eq!(bus.stats().depth, 2);
+        assert_eq!(bus.stats().dropped(), 1);
+        assert!(bus.drain().iter().all(|e| matches!(e, Event::Quit)));
+        assert_eq!(bus.tap().entries().len(), 2);
+    }
+
+    #[test]
+    fn discarded_publishes_stay_out_of_the_tap() {
+        let mut bus = bounded(1, OverflowPolicy::DropNewest);
+        bus.publish(deactivated("a"));
+        bus.publish(deactivated("b"));
+        let previews: Vec<&str> = bus
+            .tap()
+            .entries()
+            .iter()
+            .map(|entry| entry.preview.as_str())
+            .collect();
+        assert_eq!(previews, ["a"]);
+    }
+
+    #[test]
+    fn block_keeps_everything_and_signals_backpressure() {
+        let mut bus = bounded(2, OverflowPolicy::Block);
+        bus.publish(deactivated("a"));
+        assert!(!bus.is_blocking());
+        bus.publish(deactivated("b"));
+        bus.publish(deactivated("c"));
+        assert!(bus.is_blocking());
+        assert_eq!(bus.drain().len(), 3);
+        assert!(!bus.is_blocking());
+        assert_eq!(bus.stats().dropped(), 0);
+        assert!(!bounded(2, OverflowPolicy::DropOldest).is_blocking());
+    }
+
+    #[test]
+    fn block_counts_publishes_past_the_limit() {
+        let mut bus = bounded(1, OverflowPolicy::Block);
+        bus.publish(deactivated("a"));
+        assert_eq!(bus.stats().blocked, 0);
+        bus.publish(deactivated("b"));
+        bus.publish(Event::Quit);
+        assert_eq!(bus.stats().blocked, 1);
+        assert_eq!(bounded(1, OverflowPolicy::DropNewest).stats().blocked, 0);
+    }
+
+    #[test]
+    fn publish_feeds_tap() {
+        let mut bus = EventBus::new();
+        bus.publish(Event::ModuleActivated {
+            id: "hello".into(),
+            reason: ActivationReason::Switch,
+        });
+        bus.drain();
+        let entry = bus.tap().entries().back().unwrap();
+        assert_eq!(entry.kind, "module_activated");
+        assert_eq!(entry.preview, "hello (switch)");
+    }
 }
@@ -3,16 +3,20 @@ use std::time::Instant;
 
 use crate::bus::EventBus;
 use crate::console::Console;
+use crate::error::{CoreError, CoreResult};
+use crate::event::ActivationReason;
 use crate::fps::TickCounter;
+use crate::i18n::tr;
+use crate::log_file::{self, LogDirEntry};
+use crate::logging::{self, LogLevel, LogStats};
+use crate::pipeline;
 use crate::registry::ModuleRegistry;
+use crate::rng::Rng;
+use crate::spans::{format_duration, SpanStats};
+use crate::undo::{Inverse, UndoStack, CLEAR_GRACE};
+use crate::vars::Vars;
 
-/// The result of executing a console command.
-pub enum CommandOutput {
-    /// Lines to display in the console.
-    Lines(Vec<String>),
-    /// Signal that the application should quit.
-    Quit,
-}
+pub use crate::output::{Align, Column, CommandOutput, CommandStatus, Table};
 
 /// Shared context passed to every command during execution.
 ///
@@ -29,6 +33,35 @@ pub struct CommandContext<'a> {
     pub tick_counter: &'a TickCounter,
     /// When the application started (for uptime calculation).
     pub started_at: Instant,
+    /// Recent latency spans (for the `spans` summary).
+    pub spans: &'a SpanStats,
+    /// Seeded random numbers, so command output is reproducible with
+    /// `--seed`.
+    pub rng: &'a mut Rng,
+    /// Actions `undo` can reverse; commands push the inverse of what they
+    /// change.
+    pub undo: &'a mut UndoStack,
+    /// Who is running the command.
+    pub origin: CommandOrigin,
+}
+
+/// Where a command invocation came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandOrigin {
+    /// Typed in the console, or run by the app (alerts, scripts).
+    Console,
+    /// Invoked by a plugin over `spud.host.invoke_command`.
+    Plugin,
+}
+
+impl CommandOrigin {
+    /// Reason to tag module activations caused by this command with.
+    pub fn activation_reason(self) -> ActivationReason {
+        match self {
+            Self::Console => ActivationReason::Switch,
+            Self::Plugin => ActivationReason::Plugin,
+        }
+    }
 }
 
 /// Trait implemented by each console command.
@@ -50,6 +83,21 @@ pub trait Command: Send + Sync {
     }
     /// Execute the command with the given arguments and context.
     fn execute(&self, args: &[&str], ctx: &mut CommandContext) -> CommandOutput;
+    /// Whether running with `args` is hard to undo, so `[commands]
+    /// destructive` in `spud.toml` applies (see [`crate::guard`]).
+    ///
+    /// The default implementation returns `false`.
+    fn destructive(&self, _args: &[&str]) -> bool {
+        false
+    }
+    /// Handle the line typed in reply to a [`CommandOutput::Prompt`] this
+    /// command returned. `token` is the prompt's `callback_token`. Return
+    /// another prompt to ask a further question.
+    ///
+    /// The default implementation reports that the command asks nothing.
+    fn answer(&self, _token: &str, _answer: &str, _ctx: &mut CommandContext) -> CommandOutput {
+        CommandOutput::Error(vec![format!("{} has no pending question", self.name())])
+    }
 }
 
 /// Stores and looks up console commands by name and alias.
@@ -83,30 +131,62 @@ impl CommandRegistry {
         self.commands.push(cmd);
     }
 
-    /// Parse and execute a command string (e.g. `"switch stats"`).
+    /// Parse and execute a command line (e.g. `"switch stats"` or
+    /// `"modules | grep stats ; tps"`). See [`crate::pipeline`].
     ///
-    /// Returns an error message as `Lines` if the command is not found.
+    /// Returns [`CommandOutput::Error`] if a command is not found.
     pub fn execute(&self, input: &str, ctx: &mut CommandContext) -> CommandOutput {
-        let parts: Vec<&str> = input.split_whitespace().collect();
-        if parts.is_empty() {
+        let vars = Vars::builtin(ctx.registry, ctx.tick_counter, ctx.started_at);
+        pipeline::run(input, &vars, |words| self.execute_words(words, ctx))
+    }
+
+    /// Execute one already-split command (name followed by arguments).
+    pub fn execute_words(&self, words: &[&str], ctx: &mut CommandContext) -> CommandOutput {
+        let Some((&name, args)) = words.split_first() else {
             return CommandOutput::Lines(vec![]);
-        }
+        };
 
-        let name = parts[0];
-        let args = &parts[1..];
+        match self.resolve(name) {
+            Ok(command) => command.execute(args, ctx),
+            Err(err) => CommandOutput::Error(vec![err.to_string()]),
+        }
+    }
 
-        match self.lookup.get(name) {
-            Some(&idx) => self.commands[idx].execute(args, ctx),
-            None => CommandOutput::Lines(vec![format!(
-                "unknown command: '{}'. Type 'help' for available commands.",
-                name
-            )]),
+    /// Route the reply to a prompt back to the command named `name`.
+    pub fn answer(
+        &self,
+        name: &str,
+        token: &str,
+        answer: &str,
+        ctx: &mut CommandContext,
+    ) -> CommandOutput {
+        match self.resolve(name) {
+            Ok(command) => command.answer(token, answer, ctx),
+            Err(err) => CommandOutput::Error(vec![err.to_string()]),
         }
     }
 
     pub fn commands(&self) -> &[Box<dyn Command>] {
         &self.commands
     }
+
+    /// Look up a command by name or alias.
+    pub fn get(&self, name: &str) -> Option<&dyn Command> {
+        self.lookup
+            .get(name)
+            .map(|&idx| self.commands[idx].as_ref())
+    }
+
+    /// Like [`get`](Self::get), failing with [`CoreError::UnknownCommand`].
+    pub fn resolve(&self, name: &str) -> CoreResult<&dyn Command> {
+        self.get(name)
+            .ok_or_else(|| CoreError::UnknownCommand(name.to_string()))
+    }
+
+    /// Every name and alias that invokes a command, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.lookup.keys().map(String::as_str)
+    }
 }
 
 // ── Built-in commands ──
@@ -123,7 +203,7 @@ impl Command for HelpCommand {
         &["?"]
     }
     fn description(&self) -> &str {
-        "List commands or show specific help"
+        tr("List commands or show specific help")
     }
     fn usage(&self) -> &str {
         "help [command]"
@@ -151,11 +231,19 @@ impl Command for ClearCommand {
         &["cls"]
     }
     fn description(&self) -> &str {
-        "Clear console log"
+        tr("Clear console log")
     }
 
     fn execute(&self, _args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
-        ctx.console.clear_logs();
+        let cleared = ctx.console.clear_logs();
+        if !cleared.is_empty() {
+            ctx.undo.push_expiring(
+                self.name(),
+                Inverse::RestoreLogs(cleared),
+                Instant::now(),
+                CLEAR_GRACE,
+            );
+        }
         CommandOutput::Lines(vec![])
     }
 }
@@ -171,25 +259,43 @@ impl Command for ModulesCommand {
         &["mods"]
     }
     fn description(&self) -> &str {
-        "List registered modules"
+        tr("List registered modules")
     }
 
     fn execute(&self, _args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
         let active_id = ctx.registry.active_id().map(|s| s.to_string());
-        let lines: Vec<String> = ctx
-            .registry
-            .list()
-            .iter()
-            .map(|(id, title)| {
-                let marker = if Some(id.to_string()) == active_id {
-                    " *"
-                } else {
-                    ""
-                };
-                format!("  {} — {}{}", id, title, marker)
-            })
-            .collect();
-        CommandOutput::Lines(lines)
+        let mut table = Table::new(vec![
+            Column::left("ID"),
+            Column::left("TITLE"),
+            Column::left("ACTIVE"),
+            Column::left("ATTENTION"),
+            Column::left("STATUS"),
+        ]);
+        for (id, title) in ctx.registry.list() {
+            let marker = if Some(id.to_string()) == active_id {
+                "*"
+            } else {
+                ""
+            };
+            let attention = ctx
+                .registry
+                .attention(id)
+                .map(|badge| format!("{} ({})", badge.level.name(), badge.count))
+                .unwrap_or_default();
+            let status = if ctx.registry.crash(id).is_some() {
+                "crashed"
+            } else {
+                "ok"
+            };
+            table.push_row(vec![
+                id.to_string(),
+                title.to_string(),
+                marker.into(),
+                attention,
+                status.into(),
+            ]);
+        }
+        CommandOutput::Table(table)
     }
 }
 
@@ -204,7 +310,7 @@ impl Command for SwitchCommand {
         &["sw"]
     }
     fn description(&self) -> &str {
-        "Switch active module"
+        tr("Switch active module")
     }
     fn usage(&self) -> &str {
         "switch <module_id>"
@@ -212,9 +318,10 @@ impl Command for SwitchCommand {
 
     fn execute(&self, args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
         if args.is_empty() {
-            return CommandOutput::Lines(vec!["usage: switch <module_id>".into()]);
+            return CommandOutput::Error(vec!["usage: switch <module_id>".into()]);
         }
-        match ctx.registry.activate(args[0]) {
+        let reason = ctx.origin.activation_reason();
+        match ctx.registry.activate_with(args[0], reason) {
             Ok(events) => {
                 for ev in events {
                     ctx.bus.publish(ev);
@@ -222,8 +329,35 @@ impl Command for SwitchCommand {
                 let title = ctx.registry.active().map(|m| m.title()).unwrap_or("?");
                 CommandOutput::Lines(vec![format!("Switched to: {}", title)])
             }
-            Err(e) => CommandOutput::Lines(vec![format!("error: {}", e)]),
+            Err(e) => CommandOutput::Error(vec![e.to_string()]),
+        }
+    }
+}
+
+/// Built-in command that returns to the previously active module.
+pub struct BackCommand;
+
+impl Command for BackCommand {
+    fn name(&self) -> &str {
+        "back"
+    }
+    fn description(&self) -> &str {
+        tr("Return to the previous module")
+    }
+    fn usage(&self) -> &str {
+        "back"
+    }
+
+    fn execute(&self, _args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
+        let events = ctx.registry.back();
+        if events.is_empty() {
+            return CommandOutput::Error(vec!["no previous module".into()]);
+        }
+        for ev in events {
+            ctx.bus.publish(ev);
         }
+        let title = ctx.registry.active().map(|m| m.title()).unwrap_or("?");
+        CommandOutput::Lines(vec![format!("Back to: {}", title)])
     }
 }
 
@@ -238,12 +372,16 @@ impl Command for QuitCommand {
         &["exit", "q"]
     }
     fn description(&self) -> &str {
-        "Exit SPUD"
+        tr("Exit SPUD")
     }
 
     fn execute(&self, _args: &[&str], _ctx: &mut CommandContext) -> CommandOutput {
         CommandOutput::Quit
     }
+
+    fn destructive(&self, _args: &[&str]) -> bool {
+        true
+    }
 }
 
 /// Built-in command that displays how long the application has been running.
@@ -257,7 +395,7 @@ impl Command for UptimeCommand {
         &[]
     }
     fn description(&self) -> &str {
-        "Show runtime uptime"
+        tr("Show runtime uptime")
     }
 
     fn execute(&self, _args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
@@ -281,7 +419,7 @@ impl Command for TpsCommand {
         &["fps"]
     }
     fn description(&self) -> &str {
-        "Show ticks-per-second"
+        tr("Show ticks-per-second")
     }
 
     fn execute(&self, _args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
@@ -300,7 +438,7 @@ impl Command for EchoCommand {
         &[]
     }
     fn description(&self) -> &str {
-        "Print message to console"
+        tr("Print message to console")
     }
     fn usage(&self) -> &str {
         "echo <message>"
@@ -311,20 +449,283 @@ impl Command for EchoCommand {
     }
 }
 
+/// Built-in command that prints a number from the seeded RNG.
+pub struct RandomCommand;
+
+impl Command for RandomCommand {
+    fn name(&self) -> &str {
+        "random"
+    }
+    fn description(&self) -> &str {
+        tr("Print a random number below max (default 100)")
+    }
+    fn usage(&self) -> &str {
+        "random [max]"
+    }
+
+    fn execute(&self, args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
+        let max = match args {
+            [] => 100,
+            [raw] => match raw.parse::<u64>() {
+                Ok(max) if max > 0 => max,
+                _ => return CommandOutput::Error(vec![format!("invalid max: {raw}")]),
+            },
+            _ => return CommandOutput::Error(vec![format!("usage: {}", self.usage())]),
+        };
+        CommandOutput::Lines(vec![ctx.rng.below(max).to_string()])
+    }
+}
+
+/// Built-in command that summarizes recent latency spans per name.
+pub struct SpansCommand;
+
+impl Command for SpansCommand {
+    fn name(&self) -> &str {
+        "spans"
+    }
+    fn description(&self) -> &str {
+        tr("Show recent span latencies")
+    }
+    fn usage(&self) -> &str {
+        "spans [name]"
+    }
+
+    fn execute(&self, args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
+        let summaries = match args.first() {
+            Some(name) => ctx.spans.summary(name).into_iter().collect(),
+            None => ctx.spans.summaries(),
+        };
+        if summaries.is_empty() {
+            return CommandOutput::Lines(vec!["no spans recorded".into()]);
+        }
+
+        let mut table = Table::new(vec![
+            Column::left("SPAN"),
+            Column::right("COUNT"),
+            Column::right("AVG"),
+            Column::right("MAX"),
+            Column::right("LAST"),
+        ]);
+        for s in summaries {
+            table.push_row(vec![
+                s.name,
+                s.count.to_string(),
+                format_duration(s.avg),
+                format_duration(s.max),
+                format_duration(s.last),
+            ]);
+        }
+        CommandOutput::Table(table)
+    }
+}
+
+/// Built-in command that lists the `$VAR` substitutions available in
+/// console input.
+pub struct VarsCommand;
+
+impl Command for VarsCommand {
+    fn name(&self) -> &str {
+        "vars"
+    }
+    fn description(&self) -> &str {
+        tr("List $variables usable in commands")
+    }
+
+    fn execute(&self, _args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
+        let vars = Vars::builtin(ctx.registry, ctx.tick_counter, ctx.started_at);
+        CommandOutput::key_value(vars.iter().map(|(name, value)| (format!("${name}"), value)))
+    }
+}
+
+/// Built-in command that inspects and adjusts logging at runtime.
+pub struct LogCommand;
+
+impl Command for LogCommand {
+    fn name(&self) -> &str {
+        "log"
+    }
+    fn description(&self) -> &str {
+        tr("Log filter, counts by level/target, error acknowledgement, disk usage")
+    }
+    fn usage(&self) -> &str {
+        "log level [filter] | stats | ack | du"
+    }
+
+    fn execute(&self, args: &[&str], _ctx: &mut CommandContext) -> CommandOutput {
+        match args {
+            ["level"] => match logging::current_filter() {
+                Some(filter) => CommandOutput::Lines(vec![format!("log level: {filter}")]),
+                None => CommandOutput::Error(vec!["logging is not initialized".into()]),
+            },
+            ["level", directive] => match logging::set_filter(directive) {
+                Ok(()) => CommandOutput::Lines(vec![format!("log level set to {directive}")]),
+                Err(err) => CommandOutput::Error(vec![format!("{err:#}")]),
+            },
+            ["stats"] => logging::with_stats(|stats| log_stats_output(stats)),
+            ["ack"] => {
+                let errors = logging::with_stats(|stats| {
+                    let errors = stats.unacknowledged_errors();
+                    stats.acknowledge();
+                    errors
+                });
+                CommandOutput::Lines(vec![format!("acknowledged {errors} error(s)")])
+            }
+            ["du"] => {
+                let dir = logging::log_dir();
+                match log_file::disk_usage(&dir) {
+                    Ok(files) => log_du_output(&dir, &files),
+                    Err(err) => {
+                        CommandOutput::Error(vec![format!("cannot read {}: {err}", dir.display())])
+                    }
+                }
+            }
+            _ => CommandOutput::Error(vec![format!("usage: {}", self.usage())]),
+        }
+    }
+}
+
+/// Totals per level, then a per-target breakdown (most severe first).
+fn log_stats_output(stats: &LogStats) -> CommandOutput {
+    let mut totals: Vec<(String, String)> = LogLevel::ALL
+        .iter()
+        .rev()
+        .map(|level| (level.to_string(), stats.count(*level).to_string()))
+        .collect();
+    totals.push((
+        "UNACKED".to_string(),
+        stats.unacknowledged_errors().to_string(),
+    ));
+
+    let mut table = Table::new(
+        std::iter::once(Column::left("TARGET"))
+            .chain(
+                LogLevel::ALL
+                    .iter()
+                    .rev()
+                    .map(|l| Column::right(l.to_string())),
+            )
+            .collect(),
+    );
+    for target in stats.targets() {
+        table.push_row(
+            std::iter::once(target.to_string())
+                .chain(
+                    LogLevel::ALL
+                        .iter()
+                        .rev()
+                        .map(|level| stats.target_count(target, *level).to_string()),
+                )
+                .collect(),
+        );
+    }
+    CommandOutput::Multi(vec![
+        CommandOutput::KeyValue(totals),
+        CommandOutput::Table(table),
+    ])
+}
+
+/// Totals for the log directory, then each file, largest first.
+fn log_du_output(dir: &std::path::Path, files: &[LogDirEntry]) -> CommandOutput {
+    let sum = |keep: fn(&LogDirEntry) -> bool| -> u64 {
+        files.iter().filter(|f| keep(f)).map(|f| f.bytes).sum()
+    };
+    let totals = CommandOutput::key_value([
+        ("dir", dir.display().to_string()),
+        ("files", files.len().to_string()),
+        ("total", format_bytes(sum(|_| true))),
+        ("logs", format_bytes(sum(|f| f.log && !f.compressed))),
+        ("compressed", format_bytes(sum(|f| f.compressed))),
+        ("other", format_bytes(sum(|f| !f.log))),
+    ]);
+    let mut sorted: Vec<&LogDirEntry> = files.iter().collect();
+    sorted.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+    let mut table = Table::new(vec![Column::left("FILE"), Column::right("SIZE")]);
+    for file in sorted {
+        table.push_row(vec![file.name.clone(), format_bytes(file.bytes)]);
+    }
+    CommandOutput::Multi(vec![totals, CommandOutput::Table(table)])
+}
+
+/// `bytes` in B, KiB, MiB, or GiB.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Built-in command that reports event bus queue depth and per-kind
+/// publish/drop counters.
+pub struct BusCommand;
+
+impl Command for BusCommand {
+    fn name(&self) -> &str {
+        "bus"
+    }
+    fn description(&self) -> &str {
+        tr("Event bus queue depth and publish/drop counters")
+    }
+    fn usage(&self) -> &str {
+        "bus stats"
+    }
+
+    fn execute(&self, args: &[&str], ctx: &mut CommandContext) -> CommandOutput {
+        if args != ["stats"] {
+            return CommandOutput::Error(vec![format!("usage: {}", self.usage())]);
+        }
+        let stats = ctx.bus.stats();
+        let totals = CommandOutput::key_value([
+            ("depth", stats.depth.to_string()),
+            ("peak", stats.peak_depth.to_string()),
+            ("limit", stats.max_queue.to_string()),
+            ("overflow", stats.overflow.name().to_string()),
+            ("published", stats.published().to_string()),
+            ("dropped", stats.dropped().to_string()),
+            ("blocked", stats.blocked.to_string()),
+        ]);
+        let mut table = Table::new(vec![
+            Column::left("KIND"),
+            Column::right("PUBLISHED"),
+            Column::right("DROPPED"),
+        ]);
+        for (kind, counts) in &stats.kinds {
+            table.push_row(vec![
+                kind.to_string(),
+                counts.published.to_string(),
+                counts.dropped.to_string(),
+            ]);
+        }
+        CommandOutput::Multi(vec![totals, CommandOutput::Table(table)])
+    }
+}
+
 /// Create a [`CommandRegistry`] pre-loaded with all built-in commands.
 ///
 /// Registers: `help`, `clear`, `modules`, `switch`, `quit`, `uptime`, `tps`,
-/// and `echo`.
+/// `echo`, `spans`, `vars`, `log`, and `bus`.
 pub fn builtin_registry() -> CommandRegistry {
     let mut reg = CommandRegistry::new();
     reg.register(Box::new(HelpCommand));
     reg.register(Box::new(ClearCommand));
     reg.register(Box::new(ModulesCommand));
     reg.register(Box::new(SwitchCommand));
+    reg.register(Box::new(BackCommand));
     reg.register(Box::new(QuitCommand));
     reg.register(Box::new(UptimeCommand));
     reg.register(Box::new(TpsCommand));
     reg.register(Box::new(EchoCommand));
+    reg.register(Box::new(RandomCommand));
+    reg.register(Box::new(SpansCommand));
+    reg.register(Box::new(VarsCommand));
+    reg.register(Box::new(LogCommand));
+    reg.register(Box::new(BusCommand));
     reg
 }
 
@@ -354,7 +755,18 @@ mod tests {
         }
     }
 
-    fn make_ctx() -> (ModuleRegistry, Console, EventBus, TickCounter, Instant) {
+    type CtxParts = (
+        ModuleRegistry,
+        Console,
+        EventBus,
+        TickCounter,
+        Instant,
+        SpanStats,
+        Rng,
+        UndoStack,
+    );
+
+    fn make_ctx() -> CtxParts {
         let mut reg = ModuleRegistry::new();
         reg.register(Box::new(FakeModule {
             id: "hello",
@@ -372,18 +784,23 @@ mod tests {
             EventBus::new(),
             TickCounter::default(),
             Instant::now(),
+            SpanStats::default(),
+            Rng::new(0),
+            UndoStack::default(),
         )
     }
 
-    fn ctx_from(
-        parts: &mut (ModuleRegistry, Console, EventBus, TickCounter, Instant),
-    ) -> CommandContext<'_> {
+    fn ctx_from(parts: &mut CtxParts) -> CommandContext<'_> {
         CommandContext {
             registry: &mut parts.0,
             console: &mut parts.1,
             bus: &mut parts.2,
             tick_counter: &parts.3,
             started_at: parts.4,
+            spans: &parts.5,
+            rng: &mut parts.6,
+            undo: &mut parts.7,
+            origin: CommandOrigin::Console,
         }
     }
 
@@ -406,13 +823,23 @@ mod tests {
         let mut parts = make_ctx();
         let mut ctx = ctx_from(&mut parts);
         match reg.execute("foobar", &mut ctx) {
-            CommandOutput::Lines(lines) => {
+            CommandOutput::Error(lines) => {
                 assert!(lines[0].contains("unknown command"));
             }
-            _ => panic!("expected Lines"),
+            _ => panic!("expected Error"),
         }
     }
 
+    #[test]
+    fn resolve_follows_aliases_and_names_unknown_commands() {
+        let reg = builtin_registry();
+        assert_eq!(
+            reg.resolve("foobar").err(),
+            Some(CoreError::UnknownCommand("foobar".into()))
+        );
+        assert_eq!(reg.resolve("fps").unwrap().name(), "tps");
+    }
+
     #[test]
     fn command_name_extraction() {
         let reg = builtin_registry();
@@ -462,6 +889,7 @@ mod tests {
             level: crate::logging::LogLevel::Info,
             target: "test".into(),
             message: "hello".into(),
+            count: 1,
         });
         assert_eq!(parts.1.log_lines().len(), 1);
         let mut ctx = ctx_from(&mut parts);
@@ -469,19 +897,73 @@ mod tests {
         assert!(parts.1.log_lines().is_empty());
     }
 
+    #[test]
+    fn clear_command_can_be_undone() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        parts.1.push_log(crate::logging::LogEntry {
+            level: crate::logging::LogLevel::Info,
+            target: "test".into(),
+            message: "hello".into(),
+            count: 1,
+        });
+        reg.execute("clear", &mut ctx_from(&mut parts));
+
+        // The cleared lines can be put back for a while
+        let entry = parts.7.pop(Instant::now()).unwrap();
+        assert_eq!(entry.label, "clear");
+        let Inverse::RestoreLogs(lines) = entry.inverse else {
+            panic!("expected cleared lines");
+        };
+        assert_eq!(lines[0].message, "hello");
+        assert!(entry.expires_at.is_some());
+    }
+
     #[test]
     fn modules_command_lists_modules() {
         let reg = builtin_registry();
         let mut parts = make_ctx();
         let mut ctx = ctx_from(&mut parts);
         match reg.execute("modules", &mut ctx) {
-            CommandOutput::Lines(lines) => {
-                assert_eq!(lines.len(), 2);
-                assert!(lines[0].contains("hello"));
-                assert!(lines[1].contains("stats"));
-                assert!(lines[0].contains("*"));
+            CommandOutput::Table(table) => {
+                assert_eq!(table.rows.len(), 2);
+                assert_eq!(table.rows[0], vec!["hello", "Hello", "*", "", "ok"]);
+                assert_eq!(table.rows[1][0], "stats");
+                assert_eq!(table.rows[1][2], "");
             }
-            _ => panic!("expected Lines"),
+            _ => panic!("expected Table"),
+        }
+    }
+
+    #[test]
+    fn modules_command_shows_crashed_modules() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        parts.0.mark_crashed("stats", "boom".to_string());
+        let mut ctx = ctx_from(&mut parts);
+        match reg.execute("modules", &mut ctx) {
+            CommandOutput::Table(table) => {
+                assert_eq!(table.rows[0][4], "ok");
+                assert_eq!(table.rows[1][4], "crashed");
+            }
+            _ => panic!("expected Table"),
+        }
+    }
+
+    #[test]
+    fn modules_command_shows_attention_badges() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        parts
+            .0
+            .request_attention("stats", crate::event::AttentionLevel::Warn);
+        let mut ctx = ctx_from(&mut parts);
+        match reg.execute("modules", &mut ctx) {
+            CommandOutput::Table(table) => {
+                assert_eq!(table.rows[0][3], "");
+                assert_eq!(table.rows[1][3], "warn (1)");
+            }
+            _ => panic!("expected Table"),
         }
     }
 
@@ -508,7 +990,39 @@ mod tests {
         let events = parts.2.drain();
         assert_eq!(events.len(), 2);
         assert!(matches!(&events[0], Event::ModuleDeactivated { id } if id == "hello"));
-        assert!(matches!(&events[1], Event::ModuleActivated { id } if id == "stats"));
+        assert!(matches!(
+            &events[1],
+            Event::ModuleActivated { id, reason: ActivationReason::Switch } if id == "stats"
+        ));
+    }
+
+    #[test]
+    fn back_command_returns_to_previous_module() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        assert!(reg.execute("back", &mut ctx_from(&mut parts)).is_error());
+
+        reg.execute("switch stats", &mut ctx_from(&mut parts));
+        parts.2.drain();
+        let mut ctx = ctx_from(&mut parts);
+        ctx.origin = CommandOrigin::Plugin;
+        reg.execute("switch hello", &mut ctx);
+        let events = parts.2.drain();
+        assert!(matches!(
+            &events[1],
+            Event::ModuleActivated {
+                reason: ActivationReason::Plugin,
+                ..
+            }
+        ));
+
+        let output = reg.execute("back", &mut ctx_from(&mut parts));
+        assert_eq!(output.to_lines(), vec!["Back to: Stats"]);
+        let events = parts.2.drain();
+        assert!(matches!(
+            &events[1],
+            Event::ModuleActivated { id, reason: ActivationReason::Back } if id == "stats"
+        ));
     }
 
     #[test]
@@ -517,10 +1031,10 @@ mod tests {
         let mut parts = make_ctx();
         let mut ctx = ctx_from(&mut parts);
         match reg.execute("switch nope", &mut ctx) {
-            CommandOutput::Lines(lines) => {
-                assert!(lines[0].contains("error"));
+            CommandOutput::Error(lines) => {
+                assert!(lines[0].contains("nope"));
             }
-            _ => panic!("expected Lines"),
+            _ => panic!("expected Error"),
         }
     }
 
@@ -530,10 +1044,10 @@ mod tests {
         let mut parts = make_ctx();
         let mut ctx = ctx_from(&mut parts);
         match reg.execute("switch", &mut ctx) {
-            CommandOutput::Lines(lines) => {
+            CommandOutput::Error(lines) => {
                 assert!(lines[0].contains("usage"));
             }
-            _ => panic!("expected Lines"),
+            _ => panic!("expected Error"),
         }
     }
 
@@ -558,6 +1072,44 @@ mod tests {
         assert!(matches!(reg.execute("q", &mut ctx), CommandOutput::Quit));
     }
 
+    /// Asks for a name, then greets whoever answers.
+    struct GreetCommand;
+
+    impl Command for GreetCommand {
+        fn name(&self) -> &str {
+            "greet"
+        }
+        fn description(&self) -> &str {
+            "Greet someone"
+        }
+        fn execute(&self, _args: &[&str], _ctx: &mut CommandContext) -> CommandOutput {
+            CommandOutput::prompt("Name?", "name")
+        }
+        fn answer(&self, token: &str, answer: &str, _ctx: &mut CommandContext) -> CommandOutput {
+            CommandOutput::Lines(vec![format!("{token}: hello, {answer}")])
+        }
+    }
+
+    #[test]
+    fn answers_are_routed_to_the_asking_command() {
+        let mut reg = builtin_registry();
+        reg.register(Box::new(GreetCommand));
+        let mut parts = make_ctx();
+        let mut ctx = ctx_from(&mut parts);
+
+        let output = reg.execute("greet", &mut ctx);
+        assert_eq!(output, CommandOutput::prompt("Name?", "name"));
+        assert_eq!(
+            reg.answer("greet", "name", "Ada", &mut ctx).to_lines(),
+            vec!["name: hello, Ada"]
+        );
+        assert_eq!(
+            reg.answer("uptime", "name", "Ada", &mut ctx).to_lines(),
+            vec!["uptime has no pending question"]
+        );
+        assert!(reg.answer("nope", "name", "Ada", &mut ctx).is_error());
+    }
+
     #[test]
     fn uptime_command() {
         let reg = builtin_registry();
@@ -610,6 +1162,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn random_is_seeded_and_bounded() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        let mut expected = Rng::new(0);
+        let mut ctx = ctx_from(&mut parts);
+        assert_eq!(
+            reg.execute("random 6", &mut ctx).to_lines(),
+            vec![expected.below(6).to_string()]
+        );
+        assert!(reg.execute("random 0", &mut ctx).is_error());
+        assert!(reg.execute("random x", &mut ctx).is_error());
+    }
+
     #[test]
     fn echo_empty() {
         let reg = builtin_registry();
@@ -622,4 +1188,103 @@ mod tests {
             _ => panic!("expected Lines"),
         }
     }
+
+    #[test]
+    fn spans_command_summarizes_by_name() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        parts
+            .5
+            .record("render", std::time::Duration::from_millis(4));
+        let mut ctx = ctx_from(&mut parts);
+        match reg.execute("spans", &mut ctx) {
+            output @ CommandOutput::Table(_) => {
+                let lines = output.to_lines();
+                assert_eq!(lines.len(), 2);
+                assert!(lines[1].contains("render"));
+                assert!(lines[1].contains("4.0ms"));
+            }
+            _ => panic!("expected Table"),
+        }
+        match reg.execute("spans missing", &mut ctx) {
+            CommandOutput::Lines(lines) => assert_eq!(lines[0], "no spans recorded"),
+            _ => panic!("expected Lines"),
+        }
+    }
+
+    #[test]
+    fn variables_expand_and_list() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        let mut ctx = ctx_from(&mut parts);
+        reg.execute("switch stats", &mut ctx);
+        match reg.execute("echo $active_module from $last_module", &mut ctx) {
+            CommandOutput::Lines(lines) => assert_eq!(lines[0], "stats from hello"),
+            _ => panic!("expected Lines"),
+        }
+        match reg.execute("vars | grep active_module", &mut ctx) {
+            CommandOutput::KeyValue(pairs) => {
+                assert_eq!(pairs, vec![("$active_module".into(), "stats".into())]);
+            }
+            _ => panic!("expected KeyValue"),
+        }
+    }
+
+    #[test]
+    fn log_level_rejects_bad_input() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        let mut ctx = ctx_from(&mut parts);
+        assert!(reg.execute("log", &mut ctx).is_error());
+        let output = reg.execute("log level 'spud=[bogus'", &mut ctx);
+        assert!(output.to_lines()[0].contains("invalid log filter"));
+    }
+
+    #[test]
+    fn bus_stats_reports_counters_by_kind() {
+        let reg = builtin_registry();
+        let mut parts = make_ctx();
+        parts.2.publish(Event::Quit);
+        let mut ctx = ctx_from(&mut parts);
+        assert!(reg.execute("bus", &mut ctx).is_error());
+        let lines = reg.execute("bus stats", &mut ctx).to_lines();
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("overflow") && line.contains("drop-oldest")));
+        assert!(lines.last().unwrap().contains("quit"));
+    }
+
+    #[test]
+    fn log_stats_lists_totals_and_targets() {
+        let mut stats = LogStats::new();
+        stats.record(LogLevel::Error, "spud_app");
+        stats.record(LogLevel::Info, "spud_app");
+        let lines = log_stats_output(&stats).to_lines();
+        assert_eq!(lines[0], "  ERROR    1");
+        assert_eq!(lines[5], "  UNACKED  1");
+        assert_eq!(lines[6], "  TARGET    ERROR  WARN  INFO  DEBUG  TRACE");
+        assert_eq!(lines[7], "  spud_app      1     0     1      0      0");
+    }
+
+    #[test]
+    fn log_du_totals_by_kind_and_lists_largest_first() {
+        let entry = |name: &str, bytes, log, compressed| LogDirEntry {
+            name: name.into(),
+            bytes,
+            log,
+            compressed,
+        };
+        let files = [
+            entry("plugin-audit.jsonl", 512, false, false),
+            entry("spud.log.2026-10-15.gz", 3 * 1024, true, true),
+            entry("spud.log.2026-10-16", 2 * 1024 * 1024, true, false),
+        ];
+        let lines = log_du_output(std::path::Path::new("/logs"), &files).to_lines();
+        assert!(lines[2].ends_with("2.0 MiB"), "{lines:?}");
+        assert!(lines[3].ends_with("2.0 MiB"));
+        assert!(lines[4].ends_with("3.0 KiB"));
+        assert!(lines[5].ends_with("512 B"));
+        assert!(lines[7].starts_with("  spud.log.2026-10-16"));
+        assert!(lines[9].starts_with("  plugin-audit.jsonl"));
+    }
 }
@@ -1,7 +1,69 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
 
-use crate::logging::LogEntry;
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::command::{CommandOutput, CommandRegistry};
+use crate::easing::{ease, progress_at, Easing};
+use crate::logging::{LogEntry, LogLevel};
+use crate::usage::CommandUsage;
+
+/// Log target for echoed console input; the UI highlights these entries.
+pub const INPUT_TARGET: &str = "input";
+/// Log target for plain command output.
+pub const OUTPUT_TARGET: &str = "console";
+/// Log target for the header line of a table; the UI styles it.
+pub const TABLE_HEADER_TARGET: &str = "table.header";
+/// Log target for table rows.
+pub const TABLE_TARGET: &str = "table";
+/// Log target for key-value lines; the UI styles the keys.
+pub const KEY_VALUE_TARGET: &str = "key_value";
+
+/// Whether entries logged under `target` are column-aligned output, which
+/// the UI scrolls sideways instead of wrapping.
+pub fn is_tabular(target: &str) -> bool {
+    matches!(
+        target,
+        TABLE_HEADER_TARGET | TABLE_TARGET | KEY_VALUE_TARGET
+    )
+}
+
+/// Number of submitted commands remembered for suggestions.
+const HISTORY_CAPACITY: usize = 100;
+
+/// Identical log messages closer together than this collapse into one line.
+const COALESCE_WINDOW: Duration = Duration::from_secs(2);
+/// Log lines a single target may add per [`RATE_WINDOW`]; the rest are
+/// dropped and summarised when the window ends.
+const TARGET_RATE_LIMIT: usize = 50;
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Per-target line budget for [`Console::ingest_log`].
+#[derive(Debug, Clone, Copy)]
+struct RateWindow {
+    started_at: Instant,
+    lines: usize,
+    suppressed: usize,
+}
+
+/// Serializable view of a [`Console`], for state dumps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConsoleSnapshot {
+    pub open: bool,
+    pub visible: bool,
+    pub log_lines: usize,
+    pub max_lines: usize,
+    pub scroll_offset: usize,
+    pub slide_duration_ms: u64,
+    pub easing: Easing,
+    pub input: String,
+    /// Submitted commands, oldest first.
+    pub history: Vec<String>,
+    /// Question the next submitted line answers, if one is pending.
+    pub prompt: Option<String>,
+}
 
 /// Animation state for the drop-down console slide.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -16,6 +78,98 @@ pub enum SlideState {
     Closing { started_at: Instant },
 }
 
+/// Inline hint shown after the console input while typing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputHint {
+    /// A likely completion of the input, from history or the command list.
+    Suggest {
+        /// Text appended to the input when the suggestion is accepted.
+        completion: String,
+        /// Usage of the matched command, shown but never inserted.
+        usage: Option<String>,
+    },
+    /// The first word does not start any known command.
+    Unknown,
+}
+
+/// Who asked a pending [`ConsolePrompt`], and so receives the answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptOrigin {
+    /// A built-in or app-level command, by name.
+    Command(String),
+    /// A module's console command, by module ID.
+    Module(String),
+    /// A plugin, by plugin ID.
+    Plugin(String),
+    /// The app, confirming a destructive command before running these
+    /// words (see [`crate::guard`]).
+    Confirm(Vec<String>),
+}
+
+/// A follow-up question that owns the input line: the next submitted line
+/// is routed to `origin` with `callback_token` instead of running as a
+/// command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsolePrompt {
+    pub question: String,
+    pub callback_token: String,
+    pub origin: PromptOrigin,
+}
+
+/// Reverse incremental search through the command history (Ctrl-R), as in
+/// readline: the newest command containing the query is the match, and
+/// searching again steps to older ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistorySearch {
+    query: String,
+    /// History index of the match.
+    matched: Option<usize>,
+    /// The query matches nothing older than the last match (or nothing at
+    /// all); the last match stays shown.
+    failed: bool,
+    /// Input line and cursor from before the search, restored on cancel.
+    saved_input: String,
+    saved_cursor: usize,
+}
+
+impl HistorySearch {
+    /// Text typed since the search started.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// The matched command and the byte offset of the query within it.
+    pub fn matched<'a>(&self, history: &'a VecDeque<String>) -> Option<(&'a str, usize)> {
+        let entry = history.get(self.matched?)?;
+        let at = entry.find(&self.query).unwrap_or(0);
+        Some((entry, at))
+    }
+
+    /// The last step found nothing; shown as a failed search.
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Find the newest entry before `before` (exclusive) containing the
+    /// query and differing from the current match.
+    fn find(&mut self, history: &VecDeque<String>, before: usize) {
+        let current = self.matched.and_then(|index| history.get(index));
+        let found = history
+            .iter()
+            .enumerate()
+            .take(before)
+            .rev()
+            .find(|(_, entry)| entry.contains(&self.query) && Some(*entry) != current);
+        match found {
+            Some((index, _)) => {
+                self.matched = Some(index);
+                self.failed = false;
+            }
+            None => self.failed = true,
+        }
+    }
+}
+
 /// Drop-down console state.
 ///
 /// Manages visibility, a ring buffer of log lines, a single-line input buffer
@@ -24,15 +178,30 @@ pub enum SlideState {
 pub struct Console {
     /// Current slide animation state.
     pub slide: SlideState,
-    /// Duration of the slide animation (must be > 0).
+    /// Duration of the slide animation; zero means no animation.
     slide_duration: Duration,
+    /// Curve the slide follows, in both directions.
+    easing: Easing,
     log_lines: VecDeque<LogEntry>,
     /// The current text in the input line.
     pub input_buffer: String,
     /// Byte offset of the cursor within `input_buffer`.
     pub cursor_pos: usize,
     scroll_offset: usize,
+    /// Columns tabular lines are scrolled sideways by.
+    h_scroll: usize,
     max_lines: usize,
+    history: VecDeque<String>,
+    /// How often each command has run; ranks name completions.
+    usage: CommandUsage,
+    hint: Option<InputHint>,
+    prompt: Option<ConsolePrompt>,
+    /// Ctrl-R search in progress; it owns the input line until accepted
+    /// or cancelled.
+    search: Option<HistorySearch>,
+    /// When the newest line was last added or coalesced by `ingest_log`.
+    last_ingest: Option<Instant>,
+    rate_windows: HashMap<String, RateWindow>,
 }
 
 impl Default for Console {
@@ -45,60 +214,81 @@ impl Console {
     /// Create a new console with the given maximum log line capacity.
     ///
     pub fn new(max_lines: usize) -> Self {
-        let slide_duration = Duration::from_millis(250);
-        debug_assert!(!slide_duration.is_zero(), "slide_duration must be > 0");
         Self {
             slide: SlideState::Hidden,
-            slide_duration,
+            slide_duration: Duration::from_millis(250),
+            easing: Easing::default(),
             log_lines: VecDeque::with_capacity(max_lines),
             input_buffer: String::new(),
             cursor_pos: 0,
             scroll_offset: 0,
+            h_scroll: 0,
             max_lines,
+            history: VecDeque::with_capacity(HISTORY_CAPACITY),
+            usage: CommandUsage::default(),
+            hint: None,
+            prompt: None,
+            search: None,
+            last_ingest: None,
+            rate_windows: HashMap::new(),
         }
     }
 
     /// Toggle the console open/closed. Handles mid-animation reversals
     /// by preserving the current visual position.
     pub fn toggle(&mut self, now: Instant) {
+        if self.slide_duration.is_zero() {
+            self.slide = match self.slide {
+                SlideState::Hidden | SlideState::Closing { .. } => SlideState::Open,
+                SlideState::Open | SlideState::Opening { .. } => SlideState::Hidden,
+            };
+            return;
+        }
         self.slide = match self.slide {
             SlideState::Hidden => SlideState::Opening { started_at: now },
             SlideState::Open => SlideState::Closing { started_at: now },
             SlideState::Opening { started_at } => {
-                // Reverse: compute current open fraction, then backdate the
-                // Closing started_at so its fraction equals the same position.
-                // Closing fraction = 1.0 - close_progress, so we need
-                // close_progress = 1.0 - open_fraction.
-                let elapsed = now
-                    .checked_duration_since(started_at)
-                    .unwrap_or(Duration::ZERO);
-                let open_fraction =
-                    (elapsed.as_secs_f64() / self.slide_duration.as_secs_f64()).min(1.0);
-                let elapsed_closing = self.slide_duration.mul_f64(1.0 - open_fraction);
+                // Reverse: compute the current open fraction, then backdate
+                // the Closing started_at so its fraction equals the same
+                // position. Closing fraction = 1.0 - ease(close_progress), so
+                // close_progress = progress_at(1.0 - open_fraction).
+                let open_fraction = ease(self.easing, self.slide_progress(started_at, now));
+                let close_progress = progress_at(self.easing, 1.0 - open_fraction);
                 SlideState::Closing {
-                    started_at: now - elapsed_closing,
+                    started_at: now - self.slide_duration.mul_f64(close_progress),
                 }
             }
             SlideState::Closing { started_at } => {
-                // Reverse: compute current visible fraction, then backdate the
-                // Opening started_at so its fraction equals the same position.
-                // Opening fraction = open_progress, and current fraction =
-                // 1.0 - close_progress, so open_progress = 1.0 - close_progress.
-                let elapsed = now
-                    .checked_duration_since(started_at)
-                    .unwrap_or(Duration::ZERO);
-                let close_progress =
-                    (elapsed.as_secs_f64() / self.slide_duration.as_secs_f64()).min(1.0);
-                let elapsed_opening = self.slide_duration.mul_f64(1.0 - close_progress);
+                // Reverse: compute the current visible fraction, then backdate
+                // the Opening started_at so its fraction equals the same
+                // position. Opening fraction = ease(open_progress), so
+                // open_progress = progress_at(visible_fraction).
+                let visible_fraction =
+                    1.0 - ease(self.easing, self.slide_progress(started_at, now));
+                let open_progress = progress_at(self.easing, visible_fraction);
                 SlideState::Opening {
-                    started_at: now - elapsed_opening,
+                    started_at: now - self.slide_duration.mul_f64(open_progress),
                 }
             }
         };
     }
 
-    /// Advance animation state. Call each loop iteration before rendering.
+    /// Advance animation state and report log targets whose rate window
+    /// ended with lines suppressed. Call each loop iteration before
+    /// rendering.
     pub fn update(&mut self, now: Instant) {
+        let expired: Vec<String> = self
+            .rate_windows
+            .iter()
+            .filter(|(_, window)| now.saturating_duration_since(window.started_at) >= RATE_WINDOW)
+            .map(|(target, _)| target.clone())
+            .collect();
+        for target in expired {
+            if let Some(summary) = self.roll_rate_window(&target, now) {
+                self.push_log(summary);
+            }
+        }
+
         self.slide = match self.slide {
             SlideState::Opening { started_at } => {
                 if now
@@ -126,27 +316,29 @@ impl Console {
         };
     }
 
-    /// Returns 0.0 (hidden) to 1.0 (fully open), linearly interpolated.
+    /// Returns 0.0 (hidden) to 1.0 (fully open), following the easing
+    /// curve in both directions.
     pub fn overlay_fraction(&self, now: Instant) -> f64 {
         match self.slide {
             SlideState::Hidden => 0.0,
             SlideState::Open => 1.0,
             SlideState::Opening { started_at } => {
-                let elapsed = now
-                    .checked_duration_since(started_at)
-                    .unwrap_or(Duration::ZERO);
-                (elapsed.as_secs_f64() / self.slide_duration.as_secs_f64()).min(1.0)
+                ease(self.easing, self.slide_progress(started_at, now))
             }
             SlideState::Closing { started_at } => {
-                let elapsed = now
-                    .checked_duration_since(started_at)
-                    .unwrap_or(Duration::ZERO);
-                let progress = (elapsed.as_secs_f64() / self.slide_duration.as_secs_f64()).min(1.0);
-                1.0 - progress
+                1.0 - ease(self.easing, self.slide_progress(started_at, now))
             }
         }
     }
 
+    /// Linear progress, 0.0 to 1.0, of a slide that began at `started_at`.
+    fn slide_progress(&self, started_at: Instant, now: Instant) -> f64 {
+        let elapsed = now
+            .checked_duration_since(started_at)
+            .unwrap_or(Duration::ZERO);
+        (elapsed.as_secs_f64() / self.slide_duration.as_secs_f64()).min(1.0)
+    }
+
     /// Returns true if the console needs rendering (any state except Hidden).
     pub fn is_visible(&self) -> bool {
         !matches!(self.slide, SlideState::Hidden)
@@ -162,6 +354,30 @@ impl Console {
         self.slide_duration
     }
 
+    /// Change the slide animation duration. Zero disables the animation
+    /// and finishes one in progress.
+    pub fn set_slide_duration(&mut self, duration: Duration) {
+        self.slide_duration = duration;
+        if duration.is_zero() {
+            self.slide = match self.slide {
+                SlideState::Opening { .. } => SlideState::Open,
+                SlideState::Closing { .. } => SlideState::Hidden,
+                other => other,
+            };
+        }
+    }
+
+    /// Returns the slide easing curve.
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// Change the slide easing curve. A slide in progress continues on the
+    /// new curve from the same point in time.
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
     /// Append a log entry. Drops the oldest entry if the buffer is full.
     pub fn push_log(&mut self, entry: LogEntry) {
         if self.log_lines.len() >= self.max_lines {
@@ -173,15 +389,130 @@ impl Console {
         self.log_lines.push_back(entry);
     }
 
+    /// Append a command's output. Error lines get an `error: ` prefix;
+    /// tables and key-value lists are logged under their own targets (see
+    /// [`is_tabular`]) so the UI can style them. Prompts are left to the
+    /// input line.
+    pub fn push_output(&mut self, output: &CommandOutput) {
+        for part in output.parts() {
+            let (first, rest) = match part {
+                CommandOutput::Prompt { .. } => continue,
+                CommandOutput::Table(_) => (TABLE_HEADER_TARGET, TABLE_TARGET),
+                CommandOutput::KeyValue(_) => (KEY_VALUE_TARGET, KEY_VALUE_TARGET),
+                _ => (OUTPUT_TARGET, OUTPUT_TARGET),
+            };
+            let (level, prefix) = if part.is_error() {
+                (LogLevel::Error, "error: ")
+            } else {
+                (LogLevel::Info, "")
+            };
+            for (index, line) in part.to_lines().into_iter().enumerate() {
+                self.push_log(LogEntry {
+                    level,
+                    target: if index == 0 { first } else { rest }.into(),
+                    message: format!("{prefix}{line}"),
+                    count: 1,
+                });
+            }
+        }
+    }
+
+    /// Append a log entry from the tracing pipeline, guarding against spam.
+    ///
+    /// A message identical to the newest line (same level, target, and text)
+    /// within [`COALESCE_WINDOW`] bumps that line's count instead of adding a
+    /// line. Each target may add at most [`TARGET_RATE_LIMIT`] lines per
+    /// [`RATE_WINDOW`]; excess lines are dropped and reported by
+    /// [`update`](Self::update) once the window ends.
+    pub fn ingest_log(&mut self, entry: LogEntry, now: Instant) {
+        let recent = self
+            .last_ingest
+            .is_some_and(|at| now.saturating_duration_since(at) < COALESCE_WINDOW);
+        if let Some(last) = self.log_lines.back_mut().filter(|_| recent) {
+            if last.level == entry.level
+                && last.target == entry.target
+                && last.message == entry.message
+            {
+                last.count = last.count.saturating_add(entry.count);
+                self.last_ingest = Some(now);
+                return;
+            }
+        }
+
+        if let Some(summary) = self.roll_rate_window(&entry.target, now) {
+            self.push_log(summary);
+        }
+        let window = self
+            .rate_windows
+            .entry(entry.target.clone())
+            .or_insert(RateWindow {
+                started_at: now,
+                lines: 0,
+                suppressed: 0,
+            });
+        if window.lines >= TARGET_RATE_LIMIT {
+            window.suppressed += 1;
+            return;
+        }
+        window.lines += 1;
+        self.push_log(entry);
+        self.last_ingest = Some(now);
+    }
+
+    /// End `target`'s rate window if it has expired, returning a summary
+    /// line if anything was suppressed in it.
+    fn roll_rate_window(&mut self, target: &str, now: Instant) -> Option<LogEntry> {
+        let window = self.rate_windows.get(target)?;
+        if now.saturating_duration_since(window.started_at) < RATE_WINDOW {
+            return None;
+        }
+        let suppressed = self.rate_windows.remove(target)?.suppressed;
+        (suppressed > 0).then(|| LogEntry {
+            level: LogLevel::Warn,
+            target: target.to_string(),
+            message: format!("suppressed {suppressed} log line(s) from {target} (rate limit)"),
+            count: 1,
+        })
+    }
+
+    /// Serializable view for state dumps.
+    pub fn snapshot(&self) -> ConsoleSnapshot {
+        ConsoleSnapshot {
+            open: self.is_open(),
+            visible: self.is_visible(),
+            log_lines: self.log_lines.len(),
+            max_lines: self.max_lines,
+            scroll_offset: self.scroll_offset,
+            slide_duration_ms: self.slide_duration.as_millis() as u64,
+            easing: self.easing,
+            input: self.input_buffer.clone(),
+            history: self.history.iter().cloned().collect(),
+            prompt: self.prompt.as_ref().map(|prompt| prompt.question.clone()),
+        }
+    }
+
     /// Return a reference to the log line buffer.
     pub fn log_lines(&self) -> &VecDeque<LogEntry> {
         &self.log_lines
     }
 
-    /// Clear all log lines and reset the scroll position.
-    pub fn clear_logs(&mut self) {
-        self.log_lines.clear();
+    /// Clear all log lines and reset the scroll position, returning the
+    /// lines removed.
+    pub fn clear_logs(&mut self) -> Vec<LogEntry> {
         self.scroll_offset = 0;
+        self.h_scroll = 0;
+        self.log_lines.drain(..).collect()
+    }
+
+    /// Put back lines removed by [`clear_logs`](Self::clear_logs), before
+    /// any logged since. The oldest go if that is more than the console
+    /// holds.
+    pub fn restore_logs(&mut self, lines: Vec<LogEntry>) {
+        let newer = std::mem::take(&mut self.log_lines);
+        self.log_lines = lines.into();
+        self.log_lines.extend(newer);
+        let excess = self.log_lines.len().saturating_sub(self.max_lines);
+        self.log_lines.drain(..excess);
     }
 
     /// Return the current scroll offset (0 = bottom / most recent).
@@ -200,54 +531,350 @@ impl Console {
         self.scroll_offset = self.scroll_offset.saturating_sub(amount);
     }
 
+    /// Return how many columns tabular lines are scrolled sideways by.
+    pub fn h_scroll(&self) -> usize {
+        self.h_scroll
+    }
+
+    /// Scroll tabular lines left (toward their start) by `amount` columns.
+    pub fn scroll_left(&mut self, amount: usize) {
+        self.h_scroll = self.h_scroll.saturating_sub(amount);
+    }
+
+    /// Scroll tabular lines right by `amount` columns, stopping with the
+    /// last column of the widest one still in view.
+    pub fn scroll_right(&mut self, amount: usize) {
+        let widest = self
+            .log_lines
+            .iter()
+            .filter(|entry| is_tabular(&entry.target))
+            .map(|entry| entry.message.width())
+            .max()
+            .unwrap_or(0);
+        self.h_scroll = (self.h_scroll + amount).min(widest.saturating_sub(1));
+    }
+
+    /// Scroll to `offset` lines above the bottom, clamped like
+    /// [`scroll_up`](Self::scroll_up).
+    pub fn set_scroll_offset(&mut self, offset: usize) {
+        self.scroll_offset = offset.min(self.log_lines.len().saturating_sub(1));
+    }
+
+    /// Show or hide the console at once, skipping the slide animation.
+    pub fn set_open(&mut self, open: bool) {
+        self.slide = if open {
+            SlideState::Open
+        } else {
+            SlideState::Hidden
+        };
+    }
+
     /// Insert a character at the current cursor position.
+    ///
+    /// A combining mark typed after a letter (how some terminals send a
+    /// dead-key accent) joins the letter's grapheme cluster, and the
+    /// cursor stays after the whole cluster.
     pub fn insert_char(&mut self, c: char) {
         self.input_buffer.insert(self.cursor_pos, c);
         self.cursor_pos += c.len_utf8();
     }
 
-    /// Delete the character before the cursor (backspace).
+    /// Delete the character before the cursor (backspace). A letter and
+    /// its combining marks go together.
     pub fn backspace(&mut self) {
         if self.cursor_pos > 0 {
-            let prev = self.input_buffer[..self.cursor_pos]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
-            self.input_buffer.remove(prev);
+            let prev = self.grapheme_before(self.cursor_pos);
+            self.input_buffer.replace_range(prev..self.cursor_pos, "");
             self.cursor_pos = prev;
         }
     }
 
-    /// Move the cursor one character to the left.
+    /// Move the cursor one character to the left, over a whole grapheme
+    /// cluster.
     pub fn cursor_left(&mut self) {
-        if self.cursor_pos > 0 {
-            self.cursor_pos = self.input_buffer[..self.cursor_pos]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
-        }
+        self.cursor_pos = self.grapheme_before(self.cursor_pos);
     }
 
-    /// Move the cursor one character to the right.
+    /// Move the cursor one character to the right, over a whole grapheme
+    /// cluster. At the end of the input this accepts the inline suggestion
+    /// instead.
     pub fn cursor_right(&mut self) {
-        if self.cursor_pos < self.input_buffer.len() {
-            self.cursor_pos = self.input_buffer[self.cursor_pos..]
-                .char_indices()
-                .nth(1)
-                .map(|(i, _)| self.cursor_pos + i)
-                .unwrap_or(self.input_buffer.len());
+        if self.accept_suggestion() {
+            return;
         }
+        self.cursor_pos += self.input_buffer[self.cursor_pos..]
+            .graphemes(true)
+            .next()
+            .map_or(0, str::len);
+    }
+
+    /// Byte offset of the grapheme cluster ending at `pos`.
+    fn grapheme_before(&self, pos: usize) -> usize {
+        self.input_buffer[..pos]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
     }
 
     /// Submit the current input, returning the text and clearing the buffer.
+    ///
+    /// Non-empty input is remembered in the suggestion history, unless it
+    /// answers a pending prompt.
     pub fn submit_input(&mut self) -> String {
         let input = self.input_buffer.clone();
         self.input_buffer.clear();
         self.cursor_pos = 0;
+        self.hint = None;
+
+        if self.prompt.is_some() {
+            return input;
+        }
+        let trimmed = input.trim();
+        if !trimmed.is_empty() && self.history.back().map(String::as_str) != Some(trimmed) {
+            if self.history.len() >= HISTORY_CAPACITY {
+                self.history.pop_front();
+            }
+            self.history.push_back(trimmed.to_string());
+        }
         input
     }
+
+    /// Replace the history with `entries`, oldest first, keeping the newest
+    /// [`HISTORY_CAPACITY`].
+    pub fn restore_history(&mut self, entries: impl IntoIterator<Item = String>) {
+        self.history.clear();
+        for entry in entries {
+            if self.history.len() >= HISTORY_CAPACITY {
+                self.history.pop_front();
+            }
+            self.history.push_back(entry);
+        }
+    }
+
+    /// Return previously submitted commands, oldest first.
+    pub fn history(&self) -> &VecDeque<String> {
+        &self.history
+    }
+
+    /// Command usage counts, which rank name completions.
+    pub fn usage(&self) -> &CommandUsage {
+        &self.usage
+    }
+
+    /// Command usage counts, for recording runs or restoring saved counts.
+    pub fn usage_mut(&mut self) -> &mut CommandUsage {
+        &mut self.usage
+    }
+
+    /// Return the inline hint for the current input, if any.
+    pub fn hint(&self) -> Option<&InputHint> {
+        self.hint.as_ref()
+    }
+
+    /// Recompute the inline hint for the current input.
+    ///
+    /// History wins over the command list (fish-style), and among command
+    /// names the most used wins; `extra` supplies `(name, usage)` pairs
+    /// for commands handled outside `registry`. There
+    /// is no hint while a prompt is pending, as the input is not a command.
+    pub fn refresh_hint(&mut self, registry: &CommandRegistry, extra: &[(&str, &str)]) {
+        self.hint = match self.prompt {
+            Some(_) => None,
+            None => compute_hint(
+                &self.input_buffer,
+                &self.history,
+                &self.usage,
+                registry,
+                extra,
+            ),
+        };
+    }
+
+    /// Hand the next submitted line to `prompt`'s origin, replacing any
+    /// prompt already pending.
+    pub fn set_prompt(&mut self, prompt: ConsolePrompt) {
+        self.prompt = Some(prompt);
+        self.hint = None;
+    }
+
+    /// Return the pending prompt, if any.
+    pub fn prompt(&self) -> Option<&ConsolePrompt> {
+        self.prompt.as_ref()
+    }
+
+    /// Remove and return the pending prompt, so the input line runs
+    /// commands again.
+    pub fn take_prompt(&mut self) -> Option<ConsolePrompt> {
+        self.prompt.take()
+    }
+
+    /// Start a reverse history search, or step to the next older match if
+    /// one is running.
+    pub fn search_older(&mut self) {
+        match &mut self.search {
+            Some(search) => {
+                let before = search.matched.unwrap_or(self.history.len());
+                search.find(&self.history, before);
+            }
+            None => {
+                self.search = Some(HistorySearch {
+                    query: String::new(),
+                    matched: None,
+                    failed: false,
+                    saved_input: self.input_buffer.clone(),
+                    saved_cursor: self.cursor_pos,
+                });
+                self.hint = None;
+            }
+        }
+    }
+
+    /// The running history search, if any.
+    pub fn search(&self) -> Option<&HistorySearch> {
+        self.search.as_ref()
+    }
+
+    /// The running search's match and the query's offset within it.
+    pub fn search_match(&self) -> Option<(&str, usize)> {
+        self.search.as_ref()?.matched(&self.history)
+    }
+
+    /// Add `c` to the search query and match the newest command with it.
+    pub fn search_insert(&mut self, c: char) {
+        if let Some(search) = &mut self.search {
+            search.query.push(c);
+            let last = search.matched.take();
+            search.find(&self.history, self.history.len());
+            if search.failed {
+                search.matched = last;
+            }
+        }
+    }
+
+    /// Remove the last character of the search query and match again from
+    /// the newest command.
+    pub fn search_backspace(&mut self) {
+        if let Some(search) = &mut self.search {
+            search.query.pop();
+            search.matched = None;
+            search.failed = false;
+            if !search.query.is_empty() {
+                search.find(&self.history, self.history.len());
+            }
+        }
+    }
+
+    /// End the search with its match in the input line, cursor at the end.
+    /// Without a match the input is left as it was before the search.
+    pub fn accept_search(&mut self) {
+        let Some(search) = self.search.take() else {
+            return;
+        };
+        match search.matched(&self.history) {
+            Some((entry, _)) => {
+                self.input_buffer = entry.to_string();
+                self.cursor_pos = self.input_buffer.len();
+            }
+            None => {
+                self.input_buffer = search.saved_input;
+                self.cursor_pos = search.saved_cursor;
+            }
+        }
+    }
+
+    /// End the search and restore the input line from before it.
+    pub fn cancel_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.input_buffer = search.saved_input;
+            self.cursor_pos = search.saved_cursor;
+        }
+    }
+
+    /// Append the suggested completion if the cursor is at the end of the
+    /// input. Returns `true` if a completion was accepted.
+    pub fn accept_suggestion(&mut self) -> bool {
+        if self.cursor_pos != self.input_buffer.len() {
+            return false;
+        }
+        let Some(InputHint::Suggest { completion, .. }) = &mut self.hint else {
+            return false;
+        };
+        if completion.is_empty() {
+            return false;
+        }
+        self.input_buffer.push_str(&std::mem::take(completion));
+        self.cursor_pos = self.input_buffer.len();
+        true
+    }
+}
+
+fn compute_hint(
+    input: &str,
+    history: &VecDeque<String>,
+    usage: &CommandUsage,
+    registry: &CommandRegistry,
+    extra: &[(&str, &str)],
+) -> Option<InputHint> {
+    let typed = input.trim_start();
+    if typed.is_empty() {
+        return None;
+    }
+    let usage_of = |name: &str| {
+        registry
+            .get(name)
+            .map(|cmd| cmd.usage().to_string())
+            .or_else(|| {
+                extra
+                    .iter()
+                    .find(|(extra_name, _)| *extra_name == name)
+                    .map(|(_, usage)| usage.to_string())
+            })
+    };
+
+    if let Some(entry) = history
+        .iter()
+        .rev()
+        .find(|entry| entry.len() > input.len() && entry.starts_with(input))
+    {
+        let name = entry.split_whitespace().next().unwrap_or_default();
+        return Some(InputHint::Suggest {
+            completion: entry[input.len()..].to_string(),
+            usage: usage_of(name),
+        });
+    }
+
+    let mut words = typed.split_whitespace();
+    let first = words.next().unwrap_or_default();
+    let typing_name = !typed.contains(char::is_whitespace);
+
+    if typing_name {
+        let best = registry
+            .names()
+            .chain(extra.iter().map(|(name, _)| *name))
+            .filter(|name| name.starts_with(first))
+            .min_by(|a, b| {
+                usage
+                    .count(b)
+                    .cmp(&usage.count(a))
+                    .then(a.len().cmp(&b.len()))
+                    .then(a.cmp(b))
+            });
+        return match best {
+            Some(name) => Some(InputHint::Suggest {
+                completion: name[first.len()..].to_string(),
+                usage: usage_of(name),
+            }),
+            None => Some(InputHint::Unknown),
+        };
+    }
+
+    match usage_of(first) {
+        Some(usage) => Some(InputHint::Suggest {
+            completion: String::new(),
+            usage: Some(usage),
+        }),
+        None => Some(InputHint::Unknown),
+    }
 }
 
 #[cfg(test)]
@@ -260,6 +887,7 @@ mod tests {
             level: LogLevel::Info,
             target: "test".into(),
             message: msg.into(),
+            count: 1,
         }
     }
 
@@ -282,9 +910,76 @@ mod tests {
     }
 
     #[test]
-    fn toggle_mid_opening_reverses_to_closing() {
+    fn zero_slide_duration_toggles_instantly() {
+        let mut c = Console::default();
+        let now = Instant::now();
+        c.toggle(now);
+        c.set_slide_duration(Duration::ZERO);
+        assert_eq!(c.slide, SlideState::Open, "animation in progress finishes");
+        c.toggle(now);
+        assert_eq!(c.slide, SlideState::Hidden);
+        c.toggle(now);
+        assert!(c.is_open());
+    }
+
+    /// A console whose slide moves at constant speed.
+    fn linear_console() -> Console {
+        let mut c = Console::default();
+        c.set_easing(Easing::Linear);
+        c
+    }
+
+    #[test]
+    fn default_easing_starts_fast_and_settles() {
         let mut c = Console::default();
         let start = Instant::now();
+        c.toggle(start);
+        let quarter = c.overlay_fraction(start + c.slide_duration() / 4);
+        let three_quarters = c.overlay_fraction(start + c.slide_duration() * 3 / 4);
+        assert!((quarter - 0.578).abs() < 0.01, "got {quarter}");
+        assert!(
+            (three_quarters - 0.984).abs() < 0.01,
+            "got {three_quarters}"
+        );
+    }
+
+    #[test]
+    fn reversal_keeps_position_on_every_curve() {
+        for easing in Easing::ALL {
+            for percent in [10, 25, 50, 80] {
+                let mut c = Console::default();
+                c.set_easing(easing);
+                let start = Instant::now();
+                let at = start + c.slide_duration() * percent / 100;
+                let later = at + c.slide_duration() / 10;
+
+                c.toggle(start); // Hidden -> Opening
+                let before = c.overlay_fraction(at);
+                c.toggle(at); // Opening -> Closing
+                let after = c.overlay_fraction(at);
+                assert!(
+                    (after - before).abs() < 1e-6,
+                    "{easing} at {percent}%: opening {before}, closing {after}"
+                );
+                let closing = c.overlay_fraction(later);
+                assert!(closing < after, "{easing} keeps closing");
+
+                c.toggle(later); // Closing -> Opening
+                let reopened = c.overlay_fraction(later);
+                assert!(
+                    (reopened - closing).abs() < 1e-6,
+                    "{easing} at {percent}%: closing {closing}, opening {reopened}"
+                );
+                c.update(later + c.slide_duration());
+                assert!(c.is_open());
+            }
+        }
+    }
+
+    #[test]
+    fn toggle_mid_opening_reverses_to_closing() {
+        let mut c = linear_console();
+        let start = Instant::now();
         c.toggle(start); // Hidden -> Opening
 
         // Advance halfway through the animation
@@ -299,7 +994,7 @@ mod tests {
 
     #[test]
     fn toggle_mid_closing_reverses_to_opening() {
-        let mut c = Console::default();
+        let mut c = linear_console();
         let start = Instant::now();
         c.slide = SlideState::Open;
         c.toggle(start); // Open -> Closing
@@ -314,7 +1009,7 @@ mod tests {
 
     #[test]
     fn toggle_mid_opening_reverses_at_quarter() {
-        let mut c = Console::default();
+        let mut c = linear_console();
         let start = Instant::now();
         c.toggle(start); // Hidden -> Opening
 
@@ -338,7 +1033,7 @@ mod tests {
 
     #[test]
     fn toggle_mid_closing_reverses_at_quarter() {
-        let mut c = Console::default();
+        let mut c = linear_console();
         let start = Instant::now();
         c.slide = SlideState::Open;
         c.toggle(start); // Open -> Closing
@@ -399,7 +1094,7 @@ mod tests {
 
     #[test]
     fn overlay_fraction_mid_animation() {
-        let mut c = Console::default();
+        let mut c = linear_console();
         let start = Instant::now();
         c.toggle(start);
 
@@ -526,6 +1221,30 @@ mod tests {
         assert_eq!(c.cursor_pos, 1);
     }
 
+    #[test]
+    fn cursor_and_backspace_step_over_grapheme_clusters() {
+        let mut c = Console::default();
+        // "café" with the accent as a combining mark, then CJK
+        type_str(&mut c, "cafe\u{301} 日本");
+        c.cursor_left();
+        c.cursor_left();
+        c.cursor_left();
+        assert_eq!(&c.input_buffer[c.cursor_pos..], " 日本");
+        c.backspace();
+        assert_eq!(c.input_buffer, "caf 日本");
+        c.cursor_right();
+        c.cursor_right();
+        assert_eq!(&c.input_buffer[..c.cursor_pos], "caf 日");
+
+        // An accent typed after a letter joins it
+        c.cursor_left();
+        c.cursor_left();
+        type_str(&mut c, "e\u{300}");
+        assert_eq!(c.input_buffer, "cafe\u{300} 日本");
+        c.cursor_left();
+        assert_eq!(&c.input_buffer[c.cursor_pos..], "e\u{300} 日本");
+    }
+
     #[test]
     fn backspace_at_zero_is_noop() {
         let mut c = Console::default();
@@ -545,4 +1264,391 @@ mod tests {
         assert!(c.log_lines().is_empty());
         assert_eq!(c.scroll_offset(), 0);
     }
+
+    #[test]
+    fn restore_logs_puts_cleared_lines_back_before_newer_ones() {
+        let mut c = Console::new(100);
+        for i in 0..10 {
+            c.push_log(entry(&format!("msg {}", i)));
+        }
+        let cleared = c.clear_logs();
+        assert_eq!(cleared.len(), 10);
+
+        c.push_log(entry("after"));
+        c.restore_logs(cleared);
+        let messages: Vec<_> = c.log_lines().iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages.len(), 11);
+        assert_eq!(messages[0], "msg 0");
+        assert_eq!(messages[10], "after");
+
+        // Restoring past capacity keeps the newest lines
+        let mut small = Console::new(3);
+        for i in 0..3 {
+            small.push_log(entry(&format!("old {i}")));
+        }
+        let cleared = small.clear_logs();
+        small.push_log(entry("new"));
+        small.restore_logs(cleared);
+        let messages: Vec<_> = small
+            .log_lines()
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect();
+        assert_eq!(messages, ["old 1", "old 2", "new"]);
+    }
+
+    #[test]
+    fn output_keeps_table_and_key_value_targets_and_scrolls_sideways() {
+        use crate::output::{Column, Table};
+
+        let mut table = Table::new(vec![Column::left("NAME"), Column::left("USAGE")]);
+        table.push_row(vec![
+            "console".into(),
+            "console [size <pct> | reset]".into(),
+        ]);
+        let mut c = Console::new(100);
+        c.push_output(&CommandOutput::Multi(vec![
+            CommandOutput::Table(table),
+            CommandOutput::key_value([("tps", "10.0")]),
+            CommandOutput::Error(vec!["nope".into()]),
+            CommandOutput::prompt("Sure?", "token"),
+        ]));
+        let lines: Vec<(&str, &str)> = c
+            .log_lines()
+            .iter()
+            .map(|entry| (entry.target.as_str(), entry.message.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (TABLE_HEADER_TARGET, "  NAME     USAGE"),
+                (TABLE_TARGET, "  console  console [size <pct> | reset]"),
+                (KEY_VALUE_TARGET, "  tps  10.0"),
+                (OUTPUT_TARGET, "error: nope"),
+            ]
+        );
+        assert_eq!(c.log_lines()[3].level, LogLevel::Error);
+
+        c.scroll_right(30);
+        assert_eq!(c.h_scroll(), 30);
+        c.scroll_right(100);
+        assert_eq!(c.h_scroll(), 38);
+        c.scroll_left(8);
+        assert_eq!(c.h_scroll(), 30);
+        c.clear_logs();
+        assert_eq!(c.h_scroll(), 0);
+    }
+
+    fn type_str(c: &mut Console, text: &str) {
+        for ch in text.chars() {
+            c.insert_char(ch);
+        }
+    }
+
+    #[test]
+    fn hint_completes_command_name_with_usage() {
+        let registry = crate::command::builtin_registry();
+        let mut c = Console::default();
+        type_str(&mut c, "swi");
+        c.refresh_hint(&registry, &[]);
+        assert_eq!(
+            c.hint(),
+            Some(&InputHint::Suggest {
+                completion: "tch".into(),
+                usage: Some("switch <module_id>".into()),
+            })
+        );
+        c.cursor_right();
+        assert_eq!(c.input_buffer, "switch");
+        assert_eq!(c.cursor_pos, 6);
+    }
+
+    #[test]
+    fn hint_ranks_names_by_usage() {
+        let registry = crate::command::builtin_registry();
+        let extra = [("zzap", "zzap"), ("zzbuild", "zzbuild")];
+        let completion = |c: &mut Console| {
+            c.refresh_hint(&registry, &extra);
+            match c.hint() {
+                Some(InputHint::Suggest { completion, .. }) => completion.clone(),
+                other => panic!("expected a suggestion, got {other:?}"),
+            }
+        };
+        let mut c = Console::default();
+        type_str(&mut c, "zz");
+        // Unused names: the shortest first
+        assert_eq!(completion(&mut c), "ap");
+        let now = std::time::SystemTime::now();
+        c.usage_mut().record("zzbuild", now);
+        assert_eq!(completion(&mut c), "build");
+        c.usage_mut().record("zzap", now);
+        assert_eq!(completion(&mut c), "ap");
+    }
+
+    #[test]
+    fn hint_prefers_history_and_flags_unknown() {
+        let registry = crate::command::builtin_registry();
+        let mut c = Console::default();
+        type_str(&mut c, "echo hi there");
+        c.submit_input();
+        type_str(&mut c, "ec");
+        c.refresh_hint(&registry, &[]);
+        assert!(matches!(
+            c.hint(),
+            Some(InputHint::Suggest { completion, .. }) if completion == "ho hi there"
+        ));
+
+        c.submit_input();
+        type_str(&mut c, "zzz");
+        c.refresh_hint(&registry, &[("plugins", "plugins list")]);
+        assert_eq!(c.hint(), Some(&InputHint::Unknown));
+        assert!(!c.accept_suggestion());
+
+        c.submit_input();
+        type_str(&mut c, "pl");
+        c.refresh_hint(&registry, &[("plugins", "plugins list")]);
+        assert!(matches!(
+            c.hint(),
+            Some(InputHint::Suggest { usage: Some(usage), .. }) if usage == "plugins list"
+        ));
+    }
+
+    #[test]
+    fn prompt_answers_skip_hints_and_history() {
+        let registry = crate::command::builtin_registry();
+        let mut c = Console::default();
+        c.set_prompt(ConsolePrompt {
+            question: "Name?".into(),
+            callback_token: "name".into(),
+            origin: PromptOrigin::Module("hello".into()),
+        });
+        type_str(&mut c, "he");
+        c.refresh_hint(&registry, &[]);
+        assert_eq!(c.hint(), None);
+        assert_eq!(c.snapshot().prompt.as_deref(), Some("Name?"));
+        assert_eq!(c.submit_input(), "he");
+        assert!(c.history().is_empty());
+
+        let prompt = c.take_prompt().unwrap();
+        assert_eq!(prompt.origin, PromptOrigin::Module("hello".into()));
+        assert_eq!(c.prompt(), None);
+        type_str(&mut c, "he");
+        c.submit_input();
+        assert_eq!(c.history().len(), 1);
+    }
+
+    #[test]
+    fn history_skips_blank_and_repeated_input() {
+        let mut c = Console::default();
+        type_str(&mut c, "tps");
+        c.submit_input();
+        type_str(&mut c, "tps");
+        c.submit_input();
+        type_str(&mut c, "  ");
+        c.submit_input();
+        assert_eq!(c.history().len(), 1);
+    }
+
+    #[test]
+    fn reverse_search_narrows_and_cycles_older_matches() {
+        let mut c = Console::default();
+        c.restore_history(
+            ["echo one", "tps", "echo two", "stats export", "echo three"].map(String::from),
+        );
+        type_str(&mut c, "draft");
+        c.search_older();
+        assert_eq!(c.search().unwrap().query(), "");
+        assert_eq!(c.search_match(), None);
+
+        c.search_insert('e');
+        c.search_insert('c');
+        assert_eq!(c.search_match(), Some(("echo three", 0)));
+        c.search_older();
+        assert_eq!(c.search_match(), Some(("echo two", 0)));
+        c.search_older();
+        assert_eq!(c.search_match(), Some(("echo one", 0)));
+        // Nothing older: the match stays and the search shows as failed
+        c.search_older();
+        assert_eq!(c.search_match(), Some(("echo one", 0)));
+        assert!(c.search().unwrap().failed());
+
+        // Typing starts again from the newest command
+        c.search_backspace();
+        c.search_backspace();
+        "port".chars().for_each(|ch| c.search_insert(ch));
+        assert_eq!(c.search_match(), Some(("stats export", 8)));
+        c.search_insert('x');
+        assert!(c.search().unwrap().failed());
+        assert_eq!(c.search_match(), Some(("stats export", 0)));
+
+        c.cancel_search();
+        assert!(c.search().is_none());
+        assert_eq!(c.input_buffer, "draft");
+        assert_eq!(c.cursor_pos, 5);
+    }
+
+    #[test]
+    fn accepted_search_fills_the_input() {
+        let mut c = Console::default();
+        c.restore_history(["echo one".to_string(), "tps".to_string()]);
+        c.search_older();
+        c.search_insert('o');
+        c.accept_search();
+        assert!(c.search().is_none());
+        assert_eq!(c.input_buffer, "echo one");
+        assert_eq!(c.cursor_pos, c.input_buffer.len());
+        assert_eq!(c.submit_input(), "echo one");
+
+        // Without a match the input is kept
+        type_str(&mut c, "x");
+        c.search_older();
+        c.search_insert('z');
+        c.accept_search();
+        assert_eq!(c.input_buffer, "x");
+    }
+
+    #[test]
+    fn restores_history_open_state_and_scroll() {
+        let mut c = Console::default();
+        c.restore_history((0..HISTORY_CAPACITY + 5).map(|i| format!("echo {i}")));
+        assert_eq!(c.history().len(), HISTORY_CAPACITY);
+        assert_eq!(c.history().front().map(String::as_str), Some("echo 5"));
+
+        c.set_open(true);
+        assert!(c.is_open());
+        for i in 0..5 {
+            c.push_log(entry(&format!("line {i}")));
+        }
+        c.set_scroll_offset(100);
+        assert_eq!(c.scroll_offset(), 4);
+    }
+
+    #[test]
+    fn ingest_coalesces_repeats_within_window() {
+        let mut c = Console::new(10);
+        let t0 = Instant::now();
+        c.ingest_log(entry("disk full"), t0);
+        c.ingest_log(entry("disk full"), t0 + Duration::from_millis(500));
+        c.ingest_log(entry("disk full"), t0 + Duration::from_millis(900));
+        assert_eq!(c.log_lines().len(), 1);
+        assert_eq!(c.log_lines()[0].count, 3);
+
+        c.ingest_log(entry("disk full"), t0 + Duration::from_secs(5));
+        assert_eq!(c.log_lines().len(), 2);
+        assert_eq!(c.log_lines()[1].count, 1);
+    }
+
+    #[test]
+    fn ingest_rate_limits_per_target_and_reports_suppressed() {
+        let mut c = Console::new(1000);
+        let t0 = Instant::now();
+        for i in 0..TARGET_RATE_LIMIT + 5 {
+            c.ingest_log(entry(&format!("spam {i}")), t0);
+        }
+        let mut other = entry("still heard");
+        other.target = "other".into();
+        c.ingest_log(other, t0);
+        assert_eq!(c.log_lines().len(), TARGET_RATE_LIMIT + 1);
+
+        c.update(t0 + RATE_WINDOW);
+        let last = c.log_lines().back().unwrap();
+        assert_eq!(last.level, LogLevel::Warn);
+        assert!(last.message.contains("suppressed 5 log line(s) from test"));
+
+        c.ingest_log(entry("after"), t0 + RATE_WINDOW);
+        assert_eq!(c.log_lines().back().unwrap().message, "after");
+    }
+
+    /// One input-line edit, for the generative tests below.
+    #[derive(Debug, Clone)]
+    enum Edit {
+        Insert(char),
+        Backspace,
+        Left,
+        Right,
+        Submit,
+        RefreshHint,
+    }
+
+    fn edits(with_hints: bool) -> impl proptest::strategy::Strategy<Value = Vec<Edit>> {
+        use proptest::prelude::*;
+
+        let edit = prop_oneof![
+            4 => any::<char>().prop_map(Edit::Insert),
+            2 => "[a-z ]".prop_map(|s| Edit::Insert(s.chars().next().unwrap())),
+            2 => Just(Edit::Backspace),
+            2 => Just(Edit::Left),
+            2 => Just(Edit::Right),
+            1 => Just(Edit::Submit),
+            1 => Just(Edit::RefreshHint),
+        ]
+        .prop_filter("hints only when asked", move |edit| {
+            with_hints || !matches!(edit, Edit::RefreshHint)
+        });
+        proptest::collection::vec(edit, 0..64)
+    }
+
+    fn apply(console: &mut Console, edit: &Edit, registry: &CommandRegistry) -> Option<String> {
+        match edit {
+            Edit::Insert(c) => console.insert_char(*c),
+            Edit::Backspace => console.backspace(),
+            Edit::Left => console.cursor_left(),
+            Edit::Right => console.cursor_right(),
+            Edit::Submit => return Some(console.submit_input()),
+            Edit::RefreshHint => console.refresh_hint(registry, &[]),
+        }
+        None
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn edits_match_a_text_model(edits in edits(false)) {
+            let registry = CommandRegistry::new();
+            let mut console = Console::default();
+            let mut model = String::new();
+            let mut cursor = 0;
+            // Left, Right and Backspace step over whole grapheme clusters
+            let before = |model: &str, cursor: usize| {
+                model[..cursor].grapheme_indices(true).next_back().map_or(0, |(i, _)| i)
+            };
+            for edit in &edits {
+                let submitted = apply(&mut console, edit, &registry);
+                match edit {
+                    Edit::Insert(c) => {
+                        model.insert(cursor, *c);
+                        cursor += c.len_utf8();
+                    }
+                    Edit::Backspace => {
+                        let start = before(&model, cursor);
+                        model.replace_range(start..cursor, "");
+                        cursor = start;
+                    }
+                    Edit::Left => cursor = before(&model, cursor),
+                    Edit::Right => {
+                        cursor += model[cursor..].graphemes(true).next().map_or(0, str::len);
+                    }
+                    Edit::Submit => {
+                        proptest::prop_assert_eq!(submitted, Some(model.clone()));
+                        model.clear();
+                        cursor = 0;
+                    }
+                    _ => {}
+                }
+                proptest::prop_assert_eq!(&console.input_buffer, &model);
+                proptest::prop_assert_eq!(console.cursor_pos, cursor);
+            }
+        }
+
+        #[test]
+        fn cursor_stays_on_a_char_boundary(edits in edits(true)) {
+            let registry = crate::command::builtin_registry();
+            let mut console = Console::default();
+            console.restore_history(["help".to_string(), "echo hello".to_string()]);
+            for edit in &edits {
+                apply(&mut console, edit, &registry);
+                proptest::prop_assert!(console.cursor_pos <= console.input_buffer.len());
+                proptest::prop_assert!(console.input_buffer.is_char_boundary(console.cursor_pos));
+            }
+        }
+    }
 }
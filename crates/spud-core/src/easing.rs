@@ -0,0 +1,73 @@
+//! Easing curves for UI animations.
+//!
+//! A curve maps linear progress through an animation (`0.0` at the start,
+//! `1.0` at the end) to how far the animated value has moved. Every curve
+//! starts at 0, ends at 1, and only rises, so [`progress_at`] can invert it
+//! to resume an animation from a position reached on a different curve or
+//! direction.
+
+pub use spud_config::Easing;
+
+/// Position reached after `progress` of an animation following `easing`.
+/// `progress` is clamped to `0.0..=1.0`.
+pub fn ease(easing: Easing, progress: f64) -> f64 {
+    let t = progress.clamp(0.0, 1.0);
+    match easing {
+        Easing::Linear => t,
+        Easing::EaseOutQuad => 1.0 - (1.0 - t).powi(2),
+        Easing::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+        Easing::EaseInOutCubic => {
+            if t < 0.5 {
+                4.0 * t.powi(3)
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            }
+        }
+    }
+}
+
+/// Progress at which `easing` reaches `position`, the inverse of [`ease`].
+/// `position` is clamped to `0.0..=1.0`.
+pub fn progress_at(easing: Easing, position: f64) -> f64 {
+    let y = position.clamp(0.0, 1.0);
+    match easing {
+        Easing::Linear => y,
+        Easing::EaseOutQuad => 1.0 - (1.0 - y).sqrt(),
+        Easing::EaseOutCubic => 1.0 - (1.0 - y).cbrt(),
+        Easing::EaseInOutCubic => {
+            if y < 0.5 {
+                (y / 4.0).cbrt()
+            } else {
+                1.0 - (2.0 * (1.0 - y)).cbrt() / 2.0
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_run_from_zero_to_one_and_invert() {
+        for easing in Easing::ALL {
+            assert_eq!(easing.name().parse::<Easing>().unwrap(), easing);
+            assert_eq!(ease(easing, 0.0), 0.0);
+            assert!((ease(easing, 1.0) - 1.0).abs() < 1e-12);
+            let mut last = 0.0;
+            for step in 1..=100 {
+                let progress = f64::from(step) / 100.0;
+                let position = ease(easing, progress);
+                assert!(position >= last, "{easing} falls at {progress}");
+                assert!(
+                    (progress_at(easing, position) - progress).abs() < 1e-9,
+                    "{easing} does not invert at {progress}"
+                );
+                last = position;
+            }
+        }
+        assert_eq!(ease(Easing::EaseOutCubic, 2.0), 1.0);
+        assert!(ease(Easing::EaseOutCubic, 0.5) > ease(Easing::Linear, 0.5));
+        assert!(ease(Easing::EaseInOutCubic, 0.25) < ease(Easing::Linear, 0.25));
+    }
+}
@@ -0,0 +1,34 @@
+//! Errors returned by spud-core's registry, command, and schema APIs.
+//!
+//! Library callers (the plugin host, tests, embedders) match on
+//! [`CoreError`] variants; the app converts them into `anyhow` errors at
+//! its boundary with `?`.
+
+use thiserror::Error;
+
+/// A failed spud-core operation.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum CoreError {
+    /// A module with this id is already registered.
+    #[error("duplicate module id: {0}")]
+    DuplicateModule(String),
+    /// No module with this id is registered.
+    #[error("unknown module id: {0}")]
+    UnknownModule(String),
+    /// No console command has this name or alias.
+    #[error("unknown command: '{0}'. Type 'help' for available commands.")]
+    UnknownCommand(String),
+    /// Event schemas need a non-empty tag.
+    #[error("event tag must not be empty")]
+    EmptyEventTag,
+    /// Another module or plugin already registered a schema for this tag.
+    #[error("event tag {tag:?} already has a schema registered by {owner}")]
+    EventTagTaken { tag: String, owner: String },
+    /// The schema uses unsupported keywords or malformed values.
+    #[error("invalid event schema: {0}")]
+    InvalidSchema(String),
+}
+
+/// `Result` with [`CoreError`].
+pub type CoreResult<T> = std::result::Result<T, CoreError>;
@@ -1,7 +1,13 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-/// A typed value attached to a [`Event::Telemetry`] event.
-#[derive(Debug, Clone)]
+use serde::Serialize;
+use spud_config::Profile;
+
+/// A typed value attached to a [`Event::Telemetry`] event. Serializes as a
+/// bare JSON number, string, or boolean; durations as `{"duration_ms": n}`
+/// and histograms as `{"bounds", "counts", "sum"}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
 pub enum TelemetryValue {
     /// A floating-point metric (e.g. CPU percentage).
     Float(f64),
@@ -9,6 +15,207 @@ pub enum TelemetryValue {
     Int(i64),
     /// A textual metric (e.g. version string).
     Text(String),
+    /// A flag (e.g. whether on AC power).
+    Bool(bool),
+    /// A duration in milliseconds (e.g. request latency).
+    #[serde(serialize_with = "serialize_duration_ms")]
+    DurationMs(f64),
+    /// A distribution of observations (e.g. frame times).
+    Histogram(Histogram),
+}
+
+impl TelemetryValue {
+    /// Numeric value as `f64`, or `None` for text and empty histograms.
+    /// Flags read as `0`/`1`, durations as milliseconds, and histograms
+    /// as their mean.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float(value) | Self::DurationMs(value) => Some(*value),
+            Self::Int(value) => Some(*value as f64),
+            Self::Bool(value) => Some(f64::from(u8::from(*value))),
+            Self::Histogram(histogram) => histogram.mean(),
+            Self::Text(_) => None,
+        }
+    }
+
+    /// A duration metric, in fractional milliseconds.
+    pub fn duration(duration: Duration) -> Self {
+        Self::DurationMs(duration.as_secs_f64() * 1000.0)
+    }
+}
+
+fn serialize_duration_ms<S: serde::Serializer>(ms: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeMap;
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry("duration_ms", ms)?;
+    map.end()
+}
+
+/// Observation counts in buckets with fixed upper bounds, for
+/// [`TelemetryValue::Histogram`]. Bucket `i` counts values up to
+/// `bounds[i]`; the extra last bucket counts values above every bound.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    /// An empty histogram over `bounds`. Bounds are sorted, and repeated or
+    /// non-finite ones dropped.
+    pub fn new(bounds: impl Into<Vec<f64>>) -> Self {
+        let mut bounds: Vec<f64> = bounds.into();
+        bounds.retain(|bound| bound.is_finite());
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        let counts = vec![0; bounds.len() + 1];
+        Self {
+            bounds,
+            counts,
+            sum: 0.0,
+        }
+    }
+
+    /// Count `value` in its bucket. Non-finite values are ignored.
+    pub fn observe(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let bucket = self.bounds.partition_point(|bound| *bound < value);
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    /// Bucket upper bounds, ascending.
+    pub fn bounds(&self) -> &[f64] {
+        &self.bounds
+    }
+
+    /// Observations per bucket; one longer than [`bounds`](Self::bounds).
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Number of observations.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Sum of the observations.
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// Mean observation, or `None` when empty.
+    pub fn mean(&self) -> Option<f64> {
+        let count = self.count();
+        (count > 0).then(|| self.sum / count as f64)
+    }
+}
+
+/// Why a module became active. See [`Event::ModuleActivated`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivationReason {
+    /// Tab / cycling through modules in order.
+    Cycle,
+    /// Activated by ID, e.g. the `switch` command or `--module`.
+    Switch,
+    /// Returned to with `back` / Backspace.
+    Back,
+    /// A plugin asked for it.
+    Plugin,
+    /// The active module was removed or replaced and this one took its
+    /// place.
+    Fallback,
+}
+
+impl ActivationReason {
+    /// Lowercase name, e.g. `"cycle"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Cycle => "cycle",
+            Self::Switch => "switch",
+            Self::Back => "back",
+            Self::Plugin => "plugin",
+            Self::Fallback => "fallback",
+        }
+    }
+}
+
+/// How urgently a background module wants to be looked at. See
+/// [`Event::AttentionRequested`]. Ordered from least to most urgent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttentionLevel {
+    /// Something new, nothing wrong.
+    Info,
+    /// Worth a look soon.
+    Warn,
+    /// Needs the user now.
+    Alert,
+}
+
+impl AttentionLevel {
+    /// Lowercase name, e.g. `"warn"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Alert => "alert",
+        }
+    }
+}
+
+/// A POSIX signal the app listens for. See [`Event::Signal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    /// `SIGHUP`; restarts plugins by default.
+    Hup,
+    /// `SIGUSR1`; reloads `spud.toml` by default.
+    Usr1,
+    /// `SIGUSR2`; toggles the console by default.
+    Usr2,
+}
+
+impl Signal {
+    /// Every signal the app listens for.
+    pub const ALL: [Signal; 3] = [Signal::Hup, Signal::Usr1, Signal::Usr2];
+
+    /// Conventional name, e.g. `"SIGUSR1"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hup => "SIGHUP",
+            Self::Usr1 => "SIGUSR1",
+            Self::Usr2 => "SIGUSR2",
+        }
+    }
+}
+
+/// Where a plugin is in starting up or running. See
+/// [`Event::PluginStatus`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginStatus {
+    /// The process was spawned and its handshake is pending.
+    Starting,
+    /// The handshake completed.
+    Running,
+    /// The plugin could not be started, for this reason.
+    Failed(String),
+    /// The process exited.
+    Exited,
+}
+
+impl PluginStatus {
+    /// Lowercase name, e.g. `"running"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::Running => "running",
+            Self::Failed(_) => "failed",
+            Self::Exited => "exited",
+        }
+    }
 }
 
 /// Events flowing through the SPUD event bus.
@@ -26,15 +233,120 @@ pub enum Event {
     /// Request to shut down the application.
     Quit,
     /// A module has become the active (foreground) module.
-    ModuleActivated { id: String },
+    ModuleActivated {
+        id: String,
+        reason: ActivationReason,
+    },
     /// A module has been moved to the background.
     ModuleDeactivated { id: String },
+    /// A background module wants the user's attention without taking
+    /// focus. Modules publish it from [`Module::drain_events`] with their
+    /// own ID; the registry counts it as a badge on the module's tab until
+    /// the module is activated. Ignored for the active module. Not
+    /// delivered to modules.
+    ///
+    /// [`Module::drain_events`]: crate::module::Module::drain_events
+    AttentionRequested { id: String, level: AttentionLevel },
     /// A telemetry data point emitted by a module or subsystem.
     Telemetry {
         source: String,
         key: String,
         value: TelemetryValue,
     },
+    /// How long a named operation took. See [`crate::spans`].
+    Span { name: String, duration: Duration },
+    /// `spud.toml` was reloaded; `changed` names the settings that differ
+    /// (e.g. `"hud.height"`). Sent to all modules.
+    ConfigReloaded { changed: Vec<String> },
+    /// The runtime profile changed, or was set at startup. Modules with
+    /// their own timers should follow [`Profile::tuning`]. Sent to all
+    /// modules.
+    ProfileChanged { profile: Profile },
+    /// The process received a signal, after the app ran its default action
+    /// for it. Sent to all modules.
+    Signal { signal: Signal },
+    /// A plugin process changed state. Plugins are discovered and started
+    /// in the background, so this is how to learn they are up. Sent to all
+    /// modules.
+    PluginStatus {
+        plugin_id: String,
+        status: PluginStatus,
+    },
     /// An application-defined event for extension points.
     Custom { tag: String, payload: String },
 }
+
+impl Event {
+    /// Every [`kind`](Self::kind), in declaration order.
+    pub const KINDS: [&'static str; 14] = [
+        "tick",
+        "key",
+        "resize",
+        "quit",
+        "module_activated",
+        "module_deactivated",
+        "attention_requested",
+        "telemetry",
+        "span",
+        "config_reloaded",
+        "profile_changed",
+        "signal",
+        "plugin_status",
+        "custom",
+    ];
+
+    /// Short snake_case name of the variant, e.g. `"telemetry"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Tick { .. } => "tick",
+            Self::Key(_) => "key",
+            Self::Resize { .. } => "resize",
+            Self::Quit => "quit",
+            Self::ModuleActivated { .. } => "module_activated",
+            Self::ModuleDeactivated { .. } => "module_deactivated",
+            Self::AttentionRequested { .. } => "attention_requested",
+            Self::Telemetry { .. } => "telemetry",
+            Self::Span { .. } => "span",
+            Self::ConfigReloaded { .. } => "config_reloaded",
+            Self::ProfileChanged { .. } => "profile_changed",
+            Self::Signal { .. } => "signal",
+            Self::PluginStatus { .. } => "plugin_status",
+            Self::Custom { .. } => "custom",
+        }
+    }
+
+    /// One-line summary of the payload for debug views.
+    pub fn preview(&self) -> String {
+        match self {
+            Self::Tick { .. } | Self::Quit => String::new(),
+            Self::Key(key) => format!("{:?} {:?}", key.code, key.modifiers),
+            Self::Resize { cols, rows } => format!("{cols}x{rows}"),
+            Self::ModuleActivated { id, reason } => format!("{id} ({})", reason.name()),
+            Self::ModuleDeactivated { id } => id.clone(),
+            Self::AttentionRequested { id, level } => format!("{id} ({})", level.name()),
+            Self::Telemetry { source, key, value } => {
+                let value = match value {
+                    TelemetryValue::Float(v) => format!("{v:.3}"),
+                    TelemetryValue::Int(v) => v.to_string(),
+                    TelemetryValue::Text(v) => format!("{v:?}"),
+                    TelemetryValue::Bool(v) => v.to_string(),
+                    TelemetryValue::DurationMs(v) => format!("{v:.3}ms"),
+                    TelemetryValue::Histogram(h) => match h.mean() {
+                        Some(mean) => format!("{} samples, mean {mean:.3}", h.count()),
+                        None => "0 samples".to_string(),
+                    },
+                };
+                format!("{source}.{key} = {value}")
+            }
+            Self::Span { name, duration } => format!("{name} {duration:?}"),
+            Self::ConfigReloaded { changed } => changed.join(", "),
+            Self::ProfileChanged { profile } => profile.to_string(),
+            Self::Signal { signal } => signal.name().to_string(),
+            Self::PluginStatus { plugin_id, status } => match status {
+                PluginStatus::Failed(reason) => format!("{plugin_id} (failed: {reason})"),
+                status => format!("{plugin_id} ({})", status.name()),
+            },
+            Self::Custom { tag, payload } => format!("{tag} {payload}"),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
+use serde::Serialize;
+
 /// Measures ticks-per-second over a sliding time window.
 ///
 /// Call [`tick`](TickCounter::tick) once per frame/tick, then
@@ -9,6 +11,16 @@ use std::time::{Duration, Instant};
 pub struct TickCounter {
     timestamps: VecDeque<Instant>,
     window: Duration,
+    total: u64,
+}
+
+/// Serializable view of a [`TickCounter`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TickSnapshot {
+    /// Ticks since the counter was created.
+    pub total: u64,
+    pub tps: f64,
+    pub window_ms: u64,
 }
 
 impl Default for TickCounter {
@@ -23,37 +35,53 @@ impl TickCounter {
         Self {
             timestamps: VecDeque::new(),
             window,
+            total: 0,
         }
     }
 
     /// Record a tick at the given instant and prune expired timestamps.
     pub fn tick(&mut self, now: Instant) {
         self.timestamps.push_back(now);
+        self.total += 1;
         self.prune(now);
     }
 
+    /// Serializable view for state dumps.
+    pub fn snapshot(&self) -> TickSnapshot {
+        TickSnapshot {
+            total: self.total,
+            tps: self.tps(),
+            window_ms: self.window.as_millis() as u64,
+        }
+    }
+
     /// Return the current ticks-per-second based on timestamps in the window.
     ///
-    /// Returns `0.0` if fewer than two ticks have been recorded.
+    /// Returns `0.0` if fewer than two ticks have been recorded or the
+    /// window is empty, so the result is always finite and non-negative.
     pub fn tps(&self) -> f64 {
-        if self.timestamps.len() < 2 {
+        if self.timestamps.len() < 2 || self.window.is_zero() {
             return 0.0;
         }
         let now = self.timestamps.back().copied().unwrap();
-        let window_start = now - self.window;
         let count = self
             .timestamps
             .iter()
-            .filter(|&&t| t >= window_start)
+            .filter(|&&t| {
+                now.checked_duration_since(t)
+                    .is_none_or(|age| age <= self.window)
+            })
             .count();
         count as f64 / self.window.as_secs_f64()
     }
 
     /// Remove timestamps older than `now - window`.
     fn prune(&mut self, now: Instant) {
-        let cutoff = now - self.window;
         while let Some(&front) = self.timestamps.front() {
-            if front < cutoff {
+            if now
+                .checked_duration_since(front)
+                .is_some_and(|age| age > self.window)
+            {
                 self.timestamps.pop_front();
             } else {
                 break;
@@ -109,4 +137,33 @@ mod tests {
             counter.timestamps.len()
         );
     }
+
+    #[test]
+    fn total_counts_every_tick() {
+        let mut counter = TickCounter::default();
+        let start = Instant::now();
+        for i in 0..5 {
+            counter.tick(start + Duration::from_secs(i));
+        }
+        let snapshot = counter.snapshot();
+        assert_eq!(snapshot.total, 5);
+        assert_eq!(snapshot.window_ms, 1_000);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn tps_is_finite_and_non_negative(
+            window_ms in 0u64..2_000,
+            offsets_ms in proptest::collection::vec(0u64..5_000, 0..64),
+        ) {
+            let mut counter = TickCounter::new(Duration::from_millis(window_ms));
+            let base = Instant::now();
+            for (ticks, offset) in offsets_ms.iter().enumerate() {
+                counter.tick(base + Duration::from_millis(*offset));
+                let tps = counter.tps();
+                proptest::prop_assert!(tps.is_finite() && tps >= 0.0, "tps was {}", tps);
+                proptest::prop_assert_eq!(counter.snapshot().total, ticks as u64 + 1);
+            }
+        }
+    }
 }
@@ -0,0 +1,145 @@
+//! Checks a command against `[commands]` in `spud.toml` before it runs.
+//!
+//! Destructive commands (those whose [`Command::destructive`] or
+//! [`Module::console_destructive`] says so) may need a confirmation or be
+//! refused, and commands listed in `disabled` are refused outright.
+//!
+//! [`Command::destructive`]: crate::command::Command::destructive
+//! [`Module::console_destructive`]: crate::module::Module::console_destructive
+
+use spud_config::{CommandsConfig, DestructivePolicy};
+
+use crate::command::CommandRegistry;
+
+/// Whether a command may run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Gate {
+    Run,
+    /// Ask first, with this question.
+    Confirm(String),
+    /// Refuse, for this reason.
+    Refuse(String),
+}
+
+/// Check `words` (command name, then arguments). Aliases match their
+/// command's entries in `disabled`.
+///
+/// `interactive` is whether someone at the console can answer. Commands
+/// from elsewhere (alert rules, plugins, module keys) skip the
+/// confirmation, since whoever set them up already chose to run them, but
+/// are still refused by `deny` and `disabled`.
+pub fn check(
+    config: &CommandsConfig,
+    registry: &CommandRegistry,
+    words: &[&str],
+    destructive: bool,
+    interactive: bool,
+) -> Gate {
+    let Some((&name, args)) = words.split_first() else {
+        return Gate::Run;
+    };
+    let canonical = |name: &str| {
+        registry
+            .get(name)
+            .map_or_else(|| name.to_string(), |command| command.name().to_string())
+    };
+    let name = canonical(name);
+    let disabled = config.disabled.iter().any(|entry| {
+        let mut entry = entry.split_whitespace();
+        entry.next().is_some_and(|first| canonical(first) == name)
+            && entry
+                .enumerate()
+                .all(|(idx, word)| args.get(idx) == Some(&word))
+    });
+    let line = words.join(" ");
+    if disabled {
+        return Gate::Refuse(format!("'{line}' is disabled in spud.toml"));
+    }
+    if !destructive {
+        return Gate::Run;
+    }
+    match config.destructive {
+        DestructivePolicy::Allow => Gate::Run,
+        DestructivePolicy::Deny => Gate::Refuse(format!(
+            "'{line}' is destructive and spud.toml denies destructive commands"
+        )),
+        DestructivePolicy::Confirm if interactive => {
+            Gate::Confirm(format!("Really run '{line}'? [y/N]"))
+        }
+        DestructivePolicy::Confirm => Gate::Run,
+    }
+}
+
+/// Whether `answer` to a [`Gate::Confirm`] question means yes.
+pub fn confirmed(answer: &str) -> bool {
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::builtin_registry;
+
+    fn config(destructive: DestructivePolicy, disabled: &[&str]) -> CommandsConfig {
+        CommandsConfig {
+            destructive,
+            disabled: disabled.iter().map(|entry| entry.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn destructive_commands_follow_the_policy() {
+        let registry = builtin_registry();
+        let confirm = config(DestructivePolicy::Confirm, &[]);
+        assert_eq!(
+            check(&confirm, &registry, &["exit"], true, true),
+            Gate::Confirm("Really run 'exit'? [y/N]".into())
+        );
+        assert_eq!(
+            check(&confirm, &registry, &["exit"], true, false),
+            Gate::Run
+        );
+        assert_eq!(check(&confirm, &registry, &["tps"], false, true), Gate::Run);
+
+        let allow = config(DestructivePolicy::Allow, &[]);
+        assert_eq!(check(&allow, &registry, &["quit"], true, true), Gate::Run);
+
+        let deny = config(DestructivePolicy::Deny, &[]);
+        assert!(matches!(
+            check(&deny, &registry, &["quit"], true, false),
+            Gate::Refuse(_)
+        ));
+
+        assert!(confirmed(" Yes "));
+        assert!(!confirmed(""));
+        assert!(!confirmed("no"));
+    }
+
+    #[test]
+    fn disabled_entries_match_aliases_and_leading_arguments() {
+        let registry = builtin_registry();
+        let kiosk = config(DestructivePolicy::Allow, &["q", "plugins stop"]);
+        assert_eq!(
+            check(&kiosk, &registry, &["exit"], true, true),
+            Gate::Refuse("'exit' is disabled in spud.toml".into())
+        );
+        assert!(matches!(
+            check(&kiosk, &registry, &["plugins", "stop", "demo"], false, true),
+            Gate::Refuse(_)
+        ));
+        assert_eq!(
+            check(
+                &kiosk,
+                &registry,
+                &["plugins", "start", "demo"],
+                false,
+                true
+            ),
+            Gate::Run
+        );
+        assert_eq!(
+            check(&kiosk, &registry, &["plugins"], false, true),
+            Gate::Run
+        );
+    }
+}
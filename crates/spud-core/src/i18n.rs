@@ -0,0 +1,220 @@
+//! Translated UI strings.
+//!
+//! Strings are looked up by their English text, gettext-style: call sites
+//! write `tr("toggle this help")` and get the active catalog's translation,
+//! or the English text itself when there is none. English therefore needs
+//! no catalog and an incomplete translation degrades string by string.
+//!
+//! Catalogs are TOML files of `"English" = "translation"` pairs in
+//! `<config_dir>/locales/`, named after the locale (`de.toml`,
+//! `pt-BR.toml`). Placeholders are written `{name}` and filled by
+//! [`tr_args`]. The catalog is chosen once at startup by [`install`]; a
+//! locale change in `spud.toml` applies on the next run.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+
+/// Locale used when nothing else is configured; needs no catalog.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Subdirectory of the config dir holding catalogs.
+pub const LOCALES_DIR: &str = "locales";
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Translations for one locale.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catalog {
+    locale: String,
+    entries: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parse a catalog of `"English" = "translation"` pairs.
+    pub fn from_toml_str(locale: &str, input: &str) -> Result<Self> {
+        let entries: HashMap<String, String> =
+            toml::from_str(input).with_context(|| format!("invalid {locale} catalog"))?;
+        Ok(Self {
+            locale: locale.to_string(),
+            entries,
+        })
+    }
+
+    /// Load the catalog for `locale` from `dir`, trying the full tag
+    /// (`pt-BR.toml`) before the language (`pt.toml`). English and locales
+    /// without a catalog yield `Ok(None)`.
+    pub fn load(dir: &Path, locale: &str) -> Result<Option<Self>> {
+        for candidate in candidates(locale) {
+            if candidate == DEFAULT_LOCALE {
+                return Ok(None);
+            }
+            let path = dir.join(format!("{candidate}.toml"));
+            if !path.exists() {
+                continue;
+            }
+            let raw = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            return Self::from_toml_str(&candidate, &raw)
+                .with_context(|| format!("invalid {}", path.display()))
+                .map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Locale tag this catalog translates to.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Number of translated strings.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return `true` if the catalog translates nothing.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Translation of `text`, if the catalog has one.
+    pub fn get(&self, text: &str) -> Option<&str> {
+        self.entries.get(text).map(String::as_str)
+    }
+}
+
+/// Make `catalog` the active one. Only the first call takes effect;
+/// returns `false` if a catalog was already installed.
+pub fn install(catalog: Catalog) -> bool {
+    CATALOG.set(catalog).is_ok()
+}
+
+/// Locale of the active catalog.
+pub fn locale() -> &'static str {
+    CATALOG.get().map_or(DEFAULT_LOCALE, Catalog::locale)
+}
+
+/// Translate `text`, falling back to `text` itself.
+pub fn tr(text: &'static str) -> &'static str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.get(text))
+        .unwrap_or(text)
+}
+
+/// Translate `text` and replace each `{name}` placeholder with its value.
+pub fn tr_args(text: &'static str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    fill(tr(text), args)
+}
+
+fn fill(template: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    args.iter()
+        .fold(template.to_string(), |out, (name, value)| {
+            out.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// Pick the locale: `configured` (from `spud.toml`) wins, then `LC_ALL`,
+/// `LC_MESSAGES`, and `LANG`. `C`/`POSIX` and unset mean English.
+pub fn detect_locale(configured: Option<&str>) -> String {
+    let env = |key: &str| std::env::var(key).ok();
+    resolve_locale(configured, &env)
+}
+
+fn resolve_locale(configured: Option<&str>, env: &dyn Fn(&str) -> Option<String>) -> String {
+    configured
+        .map(str::to_string)
+        .into_iter()
+        .chain(
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .into_iter()
+                .filter_map(env),
+        )
+        .find(|value| !value.is_empty())
+        .and_then(|value| normalize(&value))
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// `de_DE.UTF-8@euro` → `de-DE`; `C` and `POSIX` → `None`.
+fn normalize(raw: &str) -> Option<String> {
+    let tag = raw.split(['.', '@']).next().unwrap_or_default();
+    if tag.is_empty() || tag == "C" || tag == "POSIX" {
+        return None;
+    }
+    Some(tag.replace('_', "-"))
+}
+
+/// `pt-BR` → `["pt-BR", "pt"]`.
+fn candidates(locale: &str) -> Vec<String> {
+    let mut out = vec![locale.to_string()];
+    if let Some((language, _)) = locale.split_once('-') {
+        out.push(language.to_string());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn untranslated_text_falls_back_to_english() {
+        assert_eq!(tr("quit"), "quit");
+        assert_eq!(
+            tr_args("{count} check(s) failed", &[("count", &2)]),
+            "2 check(s) failed"
+        );
+    }
+
+    #[test]
+    fn catalog_translates_and_fills_placeholders() {
+        let catalog = Catalog::from_toml_str(
+            "de",
+            r#"
+"quit" = "beenden"
+"MODULE: {title}" = "MODUL: {title}"
+"#,
+        )
+        .unwrap();
+        assert_eq!(catalog.get("quit"), Some("beenden"));
+        assert_eq!(catalog.get("help"), None);
+        assert_eq!(
+            fill(
+                catalog.get("MODULE: {title}").unwrap(),
+                &[("title", &"Stats")]
+            ),
+            "MODUL: Stats"
+        );
+        assert!(Catalog::from_toml_str("de", "quit = 1").is_err());
+    }
+
+    #[test]
+    fn locale_precedence_and_normalization() {
+        let env = |key: &str| match key {
+            "LC_ALL" => Some(String::new()),
+            "LANG" => Some("pt_BR.UTF-8".to_string()),
+            _ => None,
+        };
+        assert_eq!(resolve_locale(None, &env), "pt-BR");
+        assert_eq!(resolve_locale(Some("fr"), &env), "fr");
+        assert_eq!(resolve_locale(Some("C"), &env), DEFAULT_LOCALE);
+        assert_eq!(resolve_locale(None, &|_| None), DEFAULT_LOCALE);
+        assert_eq!(candidates("pt-BR"), vec!["pt-BR", "pt"]);
+    }
+
+    #[test]
+    fn load_prefers_full_tag_then_language() {
+        let dir = TestDir::new("i18n");
+        std::fs::write(dir.join("pt.toml"), "\"quit\" = \"sair\"\n").unwrap();
+
+        let catalog = Catalog::load(&dir, "pt-BR").unwrap().unwrap();
+        assert_eq!(catalog.locale(), "pt");
+        assert_eq!(catalog.get("quit"), Some("sair"));
+        assert!(Catalog::load(&dir, "de").unwrap().is_none());
+        assert!(Catalog::load(&dir, "en-US").unwrap().is_none());
+    }
+}
@@ -0,0 +1,115 @@
+//! Input idle tracking for attract mode.
+//!
+//! The app notes every key press. After a configured spell without one it
+//! enters attract mode, where the shell dims and the agent shows off, and
+//! optionally moves to the next module every so often. The next key leaves
+//! attract mode and does nothing else.
+
+use std::time::{Duration, Instant};
+
+/// What [`IdleTracker::update`] asks the app to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleStep {
+    /// Attract mode just started.
+    Enter,
+    /// Time to switch to the next module.
+    Cycle,
+}
+
+/// Tracks time since the last key press and whether attract mode is on.
+#[derive(Debug, Clone)]
+pub struct IdleTracker {
+    last_input: Instant,
+    /// When the current attract-mode module was shown, while attract mode
+    /// is on.
+    shown_at: Option<Instant>,
+}
+
+impl IdleTracker {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            last_input: now,
+            shown_at: None,
+        }
+    }
+
+    /// Note a key press. Returns `true` if it ended attract mode, in which
+    /// case the key should do nothing else.
+    pub fn input(&mut self, now: Instant) -> bool {
+        self.last_input = now;
+        self.shown_at.take().is_some()
+    }
+
+    /// Whether attract mode is on.
+    pub fn is_attract(&self) -> bool {
+        self.shown_at.is_some()
+    }
+
+    /// Time since the last key press.
+    pub fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_input)
+    }
+
+    /// Enter attract mode once idle for `after`, and while in it ask for
+    /// the next module every `cycle`. `None` turns either off.
+    pub fn update(
+        &mut self,
+        now: Instant,
+        after: Option<Duration>,
+        cycle: Option<Duration>,
+    ) -> Option<IdleStep> {
+        match self.shown_at {
+            None => {
+                let after = after?;
+                if self.idle_for(now) < after {
+                    return None;
+                }
+                self.shown_at = Some(now);
+                Some(IdleStep::Enter)
+            }
+            Some(shown_at) => {
+                let cycle = cycle?;
+                if now.saturating_duration_since(shown_at) < cycle {
+                    return None;
+                }
+                self.shown_at = Some(now);
+                Some(IdleStep::Cycle)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enters_after_idle_cycles_and_leaves_on_input() {
+        let start = Instant::now();
+        let secs = Duration::from_secs;
+        let mut idle = IdleTracker::new(start);
+
+        assert_eq!(idle.update(start + secs(100), None, None), None);
+        assert_eq!(idle.update(start + secs(59), Some(secs(60)), None), None);
+        assert!(!idle.input(start + secs(59)));
+        assert_eq!(idle.update(start + secs(100), Some(secs(60)), None), None);
+        assert_eq!(
+            idle.update(start + secs(119), Some(secs(60)), Some(secs(10))),
+            Some(IdleStep::Enter)
+        );
+        assert!(idle.is_attract());
+
+        let cycle = Some(secs(10));
+        assert_eq!(idle.update(start + secs(128), Some(secs(60)), cycle), None);
+        assert_eq!(
+            idle.update(start + secs(129), Some(secs(60)), cycle),
+            Some(IdleStep::Cycle)
+        );
+        assert_eq!(idle.update(start + secs(200), Some(secs(60)), None), None);
+
+        assert!(idle.input(start + secs(201)));
+        assert!(!idle.is_attract());
+        assert_eq!(idle.idle_for(start + secs(205)), secs(4));
+        assert!(!idle.input(start + secs(205)));
+    }
+}
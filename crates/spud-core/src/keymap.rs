@@ -0,0 +1,329 @@
+//! Declarative keybindings for the shell and its modules.
+//!
+//! The app declares its global keys and each module declares its own with
+//! [`Module::keybindings`](crate::module::Module::keybindings). A
+//! [`Keymap`] merges the global keys with the active module's, resolves key
+//! presses to actions, and builds the hints shown in the HUD and the F1
+//! help overlay, so those never drift from what the keys actually do.
+//! Global keys win: a module binding on the same chord is left out.
+
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::module::{HudLine, KeyHint};
+
+/// Name of an action a binding triggers, e.g. `"heatmap"`. Modules match on
+/// it in [`Module::handle_action`](crate::module::Module::handle_action).
+pub type ActionId = &'static str;
+
+/// A key with its modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// A key pressed with `modifiers`.
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// A key pressed on its own.
+    pub const fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// A character key pressed on its own.
+    pub const fn char(c: char) -> Self {
+        Self::key(KeyCode::Char(c))
+    }
+
+    /// Whether `key` is this chord. Shift is ignored for characters, since
+    /// it is already part of the character (`~` arrives as Shift+`~`).
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+        self.code == key.code && self.modifiers == modifiers
+    }
+
+    /// Whether `key` is this chord with Shift held as well, for chords that
+    /// don't bind Shift themselves: Shift+F1 still opens help.
+    pub fn matches_shifted(&self, key: &KeyEvent) -> bool {
+        !self.modifiers.contains(KeyModifiers::SHIFT)
+            && key.modifiers.contains(KeyModifiers::SHIFT)
+            && self.matches(&KeyEvent::new(
+                key.code,
+                key.modifiers - KeyModifiers::SHIFT,
+            ))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Up => f.write_str("↑"),
+            KeyCode::Down => f.write_str("↓"),
+            KeyCode::Left => f.write_str("←"),
+            KeyCode::Right => f.write_str("→"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::Delete => f.write_str("Del"),
+            other => write!(f, "{other:?}"),
+        }
+    }
+}
+
+/// A chord, the action it triggers, and what that does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub chord: KeyChord,
+    pub action: ActionId,
+    /// Short description for hints, e.g. `"toggle per-core heatmap"`.
+    pub description: String,
+}
+
+impl KeyBinding {
+    /// Bind `chord` to `action`.
+    pub fn new(chord: KeyChord, action: ActionId, description: impl Into<String>) -> Self {
+        Self {
+            chord,
+            action,
+            description: description.into(),
+        }
+    }
+}
+
+/// The action bound to `key` in `bindings`, if any. A binding for the
+/// exact chord wins over one that matches with Shift ignored.
+pub fn action_for(bindings: &[KeyBinding], key: &KeyEvent) -> Option<ActionId> {
+    exact_action(bindings, key).or_else(|| shifted_action(bindings, key))
+}
+
+fn exact_action(bindings: &[KeyBinding], key: &KeyEvent) -> Option<ActionId> {
+    bindings
+        .iter()
+        .find(|binding| binding.chord.matches(key))
+        .map(|binding| binding.action)
+}
+
+fn shifted_action(bindings: &[KeyBinding], key: &KeyEvent) -> Option<ActionId> {
+    bindings
+        .iter()
+        .find(|binding| binding.chord.matches_shifted(key))
+        .map(|binding| binding.action)
+}
+
+/// Who handles a key press, from [`Keymap::lookup`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyTarget {
+    /// One of the app's global actions.
+    Global(ActionId),
+    /// An action of the active module.
+    Module(ActionId),
+}
+
+/// The app's global bindings merged with the active module's.
+#[derive(Debug, Clone, Default)]
+pub struct Keymap {
+    global: Vec<KeyBinding>,
+    module: Vec<KeyBinding>,
+    /// [`hud_lines`](Self::hud_lines), rebuilt when the bindings change.
+    hud: Vec<HudLine>,
+}
+
+impl Keymap {
+    /// A keymap with the app's global bindings and no module bindings.
+    pub fn new(global: Vec<KeyBinding>) -> Self {
+        let mut keymap = Self {
+            global,
+            module: Vec::new(),
+            hud: Vec::new(),
+        };
+        keymap.hud = keymap.build_hud_lines();
+        keymap
+    }
+
+    /// Replace the module bindings with the active module's. Bindings on a
+    /// chord that is already global are dropped.
+    pub fn set_module(&mut self, mut bindings: Vec<KeyBinding>) {
+        bindings.retain(|binding| !self.global.iter().any(|g| g.chord == binding.chord));
+        // Called every frame; the HUD lines only change with the bindings
+        if bindings != self.module {
+            self.module = bindings;
+            self.hud = self.build_hud_lines();
+        }
+    }
+
+    /// The action bound to `key`: global bindings first, then the module's.
+    /// Exact chords in either win over chords matched with Shift ignored.
+    pub fn lookup(&self, key: &KeyEvent) -> Option<KeyTarget> {
+        exact_action(&self.global, key)
+            .map(KeyTarget::Global)
+            .or_else(|| exact_action(&self.module, key).map(KeyTarget::Module))
+            .or_else(|| shifted_action(&self.global, key).map(KeyTarget::Global))
+            .or_else(|| shifted_action(&self.module, key).map(KeyTarget::Module))
+    }
+
+    /// Hints for the global bindings, one per action.
+    pub fn global_hints(&self) -> Vec<KeyHint> {
+        hints(&self.global)
+    }
+
+    /// Hints for the active module's bindings, one per action.
+    pub fn module_hints(&self) -> Vec<KeyHint> {
+        hints(&self.module)
+    }
+
+    /// `key: action` lines for the HUD's left column: the global keys, then
+    /// the module's, so a short HUD clips module keys rather than F1.
+    pub fn hud_lines(&self) -> &[HudLine] {
+        &self.hud
+    }
+
+    fn build_hud_lines(&self) -> Vec<HudLine> {
+        self.global_hints()
+            .into_iter()
+            .chain(self.module_hints())
+            .map(|hint| HudLine::from(format!("{}: {}", hint.key, hint.action)))
+            .collect()
+    }
+}
+
+/// One hint per action in declaration order, listing every chord bound to
+/// it, e.g. `` `/~ ``.
+fn hints(bindings: &[KeyBinding]) -> Vec<KeyHint> {
+    let mut hints: Vec<(ActionId, KeyHint)> = Vec::new();
+    for binding in bindings {
+        let chord = binding.chord.to_string();
+        match hints
+            .iter_mut()
+            .find(|(action, _)| *action == binding.action)
+        {
+            Some((_, hint)) => {
+                hint.key.push('/');
+                hint.key.push_str(&chord);
+            }
+            None => hints.push((
+                binding.action,
+                KeyHint::new(chord, binding.description.clone()),
+            )),
+        }
+    }
+    hints.into_iter().map(|(_, hint)| hint).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn merges_module_bindings_behind_global_ones() {
+        let mut keymap = Keymap::new(vec![
+            KeyBinding::new(KeyChord::char('`'), "console", "console"),
+            KeyBinding::new(KeyChord::char('~'), "console", "console"),
+            KeyBinding::new(KeyChord::key(KeyCode::Tab), "next_module", "next module"),
+        ]);
+        keymap.set_module(vec![
+            KeyBinding::new(KeyChord::key(KeyCode::Up), "up", "previous row"),
+            KeyBinding::new(KeyChord::char('k'), "up", "previous row"),
+            KeyBinding::new(KeyChord::key(KeyCode::Tab), "grab_tab", "stolen"),
+            KeyBinding::new(
+                KeyChord::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+                "reset",
+                "reset",
+            ),
+        ]);
+
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::Tab, KeyModifiers::NONE)),
+            Some(KeyTarget::Global("next_module"))
+        );
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::Char('~'), KeyModifiers::SHIFT)),
+            Some(KeyTarget::Global("console"))
+        );
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::Char('k'), KeyModifiers::NONE)),
+            Some(KeyTarget::Module("up"))
+        );
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::Char('r'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::Char('r'), KeyModifiers::CONTROL)),
+            Some(KeyTarget::Module("reset"))
+        );
+
+        assert_eq!(
+            keymap.module_hints(),
+            vec![
+                KeyHint::new("↑/k", "previous row"),
+                KeyHint::new("Ctrl+r", "reset"),
+            ]
+        );
+        let hud: Vec<String> = keymap.hud_lines().iter().map(HudLine::plain_text).collect();
+        assert_eq!(
+            hud,
+            [
+                "`/~: console",
+                "Tab: next module",
+                "↑/k: previous row",
+                "Ctrl+r: reset"
+            ]
+        );
+    }
+
+    #[test]
+    fn shift_falls_back_to_the_unshifted_chord() {
+        let mut keymap = Keymap::new(vec![
+            KeyBinding::new(KeyChord::key(KeyCode::F(1)), "help", "help"),
+            KeyBinding::new(KeyChord::key(KeyCode::Tab), "next_module", "next module"),
+        ]);
+        keymap.set_module(vec![
+            KeyBinding::new(KeyChord::key(KeyCode::Up), "up", "previous row"),
+            KeyBinding::new(
+                KeyChord::new(KeyCode::Tab, KeyModifiers::SHIFT),
+                "back_tab",
+                "previous tab",
+            ),
+        ]);
+
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::F(1), KeyModifiers::SHIFT)),
+            Some(KeyTarget::Global("help"))
+        );
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::Tab, KeyModifiers::SHIFT)),
+            Some(KeyTarget::Module("back_tab"))
+        );
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::Up, KeyModifiers::SHIFT)),
+            Some(KeyTarget::Module("up"))
+        );
+        assert_eq!(
+            keymap.lookup(&press(KeyCode::F(1), KeyModifiers::CONTROL)),
+            None
+        );
+    }
+}
@@ -2,14 +2,38 @@
 //!
 //! This crate provides the foundational building blocks shared by the
 //! application shell and all SPUD modules: an event bus, module registry,
-//! drop-down console, command system, logging subsystem, and common types.
+//! drop-down console, command system and usage counts, logging subsystem,
+//! telemetry history,
+//! translated UI strings, and common types. Fallible library APIs return
+//! [`error::CoreError`].
 
+pub mod alerts;
 pub mod bus;
 pub mod command;
 pub mod console;
+pub mod easing;
+pub mod error;
 pub mod event;
 pub mod fps;
+pub mod guard;
+pub mod i18n;
+pub mod idle;
+pub mod keymap;
+pub mod log_file;
 pub mod logging;
 pub mod module;
+pub mod output;
+pub mod pipeline;
 pub mod registry;
+pub mod rng;
+pub mod schema;
+pub mod spans;
 pub mod state;
+pub mod tap;
+pub mod telemetry;
+pub mod undo;
+pub mod usage;
+pub mod vars;
+
+/// Version of this crate, as reported by `spud version`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,658 @@
+//! The log file writer: daily files, rotated by size, compressed, with
+//! retention.
+//!
+//! Lines go to `spud.log.<YYYY-MM-DD>` (UTC date) in the log directory.
+//! When a line would take the file past [`Rotation::max_bytes`] it becomes
+//! segment `spud.log.<date>.1`, older segments of the day move up by one,
+//! and a fresh file starts; a new day also starts a new file.
+//!
+//! The cleanup pass runs at startup, after each rotation, and when the
+//! settings change. It deletes files older than [`Rotation::retention`],
+//! compresses the files no longer written to (`.gz` or `.zst`, per
+//! [`Rotation::compression`]), then deletes the oldest while there are more
+//! than [`Rotation::max_files`] or they take more than
+//! [`Rotation::max_total_bytes`]. It runs on a background thread, so a
+//! rotation does not hold up logging while a file is compressed; the next
+//! rotation waits for it before renaming segments. Files it failed to
+//! compress are reported by [`LogFile::take_warnings`].
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use spud_config::{LogCompression, LogConfig};
+
+/// Name prefix of every log file. Cleanup only touches files that start
+/// with it, in case the log directory is shared.
+pub const PREFIX: &str = "spud.log";
+
+/// Extensions of compressed log files.
+const COMPRESSED: [&str; 2] = ["gz", "zst"];
+
+/// When log files are rotated, compressed, and deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    /// Size a file may reach before the next one starts; `None` rotates
+    /// daily only.
+    pub max_bytes: Option<u64>,
+    /// Files kept in all, counting the one being written.
+    pub max_files: usize,
+    /// Bytes all files may take together, counting the one being written.
+    pub max_total_bytes: Option<u64>,
+    /// How long a file is kept.
+    pub retention: Duration,
+    pub compression: LogCompression,
+}
+
+impl Rotation {
+    pub fn from_config(config: &LogConfig) -> Self {
+        Self {
+            max_bytes: config.max_file_bytes(),
+            max_files: config.max_files,
+            max_total_bytes: config.max_total_bytes(),
+            retention: config.retention(),
+            compression: config.compress,
+        }
+    }
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self::from_config(&LogConfig::default())
+    }
+}
+
+/// A log file writer; write through `&LogFile` from any thread.
+///
+/// Each `write` call is taken as one whole line, so rotation never splits
+/// one; the `fmt` layer writes each event in a single call.
+#[derive(Debug)]
+pub struct LogFile {
+    dir: PathBuf,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    rotation: Rotation,
+    /// Date of the open file.
+    date: String,
+    file: Option<File>,
+    size: u64,
+    /// The cleanup pass in progress, returning its warnings.
+    cleanup: Option<JoinHandle<Vec<String>>>,
+    /// Warnings from finished cleanup passes, for [`LogFile::take_warnings`].
+    warnings: Vec<String>,
+}
+
+impl State {
+    /// Wait for the cleanup pass in progress, if any.
+    fn finish_cleanup(&mut self) {
+        if let Some(cleanup) = self.cleanup.take() {
+            match cleanup.join() {
+                Ok(warnings) => self.warnings.extend(warnings),
+                Err(_) => self.warnings.push("log cleanup panicked".to_string()),
+            }
+        }
+    }
+
+    /// Start a cleanup pass over `dir` once the previous one is done, on a
+    /// background thread, or inline if the thread cannot start.
+    fn start_cleanup(&mut self, dir: &Path, now: SystemTime) {
+        self.finish_cleanup();
+        let rotation = self.rotation;
+        let owned = dir.to_path_buf();
+        match thread::Builder::new()
+            .name("spud-log-cleanup".into())
+            .spawn(move || cleanup(&owned, rotation, now))
+        {
+            Ok(handle) => self.cleanup = Some(handle),
+            Err(_) => {
+                let warnings = cleanup(dir, rotation, now);
+                self.warnings.extend(warnings);
+            }
+        }
+    }
+}
+
+impl LogFile {
+    /// Write into `dir`, starting a cleanup of old files. The file itself
+    /// is opened on the first write.
+    pub fn new(dir: impl Into<PathBuf>, rotation: Rotation) -> Self {
+        let dir = dir.into();
+        let mut state = State {
+            rotation,
+            date: String::new(),
+            file: None,
+            size: 0,
+            cleanup: None,
+            warnings: Vec::new(),
+        };
+        state.start_cleanup(&dir, SystemTime::now());
+        Self {
+            dir,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Change the limits and start a cleanup pass with them.
+    pub fn set_rotation(&self, rotation: Rotation) {
+        let mut state = self.lock();
+        state.rotation = rotation;
+        state.start_cleanup(&self.dir, SystemTime::now());
+    }
+
+    /// Warnings from cleanup passes that finished since the last call,
+    /// e.g. a file that could not be compressed. Not logged from the
+    /// cleanup thread, since logging would write back into this file.
+    pub fn take_warnings(&self) -> Vec<String> {
+        let mut state = self.lock();
+        if state.cleanup.as_ref().is_some_and(JoinHandle::is_finished) {
+            state.finish_cleanup();
+        }
+        std::mem::take(&mut state.warnings)
+    }
+
+    /// Path of the file for `date`.
+    pub fn path(&self, date: &str) -> PathBuf {
+        self.dir.join(format!("{PREFIX}.{date}"))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write_line(&self, line: &[u8], now: SystemTime) -> io::Result<()> {
+        let mut state = self.lock();
+        let date = utc_date(now);
+        let len = line.len() as u64;
+        if state.file.is_none() || state.date != date {
+            let new_day = !state.date.is_empty() && state.date != date;
+            state.file = None;
+            state.date = date;
+            self.open(&mut state)?;
+            if new_day {
+                state.start_cleanup(&self.dir, now);
+            }
+        } else if state
+            .rotation
+            .max_bytes
+            .is_some_and(|max| state.size > 0 && state.size + len > max)
+        {
+            state.file = None;
+            // The cleanup pass may still be compressing a segment
+            state.finish_cleanup();
+            self.rotate(&state.date)?;
+            self.open(&mut state)?;
+            state.start_cleanup(&self.dir, now);
+        }
+        if let Some(file) = state.file.as_mut() {
+            file.write_all(line)?;
+            state.size += len;
+        }
+        Ok(())
+    }
+
+    fn open(&self, state: &mut State) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(&state.date))?;
+        state.size = file.metadata().map_or(0, |meta| meta.len());
+        state.file = Some(file);
+        Ok(())
+    }
+
+    /// Move the file for `date` to segment 1, shifting older segments,
+    /// compressed or not, up by one.
+    fn rotate(&self, date: &str) -> io::Result<()> {
+        let current = self.path(date).display().to_string();
+        let variants = |n: usize| -> Vec<PathBuf> {
+            std::iter::once(format!("{current}.{n}"))
+                .chain(COMPRESSED.iter().map(|ext| format!("{current}.{n}.{ext}")))
+                .map(PathBuf::from)
+                .collect()
+        };
+        let mut top = 0;
+        while variants(top + 1).iter().any(|path| path.exists()) {
+            top += 1;
+        }
+        for n in (1..=top).rev() {
+            for (from, to) in variants(n).into_iter().zip(variants(n + 1)) {
+                if from.exists() {
+                    fs::rename(from, to)?;
+                }
+            }
+        }
+        fs::rename(&current, format!("{current}.1"))
+    }
+}
+
+impl Write for &LogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_line(buf, SystemTime::now())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.lock().file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Age order of a log file name: date, then segment, newest last. `None`
+/// for names this writer did not make.
+fn age_key(name: &str) -> Option<(String, std::cmp::Reverse<usize>)> {
+    let rest = name.strip_prefix(PREFIX)?.strip_prefix('.')?;
+    let rest = COMPRESSED
+        .iter()
+        .find_map(|ext| rest.strip_suffix(ext)?.strip_suffix('.'))
+        .unwrap_or(rest);
+    let (date, segment) = match rest.split_once('.') {
+        Some((date, n)) => (date, n.parse().ok()?),
+        None => (rest, 0),
+    };
+    Some((date.to_string(), std::cmp::Reverse(segment)))
+}
+
+fn is_compressed(name: &str) -> bool {
+    COMPRESSED.iter().any(|ext| {
+        name.strip_suffix(ext)
+            .is_some_and(|rest| rest.ends_with('.'))
+    })
+}
+
+/// Compress `path` next to itself and remove it; returns the new path and
+/// its size.
+fn compress(path: &Path, compression: LogCompression) -> io::Result<(PathBuf, u64)> {
+    let ext = match compression {
+        LogCompression::None => return Ok((path.to_path_buf(), fs::metadata(path)?.len())),
+        LogCompression::Gzip => "gz",
+        LogCompression::Zstd => "zst",
+    };
+    let target = PathBuf::from(format!("{}.{ext}", path.display()));
+    let written = (|| {
+        let mut input = File::open(path)?;
+        let output = File::create(&target)?;
+        match compression {
+            LogCompression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(output, flate2::Compression::fast());
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?.sync_all()
+            }
+            _ => {
+                let mut encoder = zstd::Encoder::new(output, 0)?;
+                io::copy(&mut input, &mut encoder)?;
+                encoder.finish()?.sync_all()
+            }
+        }
+    })();
+    if let Err(err) = written {
+        let _ = fs::remove_file(&target);
+        return Err(err);
+    }
+    fs::remove_file(path)?;
+    Ok((target.clone(), fs::metadata(&target)?.len()))
+}
+
+/// The cleanup pass over `dir`; see the module docs. The file for the
+/// current date is never compressed or deleted, nor is one for a later
+/// date: the writer may have moved on to it while this pass ran. Returns a
+/// warning for each file that could not be compressed.
+fn cleanup(dir: &Path, rotation: Rotation, now: SystemTime) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return warnings;
+    };
+    let today = utc_date(now);
+    let live = |name: &str| {
+        name.strip_prefix(PREFIX)
+            .and_then(|rest| rest.strip_prefix('.'))
+            .is_some_and(|date| date.len() == today.len() && date >= today.as_str())
+    };
+    let cutoff = now.checked_sub(rotation.retention);
+    let mut kept = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with(PREFIX) {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if let (Ok(modified), Some(cutoff)) = (meta.modified(), cutoff) {
+            if modified < cutoff && !live(&name) {
+                let _ = fs::remove_file(entry.path());
+                continue;
+            }
+        }
+        let Some(key) = age_key(&name) else {
+            continue;
+        };
+        let mut path = entry.path();
+        let mut size = meta.len();
+        if !live(&name) && !is_compressed(&name) {
+            match compress(&path, rotation.compression) {
+                Ok(compressed) => (path, size) = compressed,
+                Err(err) => {
+                    warnings.push(format!("failed to compress {}: {err}", path.display()));
+                }
+            }
+        }
+        kept.push((key, live(&name), path, size));
+    }
+
+    kept.sort();
+    let mut files = kept.len();
+    let mut total: u64 = kept.iter().map(|(.., size)| size).sum();
+    for (_, is_live, path, size) in kept {
+        let over_count = files > rotation.max_files;
+        let over_size = rotation.max_total_bytes.is_some_and(|max| total > max);
+        if !(over_count || over_size) {
+            break;
+        }
+        if is_live || fs::remove_file(&path).is_err() {
+            continue;
+        }
+        files -= 1;
+        total -= size;
+    }
+    warnings
+}
+
+/// A file in the log directory, as listed by [`disk_usage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogDirEntry {
+    pub name: String,
+    pub bytes: u64,
+    /// Written by [`LogFile`], rather than a transcript, dump, or audit log.
+    pub log: bool,
+    pub compressed: bool,
+}
+
+/// Files in `dir` with their sizes, in name order.
+pub fn disk_usage(dir: &Path) -> io::Result<Vec<LogDirEntry>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if !meta.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        files.push(LogDirEntry {
+            log: age_key(&name).is_some(),
+            compressed: is_compressed(&name),
+            bytes: meta.len(),
+            name,
+        });
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(files)
+}
+
+/// `YYYY-MM-DD` of `time` in UTC.
+fn utc_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Calendar date of a day count since 1970-01-01 (Howard Hinnant's
+/// `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+    use std::io::Read;
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Wait for `log`'s cleanup pass.
+    fn settle(log: &LogFile) {
+        log.lock().finish_cleanup();
+    }
+
+    fn plain(max_bytes: Option<u64>, max_files: usize) -> Rotation {
+        Rotation {
+            max_bytes,
+            max_files,
+            max_total_bytes: None,
+            retention: Duration::from_secs(86_400),
+            compression: LogCompression::None,
+        }
+    }
+
+    #[test]
+    fn dates_are_utc_calendar_days() {
+        assert_eq!(utc_date(SystemTime::UNIX_EPOCH), "1970-01-01");
+        let leap_day = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+        assert_eq!(utc_date(leap_day), "2024-02-29");
+        assert_eq!(
+            utc_date(leap_day + Duration::from_secs(86_399)),
+            "2024-02-29"
+        );
+        assert_eq!(
+            utc_date(leap_day + Duration::from_secs(86_400)),
+            "2024-03-01"
+        );
+    }
+
+    #[test]
+    fn rotates_by_size_and_keeps_max_files() {
+        let dir = TestDir::new("log-size");
+        let log = LogFile::new(dir.to_path_buf(), plain(Some(10), 3));
+        settle(&log);
+        let day = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+        for line in ["aaaaaa\n", "bbbbbb\n", "cccccc\n", "dddddd\n"] {
+            log.write_line(line.as_bytes(), day).unwrap();
+        }
+        settle(&log);
+        assert_eq!(
+            names(&dir),
+            [
+                "spud.log.2024-02-29",
+                "spud.log.2024-02-29.1",
+                "spud.log.2024-02-29.2"
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(log.path("2024-02-29")).unwrap(),
+            "dddddd\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("spud.log.2024-02-29.2")).unwrap(),
+            "bbbbbb\n"
+        );
+
+        let next_day = day + Duration::from_secs(86_400);
+        log.write_line(b"eeeeee\n", next_day).unwrap();
+        assert!(dir.join("spud.log.2024-03-01").exists());
+    }
+
+    #[test]
+    fn a_line_over_the_limit_still_gets_written() {
+        let dir = TestDir::new("log-long");
+        let log = LogFile::new(dir.to_path_buf(), plain(Some(4), 20));
+        settle(&log);
+        let day = SystemTime::UNIX_EPOCH;
+        log.write_line(b"a long line\n", day).unwrap();
+        assert_eq!(
+            fs::read_to_string(log.path("1970-01-01")).unwrap(),
+            "a long line\n"
+        );
+    }
+
+    #[test]
+    fn rotated_files_are_compressed_and_shift_with_their_extension() {
+        for (compression, ext) in [(LogCompression::Gzip, "gz"), (LogCompression::Zstd, "zst")] {
+            let dir = TestDir::new(&format!("log-{ext}"));
+            let log = LogFile::new(
+                dir.to_path_buf(),
+                Rotation {
+                    compression,
+                    ..plain(Some(10), 5)
+                },
+            );
+            settle(&log);
+            let day = SystemTime::UNIX_EPOCH;
+            for line in ["aaaaaa\n", "bbbbbb\n", "cccccc\n"] {
+                log.write_line(line.as_bytes(), day).unwrap();
+            }
+            settle(&log);
+            assert_eq!(
+                names(&dir),
+                [
+                    "spud.log.1970-01-01".to_string(),
+                    format!("spud.log.1970-01-01.1.{ext}"),
+                    format!("spud.log.1970-01-01.2.{ext}"),
+                ]
+            );
+
+            let oldest = File::open(dir.join(format!("spud.log.1970-01-01.2.{ext}"))).unwrap();
+            let mut text = String::new();
+            match compression {
+                LogCompression::Gzip => flate2::read::GzDecoder::new(oldest)
+                    .read_to_string(&mut text)
+                    .unwrap(),
+                _ => zstd::Decoder::new(oldest)
+                    .unwrap()
+                    .read_to_string(&mut text)
+                    .unwrap(),
+            };
+            assert_eq!(text, "aaaaaa\n");
+        }
+    }
+
+    #[test]
+    fn compression_failures_become_warnings() {
+        let dir = TestDir::new("log-warn");
+        // A directory with a log file's name cannot be compressed
+        fs::create_dir(dir.join("spud.log.2025-01-01")).unwrap();
+        let log = LogFile::new(
+            dir.to_path_buf(),
+            Rotation {
+                compression: LogCompression::Gzip,
+                retention: Duration::from_secs(u64::from(u32::MAX)),
+                ..plain(None, 20)
+            },
+        );
+        settle(&log);
+        let warnings = log.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("failed to compress"));
+        assert!(log.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn cleanup_removes_stale_and_excess_files() {
+        let dir = TestDir::new("log-cleanup");
+        for name in [
+            "spud.log.2025-01-01",
+            "spud.log.2025-01-02.1",
+            "spud.log.2025-01-02",
+            "spud.log.9999-12-31",
+            "other.txt",
+        ] {
+            fs::write(dir.join(name), "x").unwrap();
+        }
+        let rotation = Rotation {
+            retention: Duration::from_secs(3_600),
+            ..plain(None, 3)
+        };
+        cleanup(&dir, rotation, SystemTime::now());
+        assert_eq!(
+            names(&dir),
+            [
+                "other.txt",
+                "spud.log.2025-01-02",
+                "spud.log.2025-01-02.1",
+                "spud.log.9999-12-31"
+            ]
+        );
+
+        // Everything is older than an hour from now; a file for a later
+        // date is still being written.
+        cleanup(
+            &dir,
+            rotation,
+            SystemTime::now() + Duration::from_secs(7_200),
+        );
+        assert_eq!(names(&dir), ["other.txt", "spud.log.9999-12-31"]);
+    }
+
+    #[test]
+    fn cleanup_keeps_the_total_size_under_the_limit_but_spares_the_current_file() {
+        let dir = TestDir::new("log-total");
+        let now = SystemTime::now();
+        let current = format!("{PREFIX}.{}", utc_date(now));
+        for name in ["spud.log.2025-01-01.gz", "spud.log.2025-01-02.1.gz"] {
+            fs::write(dir.join(name), [b'x'; 40]).unwrap();
+        }
+        fs::write(dir.join(&current), [b'x'; 100]).unwrap();
+        let rotation = Rotation {
+            max_total_bytes: Some(150),
+            ..plain(None, 20)
+        };
+        cleanup(&dir, rotation, now);
+        assert_eq!(names(&dir), ["spud.log.2025-01-02.1.gz", current.as_str()]);
+
+        let rotation = Rotation {
+            max_total_bytes: Some(10),
+            ..rotation
+        };
+        cleanup(&dir, rotation, now);
+        assert_eq!(names(&dir), [current.as_str()]);
+
+        let usage = disk_usage(&dir).unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].bytes, 100);
+        assert!(usage[0].log && !usage[0].compressed);
+    }
+
+    #[test]
+    fn age_keys_ignore_compression() {
+        assert_eq!(
+            age_key("spud.log.2025-01-02.3.zst"),
+            Some(("2025-01-02".into(), std::cmp::Reverse(3)))
+        );
+        assert_eq!(
+            age_key("spud.log.2025-01-02.gz"),
+            Some(("2025-01-02".into(), std::cmp::Reverse(0)))
+        );
+        assert_eq!(age_key("spud.log.2025-01-02.x"), None);
+        assert!(is_compressed("spud.log.2025-01-02.1.gz"));
+        assert!(!is_compressed("spud.log.2025-01-02"));
+    }
+}
@@ -1,10 +1,15 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 
-use tracing_appender::rolling;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+use crate::log_file::{LogFile, Rotation};
+
+use anyhow::{Context, Result};
+
+use spud_config::LogConfig;
+use tracing_subscriber::{
+    layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
 
 /// Log severity level (mirrors tracing levels for UI use).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,12 +33,107 @@ impl fmt::Display for LogLevel {
     }
 }
 
+impl LogLevel {
+    /// All levels from least to most severe.
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Counts of log events since startup, by level and by target.
+///
+/// Only events that pass the active filter are counted. Errors also count
+/// towards [`unacknowledged_errors`](Self::unacknowledged_errors) until
+/// [`acknowledge`](Self::acknowledge) is called.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogStats {
+    by_level: [u64; 5],
+    by_target: BTreeMap<String, [u64; 5]>,
+    unacknowledged_errors: u64,
+}
+
+impl LogStats {
+    /// Empty counters.
+    pub const fn new() -> Self {
+        Self {
+            by_level: [0; 5],
+            by_target: BTreeMap::new(),
+            unacknowledged_errors: 0,
+        }
+    }
+
+    /// Count one event.
+    pub fn record(&mut self, level: LogLevel, target: &str) {
+        self.by_level[level.index()] += 1;
+        match self.by_target.get_mut(target) {
+            Some(counts) => counts[level.index()] += 1,
+            None => {
+                let mut counts = [0; 5];
+                counts[level.index()] = 1;
+                self.by_target.insert(target.to_string(), counts);
+            }
+        }
+        if level == LogLevel::Error {
+            self.unacknowledged_errors += 1;
+        }
+    }
+
+    /// Events logged at `level`.
+    pub fn count(&self, level: LogLevel) -> u64 {
+        self.by_level[level.index()]
+    }
+
+    /// Events logged by `target` at `level`.
+    pub fn target_count(&self, target: &str, level: LogLevel) -> u64 {
+        self.by_target
+            .get(target)
+            .map_or(0, |counts| counts[level.index()])
+    }
+
+    /// Targets that have logged anything, in name order.
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.by_target.keys().map(String::as_str)
+    }
+
+    /// Errors logged since the last [`acknowledge`](Self::acknowledge).
+    pub fn unacknowledged_errors(&self) -> u64 {
+        self.unacknowledged_errors
+    }
+
+    /// Mark all errors so far as seen.
+    pub fn acknowledge(&mut self) {
+        self.unacknowledged_errors = 0;
+    }
+}
+
+/// Process-wide counters, fed by the console layer.
+static STATS: Mutex<LogStats> = Mutex::new(LogStats::new());
+
+/// Run `f` with the process-wide log counters.
+pub fn with_stats<R>(f: impl FnOnce(&mut LogStats) -> R) -> R {
+    let mut stats = STATS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut stats)
+}
+
 /// A single log entry for display in the console overlay.
 #[derive(Debug, Clone)]
 pub struct LogEntry {
     pub level: LogLevel,
     pub target: String,
     pub message: String,
+    /// How many identical messages this line stands for; above 1 when the
+    /// console coalesced repeats.
+    pub count: u32,
 }
 
 /// Shared ring buffer for log entries consumed by the console UI.
@@ -46,59 +146,17 @@ pub fn new_log_buffer(capacity: usize) -> LogBuffer {
 
 /// Return the log directory path.
 ///
-/// Precedence: `SPUD_LOG_DIR` env var > platform default.
-/// macOS: `~/Library/Logs/spud/`
-/// Linux: `$XDG_DATA_HOME/spud/logs/` or `~/.local/share/spud/logs/`
-pub fn log_dir() -> PathBuf {
-    if let Ok(dir) = std::env::var("SPUD_LOG_DIR") {
-        return PathBuf::from(dir);
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(home) = dirs::home_dir() {
-            return home.join("Library").join("Logs").join("spud");
-        }
-    }
-
-    #[cfg(not(target_os = "macos"))]
-    {
-        if let Some(data) = dirs::data_dir() {
-            return data.join("spud").join("logs");
-        }
-    }
-
-    PathBuf::from("logs")
-}
+/// Resolved by [`spud_config::paths::log_dir`]: `SPUD_LOG_DIR` env var >
+/// platform default.
+pub use spud_config::paths::log_dir;
 
 const MAX_CONSOLE_LINES: usize = 1000;
-const LOG_RETENTION_DAYS: u64 = 7;
 
-/// Remove SPUD log files older than `max_age_days` from the given directory.
-///
-/// Only deletes files whose name starts with `spud.log` (the prefix used by
-/// the daily rolling appender) to avoid accidentally removing unrelated files
-/// if the log directory is shared.
-fn cleanup_old_logs(log_path: &std::path::Path, max_age_days: u64) {
-    let cutoff =
-        std::time::SystemTime::now() - std::time::Duration::from_secs(max_age_days * 86400);
-    if let Ok(entries) = std::fs::read_dir(log_path) {
-        for entry in entries.flatten() {
-            let name = entry.file_name();
-            let name = name.to_string_lossy();
-            if !name.starts_with("spud.log") {
-                continue;
-            }
-            if let Ok(meta) = entry.metadata() {
-                if let Ok(modified) = meta.modified() {
-                    if modified < cutoff {
-                        let _ = std::fs::remove_file(entry.path());
-                    }
-                }
-            }
-        }
-    }
-}
+/// Handle to swap the active filter, set by [`init_with_filter`].
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// The log file writer, set by [`init_with_filter`].
+static LOG_FILE: OnceLock<Arc<LogFile>> = OnceLock::new();
 
 /// A tracing layer that pushes log entries into a shared ring buffer.
 struct ConsoleLayer {
@@ -121,6 +179,7 @@ impl<S: tracing::Subscriber> Layer<S> for ConsoleLayer {
         };
 
         let target = event.metadata().target().to_string();
+        with_stats(|stats| stats.record(level, &target));
 
         let mut visitor = MessageVisitor {
             message: None,
@@ -133,6 +192,7 @@ impl<S: tracing::Subscriber> Layer<S> for ConsoleLayer {
             level,
             target,
             message,
+            count: 1,
         };
 
         if let Ok(mut buf) = self.buffer.lock() {
@@ -181,12 +241,30 @@ impl tracing::field::Visit for MessageVisitor {
 /// Initialize the logging subsystem. Returns the shared log buffer for the console.
 ///
 /// Filter controlled by `SPUD_LOG` or `RUST_LOG` (default: `info`).
-/// File output: daily rotation in `log_dir()`, 7-day retention.
+/// File output: [`LogFile`] in `log_dir()`, rotated daily and at 10 MiB,
+/// 20 files and 7 days kept.
 /// Console buffer: ring buffer of `MAX_CONSOLE_LINES` entries.
 pub fn init() -> LogBuffer {
+    init_with_filter(None, &LogConfig::default())
+}
+
+/// Like [`init`], but an explicit filter directive (e.g. `"debug"` or
+/// `"spud_remote=trace"`) takes precedence over the environment, and `files`
+/// sets the file rotation. An invalid directive is reported on stderr and
+/// ignored.
+pub fn init_with_filter(directive: Option<&str>, files: &LogConfig) -> LogBuffer {
     let buffer = new_log_buffer(MAX_CONSOLE_LINES);
 
-    let filter = EnvFilter::try_from_env("SPUD_LOG")
+    let explicit = directive.and_then(|directive| match EnvFilter::try_new(directive) {
+        Ok(filter) => Some(filter),
+        Err(e) => {
+            eprintln!("warning: ignoring invalid log filter {directive:?}: {e}");
+            None
+        }
+    });
+    let filter = explicit
+        .map(Ok)
+        .unwrap_or_else(|| EnvFilter::try_from_env("SPUD_LOG"))
         .or_else(|_| EnvFilter::try_from_env("RUST_LOG"))
         .unwrap_or_else(|_| EnvFilter::new("info"));
 
@@ -198,11 +276,10 @@ pub fn init() -> LogBuffer {
         );
     }
 
-    cleanup_old_logs(&log_path, LOG_RETENTION_DAYS);
-
-    let file_appender = rolling::daily(&log_path, "spud.log");
+    let log_file = Arc::new(LogFile::new(&log_path, Rotation::from_config(files)));
+    let _ = LOG_FILE.set(log_file.clone());
     let file_layer = tracing_subscriber::fmt::layer()
-        .with_writer(file_appender)
+        .with_writer(log_file)
         .with_ansi(false)
         .with_target(true);
 
@@ -211,6 +288,9 @@ pub fn init() -> LogBuffer {
         max_lines: MAX_CONSOLE_LINES,
     };
 
+    let (filter, handle) = reload::Layer::new(filter);
+    let _ = FILTER_HANDLE.set(handle);
+
     tracing_subscriber::registry()
         .with(filter)
         .with(file_layer)
@@ -220,9 +300,68 @@ pub fn init() -> LogBuffer {
     buffer
 }
 
+/// The filter used when none is given explicitly: `SPUD_LOG`, then
+/// `RUST_LOG`, then `info`.
+pub fn default_directive() -> String {
+    ["SPUD_LOG", "RUST_LOG"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_else(|| "info".to_string())
+}
+
+/// Replace the active log filter with `directive` (e.g. `"debug"` or
+/// `"spud_remote=trace"`) without restarting.
+///
+/// # Errors
+///
+/// Returns an error if the directive is invalid or logging was not
+/// initialized with [`init`] / [`init_with_filter`].
+pub fn set_filter(directive: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(directive)
+        .with_context(|| format!("invalid log filter {directive:?}"))?;
+    FILTER_HANDLE
+        .get()
+        .context("logging is not initialized")?
+        .reload(filter)
+        .context("failed to swap log filter")
+}
+
+/// Apply new `[log]` rotation settings to the log file.
+///
+/// # Errors
+///
+/// Returns an error if logging was not initialized with [`init`] /
+/// [`init_with_filter`].
+pub fn set_rotation(files: &LogConfig) -> Result<()> {
+    LOG_FILE
+        .get()
+        .context("logging is not initialized")?
+        .set_rotation(Rotation::from_config(files));
+    Ok(())
+}
+
+/// Warnings from the log file's background cleanup since the last call,
+/// e.g. a rotated file that could not be compressed. Empty before logging
+/// is initialized.
+pub fn take_file_warnings() -> Vec<String> {
+    LOG_FILE
+        .get()
+        .map(|log_file| log_file.take_warnings())
+        .unwrap_or_default()
+}
+
+/// The active log filter, or `None` before logging is initialized.
+pub fn current_filter() -> Option<String> {
+    FILTER_HANDLE
+        .get()?
+        .with_current(|filter| filter.to_string())
+        .ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
     use std::sync::Mutex as StdMutex;
 
     // Serialize env-mutating tests to avoid data races.
@@ -261,6 +400,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn stats_count_by_level_and_target() {
+        let mut stats = LogStats::new();
+        stats.record(LogLevel::Error, "spud_app");
+        stats.record(LogLevel::Warn, "spud_remote");
+        stats.record(LogLevel::Warn, "spud_remote");
+        assert_eq!(stats.count(LogLevel::Warn), 2);
+        assert_eq!(stats.target_count("spud_remote", LogLevel::Warn), 2);
+        assert_eq!(stats.target_count("spud_app", LogLevel::Warn), 0);
+        assert_eq!(
+            stats.targets().collect::<Vec<_>>(),
+            vec!["spud_app", "spud_remote"]
+        );
+
+        assert_eq!(stats.unacknowledged_errors(), 1);
+        stats.acknowledge();
+        assert_eq!(stats.unacknowledged_errors(), 0);
+        assert_eq!(stats.count(LogLevel::Error), 1);
+    }
+
     #[test]
     fn console_ring_buffer_caps_at_max() {
         let buf = new_log_buffer(3);
@@ -274,6 +433,7 @@ mod tests {
                     level: LogLevel::Info,
                     target: "test".into(),
                     message: format!("msg {}", i),
+                    count: 1,
                 });
             }
         }
@@ -290,6 +450,7 @@ mod tests {
             level: LogLevel::Warn,
             target: "spud_core::foo".into(),
             message: "something happened".into(),
+            count: 1,
         };
         assert_eq!(entry.level, LogLevel::Warn);
         assert_eq!(entry.target, "spud_core::foo");
@@ -342,25 +503,4 @@ mod tests {
         };
         assert_eq!(v.finish(), "");
     }
-
-    #[test]
-    fn cleanup_old_logs_removes_stale_files() {
-        let tmp = std::env::temp_dir().join("spud-test-cleanup");
-        let _ = std::fs::create_dir_all(&tmp);
-
-        let spud_a = tmp.join("spud.log.2025-01-01");
-        let spud_b = tmp.join("spud.log.2025-01-02");
-        let other = tmp.join("other.txt");
-        std::fs::write(&spud_a, "a").unwrap();
-        std::fs::write(&spud_b, "b").unwrap();
-        std::fs::write(&other, "c").unwrap();
-
-        // max_age_days=0 means cutoff is "now", so all matching files get cleaned
-        cleanup_old_logs(&tmp, 0);
-        assert!(!spud_a.exists(), "spud log file should be deleted");
-        assert!(!spud_b.exists(), "spud log file should be deleted");
-        assert!(other.exists(), "non-spud file should be preserved");
-
-        let _ = std::fs::remove_dir_all(&tmp);
-    }
 }
@@ -1,14 +1,166 @@
 use std::any::Any;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+
+use ratatui::{layout::Rect, Frame};
+use serde_json::Value;
+use spud_agent::Agent;
+use spud_config::AppConfig;
 
 use crate::event::Event;
+use crate::keymap::{ActionId, KeyBinding};
+use crate::output::CommandOutput;
+use crate::rng::Rng;
 
 /// Lines contributed by a module to the Doom-style HUD panel.
-#[derive(Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct HudContribution {
     /// Lines rendered in the left HUD column (e.g. keybindings, status).
-    pub left_lines: Vec<String>,
+    pub left_lines: Vec<HudLine>,
     /// Lines rendered in the right HUD column (e.g. metrics, indicators).
-    pub right_lines: Vec<String>,
+    pub right_lines: Vec<HudLine>,
+}
+
+/// Semantic style for HUD text, mapped to concrete colors by the UI layer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HudStyle {
+    /// Default foreground.
+    #[default]
+    Normal,
+    /// De-emphasized text such as hints.
+    Dim,
+    /// Bold, neutral emphasis.
+    Emphasis,
+    /// Healthy value.
+    Good,
+    /// Value approaching a threshold.
+    Warn,
+    /// Value past a threshold.
+    Critical,
+}
+
+/// A run of text with one semantic style.
+///
+/// HUD text is built every frame, so it is a [`Cow`]: fixed labels stay
+/// borrowed and only computed values allocate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HudSpan {
+    pub text: Cow<'static, str>,
+    pub style: HudStyle,
+}
+
+impl HudSpan {
+    /// Create a span with the given style.
+    pub fn new(text: impl Into<Cow<'static, str>>, style: HudStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+/// One line of HUD content.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HudLine {
+    /// Styled spans rendered left to right.
+    Spans(Vec<HudSpan>),
+    /// Label on the left, value right-aligned to the column edge.
+    KeyValue {
+        key: Cow<'static, str>,
+        value: Cow<'static, str>,
+        style: HudStyle,
+    },
+    /// Label, inline bar filled to `ratio` (0.0–1.0), and value text.
+    Gauge {
+        label: Cow<'static, str>,
+        ratio: f32,
+        value: Cow<'static, str>,
+        style: HudStyle,
+    },
+}
+
+impl HudLine {
+    /// A single span of text in the given style.
+    pub fn styled(text: impl Into<Cow<'static, str>>, style: HudStyle) -> Self {
+        Self::Spans(vec![HudSpan::new(text, style)])
+    }
+
+    /// A label with a right-aligned value.
+    pub fn key_value(
+        key: impl Into<Cow<'static, str>>,
+        value: impl Into<Cow<'static, str>>,
+        style: HudStyle,
+    ) -> Self {
+        Self::KeyValue {
+            key: key.into(),
+            value: value.into(),
+            style,
+        }
+    }
+
+    /// An inline gauge; `ratio` is clamped to `0.0..=1.0` and NaN reads as empty.
+    pub fn gauge(
+        label: impl Into<Cow<'static, str>>,
+        ratio: f32,
+        value: impl Into<Cow<'static, str>>,
+        style: HudStyle,
+    ) -> Self {
+        let ratio = if ratio.is_nan() {
+            0.0
+        } else {
+            ratio.clamp(0.0, 1.0)
+        };
+        Self::Gauge {
+            label: label.into(),
+            ratio,
+            value: value.into(),
+            style,
+        }
+    }
+
+    /// The line's text without styling or bars, for logs and tests.
+    pub fn plain_text(&self) -> String {
+        match self {
+            Self::Spans(spans) => spans.iter().map(|span| span.text.as_ref()).collect(),
+            Self::KeyValue { key, value, .. }
+            | Self::Gauge {
+                label: key, value, ..
+            } => format!("{key} {value}"),
+        }
+    }
+}
+
+impl From<String> for HudLine {
+    fn from(text: String) -> Self {
+        Self::styled(text, HudStyle::Normal)
+    }
+}
+
+impl From<&str> for HudLine {
+    fn from(text: &str) -> Self {
+        Self::styled(text.to_string(), HudStyle::Normal)
+    }
+}
+
+/// A key and what it does, listed in the help overlay. Built from
+/// [`KeyBinding`]s by [`crate::keymap::Keymap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyHint {
+    /// Key label as the user would type it (e.g. `"Tab"`, `"r"`).
+    pub key: String,
+    /// Short description of the action.
+    pub action: String,
+}
+
+impl KeyHint {
+    /// Create a hint for `key`.
+    pub fn new(key: impl Into<String>, action: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            action: action.into(),
+        }
+    }
 }
 
 /// A pluggable SPUD module.
@@ -17,9 +169,8 @@ pub struct HudContribution {
 /// unique [`id`](Module::id), a human-readable [`title`](Module::title), and
 /// optional implementations for event handling and HUD contributions.
 ///
-/// Rendering is handled separately via `spud_ui::renderer::HeroRenderer`.
-/// Modules that render hero content should implement that trait in addition
-/// to `Module`.
+/// Modules that render hero content implement [`HeroRenderer`] and return
+/// `Some(self)` from [`as_hero_renderer`](Module::as_hero_renderer).
 ///
 /// Modules are registered with [`crate::registry::ModuleRegistry`] and receive
 /// events via [`handle_event`](Module::handle_event).
@@ -42,7 +193,215 @@ pub trait Module {
         HudContribution::default()
     }
 
-    /// Return `self` as `&dyn Any` to enable downcasting for type-aware
-    /// rendering in spud-ui.
+    /// Return the keys this module handles while active. A bound key goes
+    /// to [`handle_action`](Module::handle_action) instead of
+    /// [`handle_event`](Module::handle_event), and the bindings are listed
+    /// in the HUD and the help overlay. Bindings on one of the app's global
+    /// keys are ignored.
+    ///
+    /// The default implementation binds nothing.
+    fn keybindings(&self) -> Vec<KeyBinding> {
+        Vec::new()
+    }
+
+    /// Run the action of a key from [`keybindings`](Module::keybindings),
+    /// pressed while this module is active.
+    ///
+    /// The default implementation is a no-op.
+    fn handle_action(&mut self, _action: ActionId) {}
+
+    /// Receive the module's random number stream. Called by the registry
+    /// when the module is registered; modules that need randomness keep it
+    /// and draw from it so runs are reproducible with `--seed`.
+    ///
+    /// The default implementation drops it.
+    fn seed_rng(&mut self, _rng: Rng) {}
+
+    /// Apply the module's settings from `spud.toml`, usually its
+    /// `[modules.<id>]` section. Called by the app at startup and after
+    /// every reload, so modules should ignore sections that did not change.
+    ///
+    /// The default implementation ignores the config.
+    fn configure(&mut self, _config: &AppConfig) {}
+
+    /// Handle `<id> <args…>` typed in the console, e.g. `stats hide cores`.
+    /// Built-in and app commands of the same name take precedence.
+    ///
+    /// The default implementation returns `None`: the module has no console
+    /// command and the input is reported as unknown.
+    fn console_command(&mut self, _args: &[&str]) -> Option<CommandOutput> {
+        None
+    }
+
+    /// Whether `<id> <args…>` is hard to undo, so `[commands] destructive`
+    /// in `spud.toml` applies: it may need a confirmation, or be refused.
+    ///
+    /// The default implementation returns `false`.
+    fn console_destructive(&self, _args: &[&str]) -> bool {
+        false
+    }
+
+    /// Handle the line typed in reply to a [`CommandOutput::Prompt`] the
+    /// module's console command returned. `token` is the prompt's
+    /// `callback_token`; return another prompt to ask a further question.
+    ///
+    /// The default implementation returns `None`: the answer is reported as
+    /// unexpected.
+    fn console_answer(&mut self, _token: &str, _answer: &str) -> Option<CommandOutput> {
+        None
+    }
+
+    /// Return `(usage, description)` lines for the module's console
+    /// command, listed by `help`.
+    ///
+    /// The default implementation returns none.
+    fn console_usage(&self) -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
+
+    /// Return `(tag, JSON Schema)` for the custom events this module
+    /// publishes. The host registers them at startup and drops events whose
+    /// payload does not match; see [`crate::schema`] for the supported
+    /// keywords.
+    ///
+    /// The default implementation returns none, leaving payloads unchecked.
+    fn event_schemas(&self) -> Vec<(&'static str, Value)> {
+        Vec::new()
+    }
+
+    /// Take events the module wants published on the bus, such as
+    /// [`Event::Telemetry`] samples.
+    ///
+    /// Called by the app after each broadcast. The default returns nothing.
+    fn drain_events(&mut self) -> Vec<Event> {
+        Vec::new()
+    }
+
+    /// Return the agent face shown in the HUD while this module is active.
+    ///
+    /// The default implementation returns `None`, which falls back to the
+    /// app's global agent. Modules that own an agent advance it themselves,
+    /// typically on [`Event::Tick`].
+    fn agent(&self) -> Option<&Agent> {
+        None
+    }
+
+    /// Return the module's hero renderer, if it draws the hero area.
+    ///
+    /// The default implementation returns `None`, leaving the hero area blank.
+    fn as_hero_renderer(&self) -> Option<&dyn HeroRenderer> {
+        None
+    }
+
+    /// Return `self` as `&dyn Any` to enable downcasting to the concrete
+    /// module type. Prefer [`downcast_ref`](trait.Module.html#method.downcast_ref).
     fn as_any(&self) -> &dyn Any;
 }
+
+impl dyn Module + '_ {
+    /// Downcast to a concrete module type.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+}
+
+/// Trait for modules that render content in the hero (main) area.
+///
+/// Implement this alongside [`Module`] and expose it through
+/// [`Module::as_hero_renderer`].
+pub trait HeroRenderer {
+    /// Render the hero (main content) area of the screen.
+    ///
+    /// Called each frame when this module is active, unless
+    /// [`content_version`](Self::content_version) says nothing changed.
+    fn render_hero(&self, f: &mut Frame, area: Rect);
+
+    /// Version of the hero content, for heroes that change rarely.
+    ///
+    /// While this returns the same `Some` value and the hero area keeps
+    /// its size, the shell reuses the cells of the last render instead of
+    /// calling [`render_hero`](Self::render_hero). Return a new value
+    /// whenever the output would change, and `None` (the default) to render
+    /// every frame. A hero that places the cursor should keep `None`: the
+    /// reused cells do not bring it back.
+    fn content_version(&self) -> Option<u64> {
+        None
+    }
+
+    /// Escape sequences to write after the frame's cells, for output the
+    /// cell grid cannot hold (Sixel, iTerm2, or Kitty images over `area`).
+    ///
+    /// Called each frame after the hero is drawn, cached or not. Coordinates
+    /// are the screen's, so start with a cursor move; the cursor is saved
+    /// before the bytes and restored after them, and they go out in the
+    /// same write as the frame. Served screens (`spud serve`) drop them.
+    /// The default writes nothing.
+    fn post_draw(&self, _area: Rect, _out: &mut Vec<u8>) {}
+}
+
+thread_local! {
+    static ISOLATING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run module code, turning a panic into its message so one broken module
+/// cannot take the app down. The panic hook still runs; it can check
+/// [`isolating`] to skip printing over the terminal.
+///
+/// # Errors
+///
+/// Returns the panic message if `f` panicked.
+pub fn isolate<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    let outer = ISOLATING.with(|flag| flag.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    ISOLATING.with(|flag| flag.set(outer));
+    result.map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic with a non-string payload".to_string())
+    })
+}
+
+/// Whether the current thread is inside [`isolate`], for panic hooks.
+pub fn isolating() -> bool {
+    ISOLATING.with(Cell::get)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gauge_ratio_is_clamped() {
+        let HudLine::Gauge { ratio, .. } = HudLine::gauge("CPU", 1.7, "170%", HudStyle::Critical)
+        else {
+            panic!("expected gauge");
+        };
+        assert_eq!(ratio, 1.0);
+        let HudLine::Gauge { ratio, .. } = HudLine::gauge("CPU", f32::NAN, "--", HudStyle::Dim)
+        else {
+            panic!("expected gauge");
+        };
+        assert_eq!(ratio, 0.0);
+    }
+
+    #[test]
+    fn isolate_returns_panic_messages() {
+        assert_eq!(isolate(|| 7), Ok(7));
+        let literal = isolate(|| -> u8 { panic!("boom") });
+        let formatted = isolate(|| -> u8 { panic!("bad row {}", 3) });
+        assert_eq!(literal, Err("boom".to_string()));
+        assert_eq!(formatted, Err("bad row 3".to_string()));
+        assert!(!isolating());
+    }
+
+    #[test]
+    fn plain_text_joins_parts() {
+        assert_eq!(HudLine::from("q: quit").plain_text(), "q: quit");
+        assert_eq!(
+            HudLine::key_value("RSS", "12 MiB", HudStyle::Normal).plain_text(),
+            "RSS 12 MiB"
+        );
+    }
+}
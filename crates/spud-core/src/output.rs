@@ -0,0 +1,348 @@
+//! Structured results returned by console commands.
+//!
+//! Commands return a [`CommandOutput`]; the console turns it into text with
+//! [`CommandOutput::to_lines`], and remote callers (plugins, control clients)
+//! receive [`CommandOutput::to_json`].
+
+use serde_json::{json, Value};
+use unicode_width::UnicodeWidthStr;
+
+/// The result of executing a console command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandOutput {
+    /// Lines to display in the console.
+    Lines(Vec<String>),
+    /// Error lines to display in the console with error styling.
+    Error(Vec<String>),
+    /// Labelled values, shown as an aligned two-column list.
+    KeyValue(Vec<(String, String)>),
+    /// Rows under column headers.
+    Table(Table),
+    /// Results of `;`-chained commands, in order.
+    Multi(Vec<CommandOutput>),
+    /// Ask a follow-up question. The console shows `question` in place of
+    /// its prompt, and the next line submitted is answered back to whoever
+    /// asked, with `callback_token`, instead of running as a command.
+    Prompt {
+        question: String,
+        callback_token: String,
+    },
+    /// Signal that the application should quit.
+    Quit,
+}
+
+/// Outcome of a command, independent of how its output is shaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandStatus {
+    Ok,
+    Error,
+    Quit,
+}
+
+impl CommandStatus {
+    /// Lowercase name used in JSON output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Error => "error",
+            Self::Quit => "quit",
+        }
+    }
+}
+
+impl CommandOutput {
+    /// Build a [`CommandOutput::Prompt`].
+    pub fn prompt(question: impl Into<String>, callback_token: impl Into<String>) -> Self {
+        Self::Prompt {
+            question: question.into(),
+            callback_token: callback_token.into(),
+        }
+    }
+
+    /// Build a [`CommandOutput::KeyValue`] from string-like pairs.
+    pub fn key_value<K: Into<String>, V: Into<String>>(
+        pairs: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        Self::KeyValue(
+            pairs
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+
+    /// Whether the command succeeded, failed, or asked to quit. A chain
+    /// quits if any part quit, else fails if any part failed.
+    pub fn status(&self) -> CommandStatus {
+        match self {
+            Self::Error(_) => CommandStatus::Error,
+            Self::Quit => CommandStatus::Quit,
+            Self::Multi(outputs) => outputs
+                .iter()
+                .map(Self::status)
+                .max_by_key(|status| match status {
+                    CommandStatus::Ok => 0,
+                    CommandStatus::Error => 1,
+                    CommandStatus::Quit => 2,
+                })
+                .unwrap_or(CommandStatus::Ok),
+            _ => CommandStatus::Ok,
+        }
+    }
+
+    /// The individual outputs of a chain, or just `self`.
+    pub fn parts(&self) -> Vec<&CommandOutput> {
+        match self {
+            Self::Multi(outputs) => outputs.iter().flat_map(Self::parts).collect(),
+            other => vec![other],
+        }
+    }
+
+    /// Return `true` for [`CommandOutput::Error`].
+    pub fn is_error(&self) -> bool {
+        self.status() == CommandStatus::Error
+    }
+
+    /// Render as console text. Tables and key-value lists are
+    /// column-aligned; error lines are returned without a prefix so the
+    /// caller can style them.
+    pub fn to_lines(&self) -> Vec<String> {
+        match self {
+            Self::Lines(lines) | Self::Error(lines) => lines.clone(),
+            Self::KeyValue(pairs) => {
+                let width = pairs
+                    .iter()
+                    .map(|(key, _)| text_width(key))
+                    .max()
+                    .unwrap_or(0);
+                pairs
+                    .iter()
+                    .map(|(key, value)| format!("  {}  {}", pad(key, width, Align::Left), value))
+                    .collect()
+            }
+            Self::Table(table) => table.to_lines(),
+            Self::Multi(outputs) => outputs.iter().flat_map(Self::to_lines).collect(),
+            Self::Prompt { question, .. } => vec![question.clone()],
+            Self::Quit => Vec::new(),
+        }
+    }
+
+    /// Machine-readable form for remote callers.
+    ///
+    /// Every object has `status` and `kind`; the remaining fields depend on
+    /// the kind (`lines`, `entries`, `columns` + `rows`, `results`, or
+    /// `question` + `callback_token`).
+    pub fn to_json(&self) -> Value {
+        let status = self.status().as_str();
+        match self {
+            Self::Lines(lines) | Self::Error(lines) => {
+                json!({ "status": status, "kind": "lines", "lines": lines })
+            }
+            Self::KeyValue(pairs) => json!({
+                "status": status,
+                "kind": "key_value",
+                "entries": pairs
+                    .iter()
+                    .map(|(key, value)| json!({ "key": key, "value": value }))
+                    .collect::<Vec<_>>(),
+            }),
+            Self::Table(table) => json!({
+                "status": status,
+                "kind": "table",
+                "columns": table.columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(),
+                "rows": table.rows,
+            }),
+            Self::Multi(outputs) => json!({
+                "status": status,
+                "kind": "multi",
+                "results": outputs.iter().map(Self::to_json).collect::<Vec<_>>(),
+            }),
+            Self::Prompt {
+                question,
+                callback_token,
+            } => json!({
+                "status": status,
+                "kind": "prompt",
+                "question": question,
+                "callback_token": callback_token,
+            }),
+            Self::Quit => json!({ "status": status, "kind": "quit" }),
+        }
+    }
+}
+
+/// Horizontal alignment of a table column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A table column header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub align: Align,
+}
+
+impl Column {
+    /// A left-aligned column (text).
+    pub fn left(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            align: Align::Left,
+        }
+    }
+
+    /// A right-aligned column (numbers).
+    pub fn right(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            align: Align::Right,
+        }
+    }
+}
+
+/// Column-aligned rows for [`CommandOutput::Table`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub columns: Vec<Column>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Create an empty table with the given columns.
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row. Missing cells render empty; extra cells are ignored.
+    pub fn push_row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// Return `true` if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Render the header and rows with two-space gutters.
+    pub fn to_lines(&self) -> Vec<String> {
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, column)| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(idx))
+                    .map(|cell| text_width(cell))
+                    .chain([text_width(&column.name)])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let render_row = |cells: &mut dyn Iterator<Item = &str>| {
+            let rendered: Vec<String> = self
+                .columns
+                .iter()
+                .zip(&widths)
+                .map(|(column, &width)| pad(cells.next().unwrap_or(""), width, column.align))
+                .collect();
+            format!("  {}", rendered.join("  ").trim_end())
+        };
+
+        let mut lines = Vec::with_capacity(self.rows.len() + 1);
+        lines.push(render_row(
+            &mut self.columns.iter().map(|column| column.name.as_str()),
+        ));
+        for row in &self.rows {
+            lines.push(render_row(&mut row.iter().map(String::as_str)));
+        }
+        lines
+    }
+}
+
+/// Cells `text` takes in the console; wide chars count twice.
+fn text_width(text: &str) -> usize {
+    text.width()
+}
+
+fn pad(text: &str, width: usize, align: Align) -> String {
+    let fill = " ".repeat(width.saturating_sub(text_width(text)));
+    match align {
+        Align::Left => format!("{text}{fill}"),
+        Align::Right => format!("{fill}{text}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_aligns_columns() {
+        let mut table = Table::new(vec![Column::left("NAME"), Column::right("COUNT")]);
+        table.push_row(vec!["render".into(), "7".into()]);
+        table.push_row(vec!["io".into(), "1200".into()]);
+        assert_eq!(
+            table.to_lines(),
+            vec!["  NAME    COUNT", "  render      7", "  io       1200"]
+        );
+    }
+
+    #[test]
+    fn wide_cells_align_by_display_width() {
+        let mut table = Table::new(vec![Column::left("NAME"), Column::right("N")]);
+        table.push_row(vec!["日本".into(), "1".into()]);
+        table.push_row(vec!["plugin".into(), "22".into()]);
+        assert_eq!(
+            table.to_lines(),
+            vec!["  NAME     N", "  日本     1", "  plugin  22"]
+        );
+    }
+
+    #[test]
+    fn key_value_pads_keys() {
+        let output = CommandOutput::key_value([("uptime", "00:01:00"), ("tps", "10.0")]);
+        assert_eq!(
+            output.to_lines(),
+            vec!["  uptime  00:01:00", "  tps     10.0"]
+        );
+        assert_eq!(output.status(), CommandStatus::Ok);
+    }
+
+    #[test]
+    fn json_carries_status_and_shape() {
+        let error = CommandOutput::Error(vec!["nope".into()]);
+        assert!(error.is_error());
+        assert_eq!(
+            error.to_json(),
+            json!({ "status": "error", "kind": "lines", "lines": ["nope"] })
+        );
+
+        let mut table = Table::new(vec![Column::left("ID")]);
+        table.push_row(vec!["hello".into()]);
+        assert_eq!(
+            CommandOutput::Table(table).to_json(),
+            json!({ "status": "ok", "kind": "table", "columns": ["ID"], "rows": [["hello"]] })
+        );
+
+        let prompt = CommandOutput::prompt("Really? [y/N]", "confirm");
+        assert_eq!(prompt.status(), CommandStatus::Ok);
+        assert_eq!(prompt.to_lines(), vec!["Really? [y/N]"]);
+        assert_eq!(
+            prompt.to_json(),
+            json!({
+                "status": "ok",
+                "kind": "prompt",
+                "question": "Really? [y/N]",
+                "callback_token": "confirm",
+            })
+        );
+    }
+}
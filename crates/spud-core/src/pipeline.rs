@@ -0,0 +1,775 @@
+//! Console command language: quoting, `;` chaining, `|` filters, and the
+//! `sleep`, `repeat`, and `when` primitives.
+//!
+//! `modules | grep stats ; tps` runs `modules`, keeps rows containing
+//! `stats`, then runs `tps`. Filters operate on [`CommandOutput`] so tables
+//! keep their header and structure.
+//!
+//! `repeat 3 tps` runs a command several times and `when tps < 5 echo slow`
+//! runs it if a variable compares true. Either takes a quoted line instead
+//! of a single command: `repeat 2 'echo a ; echo b'`. `sleep 2s` between
+//! chained commands ends the run early; [`start`] hands back the rest as a
+//! [`Pending`] for the caller to [`resume`] later, without blocking.
+
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::Duration;
+
+use spud_config::alerts::parse_duration;
+
+use crate::output::CommandOutput;
+use crate::vars::{is_name_char, Vars};
+
+/// Filters accepted after `|`, for help and error messages.
+pub const FILTER_USAGE: &str = "grep [-v] [-i] <pattern> | head <n> | tail <n>";
+
+/// The control-flow primitives as `(name, usage, description)`. They are
+/// part of the language, not registered commands.
+pub const CONTROL_COMMANDS: [(&str, &str, &str); 3] = [
+    (
+        "sleep",
+        "sleep <duration>",
+        "Run the rest of the line after a pause (e.g. 500ms, 2s)",
+    ),
+    ("repeat", "repeat <n> <command>", "Run a command n times"),
+    (
+        "when",
+        "when <var> <op> <value> <command>",
+        "Run a command if a variable or telemetry value compares true",
+    ),
+];
+
+/// Most times `repeat` runs a command.
+pub const REPEAT_LIMIT: usize = 100;
+
+/// Most commands one console line runs, counting every `repeat` pass and
+/// nested line, so `repeat 100 repeat 100 ...` cannot multiply past it.
+pub const COMMAND_BUDGET: usize = 1_000;
+
+/// Deepest `repeat` and `when` nesting.
+pub const DEPTH_LIMIT: usize = 8;
+
+/// Longest `sleep`.
+const SLEEP_LIMIT: Duration = Duration::from_secs(3_600);
+
+/// What a run has left of [`COMMAND_BUDGET`] and [`DEPTH_LIMIT`].
+struct Budget {
+    commands: usize,
+    depth: usize,
+    exhausted: bool,
+}
+
+impl Budget {
+    fn new() -> Self {
+        Self {
+            commands: COMMAND_BUDGET,
+            depth: 0,
+            exhausted: false,
+        }
+    }
+}
+
+/// One stage of a pipeline, as shell-like words with quotes removed.
+pub type Words = Vec<String>;
+
+/// A command followed by zero or more `|` filters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    pub command: Words,
+    pub filters: Vec<Words>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Pipe,
+    Semicolon,
+}
+
+/// Split `input` into words and operators. Single and double quotes group
+/// text (including `|` and `;`) into one word. `$name` and `${name}` are
+/// replaced from `vars` outside single quotes.
+fn tokenize(input: &str, vars: &Vars) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(other) => word.push(other),
+                        None => return Err("unterminated ' quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('$') => expand(&mut chars, vars, &mut word)?,
+                        Some(other) => word.push(other),
+                        None => return Err("unterminated \" quote".to_string()),
+                    }
+                }
+            }
+            '$' => {
+                in_word = true;
+                expand(&mut chars, vars, &mut word)?;
+            }
+            '|' | ';' => {
+                if in_word {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                    in_word = false;
+                }
+                tokens.push(if c == '|' {
+                    Token::Pipe
+                } else {
+                    Token::Semicolon
+                });
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        tokens.push(Token::Word(word));
+    }
+    Ok(tokens)
+}
+
+/// Expand the variable reference following a `$` into `out`. A `$` not
+/// followed by a name is kept literally.
+fn expand(chars: &mut Peekable<Chars<'_>>, vars: &Vars, out: &mut String) -> Result<(), String> {
+    let name = if chars.next_if_eq(&'{').is_some() {
+        let mut name = String::new();
+        loop {
+            match chars.next() {
+                Some('}') => break name,
+                Some(c) => name.push(c),
+                None => return Err("unterminated ${".to_string()),
+            }
+        }
+    } else {
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| is_name_char(*c)) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            out.push('$');
+            return Ok(());
+        }
+        name
+    };
+    match vars.get(&name) {
+        Some(value) => {
+            out.push_str(value);
+            Ok(())
+        }
+        None => Err(format!("unknown variable: ${name} (see 'vars')")),
+    }
+}
+
+/// Parse a command line into `;`-separated pipelines, expanding variables
+/// from `vars`. Empty segments (e.g. a trailing `;`) are skipped.
+///
+/// # Errors
+///
+/// Returns a message for unterminated quotes, unknown variables, or an
+/// empty `|` stage.
+pub fn parse(input: &str, vars: &Vars) -> Result<Vec<Pipeline>, String> {
+    let mut pipelines = Vec::new();
+    for segment in tokenize(input, vars)?.split(|token| *token == Token::Semicolon) {
+        if segment.is_empty() {
+            continue;
+        }
+        let mut stages = segment.split(|token| *token == Token::Pipe).map(|stage| {
+            stage
+                .iter()
+                .filter_map(|token| match token {
+                    Token::Word(word) => Some(word.clone()),
+                    _ => None,
+                })
+                .collect::<Words>()
+        });
+        let command = stages.next().unwrap_or_default();
+        let filters: Vec<Words> = stages.collect();
+        if command.is_empty() || filters.iter().any(Vec::is_empty) {
+            return Err("empty command in pipeline".to_string());
+        }
+        pipelines.push(Pipeline { command, filters });
+    }
+    Ok(pipelines)
+}
+
+/// Parse and run `input`, calling `exec` with each command's words.
+///
+/// Variables are expanded from `vars` before anything runs. A single
+/// command returns its output unchanged (after filters); chained
+/// commands return [`CommandOutput::Multi`]. Execution stops at the first
+/// command that asks to quit or asks a follow-up [`CommandOutput::Prompt`],
+/// which filters leave untouched. `sleep` is refused, since nothing would
+/// run the rest later; see [`start`].
+pub fn run(
+    input: &str,
+    vars: &Vars,
+    mut exec: impl FnMut(&[&str]) -> CommandOutput,
+) -> CommandOutput {
+    match parse(input, vars) {
+        Ok(pipelines) => chain(&pipelines, vars, &mut exec, false, &mut Budget::new()).0,
+        Err(err) => CommandOutput::Error(vec![err]),
+    }
+}
+
+/// Commands left after a `sleep`, to [`resume`] once `delay` has passed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pending {
+    pub delay: Duration,
+    pub rest: Vec<Pipeline>,
+}
+
+/// Like [`run`], but a `sleep` between chained commands ends the run and
+/// returns the commands after it as [`Pending`].
+pub fn start(
+    input: &str,
+    vars: &Vars,
+    mut exec: impl FnMut(&[&str]) -> CommandOutput,
+) -> (CommandOutput, Option<Pending>) {
+    match parse(input, vars) {
+        Ok(pipelines) => chain(&pipelines, vars, &mut exec, true, &mut Budget::new()),
+        Err(err) => (CommandOutput::Error(vec![err]), None),
+    }
+}
+
+/// Run the commands a `sleep` left, as [`start`] does, with a fresh
+/// [`COMMAND_BUDGET`]. Variables in them were expanded when the line was
+/// first parsed.
+pub fn resume(
+    rest: &[Pipeline],
+    vars: &Vars,
+    mut exec: impl FnMut(&[&str]) -> CommandOutput,
+) -> (CommandOutput, Option<Pending>) {
+    chain(rest, vars, &mut exec, true, &mut Budget::new())
+}
+
+/// Run `pipelines` in order. With `can_sleep`, a `sleep` stops the chain
+/// and the commands after it are returned; otherwise it is an error. The
+/// chain also stops once `budget` runs out.
+fn chain(
+    pipelines: &[Pipeline],
+    vars: &Vars,
+    exec: &mut dyn FnMut(&[&str]) -> CommandOutput,
+    can_sleep: bool,
+    budget: &mut Budget,
+) -> (CommandOutput, Option<Pending>) {
+    let mut outputs = Vec::with_capacity(pipelines.len());
+    let mut pending = None;
+    for (index, pipeline) in pipelines.iter().enumerate() {
+        let words: Vec<&str> = pipeline.command.iter().map(String::as_str).collect();
+        if let (true, ["sleep", args @ ..]) = (can_sleep, words.as_slice()) {
+            match sleep_delay(args) {
+                Ok(delay) => {
+                    let rest = pipelines[index + 1..].to_vec();
+                    if !rest.is_empty() {
+                        pending = Some(Pending { delay, rest });
+                    }
+                    break;
+                }
+                Err(err) => {
+                    outputs.push(CommandOutput::Error(vec![err]));
+                    continue;
+                }
+            }
+        }
+        let mut output = step(&words, vars, exec, budget);
+        for filter in &pipeline.filters {
+            if stops_chain(&output) {
+                break;
+            }
+            output = apply_filter(output, filter);
+        }
+        let stop = stops_chain(&output) || budget.exhausted;
+        outputs.push(output);
+        if stop {
+            break;
+        }
+    }
+    (collect(outputs), pending)
+}
+
+/// One output, or a [`CommandOutput::Multi`] of several.
+fn collect(mut outputs: Vec<CommandOutput>) -> CommandOutput {
+    match outputs.len() {
+        0 => CommandOutput::Lines(Vec::new()),
+        1 => outputs.remove(0),
+        _ => CommandOutput::Multi(outputs),
+    }
+}
+
+/// Run one command, handling `repeat` and `when` here and passing anything
+/// else to `exec` while `budget` allows.
+fn step(
+    words: &[&str],
+    vars: &Vars,
+    exec: &mut dyn FnMut(&[&str]) -> CommandOutput,
+    budget: &mut Budget,
+) -> CommandOutput {
+    match words {
+        ["sleep", ..] => CommandOutput::Error(vec![
+            "sleep only works between ';'-chained commands typed in the console".to_string(),
+        ]),
+        ["repeat", count, command @ ..] if !command.is_empty() => {
+            let times = match count.parse::<usize>() {
+                Ok(times) if (1..=REPEAT_LIMIT).contains(&times) => times,
+                _ => {
+                    return CommandOutput::Error(vec![format!(
+                        "repeat count must be between 1 and {REPEAT_LIMIT}"
+                    )])
+                }
+            };
+            // Stop early rather than repeat a failure
+            let mut outputs = Vec::with_capacity(times);
+            for _ in 0..times {
+                let output = nested(command, vars, exec, budget);
+                let stop = stops_chain(&output) || output.is_error();
+                outputs.push(output);
+                if stop {
+                    break;
+                }
+            }
+            collect(outputs)
+        }
+        ["when", name, op, value, command @ ..] if !command.is_empty() => {
+            match compare(vars, name, op, value) {
+                Ok(true) => nested(command, vars, exec, budget),
+                Ok(false) => CommandOutput::Lines(Vec::new()),
+                Err(err) => CommandOutput::Error(vec![err]),
+            }
+        }
+        [name @ ("repeat" | "when"), ..] => {
+            let usage = CONTROL_COMMANDS
+                .iter()
+                .find(|(control, ..)| control == name)
+                .map_or("", |(_, usage, _)| usage);
+            CommandOutput::Error(vec![format!("usage: {usage}")])
+        }
+        _ if budget.commands == 0 => {
+            budget.exhausted = true;
+            CommandOutput::Error(vec![format!(
+                "stopped: a line may run at most {COMMAND_BUDGET} commands"
+            )])
+        }
+        _ => {
+            budget.commands -= 1;
+            exec(words)
+        }
+    }
+}
+
+/// Run the command given to `repeat` or `when`: its words, or a single
+/// quoted word holding a whole command line.
+fn nested(
+    command: &[&str],
+    vars: &Vars,
+    exec: &mut dyn FnMut(&[&str]) -> CommandOutput,
+    budget: &mut Budget,
+) -> CommandOutput {
+    if budget.depth == DEPTH_LIMIT {
+        return CommandOutput::Error(vec![format!(
+            "repeat and when nest at most {DEPTH_LIMIT} deep"
+        )]);
+    }
+    budget.depth += 1;
+    let output = match command {
+        [line] if line.contains([' ', ';', '|']) => match parse(line, vars) {
+            Ok(pipelines) => chain(&pipelines, vars, exec, false, budget).0,
+            Err(err) => CommandOutput::Error(vec![err]),
+        },
+        _ => step(command, vars, exec, budget),
+    };
+    budget.depth -= 1;
+    output
+}
+
+fn sleep_delay(args: &[&str]) -> Result<Duration, String> {
+    let [duration] = args else {
+        return Err("usage: sleep <duration>".to_string());
+    };
+    let delay = parse_duration(duration).map_err(|err| err.to_string())?;
+    if delay > SLEEP_LIMIT {
+        return Err("sleep is limited to 1h".to_string());
+    }
+    Ok(delay)
+}
+
+/// Evaluate `when`'s `<name> <op> <value>`. `name` is a variable without
+/// its `$`, e.g. `tps` or `stats.cpu`, or a number. Values compare as
+/// numbers when both sides are numbers; otherwise only `==` and `!=` work.
+fn compare(vars: &Vars, name: &str, op: &str, value: &str) -> Result<bool, String> {
+    let current = match vars.get(name) {
+        Some(current) => current,
+        None if name.parse::<f64>().is_ok() => name,
+        None => return Err(format!("unknown variable: {name} (see 'vars')")),
+    };
+    if let (Ok(left), Ok(right)) = (current.parse::<f64>(), value.parse::<f64>()) {
+        return match op {
+            "==" | "=" => Ok(left == right),
+            "!=" => Ok(left != right),
+            "<" => Ok(left < right),
+            "<=" => Ok(left <= right),
+            ">" => Ok(left > right),
+            ">=" => Ok(left >= right),
+            other => Err(unknown_op(other)),
+        };
+    }
+    match op {
+        "==" | "=" => Ok(current == value),
+        "!=" => Ok(current != value),
+        "<" | "<=" | ">" | ">=" => Err(format!(
+            "cannot compare {current:?} {op} {value:?}: not numbers"
+        )),
+        other => Err(unknown_op(other)),
+    }
+}
+
+fn unknown_op(op: &str) -> String {
+    format!("unsupported comparison `{op}` (use ==, !=, <, <=, >, >=)")
+}
+
+/// Whether nothing after `output` should run: the app is quitting, or the
+/// next line belongs to a prompt.
+fn stops_chain(output: &CommandOutput) -> bool {
+    matches!(output, CommandOutput::Quit | CommandOutput::Prompt { .. })
+}
+
+/// Apply one `|` stage to a command's output.
+fn apply_filter(output: CommandOutput, filter: &[String]) -> CommandOutput {
+    let words: Vec<&str> = filter.iter().map(String::as_str).collect();
+    match words.as_slice() {
+        ["grep", rest @ ..] => {
+            let mut invert = false;
+            let mut ignore_case = false;
+            let mut pattern = None;
+            for word in rest {
+                match *word {
+                    "-v" => invert = true,
+                    "-i" => ignore_case = true,
+                    "-vi" | "-iv" => {
+                        invert = true;
+                        ignore_case = true;
+                    }
+                    _ if pattern.is_none() => pattern = Some(*word),
+                    _ => return filter_usage(),
+                }
+            }
+            let Some(pattern) = pattern else {
+                return filter_usage();
+            };
+            let needle = if ignore_case {
+                pattern.to_lowercase()
+            } else {
+                pattern.to_string()
+            };
+            retain(output, &mut |text| {
+                let found = if ignore_case {
+                    text.to_lowercase().contains(&needle)
+                } else {
+                    text.contains(&needle)
+                };
+                found != invert
+            })
+        }
+        ["head", n] | ["tail", n] => {
+            let Ok(n) = n.parse::<usize>() else {
+                return filter_usage();
+            };
+            let from_end = words[0] == "tail";
+            keep_range(output, n, from_end)
+        }
+        _ => filter_usage(),
+    }
+}
+
+fn filter_usage() -> CommandOutput {
+    CommandOutput::Error(vec![format!("usage: <command> | {FILTER_USAGE}")])
+}
+
+/// Keep lines, pairs, or table rows whose text satisfies `keep`.
+fn retain(output: CommandOutput, keep: &mut dyn FnMut(&str) -> bool) -> CommandOutput {
+    match output {
+        CommandOutput::Lines(mut lines) => {
+            lines.retain(|line| keep(line));
+            CommandOutput::Lines(lines)
+        }
+        CommandOutput::Error(mut lines) => {
+            lines.retain(|line| keep(line));
+            CommandOutput::Error(lines)
+        }
+        CommandOutput::KeyValue(mut pairs) => {
+            pairs.retain(|(key, value)| keep(&format!("{key} {value}")));
+            CommandOutput::KeyValue(pairs)
+        }
+        CommandOutput::Table(mut table) => {
+            table.rows.retain(|row| keep(&row.join(" ")));
+            CommandOutput::Table(table)
+        }
+        CommandOutput::Multi(outputs) => CommandOutput::Multi(
+            outputs
+                .into_iter()
+                .map(|output| retain(output, keep))
+                .collect(),
+        ),
+        other @ (CommandOutput::Prompt { .. } | CommandOutput::Quit) => other,
+    }
+}
+
+/// Keep the first (or last) `n` lines, pairs, or table rows.
+fn keep_range(output: CommandOutput, n: usize, from_end: bool) -> CommandOutput {
+    fn cut<T>(mut items: Vec<T>, n: usize, from_end: bool) -> Vec<T> {
+        if from_end {
+            items.split_off(items.len().saturating_sub(n))
+        } else {
+            items.truncate(n);
+            items
+        }
+    }
+    match output {
+        CommandOutput::Lines(lines) => CommandOutput::Lines(cut(lines, n, from_end)),
+        CommandOutput::Error(lines) => CommandOutput::Error(cut(lines, n, from_end)),
+        CommandOutput::KeyValue(pairs) => CommandOutput::KeyValue(cut(pairs, n, from_end)),
+        CommandOutput::Table(mut table) => {
+            table.rows = cut(table.rows, n, from_end);
+            CommandOutput::Table(table)
+        }
+        CommandOutput::Multi(outputs) => CommandOutput::Multi(
+            outputs
+                .into_iter()
+                .map(|output| keep_range(output, n, from_end))
+                .collect(),
+        ),
+        other @ (CommandOutput::Prompt { .. } | CommandOutput::Quit) => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{Column, Table};
+
+    fn words(items: &[&str]) -> Words {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_splits_chains_pipes_and_quotes() {
+        let parsed = parse("echo 'a | b';tps | head 1 ;", &Vars::new()).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                Pipeline {
+                    command: words(&["echo", "a | b"]),
+                    filters: vec![],
+                },
+                Pipeline {
+                    command: words(&["tps"]),
+                    filters: vec![words(&["head", "1"])],
+                },
+            ]
+        );
+        assert!(parse("echo \"open", &Vars::new()).is_err());
+        assert!(parse("modules |", &Vars::new()).is_err());
+    }
+
+    #[test]
+    fn grep_filters_table_rows_and_keeps_header() {
+        let output = run("modules | grep -i STATS", &Vars::new(), |_| {
+            let mut table = Table::new(vec![Column::left("ID")]);
+            table.push_row(vec!["hello".into()]);
+            table.push_row(vec!["stats".into()]);
+            CommandOutput::Table(table)
+        });
+        assert_eq!(output.to_lines(), vec!["  ID", "  stats"]);
+    }
+
+    #[test]
+    fn chain_runs_each_command_and_stops_at_quit() {
+        let mut seen = Vec::new();
+        let output = run("a one two ; quit ; b", &Vars::new(), |words| {
+            seen.push(words.join(" "));
+            if words[0] == "quit" {
+                CommandOutput::Quit
+            } else {
+                CommandOutput::Lines(vec!["x".into(), "y".into(), "z".into()])
+            }
+        });
+        assert_eq!(seen, vec!["a one two", "quit"]);
+        assert!(matches!(output, CommandOutput::Multi(ref parts) if parts.len() == 2));
+        assert_eq!(output.status(), crate::output::CommandStatus::Quit);
+    }
+
+    #[test]
+    fn chain_stops_at_prompt_and_filters_pass_it_through() {
+        let mut seen = Vec::new();
+        let output = run("ask | grep nothing ; b", &Vars::new(), |words| {
+            seen.push(words.join(" "));
+            CommandOutput::prompt("Name?", "name")
+        });
+        assert_eq!(seen, vec!["ask"]);
+        assert_eq!(output, CommandOutput::prompt("Name?", "name"));
+    }
+
+    #[test]
+    fn head_tail_and_bad_filter() {
+        let lines = |_: &[&str]| CommandOutput::Lines(vec!["1".into(), "2".into(), "3".into()]);
+        assert_eq!(
+            run("x | tail 2", &Vars::new(), lines).to_lines(),
+            vec!["2", "3"]
+        );
+        assert_eq!(run("x | head 1", &Vars::new(), lines).to_lines(), vec!["1"]);
+        assert!(run("x | sort", &Vars::new(), lines).is_error());
+    }
+
+    #[test]
+    fn variables_expand_outside_single_quotes() {
+        let mut vars = Vars::new();
+        vars.set("active_module", "stats");
+        let parsed = parse(
+            "echo $active_module \"${active_module}!\" '$active_module' $ ;",
+            &vars,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed[0].command,
+            words(&["echo", "stats", "stats!", "$active_module", "$"])
+        );
+        assert!(parse("echo $missing", &vars)
+            .unwrap_err()
+            .contains("unknown variable"));
+    }
+
+    #[test]
+    fn sleep_hands_back_the_rest_of_the_line() {
+        let mut seen = Vec::new();
+        let mut exec = |words: &[&str]| {
+            seen.push(words.join(" "));
+            CommandOutput::Lines(vec![words.join(" ")])
+        };
+        let (output, pending) = start(
+            "a ; sleep 250ms ; b | head 1 ; sleep 1s ; c",
+            &Vars::new(),
+            &mut exec,
+        );
+        assert_eq!(output.to_lines(), vec!["a"]);
+        let pending = pending.unwrap();
+        assert_eq!(pending.delay, Duration::from_millis(250));
+        assert_eq!(pending.rest.len(), 3);
+
+        let (output, pending) = resume(&pending.rest, &Vars::new(), &mut exec);
+        assert_eq!(output.to_lines(), vec!["b"]);
+        let (_, last) = resume(&pending.unwrap().rest, &Vars::new(), &mut exec);
+        assert_eq!(last, None);
+        assert_eq!(seen, vec!["a", "b", "c"]);
+
+        let (output, pending) = start("sleep soon ; a", &Vars::new(), |_| {
+            CommandOutput::Lines(vec![])
+        });
+        assert!(matches!(output, CommandOutput::Multi(ref parts) if parts[0].is_error()));
+        assert_eq!(pending, None);
+        assert!(
+            run("a ; sleep 1s ; b", &Vars::new(), |_| CommandOutput::Lines(
+                vec![]
+            ))
+            .is_error()
+        );
+    }
+
+    #[test]
+    fn repeat_runs_commands_and_quoted_lines() {
+        let mut seen = Vec::new();
+        let output = run("repeat 2 'a ; b x' ; repeat 3 c", &Vars::new(), |words| {
+            seen.push(words.join(" "));
+            CommandOutput::Lines(vec![])
+        });
+        assert!(!output.is_error());
+        assert_eq!(seen, vec!["a", "b x", "a", "b x", "c", "c", "c"]);
+
+        let mut runs = 0;
+        let output = run("repeat 5 boom", &Vars::new(), |_| {
+            runs += 1;
+            CommandOutput::Error(vec!["boom".into()])
+        });
+        assert_eq!(runs, 1);
+        assert!(output.is_error());
+        let none = |_: &[&str]| CommandOutput::Lines(vec![]);
+        assert!(run("repeat 0 a", &Vars::new(), none).is_error());
+        assert!(run("repeat 101 a", &Vars::new(), none).is_error());
+        assert!(run("repeat 2", &Vars::new(), none).is_error());
+        assert!(run("repeat 2 'sleep 1s ; a'", &Vars::new(), none).is_error());
+    }
+
+    #[test]
+    fn nested_repeats_share_one_budget_and_depth_is_capped() {
+        let mut runs = 0;
+        let output = run(
+            "repeat 100 repeat 100 repeat 10 echo x ; b",
+            &Vars::new(),
+            |_| {
+                runs += 1;
+                CommandOutput::Lines(vec![])
+            },
+        );
+        assert_eq!(runs, COMMAND_BUDGET);
+        assert!(output
+            .to_lines()
+            .iter()
+            .any(|line| line.contains("at most")));
+
+        let mut runs = 0;
+        let too_deep = format!("{}a", "when 1 == 1 ".repeat(DEPTH_LIMIT + 1));
+        let output = run(&too_deep, &Vars::new(), |_| {
+            runs += 1;
+            CommandOutput::Lines(vec![])
+        });
+        assert_eq!(runs, 0);
+        assert!(output.is_error());
+        let within = format!("{}a", "when 1 == 1 ".repeat(DEPTH_LIMIT));
+        assert!(!run(&within, &Vars::new(), |_| CommandOutput::Lines(vec![])).is_error());
+    }
+
+    #[test]
+    fn when_compares_variables_as_numbers_or_text() {
+        let mut vars = Vars::new();
+        vars.set("stats.cpu", "81.5");
+        vars.set("active_module", "stats");
+        let ran = |line: &str| {
+            let mut ran = false;
+            let output = run(line, &vars, |_| {
+                ran = true;
+                CommandOutput::Lines(vec![])
+            });
+            (ran, output.is_error())
+        };
+        assert_eq!(ran("when stats.cpu > 80 alert"), (true, false));
+        assert_eq!(ran("when stats.cpu <= 80 alert"), (false, false));
+        assert_eq!(ran("when stats.cpu == 81.50 alert"), (true, false));
+        assert_eq!(ran("when active_module == stats alert"), (true, false));
+        assert_eq!(ran("when active_module != stats alert"), (false, false));
+        assert_eq!(ran("when active_module > 3 alert"), (false, true));
+        assert_eq!(ran("when nope == 1 alert"), (false, true));
+        assert_eq!(ran("when stats.cpu ~ 1 alert"), (false, true));
+        assert_eq!(ran("when stats.cpu > 80"), (false, true));
+    }
+}
@@ -1,19 +1,80 @@
 use std::collections::HashMap;
 
-use anyhow::{bail, Result};
+use serde::Serialize;
+use spud_config::AppConfig;
 
-use crate::event::Event;
-use crate::module::Module;
+use crate::error::{CoreError, CoreResult};
+use crate::event::{ActivationReason, AttentionLevel, Event};
+use crate::keymap;
+use crate::module::{self, Module};
+use crate::rng::RngProvider;
+use crate::schema::EventSchemas;
+
+/// Most module IDs remembered for [`ModuleRegistry::back`].
+const HISTORY_LIMIT: usize = 32;
+
+/// Serializable view of a [`ModuleRegistry`], for state dumps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RegistrySnapshot {
+    pub active: Option<String>,
+    /// Back stack, most recent last.
+    pub history: Vec<String>,
+    /// In registration order.
+    pub modules: Vec<ModuleSnapshot>,
+}
+
+/// One entry of [`RegistrySnapshot::modules`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleSnapshot {
+    pub id: String,
+    pub title: String,
+    /// Pending attention badge, if any.
+    pub attention: Option<Attention>,
+    /// Panic message, if the module crashed.
+    pub crashed: Option<String>,
+}
+
+/// Attention a background module has asked for since it was last active,
+/// shown as a badge on its tab.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Attention {
+    /// Most urgent level requested.
+    pub level: AttentionLevel,
+    /// Number of requests.
+    pub count: u32,
+}
 
 /// Owns and manages all registered SPUD modules.
 ///
 /// Modules are stored in insertion order and indexed by their unique
 /// [`Module::id`]. The registry tracks which module is currently active and
 /// provides cycling, activation, and event broadcasting.
+///
+/// Every activation pushes the module it replaces onto a back stack, so
+/// [`back`](Self::back) can return to it; going back pops instead.
+///
+/// Inactive modules can ask for attention with
+/// [`Event::AttentionRequested`]; the registry keeps a badge per module and
+/// clears it when the module is activated.
+///
+/// Each module is handed its own [`Rng`](crate::rng::Rng) stream, named
+/// after its ID, when it is registered.
+///
+/// Module code the registry calls runs under [`module::isolate`]. A module
+/// that panics is marked crashed: it stays registered, so its tab and an
+/// error panel can still be shown, but gets no more events until it is
+/// [`replace`](Self::replace)d with a fresh instance.
 pub struct ModuleRegistry {
     modules: Vec<Box<dyn Module>>,
     active_idx: Option<usize>,
     index: HashMap<String, usize>,
+    /// Back stack of module IDs, most recent last.
+    history: Vec<String>,
+    /// Pending badges by module ID.
+    attention: HashMap<String, Attention>,
+    /// Panic messages of crashed modules by module ID.
+    crashed: HashMap<String, String>,
+    rng: RngProvider,
 }
 
 impl Default for ModuleRegistry {
@@ -23,25 +84,41 @@ impl Default for ModuleRegistry {
 }
 
 impl ModuleRegistry {
-    /// Create an empty registry with no modules.
+    /// Create an empty registry with no modules, seeding module RNGs with 0.
     pub fn new() -> Self {
+        Self::with_rng(RngProvider::new(0))
+    }
+
+    /// Create an empty registry whose modules draw from `rng`.
+    pub fn with_rng(rng: RngProvider) -> Self {
         Self {
             modules: Vec::new(),
             active_idx: None,
             index: HashMap::new(),
+            history: Vec::new(),
+            attention: HashMap::new(),
+            crashed: HashMap::new(),
+            rng,
         }
     }
 
+    /// The provider module RNG streams are derived from.
+    pub fn rng(&self) -> RngProvider {
+        self.rng
+    }
+
     /// Register a module. The first module registered is automatically activated.
     ///
     /// # Errors
     ///
-    /// Returns an error if a module with the same ID is already registered.
-    pub fn register(&mut self, module: Box<dyn Module>) -> Result<()> {
+    /// Returns [`CoreError::DuplicateModule`] if a module with the same ID is
+    /// already registered.
+    pub fn register(&mut self, mut module: Box<dyn Module>) -> CoreResult<()> {
         let id = module.id().to_string();
         if self.index.contains_key(&id) {
-            bail!("duplicate module id: {}", id);
+            return Err(CoreError::DuplicateModule(id));
         }
+        module.seed_rng(self.rng.stream(&id));
         let idx = self.modules.len();
         self.index.insert(id, idx);
         self.modules.push(module);
@@ -51,6 +128,104 @@ impl ModuleRegistry {
         Ok(())
     }
 
+    /// Remove a module by ID, returning it along with lifecycle events.
+    ///
+    /// If the removed module was active, a `ModuleDeactivated` event is
+    /// returned for it, and the module that takes its slot (the next one, or
+    /// the previous one when the last module is removed) is activated like
+    /// any switch, clearing its attention badge. The registry is left with
+    /// no active module once it is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CoreError::UnknownModule`] if no module with the given ID
+    /// exists.
+    pub fn unregister(&mut self, id: &str) -> CoreResult<(Box<dyn Module>, Vec<Event>)> {
+        let Some(idx) = self.index.get(id).copied() else {
+            return Err(CoreError::UnknownModule(id.to_string()));
+        };
+
+        let module = self.modules.remove(idx);
+        self.rebuild_index();
+        self.history.retain(|entry| entry != id);
+        self.attention.remove(id);
+        self.crashed.remove(id);
+
+        let mut events = Vec::new();
+        match self.active_idx {
+            Some(active) if active == idx => {
+                events.push(Event::ModuleDeactivated { id: id.to_string() });
+                if self.modules.is_empty() {
+                    self.active_idx = None;
+                } else {
+                    let next = idx.min(self.modules.len() - 1);
+                    events.extend(self.switch_to(None, next, ActivationReason::Fallback));
+                }
+            }
+            Some(active) if active > idx => self.active_idx = Some(active - 1),
+            _ => {}
+        }
+
+        Ok((module, events))
+    }
+
+    /// Swap the module registered as `id` for `module`, keeping its position.
+    ///
+    /// Returns the previous module. The replacement starts healthy, even if
+    /// the old module crashed. When the replaced module was active, the
+    /// replacement becomes active and `ModuleDeactivated` / `ModuleActivated`
+    /// events are returned for the old and new module.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CoreError::UnknownModule`] if `id` is not registered, or
+    /// [`CoreError::DuplicateModule`] if the replacement's ID differs from
+    /// `id` and is already used by another module.
+    pub fn replace(
+        &mut self,
+        id: &str,
+        mut module: Box<dyn Module>,
+    ) -> CoreResult<(Box<dyn Module>, Vec<Event>)> {
+        let Some(idx) = self.index.get(id).copied() else {
+            return Err(CoreError::UnknownModule(id.to_string()));
+        };
+        let new_id = module.id();
+        if new_id != id && self.index.contains_key(new_id) {
+            return Err(CoreError::DuplicateModule(new_id.to_string()));
+        }
+        module.seed_rng(self.rng.stream(new_id));
+
+        let old = std::mem::replace(&mut self.modules[idx], module);
+        self.rebuild_index();
+        let new_id = self.modules[idx].id().to_string();
+        for entry in self.history.iter_mut().filter(|entry| *entry == id) {
+            entry.clone_from(&new_id);
+        }
+        self.attention.remove(id);
+        self.crashed.remove(id);
+
+        let mut events = Vec::new();
+        if self.active_idx == Some(idx) {
+            events.push(Event::ModuleDeactivated { id: id.to_string() });
+            events.push(Event::ModuleActivated {
+                id: new_id,
+                reason: ActivationReason::Fallback,
+            });
+        }
+
+        Ok((old, events))
+    }
+
+    /// Recompute the ID → position index after the module list changes.
+    fn rebuild_index(&mut self) {
+        self.index = self
+            .modules
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.id().to_string(), i))
+            .collect();
+    }
+
     /// Activate a module by ID, returning lifecycle events.
     ///
     /// Returns a `ModuleDeactivated` event for the previously active module
@@ -58,25 +233,39 @@ impl ModuleRegistry {
     ///
     /// # Errors
     ///
-    /// Returns an error if no module with the given ID exists.
-    pub fn activate(&mut self, id: &str) -> Result<Vec<Event>> {
-        let idx = self.index.get(id).copied();
-        match idx {
-            Some(i) => {
-                let mut events = Vec::new();
-                if let Some(old) = self.active_idx {
-                    if old != i {
-                        events.push(Event::ModuleDeactivated {
-                            id: self.modules[old].id().to_string(),
-                        });
-                    }
-                }
-                self.active_idx = Some(i);
-                events.push(Event::ModuleActivated { id: id.to_string() });
-                Ok(events)
+    /// Returns [`CoreError::UnknownModule`] if no module with the given ID
+    /// exists.
+    pub fn activate(&mut self, id: &str) -> CoreResult<Vec<Event>> {
+        self.activate_with(id, ActivationReason::Switch)
+    }
+
+    /// Like [`activate`](Self::activate), tagging the `ModuleActivated`
+    /// event with `reason`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CoreError::UnknownModule`] if no module with the given ID
+    /// exists.
+    pub fn activate_with(&mut self, id: &str, reason: ActivationReason) -> CoreResult<Vec<Event>> {
+        match self.index.get(id).copied() {
+            Some(i) => Ok(self.switch_to(self.active_idx, i, reason)),
+            None => Err(CoreError::UnknownModule(id.to_string())),
+        }
+    }
+
+    /// Return to the module active before the current one, popping it off
+    /// the back stack. Returns no events when there is nothing to go back
+    /// to.
+    pub fn back(&mut self) -> Vec<Event> {
+        while let Some(id) = self.history.pop() {
+            let Some(to) = self.index.get(&id).copied() else {
+                continue;
+            };
+            if Some(to) != self.active_idx {
+                return self.switch_to(self.active_idx, to, ActivationReason::Back);
             }
-            None => bail!("unknown module id: {}", id),
         }
+        Vec::new()
     }
 
     /// Return a reference to the currently active module, or `None` if empty.
@@ -94,6 +283,45 @@ impl ModuleRegistry {
         self.active_idx.map(|i| self.modules[i].id())
     }
 
+    /// Return the ID of the module that was active before the current one,
+    /// i.e. where [`back`](Self::back) would go.
+    pub fn previous_id(&self) -> Option<&str> {
+        let active = self.active_id();
+        self.history
+            .iter()
+            .rev()
+            .map(String::as_str)
+            .find(|id| self.index.contains_key(*id) && Some(*id) != active)
+    }
+
+    /// The back stack of module IDs, most recent last.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Record an attention request from module `id`, raising its badge to
+    /// `level` if that is more urgent. Returns `false`, recording nothing,
+    /// if `id` is unknown or already active.
+    pub fn request_attention(&mut self, id: &str, level: AttentionLevel) -> bool {
+        if !self.index.contains_key(id) || self.active_id() == Some(id) {
+            return false;
+        }
+        self.attention
+            .entry(id.to_string())
+            .and_modify(|badge| {
+                badge.level = badge.level.max(level);
+                badge.count = badge.count.saturating_add(1);
+            })
+            .or_insert(Attention { level, count: 1 });
+        true
+    }
+
+    /// The pending badge for module `id`, if it has asked for attention
+    /// since it was last active.
+    pub fn attention(&self, id: &str) -> Option<Attention> {
+        self.attention.get(id).copied()
+    }
+
     /// Cycle to the next module (wrapping around), returning lifecycle events.
     pub fn cycle_next(&mut self) -> Vec<Event> {
         if self.modules.is_empty() {
@@ -101,7 +329,7 @@ impl ModuleRegistry {
         }
         let cur = self.active_idx.unwrap_or(0);
         let next = (cur + 1) % self.modules.len();
-        self.switch_to(cur, next)
+        self.switch_to(Some(cur), next, ActivationReason::Cycle)
     }
 
     /// Cycle to the previous module (wrapping around), returning lifecycle events.
@@ -115,27 +343,64 @@ impl ModuleRegistry {
         } else {
             cur - 1
         };
-        self.switch_to(cur, next)
+        self.switch_to(Some(cur), next, ActivationReason::Cycle)
     }
 
-    /// Internal helper to switch between two module indices.
-    fn switch_to(&mut self, from: usize, to: usize) -> Vec<Event> {
+    /// Internal helper to switch between two module indices. Leaving a
+    /// module pushes it onto the back stack, except when going back.
+    fn switch_to(
+        &mut self,
+        from: Option<usize>,
+        to: usize,
+        reason: ActivationReason,
+    ) -> Vec<Event> {
         let mut events = Vec::new();
-        if from != to {
-            events.push(Event::ModuleDeactivated {
-                id: self.modules[from].id().to_string(),
-            });
+        if let Some(from) = from.filter(|from| *from != to) {
+            let from_id = self.modules[from].id().to_string();
+            if reason != ActivationReason::Back {
+                self.history.push(from_id.clone());
+                if self.history.len() > HISTORY_LIMIT {
+                    self.history.remove(0);
+                }
+            }
+            events.push(Event::ModuleDeactivated { id: from_id });
         }
         self.active_idx = Some(to);
+        self.attention.remove(self.modules[to].id());
         events.push(Event::ModuleActivated {
             id: self.modules[to].id().to_string(),
+            reason,
         });
         events
     }
 
     /// Return `(id, title)` pairs for all registered modules in order.
     pub fn list(&self) -> Vec<(&str, &str)> {
-        self.modules.iter().map(|m| (m.id(), m.title())).collect()
+        self.iter().collect()
+    }
+
+    /// Like [`list`](Self::list), without collecting, for code that runs
+    /// every frame.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {
+        self.modules.iter().map(|m| (m.id(), m.title()))
+    }
+
+    /// Serializable view for state dumps.
+    pub fn snapshot(&self) -> RegistrySnapshot {
+        RegistrySnapshot {
+            active: self.active_id().map(str::to_string),
+            history: self.history.clone(),
+            modules: self
+                .modules
+                .iter()
+                .map(|m| ModuleSnapshot {
+                    id: m.id().to_string(),
+                    title: m.title().to_string(),
+                    attention: self.attention(m.id()),
+                    crashed: self.crash(m.id()).map(str::to_string),
+                })
+                .collect(),
+        }
     }
 
     /// Look up a module by ID.
@@ -158,38 +423,128 @@ impl ModuleRegistry {
         self.modules.is_empty()
     }
 
+    /// Pass the current settings to every healthy module's
+    /// [`configure`](Module::configure).
+    pub fn configure(&mut self, config: &AppConfig) {
+        for idx in 0..self.modules.len() {
+            let m = &mut self.modules[idx];
+            let id = m.id();
+            if self.crashed.contains_key(id) {
+                continue;
+            }
+            if let Err(reason) = module::isolate(|| m.configure(config)) {
+                self.mark_crashed(id, reason);
+            }
+        }
+    }
+
+    /// `(usage, description)` of every module's console command, in
+    /// registry order.
+    pub fn console_usage(&self) -> Vec<(&'static str, &'static str)> {
+        self.modules
+            .iter()
+            .flat_map(|m| m.console_usage())
+            .collect()
+    }
+
+    /// Register every module's event schemas in `schemas`, owned by the
+    /// module id. Returns the registrations that were refused, as
+    /// `(module id, tag, error)`.
+    pub fn register_event_schemas(
+        &self,
+        schemas: &mut EventSchemas,
+    ) -> Vec<(&'static str, &'static str, CoreError)> {
+        let mut refused = Vec::new();
+        for m in &self.modules {
+            for (tag, schema) in m.event_schemas() {
+                if let Err(err) = schemas.register(m.id(), tag, schema) {
+                    refused.push((m.id(), tag, err));
+                }
+            }
+        }
+        refused
+    }
+
+    /// Collect pending outbound events from every healthy module, in
+    /// registry order.
+    pub fn drain_events(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        for idx in 0..self.modules.len() {
+            let m = &mut self.modules[idx];
+            let id = m.id();
+            if self.crashed.contains_key(id) {
+                continue;
+            }
+            match module::isolate(|| m.drain_events()) {
+                Ok(drained) => events.extend(drained),
+                Err(reason) => self.mark_crashed(id, reason),
+            }
+        }
+        events
+    }
+
+    /// The panic message of `id`, if it crashed.
+    pub fn crash(&self, id: &str) -> Option<&str> {
+        self.crashed.get(id).map(String::as_str)
+    }
+
+    /// Mark `id` as crashed after a panic outside the registry, e.g. while
+    /// rendering. It gets no more events until it is replaced.
+    pub fn mark_crashed(&mut self, id: &str, reason: String) {
+        tracing::error!(module = id, "module {id} crashed: {reason}");
+        self.crashed.insert(id.to_string(), reason);
+    }
+
+    /// Hand `event` to the module at `idx` unless it crashed, marking it
+    /// crashed if it panics.
+    fn deliver(&mut self, idx: usize, event: &Event) {
+        let m = &mut self.modules[idx];
+        let id = m.id();
+        if self.crashed.contains_key(id) {
+            return;
+        }
+        let handled = module::isolate(|| match event {
+            Event::Key(key) => match keymap::action_for(&m.keybindings(), key) {
+                Some(action) => m.handle_action(action),
+                None => m.handle_event(event),
+            },
+            _ => m.handle_event(event),
+        });
+        if let Err(reason) = handled {
+            self.mark_crashed(id, reason);
+        }
+    }
+
     /// Broadcast an event to modules.
     ///
     /// Routing rules:
     /// - `Tick` and `Resize` — sent to **all** modules.
-    /// - `Key` — sent to the **active** module only.
+    /// - `Key` — sent to the **active** module only, as an action if one of
+    ///   its [`Module::keybindings`] matches.
     /// - `ModuleActivated` / `ModuleDeactivated` — sent to the **named** module.
+    /// - `AttentionRequested` — recorded as a badge, not sent to modules.
     /// - Everything else (`Telemetry`, `Custom`, `Quit`) — sent to **all** modules.
+    ///
+    /// Crashed modules are skipped, and a module that panics is marked
+    /// crashed.
     pub fn broadcast(&mut self, event: &Event) {
         match event {
-            Event::Tick { .. } | Event::Resize { .. } => {
-                for m in &mut self.modules {
-                    m.handle_event(event);
-                }
-            }
             Event::Key(_) => {
-                if let Some(m) = self.active_mut() {
-                    m.handle_event(event);
+                if let Some(idx) = self.active_idx {
+                    self.deliver(idx, event);
                 }
             }
-            Event::ModuleActivated { id } => {
+            Event::ModuleActivated { id, .. } | Event::ModuleDeactivated { id } => {
                 if let Some(idx) = self.index.get(id).copied() {
-                    self.modules[idx].handle_event(event);
+                    self.deliver(idx, event);
                 }
             }
-            Event::ModuleDeactivated { id } => {
-                if let Some(idx) = self.index.get(id).copied() {
-                    self.modules[idx].handle_event(event);
-                }
+            Event::AttentionRequested { id, level } => {
+                self.request_attention(id, *level);
             }
             _ => {
-                for m in &mut self.modules {
-                    m.handle_event(event);
+                for idx in 0..self.modules.len() {
+                    self.deliver(idx, event);
                 }
             }
         }
@@ -248,11 +603,80 @@ mod tests {
                 .unwrap()
                 .push(format!("{}:{}", self.id, tag));
         }
+        fn keybindings(&self) -> Vec<keymap::KeyBinding> {
+            vec![keymap::KeyBinding::new(
+                keymap::KeyChord::char('z'),
+                "zap",
+                "zap",
+            )]
+        }
+        fn handle_action(&mut self, action: keymap::ActionId) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{}:action:{action}", self.id));
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    /// Panics on every key, counting the events it got first.
+    struct PanickyModule(u32);
+
+    impl Module for PanickyModule {
+        fn id(&self) -> &'static str {
+            "panicky"
+        }
+        fn title(&self) -> &'static str {
+            "Panicky"
+        }
+        fn handle_event(&mut self, ev: &Event) {
+            self.0 += 1;
+            if matches!(ev, Event::Key(_)) {
+                panic!("bad key");
+            }
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    /// Remembers the first number drawn from its seeded stream.
+    struct SeededModule(Option<u64>);
+
+    impl Module for SeededModule {
+        fn id(&self) -> &'static str {
+            "seeded"
+        }
+        fn title(&self) -> &'static str {
+            "Seeded"
+        }
+        fn seed_rng(&mut self, mut rng: crate::rng::Rng) {
+            self.0 = Some(rng.next_u64());
+        }
         fn as_any(&self) -> &dyn std::any::Any {
             self
         }
     }
 
+    #[test]
+    fn register_seeds_module_rng_from_provider() {
+        let first = |seed| {
+            let mut reg = ModuleRegistry::with_rng(RngProvider::new(seed));
+            reg.register(Box::new(SeededModule(None))).unwrap();
+            let module = reg.get("seeded").unwrap();
+            module.downcast_ref::<SeededModule>().unwrap().0.unwrap()
+        };
+        assert_eq!(first(9), first(9));
+        assert_ne!(first(9), first(10));
+        assert_eq!(
+            first(9),
+            RngProvider::new(9).stream("seeded").next_u64(),
+            "stream is named after the module id"
+        );
+    }
+
     #[test]
     fn register_adds_module() {
         let mut reg = ModuleRegistry::new();
@@ -272,6 +696,22 @@ mod tests {
         assert!(err.unwrap_err().to_string().contains("duplicate module id"));
     }
 
+    #[test]
+    fn registry_errors_are_typed() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        let err = reg
+            .register(Box::new(FakeModule::new("a", "Alpha2")))
+            .unwrap_err();
+        assert_eq!(err, CoreError::DuplicateModule("a".into()));
+        assert_eq!(err.to_string(), "duplicate module id: a");
+
+        let err = reg.activate("nope").unwrap_err();
+        assert_eq!(err, CoreError::UnknownModule("nope".into()));
+        assert_eq!(err.to_string(), "unknown module id: nope");
+    }
+
     #[test]
     fn activate_by_id() {
         let mut reg = ModuleRegistry::new();
@@ -283,6 +723,22 @@ mod tests {
         assert_eq!(reg.active().unwrap().id(), "b");
     }
 
+    #[test]
+    fn switching_tracks_the_previous_module() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+        assert_eq!(reg.previous_id(), None);
+        reg.activate("b").unwrap();
+        assert_eq!(reg.previous_id(), Some("a"));
+        reg.cycle_next();
+        assert_eq!(reg.previous_id(), Some("b"));
+        reg.unregister("b").unwrap();
+        assert_eq!(reg.previous_id(), None);
+    }
+
     #[test]
     fn activate_invalid_id_returns_error() {
         let mut reg = ModuleRegistry::new();
@@ -303,7 +759,7 @@ mod tests {
         let events = reg.activate("b").unwrap();
         assert_eq!(events.len(), 2);
         assert!(matches!(&events[0], Event::ModuleDeactivated { id } if id == "a"));
-        assert!(matches!(&events[1], Event::ModuleActivated { id } if id == "b"));
+        assert!(matches!(&events[1], Event::ModuleActivated { id, .. } if id == "b"));
     }
 
     #[test]
@@ -350,7 +806,7 @@ mod tests {
         let events = reg.cycle_next();
         assert_eq!(events.len(), 2);
         assert!(matches!(&events[0], Event::ModuleDeactivated { id } if id == "a"));
-        assert!(matches!(&events[1], Event::ModuleActivated { id } if id == "b"));
+        assert!(matches!(&events[1], Event::ModuleActivated { id, .. } if id == "b"));
     }
 
     #[test]
@@ -388,6 +844,16 @@ mod tests {
         assert!(reg.active().is_none());
     }
 
+    #[test]
+    fn modules_without_renderer_downcast_by_type() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        let module = reg.get("a").unwrap();
+        assert!(module.as_hero_renderer().is_none());
+        assert_eq!(module.downcast_ref::<FakeModule>().unwrap().title, "Alpha");
+    }
+
     #[test]
     fn get_and_get_mut_by_id() {
         let mut reg = ModuleRegistry::new();
@@ -399,6 +865,169 @@ mod tests {
         assert!(reg.get_mut("z").is_none());
     }
 
+    #[test]
+    fn activation_pushes_previous_module_and_back_pops_it() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("c", "Gamma")))
+            .unwrap();
+        assert!(reg.back().is_empty());
+
+        reg.activate("c").unwrap();
+        reg.cycle_next();
+        assert_eq!(reg.active_id(), Some("a"));
+        assert_eq!(reg.history(), ["a", "c"]);
+        assert_eq!(reg.previous_id(), Some("c"));
+
+        let events = reg.back();
+        assert!(matches!(&events[0], Event::ModuleDeactivated { id } if id == "a"));
+        assert!(matches!(
+            &events[1],
+            Event::ModuleActivated { id, reason: ActivationReason::Back } if id == "c"
+        ));
+        assert_eq!(reg.history(), ["a"]);
+
+        reg.back();
+        assert_eq!(reg.active_id(), Some("a"));
+        assert!(reg.history().is_empty());
+        assert!(reg.back().is_empty());
+    }
+
+    #[test]
+    fn back_skips_unregistered_modules() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("c", "Gamma")))
+            .unwrap();
+        reg.activate("b").unwrap();
+        reg.activate("c").unwrap();
+        reg.unregister("b").unwrap();
+
+        assert_eq!(reg.previous_id(), Some("a"));
+        reg.back();
+        assert_eq!(reg.active_id(), Some("a"));
+    }
+
+    #[test]
+    fn attention_badges_accumulate_until_activated() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+
+        assert!(!reg.request_attention("a", AttentionLevel::Alert));
+        assert!(!reg.request_attention("z", AttentionLevel::Info));
+        reg.broadcast(&Event::AttentionRequested {
+            id: "b".into(),
+            level: AttentionLevel::Warn,
+        });
+        reg.request_attention("b", AttentionLevel::Info);
+        assert_eq!(
+            reg.attention("b"),
+            Some(Attention {
+                level: AttentionLevel::Warn,
+                count: 2
+            })
+        );
+        assert_eq!(reg.snapshot().modules[1].attention.unwrap().count, 2);
+
+        reg.activate("b").unwrap();
+        assert_eq!(reg.attention("b"), None);
+    }
+
+    #[test]
+    fn fallback_activation_clears_the_attention_badge() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+        reg.request_attention("b", AttentionLevel::Warn);
+        assert!(reg.attention("b").is_some());
+
+        reg.unregister("a").unwrap();
+        assert_eq!(reg.active_id(), Some("b"));
+        assert_eq!(reg.attention("b"), None);
+        assert_eq!(reg.previous_id(), None);
+    }
+
+    #[test]
+    fn unregister_active_activates_next() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("c", "Gamma")))
+            .unwrap();
+        reg.activate("b").unwrap();
+
+        let (removed, events) = reg.unregister("b").unwrap();
+        assert_eq!(removed.id(), "b");
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], Event::ModuleDeactivated { id } if id == "b"));
+        assert!(matches!(&events[1], Event::ModuleActivated { id, .. } if id == "c"));
+        assert_eq!(reg.active_id(), Some("c"));
+        assert!(reg.get("b").is_none());
+        assert_eq!(reg.get("c").unwrap().title(), "Gamma");
+    }
+
+    #[test]
+    fn unregister_before_active_keeps_active_module() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+        reg.activate("b").unwrap();
+
+        let (_, events) = reg.unregister("a").unwrap();
+        assert!(events.is_empty());
+        assert_eq!(reg.active_id(), Some("b"));
+        assert_eq!(reg.list(), vec![("b", "Beta")]);
+    }
+
+    #[test]
+    fn unregister_last_module_empties_registry() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+
+        let (_, events) = reg.unregister("a").unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(reg.active().is_none());
+        assert!(reg.is_empty());
+        assert!(reg.unregister("a").is_err());
+    }
+
+    #[test]
+    fn replace_active_module_keeps_position() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(FakeModule::new("a", "Alpha")))
+            .unwrap();
+        reg.register(Box::new(FakeModule::new("b", "Beta")))
+            .unwrap();
+
+        let (old, events) = reg
+            .replace("a", Box::new(FakeModule::new("a2", "Alpha II")))
+            .unwrap();
+        assert_eq!(old.title(), "Alpha");
+        assert!(matches!(&events[0], Event::ModuleDeactivated { id } if id == "a"));
+        assert!(matches!(&events[1], Event::ModuleActivated { id, .. } if id == "a2"));
+        assert_eq!(reg.list(), vec![("a2", "Alpha II"), ("b", "Beta")]);
+        assert!(reg.get("a").is_none());
+
+        let err = reg.replace("a2", Box::new(FakeModule::new("b", "Beta II")));
+        assert!(err.is_err());
+    }
+
     #[test]
     fn broadcast_tick_goes_to_all() {
         let log_a = Arc::new(Mutex::new(Vec::new()));
@@ -417,7 +1046,7 @@ mod tests {
     }
 
     #[test]
-    fn broadcast_key_goes_to_active_only() {
+    fn broadcast_key_goes_to_active_only_as_bound_action() {
         let log_a = Arc::new(Mutex::new(Vec::new()));
         let log_b = Arc::new(Mutex::new(Vec::new()));
         let mut reg = ModuleRegistry::new();
@@ -431,10 +1060,54 @@ mod tests {
             crossterm::event::KeyModifiers::NONE,
         );
         reg.broadcast(&Event::Key(key));
-        assert_eq!(log_a.lock().unwrap().as_slice(), &["a:key"]);
+        let bound = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('z'),
+            crossterm::event::KeyModifiers::NONE,
+        );
+        reg.broadcast(&Event::Key(bound));
+        assert_eq!(log_a.lock().unwrap().as_slice(), &["a:key", "a:action:zap"]);
         assert!(log_b.lock().unwrap().is_empty());
     }
 
+    #[test]
+    fn panicking_module_is_marked_crashed_until_replaced() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(PanickyModule(0))).unwrap();
+        reg.register(Box::new(FakeModule::with_log("a", "Alpha", log.clone())))
+            .unwrap();
+        let key = crossterm::event::KeyEvent::new(
+            crossterm::event::KeyCode::Char('x'),
+            crossterm::event::KeyModifiers::NONE,
+        );
+
+        reg.broadcast(&Event::Key(key));
+        assert_eq!(reg.crash("panicky"), Some("bad key"));
+        reg.broadcast(&Event::Tick {
+            now: Instant::now(),
+        });
+        let count = |reg: &ModuleRegistry| {
+            reg.get("panicky")
+                .unwrap()
+                .downcast_ref::<PanickyModule>()
+                .unwrap()
+                .0
+        };
+        assert_eq!(count(&reg), 1, "crashed modules get no more events");
+        assert_eq!(log.lock().unwrap().as_slice(), &["a:tick"]);
+        assert_eq!(
+            reg.snapshot().modules[0].crashed.as_deref(),
+            Some("bad key")
+        );
+
+        reg.replace("panicky", Box::new(PanickyModule(0))).unwrap();
+        assert_eq!(reg.crash("panicky"), None);
+        reg.broadcast(&Event::Tick {
+            now: Instant::now(),
+        });
+        assert_eq!(count(&reg), 1);
+    }
+
     #[test]
     fn broadcast_lifecycle_goes_to_target() {
         let log_a = Arc::new(Mutex::new(Vec::new()));
@@ -445,8 +1118,212 @@ mod tests {
         reg.register(Box::new(FakeModule::with_log("b", "Beta", log_b.clone())))
             .unwrap();
 
-        reg.broadcast(&Event::ModuleActivated { id: "b".into() });
+        reg.broadcast(&Event::ModuleActivated {
+            id: "b".into(),
+            reason: ActivationReason::Switch,
+        });
         assert!(log_a.lock().unwrap().is_empty());
         assert_eq!(log_b.lock().unwrap().as_slice(), &["b:activated"]);
     }
+
+    /// Publishes `demo.ping` events with a schema.
+    struct SchemaModule(&'static str);
+
+    impl Module for SchemaModule {
+        fn id(&self) -> &'static str {
+            self.0
+        }
+        fn title(&self) -> &'static str {
+            self.0
+        }
+        fn handle_event(&mut self, _ev: &Event) {}
+        fn event_schemas(&self) -> Vec<(&'static str, serde_json::Value)> {
+            vec![("demo.ping", serde_json::json!({ "type": "object" }))]
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn registers_module_event_schemas_first_come() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(SchemaModule("first"))).unwrap();
+        reg.register(Box::new(SchemaModule("second"))).unwrap();
+        reg.register(Box::new(FakeModule::new("plain", "Plain")))
+            .unwrap();
+
+        let mut schemas = EventSchemas::new();
+        let refused = reg.register_event_schemas(&mut schemas);
+        assert_eq!(
+            schemas.iter().collect::<Vec<_>>(),
+            vec![("demo.ping", "first")]
+        );
+        assert_eq!(refused.len(), 1);
+        assert_eq!((refused[0].0, refused[0].1), ("second", "demo.ping"));
+        assert!(schemas.validate("demo.ping", "[]").is_err());
+    }
+
+    #[test]
+    fn refused_schemas_name_the_tag_owner() {
+        let mut reg = ModuleRegistry::new();
+        reg.register(Box::new(SchemaModule("first"))).unwrap();
+        reg.register(Box::new(SchemaModule("second"))).unwrap();
+
+        let refused = reg.register_event_schemas(&mut EventSchemas::new());
+        assert!(
+            matches!(&refused[0].2, CoreError::EventTagTaken { owner, .. } if owner == "first")
+        );
+    }
+
+    /// One registry operation, for the generative test below.
+    #[derive(Debug, Clone)]
+    enum Op {
+        Register(usize),
+        Unregister(usize),
+        Replace(usize),
+        Activate(usize),
+        CycleNext,
+        CyclePrev,
+        Back,
+    }
+
+    const OP_IDS: [&str; 5] = ["a", "b", "c", "d", "e"];
+
+    fn ops() -> impl proptest::strategy::Strategy<Value = Vec<Op>> {
+        use proptest::prelude::*;
+
+        let id = 0..OP_IDS.len();
+        let op = prop_oneof![
+            3 => id.clone().prop_map(Op::Register),
+            1 => id.clone().prop_map(Op::Unregister),
+            1 => id.clone().prop_map(Op::Replace),
+            2 => id.prop_map(Op::Activate),
+            2 => Just(Op::CycleNext),
+            2 => Just(Op::CyclePrev),
+            2 => Just(Op::Back),
+        ];
+        proptest::collection::vec(op, 0..80)
+    }
+
+    /// The registry's order, active module, and back stack, kept by hand.
+    #[derive(Default)]
+    struct Model {
+        order: Vec<&'static str>,
+        active: Option<usize>,
+        history: Vec<&'static str>,
+    }
+
+    impl Model {
+        fn switch(&mut self, to: usize, back: bool) {
+            if let Some(from) = self.active.filter(|from| *from != to) {
+                if !back {
+                    self.history.push(self.order[from]);
+                    if self.history.len() > HISTORY_LIMIT {
+                        self.history.remove(0);
+                    }
+                }
+            }
+            self.active = Some(to);
+        }
+
+        fn position(&self, id: &str) -> Option<usize> {
+            self.order.iter().position(|entry| *entry == id)
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn activation_and_cycling_keep_indices_consistent(ops in ops()) {
+            let mut reg = ModuleRegistry::new();
+            let mut model = Model::default();
+            for op in &ops {
+                let events = match *op {
+                    Op::Register(i) => {
+                        let id = OP_IDS[i];
+                        let result = reg.register(Box::new(FakeModule::new(id, id)));
+                        proptest::prop_assert_eq!(result.is_ok(), model.position(id).is_none());
+                        if result.is_ok() {
+                            model.order.push(id);
+                            model.active.get_or_insert(0);
+                        }
+                        Vec::new()
+                    }
+                    Op::Unregister(i) => {
+                        let id = OP_IDS[i];
+                        let result = reg.unregister(id);
+                        proptest::prop_assert_eq!(result.is_ok(), model.position(id).is_some());
+                        if let Some(idx) = model.position(id) {
+                            model.order.remove(idx);
+                            model.history.retain(|entry| *entry != id);
+                            model.active = match model.active {
+                                Some(active) if active == idx => {
+                                    (!model.order.is_empty()).then(|| idx.min(model.order.len() - 1))
+                                }
+                                Some(active) if active > idx => Some(active - 1),
+                                active => active,
+                            };
+                        }
+                        result.map(|(_, events)| events).unwrap_or_default()
+                    }
+                    Op::Replace(i) => {
+                        let id = OP_IDS[i];
+                        let result = reg.replace(id, Box::new(FakeModule::new(id, id)));
+                        proptest::prop_assert_eq!(result.is_ok(), model.position(id).is_some());
+                        result.map(|(_, events)| events).unwrap_or_default()
+                    }
+                    Op::Activate(i) => {
+                        let id = OP_IDS[i];
+                        let result = reg.activate(id);
+                        proptest::prop_assert_eq!(result.is_ok(), model.position(id).is_some());
+                        if let Some(to) = model.position(id) {
+                            model.switch(to, false);
+                        }
+                        result.unwrap_or_default()
+                    }
+                    Op::CycleNext | Op::CyclePrev => {
+                        let len = model.order.len();
+                        if len > 0 {
+                            let cur = model.active.unwrap_or(0);
+                            let to = match op {
+                                Op::CycleNext => (cur + 1) % len,
+                                _ => (cur + len - 1) % len,
+                            };
+                            model.switch(to, false);
+                        }
+                        match op {
+                            Op::CycleNext => reg.cycle_next(),
+                            _ => reg.cycle_prev(),
+                        }
+                    }
+                    Op::Back => {
+                        while let Some(id) = model.history.pop() {
+                            let to = model.position(id).unwrap();
+                            if Some(to) != model.active {
+                                model.switch(to, true);
+                                break;
+                            }
+                        }
+                        reg.back()
+                    }
+                };
+
+                let active = model.active.map(|i| model.order[i]);
+                proptest::prop_assert_eq!(reg.active_id(), active);
+                proptest::prop_assert_eq!(reg.active().map(|m| m.id()), active);
+                proptest::prop_assert_eq!(reg.active_id().is_none(), reg.is_empty());
+                let ids: Vec<&str> = reg.list().into_iter().map(|(id, _)| id).collect();
+                proptest::prop_assert_eq!(&ids, &model.order);
+                proptest::prop_assert_eq!(reg.len(), model.order.len());
+                for id in &ids {
+                    proptest::prop_assert_eq!(reg.get(id).map(|m| m.id()), Some(*id));
+                }
+                proptest::prop_assert_eq!(reg.history(), model.history.as_slice());
+                proptest::prop_assert!(reg.previous_id().is_none() || reg.previous_id() != active);
+                if let Some(Event::ModuleActivated { id, .. }) = events.last() {
+                    proptest::prop_assert_eq!(Some(id.as_str()), active);
+                }
+            }
+        }
+    }
 }
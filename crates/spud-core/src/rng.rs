@@ -0,0 +1,155 @@
+//! Seedable random numbers for reproducible behaviour.
+//!
+//! Anything random (idle animations, easter eggs, jitter) draws from an
+//! [`Rng`] handed out by the app's [`RngProvider`] instead of an ambient
+//! source, so a run can be reproduced with `--seed` and tests can pin
+//! outcomes. The seed is logged at startup and written to `--record` files.
+//!
+//! Each consumer gets its own named stream, derived from the seed and the
+//! name alone, so adding a new consumer does not shift the numbers seen by
+//! existing ones. The generator is SplitMix64: fast, tiny, and stable across
+//! releases, but not suitable for anything security-related.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Hands out independent [`Rng`] streams derived from one seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RngProvider {
+    seed: u64,
+}
+
+impl RngProvider {
+    /// Create a provider for `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Create a provider seeded from the system clock, for runs that did
+    /// not ask for a specific seed.
+    pub fn from_clock() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::new(mix(nanos))
+    }
+
+    /// The seed every stream is derived from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The stream called `name`. The same seed and name always produce the
+    /// same sequence.
+    pub fn stream(&self, name: &str) -> Rng {
+        Rng::new(mix(self.seed ^ fnv1a(name.as_bytes())))
+    }
+}
+
+/// A deterministic pseudo-random number generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator starting from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.state)
+    }
+
+    /// Uniform integer in `0..bound`. Returns 0 when `bound` is 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+
+    /// Uniform float in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// `true` with probability `p`, clamped to `[0, 1]`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    /// A random element of `items`, or `None` if it is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len() as u64) as usize)
+    }
+}
+
+/// SplitMix64 finalizer.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01B3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(rng: &mut Rng, n: usize) -> Vec<u64> {
+        (0..n).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn same_seed_and_stream_repeat() {
+        let a = RngProvider::new(42);
+        let b = RngProvider::new(42);
+        assert_eq!(
+            take(&mut a.stream("hello"), 8),
+            take(&mut b.stream("hello"), 8)
+        );
+        assert_ne!(
+            take(&mut a.stream("hello"), 8),
+            take(&mut a.stream("stats"), 8)
+        );
+        assert_ne!(
+            take(&mut a.stream("hello"), 8),
+            take(&mut RngProvider::new(43).stream("hello"), 8)
+        );
+    }
+
+    #[test]
+    fn known_sequence_is_stable() {
+        // Recordings depend on this; changing the algorithm breaks replays.
+        let mut rng = Rng::new(0);
+        assert_eq!(
+            take(&mut rng, 3),
+            vec![
+                0xE220_A839_7B1D_CDAF,
+                0x6E78_9E6A_A1B9_65F4,
+                0x06C4_5D18_8009_454F
+            ]
+        );
+    }
+
+    #[test]
+    fn helpers_stay_in_range() {
+        let mut rng = RngProvider::new(7).stream("test");
+        for _ in 0..1_000 {
+            assert!(rng.below(10) < 10);
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+        assert_eq!(rng.below(0), 0);
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+        assert_eq!(rng.choose::<u8>(&[]), None);
+        assert_eq!(rng.choose(&["only"]), Some(&"only"));
+    }
+}
@@ -0,0 +1,573 @@
+//! JSON Schemas for custom event payloads.
+//!
+//! Modules and plugins register a schema per [`Event::Custom`] tag; the host
+//! then checks payloads published under that tag and rejects the ones that
+//! do not match. Tags without a schema are not checked.
+//!
+//! A practical subset of JSON Schema is supported: `type`, `enum`, `const`,
+//! `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+//! `maxItems`, `minLength`, `maxLength`, `minimum`, `maximum`,
+//! `exclusiveMinimum`, `exclusiveMaximum`, `anyOf`, and `oneOf`, plus the
+//! annotations `$schema`, `$id`, `title`, `description`, `default`, and
+//! `examples`. Schemas using any other keyword are refused at registration
+//! rather than half-enforced.
+//!
+//! [`Event::Custom`]: crate::event::Event::Custom
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::error::{CoreError, CoreResult};
+
+/// Keywords that are checked.
+const ASSERTIONS: [&str; 17] = [
+    "type",
+    "enum",
+    "const",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "exclusiveMinimum",
+    "exclusiveMaximum",
+    "anyOf",
+    "oneOf",
+];
+
+/// Keywords that are accepted and ignored.
+const ANNOTATIONS: [&str; 6] = [
+    "$schema",
+    "$id",
+    "title",
+    "description",
+    "default",
+    "examples",
+];
+
+/// Values allowed for `type`.
+const TYPES: [&str; 7] = [
+    "null", "boolean", "object", "array", "number", "integer", "string",
+];
+
+/// One way a payload fails its schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// JSON Pointer to the offending value; empty for the payload itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// A checked schema, ready to validate payloads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema(Value);
+
+impl Schema {
+    /// Check that `schema` only uses supported keywords, with well-formed
+    /// values.
+    pub fn compile(schema: Value) -> CoreResult<Self> {
+        check_schema(&schema, "")?;
+        Ok(Self(schema))
+    }
+
+    /// Every way `payload` fails the schema; empty when it matches.
+    pub fn validate(&self, payload: &Value) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        validate_at(&self.0, payload, "", &mut violations);
+        violations
+    }
+
+    /// The schema document.
+    pub fn as_json(&self) -> &Value {
+        &self.0
+    }
+}
+
+/// A registered schema and who registered it.
+#[derive(Debug, Clone)]
+struct Entry {
+    /// Module or plugin id.
+    owner: String,
+    schema: Schema,
+}
+
+/// Schemas for custom event tags, keyed by tag.
+#[derive(Debug, Clone, Default)]
+pub struct EventSchemas {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl EventSchemas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `schema` for `tag` on behalf of `owner`, replacing the
+    /// owner's earlier schema. A tag already claimed by another owner is
+    /// refused so one plugin cannot redefine another's events.
+    pub fn register(&mut self, owner: &str, tag: &str, schema: Value) -> CoreResult<()> {
+        if tag.is_empty() {
+            return Err(CoreError::EmptyEventTag);
+        }
+        if let Some(entry) = self.entries.get(tag).filter(|entry| entry.owner != owner) {
+            return Err(CoreError::EventTagTaken {
+                tag: tag.to_string(),
+                owner: entry.owner.clone(),
+            });
+        }
+        let schema = Schema::compile(schema)?;
+        self.entries.insert(
+            tag.to_string(),
+            Entry {
+                owner: owner.to_string(),
+                schema,
+            },
+        );
+        Ok(())
+    }
+
+    /// Drop every schema registered by `owner`, e.g. when a plugin stops.
+    pub fn unregister_owner(&mut self, owner: &str) {
+        self.entries.retain(|_, entry| entry.owner != owner);
+    }
+
+    /// Schema registered for `tag`, if any.
+    pub fn get(&self, tag: &str) -> Option<&Schema> {
+        self.entries.get(tag).map(|entry| &entry.schema)
+    }
+
+    /// `(tag, owner)` of every registration, ordered by tag.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(tag, entry)| (tag.as_str(), entry.owner.as_str()))
+    }
+
+    /// Check a payload published under `tag`. Payloads must be JSON when a
+    /// schema is registered; anything goes otherwise.
+    pub fn validate(&self, tag: &str, payload: &str) -> Result<(), Vec<Violation>> {
+        let Some(schema) = self.get(tag) else {
+            return Ok(());
+        };
+        let payload: Value = serde_json::from_str(payload).map_err(|err| {
+            vec![Violation {
+                path: String::new(),
+                message: format!("payload is not valid JSON: {err}"),
+            }]
+        })?;
+        let violations = schema.validate(&payload);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn check_schema(schema: &Value, path: &str) -> CoreResult<()> {
+    let Value::Object(keywords) = schema else {
+        // `true` and `false` are valid schemas: accept or reject everything.
+        if schema.is_boolean() {
+            return Ok(());
+        }
+        return Err(invalid(format!(
+            "schema{} must be an object or boolean",
+            at(path)
+        )));
+    };
+    for (keyword, value) in keywords {
+        if ANNOTATIONS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if !ASSERTIONS.contains(&keyword.as_str()) {
+            return Err(invalid(format!(
+                "unsupported schema keyword {keyword:?}{}",
+                at(path)
+            )));
+        }
+        let here = format!("{path}/{keyword}");
+        match keyword.as_str() {
+            "type" => {
+                let names: Vec<&Value> = match value {
+                    Value::Array(names) => names.iter().collect(),
+                    other => vec![other],
+                };
+                for name in names {
+                    if !name.as_str().is_some_and(|name| TYPES.contains(&name)) {
+                        return Err(invalid(format!("unknown type {name}{}", at(&here))));
+                    }
+                }
+            }
+            "enum" | "required" if !value.is_array() => {
+                return Err(invalid(format!("{keyword} must be an array{}", at(path))))
+            }
+            "required" if !value.as_array().into_iter().flatten().all(Value::is_string) => {
+                return Err(invalid(format!(
+                    "required must list property names{}",
+                    at(path)
+                )))
+            }
+            "properties" => {
+                let Value::Object(properties) = value else {
+                    return Err(invalid(format!("properties must be an object{}", at(path))));
+                };
+                for (name, property) in properties {
+                    check_schema(property, &format!("{here}/{name}"))?;
+                }
+            }
+            "additionalProperties" | "items" => check_schema(value, &here)?,
+            "anyOf" | "oneOf" => match value {
+                Value::Array(options) if !options.is_empty() => {
+                    for (index, option) in options.iter().enumerate() {
+                        check_schema(option, &format!("{here}/{index}"))?;
+                    }
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "{keyword} must be a non-empty array{}",
+                        at(path)
+                    )))
+                }
+            },
+            "minItems" | "maxItems" | "minLength" | "maxLength" if value.as_u64().is_none() => {
+                return Err(invalid(format!(
+                    "{keyword} must be a non-negative integer{}",
+                    at(path)
+                )))
+            }
+            "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum"
+                if !value.is_number() =>
+            {
+                return Err(invalid(format!("{keyword} must be a number{}", at(path))))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn invalid(reason: String) -> CoreError {
+    CoreError::InvalidSchema(reason)
+}
+
+/// ` at <path>` for error messages, or nothing at the root.
+fn at(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" at {path}")
+    }
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, out: &mut Vec<Violation>) {
+    let mut fail = |message: String| {
+        out.push(Violation {
+            path: path.to_string(),
+            message,
+        })
+    };
+    let keywords = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => return fail("no value is allowed here".into()),
+        Value::Object(keywords) => keywords,
+        _ => return,
+    };
+
+    if let Some(expected) = keywords.get("type") {
+        let names: Vec<&str> = match expected {
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        if !names.iter().any(|name| has_type(value, name)) {
+            // Nothing else is meaningful for a value of the wrong type.
+            return fail(format!(
+                "expected {}, found {}",
+                names.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+    if let Some(Value::Array(allowed)) = keywords.get("enum") {
+        if !allowed.contains(value) {
+            fail(format!(
+                "{value} is not one of {}",
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+    if let Some(expected) = keywords.get("const") {
+        if expected != value {
+            fail(format!("expected {expected}, found {value}"));
+        }
+    }
+
+    match value {
+        Value::Object(object) => validate_object(keywords, object, path, out),
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = keywords.get("minItems").and_then(Value::as_u64) {
+                if len < min {
+                    fail(format!("expected at least {min} items, found {len}"));
+                }
+            }
+            if let Some(max) = keywords.get("maxItems").and_then(Value::as_u64) {
+                if len > max {
+                    fail(format!("expected at most {max} items, found {len}"));
+                }
+            }
+            if let Some(item_schema) = keywords.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{path}/{index}"), out);
+                }
+            }
+        }
+        Value::String(text) => {
+            let len = text.chars().count() as u64;
+            if let Some(min) = keywords.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    fail(format!("expected at least {min} characters, found {len}"));
+                }
+            }
+            if let Some(max) = keywords.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    fail(format!("expected at most {max} characters, found {len}"));
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or(f64::NAN);
+            let bound = |keyword: &str| keywords.get(keyword).and_then(Value::as_f64);
+            if let Some(min) = bound("minimum").filter(|min| number < *min) {
+                fail(format!("{number} is less than the minimum {min}"));
+            }
+            if let Some(max) = bound("maximum").filter(|max| number > *max) {
+                fail(format!("{number} is greater than the maximum {max}"));
+            }
+            if let Some(min) = bound("exclusiveMinimum").filter(|min| number <= *min) {
+                fail(format!("{number} must be greater than {min}"));
+            }
+            if let Some(max) = bound("exclusiveMaximum").filter(|max| number >= *max) {
+                fail(format!("{number} must be less than {max}"));
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(options)) = keywords.get("anyOf") {
+        let matched = options
+            .iter()
+            .any(|option| matches_schema(option, value, path));
+        if !matched {
+            out.push(Violation {
+                path: path.to_string(),
+                message: "matches none of the anyOf schemas".into(),
+            });
+        }
+    }
+    if let Some(Value::Array(options)) = keywords.get("oneOf") {
+        let matched = options
+            .iter()
+            .filter(|option| matches_schema(option, value, path))
+            .count();
+        if matched != 1 {
+            out.push(Violation {
+                path: path.to_string(),
+                message: format!("matches {matched} of the oneOf schemas, expected exactly 1"),
+            });
+        }
+    }
+}
+
+fn validate_object(
+    keywords: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    out: &mut Vec<Violation>,
+) {
+    if let Some(Value::Array(required)) = keywords.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                out.push(Violation {
+                    path: path.to_string(),
+                    message: format!("missing required property {name:?}"),
+                });
+            }
+        }
+    }
+    let properties = keywords.get("properties").and_then(Value::as_object);
+    let additional = keywords.get("additionalProperties");
+    for (name, value) in object {
+        let here = format!("{path}/{}", escape_pointer(name));
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property) => validate_at(property, value, &here, out),
+            None => match additional {
+                Some(Value::Bool(false)) => out.push(Violation {
+                    path: here,
+                    message: "unexpected property".into(),
+                }),
+                Some(schema) => validate_at(schema, value, &here, out),
+                None => {}
+            },
+        }
+    }
+}
+
+fn matches_schema(schema: &Value, value: &Value, path: &str) -> bool {
+    let mut violations = Vec::new();
+    validate_at(schema, value, path, &mut violations);
+    violations.is_empty()
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "string" => value.is_string(),
+        _ => false,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+    }
+}
+
+/// Escape a property name for a JSON Pointer (RFC 6901).
+fn escape_pointer(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ping_schema() -> Value {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "required": ["n", "source"],
+            "additionalProperties": false,
+            "properties": {
+                "n": { "type": "integer", "minimum": 0 },
+                "source": { "type": "string", "minLength": 1 },
+                "tags": { "type": "array", "items": { "enum": ["a", "b"] }, "maxItems": 2 }
+            }
+        })
+    }
+
+    #[test]
+    fn accepts_matching_payloads_and_reports_each_violation() {
+        let schema = Schema::compile(ping_schema()).unwrap();
+        assert!(schema
+            .validate(&json!({"n": 1, "source": "x", "tags": ["a"]}))
+            .is_empty());
+
+        let violations = schema.validate(&json!({"n": -1, "extra": true, "tags": ["c"]}));
+        let rendered: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            rendered,
+            vec![
+                "missing required property \"source\"",
+                "/extra: unexpected property",
+                "/n: -1 is less than the minimum 0",
+                "/tags/0: \"c\" is not one of [\"a\",\"b\"]",
+            ]
+        );
+        assert_eq!(
+            schema.validate(&json!([1]))[0].message,
+            "expected object, found array"
+        );
+    }
+
+    #[test]
+    fn refuses_unsupported_or_malformed_schemas() {
+        let err = Schema::compile(json!({"type": "string", "pattern": "^a"})).unwrap_err();
+        assert!(err.to_string().contains("\"pattern\""));
+        assert!(Schema::compile(json!({"type": "text"})).is_err());
+        assert!(Schema::compile(json!({"properties": {"a": {"minimum": "1"}}})).is_err());
+        assert!(Schema::compile(json!({"anyOf": []})).is_err());
+        assert!(Schema::compile(json!(3)).is_err());
+        assert!(Schema::compile(json!(true)).is_ok());
+    }
+
+    #[test]
+    fn any_of_and_one_of_count_matches() {
+        let schema = Schema::compile(json!({
+            "oneOf": [{ "type": "integer" }, { "type": "number" }]
+        }))
+        .unwrap();
+        // 1 is both an integer and a number.
+        assert_eq!(schema.validate(&json!(1)).len(), 1);
+        assert!(schema.validate(&json!(1.5)).is_empty());
+        let schema = Schema::compile(json!({"anyOf": [{"const": 1}, {"type": "string"}]})).unwrap();
+        assert!(schema.validate(&json!("x")).is_empty());
+        assert_eq!(schema.validate(&json!(2)).len(), 1);
+    }
+
+    #[test]
+    fn registry_checks_registered_tags_only_and_guards_ownership() {
+        let mut schemas = EventSchemas::new();
+        schemas
+            .register("metrics", "demo.ping", ping_schema())
+            .unwrap();
+        assert!(schemas.validate("other.tag", "not json").is_ok());
+        assert!(schemas
+            .validate("demo.ping", r#"{"n": 2, "source": "x"}"#)
+            .is_ok());
+        let errors = schemas.validate("demo.ping", "{oops").unwrap_err();
+        assert!(errors[0].message.starts_with("payload is not valid JSON"));
+
+        let err = schemas
+            .register("intruder", "demo.ping", json!(true))
+            .unwrap_err();
+        assert!(err.to_string().contains("registered by metrics"));
+        schemas
+            .register("metrics", "demo.ping", json!(true))
+            .unwrap();
+        assert!(schemas.validate("demo.ping", "[]").is_ok());
+
+        schemas.unregister_owner("metrics");
+        assert!(schemas.is_empty());
+        assert!(schemas.register("metrics", "", json!(true)).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Lightweight latency spans reported over the event bus.
+//!
+//! Code that wants to report how long an operation took wraps it in a
+//! [`Stopwatch`] (or [`timed`]) and publishes the resulting [`Event::Span`].
+//! The app feeds spans into [`SpanStats`], which backs the `spans` console
+//! command and the console's perf readout.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::bus::EventBus;
+use crate::event::Event;
+
+/// Default number of recent durations kept per span name.
+pub const DEFAULT_SPAN_HISTORY: usize = 128;
+
+/// Measures one operation; turn it into an event with [`finish`](Self::finish).
+#[derive(Debug)]
+pub struct Stopwatch {
+    name: String,
+    started: Instant,
+}
+
+impl Stopwatch {
+    /// Start timing an operation called `name`.
+    pub fn start(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Stop timing and build the [`Event::Span`] to publish.
+    pub fn finish(self) -> Event {
+        Event::Span {
+            name: self.name,
+            duration: self.started.elapsed(),
+        }
+    }
+}
+
+/// Run `f`, publish its duration as a span named `name`, and return its result.
+pub fn timed<T>(bus: &mut EventBus, name: &str, f: impl FnOnce() -> T) -> T {
+    let stopwatch = Stopwatch::start(name);
+    let result = f();
+    bus.publish(stopwatch.finish());
+    result
+}
+
+/// Aggregate of the recent durations recorded for one span name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanSummary {
+    pub name: String,
+    /// Spans recorded since startup, including ones no longer in history.
+    pub count: u64,
+    /// Mean over the retained history.
+    pub avg: Duration,
+    /// Maximum over the retained history.
+    pub max: Duration,
+    pub last: Duration,
+}
+
+#[derive(Debug, Default)]
+struct SpanSeries {
+    total: u64,
+    recent: VecDeque<Duration>,
+}
+
+/// Recent span durations grouped by name.
+#[derive(Debug)]
+pub struct SpanStats {
+    series: BTreeMap<String, SpanSeries>,
+    history: usize,
+}
+
+impl Default for SpanStats {
+    fn default() -> Self {
+        Self::new(DEFAULT_SPAN_HISTORY)
+    }
+}
+
+impl SpanStats {
+    /// Create an empty store keeping `history` durations per name.
+    pub fn new(history: usize) -> Self {
+        Self {
+            series: BTreeMap::new(),
+            history: history.max(1),
+        }
+    }
+
+    /// Record a span if `event` is [`Event::Span`].
+    pub fn ingest(&mut self, event: &Event) -> bool {
+        let Event::Span { name, duration } = event else {
+            return false;
+        };
+        self.record(name, *duration);
+        true
+    }
+
+    /// Record one duration for `name`.
+    pub fn record(&mut self, name: &str, duration: Duration) {
+        let series = self.series.entry(name.to_string()).or_default();
+        series.total += 1;
+        if series.recent.len() == self.history {
+            series.recent.pop_front();
+        }
+        series.recent.push_back(duration);
+    }
+
+    /// Summaries for every span name, ordered by name.
+    pub fn summaries(&self) -> Vec<SpanSummary> {
+        self.series
+            .iter()
+            .filter_map(|(name, series)| summarize(name, series))
+            .collect()
+    }
+
+    /// Summary for one span name.
+    pub fn summary(&self, name: &str) -> Option<SpanSummary> {
+        summarize(name, self.series.get(name)?)
+    }
+
+    /// The last `n` durations recorded for `name`, oldest first.
+    pub fn recent(&self, name: &str, n: usize) -> Vec<Duration> {
+        self.series.get(name).map_or_else(Vec::new, |series| {
+            let skip = series.recent.len().saturating_sub(n);
+            series.recent.iter().skip(skip).copied().collect()
+        })
+    }
+
+    /// The span name with the highest recent average, for perf readouts.
+    pub fn slowest(&self) -> Option<SpanSummary> {
+        self.summaries()
+            .into_iter()
+            .max_by_key(|summary| summary.avg)
+    }
+
+    /// Return `true` if no spans have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+}
+
+fn summarize(name: &str, series: &SpanSeries) -> Option<SpanSummary> {
+    let last = *series.recent.back()?;
+    let sum: Duration = series.recent.iter().sum();
+    Some(SpanSummary {
+        name: name.to_string(),
+        count: series.total,
+        avg: sum / series.recent.len() as u32,
+        max: series.recent.iter().copied().max().unwrap_or(last),
+        last,
+    })
+}
+
+/// Format a duration for console output (`850us`, `12.3ms`, `1.20s`).
+pub fn format_duration(duration: Duration) -> String {
+    if duration >= Duration::from_secs(1) {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else if duration >= Duration::from_millis(1) {
+        format!("{:.1}ms", duration.as_secs_f64() * 1_000.0)
+    } else {
+        format!("{}us", duration.as_micros())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_track_count_avg_and_max() {
+        let mut stats = SpanStats::new(2);
+        stats.record("render", Duration::from_millis(10));
+        stats.record("render", Duration::from_millis(20));
+        stats.record("render", Duration::from_millis(40));
+        stats.record("io", Duration::from_millis(1));
+
+        let render = stats.summary("render").unwrap();
+        assert_eq!(render.count, 3);
+        assert_eq!(render.avg, Duration::from_millis(30));
+        assert_eq!(render.max, Duration::from_millis(40));
+        assert_eq!(render.last, Duration::from_millis(40));
+        assert_eq!(stats.slowest().unwrap().name, "render");
+        assert_eq!(stats.recent("render", 1), [Duration::from_millis(40)]);
+        assert_eq!(stats.recent("render", 5).len(), 2);
+        assert!(stats.recent("missing", 5).is_empty());
+        assert_eq!(stats.summaries().len(), 2);
+    }
+
+    #[test]
+    fn timed_publishes_span_event() {
+        let mut bus = EventBus::new();
+        let value = timed(&mut bus, "work", || 7);
+        assert_eq!(value, 7);
+
+        let events = bus.drain();
+        let mut stats = SpanStats::default();
+        assert!(stats.ingest(&events[0]));
+        assert!(!stats.ingest(&Event::Quit));
+        assert_eq!(stats.summary("work").unwrap().count, 1);
+    }
+
+    #[test]
+    fn format_duration_picks_unit() {
+        assert_eq!(format_duration(Duration::from_micros(850)), "850us");
+        assert_eq!(format_duration(Duration::from_micros(12_300)), "12.3ms");
+        assert_eq!(format_duration(Duration::from_millis(1_200)), "1.20s");
+    }
+}
@@ -1,5 +1,9 @@
 use std::time::{Duration, Instant};
 
+use serde::Serialize;
+
+use crate::i18n::tr;
+
 /// Global application state shared across the app loop.
 ///
 /// Holds the startup timestamp and the status line displayed in the top bar.
@@ -22,7 +26,7 @@ impl AppState {
     pub fn new() -> Self {
         Self {
             started_at: Instant::now(),
-            status_line: "DE-EVOLUTION IN PROGRESS.".to_string(),
+            status_line: tr("DE-EVOLUTION IN PROGRESS.").to_string(),
         }
     }
 
@@ -30,4 +34,19 @@ impl AppState {
     pub fn uptime(&self) -> Duration {
         self.started_at.elapsed()
     }
+
+    /// Serializable view for state dumps.
+    pub fn snapshot(&self) -> AppStateSnapshot {
+        AppStateSnapshot {
+            status_line: self.status_line.clone(),
+            uptime_ms: self.uptime().as_millis() as u64,
+        }
+    }
+}
+
+/// Serializable view of [`AppState`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppStateSnapshot {
+    pub status_line: String,
+    pub uptime_ms: u64,
 }
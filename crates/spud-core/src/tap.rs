@@ -0,0 +1,201 @@
+//! Recent-event history for the debug overlay.
+//!
+//! The [`EventBus`](crate::bus::EventBus) copies every published event into
+//! an [`EventTap`]: a bounded ring of timestamped summaries that the app
+//! shows with F12 or `debug events`. Ticks and spans are skipped since they
+//! arrive every frame and would push everything else out.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::event::Event;
+
+/// Default number of events kept.
+pub const DEFAULT_TAP_CAPACITY: usize = 256;
+
+/// A summary of one published event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TappedEvent {
+    /// Time since the tap was created.
+    pub at: Duration,
+    /// [`Event::kind`] of the event.
+    pub kind: &'static str,
+    /// [`Event::preview`] of the event.
+    pub preview: String,
+}
+
+/// Bounded history of recent bus events, pausable and scrollable.
+#[derive(Debug)]
+pub struct EventTap {
+    origin: Instant,
+    entries: VecDeque<TappedEvent>,
+    capacity: usize,
+    paused: bool,
+    /// Events dropped while paused.
+    missed: u64,
+    /// Lines scrolled back from the newest event.
+    scroll: usize,
+}
+
+impl EventTap {
+    /// Create an empty tap keeping at most `capacity` events, timestamped
+    /// relative to `origin`.
+    pub fn new(origin: Instant, capacity: usize) -> Self {
+        Self {
+            origin,
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            paused: false,
+            missed: 0,
+            scroll: 0,
+        }
+    }
+
+    /// Record `event` as seen at `now`. Ticks and spans are ignored, and
+    /// while paused events are counted but not stored so the view holds
+    /// still.
+    pub fn record(&mut self, event: &Event, now: Instant) {
+        if matches!(event, Event::Tick { .. } | Event::Span { .. }) || self.capacity == 0 {
+            return;
+        }
+        if self.paused {
+            self.missed += 1;
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(TappedEvent {
+            at: now.saturating_duration_since(self.origin),
+            kind: event.kind(),
+            preview: event.preview(),
+        });
+    }
+
+    /// Recorded events, oldest first.
+    pub fn entries(&self) -> &VecDeque<TappedEvent> {
+        &self.entries
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stop or resume recording. Resuming jumps back to the newest event.
+    pub fn set_paused(&mut self, paused: bool) {
+        if !paused {
+            self.missed = 0;
+            self.scroll = 0;
+        }
+        self.paused = paused;
+    }
+
+    /// Number of events dropped since the tap was paused.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    /// Forget all recorded events.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.missed = 0;
+        self.scroll = 0;
+    }
+
+    /// Lines scrolled back from the newest event.
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Scroll towards older events.
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.entries.len().saturating_sub(1));
+    }
+
+    /// Scroll towards newer events.
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(tag: &str) -> Event {
+        Event::Custom {
+            tag: tag.into(),
+            payload: "{}".into(),
+        }
+    }
+
+    #[test]
+    fn records_summaries_and_skips_frame_events() {
+        let origin = Instant::now();
+        let mut tap = EventTap::new(origin, 4);
+        tap.record(&Event::Tick { now: origin }, origin);
+        tap.record(
+            &Event::Span {
+                name: "render".into(),
+                duration: Duration::from_millis(2),
+            },
+            origin,
+        );
+        tap.record(
+            &Event::Resize { cols: 80, rows: 24 },
+            origin + Duration::from_millis(1500),
+        );
+        assert_eq!(
+            tap.entries().iter().cloned().collect::<Vec<_>>(),
+            vec![TappedEvent {
+                at: Duration::from_millis(1500),
+                kind: "resize",
+                preview: "80x24".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn evicts_oldest_at_capacity() {
+        let now = Instant::now();
+        let mut tap = EventTap::new(now, 2);
+        for tag in ["a", "b", "c"] {
+            tap.record(&custom(tag), now);
+        }
+        let previews: Vec<&str> = tap.entries().iter().map(|e| e.preview.as_str()).collect();
+        assert_eq!(previews, vec!["b {}", "c {}"]);
+    }
+
+    #[test]
+    fn pause_freezes_history_and_counts_missed() {
+        let now = Instant::now();
+        let mut tap = EventTap::new(now, 8);
+        tap.record(&custom("a"), now);
+        tap.set_paused(true);
+        tap.record(&custom("b"), now);
+        tap.record(&custom("c"), now);
+        assert_eq!(tap.entries().len(), 1);
+        assert_eq!(tap.missed(), 2);
+
+        tap.set_paused(false);
+        tap.record(&custom("d"), now);
+        assert_eq!(tap.entries().len(), 2);
+        assert_eq!(tap.missed(), 0);
+    }
+
+    #[test]
+    fn scroll_is_clamped_and_reset_by_clear() {
+        let now = Instant::now();
+        let mut tap = EventTap::new(now, 8);
+        for tag in ["a", "b", "c"] {
+            tap.record(&custom(tag), now);
+        }
+        tap.scroll_up(10);
+        assert_eq!(tap.scroll(), 2);
+        tap.scroll_down(1);
+        assert_eq!(tap.scroll(), 1);
+        tap.clear();
+        assert_eq!(tap.scroll(), 0);
+        assert!(tap.entries().is_empty());
+    }
+}
@@ -0,0 +1,381 @@
+//! Telemetry history store.
+//!
+//! Keeps a bounded ring of recent samples per `(source, key)` series, fed
+//! from [`Event::Telemetry`]. Consumers query the latest value, the last N
+//! samples (e.g. for sparklines), or min/max/avg over a time window instead
+//! of tracking their own history.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::event::{Event, TelemetryValue};
+
+/// Default number of samples retained per series.
+pub const DEFAULT_HISTORY_LEN: usize = 600;
+
+/// One recorded telemetry sample.
+#[derive(Debug, Clone)]
+pub struct TelemetryPoint {
+    /// Monotonic time the sample was recorded, used for windowed queries.
+    pub at: Instant,
+    /// Wall-clock time the sample was recorded, in Unix milliseconds.
+    pub recorded_at_ms: u64,
+    pub value: TelemetryValue,
+}
+
+/// Aggregate of the numeric samples in a query window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowStats {
+    /// Number of numeric samples aggregated.
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+/// Serializable view of one series' latest sample, for state dumps.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeriesSnapshot {
+    pub source: String,
+    pub key: String,
+    pub value: TelemetryValue,
+    pub recorded_at_ms: u64,
+    /// Samples currently retained for the series.
+    pub samples: usize,
+}
+
+/// Ring-buffer time series keyed by `(source, key)`.
+#[derive(Debug)]
+pub struct TelemetryStore {
+    series: BTreeMap<(String, String), VecDeque<TelemetryPoint>>,
+    capacity: usize,
+}
+
+impl Default for TelemetryStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LEN)
+    }
+}
+
+impl TelemetryStore {
+    /// Create a store retaining at most `capacity` samples per series.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            series: BTreeMap::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Record a sample if `event` is [`Event::Telemetry`].
+    ///
+    /// Returns `true` when the event was recorded.
+    pub fn ingest(&mut self, event: &Event, now: Instant) -> bool {
+        let Event::Telemetry { source, key, value } = event else {
+            return false;
+        };
+        self.record(source, key, value.clone(), now);
+        true
+    }
+
+    /// Append a sample to the `(source, key)` series, evicting the oldest
+    /// sample once the series is at capacity.
+    pub fn record(&mut self, source: &str, key: &str, value: TelemetryValue, now: Instant) {
+        let recorded_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        let points = self
+            .series
+            .entry((source.to_string(), key.to_string()))
+            .or_default();
+        if points.len() == self.capacity {
+            points.pop_front();
+        }
+        points.push_back(TelemetryPoint {
+            at: now,
+            recorded_at_ms,
+            value,
+        });
+    }
+
+    /// Most recent sample of a series.
+    pub fn latest(&self, source: &str, key: &str) -> Option<&TelemetryPoint> {
+        self.points(source, key)?.back()
+    }
+
+    /// Latest sample of every series, ordered by `(source, key)`.
+    pub fn iter_latest(&self) -> impl Iterator<Item = (&str, &str, &TelemetryPoint)> {
+        self.series.iter().filter_map(|((source, key), points)| {
+            points
+                .back()
+                .map(|point| (source.as_str(), key.as_str(), point))
+        })
+    }
+
+    /// Latest sample of every series, ordered by `(source, key)`.
+    pub fn snapshot(&self) -> Vec<SeriesSnapshot> {
+        self.series
+            .iter()
+            .filter_map(|((source, key), points)| {
+                points.back().map(|point| SeriesSnapshot {
+                    source: source.clone(),
+                    key: key.clone(),
+                    value: point.value.clone(),
+                    recorded_at_ms: point.recorded_at_ms,
+                    samples: points.len(),
+                })
+            })
+            .collect()
+    }
+
+    /// Up to `n` most recent samples of a series, oldest first.
+    pub fn last_n(&self, source: &str, key: &str, n: usize) -> Vec<&TelemetryPoint> {
+        let Some(points) = self.points(source, key) else {
+            return Vec::new();
+        };
+        points.iter().skip(points.len().saturating_sub(n)).collect()
+    }
+
+    /// Up to `n` most recent numeric values of a series, oldest first.
+    ///
+    /// Text samples are skipped. Suited to sparkline rendering.
+    pub fn numeric_last_n(&self, source: &str, key: &str, n: usize) -> Vec<f64> {
+        let Some(points) = self.points(source, key) else {
+            return Vec::new();
+        };
+        let mut values: Vec<f64> = points
+            .iter()
+            .rev()
+            .filter_map(|point| point.value.as_f64())
+            .take(n)
+            .collect();
+        values.reverse();
+        values
+    }
+
+    /// Min/max/avg of numeric samples recorded within `window` before `now`.
+    ///
+    /// Returns `None` when the window holds no numeric samples.
+    pub fn window_stats(
+        &self,
+        source: &str,
+        key: &str,
+        window: Duration,
+        now: Instant,
+    ) -> Option<WindowStats> {
+        let cutoff = now.checked_sub(window);
+        let values = self
+            .points(source, key)?
+            .iter()
+            .rev()
+            .take_while(|point| cutoff.is_none_or(|cutoff| point.at >= cutoff))
+            .filter_map(|point| point.value.as_f64());
+
+        let (mut count, mut sum) = (0usize, 0.0f64);
+        let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+        for value in values {
+            count += 1;
+            sum += value;
+            min = min.min(value);
+            max = max.max(value);
+        }
+
+        (count > 0).then(|| WindowStats {
+            count,
+            min,
+            max,
+            avg: sum / count as f64,
+        })
+    }
+
+    /// Increase of a numeric series over `window` before `now`.
+    ///
+    /// Measured from the last sample at or before the window start (or the
+    /// first sample inside it) to the latest sample, so it suits monotonic
+    /// counters. Returns `None` when the series has no numeric samples.
+    pub fn window_delta(
+        &self,
+        source: &str,
+        key: &str,
+        window: Duration,
+        now: Instant,
+    ) -> Option<f64> {
+        let cutoff = now.checked_sub(window);
+        let mut baseline = None;
+        let mut latest = None;
+        for point in self.points(source, key)? {
+            let Some(value) = point.value.as_f64() else {
+                continue;
+            };
+            let before_window = cutoff.is_some_and(|cutoff| point.at <= cutoff);
+            if before_window || baseline.is_none() {
+                baseline = Some(value);
+            }
+            latest = Some(value);
+        }
+        Some(latest? - baseline?)
+    }
+
+    /// Sources that report `key`, in order.
+    pub fn sources_with_key<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.series
+            .keys()
+            .filter(move |(_, series_key)| series_key == key)
+            .map(|(source, _)| source.as_str())
+    }
+
+    /// Keys reported by `source`, in order.
+    pub fn keys_for_source<'a>(&'a self, source: &'a str) -> impl Iterator<Item = &'a str> {
+        self.series
+            .keys()
+            .filter(move |(series_source, _)| series_source == source)
+            .map(|(_, key)| key.as_str())
+    }
+
+    /// Number of tracked series.
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Return `true` if no samples have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    fn points(&self, source: &str, key: &str) -> Option<&VecDeque<TelemetryPoint>> {
+        self.series.get(&(source.to_string(), key.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Histogram;
+
+    fn telemetry(value: TelemetryValue) -> Event {
+        Event::Telemetry {
+            source: "stats".into(),
+            key: "cpu".into(),
+            value,
+        }
+    }
+
+    #[test]
+    fn ingest_records_only_telemetry_events() {
+        let mut store = TelemetryStore::default();
+        let now = Instant::now();
+        assert!(!store.ingest(&Event::Quit, now));
+        assert!(store.ingest(&telemetry(TelemetryValue::Float(1.5)), now));
+        assert_eq!(store.len(), 1);
+        assert_eq!(
+            store.latest("stats", "cpu").unwrap().value.as_f64(),
+            Some(1.5)
+        );
+        assert!(store.latest("stats", "mem").is_none());
+    }
+
+    #[test]
+    fn flags_durations_and_histograms_read_as_numbers() {
+        let mut histogram = Histogram::new([10.0, 1.0, f64::NAN, 5.0, 5.0]);
+        assert_eq!(histogram.mean(), None);
+        for value in [0.5, 1.0, 4.0, 12.0, f64::INFINITY] {
+            histogram.observe(value);
+        }
+        assert_eq!(histogram.bounds(), [1.0, 5.0, 10.0]);
+        assert_eq!(histogram.counts(), [2, 1, 0, 1]);
+        assert_eq!(histogram.mean(), Some(4.375));
+
+        let mut store = TelemetryStore::default();
+        let now = Instant::now();
+        store.record("stats", "ac", TelemetryValue::Bool(true), now);
+        store.record("stats", "ac", TelemetryValue::Bool(false), now);
+        store.record(
+            "stats",
+            "frame",
+            TelemetryValue::duration(Duration::from_micros(2500)),
+            now,
+        );
+        store.record("stats", "frame", TelemetryValue::Histogram(histogram), now);
+        assert_eq!(store.numeric_last_n("stats", "ac", 2), vec![1.0, 0.0]);
+        assert_eq!(store.numeric_last_n("stats", "frame", 2), vec![2.5, 4.375]);
+
+        let json = serde_json::to_value(store.snapshot()).unwrap();
+        assert_eq!(json[0]["value"], false);
+        assert_eq!(json[1]["value"]["counts"], serde_json::json!([2, 1, 0, 1]));
+        assert_eq!(
+            serde_json::to_value(TelemetryValue::DurationMs(2.5)).unwrap(),
+            serde_json::json!({ "duration_ms": 2.5 })
+        );
+    }
+
+    #[test]
+    fn ring_evicts_oldest_samples() {
+        let mut store = TelemetryStore::new(3);
+        let now = Instant::now();
+        for i in 0..5 {
+            store.record("stats", "cpu", TelemetryValue::Int(i), now);
+        }
+        assert_eq!(
+            store.numeric_last_n("stats", "cpu", 10),
+            vec![2.0, 3.0, 4.0]
+        );
+        assert_eq!(store.numeric_last_n("stats", "cpu", 2), vec![3.0, 4.0]);
+        assert_eq!(store.last_n("stats", "cpu", 1).len(), 1);
+    }
+
+    #[test]
+    fn window_delta_uses_baseline_before_window() {
+        let mut store = TelemetryStore::default();
+        let start = Instant::now();
+        store.record("plugin", "errors", TelemetryValue::Int(2), start);
+        let t1 = start + Duration::from_secs(30);
+        store.record("plugin", "errors", TelemetryValue::Int(4), t1);
+        let t2 = start + Duration::from_secs(90);
+        store.record("plugin", "errors", TelemetryValue::Int(11), t2);
+
+        let delta = store.window_delta("plugin", "errors", Duration::from_secs(60), t2);
+        assert_eq!(delta, Some(7.0));
+        assert_eq!(
+            store.window_delta("plugin", "errors", Duration::from_secs(600), t2),
+            Some(9.0)
+        );
+        assert_eq!(
+            store.sources_with_key("errors").collect::<Vec<_>>(),
+            vec!["plugin"]
+        );
+        store.record("stats", "cpu", TelemetryValue::Float(1.0), t2);
+        assert_eq!(
+            store.keys_for_source("plugin").collect::<Vec<_>>(),
+            vec!["errors"]
+        );
+    }
+
+    #[test]
+    fn window_stats_cover_only_recent_numeric_samples() {
+        let mut store = TelemetryStore::default();
+        let start = Instant::now();
+        store.record("stats", "cpu", TelemetryValue::Float(100.0), start);
+        let later = start + Duration::from_secs(10);
+        store.record("stats", "cpu", TelemetryValue::Float(10.0), later);
+        store.record("stats", "cpu", TelemetryValue::Text("n/a".into()), later);
+        store.record("stats", "cpu", TelemetryValue::Float(30.0), later);
+
+        let stats = store
+            .window_stats("stats", "cpu", Duration::from_secs(5), later)
+            .unwrap();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.min, 10.0);
+        assert_eq!(stats.max, 30.0);
+        assert_eq!(stats.avg, 20.0);
+
+        let all = store
+            .window_stats("stats", "cpu", Duration::from_secs(60), later)
+            .unwrap();
+        assert_eq!(all.count, 3);
+        assert!(store
+            .window_stats("stats", "mem", Duration::from_secs(60), later)
+            .is_none());
+    }
+}
@@ -0,0 +1,148 @@
+//! Undo for console actions that change or drop state.
+//!
+//! An action that can be reversed pushes its inverse onto the
+//! [`UndoStack`], and `undo` pops the newest one and applies it. Core
+//! commands push inverses the console can apply itself
+//! ([`Inverse::RestoreLogs`]); the app records the rest: the previous value
+//! of a setting changed by command ([`Inverse::Setting`]) and command lines
+//! that reverse others ([`Inverse::Run`], `plugins start` after `plugins
+//! stop`). Entries that hold a lot, like a cleared log, expire after a
+//! grace period.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::logging::LogEntry;
+
+/// How long `clear` can be undone.
+pub const CLEAR_GRACE: Duration = Duration::from_secs(60);
+/// Entries kept; the oldest is dropped past this.
+pub const UNDO_CAPACITY: usize = 32;
+
+/// What undoing an action does.
+#[derive(Debug, Clone)]
+pub enum Inverse {
+    /// Put back console log lines removed by `clear`, before any logged
+    /// since.
+    RestoreLogs(Vec<LogEntry>),
+    /// Set the setting owned by command `name` back to `previous`, as the
+    /// command stores it (`null` for "follow `spud.toml`").
+    Setting {
+        name: String,
+        previous: serde_json::Value,
+    },
+    /// Run a command line that reverses the action.
+    Run(String),
+}
+
+/// One reversible action.
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    /// The action as typed, shown by `undo list` and once undone.
+    pub label: String,
+    pub inverse: Inverse,
+    pub recorded_at: Instant,
+    /// After this the entry can no longer be undone.
+    pub expires_at: Option<Instant>,
+}
+
+impl UndoEntry {
+    fn expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// Reversible actions, newest last.
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    entries: VecDeque<UndoEntry>,
+}
+
+impl UndoStack {
+    /// Record an action that can be undone until it falls off the stack.
+    pub fn push(&mut self, label: impl Into<String>, inverse: Inverse, now: Instant) {
+        self.push_entry(label.into(), inverse, now, None);
+    }
+
+    /// Record an action that can be undone for `grace` only.
+    pub fn push_expiring(
+        &mut self,
+        label: impl Into<String>,
+        inverse: Inverse,
+        now: Instant,
+        grace: Duration,
+    ) {
+        self.push_entry(label.into(), inverse, now, Some(now + grace));
+    }
+
+    fn push_entry(
+        &mut self,
+        label: String,
+        inverse: Inverse,
+        now: Instant,
+        expires_at: Option<Instant>,
+    ) {
+        if self.entries.len() >= UNDO_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(UndoEntry {
+            label,
+            inverse,
+            recorded_at: now,
+            expires_at,
+        });
+    }
+
+    /// Take the newest action that can still be undone.
+    pub fn pop(&mut self, now: Instant) -> Option<UndoEntry> {
+        self.entries.retain(|entry| !entry.expired(now));
+        self.entries.pop_back()
+    }
+
+    /// Actions that can still be undone, newest first.
+    pub fn entries(&self, now: Instant) -> impl Iterator<Item = &UndoEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(move |entry| !entry.expired(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(stack: &UndoStack, now: Instant) -> Vec<&str> {
+        stack
+            .entries(now)
+            .map(|entry| entry.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn pops_newest_first_and_skips_expired() {
+        let mut stack = UndoStack::default();
+        let now = Instant::now();
+        stack.push("hud height 12", Inverse::Run("hud reset".into()), now);
+        stack.push_expiring("clear", Inverse::RestoreLogs(Vec::new()), now, CLEAR_GRACE);
+        assert_eq!(labels(&stack, now), ["clear", "hud height 12"]);
+
+        // Past the grace period the cleared log is gone
+        let later = now + CLEAR_GRACE;
+        assert_eq!(labels(&stack, later), ["hud height 12"]);
+        let entry = stack.pop(later).unwrap();
+        assert!(matches!(entry.inverse, Inverse::Run(line) if line == "hud reset"));
+        assert!(stack.pop(later).is_none());
+    }
+
+    #[test]
+    fn drops_the_oldest_past_capacity() {
+        let mut stack = UndoStack::default();
+        let now = Instant::now();
+        for i in 0..=UNDO_CAPACITY {
+            stack.push(format!("step {i}"), Inverse::Run(String::new()), now);
+        }
+        assert_eq!(stack.entries(now).count(), UNDO_CAPACITY);
+        assert_eq!(stack.entries(now).last().unwrap().label, "step 1");
+    }
+}
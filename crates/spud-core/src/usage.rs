@@ -0,0 +1,97 @@
+//! How often each console command is run, kept on this machine only.
+//!
+//! The app records every command that runs and saves the counts with the
+//! session. They rank name completions, so the commands someone actually
+//! uses come first, and `stats commands` lists them. Nothing leaves the
+//! state dir.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Runs of one command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageCount {
+    pub count: u64,
+    /// Unix time of the latest run, in seconds.
+    pub last_used: u64,
+}
+
+/// Usage counts by command name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CommandUsage {
+    commands: BTreeMap<String, UsageCount>,
+}
+
+impl CommandUsage {
+    /// Count a run of `name` at `at`.
+    pub fn record(&mut self, name: &str, at: SystemTime) {
+        let entry = self.commands.entry(name.to_string()).or_default();
+        entry.count += 1;
+        entry.last_used = at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+    }
+
+    /// Runs of `name`; zero for a command never run.
+    pub fn count(&self, name: &str) -> u64 {
+        self.commands.get(name).map_or(0, |usage| usage.count)
+    }
+
+    /// Every command run so far, most used first, then most recent.
+    pub fn ranked(&self) -> Vec<(&str, UsageCount)> {
+        let mut ranked: Vec<_> = self
+            .commands
+            .iter()
+            .map(|(name, usage)| (name.as_str(), *usage))
+            .collect();
+        ranked.sort_by(|(a_name, a), (b_name, b)| {
+            b.count
+                .cmp(&a.count)
+                .then(b.last_used.cmp(&a.last_used))
+                .then(a_name.cmp(b_name))
+        });
+        ranked
+    }
+
+    /// Whether no command has run yet.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Forget every count.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn ranks_by_count_then_recency() {
+        let mut usage = CommandUsage::default();
+        usage.record("tps", at(10));
+        usage.record("stats", at(20));
+        usage.record("switch", at(30));
+        usage.record("stats", at(40));
+        assert_eq!(usage.count("stats"), 2);
+        assert_eq!(usage.count("help"), 0);
+        let names: Vec<_> = usage.ranked().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["stats", "switch", "tps"]);
+        assert_eq!(usage.ranked()[0].1.last_used, 40);
+
+        let json = serde_json::to_string(&usage).unwrap();
+        assert_eq!(serde_json::from_str::<CommandUsage>(&json).unwrap(), usage);
+        usage.clear();
+        assert!(usage.is_empty());
+    }
+}
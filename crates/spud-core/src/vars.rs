@@ -0,0 +1,133 @@
+//! `$VAR` substitutions for console commands.
+//!
+//! Values are snapshotted from application state before a command line runs,
+//! so `switch stats ; echo $active_module` still sees the old module.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::event::TelemetryValue;
+use crate::fps::TickCounter;
+use crate::registry::ModuleRegistry;
+use crate::telemetry::TelemetryStore;
+
+/// Named values available as `$name` or `${name}` in console input.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vars {
+    values: BTreeMap<String, String>,
+}
+
+impl Vars {
+    /// Create an empty set of variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Built-in variables derived from core application state.
+    ///
+    /// Unset values (e.g. `last_module` before any switch) expand to empty.
+    pub fn builtin(
+        registry: &ModuleRegistry,
+        tick_counter: &TickCounter,
+        started_at: Instant,
+    ) -> Self {
+        let mut vars = Self::new();
+        let active = registry.active();
+        vars.set("active_module", active.map(|m| m.id()).unwrap_or_default());
+        vars.set(
+            "active_title",
+            active.map(|m| m.title()).unwrap_or_default(),
+        );
+        vars.set("last_module", registry.previous_id().unwrap_or_default());
+        vars.set(
+            "modules",
+            registry
+                .list()
+                .iter()
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        let uptime = Instant::now()
+            .checked_duration_since(started_at)
+            .unwrap_or(Duration::ZERO);
+        vars.set("uptime", uptime.as_secs().to_string());
+        vars.set("tps", format!("{:.1}", tick_counter.tps()));
+        vars
+    }
+
+    /// Add the latest value of every telemetry series as `source.key`, so
+    /// `${stats.cpu}` expands and `when stats.cpu > 80 ...` compares it.
+    /// Durations read in milliseconds and histograms as their mean.
+    pub fn add_telemetry(&mut self, store: &TelemetryStore) {
+        for (source, key, point) in store.iter_latest() {
+            let value = match &point.value {
+                TelemetryValue::Text(text) => text.clone(),
+                TelemetryValue::Bool(flag) => flag.to_string(),
+                TelemetryValue::Int(value) => value.to_string(),
+                other => other
+                    .as_f64()
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+            };
+            self.set(format!("{source}.{key}"), value);
+        }
+    }
+
+    /// Set (or overwrite) a variable.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Look up a variable by name (without the `$`).
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Iterate `(name, value)` pairs in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+/// Whether `c` may appear in a bare `$name`.
+pub(crate) fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_reflects_registry_state() {
+        let registry = ModuleRegistry::new();
+        let vars = Vars::builtin(&registry, &TickCounter::default(), Instant::now());
+        assert_eq!(vars.get("active_module"), Some(""));
+        assert_eq!(vars.get("tps"), Some("0.0"));
+        assert!(vars.get("nope").is_none());
+        assert!(vars.iter().any(|(name, _)| name == "last_module"));
+    }
+
+    #[test]
+    fn telemetry_reads_as_source_dot_key() {
+        let mut store = TelemetryStore::default();
+        let now = Instant::now();
+        store.record("stats", "cpu", TelemetryValue::Float(12.5), now);
+        store.record("stats", "cpu", TelemetryValue::Float(81.0), now);
+        store.record("net", "up", TelemetryValue::Bool(true), now);
+        store.record(
+            "net",
+            "rtt",
+            TelemetryValue::duration(Duration::from_millis(40)),
+            now,
+        );
+        let mut vars = Vars::new();
+        vars.add_telemetry(&store);
+        assert_eq!(vars.get("stats.cpu"), Some("81"));
+        assert_eq!(vars.get("net.up"), Some("true"));
+        assert_eq!(vars.get("net.rtt"), Some("40"));
+    }
+}
@@ -9,7 +9,8 @@ use ratatui::{
 
 use spud_core::{
     event::Event,
-    module::{HudContribution, Module},
+    i18n::tr,
+    module::{HudContribution, HudLine, HudStyle, Module},
 };
 use spud_ui::renderer::HeroRenderer;
 
@@ -38,18 +39,25 @@ impl Module for HelloModule {
         "hello"
     }
     fn title(&self) -> &'static str {
-        "Hello"
+        tr("Hello")
     }
 
     fn handle_event(&mut self, _ev: &Event) {}
 
     fn hud(&self) -> HudContribution {
         HudContribution {
-            left_lines: vec!["Tab: next module".into(), "q: quit".into()],
-            right_lines: vec!["HMR: (planned)".into(), "IMG: (planned)".into()],
+            left_lines: Vec::new(),
+            right_lines: vec![
+                HudLine::key_value("HMR:", "(planned)", HudStyle::Dim),
+                HudLine::key_value("IMG:", "(planned)", HudStyle::Dim),
+            ],
         }
     }
 
+    fn as_hero_renderer(&self) -> Option<&dyn HeroRenderer> {
+        Some(self)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -59,13 +67,18 @@ impl HeroRenderer for HelloModule {
     fn render_hero(&self, f: &mut Frame, area: Rect) {
         let p = Paragraph::new(vec![
             Line::from("SPUD"),
-            Line::from("Suspiciously Powerful Utility of De-evolution"),
+            Line::from(tr("Suspiciously Powerful Utility of De-evolution")),
             Line::from(""),
-            Line::from("Hello World"),
+            Line::from(tr("Hello World")),
         ])
         .alignment(Alignment::Center)
         .block(Block::default().borders(Borders::ALL).title("HERO"));
 
         f.render_widget(p, area);
     }
+
+    /// The text never changes, so the shell renders it once per hero size.
+    fn content_version(&self) -> Option<u64> {
+        Some(0)
+    }
 }
@@ -0,0 +1,83 @@
+//! State shared between the plugins module and the host.
+//!
+//! Modules cannot see the plugin runtime, so the host publishes what the
+//! module shows into a [`PluginFeed`] and runs the [`PluginAction`]s the
+//! module queues there.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use spud_config::PluginManifest;
+use spud_remote::metrics::PluginStats;
+use spud_remote::runtime::PluginSessionSnapshot;
+
+/// One registered plugin as the host last saw it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginEntry {
+    pub manifest: PluginManifest,
+    pub session: PluginSessionSnapshot,
+    pub stats: PluginStats,
+}
+
+/// A change to a plugin's process, requested from the module's keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginAction {
+    Start(String),
+    Stop(String),
+    Restart(String),
+}
+
+impl PluginAction {
+    /// Subcommand of the `plugins` console command that performs it.
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Start(_) => "start",
+            Self::Stop(_) => "stop",
+            Self::Restart(_) => "restart",
+        }
+    }
+
+    pub fn plugin_id(&self) -> &str {
+        match self {
+            Self::Start(id) | Self::Stop(id) | Self::Restart(id) => id,
+        }
+    }
+}
+
+/// Handle to the shared state; clones share it.
+#[derive(Debug, Clone, Default)]
+pub struct PluginFeed(Arc<Mutex<FeedState>>);
+
+#[derive(Debug, Default)]
+pub(crate) struct FeedState {
+    /// Whether the host runs a plugin runtime at all.
+    pub(crate) enabled: bool,
+    /// Registered plugins in ID order.
+    pub(crate) plugins: Vec<PluginEntry>,
+    /// Queued by the module for the host to run.
+    pub(crate) actions: Vec<PluginAction>,
+}
+
+impl PluginFeed {
+    /// Replace what the module shows. `None` means the plugin runtime is
+    /// off.
+    pub fn publish(&self, plugins: Option<Vec<PluginEntry>>) {
+        let mut state = self.lock();
+        state.enabled = plugins.is_some();
+        state.plugins = plugins.unwrap_or_default();
+    }
+
+    /// Actions queued by the module since the last call, oldest first.
+    pub fn take_actions(&self) -> Vec<PluginAction> {
+        std::mem::take(&mut self.lock().actions)
+    }
+
+    pub(crate) fn request(&self, action: PluginAction) {
+        self.lock().actions.push(action);
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, FeedState> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
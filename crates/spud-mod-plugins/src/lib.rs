@@ -0,0 +1,269 @@
+mod feed;
+mod render;
+
+use std::any::Any;
+
+use crossterm::event::KeyCode;
+use ratatui::{layout::Rect, Frame};
+
+use spud_core::{
+    i18n::tr,
+    keymap::{ActionId, KeyBinding, KeyChord},
+    module::{HudContribution, HudLine, HudStyle, Module},
+};
+use spud_remote::health::HealthStatus;
+use spud_ui::renderer::HeroRenderer;
+
+pub use feed::{PluginAction, PluginEntry, PluginFeed};
+
+/// Actions of the module's keys.
+mod action {
+    use spud_core::keymap::ActionId;
+
+    pub(crate) const UP: ActionId = "up";
+    pub(crate) const DOWN: ActionId = "down";
+    pub(crate) const INSPECT: ActionId = "inspect";
+    pub(crate) const START: ActionId = "start";
+    pub(crate) const STOP: ActionId = "stop";
+    pub(crate) const RESTART: ActionId = "restart";
+}
+
+/// Plugin runtime overview: each plugin's state, manifest, permissions, and
+/// live counters, with keys to start, stop, and restart plugins.
+///
+/// The host fills the module's [`PluginFeed`] while the module is active
+/// and runs the actions it queues, so nothing here touches the runtime
+/// directly.
+pub struct PluginsModule {
+    feed: PluginFeed,
+    /// Index of the highlighted plugin, clamped to the list when drawn.
+    selected: usize,
+    /// Show the details pane for the highlighted plugin.
+    inspect: bool,
+}
+
+impl Default for PluginsModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PluginsModule {
+    /// Create a module with an empty feed; the host publishes into it.
+    pub fn new() -> Self {
+        Self {
+            feed: PluginFeed::default(),
+            selected: 0,
+            inspect: false,
+        }
+    }
+
+    /// The handle the host publishes plugin state into.
+    pub fn feed(&self) -> PluginFeed {
+        self.feed.clone()
+    }
+
+    /// ID of the highlighted plugin, if any are registered.
+    fn selected_id(&self) -> Option<String> {
+        let state = self.feed.lock();
+        let index = self.selected.min(state.plugins.len().checked_sub(1)?);
+        Some(state.plugins[index].manifest.id.clone())
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let len = self.feed.lock().plugins.len();
+        let current = self.selected.min(len.saturating_sub(1));
+        self.selected = if down {
+            (current + 1).min(len.saturating_sub(1))
+        } else {
+            current.saturating_sub(1)
+        };
+    }
+}
+
+impl Module for PluginsModule {
+    fn id(&self) -> &'static str {
+        "plugins"
+    }
+
+    fn title(&self) -> &'static str {
+        tr("Plugins")
+    }
+
+    fn keybindings(&self) -> Vec<KeyBinding> {
+        let bind = |chord, action, description| KeyBinding::new(chord, action, tr(description));
+        vec![
+            bind(KeyChord::key(KeyCode::Up), action::UP, "previous plugin"),
+            bind(KeyChord::char('k'), action::UP, "previous plugin"),
+            bind(KeyChord::key(KeyCode::Down), action::DOWN, "next plugin"),
+            bind(KeyChord::char('j'), action::DOWN, "next plugin"),
+            bind(
+                KeyChord::key(KeyCode::Enter),
+                action::INSPECT,
+                "inspect plugin",
+            ),
+            bind(KeyChord::char('i'), action::INSPECT, "inspect plugin"),
+            bind(KeyChord::char('s'), action::START, "start plugin"),
+            bind(KeyChord::char('x'), action::STOP, "stop plugin"),
+            bind(KeyChord::char('r'), action::RESTART, "restart plugin"),
+        ]
+    }
+
+    fn handle_action(&mut self, action: ActionId) {
+        let request: fn(String) -> PluginAction = match action {
+            action::UP => return self.move_selection(false),
+            action::DOWN => return self.move_selection(true),
+            action::INSPECT => {
+                self.inspect = !self.inspect;
+                return;
+            }
+            action::START => PluginAction::Start,
+            action::STOP => PluginAction::Stop,
+            action::RESTART => PluginAction::Restart,
+            _ => return,
+        };
+        if let Some(id) = self.selected_id() {
+            self.feed.request(request(id));
+        }
+    }
+
+    fn hud(&self) -> HudContribution {
+        let state = self.feed.lock();
+        let running = state
+            .plugins
+            .iter()
+            .filter(|entry| entry.session.running)
+            .count();
+        let unhealthy = state
+            .plugins
+            .iter()
+            .filter(|entry| entry.session.health == Some(HealthStatus::Unhealthy))
+            .count();
+        let errors: u64 = state
+            .plugins
+            .iter()
+            .map(|entry| entry.stats.errors_returned)
+            .sum();
+
+        let right_lines = if state.enabled {
+            vec![
+                HudLine::key_value(
+                    "RUN:",
+                    format!("{running}/{}", state.plugins.len()),
+                    if running < state.plugins.len() {
+                        HudStyle::Warn
+                    } else {
+                        HudStyle::Good
+                    },
+                ),
+                HudLine::key_value(
+                    "UNHEALTHY:",
+                    unhealthy.to_string(),
+                    if unhealthy > 0 {
+                        HudStyle::Critical
+                    } else {
+                        HudStyle::Normal
+                    },
+                ),
+                HudLine::key_value(
+                    "ERRORS:",
+                    errors.to_string(),
+                    if errors > 0 {
+                        HudStyle::Warn
+                    } else {
+                        HudStyle::Normal
+                    },
+                ),
+            ]
+        } else {
+            vec![HudLine::key_value("RUNTIME:", tr("off"), HudStyle::Dim)]
+        };
+
+        HudContribution {
+            left_lines: Vec::new(),
+            right_lines,
+        }
+    }
+
+    fn as_hero_renderer(&self) -> Option<&dyn HeroRenderer> {
+        Some(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl HeroRenderer for PluginsModule {
+    fn render_hero(&self, f: &mut Frame, area: Rect) {
+        let state = self.feed.lock();
+        render::render_hero_content(f, area, &state, self.selected, self.inspect);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use spud_core::keymap;
+
+    use crate::render::tests::entry;
+
+    fn press(module: &mut PluginsModule, code: KeyCode) {
+        let key = KeyEvent::new(code, KeyModifiers::NONE);
+        let action = keymap::action_for(&module.keybindings(), &key).unwrap();
+        module.handle_action(action);
+    }
+
+    #[test]
+    fn keys_queue_actions_for_the_selected_plugin() {
+        let mut module = PluginsModule::new();
+        let feed = module.feed();
+        press(&mut module, KeyCode::Char('r'));
+        assert!(feed.take_actions().is_empty());
+
+        feed.publish(Some(vec![entry("spud.a", true), entry("spud.b", false)]));
+        press(&mut module, KeyCode::Down);
+        press(&mut module, KeyCode::Down);
+        press(&mut module, KeyCode::Char('s'));
+        press(&mut module, KeyCode::Up);
+        press(&mut module, KeyCode::Char('x'));
+        assert_eq!(
+            feed.take_actions(),
+            vec![
+                PluginAction::Start("spud.b".into()),
+                PluginAction::Stop("spud.a".into())
+            ]
+        );
+
+        // The selection follows the list when it shrinks.
+        press(&mut module, KeyCode::Char('j'));
+        feed.publish(Some(vec![entry("spud.a", true)]));
+        press(&mut module, KeyCode::Char('r'));
+        assert_eq!(
+            feed.take_actions(),
+            vec![PluginAction::Restart("spud.a".into())]
+        );
+    }
+
+    #[test]
+    fn hud_counts_running_and_unhealthy_plugins() {
+        let module = PluginsModule::new();
+        let text = |module: &PluginsModule| {
+            module
+                .hud()
+                .right_lines
+                .iter()
+                .map(HudLine::plain_text)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(&module), vec!["RUNTIME: off"]);
+
+        let mut sick = entry("spud.b", true);
+        sick.session.health = Some(HealthStatus::Unhealthy);
+        module
+            .feed()
+            .publish(Some(vec![entry("spud.a", false), sick]));
+        assert_eq!(text(&module), vec!["RUN: 1/2", "UNHEALTHY: 1", "ERRORS: 0"]);
+    }
+}
@@ -0,0 +1,289 @@
+/// Hero-pane rendering for the plugins module.
+///
+/// A table of registered plugins with their state and counters, and with
+/// inspect on, a details pane for the highlighted one: manifest summary,
+/// permission grants, and the full counters.
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, Table},
+    Frame,
+};
+
+use spud_core::i18n::tr;
+use spud_remote::health::HealthStatus;
+
+use crate::feed::{FeedState, PluginEntry};
+
+/// Rows the details pane takes when shown.
+const DETAILS_HEIGHT: u16 = 11;
+
+/// Render the plugins hero pane. `selected` is clamped to the list.
+pub(crate) fn render_hero_content(
+    f: &mut Frame,
+    area: Rect,
+    state: &FeedState,
+    selected: usize,
+    inspect: bool,
+) {
+    let block = Block::default().borders(Borders::ALL).title(tr("PLUGINS"));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if !state.enabled {
+        let message = tr("plugin runtime disabled (pass --plugin-dir or set SPUD_PLUGIN_DIRS)");
+        f.render_widget(
+            Paragraph::new(message).style(Style::default().fg(Color::DarkGray)),
+            inner,
+        );
+        return;
+    }
+    let Some(last) = state.plugins.len().checked_sub(1) else {
+        f.render_widget(
+            Paragraph::new(tr("no plugins discovered")).style(Style::default().fg(Color::DarkGray)),
+            inner,
+        );
+        return;
+    };
+    let selected = selected.min(last);
+
+    let [table_area, details_area] = if inspect {
+        Layout::vertical([Constraint::Min(3), Constraint::Length(DETAILS_HEIGHT)]).areas(inner)
+    } else {
+        [inner, Rect::default()]
+    };
+    render_table(f, table_area, &state.plugins, selected);
+    if inspect {
+        render_details(f, details_area, &state.plugins[selected]);
+    }
+}
+
+fn render_table(f: &mut Frame, area: Rect, plugins: &[PluginEntry], selected: usize) {
+    let header = Row::new([
+        "PLUGIN", "VERSION", "STATE", "HEALTH", "REQ", "ERR", "RESTARTS", "LAT",
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = plugins.iter().enumerate().map(|(index, entry)| {
+        let (state, state_color) = state_label(entry);
+        let row = Row::new(vec![
+            Span::raw(entry.manifest.id.clone()),
+            Span::raw(entry.manifest.version.clone()),
+            Span::styled(state, Style::default().fg(state_color)),
+            Span::raw(
+                entry
+                    .session
+                    .health
+                    .map_or_else(|| "-".to_string(), |health| health.to_string()),
+            ),
+            Span::raw(entry.stats.requests_handled.to_string()),
+            Span::styled(
+                entry.stats.errors_returned.to_string(),
+                if entry.stats.errors_returned > 0 {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                },
+            ),
+            Span::raw(entry.stats.restarts.to_string()),
+            Span::raw(format_duration(entry.stats.last_latency)),
+        ]);
+        if index == selected {
+            row.style(Style::default().add_modifier(Modifier::REVERSED))
+        } else {
+            row
+        }
+    });
+    let widths = [
+        Constraint::Fill(3),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(7),
+        Constraint::Length(5),
+        Constraint::Length(8),
+        Constraint::Length(8),
+    ];
+    f.render_widget(Table::new(rows, widths).header(header), area);
+}
+
+/// State word and colour: stopped plugins and unhealthy ones stand out.
+fn state_label(entry: &PluginEntry) -> (&'static str, Color) {
+    match (entry.session.running, entry.session.health) {
+        (false, _) => ("stopped", Color::DarkGray),
+        (true, Some(HealthStatus::Unhealthy)) => ("running", Color::Red),
+        (true, _) => ("running", Color::Green),
+    }
+}
+
+fn render_details(f: &mut Frame, area: Rect, entry: &PluginEntry) {
+    let manifest = &entry.manifest;
+    let stats = &entry.stats;
+    let list = |values: &[String]| {
+        if values.is_empty() {
+            "none".to_string()
+        } else {
+            values.join(", ")
+        }
+    };
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{label:<14}"), Style::default().fg(Color::DarkGray)),
+            Span::raw(value),
+        ])
+    };
+    let runtime = match &manifest.runtime.command {
+        Some(command) => format!("{command} {}", manifest.runtime.entrypoint),
+        None => manifest.runtime.entrypoint.clone(),
+    };
+    let api = match &entry.session.api_version {
+        Some(version) => format!("{} (using {version})", manifest.compatibility.host_api),
+        None => manifest.compatibility.host_api.clone(),
+    };
+    let permissions = &manifest.permissions;
+    let lines = vec![
+        field("name", format!("{} {}", manifest.name, manifest.version)),
+        field(
+            "manifest",
+            entry.session.manifest_path.display().to_string(),
+        ),
+        field("runtime", runtime),
+        field("host api", api),
+        field("commands", list(&permissions.commands)),
+        field("event tags", list(&permissions.event_tags)),
+        field("subscriptions", list(&permissions.subscriptions)),
+        field(
+            "telemetry",
+            permissions
+                .telemetry_keys
+                .as_deref()
+                .map_or_else(|| "all".to_string(), list),
+        ),
+        field(
+            "notifications",
+            format!(
+                "{} delivered, {} dropped",
+                stats.notifications_delivered, stats.notifications_dropped
+            ),
+        ),
+        field(
+            "queue wait",
+            format!(
+                "max {}, {} starved",
+                format_duration(stats.max_queue_wait),
+                stats.starved_requests
+            ),
+        ),
+    ];
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .title(manifest.id.as_str());
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn format_duration(duration: Option<Duration>) -> String {
+    match duration {
+        Some(duration) => format!("{:.1}ms", duration.as_secs_f64() * 1000.0),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+    use spud_config::PluginManifest;
+    use spud_remote::metrics::PluginStats;
+    use spud_remote::runtime::PluginSessionSnapshot;
+
+    /// A registered plugin with the given ID and state.
+    pub(crate) fn entry(id: &str, running: bool) -> PluginEntry {
+        let manifest = PluginManifest::from_toml_str(&format!(
+            r#"
+id = "{id}"
+name = "Fixture"
+version = "0.1.0"
+
+[runtime]
+entrypoint = "plugin.sh"
+command = "sh"
+
+[compatibility]
+host_api = "^1.0.0"
+
+[permissions]
+commands = ["help"]
+"#
+        ))
+        .unwrap();
+        PluginEntry {
+            session: PluginSessionSnapshot {
+                plugin_id: id.to_string(),
+                version: manifest.version.clone(),
+                manifest_path: format!("/plugins/{id}/plugin.toml").into(),
+                running,
+                api_version: running.then(|| "1.2.0".to_string()),
+                health: None,
+                subscriptions: Vec::new(),
+                dev: false,
+            },
+            stats: PluginStats {
+                plugin_id: id.to_string(),
+                running,
+                requests_handled: 12,
+                ..PluginStats::default()
+            },
+            manifest,
+        }
+    }
+
+    fn render(state: &FeedState, selected: usize, inspect: bool) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(90, 24)).unwrap();
+        terminal
+            .draw(|f| render_hero_content(f, f.area(), state, selected, inspect))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn shows_runtime_off_and_empty_states() {
+        let text = render(&FeedState::default(), 0, false);
+        assert!(text.contains("plugin runtime disabled"));
+
+        let state = FeedState {
+            enabled: true,
+            ..FeedState::default()
+        };
+        assert!(render(&state, 0, false).contains("no plugins discovered"));
+    }
+
+    #[test]
+    fn table_lists_plugins_and_inspect_shows_details() {
+        let state = FeedState {
+            enabled: true,
+            plugins: vec![entry("spud.alpha", true), entry("spud.beta", false)],
+            ..FeedState::default()
+        };
+        let text = render(&state, 5, false);
+        assert!(text.contains("spud.alpha"));
+        assert!(text.contains("running"));
+        assert!(text.contains("stopped"));
+        assert!(!text.contains("subscriptions"));
+
+        let text = render(&state, 5, true);
+        assert!(text.contains("/plugins/spud.beta/plugin.toml"));
+        assert!(text.contains("sh plugin.sh"));
+        assert!(text.contains("commands      help"));
+        assert!(text.contains("telemetry     all"));
+    }
+}
@@ -0,0 +1,197 @@
+/// Battery sampling from Linux's `/sys/class/power_supply`.
+///
+/// Drivers report either energy (µWh, with power in µW) or charge (µAh,
+/// with current in µA); both are handled. Only the first battery is
+/// sampled. Other platforms, and machines without a battery, report none.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Where Linux exposes batteries and AC adapters.
+#[cfg(target_os = "linux")]
+const POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+
+/// Whether the battery is charging, per the driver's `status` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeState {
+    Charging,
+    Discharging,
+    Full,
+    /// `Not charging` (e.g. a charge limit was reached) or unrecognised.
+    Unknown,
+}
+
+impl ChargeState {
+    /// Lowercase name, used in telemetry.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Charging => "charging",
+            Self::Discharging => "discharging",
+            Self::Full => "full",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    fn parse(status: &str) -> Self {
+        match status.trim() {
+            "Charging" => Self::Charging,
+            "Discharging" => Self::Discharging,
+            "Full" => Self::Full,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Battery metrics at one point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatterySnapshot {
+    /// Charge percentage (0.0–100.0).
+    pub percent: f32,
+    pub state: ChargeState,
+    /// Until empty while discharging, until full while charging; `None`
+    /// when the driver does not report the rate.
+    pub time_remaining: Option<Duration>,
+    /// Power flowing in or out, in watts.
+    pub power_watts: Option<f32>,
+}
+
+/// The first battery on this machine, if any.
+#[cfg(target_os = "linux")]
+pub fn detect() -> Option<PathBuf> {
+    find_battery(Path::new(POWER_SUPPLY_ROOT))
+}
+
+/// The first battery on this machine; only Linux is supported.
+#[cfg(not(target_os = "linux"))]
+pub fn detect() -> Option<PathBuf> {
+    None
+}
+
+/// First supply under `root` whose `type` is `Battery`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn find_battery(root: &Path) -> Option<PathBuf> {
+    let mut batteries: Vec<PathBuf> = fs::read_dir(root)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|supply| {
+            fs::read_to_string(supply.join("type")).is_ok_and(|kind| kind.trim() == "Battery")
+        })
+        .collect();
+    batteries.sort();
+    batteries.into_iter().next()
+}
+
+/// Read a battery's sysfs files; `None` if it has gone away.
+pub fn sample(supply: &Path) -> Option<BatterySnapshot> {
+    let read = |name: &str| -> Option<f64> {
+        fs::read_to_string(supply.join(name))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let state = ChargeState::parse(&fs::read_to_string(supply.join("status")).ok()?);
+
+    // Energy in µWh and power in µW, or charge in µAh and current in µA;
+    // either way `now / rate` is hours.
+    let (now, full, rate) = match (read("energy_now"), read("energy_full")) {
+        (Some(now), Some(full)) => (Some(now), Some(full), read("power_now")),
+        _ => (read("charge_now"), read("charge_full"), read("current_now")),
+    };
+    let percent = match (now, full) {
+        (Some(now), Some(full)) if full > 0.0 => (now / full * 100.0).min(100.0) as f32,
+        _ => read("capacity")? as f32,
+    };
+    let rate = rate.filter(|rate| *rate > 0.0);
+    let hours = match (state, now, full, rate) {
+        (ChargeState::Discharging, Some(now), _, Some(rate)) => Some(now / rate),
+        (ChargeState::Charging, Some(now), Some(full), Some(rate)) => {
+            Some((full - now).max(0.0) / rate)
+        }
+        _ => None,
+    };
+    let power_watts = match read("power_now").filter(|p| *p > 0.0) {
+        Some(microwatts) => Some(microwatts / 1e6),
+        None => match (read("current_now"), read("voltage_now")) {
+            (Some(current), Some(voltage)) if current > 0.0 => Some(current * voltage / 1e12),
+            _ => None,
+        },
+    };
+
+    Some(BatterySnapshot {
+        percent,
+        state,
+        time_remaining: hours.map(|hours| Duration::from_secs_f64(hours * 3_600.0)),
+        power_watts: power_watts.map(|watts| watts as f32),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    fn fake_supply(root: &Path, name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in files {
+            fs::write(dir.join(file), format!("{contents}\n")).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn reads_energy_reporting_battery() {
+        let root = TestDir::new("power");
+        fake_supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+        let bat = fake_supply(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("status", "Discharging"),
+                ("energy_now", "25000000"),
+                ("energy_full", "50000000"),
+                ("power_now", "10000000"),
+            ],
+        );
+
+        let found = find_battery(&root).unwrap();
+        assert_eq!(found, bat);
+        let snap = sample(&found).unwrap();
+
+        assert_eq!(snap.percent, 50.0);
+        assert_eq!(snap.state, ChargeState::Discharging);
+        assert_eq!(snap.time_remaining, Some(Duration::from_secs(9_000)));
+        assert_eq!(snap.power_watts, Some(10.0));
+    }
+
+    #[test]
+    fn reads_charge_reporting_battery_while_charging() {
+        let root = TestDir::new("power-chg");
+        let bat = fake_supply(
+            &root,
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("status", "Charging"),
+                ("charge_now", "3000000"),
+                ("charge_full", "4000000"),
+                ("current_now", "2000000"),
+                ("voltage_now", "12000000"),
+            ],
+        );
+        let snap = sample(&bat).unwrap();
+
+        assert_eq!(snap.percent, 75.0);
+        assert_eq!(snap.state, ChargeState::Charging);
+        assert_eq!(snap.time_remaining, Some(Duration::from_secs(1_800)));
+        assert_eq!(snap.power_watts, Some(24.0));
+    }
+
+    #[test]
+    fn no_battery_without_power_supplies() {
+        assert!(find_battery(Path::new("/nonexistent/power_supply")).is_none());
+    }
+}
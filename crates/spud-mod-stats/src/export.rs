@@ -0,0 +1,307 @@
+/// `stats export`: write the module's telemetry history to a file.
+///
+/// Samples taken in the same refresh share a row. CSV has a
+/// `timestamp_ms` column followed by one column per key; JSON is an array
+/// of objects with the same fields. Keys missing from a row (e.g. the
+/// battery before it was found) are left empty or omitted.
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+use spud_config::alerts::parse_duration;
+use spud_core::event::TelemetryValue;
+use spud_core::telemetry::TelemetryStore;
+
+/// File format written by `stats export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Parsed `stats export` arguments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    pub path: PathBuf,
+    pub format: ExportFormat,
+    /// Keys to include, in order; every key when `None`.
+    pub columns: Option<Vec<String>>,
+    /// Only samples this recent; everything retained when `None`.
+    pub last: Option<Duration>,
+}
+
+impl ExportOptions {
+    /// Parse `<path> [--format csv|json] [--columns a,b] [--last 10m]`.
+    ///
+    /// Without `--format`, a `.json` path writes JSON and anything else CSV.
+    pub fn parse(args: &[&str]) -> Result<Self> {
+        let mut path = None;
+        let mut format = None;
+        let mut columns = None;
+        let mut last = None;
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .copied()
+                    .with_context(|| format!("{arg} needs a value"))
+            };
+            match arg {
+                "--format" => {
+                    format = Some(match value()? {
+                        "csv" => ExportFormat::Csv,
+                        "json" => ExportFormat::Json,
+                        other => bail!("unknown format {other:?} (expected csv or json)"),
+                    })
+                }
+                "--columns" => {
+                    columns = Some(
+                        value()?
+                            .split(',')
+                            .filter(|column| !column.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                    )
+                }
+                "--last" => last = Some(parse_duration(value()?)?),
+                flag if flag.starts_with("--") => bail!("unknown option {flag}"),
+                _ if path.is_some() => bail!("unexpected argument {arg:?}"),
+                _ => path = Some(PathBuf::from(arg)),
+            }
+        }
+        let path = path.context("missing output path")?;
+        let format = format.unwrap_or(if path.extension().is_some_and(|ext| ext == "json") {
+            ExportFormat::Json
+        } else {
+            ExportFormat::Csv
+        });
+        Ok(Self {
+            path,
+            format,
+            columns,
+            last,
+        })
+    }
+}
+
+/// One refresh's samples: wall-clock time and a value per column.
+struct Row<'a> {
+    timestamp_ms: u64,
+    values: Vec<Option<&'a TelemetryValue>>,
+}
+
+/// Write `source`'s history in `store` as `options` asks, creating parent
+/// directories. Returns the number of rows written.
+pub fn write(
+    store: &TelemetryStore,
+    source: &str,
+    options: &ExportOptions,
+    now: Instant,
+) -> Result<usize> {
+    let columns: Vec<String> = match &options.columns {
+        Some(columns) => {
+            let known: Vec<&str> = store.keys_for_source(source).collect();
+            if let Some(unknown) = columns.iter().find(|c| !known.contains(&c.as_str())) {
+                bail!("no {source}.{unknown} samples recorded");
+            }
+            columns.clone()
+        }
+        None => store.keys_for_source(source).map(str::to_string).collect(),
+    };
+    let rows = collect_rows(store, source, &columns, options.last, now);
+    let contents = match options.format {
+        ExportFormat::Csv => to_csv(&columns, &rows),
+        ExportFormat::Json => to_json(&columns, &rows)?,
+    };
+
+    let path = &options.path;
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(rows.len())
+}
+
+/// Group samples into rows by the instant they were recorded, oldest first.
+fn collect_rows<'a>(
+    store: &'a TelemetryStore,
+    source: &str,
+    columns: &[String],
+    last: Option<Duration>,
+    now: Instant,
+) -> Vec<Row<'a>> {
+    let cutoff = last.and_then(|last| now.checked_sub(last));
+    let mut rows: BTreeMap<Instant, Row<'a>> = BTreeMap::new();
+    for (index, column) in columns.iter().enumerate() {
+        let points = store.last_n(source, column, usize::MAX);
+        for point in points
+            .into_iter()
+            .filter(|point| cutoff.is_none_or(|cutoff| point.at >= cutoff))
+        {
+            let row = rows.entry(point.at).or_insert_with(|| Row {
+                timestamp_ms: point.recorded_at_ms,
+                values: vec![None; columns.len()],
+            });
+            row.values[index] = Some(&point.value);
+        }
+    }
+    rows.into_values().collect()
+}
+
+fn to_csv(columns: &[String], rows: &[Row]) -> String {
+    let mut out = String::from("timestamp_ms");
+    for column in columns {
+        out.push(',');
+        out.push_str(&csv_field(column));
+    }
+    out.push('\n');
+    for row in rows {
+        out.push_str(&row.timestamp_ms.to_string());
+        for value in &row.values {
+            out.push(',');
+            match value {
+                Some(TelemetryValue::Float(value)) => out.push_str(&value.to_string()),
+                Some(TelemetryValue::Int(value)) => out.push_str(&value.to_string()),
+                Some(TelemetryValue::Text(text)) => out.push_str(&csv_field(text)),
+                Some(TelemetryValue::Bool(value)) => out.push_str(&value.to_string()),
+                Some(TelemetryValue::DurationMs(ms)) => out.push_str(&ms.to_string()),
+                // One cell per sample: the mean, full buckets are in JSON
+                Some(TelemetryValue::Histogram(histogram)) => {
+                    if let Some(mean) = histogram.mean() {
+                        out.push_str(&mean.to_string());
+                    }
+                }
+                None => {}
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Quote a CSV field if it holds a separator, quote, or newline.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_json(columns: &[String], rows: &[Row]) -> Result<String> {
+    let rows: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            let mut object = Map::new();
+            object.insert("timestamp_ms".into(), row.timestamp_ms.into());
+            for (column, value) in columns.iter().zip(&row.values) {
+                if let Some(value) = value {
+                    object.insert(column.clone(), serde_json::to_value(value)?);
+                }
+            }
+            Ok(Value::Object(object))
+        })
+        .collect::<Result<_>>()?;
+    Ok(serde_json::to_string_pretty(&rows).context("failed to serialize history")? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    fn history(start: Instant) -> TelemetryStore {
+        let mut store = TelemetryStore::default();
+        for (secs, cpu) in [(0, 10.0), (60, 20.0), (120, 30.0)] {
+            let at = start + Duration::from_secs(secs);
+            store.record("stats", "cpu", TelemetryValue::Float(cpu), at);
+            store.record("stats", "mem_used", TelemetryValue::Int(secs as i64), at);
+        }
+        let at = start + Duration::from_secs(120);
+        store.record(
+            "stats",
+            "battery_state",
+            TelemetryValue::Text("a,b".into()),
+            at,
+        );
+        store
+    }
+
+    #[test]
+    fn parses_flags_and_infers_format() {
+        let options =
+            ExportOptions::parse(&["out.json", "--last", "10m", "--columns", "cpu,mem"]).unwrap();
+        assert_eq!(options.format, ExportFormat::Json);
+        assert_eq!(options.last, Some(Duration::from_secs(600)));
+        assert_eq!(options.columns, Some(vec!["cpu".into(), "mem".into()]));
+        let options = ExportOptions::parse(&["--format", "json", "out.txt"]).unwrap();
+        assert_eq!(options.format, ExportFormat::Json);
+        assert_eq!(
+            ExportOptions::parse(&["out"]).unwrap().format,
+            ExportFormat::Csv
+        );
+        assert!(ExportOptions::parse(&[]).is_err());
+        assert!(ExportOptions::parse(&["out", "--format", "xml"]).is_err());
+        assert!(ExportOptions::parse(&["out", "--last"]).is_err());
+        assert!(ExportOptions::parse(&["a", "b"]).is_err());
+    }
+
+    #[test]
+    fn csv_groups_samples_by_refresh() {
+        let start = Instant::now();
+        let store = history(start);
+        let columns = vec![
+            "cpu".to_string(),
+            "battery_state".to_string(),
+            "mem_used".to_string(),
+        ];
+        let now = start + Duration::from_secs(120);
+        let rows = collect_rows(&store, "stats", &columns, None, now);
+        let csv = to_csv(&columns, &rows);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp_ms,cpu,battery_state,mem_used");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].ends_with(",10,,0"));
+        assert!(lines[3].ends_with(",30,\"a,b\",120"));
+
+        let recent = collect_rows(
+            &store,
+            "stats",
+            &columns,
+            Some(Duration::from_secs(90)),
+            now,
+        );
+        assert_eq!(recent.len(), 2);
+    }
+
+    #[test]
+    fn writes_json_file_with_selected_columns() {
+        let start = Instant::now();
+        let store = history(start);
+        let dir = TestDir::new("export");
+        let path = dir.join("nested").join("stats.json");
+        let options = ExportOptions {
+            path: path.clone(),
+            format: ExportFormat::Json,
+            columns: Some(vec!["cpu".into()]),
+            last: None,
+        };
+        let written = write(&store, "stats", &options, start).unwrap();
+        let json: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(written, 3);
+        assert_eq!(json[2]["cpu"], 30.0);
+        assert!(json[2].get("mem_used").is_none());
+
+        let options = ExportOptions {
+            columns: Some(vec!["gpu".into()]),
+            ..options
+        };
+        let err = write(&store, "stats", &options, start).unwrap_err();
+        assert!(err.to_string().contains("stats.gpu"));
+    }
+}
@@ -38,7 +38,6 @@ pub fn format_percent(value: f32) -> String {
 /// Format a duration in seconds as a human-readable uptime string.
 ///
 /// Returns values like `"2h 15m 30s"`, `"3d 1h 45m"`, `"0s"`.
-#[allow(dead_code)]
 pub fn format_uptime(secs: u64) -> String {
     let days = secs / 86400;
     let hours = (secs % 86400) / 3600;
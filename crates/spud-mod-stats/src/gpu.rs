@@ -0,0 +1,133 @@
+/// GPU sampling for the `gpu` feature.
+///
+/// Tries NVML first (loaded at runtime, so builds and machines without the
+/// NVIDIA driver are fine), then the amdgpu driver's sysfs files on Linux.
+/// Only the first GPU is sampled. With neither available the probe stays
+/// empty and the stats module shows no GPU row.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
+use nvml_wrapper::Nvml;
+
+use crate::telemetry::GpuSnapshot;
+
+/// Where amdgpu exposes its cards.
+const DRM_ROOT: &str = "/sys/class/drm";
+
+/// A source of GPU metrics found at startup.
+pub enum GpuProbe {
+    /// Boxed: the loaded library's function table is large.
+    Nvml(Box<Nvml>),
+    /// `device` directory of an amdgpu card, e.g. `/sys/class/drm/card0/device`.
+    Amdgpu(PathBuf),
+}
+
+impl GpuProbe {
+    /// Find a GPU to sample, or `None` if no supported driver is present.
+    pub fn detect() -> Option<Self> {
+        if let Ok(nvml) = Nvml::init() {
+            if nvml.device_count().is_ok_and(|count| count > 0) {
+                return Some(Self::Nvml(Box::new(nvml)));
+            }
+        }
+        find_amdgpu(Path::new(DRM_ROOT)).map(Self::Amdgpu)
+    }
+
+    /// Read current metrics; `None` if the GPU stopped answering.
+    pub fn sample(&self) -> Option<GpuSnapshot> {
+        match self {
+            Self::Nvml(nvml) => {
+                let device = nvml.device_by_index(0).ok()?;
+                let memory = device.memory_info().ok()?;
+                Some(GpuSnapshot {
+                    name: device.name().unwrap_or_else(|_| "NVIDIA".into()),
+                    utilization: device.utilization_rates().ok()?.gpu as f32,
+                    vram_used: memory.used,
+                    vram_total: memory.total,
+                    temperature_c: device
+                        .temperature(TemperatureSensor::Gpu)
+                        .ok()
+                        .map(|celsius| celsius as f32),
+                })
+            }
+            Self::Amdgpu(device) => sample_amdgpu(device),
+        }
+    }
+}
+
+/// First `card*/device` under `drm_root` that reports `gpu_busy_percent`.
+fn find_amdgpu(drm_root: &Path) -> Option<PathBuf> {
+    let mut devices: Vec<PathBuf> = fs::read_dir(drm_root)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            // `card0`, not connectors like `card0-DP-1`
+            name.strip_prefix("card")
+                .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .map(|entry| entry.path().join("device"))
+        .filter(|device| device.join("gpu_busy_percent").is_file())
+        .collect();
+    devices.sort();
+    devices.into_iter().next()
+}
+
+/// Read an amdgpu card's sysfs files. Temperature is optional; the rest
+/// are required.
+fn sample_amdgpu(device: &Path) -> Option<GpuSnapshot> {
+    let read = |name: &str| -> Option<u64> {
+        fs::read_to_string(device.join(name))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let temperature_c = fs::read_dir(device.join("hwmon"))
+        .ok()
+        .and_then(|mut dirs| dirs.next())
+        .and_then(|entry| fs::read_to_string(entry.ok()?.path().join("temp1_input")).ok())
+        .and_then(|raw| raw.trim().parse::<f32>().ok())
+        .map(|millidegrees| millidegrees / 1000.0);
+    Some(GpuSnapshot {
+        name: "AMD".into(),
+        utilization: read("gpu_busy_percent")? as f32,
+        vram_used: read("mem_info_vram_used")?,
+        vram_total: read("mem_info_vram_total")?,
+        temperature_c,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn reads_fake_amdgpu_sysfs() {
+        let root = TestDir::new("drm");
+        let device = root.join("card1").join("device");
+        fs::create_dir_all(device.join("hwmon").join("hwmon3")).unwrap();
+        fs::create_dir_all(root.join("card1-DP-1")).unwrap();
+        fs::write(device.join("gpu_busy_percent"), "34\n").unwrap();
+        fs::write(device.join("mem_info_vram_used"), "1073741824\n").unwrap();
+        fs::write(device.join("mem_info_vram_total"), "8589934592\n").unwrap();
+        fs::write(device.join("hwmon/hwmon3/temp1_input"), "61000\n").unwrap();
+
+        let found = find_amdgpu(&root).unwrap();
+        assert_eq!(found, device);
+        let snap = sample_amdgpu(&found).unwrap();
+
+        assert_eq!(snap.utilization, 34.0);
+        assert_eq!(snap.vram_used, 1 << 30);
+        assert_eq!(snap.vram_total, 8 << 30);
+        assert_eq!(snap.temperature_c, Some(61.0));
+    }
+
+    #[test]
+    fn missing_drm_root_finds_nothing() {
+        assert!(find_amdgpu(Path::new("/nonexistent/drm")).is_none());
+    }
+}
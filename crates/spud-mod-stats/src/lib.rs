@@ -1,28 +1,65 @@
+mod battery;
+mod export;
 mod format;
+#[cfg(feature = "gpu")]
+mod gpu;
 mod render;
 mod telemetry;
 
 use std::any::Any;
 
+use std::time::Instant;
+
 use ratatui::{layout::Rect, Frame};
+use spud_agent::{Agent, Mood};
 
+use spud_config::{AppConfig, GaugeStyle, StatsConfig, StatsSection, Thresholds};
 use spud_core::{
-    event::Event,
-    module::{HudContribution, Module},
+    command::CommandOutput,
+    event::{Event, TelemetryValue},
+    i18n::tr,
+    keymap::{ActionId, KeyBinding, KeyChord},
+    module::{HudContribution, HudLine, HudStyle, Module},
+    telemetry::{TelemetryStore, DEFAULT_HISTORY_LEN},
 };
 use spud_ui::renderer::HeroRenderer;
+use sysinfo::System;
 
-pub use telemetry::MetricsSnapshot;
+pub use battery::{BatterySnapshot, ChargeState};
+pub use telemetry::{GpuSnapshot, MetricsSnapshot};
 
-use crate::format::{format_bytes, format_percent};
+use crate::export::ExportOptions;
+use crate::format::{format_bytes, format_percent, format_uptime};
+use crate::render::{core_key, CoresView};
 use crate::telemetry::TelemetryCollector;
 
 /// System-stats module providing live CPU, memory, swap, and process telemetry.
 ///
-/// Refreshes metrics at a 1-second interval via [`TelemetryCollector`] and renders
-/// them as gauges in the hero pane and live numbers in the HUD panels.
+/// Refreshes metrics at the profile's telemetry interval (1 second by
+/// default) via [`TelemetryCollector`] and renders them as gauges in the
+/// hero pane and live numbers in the HUD panels.
+///
+/// Thresholds, visible sections, and gauge style come from
+/// `[modules.stats]` in `spud.toml` and can be changed at runtime with the
+/// `stats` console command. `h` switches the per-core section between the
+/// current-value grid and a heatmap of recent samples.
 pub struct StatsModule {
     collector: TelemetryCollector,
+    /// Settings in effect, including runtime changes.
+    config: StatsConfig,
+    /// `[modules.stats]` as last read from the file; runtime changes stick
+    /// until the section itself changes.
+    file_config: StatsConfig,
+    /// Face shown while the module is active; its mood follows CPU load.
+    agent: Option<Agent>,
+    /// Telemetry events queued since the last drain.
+    outbox: Vec<Event>,
+    /// Recent samples: everything published as telemetry, plus per-core
+    /// utilization as `stats.core<n>` for the heatmap. Source of
+    /// `stats export`.
+    history: TelemetryStore,
+    /// Draw the per-core section as a heatmap instead of a grid.
+    heatmap: bool,
 }
 
 impl Default for StatsModule {
@@ -36,6 +73,250 @@ impl StatsModule {
     pub fn new() -> Self {
         Self {
             collector: TelemetryCollector::new(),
+            config: StatsConfig::default(),
+            file_config: StatsConfig::default(),
+            agent: Agent::load_default(Instant::now()).ok(),
+            outbox: Vec::new(),
+            history: TelemetryStore::new(DEFAULT_HISTORY_LEN),
+            heatmap: false,
+        }
+    }
+
+    /// Append the latest per-core sample to the heatmap history.
+    fn record_core_history(&mut self, now: Instant) {
+        let snap = self.collector.snapshot();
+        for (core, &pct) in snap.cpu_per_core.iter().enumerate() {
+            self.history.record(
+                self.id(),
+                &core_key(core),
+                TelemetryValue::Float(f64::from(pct)),
+                now,
+            );
+        }
+    }
+
+    /// Queue the current snapshot as `stats` telemetry events and keep it
+    /// in the history.
+    fn queue_telemetry(&mut self, now: Instant) {
+        let snap = self.collector.snapshot();
+        let mut samples = vec![("cpu", TelemetryValue::Float(f64::from(snap.cpu_global)))];
+        if snap.mem_total > 0 {
+            let pct = snap.mem_used as f64 / snap.mem_total as f64 * 100.0;
+            samples.push(("mem", TelemetryValue::Float(pct)));
+        }
+        samples.push(("mem_used", TelemetryValue::Int(snap.mem_used as i64)));
+        if let Some(rss) = snap.self_rss {
+            samples.push(("rss", TelemetryValue::Int(rss as i64)));
+        }
+        if let Some([one, five, fifteen]) = snap.load_average {
+            samples.push(("load1", TelemetryValue::Float(one)));
+            samples.push(("load5", TelemetryValue::Float(five)));
+            samples.push(("load15", TelemetryValue::Float(fifteen)));
+        }
+        samples.push(("uptime", TelemetryValue::Int(snap.uptime.as_secs() as i64)));
+        if let Some(bat) = &snap.battery {
+            samples.push(("battery", TelemetryValue::Float(f64::from(bat.percent))));
+            samples.push((
+                "battery_state",
+                TelemetryValue::Text(bat.state.name().to_string()),
+            ));
+            if let Some(remaining) = bat.time_remaining {
+                samples.push((
+                    "battery_minutes",
+                    TelemetryValue::Float(remaining.as_secs_f64() / 60.0),
+                ));
+            }
+            if let Some(watts) = bat.power_watts {
+                samples.push(("battery_watts", TelemetryValue::Float(f64::from(watts))));
+            }
+        }
+        if let Some(gpu) = &snap.gpu {
+            samples.push(("gpu", TelemetryValue::Float(f64::from(gpu.utilization))));
+            samples.push(("gpu_vram_used", TelemetryValue::Int(gpu.vram_used as i64)));
+            if let Some(celsius) = gpu.temperature_c {
+                samples.push(("gpu_temp", TelemetryValue::Float(f64::from(celsius))));
+            }
+        }
+        let source = self.id();
+        for (key, value) in samples {
+            self.history.record(source, key, value.clone(), now);
+            self.outbox.push(Event::Telemetry {
+                source: source.to_string(),
+                key: key.to_string(),
+                value,
+            });
+        }
+    }
+
+    /// Settings in effect.
+    pub fn config(&self) -> &StatsConfig {
+        &self.config
+    }
+
+    /// Host, OS, and the latest metrics as labelled rows, for the `sys`
+    /// console command.
+    pub fn system_summary(&self) -> Vec<(String, String)> {
+        let snap = self.collector.snapshot();
+        let unknown = || "--".to_string();
+        let usage = |used: u64, total: u64| {
+            if total == 0 {
+                return unknown();
+            }
+            let pct = used as f32 / total as f32 * 100.0;
+            format!(
+                "{} / {} ({})",
+                format_bytes(used),
+                format_bytes(total),
+                format_percent(pct)
+            )
+        };
+        let mut rows = vec![
+            ("host".into(), System::host_name().unwrap_or_else(unknown)),
+            (
+                "os".into(),
+                System::long_os_version().unwrap_or_else(unknown),
+            ),
+            (
+                "kernel".into(),
+                System::kernel_version().unwrap_or_else(unknown),
+            ),
+            ("arch".into(), System::cpu_arch()),
+            ("uptime".into(), format_uptime(System::uptime())),
+            (
+                "load".into(),
+                match snap.load_average {
+                    Some([one, five, fifteen]) => format!("{one:.2} {five:.2} {fifteen:.2}"),
+                    None => unknown(),
+                },
+            ),
+            (
+                "cpu".into(),
+                format!(
+                    "{} across {} cores",
+                    format_percent(snap.cpu_global),
+                    snap.cpu_per_core.len()
+                ),
+            ),
+            ("mem".into(), usage(snap.mem_used, snap.mem_total)),
+            ("swap".into(), usage(snap.swap_used, snap.swap_total)),
+        ];
+        if let Some(bat) = &snap.battery {
+            rows.push((
+                "battery".into(),
+                format!("{} {}", format_percent(bat.percent), bat.state.name()),
+            ));
+        }
+        if let Some(gpu) = &snap.gpu {
+            rows.push((
+                "gpu".into(),
+                format!("{} {}", gpu.name, format_percent(gpu.utilization)),
+            ));
+        }
+        rows
+    }
+
+    /// Run `stats <args>`.
+    fn execute(&mut self, args: &[&str]) -> CommandOutput {
+        match args {
+            [] => {}
+            ["thresholds", metric, warn, crit] => {
+                let (Ok(warn), Ok(crit)) = (warn.parse(), crit.parse()) else {
+                    return CommandOutput::Error(vec![
+                        "thresholds must be whole percentages".into()
+                    ]);
+                };
+                let thresholds = Thresholds::new(warn, crit);
+                if let Err(err) = thresholds.validate() {
+                    return CommandOutput::Error(vec![err.to_string()]);
+                }
+                let slot = match *metric {
+                    "cpu" => &mut self.config.thresholds.cpu,
+                    "mem" => &mut self.config.thresholds.mem,
+                    "swap" => &mut self.config.thresholds.swap,
+                    "gpu" => &mut self.config.thresholds.gpu,
+                    other => {
+                        return CommandOutput::Error(vec![format!(
+                            "unknown metric {other:?} (expected cpu, mem, swap, or gpu)"
+                        )])
+                    }
+                };
+                *slot = thresholds;
+            }
+            [action @ ("hide" | "show"), section] => {
+                let section = match section.parse::<StatsSection>() {
+                    Ok(section) => section,
+                    Err(err) => return CommandOutput::Error(vec![err.to_string()]),
+                };
+                self.config.hide.retain(|hidden| *hidden != section);
+                if *action == "hide" {
+                    self.config.hide.push(section);
+                }
+            }
+            ["export", rest @ ..] => return self.export(rest, Instant::now()),
+            ["gauge", style] => match style.parse::<GaugeStyle>() {
+                Ok(style) => self.config.gauge = style,
+                Err(err) => return CommandOutput::Error(vec![err.to_string()]),
+            },
+            _ => return CommandOutput::Error(vec![format!("usage: {COMMAND_USAGE}")]),
+        }
+        self.describe_config()
+    }
+
+    /// Run `stats export <path> ...`.
+    fn export(&self, args: &[&str], now: Instant) -> CommandOutput {
+        let result = ExportOptions::parse(args).and_then(|options| {
+            let rows = export::write(&self.history, self.id(), &options, now)?;
+            Ok(format!(
+                "{rows} samples written to {}",
+                options.path.display()
+            ))
+        });
+        match result {
+            Ok(line) => CommandOutput::Lines(vec![line]),
+            Err(err) => {
+                CommandOutput::Error(vec![format!("{err:#}"), format!("usage: {EXPORT_USAGE}")])
+            }
+        }
+    }
+
+    /// The settings in effect, as `stats` prints them.
+    fn describe_config(&self) -> CommandOutput {
+        let config = &self.config;
+        let pair = |t: Thresholds| format!("warn {}%, crit {}%", t.warn, t.crit);
+        let hidden: Vec<&str> = StatsSection::ALL
+            .into_iter()
+            .filter(|section| !config.shows(*section))
+            .map(StatsSection::name)
+            .collect();
+        CommandOutput::KeyValue(vec![
+            ("cpu".into(), pair(config.thresholds.cpu)),
+            ("mem".into(), pair(config.thresholds.mem)),
+            ("swap".into(), pair(config.thresholds.swap)),
+            ("gpu".into(), pair(config.thresholds.gpu)),
+            (
+                "hidden".into(),
+                if hidden.is_empty() {
+                    "none".into()
+                } else {
+                    hidden.join(", ")
+                },
+            ),
+            ("gauge".into(), config.gauge.name().into()),
+        ])
+    }
+
+    /// Keep the module's agent mood in step with the CPU band and battery.
+    fn update_agent(&mut self, now: Instant) {
+        let snap = self.collector.snapshot();
+        let mood = agent_mood(
+            cpu_mood(snap.cpu_global, self.config.thresholds.cpu),
+            snap.battery.as_ref(),
+        );
+        if let Some(agent) = self.agent.as_mut() {
+            if agent.mood() != mood {
+                agent.set_mood(mood, now);
+            }
+            agent.tick(now);
         }
     }
 }
@@ -46,54 +327,216 @@ impl Module for StatsModule {
     }
 
     fn title(&self) -> &'static str {
-        "System Stats"
+        tr("System Stats")
     }
 
     fn handle_event(&mut self, ev: &Event) {
-        if let Event::Tick { now } = ev {
-            self.collector.maybe_refresh(*now);
+        match ev {
+            Event::Tick { now } => {
+                if self.collector.maybe_refresh(*now) {
+                    self.queue_telemetry(*now);
+                    self.record_core_history(*now);
+                }
+                self.update_agent(*now);
+            }
+            Event::ProfileChanged { profile } => {
+                let tuning = profile.tuning();
+                self.collector.set_interval(tuning.telemetry_interval);
+                if let Some(agent) = self.agent.as_mut() {
+                    agent.set_frame_interval(tuning.face_frame_interval);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn configure(&mut self, config: &AppConfig) {
+        let section = &config.modules.stats;
+        if *section != self.file_config {
+            self.file_config = section.clone();
+            self.config = section.clone();
+        }
+    }
+
+    fn console_command(&mut self, args: &[&str]) -> Option<CommandOutput> {
+        Some(self.execute(args))
+    }
+
+    fn console_usage(&self) -> Vec<(&'static str, &'static str)> {
+        vec![
+            (
+                COMMAND_USAGE,
+                "Show or change stats thresholds, sections, and gauges",
+            ),
+            (EXPORT_USAGE, "Write recent stats history to CSV or JSON"),
+        ]
+    }
+
+    fn drain_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    fn keybindings(&self) -> Vec<KeyBinding> {
+        vec![KeyBinding::new(
+            KeyChord::char('h'),
+            HEATMAP_ACTION,
+            tr("toggle per-core heatmap"),
+        )]
+    }
+
+    fn handle_action(&mut self, action: ActionId) {
+        if action == HEATMAP_ACTION {
+            self.heatmap = !self.heatmap;
         }
     }
 
+    fn agent(&self) -> Option<&Agent> {
+        self.agent.as_ref()
+    }
+
     fn hud(&self) -> HudContribution {
         let snap = self.collector.snapshot();
+        let thresholds = &self.config.thresholds;
 
-        let cpu_text = format!("CPU: {}", format_percent(snap.cpu_global));
-        let mem_text = if snap.mem_total > 0 {
+        let cpu = HudLine::gauge(
+            "CPU:",
+            snap.cpu_global / 100.0,
+            format_percent(snap.cpu_global),
+            threshold_style(snap.cpu_global, thresholds.cpu),
+        );
+        let mem = if snap.mem_total > 0 {
             let pct = snap.mem_used as f32 / snap.mem_total as f32 * 100.0;
-            format!(
-                "MEM: {} ({})",
+            HudLine::gauge(
+                "MEM:",
+                pct / 100.0,
                 format_percent(pct),
-                format_bytes(snap.mem_used)
+                threshold_style(pct, thresholds.mem),
             )
         } else {
-            "MEM: --".into()
+            HudLine::key_value("MEM:", "--", HudStyle::Dim)
         };
-        let rss_text = match snap.self_rss {
-            Some(rss) => format!("RSS: {}", format_bytes(rss)),
-            None => "RSS: --".into(),
+        let used = HudLine::key_value("USED:", format_bytes(snap.mem_used), HudStyle::Normal);
+        let rss = match snap.self_rss {
+            Some(rss) => HudLine::key_value("RSS:", format_bytes(rss), HudStyle::Normal),
+            None => HudLine::key_value("RSS:", "--", HudStyle::Dim),
         };
 
+        let mut right_lines = vec![cpu, mem, used, rss];
+        if let Some(bat) = &snap.battery {
+            let charging = if bat.state == ChargeState::Charging {
+                "+"
+            } else {
+                ""
+            };
+            right_lines.push(HudLine::gauge(
+                "BAT:",
+                bat.percent / 100.0,
+                format!("{charging}{}", format_percent(bat.percent)),
+                battery_style(bat),
+            ));
+        }
+        if let Some(gpu) = &snap.gpu {
+            right_lines.push(HudLine::gauge(
+                "GPU:",
+                gpu.utilization / 100.0,
+                format_percent(gpu.utilization),
+                threshold_style(gpu.utilization, thresholds.gpu),
+            ));
+        }
+
         HudContribution {
-            left_lines: vec!["Tab: next module".into(), "`: console".into()],
-            right_lines: vec![cpu_text, mem_text, rss_text],
+            left_lines: Vec::new(),
+            right_lines,
         }
     }
 
+    fn as_hero_renderer(&self) -> Option<&dyn HeroRenderer> {
+        Some(self)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
 }
 
+/// Action of the `h` key: switch the per-core section to the heatmap.
+const HEATMAP_ACTION: ActionId = "heatmap";
+
+/// Usage of the `stats` console command.
+const COMMAND_USAGE: &str =
+    "stats [thresholds <cpu|mem|swap|gpu> <warn> <crit> | hide|show <section> | gauge <style>]";
+
+/// Usage of `stats export`.
+const EXPORT_USAGE: &str =
+    "stats export <path> [--format csv|json] [--columns <key,...>] [--last <duration>]";
+
+/// Color-code a utilization percentage for the HUD.
+fn threshold_style(percent: f32, thresholds: Thresholds) -> HudStyle {
+    if percent.is_nan() {
+        HudStyle::Dim
+    } else if percent >= f32::from(thresholds.crit) {
+        HudStyle::Critical
+    } else if percent >= f32::from(thresholds.warn) {
+        HudStyle::Warn
+    } else {
+        HudStyle::Good
+    }
+}
+
+/// Battery percentage at or below which the HUD gauge warns.
+const BATTERY_WARN_PERCENT: f32 = 20.0;
+/// Battery percentage below which the HUD gauge is critical and the agent
+/// is hurting.
+const BATTERY_CRITICAL_PERCENT: f32 = 10.0;
+
+/// Color-code the battery for the HUD: low charge is only a problem while
+/// running on it.
+fn battery_style(bat: &BatterySnapshot) -> HudStyle {
+    if bat.state != ChargeState::Discharging {
+        HudStyle::Good
+    } else if bat.percent < BATTERY_CRITICAL_PERCENT {
+        HudStyle::Critical
+    } else if bat.percent <= BATTERY_WARN_PERCENT {
+        HudStyle::Warn
+    } else {
+        HudStyle::Good
+    }
+}
+
+/// Combine the CPU mood with the battery: a nearly empty battery hurts more
+/// than any CPU load.
+fn agent_mood(cpu: Mood, battery: Option<&BatterySnapshot>) -> Mood {
+    match battery.map(battery_style) {
+        Some(HudStyle::Critical) => Mood::HurtRealBad,
+        Some(HudStyle::Warn) if cpu == Mood::Neutral => Mood::Angry,
+        _ => cpu,
+    }
+}
+
+/// Agent mood for a CPU utilization percentage, using the CPU thresholds.
+fn cpu_mood(percent: f32, thresholds: Thresholds) -> Mood {
+    match threshold_style(percent, thresholds) {
+        HudStyle::Critical => Mood::HurtRealBad,
+        HudStyle::Warn => Mood::Angry,
+        _ => Mood::Neutral,
+    }
+}
+
 impl HeroRenderer for StatsModule {
     fn render_hero(&self, f: &mut Frame, area: Rect) {
-        render::render_hero_content(f, area, self.collector.snapshot());
+        let cores = if self.heatmap {
+            CoresView::Heatmap(&self.history)
+        } else {
+            CoresView::Grid
+        };
+        render::render_hero_content(f, area, self.collector.snapshot(), &self.config, cores);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use spud_testkit::TestDir;
     use std::time::Instant;
 
     #[test]
@@ -103,6 +546,13 @@ mod tests {
         assert_eq!(m.title(), "System Stats");
     }
 
+    #[test]
+    fn exposes_hero_renderer() {
+        let m: Box<dyn Module> = Box::new(StatsModule::new());
+        assert!(m.as_hero_renderer().is_some());
+        assert!(m.downcast_ref::<StatsModule>().is_some());
+    }
+
     #[test]
     fn tick_triggers_refresh() {
         let mut m = StatsModule::new();
@@ -119,8 +569,173 @@ mod tests {
             now: Instant::now(),
         });
         let hud = m.hud();
-        assert!(hud.right_lines.iter().any(|l| l.contains("CPU:")));
-        assert!(hud.right_lines.iter().any(|l| l.contains("MEM:")));
-        assert!(hud.right_lines.iter().any(|l| l.contains("RSS:")));
+        let text: Vec<String> = hud.right_lines.iter().map(HudLine::plain_text).collect();
+        assert!(text.iter().any(|l| l.contains("CPU:")));
+        assert!(text.iter().any(|l| l.contains("MEM:")));
+        assert!(text.iter().any(|l| l.contains("RSS:")));
+    }
+
+    #[test]
+    fn profile_change_retunes_sampling() {
+        let mut m = StatsModule::new();
+        m.handle_event(&Event::ProfileChanged {
+            profile: spud_config::Profile::Battery,
+        });
+        let now = Instant::now();
+        m.handle_event(&Event::Tick { now });
+        assert!(!m.drain_events().is_empty());
+
+        // Balanced would sample again after a second; battery waits 3s.
+        m.handle_event(&Event::Tick {
+            now: now + std::time::Duration::from_millis(1_500),
+        });
+        assert!(m.drain_events().is_empty());
+    }
+
+    #[test]
+    fn threshold_style_bands() {
+        let cpu = StatsConfig::default().thresholds.cpu;
+        assert_eq!(threshold_style(10.0, cpu), HudStyle::Good);
+        assert_eq!(threshold_style(80.0, cpu), HudStyle::Warn);
+        assert_eq!(threshold_style(95.0, cpu), HudStyle::Critical);
+        assert_eq!(threshold_style(f32::NAN, cpu), HudStyle::Dim);
+    }
+
+    #[test]
+    fn stats_command_changes_settings_until_file_section_changes() {
+        let mut m = StatsModule::new();
+        let output = m
+            .console_command(&["thresholds", "cpu", "80", "95"])
+            .unwrap();
+        assert!(!output.is_error());
+        assert_eq!(m.config().thresholds.cpu, Thresholds::new(80, 95));
+        assert!(!m.console_command(&["hide", "cores"]).unwrap().is_error());
+        assert!(!m.config().shows(StatsSection::Cores));
+        m.console_command(&["gauge", "thick"]);
+        assert_eq!(m.config().gauge, GaugeStyle::Thick);
+
+        assert!(m
+            .console_command(&["thresholds", "cpu", "95", "80"])
+            .unwrap()
+            .is_error());
+        assert!(m
+            .console_command(&["thresholds", "disk", "1", "2"])
+            .unwrap()
+            .is_error());
+        assert!(m.console_command(&["hide", "graph"]).unwrap().is_error());
+        assert!(m.console_command(&["frobnicate"]).unwrap().is_error());
+
+        // Reloading an unchanged file keeps runtime tweaks.
+        m.configure(&AppConfig::default());
+        assert_eq!(m.config().thresholds.cpu, Thresholds::new(80, 95));
+
+        let mut config = AppConfig::default();
+        config.modules.stats.thresholds.mem = Thresholds::new(10, 20);
+        m.configure(&config);
+        assert_eq!(m.config(), &config.modules.stats);
+    }
+
+    #[test]
+    fn refresh_queues_telemetry_events() {
+        let mut m = StatsModule::new();
+        m.handle_event(&Event::Tick {
+            now: Instant::now(),
+        });
+        let events = m.drain_events();
+        assert!(events.iter().any(|ev| matches!(
+            ev,
+            Event::Telemetry { source, key, .. } if source == "stats" && key == "cpu"
+        )));
+        assert!(events.iter().any(|ev| matches!(
+            ev,
+            Event::Telemetry { key, value: TelemetryValue::Int(secs), .. }
+                if key == "uptime" && *secs > 0
+        )));
+        assert!(m.drain_events().is_empty());
+    }
+
+    #[test]
+    fn h_toggles_heatmap_fed_from_refreshes() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        use spud_core::keymap;
+
+        let mut m = StatsModule::new();
+        m.handle_event(&Event::Tick {
+            now: Instant::now(),
+        });
+        let cores = m.collector.snapshot().cpu_per_core.len();
+        assert!(cores > 0);
+        assert_eq!(m.history.last_n("stats", &core_key(0), 10).len(), 1);
+        assert!(m.history.latest("stats", &core_key(cores - 1)).is_some());
+
+        let h = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        let action = keymap::action_for(&m.keybindings(), &h).unwrap();
+        m.handle_action(action);
+        assert!(m.heatmap);
+        m.handle_action(action);
+        assert!(!m.heatmap);
+    }
+
+    #[test]
+    fn export_writes_published_history() {
+        let mut m = StatsModule::new();
+        let start = Instant::now();
+        m.handle_event(&Event::Tick { now: start });
+        m.handle_event(&Event::Tick {
+            now: start + std::time::Duration::from_secs(2),
+        });
+        let tmp = TestDir::new("stats");
+        let path = tmp.join("stats.csv");
+        let path_arg = path.to_string_lossy().into_owned();
+        let output = m.execute(&["export", &path_arg, "--columns", "cpu,mem_used"]);
+        let csv = std::fs::read_to_string(&path).unwrap();
+
+        assert!(matches!(output, CommandOutput::Lines(_)));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp_ms,cpu,mem_used");
+        assert_eq!(lines.len(), 3);
+        assert!(matches!(m.execute(&["export"]), CommandOutput::Error(_)));
+    }
+
+    #[test]
+    fn system_summary_lists_host_and_memory() {
+        let mut m = StatsModule::new();
+        m.handle_event(&Event::Tick {
+            now: Instant::now(),
+        });
+        let rows = m.system_summary();
+        let value = |key: &str| rows.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert!(value("host").is_some());
+        assert!(value("mem").is_some_and(|mem| mem.contains('%')));
+        assert!(!value("uptime").unwrap().is_empty());
+    }
+
+    #[test]
+    fn agent_mood_follows_cpu_band() {
+        let cpu = StatsConfig::default().thresholds.cpu;
+        assert_eq!(cpu_mood(20.0, cpu), Mood::Neutral);
+        assert_eq!(cpu_mood(80.0, cpu), Mood::Angry);
+        assert_eq!(cpu_mood(99.0, cpu), Mood::HurtRealBad);
+
+        let mut bat = BatterySnapshot {
+            percent: 8.0,
+            state: ChargeState::Discharging,
+            time_remaining: None,
+            power_watts: None,
+        };
+        assert_eq!(agent_mood(Mood::Neutral, Some(&bat)), Mood::HurtRealBad);
+        bat.percent = 15.0;
+        assert_eq!(agent_mood(Mood::Neutral, Some(&bat)), Mood::Angry);
+        assert_eq!(agent_mood(Mood::HurtRealBad, Some(&bat)), Mood::HurtRealBad);
+        bat.state = ChargeState::Charging;
+        bat.percent = 5.0;
+        assert_eq!(agent_mood(Mood::Neutral, Some(&bat)), Mood::Neutral);
+        assert_eq!(agent_mood(Mood::Angry, None), Mood::Angry);
+
+        let mut m = StatsModule::new();
+        m.handle_event(&Event::Tick {
+            now: Instant::now(),
+        });
+        assert!(m.agent().is_some());
     }
 }
@@ -1,41 +1,73 @@
 /// Hero-pane rendering for the stats module.
 ///
 /// Draws CPU, memory, and swap gauges plus per-core breakdown and SPUD
-/// process info. Adapts layout based on available terminal height.
+/// process info. Adapts layout based on available terminal height. The
+/// per-core section is either an instantaneous grid or a heatmap of recent
+/// samples, which keeps short spikes visible.
 use ratatui::{
     layout::{Constraint, Layout, Rect},
     style::{Color, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, LineGauge, Paragraph},
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
-use crate::format::{format_bytes, format_percent};
-use crate::telemetry::MetricsSnapshot;
+use spud_config::{GaugeStyle, StatsConfig, StatsSection, Thresholds};
+use spud_core::i18n::tr;
+use spud_core::module::HudStyle;
+use spud_core::telemetry::TelemetryStore;
+use spud_ui::chart::{ChartTheme, Gauge, Sparkline};
 
-/// Clamp a percentage (0–100) to a ratio (0.0–1.0) safe for [`LineGauge::ratio`].
-fn clamp_ratio(pct: f32) -> f64 {
-    (pct as f64 / 100.0).clamp(0.0, 1.0)
+use crate::battery::BatterySnapshot;
+use crate::format::{format_bytes, format_percent, format_uptime};
+use crate::telemetry::{GpuSnapshot, MetricsSnapshot};
+
+/// Chart colours for a metric: `normal` until its thresholds.
+fn theme(thresholds: Thresholds, normal: Color) -> ChartTheme {
+    ChartTheme::new(normal).thresholds(thresholds)
 }
 
-/// Choose a color based on the metric value and thresholds.
-fn threshold_color(pct: f32, warn: f32, crit: f32, normal: Color) -> Color {
-    if pct >= crit {
-        Color::Red
-    } else if pct >= warn {
-        Color::Yellow
-    } else {
-        normal
-    }
+/// A hero-pane gauge drawn with the configured line style.
+fn gauge(pct: f32, label: String, theme: ChartTheme, style: GaugeStyle) -> Gauge<'static> {
+    Gauge::new(f64::from(pct), label, theme).style(style)
+}
+
+/// How the per-core section is drawn.
+#[derive(Clone, Copy)]
+pub enum CoresView<'a> {
+    /// Current utilization of each core, several cores per row.
+    Grid,
+    /// One row per core, one column per recent sample, read from the
+    /// `stats.core<n>` series of the store.
+    Heatmap(&'a TelemetryStore),
 }
 
+/// Telemetry key under which core `index`'s history is recorded.
+pub fn core_key(index: usize) -> String {
+    format!("core{index}")
+}
+
+/// Shades for the heatmap cells, from idle to saturated.
+const HEAT_SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
 /// Render the full stats hero pane into the given area.
 ///
 /// Layout adapts based on available height:
-/// - **6+ rows**: CPU gauge, MEM gauge, SWP gauge, per-core grid, SPUD process
+/// - **6+ rows**: CPU gauge, MEM gauge, SWP gauge, GPU and battery gauges
+///   (if present), per-core grid, SPUD process
 /// - **< 6 rows**: CPU, MEM, SWP gauges only (compact mode)
-pub fn render_hero_content(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
-    let block = Block::default().borders(Borders::ALL).title("SYSTEM STATS");
+///
+/// Sections hidden in `config` are left out and the rest move up.
+pub fn render_hero_content(
+    f: &mut Frame,
+    area: Rect,
+    snap: &MetricsSnapshot,
+    config: &StatsConfig,
+    cores: CoresView,
+) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(header_line(snap));
     let inner = block.inner(area);
     f.render_widget(block, area);
 
@@ -46,14 +78,27 @@ pub fn render_hero_content(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
     let compact = inner.height < 6;
 
     if compact {
-        render_compact(f, inner, snap);
+        render_compact(f, inner, snap, config);
     } else {
-        render_full(f, inner, snap);
+        render_full(f, inner, snap, config, cores);
+    }
+}
+
+/// Hero block title: `SYSTEM STATS`, then uptime and load averages once
+/// they are known.
+fn header_line(snap: &MetricsSnapshot) -> String {
+    let mut header = tr("SYSTEM STATS").to_string();
+    if !snap.uptime.is_zero() {
+        header.push_str(&format!(" · up {}", format_uptime(snap.uptime.as_secs())));
     }
+    if let Some([one, five, fifteen]) = snap.load_average {
+        header.push_str(&format!(" · load {one:.2} {five:.2} {fifteen:.2}"));
+    }
+    header
 }
 
-/// Compact layout: just the three gauges stacked vertically.
-fn render_compact(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
+/// Compact layout: just the gauges stacked vertically.
+fn render_compact(f: &mut Frame, area: Rect, snap: &MetricsSnapshot, config: &StatsConfig) {
     let rows = Layout::vertical([
         Constraint::Length(1),
         Constraint::Length(1),
@@ -61,86 +106,140 @@ fn render_compact(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
     ])
     .split(area);
 
-    render_cpu_gauge(f, rows[0], snap);
-    render_mem_gauge(f, rows[1], snap);
-    render_swap_gauge(f, rows[2], snap);
+    render_cpu_gauge(f, rows[0], snap, config);
+    render_mem_gauge(f, rows[1], snap, config);
+    if config.shows(StatsSection::Swap) {
+        render_swap_gauge(f, rows[2], snap, config);
+    }
 }
 
 /// Full layout with gauges, per-core grid, and process info.
-fn render_full(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
-    // Calculate how many rows the per-core section needs.
+fn render_full(
+    f: &mut Frame,
+    area: Rect,
+    snap: &MetricsSnapshot,
+    config: &StatsConfig,
+    cores: CoresView,
+) {
+    let show_swap = config.shows(StatsSection::Swap);
+    let show_gpu = snap.gpu.is_some() && config.shows(StatsSection::Gpu);
+    let show_battery = snap.battery.is_some() && config.shows(StatsSection::Battery);
+    let show_cores = config.shows(StatsSection::Cores);
+    let show_process = config.shows(StatsSection::Process);
+
+    // Calculate how many rows the per-core section needs. The heatmap takes
+    // a row per core, so it gets whatever the other sections leave over.
     let cores_per_row = if area.width >= 60 { 5 } else { 4 };
-    let core_rows = if snap.cpu_per_core.is_empty() {
-        1
-    } else {
-        snap.cpu_per_core.len().div_ceil(cores_per_row)
+    let core_rows = match cores {
+        _ if snap.cpu_per_core.is_empty() => 1,
+        CoresView::Grid => snap.cpu_per_core.len().div_ceil(cores_per_row),
+        CoresView::Heatmap(_) => {
+            let fixed = 3
+                + 2
+                + usize::from(show_swap)
+                + usize::from(show_gpu)
+                + usize::from(show_battery)
+                + if show_process { 3 } else { 0 };
+            let spare = usize::from(area.height).saturating_sub(fixed).max(1);
+            snap.cpu_per_core.len().min(spare)
+        }
     };
 
-    let rows = Layout::vertical([
-        Constraint::Length(1),                // CPU gauge
-        Constraint::Length(1),                // blank
-        Constraint::Length(1),                // MEM gauge
-        Constraint::Length(1),                // SWP gauge
-        Constraint::Length(1),                // blank
-        Constraint::Length(1),                // "CORES" header
-        Constraint::Length(core_rows as u16), // per-core grid
-        Constraint::Length(1),                // blank
-        Constraint::Length(1),                // "SPUD" header
-        Constraint::Length(1),                // process line
-        Constraint::Min(0),                   // remaining space
-    ])
-    .split(area);
+    // CPU gauge, blank, MEM gauge, then each visible section.
+    let mut constraints = vec![Constraint::Length(1); 3];
+    if show_swap {
+        constraints.push(Constraint::Length(1)); // SWP gauge
+    }
+    if show_gpu {
+        constraints.push(Constraint::Length(1)); // GPU gauge
+    }
+    if show_battery {
+        constraints.push(Constraint::Length(1)); // BAT gauge
+    }
+    if show_cores {
+        constraints.extend([
+            Constraint::Length(1),                // blank
+            Constraint::Length(1),                // "CORES" header
+            Constraint::Length(core_rows as u16), // per-core grid
+        ]);
+    }
+    if show_process {
+        constraints.extend([
+            Constraint::Length(1), // blank
+            Constraint::Length(1), // "SPUD" header
+            Constraint::Length(1), // process line
+        ]);
+    }
+    constraints.push(Constraint::Min(0)); // remaining space
+    let rows = Layout::vertical(constraints).split(area);
 
-    render_cpu_gauge(f, rows[0], snap);
-    render_mem_gauge(f, rows[2], snap);
-    render_swap_gauge(f, rows[3], snap);
-    render_cores(f, rows[5], rows[6], snap, cores_per_row);
-    render_process(f, rows[8], rows[9], snap);
+    render_cpu_gauge(f, rows[0], snap, config);
+    render_mem_gauge(f, rows[2], snap, config);
+    let mut next = 3;
+    if show_swap {
+        render_swap_gauge(f, rows[next], snap, config);
+        next += 1;
+    }
+    if let Some(gpu) = snap.gpu.as_ref().filter(|_| show_gpu) {
+        render_gpu_gauge(f, rows[next], gpu, config);
+        next += 1;
+    }
+    if let Some(bat) = snap.battery.as_ref().filter(|_| show_battery) {
+        render_battery_gauge(f, rows[next], bat, config);
+        next += 1;
+    }
+    if show_cores {
+        match cores {
+            CoresView::Grid => render_cores(
+                f,
+                rows[next + 1],
+                rows[next + 2],
+                snap,
+                cores_per_row,
+                config,
+            ),
+            CoresView::Heatmap(history) => {
+                render_core_heatmap(f, rows[next + 1], rows[next + 2], snap, history, config)
+            }
+        }
+        next += 3;
+    }
+    if show_process {
+        render_process(f, rows[next + 1], rows[next + 2], snap);
+    }
 }
 
 /// Render the global CPU gauge.
-fn render_cpu_gauge(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
+fn render_cpu_gauge(f: &mut Frame, area: Rect, snap: &MetricsSnapshot, config: &StatsConfig) {
     let pct = if snap.cpu_global.is_nan() {
         0.0
     } else {
         snap.cpu_global
     };
-    let color = threshold_color(pct, 70.0, 90.0, Color::Green);
+    let theme = theme(config.thresholds.cpu, Color::Green);
     let label = format!("CPU  {}", format_percent(snap.cpu_global));
-
-    let gauge = LineGauge::default()
-        .ratio(clamp_ratio(pct))
-        .label(label)
-        .filled_style(Style::default().fg(color))
-        .unfilled_style(Style::default().fg(Color::DarkGray));
-    f.render_widget(gauge, area);
+    f.render_widget(gauge(pct, label, theme, config.gauge), area);
 }
 
 /// Render the memory gauge with byte counts.
-fn render_mem_gauge(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
+fn render_mem_gauge(f: &mut Frame, area: Rect, snap: &MetricsSnapshot, config: &StatsConfig) {
     let pct = if snap.mem_total == 0 {
         0.0
     } else {
         snap.mem_used as f32 / snap.mem_total as f32 * 100.0
     };
-    let color = threshold_color(pct, 80.0, 95.0, Color::Yellow);
+    let theme = theme(config.thresholds.mem, Color::Yellow);
     let label = format!(
         "MEM  {}  ({} / {})",
         format_percent(pct),
         format_bytes(snap.mem_used),
         format_bytes(snap.mem_total)
     );
-
-    let gauge = LineGauge::default()
-        .ratio(clamp_ratio(pct))
-        .label(label)
-        .filled_style(Style::default().fg(color))
-        .unfilled_style(Style::default().fg(Color::DarkGray));
-    f.render_widget(gauge, area);
+    f.render_widget(gauge(pct, label, theme, config.gauge), area);
 }
 
 /// Render the swap gauge (or N/A if swap is not available).
-fn render_swap_gauge(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
+fn render_swap_gauge(f: &mut Frame, area: Rect, snap: &MetricsSnapshot, config: &StatsConfig) {
     if snap.swap_total == 0 {
         let text = Paragraph::new(Line::from("SWP  N/A".dark_gray()));
         f.render_widget(text, area);
@@ -148,20 +247,49 @@ fn render_swap_gauge(f: &mut Frame, area: Rect, snap: &MetricsSnapshot) {
     }
 
     let pct = snap.swap_used as f32 / snap.swap_total as f32 * 100.0;
-    let color = threshold_color(pct, 50.0, 80.0, Color::Magenta);
+    let theme = theme(config.thresholds.swap, Color::Magenta);
     let label = format!(
         "SWP  {}  ({} / {})",
         format_percent(pct),
         format_bytes(snap.swap_used),
         format_bytes(snap.swap_total)
     );
+    f.render_widget(gauge(pct, label, theme, config.gauge), area);
+}
 
-    let gauge = LineGauge::default()
-        .ratio(clamp_ratio(pct))
-        .label(label)
-        .filled_style(Style::default().fg(color))
-        .unfilled_style(Style::default().fg(Color::DarkGray));
-    f.render_widget(gauge, area);
+/// Render the GPU gauge with VRAM and temperature.
+fn render_gpu_gauge(f: &mut Frame, area: Rect, gpu: &GpuSnapshot, config: &StatsConfig) {
+    let theme = theme(config.thresholds.gpu, Color::Cyan);
+    let mut label = format!(
+        "GPU  {}  ({} / {})",
+        format_percent(gpu.utilization),
+        format_bytes(gpu.vram_used),
+        format_bytes(gpu.vram_total)
+    );
+    if let Some(celsius) = gpu.temperature_c {
+        label.push_str(&format!("  {celsius:.0}°C"));
+    }
+    f.render_widget(gauge(gpu.utilization, label, theme, config.gauge), area);
+}
+
+/// Render the battery gauge with charge state, time left, and power draw.
+fn render_battery_gauge(f: &mut Frame, area: Rect, bat: &BatterySnapshot, config: &StatsConfig) {
+    let color = match crate::battery_style(bat) {
+        HudStyle::Critical => Color::Red,
+        HudStyle::Warn => Color::Yellow,
+        _ => Color::Green,
+    };
+    let mut label = format!("BAT  {}  {}", format_percent(bat.percent), bat.state.name());
+    if let Some(remaining) = bat.time_remaining {
+        // Minute precision; seconds only jitter with the power reading.
+        let secs = remaining.as_secs() / 60 * 60;
+        label.push_str(&format!("  {}", format_uptime(secs)));
+    }
+    if let Some(watts) = bat.power_watts {
+        label.push_str(&format!("  {watts:.1} W"));
+    }
+    let theme = ChartTheme::new(color);
+    f.render_widget(gauge(bat.percent, label, theme, config.gauge), area);
 }
 
 /// Render the per-core CPU grid.
@@ -171,18 +299,20 @@ fn render_cores(
     grid_area: Rect,
     snap: &MetricsSnapshot,
     cores_per_row: usize,
+    config: &StatsConfig,
 ) {
-    let header = Paragraph::new(Line::from("CORES".bold()));
+    let header = Paragraph::new(Line::from(tr("CORES").bold()));
     f.render_widget(header, header_area);
 
     if snap.cpu_per_core.is_empty() {
         return;
     }
 
+    let theme = theme(config.thresholds.cpu, Color::Green);
     let mut lines: Vec<Line> = Vec::new();
     let mut row_spans: Vec<Span> = Vec::new();
     for (i, &pct) in snap.cpu_per_core.iter().enumerate() {
-        let color = threshold_color(pct, 70.0, 90.0, Color::Green);
+        let color = theme.color(f64::from(pct));
         row_spans.push(Span::styled(
             format!("{i:>2}: {:>3.0}%", pct),
             Style::default().fg(color),
@@ -201,6 +331,64 @@ fn render_cores(
     f.render_widget(text, grid_area);
 }
 
+/// Render per-core history as a heatmap: one row per core, newest sample
+/// at the right edge. Cores beyond the grid's height are cut off. The
+/// header row ends in a sparkline of overall CPU over the same samples.
+fn render_core_heatmap(
+    f: &mut Frame,
+    header_area: Rect,
+    grid_area: Rect,
+    snap: &MetricsSnapshot,
+    history: &TelemetryStore,
+    config: &StatsConfig,
+) {
+    let theme = theme(config.thresholds.cpu, Color::Green);
+    let header = Line::from(vec![
+        tr("CORES").bold(),
+        Span::raw(" "),
+        tr("history").dark_gray(),
+    ]);
+    let label_width = header.width() as u16 + 1;
+    f.render_widget(Paragraph::new(header), header_area);
+
+    // "NN " label, then one column per sample.
+    let columns = usize::from(grid_area.width).saturating_sub(3);
+    let cpu = history.numeric_last_n("stats", "cpu", columns);
+    let spark_area = Rect {
+        x: header_area.x + label_width,
+        width: header_area.width.saturating_sub(label_width),
+        ..header_area
+    };
+    f.render_widget(Sparkline::new(&cpu, theme).max(100.0), spark_area);
+
+    let lines: Vec<Line> = (0..snap.cpu_per_core.len())
+        .take(usize::from(grid_area.height))
+        .map(|core| {
+            let samples = history.numeric_last_n("stats", &core_key(core), columns);
+            let mut spans = vec![
+                Span::raw(format!("{core:>2} ")),
+                Span::raw(" ".repeat(columns - samples.len())),
+            ];
+            spans.extend(samples.into_iter().map(|pct| {
+                let color = theme.color(pct);
+                Span::styled(
+                    heat_shade(pct as f32).to_string(),
+                    Style::default().fg(color),
+                )
+            }));
+            Line::from(spans)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), grid_area);
+}
+
+/// Heatmap glyph for a utilization percentage; every sample gets a mark so
+/// idle cores still show as a faint row.
+fn heat_shade(pct: f32) -> char {
+    let level = (pct.max(0.0) / 25.0) as usize;
+    HEAT_SHADES[level.min(HEAT_SHADES.len() - 1)]
+}
+
 /// Render the SPUD process info section.
 fn render_process(f: &mut Frame, header_area: Rect, data_area: Rect, snap: &MetricsSnapshot) {
     let header = Paragraph::new(Line::from("SPUD".bold()));
@@ -228,18 +416,28 @@ fn render_process(f: &mut Frame, header_area: Rect, data_area: Rect, snap: &Metr
 mod tests {
     use super::*;
     use ratatui::{backend::TestBackend, Terminal};
+    use spud_core::event::TelemetryValue;
 
     /// Helper to render into a test terminal and return the buffer.
     fn render_to_buffer(
         width: u16,
         height: u16,
         snap: &MetricsSnapshot,
+    ) -> ratatui::buffer::Buffer {
+        render_with_config(width, height, snap, &StatsConfig::default())
+    }
+
+    fn render_with_config(
+        width: u16,
+        height: u16,
+        snap: &MetricsSnapshot,
+        config: &StatsConfig,
     ) -> ratatui::buffer::Buffer {
         let backend = TestBackend::new(width, height);
         let mut terminal = Terminal::new(backend).unwrap();
         terminal
             .draw(|f| {
-                render_hero_content(f, f.area(), snap);
+                render_hero_content(f, f.area(), snap, config, CoresView::Grid);
             })
             .unwrap();
         terminal.backend().buffer().clone()
@@ -285,4 +483,169 @@ mod tests {
         // No CORES header in compact mode.
         assert!(!text.contains("CORES"));
     }
+
+    #[test]
+    fn hidden_sections_are_left_out() {
+        let snap = MetricsSnapshot {
+            swap_total: 1024,
+            swap_used: 512,
+            cpu_per_core: vec![10.0, 20.0],
+            ..MetricsSnapshot::default()
+        };
+        let config = StatsConfig {
+            hide: vec![StatsSection::Cores, StatsSection::Swap],
+            ..StatsConfig::default()
+        };
+        let buf = render_with_config(60, 20, &snap, &config);
+        let text: String = buf
+            .content()
+            .iter()
+            .map(|c| c.symbol().to_string())
+            .collect();
+        assert!(text.contains("MEM"));
+        assert!(text.contains("SPUD"));
+        assert!(!text.contains("CORES"));
+        assert!(!text.contains("SWP"));
+    }
+
+    /// A busy four-core machine with swap, uptime, and load averages.
+    fn busy_snapshot() -> MetricsSnapshot {
+        MetricsSnapshot {
+            cpu_global: 42.5,
+            cpu_per_core: vec![12.0, 55.0, 91.0, 30.0],
+            mem_total: 16 * 1024 * 1024 * 1024,
+            mem_used: 11 * 1024 * 1024 * 1024,
+            swap_total: 4 * 1024 * 1024 * 1024,
+            swap_used: 512 * 1024 * 1024,
+            self_rss: Some(48 * 1024 * 1024),
+            self_cpu: Some(1.5),
+            load_average: Some([0.52, 1.10, 0.98]),
+            uptime: std::time::Duration::from_secs(3 * 86400 + 4 * 3600 + 5 * 60),
+            ..MetricsSnapshot::default()
+        }
+    }
+
+    #[test]
+    fn hero_snapshots_in_compact_and_full_modes() {
+        let snap = busy_snapshot();
+        for (name, height) in [("hero_compact", 7), ("hero_full", 20)] {
+            let text = spud_testkit::render(64, height, |f| {
+                render_hero_content(f, f.area(), &snap, &StatsConfig::default(), CoresView::Grid);
+            });
+            insta::assert_snapshot!(name, spud_testkit::normalize(&text));
+        }
+    }
+
+    #[test]
+    fn theme_uses_configured_bands() {
+        let bands = theme(Thresholds::new(60, 85), Color::Green);
+        assert_eq!(bands.color(59.9), Color::Green);
+        assert_eq!(bands.color(60.0), Color::Yellow);
+        assert_eq!(bands.color(85.0), Color::Red);
+    }
+
+    #[test]
+    fn gpu_row_appears_only_with_a_gpu() {
+        let mut snap = MetricsSnapshot::default();
+        let text = |snap: &MetricsSnapshot| -> String {
+            render_to_buffer(80, 20, snap)
+                .content()
+                .iter()
+                .map(|c| c.symbol().to_string())
+                .collect()
+        };
+        assert!(!text(&snap).contains("GPU"));
+
+        snap.gpu = Some(GpuSnapshot {
+            name: "AMD".into(),
+            utilization: 34.0,
+            vram_used: 1 << 30,
+            vram_total: 8 << 30,
+            temperature_c: Some(61.0),
+        });
+        let shown = text(&snap);
+        assert!(shown.contains("GPU"));
+        assert!(shown.contains("61°C"));
+    }
+
+    #[test]
+    fn battery_row_shows_state_time_and_power() {
+        let snap = MetricsSnapshot {
+            battery: Some(BatterySnapshot {
+                percent: 54.0,
+                state: crate::battery::ChargeState::Discharging,
+                time_remaining: Some(std::time::Duration::from_secs(2 * 3_600 + 13 * 60 + 41)),
+                power_watts: Some(11.2),
+            }),
+            ..MetricsSnapshot::default()
+        };
+        let text: String = render_to_buffer(90, 20, &snap)
+            .content()
+            .iter()
+            .map(|c| c.symbol().to_string())
+            .collect();
+        assert!(text.contains("BAT  54.0%  discharging  2h 13m 0s  11.2 W"));
+    }
+
+    #[test]
+    fn header_shows_uptime_and_load_once_known() {
+        assert_eq!(header_line(&MetricsSnapshot::default()), "SYSTEM STATS");
+        let snap = MetricsSnapshot {
+            uptime: std::time::Duration::from_secs(90_061),
+            load_average: Some([0.52, 0.61, 0.7]),
+            ..MetricsSnapshot::default()
+        };
+        assert_eq!(
+            header_line(&snap),
+            "SYSTEM STATS · up 1d 1h 1m · load 0.52 0.61 0.70"
+        );
+    }
+
+    #[test]
+    fn heatmap_draws_core_history_right_aligned() {
+        let mut history = TelemetryStore::default();
+        let now = std::time::Instant::now();
+        for pct in [5.0, 30.0, 60.0, 95.0] {
+            history.record("stats", &core_key(0), TelemetryValue::Float(pct), now);
+            history.record("stats", &core_key(1), TelemetryValue::Float(0.0), now);
+            history.record("stats", "cpu", TelemetryValue::Float(pct / 2.0), now);
+        }
+        let snap = MetricsSnapshot {
+            cpu_per_core: vec![95.0, 0.0],
+            ..MetricsSnapshot::default()
+        };
+        let backend = TestBackend::new(40, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| {
+                let view = CoresView::Heatmap(&history);
+                render_hero_content(f, f.area(), &snap, &StatsConfig::default(), view);
+            })
+            .unwrap();
+        let rows: Vec<String> = terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(40)
+            .map(|row| row.iter().map(|c| c.symbol()).collect())
+            .collect();
+        // Overall CPU as a sparkline at the end of the header
+        assert!(rows
+            .iter()
+            .any(|row| row.contains("CORES history") && row.ends_with(" ▁▁▂▄│")));
+        assert!(rows
+            .iter()
+            .any(|row| row.starts_with("│ 0 ") && row.ends_with(" ░▒▓█│")));
+        assert!(rows
+            .iter()
+            .any(|row| row.starts_with("│ 1 ") && row.ends_with(" ░░░░│")));
+    }
+
+    #[test]
+    fn heat_shade_bands() {
+        assert_eq!(heat_shade(0.0), '░');
+        assert_eq!(heat_shade(49.0), '▒');
+        assert_eq!(heat_shade(100.0), '█');
+        assert_eq!(heat_shade(f32::NAN), '░');
+    }
 }
@@ -1,12 +1,17 @@
 /// Telemetry collector wrapping `sysinfo` with interval-gated refresh.
 ///
-/// Gathers CPU, memory, swap, and SPUD process metrics. Designed to be
+/// Gathers CPU, memory, swap, load, uptime, battery, and SPUD process
+/// metrics, plus GPU
+/// metrics when built with the `gpu` feature and a supported GPU is present. Designed to be
 /// called every tick (~100ms) but only performs expensive sysinfo refreshes
 /// at the configured interval (default 1s).
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 use sysinfo::{Pid, ProcessesToUpdate, System};
 
+use crate::battery::{self, BatterySnapshot};
+
 /// A point-in-time snapshot of system and process metrics.
 #[derive(Debug, Clone)]
 pub struct MetricsSnapshot {
@@ -26,6 +31,30 @@ pub struct MetricsSnapshot {
     pub self_rss: Option<u64>,
     /// CPU usage of the SPUD process as a percentage.
     pub self_cpu: Option<f32>,
+    /// 1-, 5-, and 15-minute load averages; `None` where the OS has none
+    /// (Windows).
+    pub load_average: Option<[f64; 3]>,
+    /// Time since the machine booted.
+    pub uptime: Duration,
+    /// First GPU's metrics; `None` without the `gpu` feature or a GPU.
+    pub gpu: Option<GpuSnapshot>,
+    /// First battery's metrics; `None` on desktops and non-Linux systems.
+    pub battery: Option<BatterySnapshot>,
+}
+
+/// Metrics for one GPU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuSnapshot {
+    /// Vendor or model name.
+    pub name: String,
+    /// Busy percentage (0.0–100.0).
+    pub utilization: f32,
+    /// VRAM in use, in bytes.
+    pub vram_used: u64,
+    /// Total VRAM in bytes.
+    pub vram_total: u64,
+    /// Core temperature, if the driver reports it.
+    pub temperature_c: Option<f32>,
 }
 
 impl Default for MetricsSnapshot {
@@ -39,6 +68,10 @@ impl Default for MetricsSnapshot {
             swap_used: 0,
             self_rss: None,
             self_cpu: None,
+            load_average: None,
+            uptime: Duration::ZERO,
+            gpu: None,
+            battery: None,
         }
     }
 }
@@ -54,6 +87,10 @@ pub struct TelemetryCollector {
     interval: Duration,
     last_refresh: Option<Instant>,
     snapshot: MetricsSnapshot,
+    /// sysfs directory of the battery found at startup.
+    battery: Option<PathBuf>,
+    #[cfg(feature = "gpu")]
+    gpu: Option<crate::gpu::GpuProbe>,
 }
 
 impl TelemetryCollector {
@@ -79,9 +116,17 @@ impl TelemetryCollector {
             interval,
             last_refresh: None,
             snapshot: MetricsSnapshot::default(),
+            battery: battery::detect(),
+            #[cfg(feature = "gpu")]
+            gpu: crate::gpu::GpuProbe::detect(),
         }
     }
 
+    /// Change the refresh interval, e.g. when the runtime profile changes.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
     /// Refresh system metrics if the interval has elapsed.
     ///
     /// Returns `true` if a refresh was performed.
@@ -124,6 +169,19 @@ impl TelemetryCollector {
             }
         }
 
+        if !cfg!(windows) {
+            let load = System::load_average();
+            self.snapshot.load_average = Some([load.one, load.five, load.fifteen]);
+        }
+        self.snapshot.uptime = Duration::from_secs(System::uptime());
+
+        self.snapshot.battery = self.battery.as_deref().and_then(battery::sample);
+
+        #[cfg(feature = "gpu")]
+        {
+            self.snapshot.gpu = self.gpu.as_ref().and_then(|probe| probe.sample());
+        }
+
         true
     }
 
@@ -188,6 +246,16 @@ mod tests {
         assert!(c.snapshot().self_rss.is_some());
     }
 
+    #[test]
+    fn uptime_known_after_refresh() {
+        let mut c = TelemetryCollector::new();
+        c.maybe_refresh(Instant::now());
+        assert!(c.snapshot().uptime > Duration::ZERO);
+        if cfg!(unix) {
+            assert!(c.snapshot().load_average.is_some());
+        }
+    }
+
     #[test]
     fn default_snapshot_shows_nan_cpu() {
         let c = TelemetryCollector::new();
@@ -0,0 +1,78 @@
+//! Example plugin: run command lines typed into the console.
+//!
+//! Once connected, asks the host which commands it may run with
+//! `spud.host.list_commands` and names them in a `spud.console.prompt`
+//! question. Each answer is run with `spud.host.invoke_command`, its output is
+//! published under [`OUTPUT_TAG`], and the question is asked again. A
+//! command the manifest does not allow comes back as a host error, which
+//! is published like any other failure instead of stopping the plugin.
+
+use std::cell::RefCell;
+use std::process::ExitCode;
+use std::rc::Rc;
+
+use serde_json::json;
+use spud_plugin_examples::{print_manifest_if_asked, Example, OUTPUT_TAG, PROMPT_TOKEN, VERSION};
+use spud_plugin_sdk::{ClientError, Plugin, PluginClient};
+
+/// Shown in place of the console's input marker, before the commands.
+const QUESTION: &str = "run which command?";
+
+fn main() -> ExitCode {
+    let example = Example::CommandProvider;
+    if print_manifest_if_asked(example) {
+        return ExitCode::SUCCESS;
+    }
+
+    let question = Rc::new(RefCell::new(QUESTION.to_string()));
+    let ready_question = Rc::clone(&question);
+    let result = Plugin::new(example.plugin_id(), VERSION)
+        .on_ready(move |client, _| {
+            let names: Vec<String> = client
+                .list_commands()?
+                .into_iter()
+                .map(|command| command.name)
+                .collect();
+            *ready_question.borrow_mut() = format!("{QUESTION} ({})", names.join(", "));
+            ask(client, &ready_question.borrow())
+        })
+        .on_answer(move |client, answer| {
+            // A cancelled question stays closed until the plugin restarts
+            if answer.callback_token != PROMPT_TOKEN || answer.cancelled {
+                return Ok(());
+            }
+            let words: Vec<&str> = answer.answer.split_whitespace().collect();
+            if let Some((command, args)) = words.split_first() {
+                run(client, command, args)?;
+            }
+            ask(client, &question.borrow())
+        })
+        .run_stdio();
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}: {err}", example.plugin_id());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Put the question on the console input line. Another prompt may own it;
+/// then this one is skipped until the next answer.
+fn ask(client: &mut PluginClient, question: &str) -> Result<(), ClientError> {
+    client.prompt(question, PROMPT_TOKEN)?;
+    Ok(())
+}
+
+/// Run `command` on the host and publish its output.
+fn run(client: &mut PluginClient, command: &str, args: &[&str]) -> Result<(), ClientError> {
+    let (lines, is_error) = match client.invoke_command(command, args) {
+        Ok(result) => (result.lines, result.is_error),
+        Err(ClientError::Rpc(error)) => (vec![error.message], true),
+        Err(err) => return Err(err),
+    };
+    let payload = json!({ "command": command, "lines": lines, "is_error": is_error });
+    client.publish_event(OUTPUT_TAG, &payload.to_string())?;
+    Ok(())
+}
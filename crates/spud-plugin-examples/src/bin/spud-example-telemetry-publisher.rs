@@ -0,0 +1,44 @@
+//! Example plugin: publish a summary of host telemetry on every tick.
+//!
+//! Registers a payload schema for [`SUMMARY_TAG`] once connected, so the
+//! host refuses malformed summaries, then reads one page of telemetry per
+//! tick and publishes how many samples it saw.
+
+use std::process::ExitCode;
+
+use serde_json::json;
+use spud_plugin_examples::{
+    print_manifest_if_asked, summary_schema, Example, SUMMARY_TAG, VERSION,
+};
+use spud_plugin_sdk::{EventCategory, GetTelemetryParams, Plugin};
+
+fn main() -> ExitCode {
+    let example = Example::TelemetryPublisher;
+    if print_manifest_if_asked(example) {
+        return ExitCode::SUCCESS;
+    }
+
+    let mut ticks: u64 = 0;
+    let result = Plugin::new(example.plugin_id(), VERSION)
+        .on_ready(|client, _| {
+            client.register_event_schema(SUMMARY_TAG, &summary_schema())?;
+            Ok(())
+        })
+        .on(EventCategory::Tick, move |client, _| {
+            ticks += 1;
+            let telemetry = client.get_telemetry(&GetTelemetryParams::default())?;
+            let payload = json!({ "tick": ticks, "samples": telemetry.items.len() });
+            client.publish_event(SUMMARY_TAG, &payload.to_string())?;
+            Ok(())
+        })
+        .run_stdio();
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // stderr reaches the host's terminal, or its log in dev mode
+            eprintln!("{}: {err}", example.plugin_id());
+            ExitCode::FAILURE
+        }
+    }
+}
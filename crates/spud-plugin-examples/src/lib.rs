@@ -0,0 +1,139 @@
+//! Example plugins built on [`spud_plugin_sdk`].
+//!
+//! Two small binaries, kept working by the integration tests in `tests/`,
+//! which run them under the real plugin runtime on every platform:
+//!
+//! - `spud-example-telemetry-publisher` registers a payload schema for its
+//!   event tag, then on every tick reads host telemetry and publishes a
+//!   summary of it.
+//! - `spud-example-command-provider` lists the host commands it may run,
+//!   asks for a command line in the console, runs it on the host,
+//!   publishes what it printed, and asks again.
+//!
+//! Each binary prints the `plugin.toml` that starts it when run with
+//! `--manifest`, so trying one is:
+//!
+//! ```text
+//! cargo build -p spud-plugin-examples
+//! mkdir -p plugins/telemetry
+//! target/debug/spud-example-telemetry-publisher --manifest > plugins/telemetry/plugin.toml
+//! SPUD_PLUGIN_DIRS=plugins cargo run -p spud-app
+//! ```
+
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+/// Version every example reports in its manifest and handshake.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Tag of the telemetry publisher's summary events.
+pub const SUMMARY_TAG: &str = "examples.telemetry.summary";
+/// Tag of the command provider's output events.
+pub const OUTPUT_TAG: &str = "examples.command.output";
+/// Callback token of the command provider's prompt.
+pub const PROMPT_TOKEN: &str = "command";
+/// Commands the command provider may run on the host.
+pub const COMMANDS: [&str; 3] = ["help", "tps", "version"];
+
+/// One of the example plugins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Example {
+    TelemetryPublisher,
+    CommandProvider,
+}
+
+impl Example {
+    pub const ALL: [Example; 2] = [Example::TelemetryPublisher, Example::CommandProvider];
+
+    /// Plugin ID, as in the manifest.
+    pub fn plugin_id(self) -> &'static str {
+        match self {
+            Self::TelemetryPublisher => "examples.telemetry-publisher",
+            Self::CommandProvider => "examples.command-provider",
+        }
+    }
+
+    /// Name of the binary, without the platform's executable suffix.
+    pub fn binary(self) -> &'static str {
+        match self {
+            Self::TelemetryPublisher => "spud-example-telemetry-publisher",
+            Self::CommandProvider => "spud-example-command-provider",
+        }
+    }
+
+    /// `plugin.toml` for this example, started from `entrypoint`.
+    pub fn manifest(self, entrypoint: &Path) -> String {
+        // JSON string escapes are valid in TOML basic strings.
+        let quote = |text: &str| Value::from(text).to_string();
+        let (name, host_api, permissions) = match self {
+            // Schemas need host API 1.2.0
+            Self::TelemetryPublisher => (
+                "Telemetry publisher",
+                "^1.2.0",
+                format!(
+                    "event_tags = {}\nsubscriptions = {}",
+                    json!([SUMMARY_TAG]),
+                    json!(["tick"])
+                ),
+            ),
+            // Command listing needs host API 1.7.0
+            Self::CommandProvider => (
+                "Command provider",
+                "^1.7.0",
+                format!(
+                    "commands = {}\nevent_tags = {}",
+                    json!(COMMANDS),
+                    json!([OUTPUT_TAG])
+                ),
+            ),
+        };
+        format!(
+            r#"id = {id}
+name = {name}
+version = {version}
+
+[runtime]
+entrypoint = {entrypoint}
+
+[compatibility]
+host_api = {host_api}
+
+[permissions]
+{permissions}
+
+[health]
+ping_interval_ms = 5000
+"#,
+            id = quote(self.plugin_id()),
+            name = quote(name),
+            version = quote(VERSION),
+            entrypoint = quote(&entrypoint.to_string_lossy()),
+            host_api = quote(host_api),
+        )
+    }
+}
+
+/// JSON Schema of [`SUMMARY_TAG`] payloads.
+pub fn summary_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["tick", "samples"],
+        "properties": {
+            "tick": { "type": "integer", "minimum": 1 },
+            "samples": { "type": "integer", "minimum": 0 }
+        }
+    })
+}
+
+/// Print the example's manifest, started from this executable, when the
+/// process was run with `--manifest`. Returns whether it did.
+pub fn print_manifest_if_asked(example: Example) -> bool {
+    if std::env::args().nth(1).as_deref() != Some("--manifest") {
+        return false;
+    }
+    match std::env::current_exe() {
+        Ok(exe) => print!("{}", example.manifest(&exe)),
+        Err(err) => eprintln!("cannot find this executable: {err}"),
+    }
+    true
+}
@@ -0,0 +1,496 @@
+//! Run the example plugins under the real plugin runtime.
+//!
+//! Besides keeping the examples working, these are the runtime's
+//! end-to-end tests with real SDK plugins on every platform: handshakes,
+//! request bridging, event delivery, permission denials and their audit
+//! records, sessions, and dev mode. Protocol edge cases a well-behaved
+//! plugin never produces (malformed frames, out-of-order calls) stay with
+//! the scripted fixtures in `spud-remote`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use spud_plugin_examples::{Example, OUTPUT_TAG, PROMPT_TOKEN, SUMMARY_TAG, VERSION};
+use spud_remote::audit::{AuditAction, AuditDecision};
+use spud_remote::conformance::ConformanceHarness;
+use spud_remote::protocol::{
+    method, CommandInfo, ConsoleAnswerParams, ConsolePromptParams, ConsolePromptResult,
+    EventCategory, InvalidEventPayload, InvokeCommandParams, InvokeCommandResult, PayloadViolation,
+    PublishEventParams, PublishEventResult, RegisterEventSchemaParams, RegisterEventSchemaResult,
+    StateSnapshot, TelemetryDatum, HOST_API_VERSION,
+};
+use spud_remote::runtime::{HostBridge, HostRejection, PluginRuntime};
+use spud_testkit::TestDir;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Temporary plugin root, removed on drop.
+struct PluginRoot {
+    dir: TestDir,
+}
+
+impl PluginRoot {
+    /// A root holding `example`'s manifest, started from its built binary.
+    fn new(example: Example) -> Self {
+        let root = Self {
+            dir: TestDir::new(&format!("plugin-examples-{}", example.plugin_id())),
+        };
+        root.add(example);
+        root
+    }
+
+    /// Add `example`'s manifest next to those already in the root.
+    fn add(&self, example: Example) {
+        let plugin = self.dir.join(example.plugin_id());
+        fs::create_dir_all(&plugin).unwrap();
+        fs::write(
+            plugin.join("plugin.toml"),
+            example.manifest(&binary(example)),
+        )
+        .unwrap();
+    }
+
+    fn manifest_path(&self, example: Example) -> PathBuf {
+        self.dir.join(example.plugin_id()).join("plugin.toml")
+    }
+
+    /// Rewrite `example`'s manifest, replacing `from` with `to`.
+    fn edit_manifest(&self, example: Example, from: &str, to: &str) {
+        let path = self.manifest_path(example);
+        let manifest = fs::read_to_string(&path).unwrap();
+        assert!(manifest.contains(from), "{manifest}");
+        fs::write(&path, manifest.replace(from, to)).unwrap();
+    }
+
+    fn runtime(&self) -> PluginRuntime {
+        PluginRuntime::from_search_roots(std::slice::from_ref(&self.dir.path)).unwrap()
+    }
+}
+
+fn binary(example: Example) -> PathBuf {
+    Path::new(match example {
+        Example::TelemetryPublisher => env!("CARGO_BIN_EXE_spud-example-telemetry-publisher"),
+        Example::CommandProvider => env!("CARGO_BIN_EXE_spud-example-command-provider"),
+    })
+    .to_path_buf()
+}
+
+/// Host that answers every call and records what plugins asked for.
+#[derive(Default)]
+struct RecordingHost {
+    invoked: Vec<String>,
+    published: Vec<(String, Value)>,
+    schemas: Vec<String>,
+    prompts: Vec<ConsolePromptParams>,
+    /// Refuse every published payload, as a host schema would.
+    reject_payloads: bool,
+}
+
+impl HostBridge for RecordingHost {
+    fn state_snapshot(&mut self) -> Result<StateSnapshot> {
+        let datum = |key: &str, value: Value| TelemetryDatum {
+            source: "stats".to_string(),
+            key: key.to_string(),
+            value,
+        };
+        Ok(StateSnapshot {
+            active_module: None,
+            status_line: "OK".to_string(),
+            uptime_seconds: 1,
+            tps: 10.0,
+            telemetry: vec![
+                datum("cpu.total", json!(12.5)),
+                datum("mem.used", json!(42)),
+            ],
+        })
+    }
+
+    fn list_commands(&mut self) -> Result<Vec<CommandInfo>> {
+        let info = |name: &str, aliases: &[&str]| CommandInfo {
+            name: name.to_string(),
+            aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+            usage: name.to_string(),
+            description: String::new(),
+        };
+        Ok(vec![
+            info("quit", &["q"]),
+            info("version", &[]),
+            info("help", &["?"]),
+            info("tps", &[]),
+        ])
+    }
+
+    fn invoke_command(&mut self, params: InvokeCommandParams) -> Result<InvokeCommandResult> {
+        self.invoked.push(params.command.clone());
+        Ok(InvokeCommandResult {
+            lines: vec![format!("ok:{}", params.command)],
+            is_error: false,
+            output: None,
+        })
+    }
+
+    fn publish_event(&mut self, params: PublishEventParams) -> Result<PublishEventResult> {
+        if self.reject_payloads {
+            let rejection = InvalidEventPayload {
+                tag: params.tag,
+                violations: vec![PayloadViolation {
+                    path: String::new(),
+                    message: "summaries are not wanted".to_string(),
+                }],
+            };
+            return Err(HostRejection(rejection.to_jsonrpc_error()).into());
+        }
+        let payload = serde_json::from_str(&params.payload)?;
+        self.published.push((params.tag, payload));
+        Ok(PublishEventResult { accepted: true })
+    }
+
+    fn register_event_schema(
+        &mut self,
+        _plugin_id: &str,
+        params: RegisterEventSchemaParams,
+    ) -> Result<RegisterEventSchemaResult> {
+        self.schemas.push(params.tag);
+        Ok(RegisterEventSchemaResult { registered: true })
+    }
+
+    fn console_prompt(
+        &mut self,
+        _plugin_id: &str,
+        params: ConsolePromptParams,
+    ) -> Result<ConsolePromptResult> {
+        self.prompts.push(params);
+        Ok(ConsolePromptResult { accepted: true })
+    }
+}
+
+/// Methods of the next `count` requests from `plugin_id`, each with
+/// whether it was answered with an error.
+fn pump(
+    runtime: &mut PluginRuntime,
+    plugin_id: &str,
+    host: &mut RecordingHost,
+    count: usize,
+) -> Vec<(String, bool)> {
+    (0..count)
+        .map(|_| {
+            let handled = runtime.pump_next(plugin_id, host, TIMEOUT).unwrap();
+            (handled.method, handled.responded_with_error)
+        })
+        .collect()
+}
+
+#[test]
+fn telemetry_publisher_summarizes_telemetry_each_tick() {
+    let example = Example::TelemetryPublisher;
+    let root = PluginRoot::new(example);
+    let mut runtime = root.runtime();
+    let mut host = RecordingHost::default();
+    runtime.start(example.plugin_id()).unwrap();
+
+    assert_eq!(
+        pump(&mut runtime, example.plugin_id(), &mut host, 2),
+        [
+            (method::SUBSCRIBE.to_string(), false),
+            (method::REGISTER_EVENT_SCHEMA.to_string(), false),
+        ]
+    );
+    assert_eq!(host.schemas, [SUMMARY_TAG]);
+
+    for tick in 1..=2 {
+        let delivered = runtime
+            .broadcast_event(EventCategory::Tick, Some("tick"), json!({ "now": tick }))
+            .unwrap();
+        assert_eq!(delivered, 1);
+        assert_eq!(
+            pump(&mut runtime, example.plugin_id(), &mut host, 2),
+            [
+                (method::GET_TELEMETRY.to_string(), false),
+                (method::PUBLISH_EVENT.to_string(), false),
+            ]
+        );
+    }
+    assert_eq!(
+        host.published,
+        [
+            (SUMMARY_TAG.to_string(), json!({ "tick": 1, "samples": 2 })),
+            (SUMMARY_TAG.to_string(), json!({ "tick": 2, "samples": 2 })),
+        ]
+    );
+
+    runtime.shutdown_all();
+}
+
+#[test]
+fn command_provider_offers_and_runs_allowed_commands_from_the_console() {
+    let example = Example::CommandProvider;
+    let root = PluginRoot::new(example);
+    let mut runtime = root.runtime();
+    let mut host = RecordingHost::default();
+    runtime.start(example.plugin_id()).unwrap();
+
+    let prompt = (method::CONSOLE_PROMPT.to_string(), false);
+    assert_eq!(
+        pump(&mut runtime, example.plugin_id(), &mut host, 2),
+        [(method::LIST_COMMANDS.to_string(), false), prompt.clone()]
+    );
+    // `quit` is not in the manifest's allowlist, so it is not offered
+    assert_eq!(
+        host.prompts[0].question,
+        "run which command? (help, tps, version)"
+    );
+    assert_eq!(host.prompts[0].callback_token, PROMPT_TOKEN);
+
+    answer(&mut runtime, "help");
+    assert_eq!(
+        pump(&mut runtime, example.plugin_id(), &mut host, 3),
+        [
+            (method::INVOKE_COMMAND.to_string(), false),
+            (method::PUBLISH_EVENT.to_string(), false),
+            prompt.clone(),
+        ]
+    );
+
+    // Not in the manifest's allowlist: the host refuses, the plugin reports
+    answer(&mut runtime, "quit now");
+    assert_eq!(
+        pump(&mut runtime, example.plugin_id(), &mut host, 3),
+        [
+            (method::INVOKE_COMMAND.to_string(), true),
+            (method::PUBLISH_EVENT.to_string(), false),
+            prompt,
+        ]
+    );
+
+    assert_eq!(host.invoked, ["help"]);
+    assert_eq!(host.published[0].0, OUTPUT_TAG);
+    assert_eq!(
+        host.published[0].1,
+        json!({ "command": "help", "lines": ["ok:help"], "is_error": false })
+    );
+    let refused = &host.published[1].1;
+    assert_eq!(refused["command"], "quit");
+    assert_eq!(refused["is_error"], true);
+    assert!(refused["lines"][0].as_str().unwrap().contains("quit"));
+
+    runtime.shutdown_all();
+}
+
+#[test]
+fn examples_print_their_manifests_and_pass_conformance() {
+    for example in Example::ALL {
+        let output = Command::new(binary(example))
+            .arg("--manifest")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let printed = String::from_utf8(output.stdout).unwrap();
+        assert!(printed.contains(example.plugin_id()), "{printed}");
+
+        let root = PluginRoot::new(example);
+        let report = ConformanceHarness::from_manifest_path(&root.manifest_path(example))
+            .unwrap()
+            .run_all();
+        assert!(report.passed(), "{:#?}", report.render_lines());
+    }
+}
+
+/// Answer the command provider's question with `line`.
+fn answer(runtime: &mut PluginRuntime, line: &str) {
+    let params = ConsoleAnswerParams {
+        callback_token: PROMPT_TOKEN.to_string(),
+        answer: line.to_string(),
+        cancelled: false,
+    };
+    runtime
+        .send_console_answer(Example::CommandProvider.plugin_id(), params)
+        .unwrap();
+}
+
+/// Audited actions of `plugin_id`, oldest first.
+fn audited(runtime: &PluginRuntime, plugin_id: &str) -> Vec<(AuditAction, AuditDecision)> {
+    runtime
+        .audit_log()
+        .recent_for(plugin_id, 10)
+        .iter()
+        .map(|record| (record.action, record.decision))
+        .collect()
+}
+
+#[test]
+fn runtime_handshakes_and_bridges_requests_for_every_example() {
+    let telemetry = Example::TelemetryPublisher;
+    let commands = Example::CommandProvider;
+    let root = PluginRoot::new(telemetry);
+    root.add(commands);
+    let mut runtime = root.runtime();
+    assert_eq!(
+        runtime.plugin_ids(),
+        [commands.plugin_id(), telemetry.plugin_id()]
+    );
+    for example in Example::ALL {
+        let handshake = runtime.start(example.plugin_id()).unwrap();
+        assert_eq!(handshake.selected_api_version, HOST_API_VERSION);
+    }
+
+    let mut host = RecordingHost::default();
+    pump(&mut runtime, telemetry.plugin_id(), &mut host, 2);
+    pump(&mut runtime, commands.plugin_id(), &mut host, 2);
+    // Only the telemetry publisher subscribed to ticks
+    let delivered = runtime
+        .broadcast_event(EventCategory::Tick, Some("tick"), json!({ "now": 1 }))
+        .unwrap();
+    assert_eq!(delivered, 1);
+    pump(&mut runtime, telemetry.plugin_id(), &mut host, 2);
+    answer(&mut runtime, "version");
+    pump(&mut runtime, commands.plugin_id(), &mut host, 3);
+
+    assert_eq!(host.invoked, ["version"]);
+    let tags: Vec<&str> = host.published.iter().map(|(tag, _)| tag.as_str()).collect();
+    assert_eq!(tags, [SUMMARY_TAG, OUTPUT_TAG]);
+    let allowed = AuditDecision::Allowed;
+    assert_eq!(
+        audited(&runtime, telemetry.plugin_id()),
+        [
+            (AuditAction::Subscribe, allowed),
+            (AuditAction::RegisterEventSchema, allowed),
+            (AuditAction::PublishEvent, allowed),
+        ]
+    );
+    assert_eq!(
+        audited(&runtime, commands.plugin_id()),
+        [
+            (AuditAction::ConsolePrompt, allowed),
+            (AuditAction::InvokeCommand, allowed),
+            (AuditAction::PublishEvent, allowed),
+            (AuditAction::ConsolePrompt, allowed),
+        ]
+    );
+
+    runtime.shutdown_all();
+}
+
+#[test]
+fn sessions_report_running_examples_and_their_subscriptions() {
+    let example = Example::TelemetryPublisher;
+    let root = PluginRoot::new(example);
+    let mut runtime = root.runtime();
+    assert!(!runtime.sessions()[0].running);
+
+    runtime.start(example.plugin_id()).unwrap();
+    pump(
+        &mut runtime,
+        example.plugin_id(),
+        &mut RecordingHost::default(),
+        1,
+    );
+    let sessions = runtime.sessions();
+    assert!(sessions[0].running);
+    assert_eq!(sessions[0].api_version.as_deref(), Some(HOST_API_VERSION));
+    assert_eq!(sessions[0].subscriptions[0].category, "tick");
+
+    runtime.shutdown_all();
+}
+
+#[test]
+fn unallowlisted_commands_are_denied_and_audited() {
+    let example = Example::CommandProvider;
+    let root = PluginRoot::new(example);
+    let mut runtime = root.runtime();
+    let mut host = RecordingHost::default();
+    runtime.start(example.plugin_id()).unwrap();
+    pump(&mut runtime, example.plugin_id(), &mut host, 2);
+
+    answer(&mut runtime, "quit");
+    let handled = pump(&mut runtime, example.plugin_id(), &mut host, 1);
+    assert_eq!(handled, [(method::INVOKE_COMMAND.to_string(), true)]);
+    assert!(host.invoked.is_empty());
+
+    let audit = runtime.audit_log().recent_for(example.plugin_id(), 10);
+    let denied = audit.last().unwrap();
+    assert_eq!(denied.action, AuditAction::InvokeCommand);
+    assert_eq!(denied.decision, AuditDecision::Denied);
+    assert_eq!(denied.params["command"], "quit");
+    assert!(denied.reason.as_deref().unwrap().contains("quit"));
+
+    runtime.shutdown_all();
+}
+
+#[test]
+fn listed_commands_follow_the_allowlist_and_its_aliases() {
+    let example = Example::CommandProvider;
+    let root = PluginRoot::new(example);
+    // An alias in the allowlist lists the command it names
+    root.edit_manifest(
+        example,
+        &format!("commands = {}", json!(spud_plugin_examples::COMMANDS)),
+        r#"commands = ["tps","?"]"#,
+    );
+    let mut runtime = root.runtime();
+    let mut host = RecordingHost::default();
+    runtime.start(example.plugin_id()).unwrap();
+    pump(&mut runtime, example.plugin_id(), &mut host, 2);
+    assert_eq!(host.prompts[0].question, "run which command? (help, tps)");
+
+    runtime.shutdown_all();
+}
+
+#[test]
+fn dev_mode_pipes_stderr_and_reloads_the_manifest() {
+    let example = Example::TelemetryPublisher;
+    let root = PluginRoot::new(example);
+    let mut runtime = root.runtime();
+    let id = example.plugin_id();
+    assert!(runtime.set_dev_mode("examples.missing", true).is_err());
+    runtime.set_dev_mode(id, true).unwrap();
+    assert!(runtime.dev_mode(id));
+    assert_eq!(
+        runtime.plugin_files(id).unwrap(),
+        [root.manifest_path(example), binary(example)]
+    );
+
+    // A refused summary stops the example, which says why on stderr
+    let mut host = RecordingHost {
+        reject_payloads: true,
+        ..RecordingHost::default()
+    };
+    runtime.start(id).unwrap();
+    assert!(runtime.sessions()[0].dev);
+    pump(&mut runtime, id, &mut host, 2);
+    runtime
+        .broadcast_event(EventCategory::Tick, Some("tick"), json!({ "now": 1 }))
+        .unwrap();
+    assert_eq!(
+        pump(&mut runtime, id, &mut host, 2)[1],
+        (method::PUBLISH_EVENT.to_string(), true)
+    );
+    let deadline = Instant::now() + TIMEOUT;
+    let mut lines = Vec::new();
+    while lines.is_empty() && Instant::now() < deadline {
+        lines.extend(runtime.drain_stderr(id));
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(lines.len(), 1, "{lines:?}");
+    assert!(lines[0].starts_with(&format!("{id}: ")), "{}", lines[0]);
+    assert!(
+        lines[0].contains("summaries are not wanted"),
+        "{}",
+        lines[0]
+    );
+
+    let version = |version: &str| format!("version = {}", json!(version));
+    root.edit_manifest(example, &version(VERSION), &version("9.9.9"));
+    runtime.reload_manifest(id).unwrap();
+    assert_eq!(runtime.manifest(id).unwrap().version, "9.9.9");
+
+    root.edit_manifest(example, &json!(id).to_string(), r#""examples.renamed""#);
+    assert!(runtime.reload_manifest(id).is_err());
+    assert_eq!(runtime.manifest(id).unwrap().version, "9.9.9");
+
+    runtime.shutdown_all();
+}
@@ -0,0 +1,801 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use spud_remote::protocol::{
+    method, CommandInfo, ConsoleAnswerParams, ConsolePromptParams, ConsolePromptResult,
+    EventCategory, EventNotificationParams, GetSnapshotParams, GetTelemetryParams,
+    GetTelemetryResult, HandshakeParams, HandshakeResult, InvokeCommandParams, InvokeCommandResult,
+    JsonRpcError, ListCommandsParams, ListCommandsResult, LogEntryParams, LogSubscribeParams,
+    LogSubscriptionResult, LogUnsubscribeParams, ModuleFocusParams, ModuleUpdateParams,
+    ModuleUpdateResult, PublishEventParams, PublishEventResult, RegisterEventSchemaParams,
+    RegisterEventSchemaResult, RequestId, StateChangedParams, StateSnapshot, StateSubscribeParams,
+    StateSubscribeResult, StateUnsubscribeParams, StateUnsubscribeResult, SubscribeParams,
+    SubscriptionResult, JSONRPC_VERSION,
+};
+
+/// An event notification pushed by the host for a subscribed category.
+pub type HostEvent = EventNotificationParams;
+
+/// The line the user submitted in reply to a [`PluginClient::prompt`].
+pub type ConsoleAnswer = ConsoleAnswerParams;
+
+/// A host log entry streamed after [`PluginClient::subscribe_logs`].
+pub type HostLogEntry = LogEntryParams;
+
+/// The plugin's module was switched to or away from.
+pub type ModuleFocus = ModuleFocusParams;
+
+/// Host state changes sent after [`PluginClient::subscribe_state`].
+pub type StateUpdate = StateChangedParams;
+
+/// A notification pushed by the host.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostMessage {
+    Event(HostEvent),
+    Answer(ConsoleAnswer),
+    Log(HostLogEntry),
+    Focus(ModuleFocus),
+    State(StateUpdate),
+}
+
+/// Plugin-side client failures.
+///
+/// Host-side JSON-RPC errors are surfaced as [`ClientError::Rpc`] so plugin
+/// authors can match on stable error codes from
+/// [`spud_remote::protocol::error_code`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientError {
+    /// Reading from or writing to the host pipe failed.
+    Io(String),
+    /// The host sent something that does not follow the protocol.
+    Protocol(String),
+    /// The host answered a request with a JSON-RPC error.
+    Rpc(JsonRpcError),
+    /// The host closed the transport.
+    Closed,
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(msg) => write!(f, "{msg}"),
+            Self::Protocol(msg) => write!(f, "{msg}"),
+            Self::Rpc(error) => write!(f, "host error {}: {}", error.code, error.message),
+            Self::Closed => write!(f, "host closed the plugin transport"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// Identity a plugin presents to the host during `spud.handshake`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginInfo {
+    /// Plugin ID; must match the `id` in `plugin.toml`.
+    pub plugin_id: String,
+    /// Plugin version; must match the `version` in `plugin.toml`.
+    pub plugin_version: String,
+    /// Semver requirement for the host API (e.g. `"^1.0"`).
+    pub supported_api_versions: String,
+    /// Optional capability filter (method names and event categories).
+    pub requested_capabilities: Vec<String>,
+}
+
+impl PluginInfo {
+    /// Create plugin info accepting any `1.x` host API.
+    pub fn new(plugin_id: impl Into<String>, plugin_version: impl Into<String>) -> Self {
+        Self {
+            plugin_id: plugin_id.into(),
+            plugin_version: plugin_version.into(),
+            supported_api_versions: "^1.0".to_string(),
+            requested_capabilities: Vec::new(),
+        }
+    }
+}
+
+/// Typed JSON-RPC client for the plugin side of the host protocol.
+///
+/// Requests are written as newline-delimited JSON to the host and the client
+/// blocks until the matching response arrives. Event notifications received
+/// while waiting are queued and returned by [`next_event`](Self::next_event)
+/// or [`next_message`](Self::next_message).
+/// `spud.plugin.health` pings are answered whenever the client reads, so a
+/// plugin stuck outside the client stops answering them.
+pub struct PluginClient {
+    reader: Box<dyn BufRead + Send>,
+    writer: Box<dyn Write + Send>,
+    next_id: i64,
+    pending: VecDeque<HostMessage>,
+    handshake: Option<HandshakeResult>,
+}
+
+impl PluginClient {
+    /// Create a client over arbitrary reader/writer halves.
+    pub fn new(reader: impl BufRead + Send + 'static, writer: impl Write + Send + 'static) -> Self {
+        Self {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            next_id: 1,
+            pending: VecDeque::new(),
+            handshake: None,
+        }
+    }
+
+    /// Create a client over the process stdin/stdout (the plugin transport).
+    pub fn stdio() -> Self {
+        Self::new(BufReader::new(io::stdin()), io::stdout())
+    }
+
+    /// Perform `spud.handshake`. Must be the first call on a new client.
+    pub fn handshake(&mut self, info: &PluginInfo) -> Result<HandshakeResult, ClientError> {
+        let params = HandshakeParams {
+            plugin_id: info.plugin_id.clone(),
+            plugin_version: info.plugin_version.clone(),
+            supported_api_versions: info.supported_api_versions.clone(),
+            requested_capabilities: info.requested_capabilities.clone(),
+        };
+        let result: HandshakeResult = self.call(method::HANDSHAKE, &params)?;
+        self.handshake = Some(result.clone());
+        Ok(result)
+    }
+
+    /// Return the negotiated handshake result, if the handshake completed.
+    pub fn handshake_result(&self) -> Option<&HandshakeResult> {
+        self.handshake.as_ref()
+    }
+
+    /// Fetch a read-only host state snapshot.
+    pub fn get_snapshot(&mut self) -> Result<StateSnapshot, ClientError> {
+        self.call(method::GET_SNAPSHOT, &GetSnapshotParams {})
+    }
+
+    /// Fetch one page of filtered telemetry (host API 1.1.0+).
+    ///
+    /// Pass the returned `next_cursor` back in `params.cursor` to continue.
+    pub fn get_telemetry(
+        &mut self,
+        params: &GetTelemetryParams,
+    ) -> Result<GetTelemetryResult, ClientError> {
+        self.call(method::GET_TELEMETRY, params)
+    }
+
+    /// Subscribe to event categories, returning the full subscription set.
+    pub fn subscribe(
+        &mut self,
+        categories: &[EventCategory],
+    ) -> Result<Vec<EventCategory>, ClientError> {
+        self.subscribe_with(&SubscribeParams::new(categories.to_vec()))
+    }
+
+    /// Subscribe with tag globs and/or a delivery rate cap.
+    pub fn subscribe_with(
+        &mut self,
+        params: &SubscribeParams,
+    ) -> Result<Vec<EventCategory>, ClientError> {
+        let result: SubscriptionResult = self.call(method::SUBSCRIBE, params)?;
+        Ok(result.subscribed)
+    }
+
+    /// Unsubscribe from event categories, returning the remaining set.
+    pub fn unsubscribe(
+        &mut self,
+        categories: &[EventCategory],
+    ) -> Result<Vec<EventCategory>, ClientError> {
+        let params = SubscribeParams::new(categories.to_vec());
+        let result: SubscriptionResult = self.call(method::UNSUBSCRIBE, &params)?;
+        Ok(result.subscribed)
+    }
+
+    /// Ask the host to run a console command (must be allowlisted).
+    pub fn invoke_command(
+        &mut self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<InvokeCommandResult, ClientError> {
+        let params = InvokeCommandParams {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        };
+        self.call(method::INVOKE_COMMAND, &params)
+    }
+
+    /// List the console commands this plugin may invoke, sorted by name
+    /// (host API 1.7.0+).
+    pub fn list_commands(&mut self) -> Result<Vec<CommandInfo>, ClientError> {
+        let result: ListCommandsResult =
+            self.call(method::LIST_COMMANDS, &ListCommandsParams {})?;
+        Ok(result.commands)
+    }
+
+    /// Publish a custom event onto the host bus (tag must be allowlisted).
+    pub fn publish_event(&mut self, tag: &str, payload: &str) -> Result<bool, ClientError> {
+        let params = PublishEventParams {
+            tag: tag.to_string(),
+            payload: payload.to_string(),
+        };
+        let result: PublishEventResult = self.call(method::PUBLISH_EVENT, &params)?;
+        Ok(result.accepted)
+    }
+
+    /// Register a JSON Schema for a custom event tag (host API 1.2.0+).
+    ///
+    /// Later publishes under `tag` whose payload does not match are
+    /// rejected with `INVALID_EVENT_PAYLOAD`.
+    pub fn register_event_schema(
+        &mut self,
+        tag: &str,
+        schema: &Value,
+    ) -> Result<bool, ClientError> {
+        let params = RegisterEventSchemaParams {
+            tag: tag.to_string(),
+            schema: schema.clone(),
+        };
+        let result: RegisterEventSchemaResult =
+            self.call(method::REGISTER_EVENT_SCHEMA, &params)?;
+        Ok(result.registered)
+    }
+
+    /// Ask the user a question in the console input line (host API
+    /// 1.3.0+). Their reply arrives later as a [`HostMessage::Answer`]
+    /// carrying `callback_token`.
+    ///
+    /// Returns `false` if another prompt already owns the input line.
+    pub fn prompt(&mut self, question: &str, callback_token: &str) -> Result<bool, ClientError> {
+        let params = ConsolePromptParams {
+            question: question.to_string(),
+            callback_token: callback_token.to_string(),
+        };
+        let result: ConsolePromptResult = self.call(method::CONSOLE_PROMPT, &params)?;
+        Ok(result.accepted)
+    }
+
+    /// Stream host log entries matching `params` (host API 1.4.0+). They
+    /// arrive as [`HostMessage::Log`]; the manifest must list `"logs"` in
+    /// `permissions.subscriptions`.
+    ///
+    /// Returns the entries-per-second rate the host applies.
+    pub fn subscribe_logs(&mut self, params: &LogSubscribeParams) -> Result<u32, ClientError> {
+        let result: LogSubscriptionResult = self.call(method::LOGS_SUBSCRIBE, params)?;
+        Ok(result.max_per_sec.unwrap_or_default())
+    }
+
+    /// Stop the log stream started by [`subscribe_logs`](Self::subscribe_logs).
+    pub fn unsubscribe_logs(&mut self) -> Result<(), ClientError> {
+        let _: LogSubscriptionResult =
+            self.call(method::LOGS_UNSUBSCRIBE, &LogUnsubscribeParams {})?;
+        Ok(())
+    }
+
+    /// Replace the hero and/or HUD lines of the module declared in the
+    /// manifest's `[module]` section (host API 1.5.0+). A field left as
+    /// `None` keeps its lines.
+    ///
+    /// Returns whether the module is on screen.
+    pub fn update_module(&mut self, params: &ModuleUpdateParams) -> Result<bool, ClientError> {
+        let result: ModuleUpdateResult = self.call(method::MODULE_UPDATE, params)?;
+        Ok(result.active)
+    }
+
+    /// Fetch a state snapshot and have changes to it sent as
+    /// [`HostMessage::State`] (host API 1.6.0+). Calling it again replaces
+    /// the telemetry filter and returns a fresh snapshot; see
+    /// [`HostState`](crate::HostState) for keeping a copy current.
+    pub fn subscribe_state(
+        &mut self,
+        params: &StateSubscribeParams,
+    ) -> Result<StateSubscribeResult, ClientError> {
+        self.call(method::STATE_SUBSCRIBE, params)
+    }
+
+    /// Stop the changes started by [`subscribe_state`](Self::subscribe_state).
+    /// Returns whether there was a subscription.
+    pub fn unsubscribe_state(&mut self) -> Result<bool, ClientError> {
+        let result: StateUnsubscribeResult =
+            self.call(method::STATE_UNSUBSCRIBE, &StateUnsubscribeParams {})?;
+        Ok(result.unsubscribed)
+    }
+
+    /// Block until the next host event notification arrives. Prompt
+    /// answers and log entries received meanwhile stay queued for
+    /// [`next_message`](Self::next_message).
+    ///
+    /// Returns `Ok(None)` once the host closes the transport.
+    pub fn next_event(&mut self) -> Result<Option<HostEvent>, ClientError> {
+        let queued = self
+            .pending
+            .iter()
+            .position(|message| matches!(message, HostMessage::Event(_)));
+        if let Some(HostMessage::Event(event)) = queued.and_then(|idx| self.pending.remove(idx)) {
+            return Ok(Some(event));
+        }
+
+        loop {
+            match self.next_notification()? {
+                Some(HostMessage::Event(event)) => return Ok(Some(event)),
+                Some(other) => self.pending.push_back(other),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Block until the next host notification of any kind arrives.
+    ///
+    /// Returns `Ok(None)` once the host closes the transport.
+    pub fn next_message(&mut self) -> Result<Option<HostMessage>, ClientError> {
+        match self.pending.pop_front() {
+            Some(message) => Ok(Some(message)),
+            None => self.next_notification(),
+        }
+    }
+
+    fn next_notification(&mut self) -> Result<Option<HostMessage>, ClientError> {
+        loop {
+            match self.read_message() {
+                Ok(Incoming::Message(message)) => return Ok(Some(message)),
+                Ok(Incoming::Response { id, .. }) => {
+                    return Err(ClientError::Protocol(format!(
+                        "unexpected response {id:?} with no request in flight"
+                    )));
+                }
+                Ok(Incoming::Ignored) => continue,
+                Err(ClientError::Closed) => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Issue a raw JSON-RPC request and decode the typed result.
+    ///
+    /// The typed wrappers above cover the v1 host API; this is the escape
+    /// hatch for methods added in later protocol revisions.
+    pub fn call<P: Serialize, T: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: &P,
+    ) -> Result<T, ClientError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let params = serde_json::to_value(params).map_err(|err| {
+            ClientError::Protocol(format!("failed to encode params for {method}: {err}"))
+        })?;
+        self.send(&OutgoingRequest {
+            jsonrpc: JSONRPC_VERSION,
+            id: RequestId::Number(id),
+            method,
+            params,
+        })?;
+
+        loop {
+            match self.read_message()? {
+                Incoming::Message(message) => self.pending.push_back(message),
+                Incoming::Ignored => {}
+                Incoming::Response {
+                    id: response_id,
+                    result,
+                    error,
+                } => {
+                    if response_id != RequestId::Number(id) {
+                        return Err(ClientError::Protocol(format!(
+                            "response id mismatch for {method}: expected {id}, got {response_id:?}"
+                        )));
+                    }
+                    if let Some(error) = error {
+                        return Err(ClientError::Rpc(error));
+                    }
+                    let result = result.unwrap_or(Value::Null);
+                    return serde_json::from_value(result).map_err(|err| {
+                        ClientError::Protocol(format!("invalid result for {method}: {err}"))
+                    });
+                }
+            }
+        }
+    }
+
+    fn send<T: Serialize>(&mut self, payload: &T) -> Result<(), ClientError> {
+        let encoded = serde_json::to_string(payload)
+            .map_err(|err| ClientError::Protocol(format!("failed to encode request: {err}")))?;
+        self.writer
+            .write_all(encoded.as_bytes())
+            .and_then(|_| self.writer.write_all(b"\n"))
+            .and_then(|_| self.writer.flush())
+            .map_err(io_error)
+    }
+
+    fn read_message(&mut self) -> Result<Incoming, ClientError> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let bytes_read = self.reader.read_line(&mut line).map_err(io_error)?;
+            if bytes_read == 0 {
+                return Err(ClientError::Closed);
+            }
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let envelope: IncomingEnvelope = serde_json::from_str(line.trim()).map_err(|err| {
+            ClientError::Protocol(format!("invalid JSON-RPC message from host: {err}"))
+        })?;
+
+        if let Some(method) = envelope.method {
+            if method == method::HEALTH {
+                if let Some(id) = envelope.id {
+                    self.send(&OutgoingResponse {
+                        jsonrpc: JSONRPC_VERSION,
+                        id,
+                        result: Value::Object(Default::default()),
+                    })?;
+                }
+                return Ok(Incoming::Ignored);
+            }
+            let params = envelope.params.unwrap_or(Value::Null);
+            let message = match method.as_str() {
+                method::EVENT_NOTIFICATION => {
+                    HostMessage::Event(serde_json::from_value(params).map_err(|err| {
+                        ClientError::Protocol(format!("invalid event params: {err}"))
+                    })?)
+                }
+                method::CONSOLE_ANSWER => {
+                    HostMessage::Answer(serde_json::from_value(params).map_err(|err| {
+                        ClientError::Protocol(format!("invalid answer params: {err}"))
+                    })?)
+                }
+                method::LOG_ENTRY => {
+                    HostMessage::Log(serde_json::from_value(params).map_err(|err| {
+                        ClientError::Protocol(format!("invalid log entry params: {err}"))
+                    })?)
+                }
+                method::MODULE_FOCUS => {
+                    HostMessage::Focus(serde_json::from_value(params).map_err(|err| {
+                        ClientError::Protocol(format!("invalid module focus params: {err}"))
+                    })?)
+                }
+                method::STATE_CHANGED => {
+                    HostMessage::State(serde_json::from_value(params).map_err(|err| {
+                        ClientError::Protocol(format!("invalid state change params: {err}"))
+                    })?)
+                }
+                _ => return Ok(Incoming::Ignored),
+            };
+            return Ok(Incoming::Message(message));
+        }
+
+        Ok(Incoming::Response {
+            id: envelope.id.unwrap_or(RequestId::Null),
+            result: envelope.result,
+            error: envelope.error,
+        })
+    }
+}
+
+fn io_error(err: io::Error) -> ClientError {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        ClientError::Closed
+    } else {
+        ClientError::Io(format!("plugin transport error: {err}"))
+    }
+}
+
+#[derive(Serialize)]
+struct OutgoingRequest<'a> {
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct OutgoingResponse<'a> {
+    jsonrpc: &'a str,
+    id: RequestId,
+    result: Value,
+}
+
+#[derive(Deserialize)]
+struct IncomingEnvelope {
+    #[serde(default)]
+    id: Option<RequestId>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Option<Value>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+enum Incoming {
+    Response {
+        id: RequestId,
+        result: Option<Value>,
+        error: Option<JsonRpcError>,
+    },
+    Message(HostMessage),
+    Ignored,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+    use spud_remote::protocol::{error_code, HostCapabilities, LogSeverity};
+
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedWriter {
+        fn requests(&self) -> Vec<Value> {
+            let raw = String::from_utf8(self.0.lock().unwrap().clone()).unwrap();
+            raw.lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    fn client_with_host_lines(lines: &[Value]) -> (PluginClient, SharedWriter) {
+        let script = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        let writer = SharedWriter::default();
+        let client = PluginClient::new(Cursor::new(script.into_bytes()), writer.clone());
+        (client, writer)
+    }
+
+    fn handshake_response(id: i64) -> Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": HandshakeResult {
+                selected_api_version: "1.0.0".to_string(),
+                host_capabilities: HostCapabilities {
+                    methods: vec![method::GET_SNAPSHOT.to_string()],
+                    event_categories: vec![EventCategory::Tick],
+                },
+            }
+        })
+    }
+
+    #[test]
+    fn handshake_sends_params_and_records_result() {
+        let (mut client, writer) = client_with_host_lines(&[handshake_response(1)]);
+        let result = client
+            .handshake(&PluginInfo::new("spud.sdk", "0.1.0"))
+            .unwrap();
+
+        assert_eq!(result.selected_api_version, "1.0.0");
+        assert!(client.handshake_result().is_some());
+
+        let requests = writer.requests();
+        assert_eq!(requests[0]["method"], method::HANDSHAKE);
+        assert_eq!(requests[0]["params"]["plugin_id"], "spud.sdk");
+        assert_eq!(requests[0]["id"], 1);
+    }
+
+    #[test]
+    fn events_received_while_waiting_are_queued() {
+        let (mut client, _writer) = client_with_host_lines(&[
+            json!({
+                "jsonrpc": "2.0",
+                "method": method::EVENT_NOTIFICATION,
+                "params": {"category": "tick", "payload": {"uptime_seconds": 1.0}}
+            }),
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"accepted": true}}),
+        ]);
+
+        assert!(client.publish_event("plugin.metrics", "{}").unwrap());
+
+        let event = client.next_event().unwrap().unwrap();
+        assert_eq!(event.category, EventCategory::Tick);
+        assert_eq!(event.payload["uptime_seconds"], 1.0);
+        assert!(client.next_event().unwrap().is_none());
+    }
+
+    #[test]
+    fn health_pings_are_answered_while_waiting() {
+        let (mut client, writer) = client_with_host_lines(&[
+            json!({
+                "jsonrpc": "2.0",
+                "id": "spud.health.1",
+                "method": method::HEALTH,
+                "params": {}
+            }),
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"accepted": true}}),
+        ]);
+
+        assert!(client.publish_event("plugin.metrics", "{}").unwrap());
+
+        let sent = writer.requests();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(
+            sent[1],
+            json!({"jsonrpc": "2.0", "id": "spud.health.1", "result": {}})
+        );
+    }
+
+    #[test]
+    fn register_event_schema_sends_tag_and_schema() {
+        let (mut client, writer) = client_with_host_lines(&[json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {"registered": true}
+        })]);
+
+        let schema = json!({"type": "object", "required": ["n"]});
+        assert!(client
+            .register_event_schema("plugin.metrics", &schema)
+            .unwrap());
+
+        let requests = writer.requests();
+        assert_eq!(requests[0]["method"], method::REGISTER_EVENT_SCHEMA);
+        assert_eq!(requests[0]["params"]["tag"], "plugin.metrics");
+        assert_eq!(requests[0]["params"]["schema"], schema);
+    }
+
+    #[test]
+    fn list_commands_returns_the_listed_commands() {
+        let (mut client, writer) = client_with_host_lines(&[json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {"commands": [
+                {"name": "help", "aliases": ["?"], "usage": "help [command]", "description": "Show help"}
+            ]}
+        })]);
+
+        let commands = client.list_commands().unwrap();
+        assert_eq!(commands[0].name, "help");
+        assert_eq!(commands[0].aliases, ["?"]);
+        assert_eq!(commands[0].usage, "help [command]");
+
+        let requests = writer.requests();
+        assert_eq!(requests[0]["method"], method::LIST_COMMANDS);
+        assert_eq!(requests[0]["params"], json!({}));
+    }
+
+    #[test]
+    fn answers_wait_behind_events_for_next_message() {
+        let (mut client, writer) = client_with_host_lines(&[
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"accepted": true}}),
+            json!({
+                "jsonrpc": "2.0",
+                "method": method::CONSOLE_ANSWER,
+                "params": {"callback_token": "name", "answer": "Ada"}
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "method": method::EVENT_NOTIFICATION,
+                "params": {"category": "tick", "payload": {}}
+            }),
+        ]);
+
+        assert!(client.prompt("Name?", "name").unwrap());
+        let requests = writer.requests();
+        assert_eq!(requests[0]["method"], method::CONSOLE_PROMPT);
+        assert_eq!(requests[0]["params"]["question"], "Name?");
+
+        let event = client.next_event().unwrap().unwrap();
+        assert_eq!(event.category, EventCategory::Tick);
+        assert_eq!(
+            client.next_message().unwrap(),
+            Some(HostMessage::Answer(ConsoleAnswer {
+                callback_token: "name".to_string(),
+                answer: "Ada".to_string(),
+                cancelled: false,
+            }))
+        );
+        assert_eq!(client.next_message().unwrap(), None);
+    }
+
+    #[test]
+    fn log_entries_arrive_as_messages() {
+        let (mut client, writer) = client_with_host_lines(&[
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"subscribed": true, "max_per_sec": 20}}),
+            json!({
+                "jsonrpc": "2.0",
+                "method": method::LOG_ENTRY,
+                "params": {"level": "warn", "target": "spud_core", "message": "slow", "dropped": 3}
+            }),
+        ]);
+
+        let params = LogSubscribeParams {
+            min_level: LogSeverity::Warn,
+            max_per_sec: Some(20),
+            ..LogSubscribeParams::default()
+        };
+        assert_eq!(client.subscribe_logs(&params).unwrap(), 20);
+        let requests = writer.requests();
+        assert_eq!(requests[0]["method"], method::LOGS_SUBSCRIBE);
+        assert_eq!(requests[0]["params"]["min_level"], "warn");
+
+        assert_eq!(
+            client.next_message().unwrap(),
+            Some(HostMessage::Log(HostLogEntry {
+                level: LogSeverity::Warn,
+                target: "spud_core".to_string(),
+                message: "slow".to_string(),
+                dropped: 3,
+            }))
+        );
+    }
+
+    #[test]
+    fn module_updates_report_focus() {
+        let (mut client, writer) = client_with_host_lines(&[
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"active": true}}),
+            json!({
+                "jsonrpc": "2.0",
+                "method": method::MODULE_FOCUS,
+                "params": {"module_id": "weather", "active": false}
+            }),
+        ]);
+
+        let params = ModuleUpdateParams {
+            hero: Some(vec!["sunny".to_string()]),
+            hud: None,
+        };
+        assert!(client.update_module(&params).unwrap());
+        let requests = writer.requests();
+        assert_eq!(requests[0]["method"], method::MODULE_UPDATE);
+        assert_eq!(requests[0]["params"]["hero"], json!(["sunny"]));
+
+        assert_eq!(
+            client.next_message().unwrap(),
+            Some(HostMessage::Focus(ModuleFocus {
+                module_id: "weather".to_string(),
+                active: false,
+            }))
+        );
+    }
+
+    #[test]
+    fn rpc_errors_are_surfaced_with_codes() {
+        let (mut client, _writer) = client_with_host_lines(&[json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {"code": error_code::UNAUTHORIZED, "message": "command is not allowlisted: quit"}
+        })]);
+
+        match client.invoke_command("quit", &[]) {
+            Err(ClientError::Rpc(error)) => assert_eq!(error.code, error_code::UNAUTHORIZED),
+            other => panic!("expected Rpc error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn mismatched_response_id_is_a_protocol_error() {
+        let (mut client, _writer) =
+            client_with_host_lines(&[json!({"jsonrpc": "2.0", "id": 99, "result": {}})]);
+
+        let err = client.get_snapshot().unwrap_err();
+        assert!(matches!(err, ClientError::Protocol(_)));
+    }
+
+    #[test]
+    fn closed_transport_during_call_reports_closed() {
+        let (mut client, _writer) = client_with_host_lines(&[]);
+        let err = client.subscribe(&[EventCategory::Tick]).unwrap_err();
+        assert_eq!(err, ClientError::Closed);
+    }
+}
@@ -0,0 +1,40 @@
+//! Plugin-side SDK for the SPUD host protocol.
+//!
+//! Implements the plugin half of the JSON-RPC contract defined in
+//! [`spud_remote::protocol`]: handshake negotiation, typed host method
+//! wrappers, event subscription, log streaming, state change tracking,
+//! console prompt and module focus callbacks, and a blocking run loop over
+//! stdio.
+//!
+//! # Quick start
+//!
+//! ```no_run
+//! use spud_plugin_sdk::{EventCategory, Plugin};
+//!
+//! Plugin::new("example.ticker", "0.1.0")
+//!     .on(EventCategory::Tick, |client, event| {
+//!         let payload = event.payload.to_string();
+//!         client.publish_event("example.tick", &payload)?;
+//!         Ok(())
+//!     })
+//!     .run_stdio()
+//!     .unwrap();
+//! ```
+
+mod client;
+mod plugin;
+mod state;
+
+pub use client::{
+    ClientError, ConsoleAnswer, HostEvent, HostLogEntry, HostMessage, ModuleFocus, PluginClient,
+    PluginInfo, StateUpdate,
+};
+pub use plugin::{
+    AnswerCallback, EventCallback, FocusCallback, LogCallback, Plugin, ReadyCallback, StateCallback,
+};
+pub use spud_remote::protocol::{
+    error_code, CommandInfo, EventCategory, GetTelemetryParams, GetTelemetryResult,
+    HandshakeResult, InvokeCommandResult, LogSeverity, LogSubscribeParams, ModuleUpdateParams,
+    StateChange, StateSnapshot, StateSubscribeParams, SubscribeParams, TelemetrySample,
+};
+pub use state::HostState;
@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+
+use spud_remote::protocol::{
+    EventCategory, HandshakeResult, LogSubscribeParams, StateSnapshot, StateSubscribeParams,
+};
+
+use crate::client::{
+    ClientError, ConsoleAnswer, HostEvent, HostLogEntry, HostMessage, ModuleFocus, PluginClient,
+    PluginInfo,
+};
+use crate::state::HostState;
+
+/// Callback invoked for each host event in a subscribed category.
+///
+/// The client is passed back in so handlers can call host methods (e.g.
+/// publish an event in response to a tick).
+pub type EventCallback = Box<dyn FnMut(&mut PluginClient, &HostEvent) -> Result<(), ClientError>>;
+
+/// Callback invoked with each reply to a [`PluginClient::prompt`].
+pub type AnswerCallback =
+    Box<dyn FnMut(&mut PluginClient, &ConsoleAnswer) -> Result<(), ClientError>>;
+
+/// Callback invoked with each streamed host log entry.
+pub type LogCallback = Box<dyn FnMut(&mut PluginClient, &HostLogEntry) -> Result<(), ClientError>>;
+
+/// Callback invoked when the plugin's module is switched to or away from.
+pub type FocusCallback = Box<dyn FnMut(&mut PluginClient, &ModuleFocus) -> Result<(), ClientError>>;
+
+/// Callback invoked with the host state after subscribing and after each
+/// change.
+pub type StateCallback =
+    Box<dyn FnMut(&mut PluginClient, &StateSnapshot) -> Result<(), ClientError>>;
+
+/// Callback invoked once after the handshake and subscriptions succeed.
+pub type ReadyCallback =
+    Box<dyn FnOnce(&mut PluginClient, &HandshakeResult) -> Result<(), ClientError>>;
+
+/// High-level plugin definition: identity plus per-category event callbacks.
+///
+/// [`run`](Self::run) performs the handshake, subscribes to every category
+/// that has a callback, then dispatches host events until the host closes
+/// the transport.
+pub struct Plugin {
+    info: PluginInfo,
+    handlers: BTreeMap<EventCategory, Vec<EventCallback>>,
+    on_ready: Option<ReadyCallback>,
+    on_answer: Option<AnswerCallback>,
+    on_log: Option<(LogSubscribeParams, LogCallback)>,
+    on_focus: Option<FocusCallback>,
+    on_state: Option<(StateSubscribeParams, StateCallback)>,
+}
+
+impl Plugin {
+    /// Create a plugin with the given manifest ID and version.
+    pub fn new(plugin_id: impl Into<String>, plugin_version: impl Into<String>) -> Self {
+        Self {
+            info: PluginInfo::new(plugin_id, plugin_version),
+            handlers: BTreeMap::new(),
+            on_ready: None,
+            on_answer: None,
+            on_log: None,
+            on_focus: None,
+            on_state: None,
+        }
+    }
+
+    /// Override the accepted host API semver requirement (default `^1.0`).
+    pub fn supported_api_versions(mut self, requirement: impl Into<String>) -> Self {
+        self.info.supported_api_versions = requirement.into();
+        self
+    }
+
+    /// Register a callback for events in `category`.
+    pub fn on(
+        mut self,
+        category: EventCategory,
+        callback: impl FnMut(&mut PluginClient, &HostEvent) -> Result<(), ClientError> + 'static,
+    ) -> Self {
+        self.handlers
+            .entry(category)
+            .or_default()
+            .push(Box::new(callback));
+        self
+    }
+
+    /// Register a callback run once the plugin is connected and subscribed.
+    pub fn on_ready(
+        mut self,
+        callback: impl FnOnce(&mut PluginClient, &HandshakeResult) -> Result<(), ClientError> + 'static,
+    ) -> Self {
+        self.on_ready = Some(Box::new(callback));
+        self
+    }
+
+    /// Register the callback that receives answers to the plugin's console
+    /// prompts, and `cancelled` ones when the user dismisses a question.
+    /// Answers are dropped without one.
+    pub fn on_answer(
+        mut self,
+        callback: impl FnMut(&mut PluginClient, &ConsoleAnswer) -> Result<(), ClientError> + 'static,
+    ) -> Self {
+        self.on_answer = Some(Box::new(callback));
+        self
+    }
+
+    /// Stream host log entries matching `params` to `callback`, subscribing
+    /// after the handshake (host API 1.4.0+, `"logs"` in the manifest's
+    /// `permissions.subscriptions`).
+    pub fn on_log(
+        mut self,
+        params: LogSubscribeParams,
+        callback: impl FnMut(&mut PluginClient, &HostLogEntry) -> Result<(), ClientError> + 'static,
+    ) -> Self {
+        self.on_log = Some((params, Box::new(callback)));
+        self
+    }
+
+    /// Register the callback told when the module from the manifest's
+    /// `[module]` section is switched to or away from (host API 1.5.0+).
+    pub fn on_focus(
+        mut self,
+        callback: impl FnMut(&mut PluginClient, &ModuleFocus) -> Result<(), ClientError> + 'static,
+    ) -> Self {
+        self.on_focus = Some(Box::new(callback));
+        self
+    }
+
+    /// Keep a copy of host state current and pass it to `callback` once
+    /// subscribed and after every change (host API 1.6.0+). Telemetry is
+    /// filtered by `params`. Missed changes are recovered by subscribing
+    /// again.
+    pub fn on_state(
+        mut self,
+        params: StateSubscribeParams,
+        callback: impl FnMut(&mut PluginClient, &StateSnapshot) -> Result<(), ClientError> + 'static,
+    ) -> Self {
+        self.on_state = Some((params, Box::new(callback)));
+        self
+    }
+
+    /// Return the categories this plugin will subscribe to.
+    pub fn categories(&self) -> Vec<EventCategory> {
+        self.handlers.keys().copied().collect()
+    }
+
+    /// Run the plugin over process stdin/stdout.
+    pub fn run_stdio(self) -> Result<(), ClientError> {
+        self.run(PluginClient::stdio())
+    }
+
+    /// Run the plugin over the given client until the host disconnects.
+    pub fn run(mut self, mut client: PluginClient) -> Result<(), ClientError> {
+        let handshake = client.handshake(&self.info)?;
+
+        let categories = self.categories();
+        if !categories.is_empty() {
+            client.subscribe(&categories)?;
+        }
+        if let Some((params, _)) = &self.on_log {
+            client.subscribe_logs(params)?;
+        }
+        let mut state = match &self.on_state {
+            Some((params, _)) => Some(HostState::new(client.subscribe_state(params)?)),
+            None => None,
+        };
+
+        if let Some(on_ready) = self.on_ready.take() {
+            on_ready(&mut client, &handshake)?;
+        }
+        if let (Some(state), Some((_, on_state))) = (&state, self.on_state.as_mut()) {
+            on_state(&mut client, state.snapshot())?;
+        }
+
+        while let Some(message) = client.next_message()? {
+            match message {
+                HostMessage::Event(event) => {
+                    if let Some(handlers) = self.handlers.get_mut(&event.category) {
+                        for handler in handlers {
+                            handler(&mut client, &event)?;
+                        }
+                    }
+                }
+                HostMessage::Answer(answer) => {
+                    if let Some(on_answer) = self.on_answer.as_mut() {
+                        on_answer(&mut client, &answer)?;
+                    }
+                }
+                HostMessage::Log(entry) => {
+                    if let Some((_, on_log)) = self.on_log.as_mut() {
+                        on_log(&mut client, &entry)?;
+                    }
+                }
+                HostMessage::Focus(focus) => {
+                    if let Some(on_focus) = self.on_focus.as_mut() {
+                        on_focus(&mut client, &focus)?;
+                    }
+                }
+                HostMessage::State(update) => {
+                    let (Some(current), Some((params, on_state))) =
+                        (state.as_mut(), self.on_state.as_mut())
+                    else {
+                        continue;
+                    };
+                    if !current.apply(&update) {
+                        *current = HostState::new(client.subscribe_state(params)?);
+                    }
+                    on_state(&mut client, current.snapshot())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::io::{self, Cursor, Write};
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{json, Value};
+    use spud_remote::protocol::method;
+
+    #[derive(Clone, Default)]
+    struct SharedWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn run_handshakes_subscribes_and_dispatches_events() {
+        let host_lines = [
+            json!({"jsonrpc": "2.0", "id": 1, "result": {
+                "selected_api_version": "1.0.0",
+                "host_capabilities": {"methods": [], "event_categories": ["tick", "custom"]}
+            }}),
+            json!({"jsonrpc": "2.0", "id": 2, "result": {"subscribed": ["tick", "custom"]}}),
+            json!({"jsonrpc": "2.0", "id": 3, "result": {"subscribed": true, "max_per_sec": 50}}),
+            json!({"jsonrpc": "2.0", "method": method::EVENT_NOTIFICATION,
+                "params": {"category": "tick", "payload": {}}}),
+            json!({"jsonrpc": "2.0", "method": method::EVENT_NOTIFICATION,
+                "params": {"category": "custom", "tag": "demo", "payload": "hi"}}),
+            json!({"jsonrpc": "2.0", "method": method::CONSOLE_ANSWER,
+                "params": {"callback_token": "name", "answer": "Ada"}}),
+            json!({"jsonrpc": "2.0", "method": method::LOG_ENTRY,
+                "params": {"level": "info", "target": "spud_core", "message": "hello"}}),
+            json!({"jsonrpc": "2.0", "method": method::MODULE_FOCUS,
+                "params": {"module_id": "sdk", "active": true}}),
+        ];
+        let script: String = host_lines.iter().map(|line| format!("{line}\n")).collect();
+        let writer = SharedWriter::default();
+        let client = PluginClient::new(Cursor::new(script.into_bytes()), writer.clone());
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let ticks = seen.clone();
+        let customs = seen.clone();
+        let ready = seen.clone();
+        let answers = seen.clone();
+        let logs = seen.clone();
+        let focus = seen.clone();
+
+        Plugin::new("spud.sdk", "0.1.0")
+            .on(EventCategory::Tick, move |_, _| {
+                ticks.borrow_mut().push("tick".to_string());
+                Ok(())
+            })
+            .on(EventCategory::Custom, move |_, event| {
+                customs
+                    .borrow_mut()
+                    .push(format!("custom:{}", event.tag.as_deref().unwrap_or("")));
+                Ok(())
+            })
+            .on_ready(move |_, handshake| {
+                ready
+                    .borrow_mut()
+                    .push(format!("ready:{}", handshake.selected_api_version));
+                Ok(())
+            })
+            .on_answer(move |_, answer| {
+                answers
+                    .borrow_mut()
+                    .push(format!("{}={}", answer.callback_token, answer.answer));
+                Ok(())
+            })
+            .on_log(LogSubscribeParams::default(), move |_, entry| {
+                logs.borrow_mut().push(format!("log:{}", entry.message));
+                Ok(())
+            })
+            .on_focus(move |_, event| {
+                focus
+                    .borrow_mut()
+                    .push(format!("focus:{}={}", event.module_id, event.active));
+                Ok(())
+            })
+            .run(client)
+            .unwrap();
+
+        assert_eq!(
+            seen.borrow().as_slice(),
+            &[
+                "ready:1.0.0",
+                "tick",
+                "custom:demo",
+                "name=Ada",
+                "log:hello",
+                "focus:sdk=true"
+            ]
+        );
+
+        let raw = String::from_utf8(writer.0.lock().unwrap().clone()).unwrap();
+        let requests: Vec<Value> = raw
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(requests[1]["method"], method::SUBSCRIBE);
+        assert_eq!(
+            requests[1]["params"]["categories"],
+            json!(["tick", "custom"])
+        );
+        assert_eq!(requests[2]["method"], method::LOGS_SUBSCRIBE);
+    }
+}
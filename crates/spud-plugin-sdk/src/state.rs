@@ -0,0 +1,125 @@
+use spud_remote::protocol::{StateChange, StateSnapshot, StateSubscribeResult};
+
+use crate::client::StateUpdate;
+
+/// A host state snapshot kept current with `spud.state.changed` updates.
+///
+/// Start one from [`PluginClient::subscribe_state`](crate::PluginClient::subscribe_state)
+/// and [`apply`](Self::apply) each [`StateUpdate`]. When `apply` reports a
+/// gap, subscribe again and start over from the fresh result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostState {
+    seq: u64,
+    snapshot: StateSnapshot,
+}
+
+impl HostState {
+    /// Start from the result of `spud.state.subscribe`.
+    pub fn new(subscribed: StateSubscribeResult) -> Self {
+        Self {
+            seq: subscribed.seq,
+            snapshot: subscribed.snapshot,
+        }
+    }
+
+    /// The host state as of [`seq`](Self::seq).
+    pub fn snapshot(&self) -> &StateSnapshot {
+        &self.snapshot
+    }
+
+    /// Sequence number of the last update applied.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Apply `update` if it is the next one. Updates the snapshot already
+    /// covers are ignored.
+    ///
+    /// Returns `false` when updates were missed and the snapshot can no
+    /// longer be trusted.
+    pub fn apply(&mut self, update: &StateUpdate) -> bool {
+        if update.seq <= self.seq {
+            return true;
+        }
+        if update.seq != self.seq + 1 {
+            return false;
+        }
+        self.seq = update.seq;
+        for change in &update.changes {
+            match change {
+                StateChange::ActiveModule { module } => {
+                    self.snapshot.active_module = module.clone();
+                }
+                StateChange::StatusLine { status_line } => {
+                    self.snapshot.status_line = status_line.clone();
+                }
+                StateChange::Telemetry(datum) => {
+                    match self
+                        .snapshot
+                        .telemetry
+                        .iter_mut()
+                        .find(|old| old.source == datum.source && old.key == datum.key)
+                    {
+                        Some(old) => old.value = datum.value.clone(),
+                        None => self.snapshot.telemetry.push(datum.clone()),
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use spud_remote::protocol::TelemetryDatum;
+
+    fn datum(key: &str, value: i64) -> TelemetryDatum {
+        TelemetryDatum {
+            source: "stats".to_string(),
+            key: key.to_string(),
+            value: json!(value),
+        }
+    }
+
+    #[test]
+    fn applies_updates_in_order_and_reports_gaps() {
+        let mut state = HostState::new(StateSubscribeResult {
+            seq: 4,
+            snapshot: StateSnapshot {
+                active_module: None,
+                status_line: "ready".to_string(),
+                uptime_seconds: 1,
+                tps: 10.0,
+                telemetry: vec![datum("cpu", 1)],
+            },
+        });
+
+        let update = |seq, changes| StateUpdate { seq, changes };
+        assert!(state.apply(&update(
+            5,
+            vec![
+                StateChange::StatusLine {
+                    status_line: "busy".to_string(),
+                },
+                StateChange::Telemetry(datum("cpu", 2)),
+                StateChange::Telemetry(datum("mem", 3)),
+            ],
+        )));
+        assert_eq!(state.seq(), 5);
+        assert_eq!(state.snapshot().status_line, "busy");
+        assert_eq!(
+            state.snapshot().telemetry,
+            [datum("cpu", 2), datum("mem", 3)]
+        );
+
+        assert!(
+            state.apply(&update(3, Vec::new())),
+            "stale updates are ignored"
+        );
+        assert!(!state.apply(&update(7, Vec::new())));
+        assert_eq!(state.seq(), 5);
+    }
+}
@@ -0,0 +1,37 @@
+//! Run the protocol conformance harness against a plugin manifest.
+//!
+//! ```text
+//! cargo run -p spud-remote --example conformance -- path/to/plugin.toml
+//! ```
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use spud_remote::conformance::ConformanceHarness;
+
+fn main() -> ExitCode {
+    let Some(manifest_path) = std::env::args_os().nth(1).map(PathBuf::from) else {
+        eprintln!("usage: conformance <plugin.toml>");
+        return ExitCode::from(2);
+    };
+
+    let harness = match ConformanceHarness::from_manifest_path(&manifest_path) {
+        Ok(harness) => harness,
+        Err(err) => {
+            eprintln!("failed to load {}: {err:#}", manifest_path.display());
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = harness.run_all();
+    println!("plugin {}", report.plugin_id);
+    for line in report.render_lines() {
+        println!("  {line}");
+    }
+
+    if report.passed() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,18 @@
+//! Fuzz deserialization of host API parameter payloads.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use spud_remote::protocol::{
+    build_handshake_result, HandshakeParams, InvokeCommandParams, PublishEventParams,
+    SubscribeParams,
+};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(params) = serde_json::from_slice::<HandshakeParams>(data) {
+        let _ = build_handshake_result(&params);
+    }
+    let _ = serde_json::from_slice::<SubscribeParams>(data);
+    let _ = serde_json::from_slice::<InvokeCommandParams>(data);
+    let _ = serde_json::from_slice::<PublishEventParams>(data);
+});
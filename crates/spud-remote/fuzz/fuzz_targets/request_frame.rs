@@ -0,0 +1,9 @@
+//! Fuzz the plugin → host JSON-RPC envelope parser.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = spud_remote::runtime::validate_request_frame(data);
+});
@@ -0,0 +1,264 @@
+//! Audit trail of privileged plugin actions.
+//!
+//! Every permission-checked request (command invocation, event publish,
+//! subscription change) is recorded with its decision, both in a bounded
+//! in-memory ring for the console and, optionally, as JSON lines in a
+//! dedicated audit file.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Default number of records kept in memory.
+pub const DEFAULT_AUDIT_CAPACITY: usize = 512;
+/// File name used for the on-disk audit trail.
+pub const AUDIT_FILE_NAME: &str = "plugin-audit.jsonl";
+/// Longest string parameter stored verbatim; longer values are truncated.
+const MAX_PARAM_CHARS: usize = 512;
+
+/// Privileged action a plugin attempted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    ConsolePrompt,
+    InvokeCommand,
+    LogsSubscribe,
+    LogsUnsubscribe,
+    PublishEvent,
+    RegisterEventSchema,
+    Subscribe,
+    Unsubscribe,
+}
+
+impl AuditAction {
+    /// Stable snake_case name used in files and console output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ConsolePrompt => "console_prompt",
+            Self::InvokeCommand => "invoke_command",
+            Self::LogsSubscribe => "logs_subscribe",
+            Self::LogsUnsubscribe => "logs_unsubscribe",
+            Self::PublishEvent => "publish_event",
+            Self::RegisterEventSchema => "register_event_schema",
+            Self::Subscribe => "subscribe",
+            Self::Unsubscribe => "unsubscribe",
+        }
+    }
+}
+
+/// Permission decision for an audited action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditDecision {
+    Allowed,
+    Denied,
+}
+
+/// One audited plugin action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Unix timestamp in milliseconds.
+    pub timestamp_ms: u64,
+    pub plugin_id: String,
+    pub action: AuditAction,
+    pub decision: AuditDecision,
+    /// Request parameters, with long strings truncated.
+    pub params: Value,
+    /// Denial reason, when denied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl AuditRecord {
+    /// Build a record stamped with the current time.
+    pub fn now(
+        plugin_id: &str,
+        action: AuditAction,
+        decision: AuditDecision,
+        params: Value,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or(0),
+            plugin_id: plugin_id.to_string(),
+            action,
+            decision,
+            params: truncate_strings(params),
+            reason,
+        }
+    }
+}
+
+/// In-memory audit ring with an optional JSON-lines file sink.
+pub struct AuditLog {
+    records: VecDeque<AuditRecord>,
+    capacity: usize,
+    file: Option<(PathBuf, File)>,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_AUDIT_CAPACITY)
+    }
+}
+
+impl AuditLog {
+    /// Create an in-memory audit log holding at most `capacity` records.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity.min(DEFAULT_AUDIT_CAPACITY)),
+            capacity: capacity.max(1),
+            file: None,
+        }
+    }
+
+    /// Also append every record to `path` (created with parent directories).
+    pub fn open_file(&mut self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create audit directory {}", parent.display())
+            })?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open audit file {}", path.display()))?;
+        self.file = Some((path.to_path_buf(), file));
+        Ok(())
+    }
+
+    /// Path of the audit file, if one is attached.
+    pub fn file_path(&self) -> Option<&Path> {
+        self.file.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    /// Store a record in memory and append it to the audit file.
+    ///
+    /// A failed file write detaches the file so a full disk cannot stall
+    /// request handling; the in-memory ring keeps recording.
+    pub fn record(&mut self, record: AuditRecord) {
+        if let Some((_, file)) = self.file.as_mut() {
+            let written = serde_json::to_string(&record)
+                .map_err(std::io::Error::other)
+                .and_then(|line| writeln!(file, "{line}"));
+            if written.is_err() {
+                self.file = None;
+            }
+        }
+
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// All in-memory records, oldest first.
+    pub fn records(&self) -> impl DoubleEndedIterator<Item = &AuditRecord> {
+        self.records.iter()
+    }
+
+    /// The newest `limit` records for `plugin_id`, oldest first.
+    pub fn recent_for(&self, plugin_id: &str, limit: usize) -> Vec<&AuditRecord> {
+        let mut recent: Vec<&AuditRecord> = self
+            .records
+            .iter()
+            .rev()
+            .filter(|record| record.plugin_id == plugin_id)
+            .take(limit)
+            .collect();
+        recent.reverse();
+        recent
+    }
+}
+
+fn truncate_strings(value: Value) -> Value {
+    match value {
+        Value::String(text) if text.chars().count() > MAX_PARAM_CHARS => {
+            let mut truncated: String = text.chars().take(MAX_PARAM_CHARS).collect();
+            truncated.push('…');
+            Value::String(truncated)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(truncate_strings).collect()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, truncate_strings(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    use spud_testkit::TestDir;
+
+    fn record(plugin_id: &str, decision: AuditDecision) -> AuditRecord {
+        AuditRecord::now(
+            plugin_id,
+            AuditAction::InvokeCommand,
+            decision,
+            json!({"command": "help"}),
+            None,
+        )
+    }
+
+    #[test]
+    fn ring_evicts_oldest_and_filters_by_plugin() {
+        let mut log = AuditLog::new(3);
+        log.record(record("a", AuditDecision::Allowed));
+        log.record(record("b", AuditDecision::Denied));
+        log.record(record("a", AuditDecision::Denied));
+        log.record(record("a", AuditDecision::Allowed));
+
+        assert_eq!(log.records().count(), 3);
+        let recent = log.recent_for("a", 10);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].decision, AuditDecision::Denied);
+        assert_eq!(log.recent_for("a", 1)[0].decision, AuditDecision::Allowed);
+    }
+
+    #[test]
+    fn file_sink_writes_json_lines() {
+        let dir = TestDir::new("audit-file");
+        let path = dir.path.join("nested").join(AUDIT_FILE_NAME);
+        let mut log = AuditLog::default();
+        log.open_file(&path).unwrap();
+        log.record(record("a", AuditDecision::Allowed));
+        log.record(record("b", AuditDecision::Denied));
+
+        let raw = fs::read_to_string(&path).unwrap();
+        let lines: Vec<AuditRecord> = raw
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].plugin_id, "b");
+        assert!(raw.contains("\"decision\":\"denied\""));
+    }
+
+    #[test]
+    fn long_string_params_are_truncated() {
+        let record = AuditRecord::now(
+            "a",
+            AuditAction::PublishEvent,
+            AuditDecision::Allowed,
+            json!({"tag": "t", "payload": "x".repeat(2_000)}),
+            None,
+        );
+        let payload = record.params["payload"].as_str().unwrap();
+        assert_eq!(payload.chars().count(), MAX_PARAM_CHARS + 1);
+    }
+}
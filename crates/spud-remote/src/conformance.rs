@@ -0,0 +1,604 @@
+//! Protocol conformance harness for plugin binaries.
+//!
+//! The harness plays the host side of the JSON-RPC contract against a real
+//! plugin process and scripts adversarial host behavior — slow responses,
+//! malformed frames, permission denials, and shutdown while a request is in
+//! flight — then checks that the plugin handles each one without crashing or
+//! violating the wire protocol.
+
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ExitStatus};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use spud_config::PluginManifest;
+
+use crate::integrity::IntegrityPolicy;
+use crate::protocol::{
+    build_handshake_result, error_code, method, HandshakeParams, JsonRpcError, RequestId,
+    JSONRPC_VERSION,
+};
+use crate::runtime::{
+    plugin_command, spawn_reader, JsonRpcRequestEnvelope, JsonRpcResponseEnvelope, ReaderEvent,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_SLOW_RESPONSE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_millis(200);
+const MALFORMED_FRAME: &str = "{\"jsonrpc\":\"2.0\",\"id\":";
+const RUST_PANIC_EXIT_CODE: i32 = 101;
+
+/// A scripted host behavior exercised against the plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// Plain handshake: the first frame must be a valid `spud.handshake`.
+    Handshake,
+    /// The handshake response is delayed; the plugin must wait for it.
+    SlowResponse,
+    /// The handshake response is a truncated JSON frame.
+    MalformedResponse,
+    /// The first post-handshake request is rejected as unauthorized.
+    PermissionDenied,
+    /// Host stdin closes while a plugin request is awaiting a response.
+    ShutdownMidRequest,
+}
+
+impl Scenario {
+    /// Every scenario, in the order [`ConformanceHarness::run_all`] runs them.
+    pub const ALL: [Scenario; 5] = [
+        Scenario::Handshake,
+        Scenario::SlowResponse,
+        Scenario::MalformedResponse,
+        Scenario::PermissionDenied,
+        Scenario::ShutdownMidRequest,
+    ];
+
+    /// Stable kebab-case name used in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Scenario::Handshake => "handshake",
+            Scenario::SlowResponse => "slow-response",
+            Scenario::MalformedResponse => "malformed-response",
+            Scenario::PermissionDenied => "permission-denied",
+            Scenario::ShutdownMidRequest => "shutdown-mid-request",
+        }
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Outcome of a single scenario run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    Passed,
+    Failed(String),
+    /// The plugin never reached the state the scenario needs (e.g. it sent
+    /// no request after the handshake), so nothing could be checked.
+    Skipped(String),
+}
+
+/// Result of one scenario within a [`ConformanceReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioResult {
+    pub scenario: Scenario,
+    pub outcome: CheckOutcome,
+}
+
+/// Aggregate results for one plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceReport {
+    pub plugin_id: String,
+    pub results: Vec<ScenarioResult>,
+}
+
+impl ConformanceReport {
+    /// Returns `true` when no scenario failed (skips are not failures).
+    pub fn passed(&self) -> bool {
+        !self
+            .results
+            .iter()
+            .any(|result| matches!(result.outcome, CheckOutcome::Failed(_)))
+    }
+
+    /// Render one human-readable line per scenario.
+    pub fn render_lines(&self) -> Vec<String> {
+        self.results
+            .iter()
+            .map(|result| match &result.outcome {
+                CheckOutcome::Passed => format!("PASS {}", result.scenario),
+                CheckOutcome::Failed(reason) => format!("FAIL {}: {reason}", result.scenario),
+                CheckOutcome::Skipped(reason) => format!("SKIP {}: {reason}", result.scenario),
+            })
+            .collect()
+    }
+}
+
+/// Runs conformance scenarios against the plugin described by a manifest.
+///
+/// Each scenario spawns a fresh plugin process, so scenarios are
+/// independent of one another.
+pub struct ConformanceHarness {
+    manifest_path: PathBuf,
+    manifest: PluginManifest,
+    timeout: Duration,
+    slow_response_delay: Duration,
+    grace_period: Duration,
+}
+
+impl ConformanceHarness {
+    /// Load and validate the manifest at `manifest_path`.
+    pub fn from_manifest_path(manifest_path: &Path) -> Result<Self> {
+        let manifest = PluginManifest::from_path(manifest_path)?;
+        Ok(Self {
+            manifest_path: manifest_path.to_path_buf(),
+            manifest,
+            timeout: DEFAULT_TIMEOUT,
+            slow_response_delay: DEFAULT_SLOW_RESPONSE_DELAY,
+            grace_period: DEFAULT_GRACE_PERIOD,
+        })
+    }
+
+    /// How long to wait for plugin frames and process exit.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How long [`Scenario::SlowResponse`] holds back the handshake response.
+    pub fn with_slow_response_delay(mut self, delay: Duration) -> Self {
+        self.slow_response_delay = delay;
+        self
+    }
+
+    /// Run every scenario in [`Scenario::ALL`].
+    pub fn run_all(&self) -> ConformanceReport {
+        ConformanceReport {
+            plugin_id: self.manifest.id.clone(),
+            results: Scenario::ALL
+                .iter()
+                .map(|&scenario| ScenarioResult {
+                    scenario,
+                    outcome: self.run(scenario),
+                })
+                .collect(),
+        }
+    }
+
+    /// Run a single scenario against a freshly spawned plugin.
+    pub fn run(&self, scenario: Scenario) -> CheckOutcome {
+        let mut probe = match Probe::spawn(&self.manifest_path, &self.manifest) {
+            Ok(probe) => probe,
+            Err(reason) => return CheckOutcome::Failed(reason),
+        };
+
+        let outcome = match scenario {
+            Scenario::Handshake => self.check_handshake(&mut probe),
+            Scenario::SlowResponse => self.check_slow_response(&mut probe),
+            Scenario::MalformedResponse => self.check_malformed_response(&mut probe),
+            Scenario::PermissionDenied => self.check_permission_denied(&mut probe),
+            Scenario::ShutdownMidRequest => self.check_shutdown_mid_request(&mut probe),
+        };
+
+        match outcome {
+            Ok(outcome) => outcome,
+            Err(reason) => CheckOutcome::Failed(reason),
+        }
+    }
+
+    fn check_handshake(&self, probe: &mut Probe) -> Result<CheckOutcome, String> {
+        let request = self.expect_handshake(probe)?;
+        self.reply_handshake(probe, request)?;
+        probe.expect_survives(self.grace_period)?;
+        Ok(CheckOutcome::Passed)
+    }
+
+    fn check_slow_response(&self, probe: &mut Probe) -> Result<CheckOutcome, String> {
+        let request = self.expect_handshake(probe)?;
+        if let Some(early) = probe.next_request(self.slow_response_delay)? {
+            return Err(format!(
+                "sent {} before the handshake response arrived",
+                early.method
+            ));
+        }
+        self.reply_handshake(probe, request)?;
+        probe.expect_survives(self.grace_period)?;
+        Ok(CheckOutcome::Passed)
+    }
+
+    fn check_malformed_response(&self, probe: &mut Probe) -> Result<CheckOutcome, String> {
+        self.expect_handshake(probe)?;
+        probe.send_line(MALFORMED_FRAME)?;
+        probe.expect_survives(self.grace_period)?;
+        Ok(CheckOutcome::Passed)
+    }
+
+    fn check_permission_denied(&self, probe: &mut Probe) -> Result<CheckOutcome, String> {
+        let handshake = self.expect_handshake(probe)?;
+        self.reply_handshake(probe, handshake)?;
+        let Some(request) = probe.next_request(self.timeout)? else {
+            return Ok(CheckOutcome::Skipped(
+                "plugin sent no request after the handshake".to_string(),
+            ));
+        };
+        probe.send_response(JsonRpcResponseEnvelope {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: request.id,
+            result: None,
+            error: Some(JsonRpcError {
+                code: error_code::UNAUTHORIZED,
+                message: format!("{} denied by conformance harness", request.method),
+                data: None,
+            }),
+        })?;
+        probe.expect_survives(self.grace_period)?;
+        Ok(CheckOutcome::Passed)
+    }
+
+    fn check_shutdown_mid_request(&self, probe: &mut Probe) -> Result<CheckOutcome, String> {
+        let handshake = self.expect_handshake(probe)?;
+        self.reply_handshake(probe, handshake)?;
+        if probe.next_request(self.timeout)?.is_none() {
+            return Ok(CheckOutcome::Skipped(
+                "plugin sent no request after the handshake".to_string(),
+            ));
+        }
+        probe.close_stdin();
+        match probe.wait_exit(self.timeout) {
+            Some(status) => match crash_reason(status) {
+                Some(reason) => Err(format!("plugin {reason} after host shutdown")),
+                None => Ok(CheckOutcome::Passed),
+            },
+            None => Err(format!(
+                "plugin did not exit within {}ms of host shutdown",
+                self.timeout.as_millis()
+            )),
+        }
+    }
+
+    fn expect_handshake(&self, probe: &mut Probe) -> Result<JsonRpcRequestEnvelope, String> {
+        let request = probe
+            .next_request(self.timeout)?
+            .ok_or_else(|| "no handshake request before timeout".to_string())?;
+
+        if request.method != method::HANDSHAKE {
+            return Err(format!(
+                "first request must be {}, got {}",
+                method::HANDSHAKE,
+                request.method
+            ));
+        }
+        if request.jsonrpc != JSONRPC_VERSION {
+            return Err(format!(
+                "handshake uses jsonrpc \"{}\", expected \"{JSONRPC_VERSION}\"",
+                request.jsonrpc
+            ));
+        }
+        if request.id == RequestId::Null {
+            return Err("handshake request id must not be null".to_string());
+        }
+
+        let params: HandshakeParams = serde_json::from_value(request.params.clone())
+            .map_err(|err| format!("invalid handshake params: {err}"))?;
+        if params.plugin_id != self.manifest.id {
+            return Err(format!(
+                "handshake plugin_id {} does not match manifest id {}",
+                params.plugin_id, self.manifest.id
+            ));
+        }
+        if params.plugin_version != self.manifest.version {
+            return Err(format!(
+                "handshake plugin_version {} does not match manifest version {}",
+                params.plugin_version, self.manifest.version
+            ));
+        }
+        build_handshake_result(&params).map_err(|err| err.to_string())?;
+
+        Ok(request)
+    }
+
+    fn reply_handshake(
+        &self,
+        probe: &mut Probe,
+        request: JsonRpcRequestEnvelope,
+    ) -> Result<(), String> {
+        let params: HandshakeParams = serde_json::from_value(request.params)
+            .map_err(|err| format!("invalid handshake params: {err}"))?;
+        let result = build_handshake_result(&params).map_err(|err| err.to_string())?;
+        let result = serde_json::to_value(result)
+            .map_err(|err| format!("failed to encode handshake result: {err}"))?;
+        probe.send_response(JsonRpcResponseEnvelope {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: request.id,
+            result: Some(result),
+            error: None,
+        })
+    }
+}
+
+/// A spawned plugin process under test.
+struct Probe {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    reader_rx: Receiver<ReaderEvent>,
+    stdout_closed: bool,
+}
+
+impl Probe {
+    fn spawn(manifest_path: &Path, manifest: &PluginManifest) -> Result<Self, String> {
+        let mut command = plugin_command(manifest_path, manifest, &IntegrityPolicy::default())
+            .map_err(|err| err.to_string())?;
+        let mut child = command
+            .spawn()
+            .map_err(|err| format!("failed to spawn plugin {}: {err}", manifest.id))?;
+        let stdin = child.stdin.take();
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| format!("failed to capture plugin {} stdout pipe", manifest.id))?;
+
+        Ok(Self {
+            child,
+            stdin,
+            reader_rx: spawn_reader(stdout),
+            stdout_closed: false,
+        })
+    }
+
+    /// Wait up to `timeout` for the next plugin request.
+    ///
+    /// `Ok(None)` means the timeout elapsed; malformed frames and a closed
+    /// stdout are errors.
+    fn next_request(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Option<JsonRpcRequestEnvelope>, String> {
+        if self.stdout_closed {
+            return Err("plugin closed stdout".to_string());
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            return match self.reader_rx.recv_timeout(remaining) {
+                Ok(ReaderEvent::Request(request, _)) => Ok(Some(request)),
+                // The harness sends no requests, so stray answers are ignored.
+                Ok(ReaderEvent::Response(_)) => continue,
+                Ok(ReaderEvent::ProtocolError(message)) => {
+                    Err(format!("plugin sent a malformed frame: {message}"))
+                }
+                Ok(ReaderEvent::IoError(message)) => Err(format!("plugin stdout error: {message}")),
+                Ok(ReaderEvent::Eof) | Err(RecvTimeoutError::Disconnected) => {
+                    self.stdout_closed = true;
+                    Err("plugin closed stdout".to_string())
+                }
+                Err(RecvTimeoutError::Timeout) => Ok(None),
+            };
+        }
+    }
+
+    /// Check that the plugin neither crashes nor emits malformed frames
+    /// within `grace`. Exiting cleanly is acceptable.
+    fn expect_survives(&mut self, grace: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + grace;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match self.next_request(remaining) {
+                Ok(Some(_)) => continue,
+                Ok(None) => break,
+                Err(_) if self.stdout_closed => break,
+                Err(reason) => return Err(reason),
+            }
+        }
+
+        let status = if self.stdout_closed {
+            self.wait_exit(grace)
+        } else {
+            self.child.try_wait().ok().flatten()
+        };
+        match status.and_then(crash_reason) {
+            Some(reason) => Err(format!("plugin {reason}")),
+            None => Ok(()),
+        }
+    }
+
+    fn send_response(&mut self, response: JsonRpcResponseEnvelope) -> Result<(), String> {
+        let line = serde_json::to_string(&response)
+            .map_err(|err| format!("failed to encode response: {err}"))?;
+        self.send_line(&line)
+    }
+
+    fn send_line(&mut self, line: &str) -> Result<(), String> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| "plugin stdin already closed".to_string())?;
+        stdin
+            .write_all(line.as_bytes())
+            .and_then(|()| stdin.write_all(b"\n"))
+            .and_then(|()| stdin.flush())
+            .map_err(|err| format!("failed to write to plugin stdin: {err}"))
+    }
+
+    fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    fn wait_exit(&mut self, timeout: Duration) -> Option<ExitStatus> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match self.child.try_wait() {
+                Ok(Some(status)) => return Some(status),
+                Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+                _ => return None,
+            }
+        }
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Describe why an exit status counts as a crash, if it does.
+///
+/// Non-zero exit codes are a legitimate way to refuse a broken host, so only
+/// signal termination and Rust panics are treated as crashes.
+fn crash_reason(status: ExitStatus) -> Option<String> {
+    match status.code() {
+        None => Some("was terminated by a signal".to_string()),
+        Some(RUST_PANIC_EXIT_CODE) => Some("panicked".to_string()),
+        Some(_) => None,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use spud_testkit::TestDir;
+
+    const HANDSHAKE_LINE: &str = r#"{"jsonrpc":"2.0","id":1,"method":"spud.handshake","params":{"plugin_id":"fixture.conformance","plugin_version":"0.1.0","supported_api_versions":"^1.0"}}"#;
+    const SNAPSHOT_LINE: &str =
+        r#"{"jsonrpc":"2.0","id":2,"method":"spud.state.get_snapshot","params":{}}"#;
+
+    fn harness_for(dir: &TestDir, script: &str) -> ConformanceHarness {
+        fs::write(dir.path.join("plugin.sh"), script).unwrap();
+        fs::write(
+            dir.path.join("plugin.toml"),
+            r#"
+id = "fixture.conformance"
+name = "Conformance Fixture"
+version = "0.1.0"
+
+[runtime]
+entrypoint = "plugin.sh"
+command = "sh"
+
+[compatibility]
+host_api = "^1.0.0"
+
+[permissions]
+"#,
+        )
+        .unwrap();
+        ConformanceHarness::from_manifest_path(&dir.path.join("plugin.toml"))
+            .unwrap()
+            .with_timeout(Duration::from_millis(1000))
+            .with_slow_response_delay(Duration::from_millis(100))
+    }
+
+    fn well_behaved_script() -> String {
+        format!(
+            "printf '%s\\n' '{HANDSHAKE_LINE}'\n\
+             IFS= read -r line || exit 0\n\
+             printf '%s\\n' '{SNAPSHOT_LINE}'\n\
+             while IFS= read -r line; do :; done\n\
+             exit 0\n"
+        )
+    }
+
+    #[test]
+    fn well_behaved_plugin_passes_every_scenario() {
+        let dir = TestDir::new("conformance-pass");
+        let report = harness_for(&dir, &well_behaved_script()).run_all();
+
+        assert!(report.passed(), "{:?}", report.render_lines());
+        assert!(report
+            .results
+            .iter()
+            .all(|result| result.outcome == CheckOutcome::Passed));
+    }
+
+    #[test]
+    fn plugin_that_skips_handshake_fails() {
+        let dir = TestDir::new("conformance-no-handshake");
+        let harness = harness_for(
+            &dir,
+            &format!("printf '%s\\n' '{SNAPSHOT_LINE}'\nsleep 1\n"),
+        );
+
+        match harness.run(Scenario::Handshake) {
+            CheckOutcome::Failed(reason) => assert!(reason.contains("spud.handshake")),
+            other => panic!("expected failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plugin_emitting_garbage_fails() {
+        let dir = TestDir::new("conformance-garbage");
+        let harness = harness_for(&dir, "printf 'not json\\n'\nsleep 1\n");
+
+        match harness.run(Scenario::Handshake) {
+            CheckOutcome::Failed(reason) => assert!(reason.contains("malformed frame")),
+            other => panic!("expected failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plugin_crashing_on_denial_fails() {
+        let dir = TestDir::new("conformance-denied-crash");
+        let script = format!(
+            "printf '%s\\n' '{HANDSHAKE_LINE}'\n\
+             IFS= read -r line || exit 0\n\
+             printf '%s\\n' '{SNAPSHOT_LINE}'\n\
+             IFS= read -r line || exit 0\n\
+             case \"$line\" in *error*) kill -9 $$ ;; esac\n\
+             while IFS= read -r line; do :; done\n"
+        );
+        let harness = harness_for(&dir, &script);
+
+        match harness.run(Scenario::PermissionDenied) {
+            CheckOutcome::Failed(reason) => assert!(reason.contains("signal")),
+            other => panic!("expected failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plugin_ignoring_shutdown_fails() {
+        let dir = TestDir::new("conformance-hang");
+        let script = format!(
+            "printf '%s\\n' '{HANDSHAKE_LINE}'\n\
+             IFS= read -r line || exit 0\n\
+             printf '%s\\n' '{SNAPSHOT_LINE}'\n\
+             exec sleep 5\n"
+        );
+        let harness = harness_for(&dir, &script).with_timeout(Duration::from_millis(300));
+
+        match harness.run(Scenario::ShutdownMidRequest) {
+            CheckOutcome::Failed(reason) => assert!(reason.contains("did not exit")),
+            other => panic!("expected failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn idle_plugin_skips_request_scenarios() {
+        let dir = TestDir::new("conformance-idle");
+        let script = format!(
+            "printf '%s\\n' '{HANDSHAKE_LINE}'\n\
+             while IFS= read -r line; do :; done\n"
+        );
+        let harness = harness_for(&dir, &script).with_timeout(Duration::from_millis(200));
+
+        assert!(matches!(
+            harness.run(Scenario::PermissionDenied),
+            CheckOutcome::Skipped(_)
+        ));
+        let report = harness.run_all();
+        assert!(report.passed(), "{:?}", report.render_lines());
+        assert!(report.render_lines()[3].starts_with("SKIP permission-denied"));
+    }
+}
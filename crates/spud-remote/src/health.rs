@@ -0,0 +1,166 @@
+//! Liveness pings for plugin sessions.
+//!
+//! When a manifest sets `health.ping_interval_ms`, the host sends
+//! `spud.plugin.health` requests on that interval. A ping fails when it is
+//! answered with an error or not answered within `health.ping_timeout_ms`;
+//! `health.max_failures` failures in a row mark the plugin
+//! [`HealthStatus::Unhealthy`] until it answers a ping again.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use spud_config::PluginHealth;
+
+use crate::protocol::RequestId;
+
+/// Prefix of host ping request IDs, keeping them apart from any ID a plugin
+/// might echo by mistake.
+const PING_ID_PREFIX: &str = "spud.health.";
+
+/// Liveness of a plugin session with health pings enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    /// `max_failures` pings in a row went unanswered or failed.
+    Unhealthy,
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Healthy => "healthy",
+            Self::Unhealthy => "unhealthy",
+        })
+    }
+}
+
+/// Ping schedule and failure count for one session.
+#[derive(Debug)]
+pub(crate) struct HealthMonitor {
+    interval: Duration,
+    timeout: Duration,
+    max_failures: u32,
+    next_ping_at: Instant,
+    in_flight: Option<(RequestId, Instant)>,
+    sent: u64,
+    failures: u32,
+    status: HealthStatus,
+    reported: HealthStatus,
+}
+
+impl HealthMonitor {
+    /// Monitor for a session that finished its handshake at `now`; `None`
+    /// when the policy has pings off.
+    pub(crate) fn new(policy: &PluginHealth, now: Instant) -> Option<Self> {
+        let interval = policy.ping_interval()?;
+        Some(Self {
+            interval,
+            timeout: policy.ping_timeout(),
+            max_failures: policy.max_failures,
+            next_ping_at: now + interval,
+            in_flight: None,
+            sent: 0,
+            failures: 0,
+            status: HealthStatus::Healthy,
+            reported: HealthStatus::Healthy,
+        })
+    }
+
+    pub(crate) fn status(&self) -> HealthStatus {
+        self.status
+    }
+
+    /// Advance to `now`, failing a ping that is overdue. Returns the ID of a
+    /// ping to send when one is due.
+    pub(crate) fn poll(&mut self, now: Instant) -> Option<RequestId> {
+        if let Some((_, sent_at)) = &self.in_flight {
+            if now.saturating_duration_since(*sent_at) < self.timeout {
+                return None;
+            }
+            self.in_flight = None;
+            self.record_failure();
+        }
+        if now < self.next_ping_at {
+            return None;
+        }
+
+        self.sent += 1;
+        let id = RequestId::String(format!("{PING_ID_PREFIX}{}", self.sent));
+        self.in_flight = Some((id.clone(), now));
+        self.next_ping_at = now + self.interval;
+        Some(id)
+    }
+
+    /// Record the plugin's answer to a ping. Returns `false` when `id` is
+    /// not the ping in flight.
+    pub(crate) fn answer(&mut self, id: &RequestId, ok: bool) -> bool {
+        if self.in_flight.as_ref().map(|(pending, _)| pending) != Some(id) {
+            return false;
+        }
+        self.in_flight = None;
+        if ok {
+            self.failures = 0;
+            self.status = HealthStatus::Healthy;
+        } else {
+            self.record_failure();
+        }
+        true
+    }
+
+    /// The status, when it changed since the last call.
+    pub(crate) fn take_change(&mut self) -> Option<HealthStatus> {
+        if self.status == self.reported {
+            return None;
+        }
+        self.reported = self.status;
+        Some(self.status)
+    }
+
+    fn record_failure(&mut self) {
+        self.failures += 1;
+        if self.failures >= self.max_failures {
+            self.status = HealthStatus::Unhealthy;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missed_pings_mark_unhealthy_until_one_is_answered() {
+        let policy = PluginHealth {
+            ping_interval_ms: Some(100),
+            ping_timeout_ms: 50,
+            max_failures: 2,
+            ..PluginHealth::default()
+        };
+        assert!(HealthMonitor::new(&PluginHealth::default(), Instant::now()).is_none());
+
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut monitor = HealthMonitor::new(&policy, start).unwrap();
+        assert_eq!(monitor.poll(at(50)), None);
+
+        let first = monitor.poll(at(100)).unwrap();
+        assert_eq!(monitor.poll(at(120)), None);
+        assert!(monitor.answer(&first, true));
+        assert!(!monitor.answer(&first, true));
+
+        // Two pings time out in a row.
+        monitor.poll(at(200)).unwrap();
+        assert_eq!(monitor.poll(at(260)), None);
+        assert_eq!(monitor.status(), HealthStatus::Healthy);
+        monitor.poll(at(300)).unwrap();
+        assert_eq!(monitor.poll(at(360)), None);
+        assert_eq!(monitor.take_change(), Some(HealthStatus::Unhealthy));
+        assert_eq!(monitor.take_change(), None);
+
+        let next = monitor.poll(at(400)).unwrap();
+        assert!(monitor.answer(&next, true));
+        assert_eq!(monitor.take_change(), Some(HealthStatus::Healthy));
+    }
+}
@@ -0,0 +1,256 @@
+//! Entrypoint checks made before a plugin process is spawned.
+//!
+//! A manifest's `[integrity]` section may pin the entrypoint's SHA-256 and
+//! name a minisign signature of it. A pinned digest must always match. A
+//! signature is checked against the host's trusted keys (`[plugins]` in
+//! `spud.toml`): a bad signature from a trusted key always fails, while one
+//! from an unknown key only fails where the host requires signatures.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use minisign_verify::{Error as MinisignError, PublicKey, Signature};
+use sha2::{Digest, Sha256};
+use spud_config::{PluginManifest, PluginsConfig};
+
+/// Host side of plugin integrity: which keys may sign entrypoints and which
+/// plugins must be signed.
+#[derive(Debug, Clone, Default)]
+pub struct IntegrityPolicy {
+    trusted_keys: Vec<PublicKey>,
+    require_signatures: bool,
+    local_roots: Vec<PathBuf>,
+}
+
+impl IntegrityPolicy {
+    /// Policy from `[plugins]` in `spud.toml`. Fails on a trusted key that
+    /// is not a minisign public key.
+    pub fn from_config(config: &PluginsConfig) -> Result<Self> {
+        let trusted_keys = config
+            .trusted_keys
+            .iter()
+            .map(|key| {
+                PublicKey::from_base64(key.trim())
+                    .map_err(|err| anyhow!("invalid plugins.trusted_keys entry {key:?}: {err}"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            trusted_keys,
+            require_signatures: config.require_signatures,
+            local_roots: config.local_roots.clone(),
+        })
+    }
+
+    /// Whether the plugin at `manifest_path` must carry a trusted signature.
+    pub fn requires_signature(&self, manifest_path: &Path) -> bool {
+        self.require_signatures
+            && !self
+                .local_roots
+                .iter()
+                .any(|root| canonical(manifest_path).starts_with(canonical(root)))
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Check `entrypoint` against the manifest's `[integrity]` section and
+/// `policy`. The error says which check failed.
+pub(crate) fn verify_entrypoint(
+    policy: &IntegrityPolicy,
+    manifest_path: &Path,
+    manifest: &PluginManifest,
+    entrypoint: &Path,
+) -> std::result::Result<(), String> {
+    let integrity = &manifest.integrity;
+    let required = policy.requires_signature(manifest_path);
+    if integrity.sha256.is_none() && integrity.signature.is_none() && !required {
+        return Ok(());
+    }
+
+    let id = &manifest.id;
+    let contents = fs::read(entrypoint).map_err(|err| {
+        format!(
+            "failed to read plugin {id} entrypoint {}: {err}",
+            entrypoint.display()
+        )
+    })?;
+
+    if let Some(expected) = &integrity.sha256 {
+        let actual = sha256_hex(&contents);
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "plugin {id} entrypoint {} has sha256 {actual}, manifest expects {expected}",
+                entrypoint.display()
+            ));
+        }
+    }
+
+    let Some(signature_path) = &integrity.signature else {
+        return if required {
+            Err(format!(
+                "plugin {id} is outside plugins.local_roots and has no integrity.signature"
+            ))
+        } else {
+            Ok(())
+        };
+    };
+    let manifest_dir = manifest_path.parent().unwrap_or(Path::new("."));
+    let signature_path = signature_path
+        .split('/')
+        .fold(manifest_dir.to_path_buf(), |path, part| path.join(part));
+    let signature = Signature::from_file(&signature_path).map_err(|err| {
+        format!(
+            "failed to read plugin {id} signature {}: {err}",
+            signature_path.display()
+        )
+    })?;
+
+    for key in &policy.trusted_keys {
+        match key.verify(&contents, &signature, false) {
+            Ok(()) => return Ok(()),
+            Err(MinisignError::UnexpectedKeyId) => continue,
+            Err(err) => {
+                return Err(format!(
+                    "plugin {id} entrypoint {} failed signature check: {err}",
+                    entrypoint.display()
+                ))
+            }
+        }
+    }
+    if required {
+        Err(format!("plugin {id} is not signed by a trusted key"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Lowercase hex SHA-256 of `bytes`, as written in `integrity.sha256`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    const ENTRYPOINT: &str = "#!/bin/sh\nexit 0\n";
+    const ENTRYPOINT_SHA256: &str =
+        "306c6ca7407560340797866e077e053627ad409277d1b9da58106fce4cf717cb";
+    /// Minisign key that signed [`SIGNATURE`].
+    const SIGNING_KEY: &str = "RWRzcHVkdGVzdOpKbGPinFIKvvVQexMuxfmVR3auvr57kkIe6mkURtIs";
+    const OTHER_KEY: &str = "RWRvdGhlcmtlef0XJDhaoMdbZPt4zWAvodmR/ev3axPFjtcC6sg16fYY";
+    const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RURzcHVkdGVzdCAkr9aS6vtN6TV5vAwT+quRazl5xjYVEbAbRbpiF/lpVHgZTK9kqlpFb71GEM2MOJi6OR5RkucB0OTIdl+c0Q8=
+trusted comment: timestamp:1790000000\tfile:plugin.sh
+Wu4IP1j6EBknq84xQwaTiE3uTKszCAtZMqxrgshOOPDlxFPuyjpzZJs+Rc69ebNSbSQiw0z+dtHFABR6ZIZuDQ==
+";
+
+    struct Fixture {
+        dir: TestDir,
+        manifest: PluginManifest,
+    }
+
+    impl Fixture {
+        fn new(integrity: &str) -> Self {
+            let dir = TestDir::new("integrity");
+            fs::write(dir.path.join("plugin.sh"), ENTRYPOINT).unwrap();
+            fs::write(dir.path.join("plugin.sh.minisig"), SIGNATURE).unwrap();
+            fs::write(dir.path.join("plugin.toml"), "").unwrap();
+            let manifest = PluginManifest::from_toml_str(&format!(
+                r#"
+id = "spud.signed"
+name = "Signed"
+version = "0.1.0"
+
+[runtime]
+entrypoint = "plugin.sh"
+command = "sh"
+
+[compatibility]
+host_api = "^1.0.0"
+
+[permissions]
+
+[integrity]
+{integrity}
+"#
+            ))
+            .unwrap();
+            Self { dir, manifest }
+        }
+
+        fn verify(&self, policy: &IntegrityPolicy) -> std::result::Result<(), String> {
+            verify_entrypoint(
+                policy,
+                &self.dir.path.join("plugin.toml"),
+                &self.manifest,
+                &self.dir.path.join("plugin.sh"),
+            )
+        }
+    }
+
+    fn policy(keys: &[&str], require_signatures: bool, local_roots: &[&Path]) -> IntegrityPolicy {
+        IntegrityPolicy::from_config(&PluginsConfig {
+            trusted_keys: keys.iter().map(|key| key.to_string()).collect(),
+            require_signatures,
+            local_roots: local_roots.iter().map(|root| root.to_path_buf()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn pinned_digest_must_match() {
+        let fixture = Fixture::new(&format!("sha256 = \"{ENTRYPOINT_SHA256}\""));
+        assert_eq!(sha256_hex(ENTRYPOINT.as_bytes()), ENTRYPOINT_SHA256);
+        assert_eq!(fixture.verify(&IntegrityPolicy::default()), Ok(()));
+
+        fs::write(fixture.dir.path.join("plugin.sh"), "#!/bin/sh\nrm -rf ~\n").unwrap();
+        let err = fixture.verify(&IntegrityPolicy::default()).unwrap_err();
+        assert!(err.contains("manifest expects"), "{err}");
+    }
+
+    #[test]
+    fn signatures_are_checked_against_trusted_keys() {
+        let fixture = Fixture::new("signature = \"plugin.sh.minisig\"");
+        assert_eq!(fixture.verify(&policy(&[SIGNING_KEY], true, &[])), Ok(()));
+        assert_eq!(
+            fixture.verify(&policy(&[OTHER_KEY, SIGNING_KEY], true, &[])),
+            Ok(())
+        );
+        // An unknown signer is only refused where signatures are required.
+        assert_eq!(fixture.verify(&policy(&[OTHER_KEY], false, &[])), Ok(()));
+        let err = fixture
+            .verify(&policy(&[OTHER_KEY], true, &[]))
+            .unwrap_err();
+        assert!(err.contains("not signed by a trusted key"), "{err}");
+
+        fs::write(fixture.dir.path.join("plugin.sh"), "#!/bin/sh\nexit 1\n").unwrap();
+        let err = fixture
+            .verify(&policy(&[SIGNING_KEY], false, &[]))
+            .unwrap_err();
+        assert!(err.contains("failed signature check"), "{err}");
+    }
+
+    #[test]
+    fn local_roots_are_exempt_from_required_signatures() {
+        let fixture = Fixture::new("");
+        let err = fixture.verify(&policy(&[], true, &[])).unwrap_err();
+        assert!(err.contains("has no integrity.signature"), "{err}");
+        assert_eq!(
+            fixture.verify(&policy(&[], true, &[&fixture.dir.path])),
+            Ok(())
+        );
+        assert!(IntegrityPolicy::from_config(&PluginsConfig {
+            trusted_keys: vec!["not a key".into()],
+            ..PluginsConfig::default()
+        })
+        .is_err());
+    }
+}
@@ -3,6 +3,19 @@
 //! This crate defines the versioned JSON-RPC host API contract and strongly
 //! typed payloads shared by plugin-runtime implementation work.
 
+pub mod audit;
+pub mod conformance;
+pub mod health;
+pub mod integrity;
+pub mod metrics;
+mod outbound;
 pub mod permissions;
 pub mod protocol;
+pub mod pump;
 pub mod runtime;
+
+/// Version of this crate, as reported by `spud version`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg(test)]
+mod test_support;
@@ -0,0 +1,215 @@
+//! Per-plugin runtime counters and Prometheus text rendering.
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Queue wait after which a handled request counts as starved.
+pub const STARVED_AFTER: Duration = Duration::from_millis(100);
+
+/// Counters for one registered plugin, kept across process restarts.
+#[derive(Debug, Default)]
+pub(crate) struct PluginCounters {
+    pub(crate) requests_handled: u64,
+    pub(crate) errors_returned: u64,
+    pub(crate) notifications_delivered: u64,
+    /// Shared with the session's outbound writer, which increments it.
+    pub(crate) notifications_dropped: Arc<AtomicU64>,
+    pub(crate) starts: u64,
+    pub(crate) last_latency: Option<Duration>,
+    pub(crate) max_queue_wait: Option<Duration>,
+    pub(crate) starved_requests: u64,
+}
+
+impl PluginCounters {
+    /// Record a successful session start; starts after the first are restarts.
+    pub(crate) fn record_start(&mut self) {
+        self.starts += 1;
+    }
+
+    /// Record how long a handled request waited before the host got to it.
+    pub(crate) fn record_queue_wait(&mut self, wait: Duration) {
+        self.max_queue_wait = self.max_queue_wait.max(Some(wait));
+        if wait > STARVED_AFTER {
+            self.starved_requests += 1;
+        }
+    }
+
+    pub(crate) fn snapshot(&self, plugin_id: &str, running: bool) -> PluginStats {
+        PluginStats {
+            plugin_id: plugin_id.to_string(),
+            running,
+            requests_handled: self.requests_handled,
+            errors_returned: self.errors_returned,
+            notifications_delivered: self.notifications_delivered,
+            notifications_dropped: self.notifications_dropped.load(Ordering::Relaxed),
+            restarts: self.starts.saturating_sub(1),
+            last_latency: self.last_latency,
+            max_queue_wait: self.max_queue_wait,
+            starved_requests: self.starved_requests,
+        }
+    }
+}
+
+/// Point-in-time view of one plugin's runtime counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PluginStats {
+    pub plugin_id: String,
+    /// Whether a plugin session is currently live.
+    pub running: bool,
+    /// Requests (after the handshake) handled by the host.
+    pub requests_handled: u64,
+    /// Requests answered with a JSON-RPC error.
+    pub errors_returned: u64,
+    /// Event notifications queued for delivery.
+    pub notifications_delivered: u64,
+    /// Event notifications evicted by outbound backpressure.
+    pub notifications_dropped: u64,
+    /// Successful starts after the first one.
+    pub restarts: u64,
+    /// Host-side handling time of the most recent request.
+    pub last_latency: Option<Duration>,
+    /// Longest time a request waited between arriving and being handled.
+    pub max_queue_wait: Option<Duration>,
+    /// Requests that waited longer than [`STARVED_AFTER`].
+    pub starved_requests: u64,
+}
+
+/// Render plugin stats in the Prometheus text exposition format.
+pub fn render_prometheus(stats: &[PluginStats]) -> String {
+    type Field = fn(&PluginStats) -> Option<f64>;
+    let families: [(&str, &str, &str, Field); 9] = [
+        (
+            "spud_plugin_up",
+            "gauge",
+            "Whether the plugin session is running.",
+            |s| Some(if s.running { 1.0 } else { 0.0 }),
+        ),
+        (
+            "spud_plugin_requests_total",
+            "counter",
+            "Plugin requests handled by the host.",
+            |s| Some(s.requests_handled as f64),
+        ),
+        (
+            "spud_plugin_request_errors_total",
+            "counter",
+            "Plugin requests answered with a JSON-RPC error.",
+            |s| Some(s.errors_returned as f64),
+        ),
+        (
+            "spud_plugin_notifications_delivered_total",
+            "counter",
+            "Event notifications queued for the plugin.",
+            |s| Some(s.notifications_delivered as f64),
+        ),
+        (
+            "spud_plugin_notifications_dropped_total",
+            "counter",
+            "Event notifications dropped by outbound backpressure.",
+            |s| Some(s.notifications_dropped as f64),
+        ),
+        (
+            "spud_plugin_restarts_total",
+            "counter",
+            "Plugin session starts after the first.",
+            |s| Some(s.restarts as f64),
+        ),
+        (
+            "spud_plugin_last_request_latency_seconds",
+            "gauge",
+            "Host-side handling time of the most recent plugin request.",
+            |s| s.last_latency.map(|latency| latency.as_secs_f64()),
+        ),
+        (
+            "spud_plugin_max_queue_wait_seconds",
+            "gauge",
+            "Longest time a plugin request waited before the host handled it.",
+            |s| s.max_queue_wait.map(|wait| wait.as_secs_f64()),
+        ),
+        (
+            "spud_plugin_starved_requests_total",
+            "counter",
+            "Plugin requests that waited longer than the starvation threshold.",
+            |s| Some(s.starved_requests as f64),
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, kind, help, field) in families {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for entry in stats {
+            if let Some(value) = field(entry) {
+                let _ = writeln!(
+                    out,
+                    "{name}{{plugin=\"{}\"}} {value}",
+                    escape_label(&entry.plugin_id)
+                );
+            }
+        }
+    }
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restarts_exclude_first_start() {
+        let mut counters = PluginCounters::default();
+        assert_eq!(counters.snapshot("p", false).restarts, 0);
+        counters.record_start();
+        assert_eq!(counters.snapshot("p", true).restarts, 0);
+        counters.record_start();
+        counters
+            .notifications_dropped
+            .fetch_add(3, Ordering::Relaxed);
+        let stats = counters.snapshot("p", true);
+        assert_eq!(stats.restarts, 1);
+        assert_eq!(stats.notifications_dropped, 3);
+    }
+
+    #[test]
+    fn queue_waits_track_peak_and_starvation() {
+        let mut counters = PluginCounters::default();
+        counters.record_queue_wait(Duration::from_millis(5));
+        counters.record_queue_wait(STARVED_AFTER + Duration::from_millis(1));
+        counters.record_queue_wait(Duration::ZERO);
+
+        let stats = counters.snapshot("p", true);
+        assert_eq!(
+            stats.max_queue_wait,
+            Some(STARVED_AFTER + Duration::from_millis(1))
+        );
+        assert_eq!(stats.starved_requests, 1);
+    }
+
+    #[test]
+    fn prometheus_output_has_families_and_escaped_labels() {
+        let stats = vec![PluginStats {
+            plugin_id: "odd\"id".to_string(),
+            running: true,
+            requests_handled: 4,
+            last_latency: Some(Duration::from_millis(250)),
+            ..Default::default()
+        }];
+
+        let text = render_prometheus(&stats);
+        assert!(text.contains("# TYPE spud_plugin_requests_total counter"));
+        assert!(text.contains("spud_plugin_requests_total{plugin=\"odd\\\"id\"} 4"));
+        assert!(text.contains("spud_plugin_up{plugin=\"odd\\\"id\"} 1"));
+        assert!(
+            text.contains("spud_plugin_last_request_latency_seconds{plugin=\"odd\\\"id\"} 0.25")
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Per-plugin outbound writer thread.
+//!
+//! Host → plugin frames are queued and written by a dedicated thread so a
+//! slow or blocked plugin pipe never stalls the host main loop. Responses are
+//! always delivered in order; event notifications share a bounded budget and
+//! the oldest queued notification is dropped when a new one arrives at
+//! capacity.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// Default number of event notifications that may wait in a plugin's queue.
+pub(crate) const DEFAULT_NOTIFICATION_CAPACITY: usize = 256;
+
+enum Frame {
+    Response(String),
+    Notification(String),
+}
+
+#[derive(Default)]
+struct State {
+    frames: VecDeque<Frame>,
+    queued_notifications: usize,
+    closed: bool,
+    error: Option<String>,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    ready: Condvar,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Handle to a plugin's writer thread.
+pub(crate) struct OutboundWriter {
+    shared: Arc<Shared>,
+    capacity: usize,
+}
+
+impl OutboundWriter {
+    /// Start a writer thread that owns `sink`.
+    ///
+    /// `dropped` is incremented for every evicted notification; the runtime
+    /// shares it across sessions so counts survive plugin restarts.
+    pub(crate) fn spawn<W: Write + Send + 'static>(
+        sink: W,
+        capacity: usize,
+        dropped: Arc<AtomicU64>,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::default(),
+            ready: Condvar::new(),
+            dropped,
+        });
+        let thread_shared = Arc::clone(&shared);
+        thread::spawn(move || write_loop(sink, &thread_shared));
+        Self {
+            shared,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Queue a JSON-RPC response line. Responses are never dropped.
+    pub(crate) fn send_response(&self, line: String) -> Result<(), String> {
+        self.push(Frame::Response(line))
+    }
+
+    /// Queue an event notification line, evicting the oldest queued
+    /// notification when the queue is at capacity.
+    pub(crate) fn send_notification(&self, line: String) -> Result<(), String> {
+        self.push(Frame::Notification(line))
+    }
+
+    /// Total notifications evicted by backpressure on the shared counter.
+    pub(crate) fn dropped_notifications(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Stop accepting frames; the thread exits after draining the queue.
+    pub(crate) fn close(&self) {
+        let mut state = lock(&self.shared);
+        state.closed = true;
+        self.shared.ready.notify_all();
+    }
+
+    fn push(&self, frame: Frame) -> Result<(), String> {
+        let mut state = lock(&self.shared);
+        if let Some(error) = &state.error {
+            return Err(error.clone());
+        }
+        if state.closed {
+            return Err("plugin writer is closed".to_string());
+        }
+
+        if matches!(frame, Frame::Notification(_)) {
+            if state.queued_notifications >= self.capacity {
+                if let Some(pos) = state
+                    .frames
+                    .iter()
+                    .position(|queued| matches!(queued, Frame::Notification(_)))
+                {
+                    state.frames.remove(pos);
+                    state.queued_notifications -= 1;
+                    self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            state.queued_notifications += 1;
+        }
+
+        state.frames.push_back(frame);
+        self.shared.ready.notify_one();
+        Ok(())
+    }
+}
+
+impl Drop for OutboundWriter {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+fn lock(shared: &Shared) -> std::sync::MutexGuard<'_, State> {
+    shared
+        .state
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn write_loop<W: Write>(mut sink: W, shared: &Shared) {
+    loop {
+        let frame = {
+            let mut state = lock(shared);
+            loop {
+                if let Some(frame) = state.frames.pop_front() {
+                    if matches!(frame, Frame::Notification(_)) {
+                        state.queued_notifications -= 1;
+                    }
+                    break frame;
+                }
+                if state.closed {
+                    return;
+                }
+                state = shared
+                    .ready
+                    .wait(state)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+            }
+        };
+
+        let line = match &frame {
+            Frame::Response(line) | Frame::Notification(line) => line,
+        };
+        let written = sink
+            .write_all(line.as_bytes())
+            .and_then(|()| sink.write_all(b"\n"))
+            .and_then(|()| sink.flush());
+
+        if let Err(err) = written {
+            let mut state = lock(shared);
+            state.error = Some(err.to_string());
+            state.closed = true;
+            state.frames.clear();
+            state.queued_notifications = 0;
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::{Duration, Instant};
+
+    /// Sink that blocks each write until the test releases it.
+    struct GatedSink {
+        gate: Receiver<()>,
+        written: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Write for GatedSink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if buf != b"\n" {
+                self.gate
+                    .recv()
+                    .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+                self.written
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(buf).into_owned());
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn wait_for(written: &Arc<Mutex<Vec<String>>>, count: usize) -> Vec<String> {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        loop {
+            let lines = written.lock().unwrap().clone();
+            if lines.len() >= count || Instant::now() > deadline {
+                return lines;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn blocked_sink_drops_oldest_notifications_but_keeps_responses() {
+        let (release, gate) = mpsc::channel();
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let writer = OutboundWriter::spawn(
+            GatedSink {
+                gate,
+                written: Arc::clone(&written),
+            },
+            2,
+            Arc::default(),
+        );
+
+        // The first frame is picked up by the thread and blocks in write().
+        writer.send_notification("n0".to_string()).unwrap();
+        thread::sleep(Duration::from_millis(20));
+
+        writer.send_response("r1".to_string()).unwrap();
+        for idx in 1..=4 {
+            writer.send_notification(format!("n{idx}")).unwrap();
+        }
+        writer.send_response("r2".to_string()).unwrap();
+        assert_eq!(writer.dropped_notifications(), 2);
+
+        for _ in 0..5 {
+            release.send(()).unwrap();
+        }
+        assert_eq!(wait_for(&written, 5), vec!["n0", "r1", "n3", "n4", "r2"]);
+    }
+
+    #[test]
+    fn write_failure_surfaces_on_next_send() {
+        let (release, gate) = mpsc::channel::<()>();
+        drop(release);
+        let writer = OutboundWriter::spawn(
+            GatedSink {
+                gate,
+                written: Arc::new(Mutex::new(Vec::new())),
+            },
+            4,
+            Arc::default(),
+        );
+
+        writer.send_response("r0".to_string()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while writer.send_response("r1".to_string()).is_ok() {
+            assert!(Instant::now() < deadline, "writer never reported failure");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
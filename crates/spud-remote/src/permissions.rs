@@ -5,10 +5,13 @@ use semver::{Version, VersionReq};
 use spud_config::PluginManifest;
 
 use crate::protocol::{
-    error_code, EventCategory, InvokeCommandParams, JsonRpcError, PublishEventParams,
-    HOST_API_VERSION,
+    error_code, tag_glob_matches, EventCategory, InvokeCommandParams, JsonRpcError,
+    PublishEventParams, RegisterEventSchemaParams, SUPPORTED_HOST_API_VERSIONS,
 };
 
+/// Entry in `permissions.subscriptions` that allows `spud.logs.subscribe`.
+pub const LOG_SUBSCRIPTION: &str = "logs";
+
 /// Runtime permission policy built from a validated plugin manifest.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PermissionPolicy {
@@ -16,6 +19,8 @@ pub struct PermissionPolicy {
     commands: BTreeSet<String>,
     event_tags: BTreeSet<String>,
     subscriptions: BTreeSet<String>,
+    /// `source.key` globs; `None` allows every telemetry key.
+    telemetry_keys: Option<Vec<String>>,
 }
 
 /// Permission and compatibility failures mapped to structured JSON-RPC errors.
@@ -95,25 +100,29 @@ impl PermissionPolicy {
             commands: manifest.permissions.commands.iter().cloned().collect(),
             event_tags: manifest.permissions.event_tags.iter().cloned().collect(),
             subscriptions: manifest.permissions.subscriptions.iter().cloned().collect(),
+            telemetry_keys: manifest.permissions.telemetry_keys.clone(),
         }
     }
 
-    /// Ensure plugin compatibility requirement matches host API version.
+    /// Ensure plugin compatibility requirement matches a supported host API
+    /// version.
     pub fn ensure_host_compatibility(&self) -> std::result::Result<(), AuthorizationError> {
         let requirement = VersionReq::parse(&self.host_api_requirement).map_err(|_| {
             AuthorizationError::InvalidHostApiRequirement(self.host_api_requirement.clone())
         })?;
-        let host = Version::parse(HOST_API_VERSION)
-            .map_err(|_| AuthorizationError::InvalidHostApiVersion(HOST_API_VERSION.to_string()))?;
 
-        if requirement.matches(&host) {
-            Ok(())
-        } else {
-            Err(AuthorizationError::UnsupportedHostApi {
-                required: self.host_api_requirement.clone(),
-                host: host.to_string(),
-            })
+        for supported in SUPPORTED_HOST_API_VERSIONS {
+            let host = Version::parse(supported)
+                .map_err(|_| AuthorizationError::InvalidHostApiVersion(supported.to_string()))?;
+            if requirement.matches(&host) {
+                return Ok(());
+            }
         }
+
+        Err(AuthorizationError::UnsupportedHostApi {
+            required: self.host_api_requirement.clone(),
+            host: SUPPORTED_HOST_API_VERSIONS.join(", "),
+        })
     }
 
     /// Enforce command invocation allowlist.
@@ -121,7 +130,7 @@ impl PermissionPolicy {
         &self,
         params: &InvokeCommandParams,
     ) -> std::result::Result<(), AuthorizationError> {
-        if self.commands.contains(&params.command) {
+        if self.allows_command(&params.command) {
             Ok(())
         } else {
             Err(AuthorizationError::UnauthorizedCommand(
@@ -130,6 +139,11 @@ impl PermissionPolicy {
         }
     }
 
+    /// Whether the plugin may invoke console command `name`, as typed.
+    pub fn allows_command(&self, name: &str) -> bool {
+        self.commands.contains(name)
+    }
+
     /// Enforce event publish allowlist.
     pub fn authorize_publish_event(
         &self,
@@ -142,6 +156,28 @@ impl PermissionPolicy {
         }
     }
 
+    /// A plugin may only define the schema of a tag it may publish.
+    pub fn authorize_register_event_schema(
+        &self,
+        params: &RegisterEventSchemaParams,
+    ) -> std::result::Result<(), AuthorizationError> {
+        if self.event_tags.contains(&params.tag) {
+            Ok(())
+        } else {
+            Err(AuthorizationError::UnauthorizedEventTag(params.tag.clone()))
+        }
+    }
+
+    /// Whether the plugin may see telemetry entry `source.key`. Entries it
+    /// may not see are left out of what it receives rather than refused.
+    pub fn allows_telemetry_key(&self, source: &str, key: &str) -> bool {
+        let Some(globs) = &self.telemetry_keys else {
+            return true;
+        };
+        let name = format!("{source}.{key}");
+        globs.iter().any(|glob| tag_glob_matches(glob, &name))
+    }
+
     /// Enforce event subscription allowlist and return authorized categories.
     ///
     /// Duplicate categories in the input are silently deduplicated.
@@ -172,6 +208,19 @@ impl PermissionPolicy {
             Err(AuthorizationError::UnauthorizedSubscriptions(unauthorized))
         }
     }
+
+    /// Log streaming needs [`LOG_SUBSCRIPTION`] in the subscription
+    /// allowlist, as log lines can carry more than the plugin otherwise
+    /// sees.
+    pub fn authorize_log_subscription(&self) -> std::result::Result<(), AuthorizationError> {
+        if self.subscriptions.contains(LOG_SUBSCRIPTION) {
+            Ok(())
+        } else {
+            Err(AuthorizationError::UnauthorizedSubscriptions(vec![
+                LOG_SUBSCRIPTION.to_string(),
+            ]))
+        }
+    }
 }
 
 /// Validate manifest compatibility and return a policy ready for runtime checks.
@@ -275,6 +324,41 @@ subscriptions = [{subscriptions}]
         assert_eq!(err.code(), error_code::UNAUTHORIZED);
     }
 
+    #[test]
+    fn log_subscription_needs_the_logs_entry() {
+        let manifest = manifest_with_permissions("^1.0.0", &[], &[], &["tick"]);
+        let err = policy_from_manifest(&manifest)
+            .unwrap()
+            .authorize_log_subscription()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            AuthorizationError::UnauthorizedSubscriptions(vec!["logs".to_string()])
+        );
+
+        let manifest = manifest_with_permissions("^1.0.0", &[], &[], &["tick", "logs"]);
+        let policy = policy_from_manifest(&manifest).unwrap();
+        assert!(policy.authorize_log_subscription().is_ok());
+    }
+
+    #[test]
+    fn authorize_register_event_schema_follows_publish_allowlist() {
+        let manifest = manifest_with_permissions("^1.0.0", &[], &["plugin.metrics"], &[]);
+        let policy = policy_from_manifest(&manifest).unwrap();
+
+        let params = |tag: &str| RegisterEventSchemaParams {
+            tag: tag.to_string(),
+            schema: serde_json::json!({ "type": "object" }),
+        };
+        assert!(policy
+            .authorize_register_event_schema(&params("plugin.metrics"))
+            .is_ok());
+        let err = policy
+            .authorize_register_event_schema(&params("other.tag"))
+            .unwrap_err();
+        assert!(matches!(err, AuthorizationError::UnauthorizedEventTag(_)));
+    }
+
     #[test]
     fn authorize_publish_event_accepts_allowlisted_tag() {
         let manifest = manifest_with_permissions("^1.0.0", &[], &["plugin.metrics"], &[]);
@@ -316,6 +400,23 @@ subscriptions = [{subscriptions}]
         assert_eq!(authorized, vec![EventCategory::Tick]);
     }
 
+    #[test]
+    fn telemetry_keys_filter_by_source_and_key() {
+        let mut manifest = manifest_with_permissions("^1.0.0", &[], &[], &[]);
+        let open = policy_from_manifest(&manifest).unwrap();
+        assert!(open.allows_telemetry_key("stats", "gpu.temp"));
+
+        manifest.permissions.telemetry_keys = Some(vec!["stats.cpu*".to_string()]);
+        let policy = policy_from_manifest(&manifest).unwrap();
+        assert!(policy.allows_telemetry_key("stats", "cpu.total"));
+        assert!(!policy.allows_telemetry_key("stats", "mem.used"));
+        assert!(!policy.allows_telemetry_key("plugin", "cpu"));
+
+        manifest.permissions.telemetry_keys = Some(Vec::new());
+        let closed = policy_from_manifest(&manifest).unwrap();
+        assert!(!closed.allows_telemetry_key("stats", "cpu.total"));
+    }
+
     #[test]
     fn policy_rejects_incompatible_host_api() {
         let manifest = manifest_with_permissions("^2.0.0", &[], &[], &[]);
@@ -325,6 +426,12 @@ subscriptions = [{subscriptions}]
         assert_eq!(err.code(), error_code::UNSUPPORTED_API_VERSION);
     }
 
+    #[test]
+    fn policy_accepts_manifests_pinned_to_older_host_api() {
+        let manifest = manifest_with_permissions("~1.0.0", &[], &[], &[]);
+        assert!(policy_from_manifest(&manifest).is_ok());
+    }
+
     #[test]
     fn reload_revalidation_applies_updated_permissions() {
         let initial = manifest_with_permissions("^1.0.0", &["help"], &[], &[]);
@@ -11,22 +11,116 @@ use serde_json::Value;
 pub const JSONRPC_VERSION: &str = "2.0";
 /// OpenRPC spec version for the embedded contract document.
 pub const OPENRPC_VERSION: &str = "1.3.2";
-/// Host API version negotiated during `spud.handshake`.
-pub const HOST_API_VERSION: &str = "1.0.0";
+/// Latest host API version negotiated during `spud.handshake`.
+pub const HOST_API_VERSION: &str = "1.7.0";
+
+/// Every host API version this host can speak, oldest first.
+///
+/// Negotiation selects the newest entry matching the plugin's requirement, so
+/// plugins pinned to `~1.0` keep the 1.0.0 method set.
+pub const SUPPORTED_HOST_API_VERSIONS: [&str; 8] = [
+    "1.0.0",
+    "1.1.0",
+    "1.2.0",
+    "1.3.0",
+    "1.4.0",
+    "1.5.0",
+    "1.6.0",
+    HOST_API_VERSION,
+];
+
+/// Default page size for `spud.state.get_telemetry`.
+pub const DEFAULT_TELEMETRY_PAGE_SIZE: usize = 100;
+/// Maximum page size for `spud.state.get_telemetry`.
+pub const MAX_TELEMETRY_PAGE_SIZE: usize = 1000;
+
+/// Log entries per second a `spud.logs.subscribe` subscriber gets when it
+/// does not set `max_per_sec`.
+pub const DEFAULT_LOG_RATE: u32 = 50;
+/// Highest `max_per_sec` a log subscriber may ask for.
+pub const MAX_LOG_RATE: u32 = 1000;
+
+/// Most hero lines one `spud.module.update` may carry.
+pub const MAX_MODULE_HERO_LINES: usize = 500;
+/// Most HUD lines one `spud.module.update` may carry.
+pub const MAX_MODULE_HUD_LINES: usize = 16;
 
 /// Embedded OpenRPC contract document (source of truth for method schema).
 pub const OPENRPC_SPEC_JSON: &str = include_str!("../openrpc/spud-plugin-host-v1.openrpc.json");
 
 /// Methods that must exist in the OpenRPC contract.
-pub const REQUIRED_METHODS: [&str; 6] = [
+pub const REQUIRED_METHODS: [&str; 15] = [
     "spud.handshake",
     "spud.state.get_snapshot",
+    "spud.state.get_telemetry",
     "spud.events.subscribe",
     "spud.events.unsubscribe",
+    "spud.events.register_schema",
     "spud.host.invoke_command",
     "spud.host.publish_event",
+    "spud.console.prompt",
+    "spud.logs.subscribe",
+    "spud.logs.unsubscribe",
+    "spud.module.update",
+    "spud.state.subscribe",
+    "spud.state.unsubscribe",
+    "spud.host.list_commands",
 ];
 
+/// JSON-RPC method names used on the plugin transport.
+pub mod method {
+    /// Plugin → host: negotiate API version and capabilities.
+    pub const HANDSHAKE: &str = "spud.handshake";
+    /// Plugin → host: fetch a read-only state snapshot.
+    pub const GET_SNAPSHOT: &str = "spud.state.get_snapshot";
+    /// Plugin → host: fetch filtered, paginated telemetry (since 1.1.0).
+    pub const GET_TELEMETRY: &str = "spud.state.get_telemetry";
+    /// Plugin → host: subscribe to event categories.
+    pub const SUBSCRIBE: &str = "spud.events.subscribe";
+    /// Plugin → host: unsubscribe from event categories.
+    pub const UNSUBSCRIBE: &str = "spud.events.unsubscribe";
+    /// Plugin → host: register a JSON Schema for a custom event tag (since
+    /// 1.2.0).
+    pub const REGISTER_EVENT_SCHEMA: &str = "spud.events.register_schema";
+    /// Plugin → host: execute a console command.
+    pub const INVOKE_COMMAND: &str = "spud.host.invoke_command";
+    /// Plugin → host: publish a custom event on the host bus.
+    pub const PUBLISH_EVENT: &str = "spud.host.publish_event";
+    /// Plugin → host: ask a question in the console input line (since
+    /// 1.3.0).
+    pub const CONSOLE_PROMPT: &str = "spud.console.prompt";
+    /// Plugin → host: stream matching log entries (since 1.4.0).
+    pub const LOGS_SUBSCRIBE: &str = "spud.logs.subscribe";
+    /// Plugin → host: stop the log stream (since 1.4.0).
+    pub const LOGS_UNSUBSCRIBE: &str = "spud.logs.unsubscribe";
+    /// Plugin → host: set the hero and HUD lines of the plugin's module
+    /// (since 1.5.0).
+    pub const MODULE_UPDATE: &str = "spud.module.update";
+    /// Plugin → host: get a snapshot and then be sent state changes
+    /// (since 1.6.0).
+    pub const STATE_SUBSCRIBE: &str = "spud.state.subscribe";
+    /// Plugin → host: stop state change notifications (since 1.6.0).
+    pub const STATE_UNSUBSCRIBE: &str = "spud.state.unsubscribe";
+    /// Plugin → host: list the console commands the plugin may invoke
+    /// (since 1.7.0).
+    pub const LIST_COMMANDS: &str = "spud.host.list_commands";
+    /// Host → plugin: event notification for subscribed categories.
+    pub const EVENT_NOTIFICATION: &str = "spud.events.emit";
+    /// Host → plugin: the line the user submitted in reply to a
+    /// `spud.console.prompt`.
+    pub const CONSOLE_ANSWER: &str = "spud.console.answer";
+    /// Host → plugin: one log entry for a `spud.logs.subscribe` subscriber.
+    pub const LOG_ENTRY: &str = "spud.logs.entry";
+    /// Host → plugin: the plugin's module was shown or hidden.
+    pub const MODULE_FOCUS: &str = "spud.module.focus";
+    /// Host → plugin: what changed in host state since the previous
+    /// notification, for a `spud.state.subscribe` subscriber.
+    pub const STATE_CHANGED: &str = "spud.state.changed";
+    /// Host → plugin: liveness ping, sent only when the manifest sets
+    /// `health.ping_interval_ms`. Any non-error result counts as an answer.
+    pub const HEALTH: &str = "spud.plugin.health";
+}
+
 /// Host JSON-RPC error codes.
 pub mod error_code {
     /// JSON-RPC standard invalid request error.
@@ -41,6 +135,9 @@ pub mod error_code {
     pub const UNAUTHORIZED: i32 = -32002;
     /// Plugin host transport/runtime is unavailable.
     pub const PLUGIN_UNAVAILABLE: i32 = -32003;
+    /// A published payload does not match the schema registered for its
+    /// tag; `data` is an [`InvalidEventPayload`](super::InvalidEventPayload).
+    pub const INVALID_EVENT_PAYLOAD: i32 = -32004;
 }
 
 /// JSON-RPC request/response ID type.
@@ -105,7 +202,7 @@ impl fmt::Display for HandshakeError {
             }
             Self::UnsupportedApiVersion { host, supported } => write!(
                 f,
-                "host API version {host} is not compatible with plugin requirement {supported}"
+                "host API versions [{host}] are not compatible with plugin requirement {supported}"
             ),
             Self::UnsupportedRequestedCapabilities(requested) => write!(
                 f,
@@ -128,7 +225,7 @@ impl fmt::Display for HandshakeError {
 impl std::error::Error for HandshakeError {}
 
 /// Plugin event categories exposed by host subscription methods.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventCategory {
     Tick,
@@ -202,9 +299,10 @@ pub struct TelemetryDatum {
     pub key: String,
     /// Forward-compatible telemetry payload value.
     ///
-    /// The OpenRPC v1 schema currently constrains this to scalar JSON types for
-    /// stability, while Rust keeps `Value` to permit safe widening in future
-    /// protocol revisions.
+    /// The OpenRPC v1 schema constrains this to `TelemetryValue`: a scalar,
+    /// `{"duration_ms"}`, or a `{"bounds", "counts", "sum"}` histogram,
+    /// while Rust keeps `Value` to permit safe widening in future protocol
+    /// revisions.
     pub value: Value,
 }
 
@@ -218,10 +316,74 @@ pub struct StateSnapshot {
     pub telemetry: Vec<TelemetryDatum>,
 }
 
+/// Parameters for `spud.state.get_telemetry`.
+///
+/// All filters are optional; omitted filters match everything.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GetTelemetryParams {
+    /// Only return samples from this exact source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Only return samples whose key starts with this prefix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_prefix: Option<String>,
+    /// Only return samples updated strictly after this Unix time (ms).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since_ms: Option<u64>,
+    /// Opaque cursor returned as `next_cursor` by a previous page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    /// Page size; defaults to [`DEFAULT_TELEMETRY_PAGE_SIZE`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// A telemetry value with its last update time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TelemetrySample {
+    pub source: String,
+    pub key: String,
+    pub value: Value,
+    /// Unix timestamp in milliseconds of the latest update.
+    pub updated_at_ms: u64,
+}
+
+/// Result payload for `spud.state.get_telemetry`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GetTelemetryResult {
+    pub items: Vec<TelemetrySample>,
+    /// Cursor for the next page; `None` when no further items match.
+    pub next_cursor: Option<String>,
+}
+
 /// Parameters for subscription methods.
+///
+/// The optional filters apply to every category in the request and replace
+/// any filters from an earlier subscription to the same category. They are
+/// ignored by `spud.events.unsubscribe`.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SubscribeParams {
     pub categories: Vec<EventCategory>,
+    /// Tag globs (`*` matches any run, `?` one character). When non-empty,
+    /// only events whose tag matches a pattern are delivered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Deliver at most one event per category per interval; later events
+    /// inside the window are dropped, not queued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval_ms: Option<u64>,
+}
+
+impl SubscribeParams {
+    /// Subscribe to `categories` with no filters.
+    pub fn new(categories: Vec<EventCategory>) -> Self {
+        Self {
+            categories,
+            tags: Vec::new(),
+            min_interval_ms: None,
+        }
+    }
 }
 
 /// Result payload for subscription methods.
@@ -241,7 +403,40 @@ pub struct InvokeCommandParams {
 /// Result payload for `spud.host.invoke_command`.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct InvokeCommandResult {
+    /// Output rendered as console text.
     pub lines: Vec<String>,
+    /// Whether the command reported failure.
+    #[serde(default)]
+    pub is_error: bool,
+    /// Structured output (`status`, `kind`, and kind-specific fields), when
+    /// the host provides it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+}
+
+/// Parameters for `spud.host.list_commands`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListCommandsParams {}
+
+/// A console command as listed by `spud.host.list_commands`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandInfo {
+    pub name: String,
+    /// Other names the command answers to.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Usage line, e.g. `hud [height <rows>|reset]`.
+    pub usage: String,
+    pub description: String,
+}
+
+/// Result payload for `spud.host.list_commands`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ListCommandsResult {
+    /// Commands in the plugin's `permissions.commands` allowlist, sorted by
+    /// name.
+    pub commands: Vec<CommandInfo>,
 }
 
 /// Parameters for `spud.host.publish_event`.
@@ -257,6 +452,250 @@ pub struct PublishEventResult {
     pub accepted: bool,
 }
 
+/// Parameters for `spud.events.register_schema`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegisterEventSchemaParams {
+    /// Custom event tag the schema applies to.
+    pub tag: String,
+    /// JSON Schema for the tag's payloads.
+    pub schema: Value,
+}
+
+/// Result payload for `spud.events.register_schema`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterEventSchemaResult {
+    pub registered: bool,
+}
+
+/// Parameters for `spud.console.prompt`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolePromptParams {
+    /// Shown in place of the console's input marker.
+    pub question: String,
+    /// Opaque value sent back with the answer.
+    pub callback_token: String,
+}
+
+/// Result payload for `spud.console.prompt`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsolePromptResult {
+    /// `false` if another prompt already owns the input line.
+    pub accepted: bool,
+}
+
+/// Params of the host → plugin `spud.console.answer` notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsoleAnswerParams {
+    pub callback_token: String,
+    pub answer: String,
+    /// The prompt was dropped without an answer (Esc, or the host needed
+    /// the input line); `answer` is empty.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+}
+
+/// Severity of a streamed log entry, least severe first.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSeverity {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+/// Parameters for `spud.logs.subscribe`.
+///
+/// A new subscription replaces the previous one. Only entries that pass
+/// the host's own log filter (`log level`) can be streamed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogSubscribeParams {
+    /// Least severe level delivered.
+    #[serde(default)]
+    pub min_level: LogSeverity,
+    /// Target globs (`*` matches any run, `?` one character). When
+    /// non-empty, only entries whose target matches a pattern are
+    /// delivered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// Only deliver entries whose message contains this text, ignoring
+    /// case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+    /// Most entries delivered per second, from 1 to [`MAX_LOG_RATE`];
+    /// [`DEFAULT_LOG_RATE`] when absent. Entries over the rate are dropped
+    /// and counted in the next delivered entry's `dropped`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_per_sec: Option<u32>,
+}
+
+/// Result payload for `spud.logs.subscribe` and `spud.logs.unsubscribe`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogSubscriptionResult {
+    pub subscribed: bool,
+    /// Rate the host applies; absent after unsubscribing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_per_sec: Option<u32>,
+}
+
+/// Parameters for `spud.logs.unsubscribe`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogUnsubscribeParams {}
+
+/// Params of the host → plugin `spud.logs.entry` notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntryParams {
+    pub level: LogSeverity,
+    pub target: String,
+    pub message: String,
+    /// Matching entries dropped by the rate limit since the previous
+    /// notification.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub dropped: u64,
+}
+
+fn is_zero(count: &u64) -> bool {
+    *count == 0
+}
+
+/// Parameters for `spud.module.update`. An absent field keeps what the
+/// module showed before.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleUpdateParams {
+    /// Lines drawn in the hero area, at most [`MAX_MODULE_HERO_LINES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hero: Option<Vec<String>>,
+    /// Lines shown in the HUD while the module is active, at most
+    /// [`MAX_MODULE_HUD_LINES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hud: Option<Vec<String>>,
+}
+
+/// Result payload for `spud.module.update`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleUpdateResult {
+    /// Whether the module is on screen now.
+    pub active: bool,
+}
+
+/// Params of the host → plugin `spud.module.focus` notification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModuleFocusParams {
+    pub module_id: String,
+    /// `true` when the module became active, `false` when it was left.
+    pub active: bool,
+}
+
+/// Parameters for `spud.state.subscribe`. The telemetry filters match
+/// those of `spud.state.get_telemetry`; omitted filters match everything.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateSubscribeParams {
+    /// Only report telemetry from this exact source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Only report telemetry whose key starts with this prefix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_prefix: Option<String>,
+}
+
+/// Result payload for `spud.state.subscribe`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateSubscribeResult {
+    /// Sequence number the snapshot is current at. The next
+    /// `spud.state.changed` carries `seq + 1`; notifications at or below
+    /// it are already reflected in the snapshot.
+    pub seq: u64,
+    pub snapshot: StateSnapshot,
+}
+
+/// Parameters for `spud.state.unsubscribe`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateUnsubscribeParams {}
+
+/// Result payload for `spud.state.unsubscribe`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateUnsubscribeResult {
+    /// Whether there was a subscription to stop.
+    pub unsubscribed: bool,
+}
+
+/// One change in host state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StateChange {
+    /// Another module became active; `None` once none is.
+    ActiveModule {
+        module: Option<ActiveModule>,
+    },
+    StatusLine {
+        status_line: String,
+    },
+    /// A telemetry key got a new value.
+    Telemetry(TelemetryDatum),
+}
+
+/// Params of the host → plugin `spud.state.changed` notification.
+///
+/// `seq` goes up by one per notification. A gap means notifications were
+/// dropped under backpressure; call `spud.state.subscribe` again to
+/// resync.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StateChangedParams {
+    pub seq: u64,
+    pub changes: Vec<StateChange>,
+}
+
+/// One way a payload fails its tag's schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadViolation {
+    /// JSON Pointer to the offending value; empty for the whole payload.
+    pub path: String,
+    pub message: String,
+}
+
+/// `data` of an [`error_code::INVALID_EVENT_PAYLOAD`] error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvalidEventPayload {
+    pub tag: String,
+    pub violations: Vec<PayloadViolation>,
+}
+
+impl InvalidEventPayload {
+    /// Convert to a JSON-RPC error payload.
+    pub fn to_jsonrpc_error(&self) -> JsonRpcError {
+        let summary = self
+            .violations
+            .first()
+            .map(|violation| match violation.path.as_str() {
+                "" => violation.message.clone(),
+                path => format!("{path}: {}", violation.message),
+            })
+            .unwrap_or_default();
+        JsonRpcError {
+            code: error_code::INVALID_EVENT_PAYLOAD,
+            message: format!(
+                "payload for {} does not match its schema: {summary}",
+                self.tag
+            ),
+            data: serde_json::to_value(self).ok(),
+        }
+    }
+}
+
+/// Params of the host → plugin `spud.events.emit` notification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventNotificationParams {
+    pub category: EventCategory,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    pub payload: Value,
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenRpcDocument {
     openrpc: String,
@@ -266,6 +705,9 @@ struct OpenRpcDocument {
 #[derive(Debug, Deserialize)]
 struct OpenRpcMethod {
     name: String,
+    /// First host API version exposing the method (absent means 1.0.0).
+    #[serde(rename = "x-since", default)]
+    since: Option<String>,
 }
 
 static OPENRPC_DOCUMENT: LazyLock<std::result::Result<OpenRpcDocument, String>> =
@@ -312,12 +754,41 @@ pub fn openrpc_method_names() -> Result<Vec<String>> {
 
 /// Host capability snapshot derived from OpenRPC contract and event categories.
 pub fn host_capabilities() -> Result<HostCapabilities> {
+    host_capabilities_for(HOST_API_VERSION)
+}
+
+/// Host capabilities restricted to methods available in `api_version`.
+pub fn host_capabilities_for(api_version: &str) -> Result<HostCapabilities> {
+    let version = Version::parse(api_version)
+        .with_context(|| format!("invalid host API version: {api_version}"))?;
+    let doc = openrpc_document().context("failed to load embedded OpenRPC document")?;
+
+    let mut methods = Vec::new();
+    for method in &doc.methods {
+        let since = match &method.since {
+            Some(since) => Version::parse(since).with_context(|| {
+                format!("invalid x-since version on method {}: {since}", method.name)
+            })?,
+            None => Version::new(1, 0, 0),
+        };
+        if since <= version {
+            methods.push(method.name.clone());
+        }
+    }
+
     Ok(HostCapabilities {
-        methods: openrpc_method_names()?,
+        methods,
         event_categories: EventCategory::ALL.to_vec(),
     })
 }
 
+/// Returns `true` when `method` is part of the negotiated `api_version`.
+pub fn method_available(method: &str, api_version: &str) -> bool {
+    host_capabilities_for(api_version)
+        .map(|caps| caps.methods.iter().any(|name| name == method))
+        .unwrap_or(false)
+}
+
 /// Negotiate host API version against plugin semver requirement string.
 pub fn negotiate_api_version(
     supported_api_versions: &str,
@@ -326,17 +797,18 @@ pub fn negotiate_api_version(
         HandshakeError::InvalidVersionRequirement(supported_api_versions.to_string())
     })?;
 
-    let host = Version::parse(HOST_API_VERSION)
-        .map_err(|_| HandshakeError::HostApiVersionInvalid(HOST_API_VERSION.to_string()))?;
-
-    if supported.matches(&host) {
-        Ok(host.to_string())
-    } else {
-        Err(HandshakeError::UnsupportedApiVersion {
-            host: host.to_string(),
-            supported: supported_api_versions.to_string(),
-        })
+    for candidate in SUPPORTED_HOST_API_VERSIONS.iter().rev() {
+        let host = Version::parse(candidate)
+            .map_err(|_| HandshakeError::HostApiVersionInvalid(candidate.to_string()))?;
+        if supported.matches(&host) {
+            return Ok(host.to_string());
+        }
     }
+
+    Err(HandshakeError::UnsupportedApiVersion {
+        host: SUPPORTED_HOST_API_VERSIONS.join(", "),
+        supported: supported_api_versions.to_string(),
+    })
 }
 
 /// Build a handshake result from plugin params using version negotiation.
@@ -344,7 +816,7 @@ pub fn build_handshake_result(
     params: &HandshakeParams,
 ) -> std::result::Result<HandshakeResult, HandshakeError> {
     let selected = negotiate_api_version(&params.supported_api_versions)?;
-    let all_capabilities = host_capabilities()
+    let all_capabilities = host_capabilities_for(&selected)
         .map_err(|err| HandshakeError::HostCapabilitiesUnavailable(err.to_string()))?;
     let capabilities = filter_host_capabilities(all_capabilities, &params.requested_capabilities)?;
 
@@ -354,6 +826,97 @@ pub fn build_handshake_result(
     })
 }
 
+/// Apply `spud.state.get_telemetry` filters and pagination to host samples.
+///
+/// Samples are ordered by `(source, key)` and the cursor is keyset-based, so
+/// pages stay stable while values update between polls. Returns a message
+/// suitable for an `INVALID_PARAMS` error when the params are unusable.
+pub fn page_telemetry(
+    mut samples: Vec<TelemetrySample>,
+    params: &GetTelemetryParams,
+) -> std::result::Result<GetTelemetryResult, String> {
+    let limit = params.limit.unwrap_or(DEFAULT_TELEMETRY_PAGE_SIZE);
+    if limit == 0 {
+        return Err("limit must be at least 1".to_string());
+    }
+    let limit = limit.min(MAX_TELEMETRY_PAGE_SIZE);
+
+    let after = match &params.cursor {
+        Some(cursor) => Some(
+            serde_json::from_str::<(String, String)>(cursor)
+                .map_err(|_| format!("invalid telemetry cursor: {cursor}"))?,
+        ),
+        None => None,
+    };
+
+    samples.retain(|sample| {
+        params
+            .source
+            .as_ref()
+            .is_none_or(|source| &sample.source == source)
+            && params
+                .key_prefix
+                .as_ref()
+                .is_none_or(|prefix| sample.key.starts_with(prefix.as_str()))
+            && params
+                .since_ms
+                .is_none_or(|since| sample.updated_at_ms > since)
+            && after.as_ref().is_none_or(|(source, key)| {
+                (sample.source.as_str(), sample.key.as_str()) > (source.as_str(), key.as_str())
+            })
+    });
+    samples.sort_by(|a, b| (&a.source, &a.key).cmp(&(&b.source, &b.key)));
+
+    let has_more = samples.len() > limit;
+    samples.truncate(limit);
+    let next_cursor = if has_more {
+        samples
+            .last()
+            .map(|last| serde_json::json!([last.source, last.key]).to_string())
+    } else {
+        None
+    };
+
+    Ok(GetTelemetryResult {
+        items: samples,
+        next_cursor,
+    })
+}
+
+/// Match an event tag against a subscription glob.
+///
+/// `*` matches any (possibly empty) run of characters and `?` matches
+/// exactly one; everything else matches literally.
+pub fn tag_glob_matches(pattern: &str, tag: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let tag: Vec<char> = tag.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < tag.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == tag[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 fn filter_host_capabilities(
     all: HostCapabilities,
     requested: &[String],
@@ -413,6 +976,149 @@ mod tests {
         assert_eq!(selected, HOST_API_VERSION);
     }
 
+    #[test]
+    fn negotiate_api_version_selects_older_version_for_pinned_plugins() {
+        assert_eq!(negotiate_api_version("~1.0").unwrap(), "1.0.0");
+        assert_eq!(negotiate_api_version("~1.1").unwrap(), "1.1.0");
+        assert_eq!(negotiate_api_version("~1.2").unwrap(), "1.2.0");
+        assert_eq!(negotiate_api_version("~1.3").unwrap(), "1.3.0");
+        assert_eq!(negotiate_api_version("~1.4").unwrap(), "1.4.0");
+        assert_eq!(negotiate_api_version("~1.5").unwrap(), "1.5.0");
+        assert_eq!(negotiate_api_version("~1.6").unwrap(), "1.6.0");
+        assert_eq!(negotiate_api_version(">=1.1").unwrap(), HOST_API_VERSION);
+    }
+
+    #[test]
+    fn capabilities_exclude_methods_newer_than_selected_version() {
+        let v1_0 = host_capabilities_for("1.0.0").unwrap();
+        assert!(!v1_0.methods.iter().any(|m| m == method::GET_TELEMETRY));
+        assert_eq!(v1_0.methods.len(), REQUIRED_METHODS.len() - 9);
+        assert!(method_available(method::GET_TELEMETRY, "1.1.0"));
+        assert!(!method_available(method::REGISTER_EVENT_SCHEMA, "1.1.0"));
+        assert!(method_available(method::REGISTER_EVENT_SCHEMA, "1.2.0"));
+        assert!(!method_available(method::CONSOLE_PROMPT, "1.2.0"));
+        assert!(method_available(method::CONSOLE_PROMPT, "1.3.0"));
+        assert!(!method_available(method::LOGS_SUBSCRIBE, "1.3.0"));
+        assert!(method_available(method::LOGS_SUBSCRIBE, "1.4.0"));
+        assert!(method_available(method::LOGS_UNSUBSCRIBE, "1.4.0"));
+        assert!(!method_available(method::MODULE_UPDATE, "1.4.0"));
+        assert!(method_available(method::MODULE_UPDATE, "1.5.0"));
+        assert!(!method_available(method::STATE_SUBSCRIBE, "1.5.0"));
+        assert!(method_available(method::STATE_SUBSCRIBE, "1.6.0"));
+        assert!(method_available(method::STATE_UNSUBSCRIBE, "1.6.0"));
+        assert!(!method_available(method::LIST_COMMANDS, "1.6.0"));
+        assert!(method_available(method::LIST_COMMANDS, "1.7.0"));
+        assert!(!method_available(method::GET_TELEMETRY, "1.0.0"));
+        assert!(method_available(method::GET_SNAPSHOT, "1.0.0"));
+    }
+
+    #[test]
+    fn invalid_event_payload_error_carries_violations() {
+        let error = InvalidEventPayload {
+            tag: "demo.ping".into(),
+            violations: vec![PayloadViolation {
+                path: "/n".into(),
+                message: "expected integer, found string".into(),
+            }],
+        }
+        .to_jsonrpc_error();
+        assert_eq!(error.code, error_code::INVALID_EVENT_PAYLOAD);
+        assert_eq!(
+            error.message,
+            "payload for demo.ping does not match its schema: /n: expected integer, found string"
+        );
+        let data: InvalidEventPayload = serde_json::from_value(error.data.unwrap()).unwrap();
+        assert_eq!(data.violations[0].path, "/n");
+    }
+
+    fn sample(source: &str, key: &str, updated_at_ms: u64) -> TelemetrySample {
+        TelemetrySample {
+            source: source.to_string(),
+            key: key.to_string(),
+            value: Value::from(1),
+            updated_at_ms,
+        }
+    }
+
+    #[test]
+    fn page_telemetry_filters_by_source_prefix_and_since() {
+        let samples = vec![
+            sample("stats", "cpu.total", 10),
+            sample("stats", "cpu.core0", 30),
+            sample("stats", "mem.used", 30),
+            sample("hello", "cpu.total", 30),
+        ];
+        let params = GetTelemetryParams {
+            source: Some("stats".to_string()),
+            key_prefix: Some("cpu.".to_string()),
+            since_ms: Some(20),
+            ..Default::default()
+        };
+
+        let page = page_telemetry(samples, &params).unwrap();
+        assert_eq!(page.items, vec![sample("stats", "cpu.core0", 30)]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn page_telemetry_walks_pages_with_cursor() {
+        let samples: Vec<_> = ["d", "b", "a", "c", "e"]
+            .iter()
+            .map(|key| sample("stats", key, 1))
+            .collect();
+        let mut params = GetTelemetryParams {
+            limit: Some(2),
+            ..Default::default()
+        };
+
+        let mut keys = Vec::new();
+        loop {
+            let page = page_telemetry(samples.clone(), &params).unwrap();
+            keys.extend(page.items.into_iter().map(|item| item.key));
+            match page.next_cursor {
+                Some(cursor) => params.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(keys, vec!["a", "b", "c", "d", "e"]);
+    }
+
+    #[test]
+    fn page_telemetry_rejects_bad_params() {
+        let zero = GetTelemetryParams {
+            limit: Some(0),
+            ..Default::default()
+        };
+        assert!(page_telemetry(Vec::new(), &zero).is_err());
+
+        let bad_cursor = GetTelemetryParams {
+            cursor: Some("nope".to_string()),
+            ..Default::default()
+        };
+        assert!(page_telemetry(Vec::new(), &bad_cursor).is_err());
+    }
+
+    #[test]
+    fn tag_glob_matches_wildcards() {
+        assert!(tag_glob_matches("module.*", "module.activated"));
+        assert!(tag_glob_matches("*", ""));
+        assert!(tag_glob_matches("a?c", "abc"));
+        assert!(tag_glob_matches("*.metrics.*", "plugin.metrics.cpu"));
+        assert!(!tag_glob_matches("module.*", "plugin.module.x"));
+        assert!(!tag_glob_matches("a?c", "ac"));
+        assert!(!tag_glob_matches("exact", "exactly"));
+    }
+
+    #[test]
+    fn subscribe_params_filters_are_optional_on_the_wire() {
+        let params: SubscribeParams = serde_json::from_str(r#"{"categories":["tick"]}"#).unwrap();
+        assert_eq!(params, SubscribeParams::new(vec![EventCategory::Tick]));
+        assert_eq!(
+            serde_json::to_string(&params).unwrap(),
+            r#"{"categories":["tick"]}"#
+        );
+    }
+
     #[test]
     fn negotiate_api_version_rejects_incompatible_range() {
         let err = negotiate_api_version("^2.0").unwrap_err();
@@ -484,4 +1190,24 @@ mod tests {
         ));
         assert_eq!(err.code(), error_code::INVALID_PARAMS);
     }
+
+    #[test]
+    fn console_answers_mention_cancellation_only_when_cancelled() {
+        let answer = ConsoleAnswerParams {
+            callback_token: "name".to_string(),
+            answer: "Ada".to_string(),
+            cancelled: false,
+        };
+        let json = serde_json::to_value(&answer).unwrap();
+        assert!(json.get("cancelled").is_none());
+        let parsed: ConsoleAnswerParams = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, answer);
+
+        let cancelled = ConsoleAnswerParams {
+            answer: String::new(),
+            cancelled: true,
+            ..answer
+        };
+        assert_eq!(serde_json::to_value(&cancelled).unwrap()["cancelled"], true);
+    }
 }
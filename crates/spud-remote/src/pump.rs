@@ -0,0 +1,957 @@
+//! Background thread that services plugin sessions.
+//!
+//! [`PluginPump`] takes request handling (frame validation, permission
+//! checks, audit writes, response encoding) off the host's main loop. Only
+//! the [`HostBridge`] calls need host state: the pump thread sends each one
+//! back through a bounded queue and waits for the answer, and the main loop
+//! answers queued calls with [`PluginPump::serve`] under a time budget. A
+//! burst of plugin requests therefore slows the plugins, not the frames.
+//!
+//! The main loop never waits for the runtime on its hot paths: broadcasts
+//! and other fire-and-forget work go to the pump thread with
+//! [`PluginPump::submit`], and sessions and counters are read from the
+//! [`RuntimeView`] the pump thread publishes. [`PluginPump::with_runtime`]
+//! is left for console commands.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use spud_config::PluginManifest;
+
+use crate::health::HealthStatus;
+use crate::metrics::PluginStats;
+use crate::protocol::{
+    CommandInfo, InvokeCommandParams, InvokeCommandResult, PublishEventParams, PublishEventResult,
+    RegisterEventSchemaParams, RegisterEventSchemaResult, StateSnapshot, TelemetrySample,
+};
+use crate::runtime::{
+    HandledRequest, HostBridge, PendingSession, PluginRuntime, PluginSessionSnapshot, RuntimeError,
+};
+
+/// Host calls that may wait for the main loop. The pump thread blocks on
+/// each answer, so more than one is only queued while it is shutting down.
+const CALL_QUEUE_CAPACITY: usize = 1;
+/// Reports kept for [`PluginPump::drain_reports`]; newer ones are dropped
+/// while the host is not reading them.
+const REPORT_CAPACITY: usize = 256;
+/// Pause after a round in which no plugin had a request waiting.
+const IDLE_WAIT: Duration = Duration::from_millis(2);
+/// Handling time each plugin gets per round before the next plugin's turn.
+const PLUGIN_QUANTUM: Duration = Duration::from_millis(2);
+/// Most handling time a plugin can bank, or owe, across rounds.
+const MAX_CARRY_OVER: Duration = Duration::from_millis(8);
+/// Least time charged per request, so a flood of instant requests still
+/// ends a turn.
+const MIN_REQUEST_COST: Duration = Duration::from_micros(100);
+/// Requests handled in one turn at most, whatever their cost.
+const MAX_REQUESTS_PER_TURN: usize = 16;
+/// Longest the [`RuntimeView`] goes without a refresh.
+pub const VIEW_INTERVAL: Duration = Duration::from_millis(50);
+/// How often each side checks whether the other let go of the runtime in
+/// [`PluginPump::with_runtime`].
+const HANDOFF_WAIT: Duration = Duration::from_micros(200);
+
+/// A [`HostBridge`] call waiting to run on the host's thread.
+type HostCall = Box<dyn FnOnce(&mut dyn HostBridge) + Send>;
+/// Work for the pump thread, labelled for [`PumpReport::JobFailed`].
+type Job = (
+    &'static str,
+    Box<dyn FnOnce(&mut PluginRuntime) -> std::result::Result<(), RuntimeError> + Send>,
+);
+
+/// What happened to one pumped request, for the host to log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PumpReport {
+    Handled(HandledRequest),
+    /// The plugin process exited and its session was detached.
+    Exited {
+        plugin_id: String,
+    },
+    Failed {
+        plugin_id: String,
+        error: RuntimeError,
+    },
+    /// Health pings changed the plugin's status.
+    Health {
+        plugin_id: String,
+        status: HealthStatus,
+    },
+    /// A plugin started with [`PluginPump::start_plugin`] completed its
+    /// handshake.
+    Started {
+        plugin_id: String,
+    },
+    /// A plugin was replaced by a new process, after failing its health
+    /// pings or on request.
+    Restarted {
+        plugin_id: String,
+    },
+    /// A line a dev mode plugin wrote to stderr.
+    Stderr {
+        plugin_id: String,
+        line: String,
+    },
+    /// Work sent with [`PluginPump::submit`] failed.
+    JobFailed {
+        what: &'static str,
+        error: RuntimeError,
+    },
+}
+
+/// Sessions and counters as of the pump's last refresh, at most
+/// [`VIEW_INTERVAL`] old and current after every start and every
+/// [`PluginPump::with_runtime`]. All three lists are in plugin ID order.
+#[derive(Debug, Clone, Default)]
+pub struct RuntimeView {
+    pub sessions: Vec<PluginSessionSnapshot>,
+    pub stats: Vec<PluginStats>,
+    pub manifests: Vec<PluginManifest>,
+}
+
+impl RuntimeView {
+    fn of(runtime: &PluginRuntime) -> Self {
+        let sessions = runtime.sessions();
+        let manifests = sessions
+            .iter()
+            .filter_map(|session| runtime.manifest(&session.plugin_id).cloned())
+            .collect();
+        Self {
+            stats: runtime.stats(),
+            sessions,
+            manifests,
+        }
+    }
+}
+
+/// State shared by the host, the pump thread, and handshake threads.
+struct Shared {
+    runtime: Mutex<PluginRuntime>,
+    view: Mutex<Arc<RuntimeView>>,
+    stop: AtomicBool,
+    /// Set while the host's event bus is full: no new requests are read.
+    paused: AtomicBool,
+    /// Set while the host waits in [`PluginPump::with_runtime`]; the pump
+    /// thread lets it go first.
+    host_waiting: AtomicBool,
+}
+
+impl Shared {
+    /// Lock the runtime for the pump side, after any waiting host.
+    fn lock(&self) -> MutexGuard<'_, PluginRuntime> {
+        while self.host_waiting.load(Ordering::Acquire) && !self.stop.load(Ordering::Relaxed) {
+            thread::sleep(HANDOFF_WAIT);
+        }
+        lock(&self.runtime)
+    }
+
+    fn publish_view(&self, runtime: &PluginRuntime) {
+        let view = Arc::new(RuntimeView::of(runtime));
+        *self
+            .view
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = view;
+    }
+}
+
+/// Owns a [`PluginRuntime`] and pumps its sessions on a dedicated thread.
+///
+/// Dropping the pump stops the thread and every plugin session.
+pub struct PluginPump {
+    shared: Arc<Shared>,
+    calls: Receiver<HostCall>,
+    jobs: Sender<Job>,
+    reports: Receiver<PumpReport>,
+    report_tx: SyncSender<PumpReport>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PluginPump {
+    /// Start pumping `runtime`'s running sessions.
+    pub fn spawn(runtime: PluginRuntime) -> std::io::Result<Self> {
+        let view = Arc::new(RuntimeView::of(&runtime));
+        let shared = Arc::new(Shared {
+            runtime: Mutex::new(runtime),
+            view: Mutex::new(view),
+            stop: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            host_waiting: AtomicBool::new(false),
+        });
+        let (call_tx, calls) = mpsc::sync_channel(CALL_QUEUE_CAPACITY);
+        let (jobs, job_rx) = mpsc::channel();
+        let (report_tx, reports) = mpsc::sync_channel(REPORT_CAPACITY);
+        let thread = {
+            let shared = Arc::clone(&shared);
+            let report_tx = report_tx.clone();
+            thread::Builder::new()
+                .name("spud-plugin-pump".to_string())
+                .spawn(move || pump_loop(&shared, call_tx, &job_rx, &report_tx))?
+        };
+        Ok(Self {
+            shared,
+            calls,
+            jobs,
+            reports,
+            report_tx,
+            thread: Some(thread),
+        })
+    }
+
+    /// Queue `job` to run on the pump thread between requests, without
+    /// waiting for it. An error comes back as [`PumpReport::JobFailed`]
+    /// labelled `what`.
+    pub fn submit(
+        &self,
+        what: &'static str,
+        job: impl FnOnce(&mut PluginRuntime) -> std::result::Result<(), RuntimeError> + Send + 'static,
+    ) {
+        let _ = self.jobs.send((what, Box::new(job)));
+    }
+
+    /// The runtime's sessions and counters, without waiting for it.
+    pub fn view(&self) -> Arc<RuntimeView> {
+        Arc::clone(
+            &self
+                .shared
+                .view
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+
+    /// Stop or resume reading plugin requests, e.g. while the host's event
+    /// bus is full. Submitted jobs still run.
+    pub fn set_paused(&self, paused: bool) {
+        self.shared.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Answer queued host calls against `host` for up to `budget`. Returns
+    /// at once when none is waiting; otherwise keeps answering follow-up
+    /// calls until the budget is spent. Returns the number answered.
+    pub fn serve(&self, host: &mut dyn HostBridge, budget: Duration) -> usize {
+        let deadline = Instant::now() + budget;
+        let mut served = 0;
+        let mut next = self.calls.try_recv().ok();
+        while let Some(call) = next {
+            call(host);
+            served += 1;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            next = self.calls.recv_timeout(remaining).ok();
+        }
+        served
+    }
+
+    /// Run `f` with the runtime, for console commands that need an answer
+    /// straight away; per-frame work should use [`Self::submit`] and
+    /// [`Self::view`] instead. The pump thread lets the runtime go after
+    /// its current request. If that request is waiting on a host call, the
+    /// call is answered against `host` so the two threads cannot deadlock.
+    pub fn with_runtime<R>(
+        &self,
+        host: &mut dyn HostBridge,
+        f: impl FnOnce(&mut PluginRuntime) -> R,
+    ) -> R {
+        self.shared.host_waiting.store(true, Ordering::Release);
+        let result = loop {
+            let mut runtime = match self.shared.runtime.try_lock() {
+                Ok(runtime) => runtime,
+                Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                Err(TryLockError::WouldBlock) => {
+                    if let Ok(call) = self.calls.recv_timeout(HANDOFF_WAIT) {
+                        call(host);
+                    }
+                    continue;
+                }
+            };
+            let result = f(&mut runtime);
+            self.shared.publish_view(&runtime);
+            break result;
+        };
+        self.shared.host_waiting.store(false, Ordering::Release);
+        result
+    }
+
+    /// Start a stopped plugin, or with `restart` replace its running
+    /// process. Spawn errors are returned; the handshake runs on its own
+    /// thread and ends in [`PumpReport::Started`], [`PumpReport::Restarted`],
+    /// or [`PumpReport::Failed`].
+    pub fn start_plugin(
+        &self,
+        host: &mut dyn HostBridge,
+        plugin_id: &str,
+        restart: bool,
+    ) -> std::result::Result<(), RuntimeError> {
+        let pending = self.with_runtime(host, |runtime| {
+            if restart {
+                runtime.shutdown_plugin(plugin_id)?;
+            }
+            runtime.spawn(plugin_id)
+        })?;
+        finish_start(&self.shared, pending, restart, &self.report_tx);
+        Ok(())
+    }
+
+    /// Reports of requests pumped since the last call, oldest first.
+    pub fn drain_reports(&self) -> Vec<PumpReport> {
+        self.reports.try_iter().collect()
+    }
+}
+
+impl Drop for PluginPump {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        // Refuse calls still in flight so the pump thread can finish its
+        // current request and see the stop flag.
+        while !thread.is_finished() {
+            while let Ok(call) = self.calls.recv_timeout(IDLE_WAIT) {
+                drop(call);
+            }
+        }
+        let _ = thread.join();
+    }
+}
+
+fn pump_loop(
+    shared: &Arc<Shared>,
+    calls: SyncSender<HostCall>,
+    jobs: &Receiver<Job>,
+    reports: &SyncSender<PumpReport>,
+) {
+    let mut bridge = ChannelBridge { calls };
+    let mut scheduler = Scheduler::default();
+    let mut viewed_at = Instant::now();
+    while !shared.stop.load(Ordering::Relaxed) {
+        run_jobs(shared, jobs, reports);
+        if viewed_at.elapsed() >= VIEW_INTERVAL {
+            shared.publish_view(&shared.lock());
+            viewed_at = Instant::now();
+        }
+        if shared.paused.load(Ordering::Relaxed) {
+            thread::sleep(IDLE_WAIT);
+            continue;
+        }
+        let plugin_ids = scheduler.round(
+            shared
+                .lock()
+                .plugin_ids()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        );
+        let mut handled_any = false;
+        let mut deferred_any = false;
+        for plugin_id in plugin_ids {
+            run_jobs(shared, jobs, reports);
+            let (health, restart_unhealthy, stderr) = {
+                let mut runtime = shared.lock();
+                let health = runtime.poll_health(&plugin_id);
+                let restart = runtime
+                    .health_policy(&plugin_id)
+                    .is_some_and(|policy| policy.restart);
+                (health, restart, runtime.drain_stderr(&plugin_id))
+            };
+            for line in stderr {
+                let _ = reports.try_send(PumpReport::Stderr {
+                    plugin_id: plugin_id.clone(),
+                    line,
+                });
+            }
+            if let Some(status) = health {
+                let _ = reports.try_send(PumpReport::Health {
+                    plugin_id: plugin_id.clone(),
+                    status,
+                });
+                if status == HealthStatus::Unhealthy && restart_unhealthy {
+                    restart(shared, &plugin_id, reports);
+                    continue;
+                }
+            }
+
+            let mut turn_open = scheduler.begin_turn(&plugin_id);
+            deferred_any |= !turn_open;
+            let mut handled = 0;
+            while turn_open && handled < MAX_REQUESTS_PER_TURN {
+                if shared.stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                if shared.paused.load(Ordering::Relaxed) {
+                    break;
+                }
+                let result = shared
+                    .lock()
+                    .pump_next(&plugin_id, &mut bridge, Duration::ZERO);
+                let report = match result {
+                    Ok(request) => {
+                        handled += 1;
+                        turn_open = scheduler.charge(&plugin_id, request.latency);
+                        PumpReport::Handled(request)
+                    }
+                    Err(RuntimeError::Timeout { .. } | RuntimeError::NotRunning(_)) => {
+                        scheduler.idle(&plugin_id);
+                        break;
+                    }
+                    Err(RuntimeError::ProcessExited { .. }) => PumpReport::Exited {
+                        plugin_id: plugin_id.clone(),
+                    },
+                    Err(error) => PumpReport::Failed {
+                        plugin_id: plugin_id.clone(),
+                        error,
+                    },
+                };
+                let done = !matches!(report, PumpReport::Handled(_));
+                let _ = reports.try_send(report);
+                if done {
+                    break;
+                }
+            }
+            handled_any |= handled > 0;
+        }
+        // A plugin paying back an overrun may have requests waiting; only
+        // rest once every plugin has had a turn and found nothing to do.
+        if !handled_any && !deferred_any {
+            thread::sleep(IDLE_WAIT);
+        }
+    }
+}
+
+/// Run the jobs the host submitted since the last call, in order.
+fn run_jobs(shared: &Shared, jobs: &Receiver<Job>, reports: &SyncSender<PumpReport>) {
+    let mut pending = jobs.try_iter().peekable();
+    if pending.peek().is_none() {
+        return;
+    }
+    let mut runtime = shared.lock();
+    for (what, job) in pending {
+        if let Err(error) = job(&mut runtime) {
+            let _ = reports.try_send(PumpReport::JobFailed { what, error });
+        }
+    }
+}
+
+/// Replace `plugin_id`'s session with a new process.
+fn restart(shared: &Arc<Shared>, plugin_id: &str, reports: &SyncSender<PumpReport>) {
+    let pending = {
+        let mut runtime = shared.lock();
+        let _ = runtime.shutdown_plugin(plugin_id);
+        runtime.spawn(plugin_id)
+    };
+    match pending {
+        Ok(pending) => finish_start(shared, pending, true, reports),
+        Err(error) => {
+            let _ = reports.try_send(PumpReport::Failed {
+                plugin_id: plugin_id.to_string(),
+                error,
+            });
+        }
+    }
+}
+
+/// Wait for `pending`'s handshake and attach it. The handshake runs on its
+/// own thread so other plugins keep being served; it keeps the runtime
+/// alive until it finishes, even if the pump is dropped meanwhile. The view
+/// is refreshed before the report goes out.
+fn finish_start(
+    shared: &Arc<Shared>,
+    pending: PendingSession,
+    restarted: bool,
+    reports: &SyncSender<PumpReport>,
+) {
+    let plugin_id = pending.plugin_id().to_string();
+    let thread_shared = Arc::clone(shared);
+    let thread_reports = reports.clone();
+    let spawned = thread::Builder::new()
+        .name("spud-plugin-start".to_string())
+        .spawn(move || {
+            let plugin_id = pending.plugin_id().to_string();
+            let report = match pending.handshake().and_then(|started| {
+                let mut runtime = lock(&thread_shared.runtime);
+                let attached = runtime.attach(started);
+                thread_shared.publish_view(&runtime);
+                attached
+            }) {
+                Ok(_) if restarted => PumpReport::Restarted { plugin_id },
+                Ok(_) => PumpReport::Started { plugin_id },
+                Err(error) => PumpReport::Failed { plugin_id, error },
+            };
+            let _ = thread_reports.try_send(report);
+        });
+    if let Err(err) = spawned {
+        let _ = reports.try_send(PumpReport::Failed {
+            plugin_id,
+            error: RuntimeError::Spawn(format!("failed to start handshake thread: {err}")),
+        });
+    }
+}
+
+/// Deficit round robin over plugin sessions.
+///
+/// Each round starts one plugin later than the last, and each plugin's turn
+/// lasts until it has used its [`PLUGIN_QUANTUM`] of handling time or has no
+/// request waiting. Unused time carries into the next round while requests
+/// are still waiting, and time overrun by a slow request is paid back from
+/// later turns, so a plugin with cheap requests is not crowded out by one
+/// with expensive ones.
+#[derive(Debug, Default)]
+struct Scheduler {
+    next_start: usize,
+    /// Handling time each plugin may still spend, in nanoseconds; negative
+    /// while it is paying back an overrun.
+    balances: HashMap<String, i64>,
+}
+
+impl Scheduler {
+    /// Order `plugin_ids` for the next round.
+    fn round(&mut self, mut plugin_ids: Vec<String>) -> Vec<String> {
+        self.balances.retain(|id, _| plugin_ids.contains(id));
+        if !plugin_ids.is_empty() {
+            let start = self.next_start % plugin_ids.len();
+            plugin_ids.rotate_left(start);
+        }
+        self.next_start = self.next_start.wrapping_add(1);
+        plugin_ids
+    }
+
+    /// Grant a plugin its quantum. Returns whether it may handle a request
+    /// this turn.
+    fn begin_turn(&mut self, plugin_id: &str) -> bool {
+        let balance = self.balance(plugin_id);
+        *balance = (*balance + nanos(PLUGIN_QUANTUM)).min(nanos(MAX_CARRY_OVER));
+        *balance > 0
+    }
+
+    /// Charge a handled request. Returns whether the turn may continue.
+    fn charge(&mut self, plugin_id: &str, latency: Duration) -> bool {
+        let balance = self.balance(plugin_id);
+        *balance = (*balance - nanos(latency.max(MIN_REQUEST_COST))).max(-nanos(MAX_CARRY_OVER));
+        *balance > 0
+    }
+
+    /// The plugin had nothing waiting: it keeps any debt but not its
+    /// unused time.
+    fn idle(&mut self, plugin_id: &str) {
+        let balance = self.balance(plugin_id);
+        *balance = (*balance).min(0);
+    }
+
+    fn balance(&mut self, plugin_id: &str) -> &mut i64 {
+        self.balances.entry(plugin_id.to_string()).or_default()
+    }
+}
+
+fn nanos(duration: Duration) -> i64 {
+    i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX)
+}
+
+fn lock(runtime: &Mutex<PluginRuntime>) -> MutexGuard<'_, PluginRuntime> {
+    runtime
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// [`HostBridge`] for the pump thread: forwards every call to the host's
+/// thread and waits for the answer.
+struct ChannelBridge {
+    calls: SyncSender<HostCall>,
+}
+
+impl ChannelBridge {
+    fn call<R: Send + 'static>(
+        &mut self,
+        f: impl FnOnce(&mut dyn HostBridge) -> Result<R> + Send + 'static,
+    ) -> Result<R> {
+        let (reply_tx, reply_rx) = mpsc::sync_channel(1);
+        self.calls
+            .send(Box::new(move |host| {
+                let _ = reply_tx.send(f(host));
+            }))
+            .map_err(|_| anyhow!("host stopped serving plugin requests"))?;
+        reply_rx
+            .recv()
+            .map_err(|_| anyhow!("host stopped serving plugin requests"))?
+    }
+}
+
+impl HostBridge for ChannelBridge {
+    fn state_snapshot(&mut self) -> Result<StateSnapshot> {
+        self.call(|host| host.state_snapshot())
+    }
+
+    fn telemetry_samples(&mut self) -> Result<Vec<TelemetrySample>> {
+        self.call(|host| host.telemetry_samples())
+    }
+
+    fn invoke_command(&mut self, params: InvokeCommandParams) -> Result<InvokeCommandResult> {
+        self.call(move |host| host.invoke_command(params))
+    }
+
+    fn list_commands(&mut self) -> Result<Vec<CommandInfo>> {
+        self.call(|host| host.list_commands())
+    }
+
+    fn publish_event(&mut self, params: PublishEventParams) -> Result<PublishEventResult> {
+        self.call(move |host| host.publish_event(params))
+    }
+
+    fn register_event_schema(
+        &mut self,
+        plugin_id: &str,
+        params: RegisterEventSchemaParams,
+    ) -> Result<RegisterEventSchemaResult> {
+        let plugin_id = plugin_id.to_string();
+        self.call(move |host| host.register_event_schema(&plugin_id, params))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::protocol::{method, ActiveModule};
+    use crate::test_support::{wait_for_transcript, write_plugin_manifest};
+    use spud_testkit::TestDir;
+
+    /// Counts calls and checks they arrive on the test's own thread.
+    struct ThreadHost {
+        owner: thread::ThreadId,
+        snapshots: usize,
+        commands: Vec<String>,
+    }
+
+    impl HostBridge for ThreadHost {
+        fn state_snapshot(&mut self) -> Result<StateSnapshot> {
+            assert_eq!(thread::current().id(), self.owner);
+            self.snapshots += 1;
+            Ok(StateSnapshot {
+                active_module: Some(ActiveModule {
+                    id: "hello".to_string(),
+                    title: "Hello".to_string(),
+                }),
+                status_line: "OK".to_string(),
+                uptime_seconds: 1,
+                tps: 10.0,
+                telemetry: Vec::new(),
+            })
+        }
+
+        fn invoke_command(&mut self, params: InvokeCommandParams) -> Result<InvokeCommandResult> {
+            assert_eq!(thread::current().id(), self.owner);
+            self.commands.push(params.command.clone());
+            Ok(InvokeCommandResult {
+                lines: vec![format!("ok:{}", params.command)],
+                is_error: false,
+                output: None,
+            })
+        }
+
+        fn publish_event(&mut self, _params: PublishEventParams) -> Result<PublishEventResult> {
+            Ok(PublishEventResult { accepted: true })
+        }
+    }
+
+    #[test]
+    fn scheduler_rotates_rounds_and_carries_time_over() {
+        let mut scheduler = Scheduler::default();
+        let ids = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert_eq!(scheduler.round(ids.clone()), ["a", "b", "c"]);
+        assert_eq!(scheduler.round(ids.clone()), ["b", "c", "a"]);
+
+        // Instant requests still use up the quantum.
+        assert!(scheduler.begin_turn("a"));
+        let mut cheap = 1;
+        while scheduler.charge("a", Duration::ZERO) {
+            cheap += 1;
+        }
+        assert_eq!(
+            cheap,
+            PLUGIN_QUANTUM.as_micros() / MIN_REQUEST_COST.as_micros()
+        );
+
+        // An overrun is paid back by skipping turns.
+        assert!(scheduler.begin_turn("b"));
+        assert!(!scheduler.charge("b", PLUGIN_QUANTUM * 3));
+        let skipped = (0..10).take_while(|_| !scheduler.begin_turn("b")).count();
+        assert_eq!(skipped, 2);
+
+        // Unused time carries over while requests wait, not once idle.
+        assert!(scheduler.begin_turn("c"));
+        assert!(scheduler.charge("c", Duration::from_millis(1)));
+        assert!(scheduler.begin_turn("c"));
+        assert_eq!(scheduler.balances["c"], nanos(Duration::from_millis(3)));
+        scheduler.idle("c");
+        assert_eq!(scheduler.balances["c"], 0);
+
+        scheduler.round(vec!["c".to_string()]);
+        assert_eq!(scheduler.balances.len(), 1);
+    }
+
+    #[test]
+    fn host_calls_run_on_the_serving_thread() {
+        let root = TestDir::new("pump");
+        let plugin_dir = root.path.join("plugin");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let transcript = plugin_dir.join("transcript.log");
+        let script = r#"#!/bin/sh
+set -eu
+TRANSCRIPT="__TRANSCRIPT__"
+echo '{"jsonrpc":"2.0","id":1,"method":"spud.handshake","params":{"plugin_id":"spud.pump","plugin_version":"0.1.0","supported_api_versions":"^1.0.0","requested_capabilities":[]}}'
+IFS= read -r line
+echo '{"jsonrpc":"2.0","id":2,"method":"spud.state.get_snapshot","params":{}}'
+echo '{"jsonrpc":"2.0","id":3,"method":"spud.host.invoke_command","params":{"command":"help","args":[]}}'
+IFS= read -r line
+echo "$line" >> "$TRANSCRIPT"
+IFS= read -r line
+echo "$line" >> "$TRANSCRIPT"
+IFS= read -r line
+"#
+        .replace("__TRANSCRIPT__", &transcript.to_string_lossy());
+        fs::write(plugin_dir.join("plugin.sh"), script).unwrap();
+        write_plugin_manifest(&plugin_dir, "spud.pump", "plugin.sh", &["help"], &[], &[]);
+
+        let mut runtime =
+            PluginRuntime::from_search_roots(std::slice::from_ref(&root.path)).unwrap();
+        runtime.start("spud.pump").unwrap();
+        let pump = PluginPump::spawn(runtime).unwrap();
+        let mut host = ThreadHost {
+            owner: thread::current().id(),
+            snapshots: 0,
+            commands: Vec::new(),
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut reports = Vec::new();
+        while reports.len() < 2 && Instant::now() < deadline {
+            pump.serve(&mut host, Duration::from_millis(5));
+            reports.extend(pump.drain_reports());
+        }
+        let lines = wait_for_transcript(&transcript, 2);
+        let stats = pump.with_runtime(&mut host, |runtime| runtime.stats());
+        drop(pump);
+
+        let methods: Vec<String> = reports
+            .iter()
+            .filter_map(|report| match report {
+                PumpReport::Handled(handled) => Some(handled.method.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(methods, vec![method::GET_SNAPSHOT, method::INVOKE_COMMAND]);
+        assert_eq!(host.snapshots, 1);
+        assert_eq!(host.commands, vec!["help"]);
+        let invoke: Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(invoke["result"]["lines"][0], "ok:help");
+        assert_eq!(stats[0].requests_handled, 2);
+    }
+
+    #[test]
+    fn plugins_are_started_and_restarted_on_request() {
+        let root = TestDir::new("pump-start");
+        let plugin_dir = root.path.join("plugin");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let script = r#"#!/bin/sh
+echo '{"jsonrpc":"2.0","id":1,"method":"spud.handshake","params":{"plugin_id":"spud.manual","plugin_version":"0.1.0","supported_api_versions":"^1.0.0","requested_capabilities":[]}}'
+while IFS= read -r line; do :; done
+"#;
+        fs::write(plugin_dir.join("plugin.sh"), script).unwrap();
+        write_plugin_manifest(&plugin_dir, "spud.manual", "plugin.sh", &[], &[], &[]);
+
+        let runtime = PluginRuntime::from_search_roots(std::slice::from_ref(&root.path)).unwrap();
+        let pump = PluginPump::spawn(runtime).unwrap();
+        let mut host = ThreadHost {
+            owner: thread::current().id(),
+            snapshots: 0,
+            commands: Vec::new(),
+        };
+        let wait_for = |host: &mut ThreadHost, expected: PumpReport| {
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while Instant::now() < deadline {
+                pump.serve(host, Duration::from_millis(5));
+                if pump.drain_reports().contains(&expected) {
+                    return;
+                }
+            }
+            panic!("no {expected:?} report");
+        };
+
+        pump.start_plugin(&mut host, "spud.manual", false).unwrap();
+        wait_for(
+            &mut host,
+            PumpReport::Started {
+                plugin_id: "spud.manual".to_string(),
+            },
+        );
+        pump.start_plugin(&mut host, "spud.manual", true).unwrap();
+        wait_for(
+            &mut host,
+            PumpReport::Restarted {
+                plugin_id: "spud.manual".to_string(),
+            },
+        );
+
+        assert!(matches!(
+            pump.start_plugin(&mut host, "spud.manual", false),
+            Err(RuntimeError::AlreadyRunning(_))
+        ));
+        assert!(matches!(
+            pump.start_plugin(&mut host, "spud.missing", false),
+            Err(RuntimeError::UnknownPlugin(_))
+        ));
+        let stats = pump.with_runtime(&mut host, |runtime| runtime.stats());
+        assert!(stats[0].running);
+        assert_eq!(stats[0].restarts, 1);
+    }
+
+    #[test]
+    fn pausing_holds_requests_but_not_jobs() {
+        let root = TestDir::new("pump-pause");
+        let plugin_dir = root.path.join("plugin");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let script = r#"#!/bin/sh
+echo '{"jsonrpc":"2.0","id":1,"method":"spud.handshake","params":{"plugin_id":"spud.paused","plugin_version":"0.1.0","supported_api_versions":"^1.0.0","requested_capabilities":[]}}'
+IFS= read -r line
+echo '{"jsonrpc":"2.0","id":2,"method":"spud.state.get_snapshot","params":{}}'
+while IFS= read -r line; do :; done
+"#;
+        fs::write(plugin_dir.join("plugin.sh"), script).unwrap();
+        write_plugin_manifest(&plugin_dir, "spud.paused", "plugin.sh", &[], &[], &[]);
+
+        let runtime = PluginRuntime::from_search_roots(std::slice::from_ref(&root.path)).unwrap();
+        let pump = PluginPump::spawn(runtime).unwrap();
+        pump.set_paused(true);
+        let mut host = ThreadHost {
+            owner: thread::current().id(),
+            snapshots: 0,
+            commands: Vec::new(),
+        };
+        assert!(!pump.view().sessions[0].running);
+
+        pump.start_plugin(&mut host, "spud.paused", false).unwrap();
+        let started = PumpReport::Started {
+            plugin_id: "spud.paused".to_string(),
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !pump.drain_reports().contains(&started) {
+            assert!(Instant::now() < deadline, "no {started:?} report");
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert!(pump.view().sessions[0].running);
+
+        pump.submit("stop a missing plugin", |runtime| {
+            runtime.shutdown_plugin("spud.missing")
+        });
+        let until = Instant::now() + VIEW_INTERVAL * 4;
+        let mut reports = Vec::new();
+        while Instant::now() < until {
+            pump.serve(&mut host, Duration::from_millis(5));
+            reports.extend(pump.drain_reports());
+        }
+        assert_eq!(host.snapshots, 0);
+        assert!(matches!(
+            &reports[..],
+            [PumpReport::JobFailed {
+                what: "stop a missing plugin",
+                error: RuntimeError::UnknownPlugin(_)
+            }]
+        ));
+
+        pump.set_paused(false);
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while host.snapshots == 0 && Instant::now() < deadline {
+            pump.serve(&mut host, Duration::from_millis(5));
+        }
+        assert_eq!(host.snapshots, 1);
+        while pump.view().stats[0].requests_handled == 0 {
+            assert!(Instant::now() < deadline, "view never caught up");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn unhealthy_plugins_are_restarted() {
+        let root = TestDir::new("pump-health");
+        for (plugin_id, answers) in [("spud.live", true), ("spud.stuck", false)] {
+            let plugin_dir = root.path.join(plugin_id);
+            fs::create_dir_all(&plugin_dir).unwrap();
+            // The live plugin echoes each ping's ID back; the stuck one
+            // reads pings and never answers.
+            let answer = if answers {
+                r#"id=$(echo "$line" | sed -n 's/.*"id":"\([^"]*\)".*/\1/p')
+  echo "{\"jsonrpc\":\"2.0\",\"id\":\"$id\",\"result\":{}}""#
+            } else {
+                ":"
+            };
+            let script = format!(
+                r#"#!/bin/sh
+echo '{{"jsonrpc":"2.0","id":1,"method":"spud.handshake","params":{{"plugin_id":"{plugin_id}","plugin_version":"0.1.0","supported_api_versions":"^1.0.0","requested_capabilities":[]}}}}'
+IFS= read -r line
+while IFS= read -r line; do
+  {answer}
+done
+"#
+            );
+            fs::write(plugin_dir.join("plugin.sh"), script).unwrap();
+            write_plugin_manifest(&plugin_dir, plugin_id, "plugin.sh", &[], &[], &[]);
+            let manifest = plugin_dir.join("plugin.toml");
+            let mut raw = fs::read_to_string(&manifest).unwrap();
+            raw.push_str(
+                "\n[health]\nping_interval_ms = 50\nping_timeout_ms = 100\nmax_failures = 2\n",
+            );
+            fs::write(&manifest, raw).unwrap();
+        }
+
+        let mut runtime =
+            PluginRuntime::from_search_roots(std::slice::from_ref(&root.path)).unwrap();
+        runtime.start("spud.live").unwrap();
+        runtime.start("spud.stuck").unwrap();
+        let pump = PluginPump::spawn(runtime).unwrap();
+        let mut host = ThreadHost {
+            owner: thread::current().id(),
+            snapshots: 0,
+            commands: Vec::new(),
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(3);
+        let mut reports = Vec::new();
+        while Instant::now() < deadline
+            && !reports
+                .iter()
+                .any(|report| matches!(report, PumpReport::Restarted { .. }))
+        {
+            pump.serve(&mut host, Duration::from_millis(5));
+            reports.extend(pump.drain_reports());
+        }
+        let stats = pump.with_runtime(&mut host, |runtime| runtime.stats());
+        let sessions = pump.with_runtime(&mut host, |runtime| runtime.sessions());
+        drop(pump);
+
+        let unhealthy: Vec<&str> = reports
+            .iter()
+            .filter_map(|report| match report {
+                PumpReport::Health {
+                    plugin_id,
+                    status: HealthStatus::Unhealthy,
+                } => Some(plugin_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(unhealthy, vec!["spud.stuck"]);
+        assert!(reports.contains(&PumpReport::Restarted {
+            plugin_id: "spud.stuck".to_string()
+        }));
+        assert_eq!(stats[0].restarts, 0);
+        assert_eq!(stats[1].restarts, 1);
+        assert_eq!(sessions[0].health, Some(HealthStatus::Healthy));
+    }
+}
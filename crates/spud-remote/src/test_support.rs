@@ -0,0 +1,81 @@
+//! Shared fixtures for crate-internal tests.
+
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Write a `plugin.toml` for a `sh` fixture plugin with the given
+/// permissions.
+pub(crate) fn write_plugin_manifest(
+    dir: &Path,
+    plugin_id: &str,
+    entrypoint: &str,
+    commands: &[&str],
+    event_tags: &[&str],
+    subscriptions: &[&str],
+) {
+    let commands = toml_array(commands);
+    let event_tags = toml_array(event_tags);
+    let subscriptions = toml_array(subscriptions);
+
+    let manifest = format!(
+        r#"
+id = "{plugin_id}"
+name = "Fixture Plugin"
+version = "0.1.0"
+
+[runtime]
+entrypoint = "{entrypoint}"
+command = "sh"
+args = []
+
+[compatibility]
+host_api = "^1.0.0"
+
+[permissions]
+commands = {commands}
+event_tags = {event_tags}
+subscriptions = {subscriptions}
+"#
+    );
+
+    fs::write(dir.join("plugin.toml"), manifest).unwrap();
+}
+
+fn toml_array(values: &[&str]) -> String {
+    format!(
+        "[{}]",
+        values
+            .iter()
+            .map(|value| format!("\"{value}\""))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Lines of `path` once it has at least `min_lines`, waiting up to two
+/// seconds.
+pub(crate) fn wait_for_transcript(path: &Path, min_lines: usize) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        if let Ok(raw) = fs::read_to_string(path) {
+            let lines = raw
+                .lines()
+                .map(|line| line.to_string())
+                .collect::<Vec<String>>();
+            if lines.len() >= min_lines {
+                return lines;
+            }
+        }
+
+        if Instant::now() > deadline {
+            panic!(
+                "timed out waiting for transcript {} to reach {min_lines} lines",
+                path.display()
+            );
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,137 @@
+//! App-level `alerts` console command and alert rule loading.
+//!
+//! Lives in the app because the alert engine is owned by the app loop, next
+//! to the telemetry store it evaluates.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use spud_config::{alerts::parse_duration, AlertsConfig};
+use spud_core::alerts::{AlertEngine, AlertStatus};
+use spud_core::command::{Column, CommandOutput, Table};
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "alerts";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "alerts list | ack <name> | silence <name> [duration]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "List, acknowledge, or silence alert rules";
+/// Silence length when `alerts silence` is given no duration.
+const DEFAULT_SILENCE: Duration = Duration::from_secs(3_600);
+
+/// Return the alert rules file path.
+///
+/// Precedence: `SPUD_ALERTS_FILE` env var > `<config_dir>/alerts.toml`.
+pub(crate) fn config_path(config_dir: Option<&Path>) -> Option<PathBuf> {
+    if let Ok(path) = std::env::var("SPUD_ALERTS_FILE") {
+        return Some(PathBuf::from(path));
+    }
+    config_dir.map(|dir| dir.join("alerts.toml"))
+}
+
+/// Load alert rules, returning an empty engine when no rules file exists or
+/// it is invalid (the error is logged).
+pub(crate) fn load_engine(config_dir: Option<&Path>) -> AlertEngine {
+    let Some(path) = config_path(config_dir).filter(|path| path.exists()) else {
+        tracing::debug!("no alert rules configured");
+        return AlertEngine::default();
+    };
+
+    match AlertsConfig::from_path(&path).and_then(|config| AlertEngine::from_config(&config)) {
+        Ok(engine) => {
+            tracing::info!(
+                rules = engine.alerts().len(),
+                path = %path.display(),
+                "loaded alert rules"
+            );
+            engine
+        }
+        Err(err) => {
+            tracing::warn!(error = %format!("{err:#}"), "alert rules not loaded");
+            AlertEngine::default()
+        }
+    }
+}
+
+/// Run an `alerts` subcommand.
+pub(crate) fn execute(engine: &mut AlertEngine, args: &[&str], now: Instant) -> CommandOutput {
+    let result = match args {
+        [] | ["list"] => return list_output(engine, now),
+        ["ack", name] => engine
+            .acknowledge(name)
+            .map(|()| format!("acknowledged {name}")),
+        ["silence", name, rest @ ..] if rest.len() <= 1 => {
+            let duration = match rest.first().map(|raw| parse_duration(raw)) {
+                None => DEFAULT_SILENCE,
+                Some(Ok(duration)) => duration,
+                Some(Err(err)) => return CommandOutput::Error(vec![err.to_string()]),
+            };
+            engine
+                .silence(name, duration, now)
+                .map(|()| format!("silenced {name} for {}s", duration.as_secs()))
+        }
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    };
+    match result {
+        Ok(line) => CommandOutput::Lines(vec![line]),
+        Err(err) => CommandOutput::Error(vec![err.to_string()]),
+    }
+}
+
+fn list_output(engine: &AlertEngine, now: Instant) -> CommandOutput {
+    if engine.is_empty() {
+        return CommandOutput::Lines(vec!["no alert rules configured".to_string()]);
+    }
+
+    let mut table = Table::new(vec![
+        Column::left("ALERT"),
+        Column::left("SEVERITY"),
+        Column::left("STATE"),
+        Column::right("VALUE"),
+        Column::left("WHEN"),
+    ]);
+    for alert in engine.alerts() {
+        let mut state = alert.status.as_str().to_string();
+        if alert.is_silenced(now) {
+            state.push('*');
+        }
+        if alert.status == AlertStatus::Firing {
+            state = state.to_uppercase();
+        }
+        let value = alert
+            .last_value
+            .map(|value| format!("{value:.2}"))
+            .unwrap_or_else(|| "-".to_string());
+        table.push_row(vec![
+            alert.rule.name.clone(),
+            alert.rule.severity.as_str().to_string(),
+            state,
+            value,
+            alert.rule.expression.clone(),
+        ]);
+    }
+    CommandOutput::Table(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silence_rejects_durations_that_overflow() {
+        let config =
+            AlertsConfig::from_toml_str("[[rule]]\nname = \"x\"\nwhen = \"cpu > 1\"\n").unwrap();
+        let mut engine = AlertEngine::from_config(&config).unwrap();
+        let now = Instant::now();
+
+        let output = execute(&mut engine, &["silence", "x", "99999999999999999h"], now);
+        assert!(output.is_error());
+        // Parses, but lands past what `Instant` can hold
+        let output = execute(&mut engine, &["silence", "x", "18446744073709551615s"], now);
+        assert_eq!(output.to_lines(), ["cannot silence x for that long"]);
+        assert!(engine.alerts()[0].silenced_until.is_none());
+
+        let output = execute(&mut engine, &["silence", "x", "5m"], now);
+        assert_eq!(output.to_lines(), ["silenced x for 300s"]);
+    }
+}
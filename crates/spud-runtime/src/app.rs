@@ -0,0 +1,3242 @@
+//! The shell itself: [`App`] state, the plugin host bridge, and the
+//! terminal, served, and headless loops that [`crate::RuntimeBuilder::run`]
+//! drives.
+
+use std::collections::{HashSet, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use crossterm::{
+    event::{self, Event as CEvent, KeyCode, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::Backend, Terminal};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use spud_agent::Mood;
+use spud_config::{
+    AlertSeverity, AppConfig, CommandsConfig, ConsoleConfig, HudConfig, PluginsConfig, Profile,
+    SyncOutput, Workspace,
+};
+use spud_core::{
+    alerts::{AlertEngine, AlertTransition},
+    bus::EventBus,
+    command::{
+        self, Column, CommandContext, CommandOrigin, CommandOutput, CommandRegistry, CommandStatus,
+        Table,
+    },
+    console::{Console, ConsolePrompt, PromptOrigin, INPUT_TARGET, OUTPUT_TARGET},
+    event::{ActivationReason, AttentionLevel, Event, PluginStatus, TelemetryValue},
+    fps::TickCounter,
+    guard::{self, Gate},
+    i18n::{self, tr, tr_args},
+    idle::{IdleStep, IdleTracker},
+    keymap::{KeyBinding, KeyTarget, Keymap},
+    logging::{self, LogBuffer, LogEntry, LogLevel},
+    module::{isolate, HudContribution, HudSpan, HudStyle, KeyHint},
+    pipeline,
+    registry::ModuleRegistry,
+    rng::{Rng, RngProvider},
+    schema::EventSchemas,
+    spans::{SpanStats, Stopwatch},
+    state::AppState,
+    telemetry::TelemetryStore,
+    undo::{Inverse, UndoStack},
+    vars::Vars,
+};
+use spud_remote::{
+    audit::AUDIT_FILE_NAME,
+    health::HealthStatus,
+    integrity::IntegrityPolicy,
+    protocol::{
+        error_code, ActiveModule, CommandInfo, ConsoleAnswerParams, ConsolePromptParams,
+        ConsolePromptResult, EventCategory, InvalidEventPayload, InvokeCommandParams,
+        InvokeCommandResult, JsonRpcError, LogEntryParams, LogSeverity, LogSubscribeParams,
+        ModuleFocusParams, ModuleUpdateParams, ModuleUpdateResult, PayloadViolation,
+        PublishEventParams, PublishEventResult, RegisterEventSchemaParams,
+        RegisterEventSchemaResult, StateSnapshot, TelemetryDatum, TelemetrySample,
+    },
+    pump::{PluginPump, PumpReport, RuntimeView},
+    runtime::{HostBridge, HostRejection, PluginRuntime, PluginSessionSnapshot, RuntimeError},
+};
+use spud_ui::{
+    console::{render_console, ConsoleView},
+    events::render_event_overlay,
+    help::{render_help, HelpView},
+    layout::doom_layout,
+    renderer::{render_crashed, HeroCache},
+    shell::{render_shell, ModuleTab, ShellView},
+    splash::StepStatus,
+};
+
+use crate::builder::{ModuleFactory, Options};
+use crate::compose::{FrameComposer, PostDraw};
+use crate::control::{
+    self, AttachScreenParams, CommandParams, CommandResult, ControlServer, InfoResult, InputParams,
+    LogStreams,
+};
+use crate::frame_export::{FrameExport, FrameSnapshot};
+use crate::mdns::Advertiser;
+use crate::plugin_module::PluginModule;
+use crate::screen::{self, SharedScreen};
+use crate::session::Session;
+use crate::splash::{Splash, SPLASH_FRAME, SPLASH_TIMEOUT};
+use crate::state_sync::StateTracker;
+use crate::status_page::{self, StatusServer, StatusSnapshot};
+use crate::{
+    alerts, command_stats, console, debug, doctor, dump, events, hud, instance, keys, module,
+    plugins, profile, redirect, session, signals, sys, terminal, transcript, undo, version,
+    workspace,
+};
+
+/// `(name, usage)` of commands the app or the command language handles
+/// before the core registry, used for console suggestions: the pipeline's
+/// control commands, then [`OWN_COMMANDS`].
+const APP_COMMANDS: &[(&str, &str)] = &{
+    const CONTROL: usize = pipeline::CONTROL_COMMANDS.len();
+    let mut commands = [("", ""); CONTROL + OWN_COMMANDS.len()];
+    let mut index = 0;
+    while index < commands.len() {
+        commands[index] = if index < CONTROL {
+            let (name, usage, _) = pipeline::CONTROL_COMMANDS[index];
+            (name, usage)
+        } else {
+            OWN_COMMANDS[index - CONTROL]
+        };
+        index += 1;
+    }
+    commands
+};
+
+/// `(name, usage)` of commands the app handles itself.
+const OWN_COMMANDS: &[(&str, &str)] = &[
+    (plugins::NAME, plugins::USAGE),
+    (alerts::NAME, alerts::USAGE),
+    (doctor::NAME, doctor::USAGE),
+    (version::NAME, version::USAGE),
+    (debug::NAME, debug::USAGE),
+    (profile::NAME, profile::USAGE),
+    (dump::NAME, dump::USAGE),
+    (transcript::NAME, transcript::USAGE),
+    (sys::NAME, sys::USAGE),
+    (events::NAME, events::USAGE),
+    (workspace::NAME, workspace::USAGE),
+    (hud::NAME, hud::USAGE),
+    (console::NAME, console::USAGE),
+    (terminal::NAME, terminal::USAGE),
+    (module::NAME, module::USAGE),
+    (undo::NAME, undo::USAGE),
+];
+
+use crate::config::ConfigWatcher;
+use crate::events::EventTail;
+use crate::input::InputFilter;
+use crate::plugin_dev::DevWatcher;
+use crate::record::EventRecorder;
+use crate::redirect::Redirect;
+use crate::signals::{SignalAction, SignalListener};
+use crate::transcript::Transcript;
+use spud_mod_plugins::{PluginEntry, PluginFeed, PluginsModule};
+use spud_mod_stats::StatsModule;
+
+pub(crate) struct App {
+    state: AppState,
+    registry: ModuleRegistry,
+    bus: EventBus,
+    /// Plugin sessions, serviced on their own thread.
+    plugin_pump: Option<PluginPump>,
+    /// The discovery thread's result, until it arrives.
+    plugin_discovery: Option<Receiver<Result<PluginRuntime, String>>>,
+    /// Plugins whose handshake has not finished yet.
+    plugins_starting: HashSet<String>,
+    /// Search roots, kept so `SIGHUP` can rediscover plugins.
+    plugin_roots: Vec<PathBuf>,
+    /// The plugins module's feed, when that module is registered.
+    plugin_feed: Option<PluginFeed>,
+    /// When the plugin feed was last published.
+    plugin_feed_at: Option<Instant>,
+    /// Changes not yet sent to `spud.state.subscribe` subscribers.
+    state_changes: StateTracker,
+    signals: SignalListener,
+    log_buffer: LogBuffer,
+    console: Console,
+    tick_counter: TickCounter,
+    commands: CommandRegistry,
+    agent: spud_agent::Agent,
+    telemetry: TelemetryStore,
+    alerts: AlertEngine,
+    spans: SpanStats,
+    /// Stream for console commands; modules get theirs from the registry.
+    rng: Rng,
+    recorder: Option<EventRecorder>,
+    transcript: Transcript,
+    /// `[export]` file, while it is on.
+    frame_export: Option<FrameExport>,
+    /// `[http]` status page server, while it is on.
+    status_server: Option<StatusServer>,
+    /// Control socket for `spud attach`; `None` where there is none.
+    control: Option<ControlServer>,
+    /// Control clients tailing the log with `spud.control.logs_subscribe`.
+    log_streams: LogStreams,
+    /// Screen streamed to attached clients, under `spud serve`.
+    screen: Option<SharedScreen>,
+    /// Input from attached clients, waiting for the loop.
+    screen_input: VecDeque<CEvent>,
+    /// Last hero of a module that reports a content version.
+    hero_cache: HeroCache,
+    /// Hero output written after each frame's cells; shared with the
+    /// terminal's [`FrameComposer`] by [`run`].
+    post_draw: PostDraw,
+    /// Top-bar tabs and badges, refilled every frame without reallocating.
+    shell_tabs: Vec<ModuleTab<'static>>,
+    shell_badges: Vec<HudSpan>,
+    /// `[mdns]` advertisement of the status page, while it is on.
+    advertiser: Option<Advertiser>,
+    /// When the status page snapshot was last rebuilt.
+    status_published_at: Option<Instant>,
+    /// Command lines waiting out a `sleep`, in the order they paused.
+    sleeping: Vec<Sleeping>,
+    doctor: doctor::Settings,
+    show_help: bool,
+    show_events: bool,
+    /// Running `event tail`, copying bus events into the console.
+    event_tail: Option<EventTail>,
+    /// Payload schemas for custom event tags, from modules and plugins.
+    event_schemas: EventSchemas,
+    settings: AppConfig,
+    /// Active profile: from `spud.toml`, or the `profile` command.
+    profile: Profile,
+    config_watcher: ConfigWatcher,
+    /// Files of plugins in dev mode, restarted when they change.
+    plugin_dev: DevWatcher,
+    /// `--log-level`, restored when `log_level` is removed from `spud.toml`.
+    cli_log_level: Option<String>,
+    /// Startup options before any workspace is applied.
+    options: Options,
+    /// Active workspace, if any.
+    workspace: Option<Workspace>,
+    /// Rebuild the modules on a workspace switch.
+    module_factories: Vec<ModuleFactory>,
+    /// HUD size from the `hud` command, overriding `[hud]` in `spud.toml`.
+    hud_size: Option<HudConfig>,
+    /// Overlay layout from the `console` command, overriding `[console]` in
+    /// `spud.toml`.
+    console_layout: Option<ConsoleConfig>,
+    /// Synchronized output mode from the `terminal` command, overriding
+    /// `[terminal]` in `spud.toml`.
+    sync_output: Option<SyncOutput>,
+    /// Actions `undo` can reverse.
+    undo: UndoStack,
+    /// The terminal's [`FrameComposer::sync`] switch, shared by [`run`].
+    terminal_sync: Arc<AtomicBool>,
+    /// Global keys merged with the active module's.
+    keymap: Keymap,
+    /// Time since the last key, for attract mode.
+    idle: IdleTracker,
+    /// What attract mode replaced, put back on the next key.
+    attract_return: Option<AttractReturn>,
+}
+
+/// The rest of a command line after `sleep`.
+struct Sleeping {
+    /// When to run it.
+    at: Instant,
+    /// The line as typed, for the transcript.
+    input: String,
+    rest: Vec<pipeline::Pipeline>,
+    interactive: bool,
+    /// Where the line's output goes; appended to after the first part.
+    redirect: Option<Redirect>,
+}
+
+/// Module and agent mood from before attract mode.
+struct AttractReturn {
+    module: Option<String>,
+    mood: Mood,
+}
+
+impl App {
+    pub(crate) fn new(
+        log_buffer: LogBuffer,
+        options: Options,
+        workspace: Option<Workspace>,
+        module_factories: Vec<ModuleFactory>,
+        settings: AppConfig,
+        config_watcher: ConfigWatcher,
+        splash: &mut Splash<'_>,
+    ) -> Result<Self> {
+        let rng = options
+            .seed
+            .map(RngProvider::new)
+            .unwrap_or_else(RngProvider::from_clock);
+        tracing::info!(seed = rng.seed(), "random seed (replay with --seed)");
+
+        let effective = workspace::apply(&options, workspace.as_ref());
+        let registry = workspace::build_registry(rng, &module_factories, workspace.as_ref())?;
+        for (_, title) in registry.list() {
+            splash.done(tr_args("{title} module", &[("title", &title)]), "");
+        }
+        let mut event_schemas = EventSchemas::new();
+        for (module, tag, err) in registry.register_event_schemas(&mut event_schemas) {
+            tracing::warn!(module, tag, error = %err, "event schema not registered");
+        }
+
+        let agent = spud_agent::Agent::load_default(Instant::now())?;
+        let state = AppState::new();
+        let transcript = Transcript::new(
+            logging::log_dir().join(Transcript::file_name(unix_ms())),
+            format!(
+                "spud {} console transcript (seed {})",
+                version::SUMMARY,
+                rng.seed()
+            ),
+            state.started_at,
+        );
+        let mut app = Self {
+            state,
+            registry,
+            bus: EventBus::new(),
+            plugin_pump: None,
+            plugin_discovery: None,
+            plugins_starting: HashSet::new(),
+            plugin_roots: effective.plugin_roots.clone(),
+            plugin_feed: None,
+            plugin_feed_at: None,
+            state_changes: StateTracker::default(),
+            signals: SignalListener::install(),
+            log_buffer,
+            console: Console::default(),
+            tick_counter: TickCounter::default(),
+            commands: command::builtin_registry(),
+            agent,
+            telemetry: TelemetryStore::default(),
+            alerts: alerts::load_engine(effective.config_dir().as_deref()),
+            spans: SpanStats::default(),
+            rng: rng.stream("commands"),
+            recorder: options
+                .record
+                .as_deref()
+                .map(|path| EventRecorder::create(path, rng.seed()))
+                .transpose()?,
+            transcript,
+            frame_export: None,
+            status_server: None,
+            advertiser: None,
+            control: None,
+            log_streams: LogStreams::default(),
+            screen: None,
+            screen_input: VecDeque::new(),
+            hero_cache: HeroCache::default(),
+            post_draw: PostDraw::default(),
+            shell_tabs: Vec::new(),
+            shell_badges: Vec::new(),
+            status_published_at: None,
+            sleeping: Vec::new(),
+            doctor: doctor::Settings::from_options(&effective),
+            show_help: false,
+            show_events: false,
+            event_tail: None,
+            event_schemas,
+            profile: settings.profile,
+            settings,
+            config_watcher,
+            plugin_dev: DevWatcher::default(),
+            cli_log_level: options.log_level.clone(),
+            options,
+            workspace,
+            module_factories,
+            hud_size: None,
+            console_layout: None,
+            sync_output: None,
+            undo: UndoStack::default(),
+            terminal_sync: Arc::default(),
+            keymap: Keymap::new(keys::global()),
+            idle: IdleTracker::new(Instant::now()),
+            attract_return: None,
+        };
+        if let Some(id) = app.options.initial_module.clone() {
+            let events = app.registry.activate(&id).with_context(|| {
+                let ids: Vec<&str> = app.registry.list().iter().map(|(id, _)| *id).collect();
+                format!(
+                    "initial module {id}: available modules are {}",
+                    ids.join(", ")
+                )
+            })?;
+            for ev in events {
+                app.bus.publish(ev);
+            }
+            if let Some(m) = app.registry.active() {
+                app.state.status_line = module_status(m.title());
+            }
+        }
+        if !app.options.headless && !app.options.fresh {
+            app.restore_session(true, app.options.initial_module.is_none());
+        }
+        app.attach_plugin_feed();
+        app.registry.configure(&app.settings);
+        app.bus.configure(app.settings.bus);
+        app.apply_profile(app.profile);
+        app.apply_transcript(app.settings.transcript);
+        app.apply_export();
+        app.apply_http();
+        app.apply_mdns();
+        app.control = app.options.control_socket.as_deref().and_then(|path| {
+            ControlServer::start(path)
+                .inspect(|server| {
+                    tracing::info!(path = %server.path().display(), "control socket listening");
+                })
+                .inspect_err(|err| tracing::warn!("control socket unavailable: {err:#}"))
+                .ok()
+        });
+        app.init_plugin_runtime(app.plugin_roots.clone(), splash);
+        Ok(app)
+    }
+
+    /// Publish a tick if the configured tick interval has passed since
+    /// `last_tick`.
+    fn tick_if_due(&mut self, last_tick: &mut Instant) {
+        if last_tick.elapsed() >= self.settings.tick_interval(self.profile) {
+            *last_tick = Instant::now();
+            self.tick_counter.tick(*last_tick);
+            self.bus.publish(Event::Tick { now: *last_tick });
+        }
+    }
+
+    /// Apply `spud.toml` if it changed on disk. Invalid files are reported
+    /// as warnings and the current settings stay in effect.
+    fn poll_config(&mut self, now: Instant) {
+        match self.config_watcher.poll(now) {
+            None => {}
+            Some(Ok(settings)) => self.apply_settings(settings),
+            Some(Err(err)) => {
+                tracing::warn!("settings not reloaded: {err:#}");
+            }
+        }
+    }
+
+    /// Restart plugins in dev mode whose files changed.
+    fn poll_plugin_dev(&mut self, now: Instant) {
+        // Lent out while the host bridge borrows the rest of the app
+        let mut watcher = std::mem::take(&mut self.plugin_dev);
+        self.with_plugin_host(|pump, host| {
+            plugins::restart_changed(pump, host, &mut watcher, now);
+        });
+        self.plugin_dev = watcher;
+    }
+
+    /// Run the default action for each signal received since the last
+    /// call, then publish it.
+    fn poll_signals(&mut self) {
+        if self.signals.stop_requested() {
+            tracing::info!("stop signal received; shutting down");
+            self.bus.publish(Event::Quit);
+        }
+        for signal in self.signals.drain() {
+            tracing::info!(signal = signal.name(), "signal received");
+            match signals::default_action(signal) {
+                SignalAction::ReloadConfig => match self.config_watcher.reload() {
+                    Ok(settings) => self.apply_settings(settings),
+                    Err(err) => tracing::warn!("settings not reloaded: {err:#}"),
+                },
+                SignalAction::ToggleConsole => self.console.toggle(Instant::now()),
+                SignalAction::ReloadPlugins => self.reload_plugins(),
+            }
+            self.bus.publish(Event::Signal { signal });
+        }
+    }
+
+    /// Answer requests that arrived on the control socket.
+    fn poll_control(&mut self) {
+        let Some(requests) = self.control.as_ref().map(ControlServer::drain) else {
+            return;
+        };
+        for request in requests {
+            let result = match request.method.as_str() {
+                control::method::INFO => Ok(json!(InfoResult {
+                    pid: std::process::id(),
+                    version: version::SUMMARY.to_string(),
+                    uptime_secs: self.state.started_at.elapsed().as_secs(),
+                    active_module: self.registry.active_id().map(str::to_string),
+                    serving: self.screen.is_some(),
+                })),
+                control::method::COMMAND => request
+                    .params::<CommandParams>()
+                    .map(|params| json!(self.run_control_line(&params.line))),
+                control::method::QUIT => {
+                    tracing::info!("quit requested over the control socket");
+                    self.bus.publish(Event::Quit);
+                    Ok(json!({}))
+                }
+                control::method::ATTACH_SCREEN => match &self.screen {
+                    Some(screen) => request.params::<AttachScreenParams>().map(|params| {
+                        screen.attach(request.outbox(), params.cols, params.rows);
+                        tracing::info!(clients = screen.clients(), "screen attached");
+                        json!({})
+                    }),
+                    None => Err(JsonRpcError {
+                        code: error_code::PLUGIN_UNAVAILABLE,
+                        message: "SPUD is not serving its screen; start it with `spud serve`"
+                            .into(),
+                        data: None,
+                    }),
+                },
+                control::method::INPUT => request.params::<InputParams>().map(|params| {
+                    if let (Some(screen), CEvent::Resize(cols, rows)) =
+                        (&self.screen, &params.event)
+                    {
+                        screen.resize(*cols, *rows);
+                    }
+                    self.screen_input.push_back(params.event);
+                    json!({})
+                }),
+                control::method::LOGS_SUBSCRIBE => {
+                    request.params::<LogSubscribeParams>().and_then(|params| {
+                        let result = self.log_streams.subscribe(request.outbox(), &params)?;
+                        tracing::info!("log stream opened over the control socket");
+                        Ok(json!(result))
+                    })
+                }
+                other => Err(control::method_not_found(other)),
+            };
+            request.reply(result);
+        }
+    }
+
+    /// Run a line sent over the control socket like a typed one, without
+    /// confirmations, and show it in the console too.
+    fn run_control_line(&mut self, input: &str) -> CommandResult {
+        self.console.push_log(LogEntry {
+            level: LogLevel::Info,
+            target: INPUT_TARGET.into(),
+            message: input.trim().to_string(),
+            count: 1,
+        });
+        let output = match redirect::split(input, true, redirect::default_dir().as_deref()) {
+            Ok((line, redirect)) => {
+                let vars = self.command_vars();
+                let output =
+                    pipeline::run(line, &vars, |words| self.execute_words_from(words, false).0);
+                self.transcript.record(input, &output, Instant::now());
+                match &redirect {
+                    Some(redirect) => redirect::apply(redirect, output),
+                    None => output,
+                }
+            }
+            Err(err) => CommandOutput::Error(vec![err]),
+        };
+        let quit = self.show_output(output.clone());
+        if quit {
+            self.bus.publish(Event::Quit);
+        }
+        CommandResult {
+            lines: output.to_lines(),
+            is_error: output.is_error(),
+            quit,
+        }
+    }
+
+    fn apply_settings(&mut self, settings: AppConfig) {
+        let changed = settings.changed_fields(&self.settings);
+        if changed.is_empty() {
+            return;
+        }
+        if changed.contains(&"locale") {
+            tracing::info!("locale change takes effect after restart");
+        }
+        if changed.contains(&"profile") {
+            self.apply_profile(settings.profile);
+        }
+        if changed.contains(&"transcript") {
+            self.apply_transcript(settings.transcript);
+        }
+        if changed.iter().any(|field| field.starts_with("hud.")) {
+            self.hud_size = None;
+        }
+        if changed.iter().any(|field| field.starts_with("console.")) {
+            self.console_layout = None;
+        }
+        if changed.contains(&"terminal.sync_output") {
+            self.sync_output = None;
+        }
+        if changed.iter().any(|field| field.starts_with("bus.")) {
+            self.bus.configure(settings.bus);
+        }
+        if changed.iter().any(|field| field.starts_with("plugins.")) {
+            match IntegrityPolicy::from_config(&settings.plugins) {
+                Ok(policy) => {
+                    self.submit_to_plugins("apply plugin signature settings", move |runtime| {
+                        runtime.set_integrity_policy(policy);
+                        Ok(())
+                    });
+                    tracing::info!(
+                        "plugin signature settings apply to plugins started from now on"
+                    );
+                }
+                Err(err) => tracing::warn!("plugin signature settings not changed: {err:#}"),
+            }
+        }
+        if changed.contains(&"log") {
+            if let Err(err) = logging::set_rotation(&settings.log) {
+                tracing::warn!("log rotation not changed: {err:#}");
+            }
+        }
+        if changed.contains(&"log_level") {
+            let directive = settings
+                .log_level
+                .clone()
+                .or_else(|| self.cli_log_level.clone())
+                .unwrap_or_else(logging::default_directive);
+            if let Err(err) = logging::set_filter(&directive) {
+                tracing::warn!("log filter not changed: {err:#}");
+            }
+        }
+        self.settings = settings;
+        if changed.iter().any(|field| field.starts_with("console.")) {
+            self.apply_console_motion();
+        }
+        if changed.contains(&"terminal.sync_output") {
+            self.apply_sync_output();
+        }
+        if changed.contains(&"export") {
+            self.apply_export();
+        }
+        if changed.contains(&"http") {
+            self.apply_http();
+        }
+        if changed.contains(&"http") || changed.contains(&"mdns") {
+            self.apply_mdns();
+        }
+        if changed.iter().any(|field| field.starts_with("modules.")) {
+            self.registry.configure(&self.settings);
+        }
+        tracing::info!(changed = %changed.join(", "), "settings reloaded");
+        self.bus.publish(Event::ConfigReloaded {
+            changed: changed.into_iter().map(str::to_string).collect(),
+        });
+    }
+
+    /// Start or stop the console transcript, logging where it goes.
+    fn apply_transcript(&mut self, enabled: bool) {
+        if enabled == self.transcript.is_enabled() {
+            return;
+        }
+        match self.transcript.set_enabled(enabled) {
+            Ok(()) if enabled => {
+                tracing::info!(path = %self.transcript.path().display(), "console transcript on");
+            }
+            Ok(()) => tracing::info!("console transcript off"),
+            Err(err) => tracing::warn!("console transcript unavailable: {err:#}"),
+        }
+    }
+
+    /// Open or close the `[export]` file to match the settings.
+    fn apply_export(&mut self) {
+        // The old file is marked stopped before a new one is opened
+        self.frame_export = None;
+        if !self.settings.export.enabled {
+            return;
+        }
+        match FrameExport::open(&self.settings.export, Some(&instance::lock_dir())) {
+            Ok(export) => {
+                tracing::info!(path = %export.path().display(), "frame export on");
+                self.frame_export = Some(export);
+            }
+            Err(err) => tracing::warn!("frame export unavailable: {err:#}"),
+        }
+    }
+
+    /// Rewrite the `[export]` file once its interval has passed.
+    fn export_frame(&mut self, now: Instant) {
+        let Some(export) = self.frame_export.as_mut().filter(|export| export.due(now)) else {
+            return;
+        };
+        let percent = |key| match self
+            .telemetry
+            .latest("stats", key)
+            .map(|point| &point.value)
+        {
+            Some(TelemetryValue::Float(value)) => Some(*value as f32),
+            _ => None,
+        };
+        let active = self.registry.active();
+        // Same face as the HUD shows
+        let agent = active
+            .and_then(|module| module.agent())
+            .filter(|_| !self.idle.is_attract())
+            .unwrap_or(&self.agent);
+        let snapshot = FrameSnapshot {
+            uptime: now.saturating_duration_since(self.state.started_at),
+            tps: self.tick_counter.tps(),
+            cpu_percent: percent("cpu"),
+            mem_percent: percent("mem"),
+            mood: agent.mood(),
+            frame: agent.current_frame_lines(),
+            active_module: active.map(|module| module.id()).unwrap_or_default(),
+            status_line: &self.state.status_line,
+        };
+        if let Err(err) = export.write(&snapshot, now) {
+            tracing::warn!("frame export stopped: {err:#}");
+            self.frame_export = None;
+        }
+    }
+
+    /// Start or stop the `[http]` status page to match the settings.
+    fn apply_http(&mut self) {
+        // Free the old address before binding a new one
+        self.status_server = None;
+        self.status_published_at = None;
+        let http = self.settings.http;
+        if !http.enabled {
+            return;
+        }
+        match StatusServer::start(http.bind) {
+            Ok(server) => {
+                tracing::info!(addr = %server.addr(), "status page on");
+                self.status_server = Some(server);
+            }
+            Err(err) => tracing::warn!("status page unavailable: {err:#}"),
+        }
+    }
+
+    /// Switch synchronized output to match the `terminal` command, else
+    /// the settings.
+    fn apply_sync_output(&mut self) {
+        let mode = self
+            .sync_output
+            .unwrap_or(self.settings.terminal.sync_output);
+        let supported = doctor::sync_output_terminal(&|key| std::env::var(key).ok()).is_some();
+        self.terminal_sync
+            .store(mode.enabled(supported), Ordering::Relaxed);
+    }
+
+    /// Advertise the status page over mDNS to match the settings.
+    fn apply_mdns(&mut self) {
+        self.advertiser = None;
+        if !self.settings.mdns.enabled {
+            return;
+        }
+        let Some(server) = &self.status_server else {
+            tracing::warn!("mdns is on but there is no status page to advertise; enable [http]");
+            return;
+        };
+        match Advertiser::start(&self.settings.mdns, server.addr()) {
+            Ok(advertiser) => {
+                tracing::info!(
+                    name = advertiser.name(),
+                    "advertising the status page over mDNS"
+                );
+                self.advertiser = Some(advertiser);
+            }
+            Err(err) => tracing::warn!("mDNS advertisement unavailable: {err:#}"),
+        }
+    }
+
+    /// Rebuild the status page snapshot once [`status_page::REFRESH`] has
+    /// passed.
+    fn publish_status(&mut self, now: Instant) {
+        if self.status_server.is_none()
+            || self
+                .status_published_at
+                .is_some_and(|at| now.saturating_duration_since(at) < status_page::REFRESH)
+        {
+            return;
+        }
+        self.status_published_at = Some(now);
+        let snapshot = self.status_snapshot(now);
+        if let Some(server) = &self.status_server {
+            server.publish(snapshot);
+        }
+    }
+
+    /// What the status page shows right now.
+    fn status_snapshot(&mut self, now: Instant) -> StatusSnapshot {
+        let view = self.plugin_view().unwrap_or_default();
+        let plugins = view
+            .sessions
+            .iter()
+            .map(|session| status_page::PluginStatus {
+                id: session.plugin_id.clone(),
+                version: session.version.clone(),
+                running: session.running,
+                health: session.health,
+            })
+            .collect();
+        let plugin_stats = view.stats.clone();
+        let logs = self.console.log_lines();
+        let logs = logs
+            .iter()
+            .skip(logs.len().saturating_sub(status_page::LOG_LINES))
+            .map(|entry| status_page::LogLine {
+                level: entry.level.to_string(),
+                target: entry.target.clone(),
+                message: entry.message.clone(),
+            })
+            .collect();
+        let charts = self
+            .telemetry
+            .snapshot()
+            .into_iter()
+            .filter_map(|series| {
+                let points = self.telemetry.numeric_last_n(
+                    &series.source,
+                    &series.key,
+                    status_page::CHART_POINTS,
+                );
+                (points.len() > 1).then_some(status_page::Chart {
+                    source: series.source,
+                    key: series.key,
+                    points,
+                })
+            })
+            .collect();
+        StatusSnapshot {
+            version: version::SUMMARY,
+            uptime_secs: now
+                .saturating_duration_since(self.state.started_at)
+                .as_secs(),
+            tps: self.tick_counter.tps(),
+            active_module: self.registry.active_id().map(str::to_string),
+            modules: self
+                .registry
+                .list()
+                .into_iter()
+                .map(|(id, title)| status_page::ModuleStatus {
+                    id: id.to_string(),
+                    title: title.to_string(),
+                })
+                .collect(),
+            plugins,
+            logs,
+            charts,
+            plugin_stats,
+            bus: Some(self.bus.stats()),
+        }
+    }
+
+    /// Retune the console and agent for `profile` and tell modules. The
+    /// loop reads tick and poll rates from `self.profile` directly.
+    fn apply_profile(&mut self, profile: Profile) {
+        let tuning = profile.tuning();
+        self.profile = profile;
+        self.apply_console_motion();
+        self.agent.set_frame_interval(tuning.face_frame_interval);
+        tracing::info!(%profile, "profile active");
+        self.bus.publish(Event::ProfileChanged { profile });
+    }
+
+    /// Set the console slide length and curve from the `console` command or
+    /// `[console]`, falling back to the profile's slide length.
+    fn apply_console_motion(&mut self) {
+        let layout = self.console_layout.unwrap_or(self.settings.console);
+        self.console
+            .set_slide_duration(layout.slide_duration(self.profile));
+        self.console.set_easing(layout.easing);
+    }
+
+    /// Drain the bus and route each event. Returns `true` on quit.
+    fn process_events(&mut self) -> bool {
+        let events = self.bus.drain();
+        for ev in &events {
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record(ev);
+            }
+            if matches!(ev, Event::Quit) {
+                return true;
+            }
+            if let Event::ModuleActivated { id, .. } = ev {
+                if let Some(m) = self.registry.get(id) {
+                    self.state.status_line = module_status(m.title());
+                }
+            }
+            if let Event::AttentionRequested {
+                id,
+                level: AttentionLevel::Alert,
+            } = ev
+            {
+                // Alert-level requests also surface in the log, like alerts.
+                if let Some(m) = self
+                    .registry
+                    .get(id)
+                    .filter(|_| self.registry.active_id() != Some(id.as_str()))
+                {
+                    tracing::warn!(module = %id, "{} needs attention", m.title());
+                }
+            }
+            if self
+                .event_tail
+                .as_ref()
+                .is_some_and(|tail| tail.matches(ev))
+            {
+                self.console.push_log(LogEntry {
+                    level: LogLevel::Info,
+                    target: events::TAIL_TARGET.into(),
+                    message: EventTail::line(ev),
+                    count: 1,
+                });
+            }
+            self.registry.broadcast(ev);
+            self.telemetry.ingest(ev, Instant::now());
+            self.state_changes.observe(ev);
+            self.spans.ingest(ev);
+            if let Event::Tick { now } = ev {
+                self.record_bus_telemetry(*now);
+                for warning in logging::take_file_warnings() {
+                    tracing::warn!("log cleanup: {warning}");
+                }
+                self.evaluate_alerts(*now);
+                if self.run_sleeping(*now) {
+                    self.bus.publish(Event::Quit);
+                }
+                self.sync_plugin_feed(*now);
+                self.forward_state_to_plugins();
+            }
+            self.forward_event_to_plugins(ev);
+        }
+        // Under the `block` overflow policy, modules keep their events
+        // until the queue has room.
+        let module_events = if self.bus.is_blocking() {
+            Vec::new()
+        } else {
+            self.registry.drain_events()
+        };
+        for ev in module_events {
+            if let Event::Custom { tag, payload } = &ev {
+                if let Err(violations) = self.event_schemas.validate(tag, payload) {
+                    tracing::warn!(
+                        tag = %tag,
+                        "dropping module event that does not match its schema: {}",
+                        violations[0]
+                    );
+                    continue;
+                }
+            }
+            self.bus.publish(ev);
+        }
+        false
+    }
+
+    /// Drain new entries from the shared log buffer into the console, and
+    /// stream them to plugins subscribed with `spud.logs.subscribe` and
+    /// control clients subscribed with `spud.control.logs_subscribe`.
+    fn sync_logs(&mut self) {
+        let now = Instant::now();
+        // Taken out before forwarding: host calls answered meanwhile may log.
+        let entries: Vec<LogEntry> = match self.log_buffer.lock() {
+            Ok(mut buf) => buf.drain(..).collect(),
+            Err(_) => return,
+        };
+        if entries.is_empty() {
+            return;
+        }
+        self.forward_logs(&entries, now);
+        for entry in entries {
+            self.console.ingest_log(entry, now);
+        }
+    }
+
+    /// Log a line a dev mode plugin wrote to stderr at debug level. Dev
+    /// mode is asked for to see that output, so when the log filter would
+    /// drop it, the line goes to the console feed (and headless stderr)
+    /// anyway, as the log event would have appeared.
+    fn show_plugin_stderr(&mut self, plugin_id: &str, line: &str) {
+        if !tracing::enabled!(target: PLUGIN_STDERR_TARGET, tracing::Level::DEBUG) {
+            let entry = LogEntry {
+                level: LogLevel::Debug,
+                target: PLUGIN_STDERR_TARGET.to_string(),
+                message: format!("{line} plugin_id={plugin_id}"),
+                count: 1,
+            };
+            if let Ok(mut buf) = self.log_buffer.lock() {
+                buf.push_back(entry);
+            }
+        }
+        tracing::debug!(target: PLUGIN_STDERR_TARGET, plugin_id = %plugin_id, "{line}");
+    }
+
+    /// Execute a console command and handle the output. While a prompt is
+    /// pending the input answers it instead. `interactive` is whether the
+    /// line was typed, so destructive commands can ask for confirmation.
+    fn dispatch_command(&mut self, input: &str, interactive: bool) -> bool {
+        // Only a typed line answers a pending question; alert commands
+        // and other non-interactive callers leave it for the user.
+        if interactive {
+            if let Some(prompt) = self.console.take_prompt() {
+                return self.answer_prompt(prompt, input.trim());
+            }
+        }
+        if input.trim().is_empty() {
+            return false;
+        }
+
+        // Echo the command itself
+        self.console.push_log(LogEntry {
+            level: LogLevel::Info,
+            target: INPUT_TARGET.into(),
+            message: input.trim().to_string(),
+            count: 1,
+        });
+
+        let (line, redirect) =
+            match redirect::split(input, interactive, redirect::default_dir().as_deref()) {
+                Ok(split) => split,
+                Err(err) => return self.show_output(CommandOutput::Error(vec![err])),
+            };
+        let vars = self.command_vars();
+        let mut asked_by = None;
+        let (output, pending) = pipeline::start(line, &vars, |words| {
+            let (output, origin) = self.execute_words_from(words, interactive);
+            asked_by = Some(origin);
+            output
+        });
+        self.finish_command(input, output, pending, asked_by, interactive, redirect)
+    }
+
+    /// Variables for a command line: the built-ins plus the latest value
+    /// of every telemetry series as `source.key`.
+    fn command_vars(&self) -> Vars {
+        let mut vars = Vars::builtin(&self.registry, &self.tick_counter, self.state.started_at);
+        vars.add_telemetry(&self.telemetry);
+        vars
+    }
+
+    /// Record and show (or redirect) a command line's output, ask any
+    /// follow-up, and schedule what a `sleep` left. Returns `true` on quit.
+    fn finish_command(
+        &mut self,
+        input: &str,
+        output: CommandOutput,
+        pending: Option<pipeline::Pending>,
+        asked_by: Option<PromptOrigin>,
+        interactive: bool,
+        redirect: Option<Redirect>,
+    ) -> bool {
+        self.transcript.record(input, &output, Instant::now());
+        if let Some(origin) = asked_by {
+            self.ask_follow_up(&output, origin);
+        }
+        let output = match &redirect {
+            Some(redirect) => redirect::apply(redirect, output),
+            None => output,
+        };
+        if let Some(pending) = pending {
+            self.sleeping.push(Sleeping {
+                at: Instant::now() + pending.delay,
+                input: input.to_string(),
+                rest: pending.rest,
+                interactive,
+                redirect: redirect.map(|redirect| Redirect {
+                    append: true,
+                    ..redirect
+                }),
+            });
+        }
+        self.show_output(output)
+    }
+
+    /// Resume command lines whose `sleep` is over. Returns `true` on quit.
+    fn run_sleeping(&mut self, now: Instant) -> bool {
+        let (due, waiting) = std::mem::take(&mut self.sleeping)
+            .into_iter()
+            .partition::<Vec<_>, _>(|sleeping| sleeping.at <= now);
+        self.sleeping = waiting;
+        for Sleeping {
+            input,
+            rest,
+            interactive,
+            redirect,
+            ..
+        } in due
+        {
+            let vars = self.command_vars();
+            let mut asked_by = None;
+            let (output, pending) = pipeline::resume(&rest, &vars, |words| {
+                let (output, origin) = self.execute_words_from(words, interactive);
+                asked_by = Some(origin);
+                output
+            });
+            if self.finish_command(&input, output, pending, asked_by, interactive, redirect) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Send `answer` to whoever asked `prompt`, then show their reply.
+    fn answer_prompt(&mut self, prompt: ConsolePrompt, answer: &str) -> bool {
+        self.console.push_log(LogEntry {
+            level: LogLevel::Info,
+            target: OUTPUT_TARGET.into(),
+            message: format!("{} {answer}", prompt.question),
+            count: 1,
+        });
+        let ConsolePrompt {
+            callback_token,
+            origin,
+            ..
+        } = prompt;
+        let token = callback_token.as_str();
+        let (output, origin) = match origin {
+            PromptOrigin::Confirm(words) => {
+                if guard::confirmed(answer) {
+                    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+                    self.route_words(&words)
+                } else {
+                    let output = CommandOutput::Lines(vec!["cancelled".to_string()]);
+                    (output, PromptOrigin::Confirm(words))
+                }
+            }
+            PromptOrigin::Command(name) => {
+                let mut ctx = CommandContext {
+                    registry: &mut self.registry,
+                    console: &mut self.console,
+                    bus: &mut self.bus,
+                    tick_counter: &self.tick_counter,
+                    started_at: self.state.started_at,
+                    spans: &self.spans,
+                    rng: &mut self.rng,
+                    undo: &mut self.undo,
+                    origin: CommandOrigin::Console,
+                };
+                let output = self.commands.answer(&name, token, answer, &mut ctx);
+                (output, PromptOrigin::Command(name))
+            }
+            PromptOrigin::Module(id) => {
+                let output = self
+                    .registry
+                    .get_mut(&id)
+                    .and_then(|module| module.console_answer(token, answer))
+                    .unwrap_or_else(|| {
+                        CommandOutput::Error(vec![format!("{id} has no pending question")])
+                    });
+                (output, PromptOrigin::Module(id))
+            }
+            PromptOrigin::Plugin(id) => {
+                let params = ConsoleAnswerParams {
+                    callback_token: callback_token.clone(),
+                    answer: answer.to_string(),
+                    cancelled: false,
+                };
+                let sent =
+                    self.with_plugin_runtime(|runtime| runtime.send_console_answer(&id, params));
+                let output = match sent {
+                    Some(Ok(())) => CommandOutput::Lines(Vec::new()),
+                    Some(Err(err)) => CommandOutput::Error(vec![err.to_string()]),
+                    None => CommandOutput::Error(vec!["plugin runtime is disabled".to_string()]),
+                };
+                (output, PromptOrigin::Plugin(id))
+            }
+        };
+        self.transcript.record(answer, &output, Instant::now());
+        self.ask_follow_up(&output, origin);
+        self.show_output(output)
+    }
+
+    /// Hand the input line to `origin` if `output` ends with a prompt. A
+    /// question already waiting for an answer keeps the input line; the new
+    /// one is logged and dropped.
+    fn ask_follow_up(&mut self, output: &CommandOutput, origin: PromptOrigin) {
+        if let Some(CommandOutput::Prompt {
+            question,
+            callback_token,
+        }) = output.parts().last()
+        {
+            if let Some(pending) = self.console.prompt() {
+                tracing::warn!(
+                    "not asking \"{question}\": \"{}\" is still waiting for an answer",
+                    pending.question
+                );
+                return;
+            }
+            self.console.set_prompt(ConsolePrompt {
+                question: question.clone(),
+                callback_token: callback_token.clone(),
+                origin,
+            });
+        }
+    }
+
+    /// Drop the pending question, if any, telling a plugin that asked it
+    /// that no answer is coming.
+    fn cancel_prompt(&mut self) {
+        let Some(prompt) = self.console.take_prompt() else {
+            return;
+        };
+        tracing::info!("prompt cancelled");
+        if let PromptOrigin::Plugin(plugin_id) = prompt.origin {
+            let params = ConsoleAnswerParams {
+                callback_token: prompt.callback_token,
+                answer: String::new(),
+                cancelled: true,
+            };
+            self.submit_to_plugins("send a cancelled answer", move |runtime| {
+                runtime.send_console_answer(&plugin_id, params)
+            });
+        }
+    }
+
+    /// Run one command that was not typed, checking app-level commands
+    /// before the core registry.
+    fn execute_words(&mut self, words: &[&str]) -> CommandOutput {
+        self.execute_words_from(words, false).0
+    }
+
+    /// Like [`execute_words`](Self::execute_words), also returning who would
+    /// receive the answer if the command asked a question. `[commands]` in
+    /// `spud.toml` is checked first; see [`guard::check`].
+    fn execute_words_from(
+        &mut self,
+        words: &[&str],
+        interactive: bool,
+    ) -> (CommandOutput, PromptOrigin) {
+        match self.gate(words, interactive) {
+            Gate::Run => self.route_words(words),
+            Gate::Confirm(question) => (
+                CommandOutput::prompt(question, CONFIRM_TOKEN),
+                PromptOrigin::Confirm(words.iter().map(|word| word.to_string()).collect()),
+            ),
+            Gate::Refuse(reason) => (
+                CommandOutput::Error(vec![reason]),
+                PromptOrigin::Command(words.first().copied().unwrap_or_default().to_string()),
+            ),
+        }
+    }
+
+    /// Whether `[commands]` in `spud.toml` lets `words` run.
+    fn gate(&self, words: &[&str], interactive: bool) -> Gate {
+        let (name, args) = words.split_first().unwrap_or((&"", &[]));
+        let destructive = if let Some(command) = self.commands.get(name) {
+            command.destructive(args)
+        } else if *name == plugins::NAME {
+            plugins::destructive(args)
+        } else {
+            self.registry
+                .get(name)
+                .is_some_and(|module| module.console_destructive(args))
+        };
+        guard::check(
+            &self.settings.commands,
+            &self.commands,
+            words,
+            destructive,
+            interactive,
+        )
+    }
+
+    /// Ask in the console before quitting from the `q` key, if `spud.toml`
+    /// wants that. Returns whether to quit now.
+    fn quit_key(&mut self) -> bool {
+        match self.gate(&["quit"], !self.options.headless) {
+            Gate::Run => true,
+            Gate::Confirm(question) => {
+                // The quit key outranks whatever was asked before
+                self.cancel_prompt();
+                self.console.set_prompt(ConsolePrompt {
+                    question,
+                    callback_token: CONFIRM_TOKEN.to_string(),
+                    origin: PromptOrigin::Confirm(vec!["quit".to_string()]),
+                });
+                if !self.console.is_visible() {
+                    self.console.toggle(Instant::now());
+                }
+                false
+            }
+            Gate::Refuse(reason) => {
+                tracing::warn!("{reason}");
+                false
+            }
+        }
+    }
+
+    /// Run one command that passed the gate, returning who would receive
+    /// the answer if it asked a question.
+    fn route_words(&mut self, words: &[&str]) -> (CommandOutput, PromptOrigin) {
+        let name = words.first().copied().unwrap_or_default();
+        self.record_usage(name);
+        if let [module, subcommand, args @ ..] = words {
+            if *module == command_stats::MODULE && *subcommand == command_stats::SUBCOMMAND {
+                let output =
+                    command_stats::execute(self.console.usage_mut(), args, SystemTime::now());
+                return (output, PromptOrigin::Command(name.to_string()));
+            }
+        }
+        if self.commands.get(name).is_none() && !APP_COMMANDS.iter().any(|(app, _)| *app == name) {
+            // `<module id> ...`, unless a command has that name
+            if let Some(module) = self.registry.get_mut(name) {
+                if let Some(output) = module.console_command(&words[1..]) {
+                    return (output, PromptOrigin::Module(name.to_string()));
+                }
+            }
+        }
+        let output = self.run_words(words);
+        (output, PromptOrigin::Command(name.to_string()))
+    }
+
+    /// Let `undo` put back the setting `words` changed, if it changed.
+    /// `previous` and `current` are the command's override before and
+    /// after.
+    fn record_setting<T: Serialize + PartialEq>(
+        &mut self,
+        words: &[&str],
+        previous: T,
+        current: T,
+    ) {
+        if previous == current {
+            return;
+        }
+        match serde_json::to_value(&previous) {
+            Ok(previous) => self.undo.push(
+                words.join(" "),
+                Inverse::Setting {
+                    name: words[0].to_string(),
+                    previous,
+                },
+                Instant::now(),
+            ),
+            Err(err) => tracing::warn!("{} cannot be undone: {err}", words.join(" ")),
+        }
+    }
+
+    /// Reverse an action popped by `undo`.
+    fn apply_inverse(&mut self, inverse: Inverse) -> Result<(), String> {
+        match inverse {
+            Inverse::RestoreLogs(lines) => self.console.restore_logs(lines),
+            Inverse::Setting { name, previous } => {
+                let invalid = |err: serde_json::Error| format!("cannot restore {name}: {err}");
+                match name.as_str() {
+                    hud::NAME => {
+                        self.hud_size = serde_json::from_value(previous).map_err(invalid)?
+                    }
+                    console::NAME => {
+                        self.console_layout = serde_json::from_value(previous).map_err(invalid)?;
+                        self.apply_console_motion();
+                    }
+                    terminal::NAME => {
+                        self.sync_output = serde_json::from_value(previous).map_err(invalid)?;
+                        self.apply_sync_output();
+                    }
+                    _ => return Err(format!("cannot restore {name}")),
+                }
+            }
+            Inverse::Run(line) => {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                let output = self.execute_words(&words);
+                if output.is_error() {
+                    return Err(output.to_lines().join("; "));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Count a run of the command `name` names, under its canonical name.
+    /// Unknown names are not counted.
+    fn record_usage(&mut self, name: &str) {
+        let known = match self.commands.get(name) {
+            Some(command) => Some(command.name().to_string()),
+            None => (APP_COMMANDS.iter().any(|(app, _)| *app == name)
+                || self.registry.get(name).is_some())
+            .then(|| name.to_string()),
+        };
+        if let Some(known) = known {
+            self.console.usage_mut().record(&known, SystemTime::now());
+        }
+    }
+
+    fn run_words(&mut self, words: &[&str]) -> CommandOutput {
+        match words {
+            // List every command, including the app-level ones
+            ["help"] | ["?"] => CommandOutput::Table(self.help_table()),
+            [name, ..] if *name == plugins::NAME && self.plugin_discovery.is_some() => {
+                CommandOutput::Lines(vec![tr("plugin discovery still running").to_string()])
+            }
+            [name, action, plugin_id]
+                if *name == plugins::NAME && plugins::CONTROL.contains(action) =>
+            {
+                let output = self
+                    .with_plugin_host(|pump, host| plugins::control(pump, host, action, plugin_id))
+                    .unwrap_or_else(|| plugins::execute(None, &[]));
+                if *action == "stop" && !output.is_error() {
+                    self.undo.push(
+                        words.join(" "),
+                        Inverse::Run(format!("{} start {plugin_id}", plugins::NAME)),
+                        Instant::now(),
+                    );
+                }
+                output
+            }
+            [name, action, args @ ..] if *name == plugins::NAME && *action == plugins::DEV => {
+                let mut watcher = std::mem::take(&mut self.plugin_dev);
+                let output = self
+                    .with_plugin_host(|pump, host| plugins::dev(pump, host, &mut watcher, args))
+                    .unwrap_or_else(|| plugins::execute(None, &[]));
+                self.plugin_dev = watcher;
+                output
+            }
+            [name, args @ ..] if *name == plugins::NAME => self
+                .with_plugin_runtime(|runtime| plugins::execute(Some(runtime), args))
+                .unwrap_or_else(|| plugins::execute(None, args)),
+            [name, args @ ..] if *name == alerts::NAME => {
+                let now = Instant::now();
+                let output = alerts::execute(&mut self.alerts, args, now);
+                self.sync_alert_mood(now);
+                output
+            }
+            [name, args @ ..] if *name == doctor::NAME => doctor::execute(&self.doctor, args),
+            [name, args @ ..] if *name == version::NAME => version::execute(args),
+            [name, args @ ..] if *name == profile::NAME => {
+                let (output, switch) = profile::execute(self.profile, args);
+                if let Some(profile) = switch {
+                    self.apply_profile(profile);
+                }
+                output
+            }
+            [name, args @ ..] if *name == debug::NAME => {
+                debug::execute(self.bus.tap_mut(), &mut self.show_events, args)
+            }
+            [name, args @ ..] if *name == transcript::NAME => {
+                transcript::execute(&mut self.transcript, args)
+            }
+            [name, args @ ..] if *name == sys::NAME => {
+                let stats = self
+                    .registry
+                    .get("stats")
+                    .and_then(|module| module.downcast_ref::<StatsModule>());
+                sys::execute(stats, args)
+            }
+            [name, args @ ..] if *name == events::NAME => {
+                let (output, event) =
+                    events::execute(&mut self.event_tail, &self.event_schemas, args);
+                if let Some(event) = event {
+                    self.bus.publish(event);
+                }
+                output
+            }
+            [name, args @ ..] if *name == workspace::NAME => {
+                let (output, switch) =
+                    workspace::execute(&self.options, self.workspace.as_ref(), &self.doctor, args);
+                match switch {
+                    Some(switch) => self.switch_workspace(switch),
+                    None => output,
+                }
+            }
+            [name, args @ ..] if *name == undo::NAME => {
+                let (output, entry) = undo::execute(&mut self.undo, args, Instant::now());
+                match entry.map(|entry| self.apply_inverse(entry.inverse)) {
+                    Some(Err(err)) => CommandOutput::Error(vec![err]),
+                    _ => output,
+                }
+            }
+            [name, args @ ..] if *name == hud::NAME => {
+                let previous = self.hud_size;
+                let output = hud::execute(&mut self.hud_size, self.settings.hud, args);
+                self.record_setting(words, previous, self.hud_size);
+                output
+            }
+            [name, args @ ..] if *name == console::NAME => {
+                let previous = self.console_layout;
+                let output =
+                    console::execute(&mut self.console_layout, self.settings.console, args);
+                self.record_setting(words, previous, self.console_layout);
+                self.apply_console_motion();
+                output
+            }
+            [name, args @ ..] if *name == terminal::NAME => {
+                let detected = doctor::sync_output_terminal(&|key| std::env::var(key).ok());
+                let previous = self.sync_output;
+                let output = terminal::execute(
+                    &mut self.sync_output,
+                    self.settings.terminal.sync_output,
+                    detected,
+                    args,
+                );
+                self.record_setting(words, previous, self.sync_output);
+                self.apply_sync_output();
+                output
+            }
+            [name, args @ ..] if *name == module::NAME => {
+                let (output, events) = module::execute(
+                    &mut self.registry,
+                    &self.module_factories,
+                    &self.settings,
+                    args,
+                );
+                self.after_module_restart(events);
+                output
+            }
+            [name, args @ ..] if *name == dump::NAME => {
+                let plugins = self
+                    .plugin_view()
+                    .map(|view| view.sessions.clone())
+                    .unwrap_or_default();
+                dump::execute(&self.state_dump(plugins), &logging::log_dir(), args)
+            }
+            _ => {
+                let mut ctx = CommandContext {
+                    registry: &mut self.registry,
+                    console: &mut self.console,
+                    bus: &mut self.bus,
+                    tick_counter: &self.tick_counter,
+                    started_at: self.state.started_at,
+                    spans: &self.spans,
+                    rng: &mut self.rng,
+                    undo: &mut self.undo,
+                    origin: CommandOrigin::Console,
+                };
+                self.commands.execute_words(words, &mut ctx)
+            }
+        }
+    }
+
+    /// Serializable snapshot of every subsystem, for `dump state`.
+    fn state_dump(&self, plugins: Vec<PluginSessionSnapshot>) -> dump::StateDump {
+        dump::StateDump {
+            version: version::SUMMARY,
+            taken_at_ms: unix_ms(),
+            seed: self.registry.rng().seed(),
+            profile: self.profile,
+            state: self.state.snapshot(),
+            modules: self.registry.snapshot(),
+            console: self.console.snapshot(),
+            ticks: self.tick_counter.snapshot(),
+            plugins,
+            telemetry: self.telemetry.snapshot(),
+        }
+    }
+
+    /// Every command with its description, including the app-level ones
+    /// and module console commands.
+    fn help_table(&self) -> Table {
+        let mut table = Table::new(vec![
+            Column::left(tr("COMMAND")),
+            Column::left(tr("DESCRIPTION")),
+        ]);
+        for cmd in self.commands.commands() {
+            let aliases = cmd.aliases();
+            let description = if aliases.is_empty() {
+                cmd.description().to_string()
+            } else {
+                format!("{} (aliases: {})", cmd.description(), aliases.join(", "))
+            };
+            table.push_row(vec![cmd.usage().to_string(), description]);
+        }
+        for (usage, description) in [
+            (plugins::USAGE, plugins::DESCRIPTION),
+            (alerts::USAGE, alerts::DESCRIPTION),
+            (doctor::USAGE, doctor::DESCRIPTION),
+            (version::USAGE, version::DESCRIPTION),
+            (debug::USAGE, debug::DESCRIPTION),
+            (profile::USAGE, profile::DESCRIPTION),
+            (dump::USAGE, dump::DESCRIPTION),
+            (transcript::USAGE, transcript::DESCRIPTION),
+            (sys::USAGE, sys::DESCRIPTION),
+            (undo::USAGE, undo::DESCRIPTION),
+            (command_stats::USAGE, command_stats::DESCRIPTION),
+            (events::USAGE, events::DESCRIPTION),
+            (workspace::USAGE, workspace::DESCRIPTION),
+            (hud::USAGE, hud::DESCRIPTION),
+            (console::USAGE, console::DESCRIPTION),
+            (terminal::USAGE, terminal::DESCRIPTION),
+            (module::USAGE, module::DESCRIPTION),
+        ] {
+            table.push_row(vec![usage.to_string(), tr(description).to_string()]);
+        }
+        for (usage, description) in self.registry.console_usage() {
+            table.push_row(vec![usage.to_string(), tr(description).to_string()]);
+        }
+        for (_, usage, description) in pipeline::CONTROL_COMMANDS {
+            table.push_row(vec![usage.to_string(), tr(description).to_string()]);
+        }
+        table.push_row(vec![
+            "<cmd> | ...".to_string(),
+            tr_args(
+                "Filter output ({usage})",
+                &[("usage", &pipeline::FILTER_USAGE)],
+            ),
+        ]);
+        table.push_row(vec![
+            "<cmd> ; <cmd>".to_string(),
+            tr("Run commands in order").to_string(),
+        ]);
+        table
+    }
+
+    /// Print a command's output to the console. Returns `true` if the
+    /// command asked to quit. Prompts are left to the input line.
+    fn show_output(&mut self, output: CommandOutput) -> bool {
+        self.console.push_output(&output);
+        output.status() == CommandStatus::Quit
+    }
+
+    /// Discover plugins under `roots` on a background thread, so the shell
+    /// comes up without waiting on manifests and integrity checks. The
+    /// plugins start once [`poll_plugin_discovery`](Self::poll_plugin_discovery)
+    /// picks up the runtime.
+    fn init_plugin_runtime(&mut self, roots: Vec<PathBuf>, splash: &mut Splash<'_>) {
+        self.plugin_discovery = None;
+        self.plugins_starting.clear();
+        if roots.is_empty() {
+            tracing::info!(
+                "plugin runtime disabled (pass --plugin-dir or set SPUD_PLUGIN_DIRS to enable)"
+            );
+            return;
+        }
+
+        let config = self.settings.plugins.clone();
+        let (tx, rx) = mpsc::sync_channel(1);
+        let spawned = thread::Builder::new()
+            .name("spud-plugin-discovery".into())
+            .spawn(move || {
+                // The app may have reloaded or quit meanwhile
+                let _ = tx.send(discover_plugins(&roots, &config));
+            });
+        match spawned {
+            Ok(_) => {
+                self.plugin_discovery = Some(rx);
+                splash.wait_on(DISCOVERY_STEP, tr("plugin discovery"));
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "plugin discovery thread failed to start; plugins disabled");
+                splash.failed(tr("plugin discovery"), err.to_string());
+            }
+        }
+    }
+
+    /// Start the discovered plugins once the discovery thread is done.
+    /// Handshakes run side by side on their own threads and end in
+    /// `PumpReport::Started` or `Failed`.
+    fn poll_plugin_discovery(&mut self, splash: &mut Splash<'_>) {
+        let Some(discovery) = &self.plugin_discovery else {
+            return;
+        };
+        let result = match discovery.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("discovery thread stopped".to_string()),
+        };
+        self.plugin_discovery = None;
+        let mut runtime = match result {
+            Ok(runtime) => runtime,
+            Err(err) => {
+                tracing::warn!("plugins not started: {err}");
+                splash.resolve(DISCOVERY_STEP, StepStatus::Failed(err));
+                return;
+            }
+        };
+
+        let plugin_ids = runtime
+            .plugin_ids()
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        splash.resolve(
+            DISCOVERY_STEP,
+            StepStatus::Done(tr_args("{count} found", &[("count", &plugin_ids.len())])),
+        );
+        // Dev mode outlives a reload for plugins still discovered
+        for plugin_id in self.plugin_dev.plugin_ids() {
+            match runtime.set_dev_mode(&plugin_id, true) {
+                Ok(()) => self.plugin_dev.watch(
+                    &plugin_id,
+                    runtime.plugin_files(&plugin_id).unwrap_or_default(),
+                ),
+                Err(_) => {
+                    self.plugin_dev.unwatch(&plugin_id);
+                }
+            }
+        }
+        self.register_plugin_modules(&runtime);
+        self.start_plugin_pump(runtime);
+
+        if plugin_ids.is_empty() {
+            tracing::info!("plugin runtime enabled but no plugin manifests were discovered");
+            return;
+        }
+
+        tracing::info!(
+            plugin_count = plugin_ids.len(),
+            "starting discovered plugin runtime sessions"
+        );
+
+        for plugin_id in plugin_ids {
+            let label = tr_args("plugin {id}", &[("id", &plugin_id)]);
+            let status = match self
+                .with_plugin_host(|pump, host| pump.start_plugin(host, &plugin_id, false))
+            {
+                Some(Ok(())) => {
+                    splash.wait_on(plugin_id.clone(), label);
+                    self.plugins_starting.insert(plugin_id.clone());
+                    PluginStatus::Starting
+                }
+                Some(Err(err)) => {
+                    tracing::warn!(
+                        plugin_id = %plugin_id,
+                        error = %err,
+                        "failed to start plugin runtime session"
+                    );
+                    splash.failed(label, err.to_string());
+                    PluginStatus::Failed(err.to_string())
+                }
+                None => return,
+            };
+            self.bus.publish(Event::PluginStatus { plugin_id, status });
+        }
+    }
+
+    /// Add a [`PluginModule`] for each discovered plugin with a `[module]`
+    /// section. A module id already taken by another module is skipped.
+    fn register_plugin_modules(&mut self, runtime: &PluginRuntime) {
+        for plugin_id in runtime.plugin_ids() {
+            let Some(spec) = runtime
+                .manifest(plugin_id)
+                .and_then(|manifest| manifest.module.as_ref())
+            else {
+                continue;
+            };
+            let module = PluginModule::new(plugin_id, spec);
+            if let Err(err) = self.registry.register(Box::new(module)) {
+                tracing::warn!(plugin_id, error = %err, "plugin module not registered");
+            }
+        }
+    }
+
+    /// Remove the modules registered by [`register_plugin_modules`](Self::register_plugin_modules).
+    fn unregister_plugin_modules(&mut self) {
+        let ids: Vec<String> = self
+            .registry
+            .list()
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|id| {
+                self.registry
+                    .get(id)
+                    .is_some_and(|module| module.downcast_ref::<PluginModule>().is_some())
+            })
+            .map(str::to_string)
+            .collect();
+        for id in ids {
+            if let Ok((_, events)) = self.registry.unregister(&id) {
+                for ev in events {
+                    self.bus.publish(ev);
+                }
+            }
+        }
+    }
+
+    /// Keep the splash up until discovery and every plugin on it have
+    /// finished, a key is pressed, or [`SPLASH_TIMEOUT`] passes. Whatever
+    /// is still starting then finishes in the background.
+    pub(crate) fn await_splash(&mut self, splash: &mut Splash<'_>) -> Result<()> {
+        let deadline = Instant::now() + SPLASH_TIMEOUT;
+        while splash.is_waiting() {
+            self.pump_plugin_runtime(PLUGIN_PUMP_BUDGET, splash);
+            if !splash.is_waiting() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                let waiting: Vec<&str> = splash.waiting_keys().collect();
+                tracing::info!("still starting in the background: {}", waiting.join(", "));
+                break;
+            }
+            splash.draw();
+            if event::poll(SPLASH_FRAME)? && matches!(event::read()?, CEvent::Key(_)) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the active module crashed.
+    fn active_crashed(&self) -> bool {
+        self.registry
+            .active_id()
+            .is_some_and(|id| self.registry.crash(id).is_some())
+    }
+
+    /// The active module's keys for the keymap, or the restart key once it
+    /// crashed.
+    fn active_keybindings(&mut self) -> Vec<KeyBinding> {
+        let Some(m) = self.registry.active() else {
+            return Vec::new();
+        };
+        let id = m.id();
+        if self.registry.crash(id).is_some() {
+            return keys::crashed();
+        }
+        match isolate(|| m.keybindings()) {
+            Ok(bindings) => bindings,
+            Err(reason) => {
+                self.registry.mark_crashed(id, reason);
+                keys::crashed()
+            }
+        }
+    }
+
+    /// Restart the active module after it crashed, for the `r` key.
+    fn restart_active_module(&mut self) {
+        let Some(id) = self.registry.active_id().map(str::to_string) else {
+            return;
+        };
+        match module::restart(
+            &mut self.registry,
+            &self.module_factories,
+            &self.settings,
+            &id,
+        ) {
+            Ok(events) => self.after_module_restart(events),
+            Err(err) => tracing::warn!("{err}"),
+        }
+    }
+
+    /// Publish a restart's lifecycle events and hand a fresh plugins module
+    /// its feed.
+    fn after_module_restart(&mut self, events: Vec<Event>) {
+        for ev in events {
+            self.bus.publish(ev);
+        }
+        self.attach_plugin_feed();
+    }
+
+    /// Find the plugins module's feed in the current registry.
+    fn attach_plugin_feed(&mut self) {
+        self.plugin_feed = self
+            .registry
+            .get(PLUGINS_MODULE)
+            .and_then(|module| module.downcast_ref::<PluginsModule>())
+            .map(PluginsModule::feed);
+        self.plugin_feed_at = None;
+    }
+
+    /// Run the actions the plugins module queued, and while it is on
+    /// screen publish plugin state to it every [`PLUGIN_FEED_INTERVAL`].
+    fn sync_plugin_feed(&mut self, now: Instant) {
+        let Some(feed) = self.plugin_feed.clone() else {
+            return;
+        };
+        for action in feed.take_actions() {
+            let output = self.execute_words(&[plugins::NAME, action.verb(), action.plugin_id()]);
+            let message = output.to_lines().join(" ");
+            if output.is_error() {
+                tracing::warn!(plugin_id = %action.plugin_id(), "{message}");
+            } else {
+                tracing::info!(plugin_id = %action.plugin_id(), "{message}");
+            }
+            self.plugin_feed_at = None;
+        }
+        if self.registry.active_id() != Some(PLUGINS_MODULE)
+            || self
+                .plugin_feed_at
+                .is_some_and(|at| now.saturating_duration_since(at) < PLUGIN_FEED_INTERVAL)
+        {
+            return;
+        }
+        self.plugin_feed_at = Some(now);
+        let plugins = self.plugin_view().map(|view| {
+            view.sessions
+                .iter()
+                .zip(&view.stats)
+                .zip(&view.manifests)
+                .map(|((session, stats), manifest)| PluginEntry {
+                    manifest: manifest.clone(),
+                    stats: stats.clone(),
+                    session: session.clone(),
+                })
+                .collect()
+        });
+        feed.publish(plugins);
+    }
+
+    /// Hand `runtime` to a pump thread, which handles plugin requests off
+    /// the main loop.
+    fn start_plugin_pump(&mut self, runtime: PluginRuntime) {
+        match PluginPump::spawn(runtime) {
+            Ok(pump) => self.plugin_pump = Some(pump),
+            Err(err) => {
+                tracing::warn!(error = %err, "plugin pump thread failed to start; plugins disabled");
+            }
+        }
+    }
+
+    /// Run `f` with the plugin pump and a host bridge over the app state.
+    fn with_plugin_host<R>(&mut self, f: impl FnOnce(&PluginPump, &mut AppHost) -> R) -> Option<R> {
+        let pump = self.plugin_pump.as_ref()?;
+        let mut host = AppHost {
+            state: &self.state,
+            registry: &mut self.registry,
+            bus: &mut self.bus,
+            console: &mut self.console,
+            tick_counter: &self.tick_counter,
+            commands: &self.commands,
+            telemetry: &self.telemetry,
+            spans: &self.spans,
+            rng: &mut self.rng,
+            undo: &mut self.undo,
+            event_schemas: &mut self.event_schemas,
+            command_policy: &self.settings.commands,
+            interactive: !self.options.headless,
+        };
+        Some(f(pump, &mut host))
+    }
+
+    /// Run `f` with the plugin runtime, answering plugin host calls while
+    /// the pump thread holds it. `None` when the plugin runtime is off. For
+    /// commands; per-frame work uses [`Self::submit_to_plugins`] and
+    /// [`Self::plugin_view`].
+    fn with_plugin_runtime<R>(&mut self, f: impl FnOnce(&mut PluginRuntime) -> R) -> Option<R> {
+        self.with_plugin_host(|pump, host| pump.with_runtime(host, f))
+    }
+
+    /// Hand `job` to the pump thread without waiting for the runtime.
+    /// Failures come back as [`PumpReport::JobFailed`].
+    fn submit_to_plugins(
+        &self,
+        what: &'static str,
+        job: impl FnOnce(&mut PluginRuntime) -> Result<(), RuntimeError> + Send + 'static,
+    ) {
+        if let Some(pump) = &self.plugin_pump {
+            pump.submit(what, job);
+        }
+    }
+
+    /// Plugin sessions and counters as the pump thread last published
+    /// them. `None` when the plugin runtime is off.
+    fn plugin_view(&self) -> Option<Arc<RuntimeView>> {
+        self.plugin_pump.as_ref().map(PluginPump::view)
+    }
+
+    /// Tear down modules and plugins and start them again in another
+    /// workspace. Nothing is torn down if the new workspace's modules or
+    /// settings fail to load.
+    fn switch_workspace(&mut self, switch: workspace::Switch) -> CommandOutput {
+        let target = match switch {
+            workspace::Switch::To(workspace) => Some(workspace),
+            workspace::Switch::Leave => None,
+        };
+        let effective = workspace::apply(&self.options, target.as_ref());
+        let config_dir = effective.config_dir();
+        let config_watcher = ConfigWatcher::new(config_dir.as_deref(), Instant::now());
+        let prepared = config_watcher.load().and_then(|settings| {
+            let registry = workspace::build_registry(
+                self.registry.rng(),
+                &self.module_factories,
+                target.as_ref(),
+            )?;
+            Ok((settings, registry))
+        });
+        let (settings, registry) = match prepared {
+            Ok(prepared) => prepared,
+            Err(err) => return CommandOutput::Error(vec![format!("{err:#}")]),
+        };
+
+        if !self.options.headless {
+            self.save_session();
+        }
+        self.plugin_pump = None;
+        // The outgoing module hears its deactivation before it is dropped;
+        // the bus copy still reaches the tap, the recorder, and telemetry.
+        if let Some(id) = self.registry.active_id() {
+            let deactivated = Event::ModuleDeactivated { id: id.to_string() };
+            self.registry.broadcast(&deactivated);
+            self.bus.publish(deactivated);
+        }
+        self.registry = registry;
+        self.attach_plugin_feed();
+        self.event_schemas = EventSchemas::new();
+        for (module, tag, err) in self
+            .registry
+            .register_event_schemas(&mut self.event_schemas)
+        {
+            tracing::warn!(module, tag, error = %err, "event schema not registered");
+        }
+        if let Some(module) = self.registry.active() {
+            self.state.status_line = module_status(module.title());
+            self.bus.publish(Event::ModuleActivated {
+                id: module.id().to_string(),
+                reason: ActivationReason::Switch,
+            });
+        }
+
+        self.config_watcher = config_watcher;
+        self.alerts = alerts::load_engine(config_dir.as_deref());
+        self.sync_alert_mood(Instant::now());
+        self.doctor = doctor::Settings::from_options(&effective);
+        self.plugin_roots = effective.plugin_roots;
+        self.apply_settings(settings);
+        self.registry.configure(&self.settings);
+        self.workspace = target;
+        if !self.options.headless {
+            self.restore_session(false, true);
+        }
+        self.init_plugin_runtime(self.plugin_roots.clone(), &mut Splash::hidden());
+
+        let name = self
+            .workspace
+            .as_ref()
+            .map_or("(none)", |workspace| workspace.name.as_str());
+        tracing::info!(workspace = name, "workspace active");
+        CommandOutput::Lines(vec![format!("workspace: {name}")])
+    }
+
+    /// `session.json` in the active workspace's state dir.
+    fn session_path(&self) -> Option<PathBuf> {
+        session::path(workspace::state_dir(self.workspace.as_ref()).as_deref())
+    }
+
+    /// Save console and window state for the next start, warning on
+    /// failure.
+    pub(crate) fn save_session(&self) {
+        let Some(path) = self.session_path() else {
+            return;
+        };
+        let session = Session {
+            console_open: self.console.is_open(),
+            scroll_offset: self.console.scroll_offset(),
+            history: self.console.history().iter().cloned().collect(),
+            active_module: self.registry.active_id().map(str::to_string),
+            hud: self.hud_size,
+            console: self.console_layout,
+            command_usage: self.console.usage().clone(),
+        };
+        match session.save(&path) {
+            Ok(()) => tracing::debug!(path = %path.display(), "session saved"),
+            Err(err) => tracing::warn!("session not saved: {err:#}"),
+        }
+    }
+
+    /// Apply the saved session: history, command counts, HUD size, and
+    /// console layout always, console visibility and scroll with `window`, the active
+    /// module with `module`. A missing session resets history, HUD size, and
+    /// console layout to the defaults.
+    fn restore_session(&mut self, window: bool, module: bool) {
+        let Some(path) = self.session_path() else {
+            return;
+        };
+        let session = match Session::load(&path) {
+            Ok(Some(session)) => {
+                tracing::info!(path = %path.display(), "session restored (skip with --fresh)");
+                session
+            }
+            Ok(None) => Session::default(),
+            Err(err) => {
+                tracing::warn!("session not restored: {err:#}");
+                Session::default()
+            }
+        };
+        self.console.restore_history(session.history);
+        *self.console.usage_mut() = session.command_usage;
+        self.hud_size = session.hud.filter(|hud| hud.validate().is_ok());
+        self.console_layout = session.console.filter(|console| console.validate().is_ok());
+        self.apply_console_motion();
+        if window {
+            // Startup logs first, so the scroll position has lines to keep
+            self.sync_logs();
+            self.console.set_open(session.console_open);
+            self.console.set_scroll_offset(session.scroll_offset);
+        }
+        let restored = session
+            .active_module
+            .filter(|_| module)
+            .and_then(|id| self.registry.activate(&id).ok());
+        if let Some(events) = restored {
+            for ev in events {
+                self.bus.publish(ev);
+            }
+            if let Some(m) = self.registry.active() {
+                self.state.status_line = module_status(m.title());
+            }
+        }
+    }
+
+    /// Stop every plugin session and start over from discovery, picking up
+    /// added, removed, and edited plugins.
+    fn reload_plugins(&mut self) {
+        let view = self.plugin_view().unwrap_or_default();
+        for session in &view.sessions {
+            self.event_schemas.unregister_owner(&session.plugin_id);
+        }
+        self.plugin_pump = None;
+        self.unregister_plugin_modules();
+        self.init_plugin_runtime(self.plugin_roots.clone(), &mut Splash::hidden());
+    }
+
+    /// Start plugins once discovery is done, log what the pump thread did
+    /// since the last frame, and answer its queued host calls for up to
+    /// `budget`. Handshake outcomes are published as
+    /// [`Event::PluginStatus`] and finish their lines on `splash`.
+    fn pump_plugin_runtime(&mut self, budget: Duration, splash: &mut Splash<'_>) {
+        self.poll_plugin_discovery(splash);
+        let Some(pump) = self.plugin_pump.as_ref() else {
+            return;
+        };
+        for report in pump.drain_reports() {
+            match report {
+                PumpReport::Handled(handled) => {
+                    tracing::debug!(
+                        plugin_id = %handled.plugin_id,
+                        method = %handled.method,
+                        responded_with_error = handled.responded_with_error,
+                        "handled plugin request"
+                    );
+                }
+                PumpReport::Exited { plugin_id } => {
+                    tracing::warn!(
+                        plugin_id = %plugin_id,
+                        "plugin process exited; runtime session detached"
+                    );
+                    self.plugin_status(plugin_id, PluginStatus::Exited, splash);
+                }
+                PumpReport::Failed { plugin_id, error } => {
+                    tracing::warn!(
+                        plugin_id = %plugin_id,
+                        error = %error,
+                        "plugin runtime pump error"
+                    );
+                    // Later failures are about requests, not the plugin
+                    if self.plugins_starting.contains(&plugin_id) {
+                        let status = PluginStatus::Failed(error.to_string());
+                        self.plugin_status(plugin_id, status, splash);
+                    }
+                }
+                PumpReport::Health {
+                    plugin_id,
+                    status: HealthStatus::Unhealthy,
+                } => {
+                    tracing::warn!(plugin_id = %plugin_id, "plugin stopped answering health pings");
+                }
+                PumpReport::Health {
+                    plugin_id,
+                    status: HealthStatus::Healthy,
+                } => {
+                    tracing::info!(plugin_id = %plugin_id, "plugin is answering health pings again");
+                }
+                PumpReport::Started { plugin_id } => {
+                    tracing::info!(plugin_id = %plugin_id, "plugin started");
+                    self.plugin_status(plugin_id, PluginStatus::Running, splash);
+                }
+                PumpReport::Restarted { plugin_id } => {
+                    tracing::info!(plugin_id = %plugin_id, "plugin restarted");
+                    self.plugin_status(plugin_id, PluginStatus::Running, splash);
+                }
+                PumpReport::Stderr { plugin_id, line } => {
+                    self.show_plugin_stderr(&plugin_id, &line);
+                }
+                PumpReport::JobFailed { what, error } => {
+                    tracing::warn!(error = %error, "failed to {what}");
+                }
+            }
+        }
+        let blocking = self.bus.is_blocking();
+        if let Some(pump) = &self.plugin_pump {
+            pump.set_paused(blocking);
+        }
+        if blocking {
+            tracing::debug!("event bus full; deferring plugin requests");
+            return;
+        }
+        self.with_plugin_host(|pump, host| pump.serve(host, budget));
+    }
+
+    /// Publish a plugin's new status and finish its splash line, if any.
+    fn plugin_status(&mut self, plugin_id: String, status: PluginStatus, splash: &mut Splash<'_>) {
+        self.plugins_starting.remove(&plugin_id);
+        match &status {
+            PluginStatus::Running if splash.is_waiting() => {
+                let api_version = self.plugin_view().and_then(|view| {
+                    view.sessions
+                        .iter()
+                        .find(|session| session.plugin_id == plugin_id)
+                        .and_then(|session| session.api_version.clone())
+                });
+                let detail = api_version
+                    .map(|version| tr_args("host api {version}", &[("version", &version)]))
+                    .unwrap_or_default();
+                splash.resolve(&plugin_id, StepStatus::Done(detail));
+            }
+            PluginStatus::Failed(reason) => {
+                splash.resolve(&plugin_id, StepStatus::Failed(reason.clone()));
+            }
+            PluginStatus::Exited => {
+                splash.resolve(&plugin_id, StepStatus::Failed(tr("exited").to_string()))
+            }
+            _ => {}
+        }
+        self.bus.publish(Event::PluginStatus { plugin_id, status });
+    }
+
+    /// Evaluate alert rules against the telemetry store and announce changes.
+    fn evaluate_alerts(&mut self, now: Instant) {
+        if self.alerts.is_empty() {
+            return;
+        }
+        self.record_plugin_telemetry(now);
+
+        for transition in self.alerts.evaluate(&self.telemetry, now) {
+            match transition {
+                AlertTransition::Fired {
+                    name,
+                    severity,
+                    message,
+                    value,
+                    command,
+                } => {
+                    if severity == AlertSeverity::Critical {
+                        tracing::error!(alert = %name, value, "ALERT {name}: {message}");
+                    } else {
+                        tracing::warn!(alert = %name, value, "ALERT {name}: {message}");
+                    }
+                    if let Some(command) = command {
+                        if self.dispatch_command(&command, false) {
+                            tracing::warn!(alert = %name, "ignoring quit requested by alert command");
+                        }
+                    }
+                }
+                AlertTransition::Resolved { name } => {
+                    tracing::info!(alert = %name, "alert {name} resolved");
+                }
+            }
+        }
+        self.sync_alert_mood(now);
+    }
+
+    /// Point the global agent's mood at the most severe active alert.
+    fn sync_alert_mood(&mut self, now: Instant) {
+        if self.alerts.is_empty() || self.idle.is_attract() {
+            return;
+        }
+        let mood = match self.alerts.active_severity(now) {
+            Some(AlertSeverity::Critical) => Mood::HurtRealBad,
+            Some(AlertSeverity::Warn) => Mood::Angry,
+            Some(AlertSeverity::Info) => Mood::Thinking,
+            None => Mood::Neutral,
+        };
+        if self.agent.mood() != mood {
+            self.agent.set_mood(mood, now);
+        }
+    }
+
+    /// Start attract mode after `[idle] after_secs` without a key, and while
+    /// it is on move to the next module every `cycle_secs`.
+    fn update_idle(&mut self, now: Instant) {
+        let idle = self.settings.idle;
+        match self.idle.update(now, idle.after(), idle.cycle()) {
+            Some(IdleStep::Enter) => {
+                self.attract_return = Some(AttractReturn {
+                    module: self.registry.active_id().map(str::to_string),
+                    mood: self.agent.mood(),
+                });
+                self.agent.set_mood(Mood::GodMode, now);
+                tracing::info!("attract mode; press any key to return");
+            }
+            Some(IdleStep::Cycle) => {
+                for ev in self.registry.cycle_next() {
+                    self.bus.publish(ev);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Leave attract mode, going back to the module and agent mood it
+    /// replaced.
+    fn leave_attract(&mut self, now: Instant) {
+        let Some(previous) = self.attract_return.take() else {
+            return;
+        };
+        if let Some(id) = previous.module {
+            if self.registry.active_id() != Some(id.as_str()) {
+                match self.registry.activate(&id) {
+                    Ok(events) => {
+                        for ev in events {
+                            self.bus.publish(ev);
+                        }
+                    }
+                    Err(err) => tracing::warn!("leaving attract mode: {err}"),
+                }
+            }
+        }
+        self.agent.set_mood(previous.mood, now);
+        self.sync_alert_mood(now);
+    }
+
+    /// Record totals of plugin runtime counters as `plugin` telemetry so
+    /// alert rules can watch them. Samples are only added when a total
+    /// changes, which keeps rate windows accurate without flooding history.
+    fn record_plugin_telemetry(&mut self, now: Instant) {
+        let Some(view) = self.plugin_view() else {
+            return;
+        };
+        let stats = &view.stats;
+        let totals = [
+            (
+                "requests",
+                stats.iter().map(|s| s.requests_handled).sum::<u64>(),
+            ),
+            ("errors", stats.iter().map(|s| s.errors_returned).sum()),
+            (
+                "dropped",
+                stats.iter().map(|s| s.notifications_dropped).sum(),
+            ),
+            ("restarts", stats.iter().map(|s| s.restarts).sum()),
+            ("starved", stats.iter().map(|s| s.starved_requests).sum()),
+        ];
+        for (key, total) in totals {
+            let previous = self.telemetry.numeric_last_n("plugin", key, 1);
+            if previous.first() != Some(&(total as f64)) {
+                self.telemetry
+                    .record("plugin", key, TelemetryValue::Int(total as i64), now);
+            }
+        }
+    }
+
+    /// Record bus counters as `bus` telemetry: `published`, `dropped`,
+    /// `blocked`, and `peak_depth` totals, plus `published.<kind>` and
+    /// `dropped.<kind>`.
+    /// Like plugin counters, samples are only added when a value changes.
+    fn record_bus_telemetry(&mut self, now: Instant) {
+        let stats = self.bus.stats();
+        let mut values = vec![
+            ("published".to_string(), stats.published()),
+            ("dropped".to_string(), stats.dropped()),
+            ("blocked".to_string(), stats.blocked),
+            ("peak_depth".to_string(), stats.peak_depth as u64),
+        ];
+        for (kind, counts) in &stats.kinds {
+            values.push((format!("published.{kind}"), counts.published));
+            values.push((format!("dropped.{kind}"), counts.dropped));
+        }
+        for (key, total) in values {
+            let previous = self.telemetry.numeric_last_n("bus", &key, 1);
+            if previous.first() != Some(&(total as f64)) {
+                self.telemetry
+                    .record("bus", &key, TelemetryValue::Int(total as i64), now);
+            }
+        }
+    }
+
+    fn forward_logs(&mut self, entries: &[LogEntry], now: Instant) {
+        if self.plugin_pump.is_none() && self.log_streams.is_empty() {
+            return;
+        }
+        let entries: Vec<LogEntryParams> = entries
+            .iter()
+            .map(|entry| LogEntryParams {
+                level: log_severity(entry.level),
+                target: entry.target.clone(),
+                message: entry.message.clone(),
+                dropped: 0,
+            })
+            .collect();
+        if !self.log_streams.is_empty() {
+            self.log_streams.send(&entries, now);
+        }
+        if self.plugin_pump.is_none() {
+            return;
+        }
+        self.submit_to_plugins("stream logs to plugins", move |runtime| {
+            runtime.broadcast_logs(&entries).map(drop)
+        });
+    }
+
+    /// Send what changed since the last tick to `spud.state.subscribe`
+    /// subscribers.
+    fn forward_state_to_plugins(&mut self) {
+        let active_module = self.registry.active().map(|module| ActiveModule {
+            id: module.id().to_string(),
+            title: module.title().to_string(),
+        });
+        let changes = self
+            .state_changes
+            .take(active_module, &self.state.status_line);
+        if changes.is_empty() || self.plugin_pump.is_none() {
+            return;
+        }
+        self.submit_to_plugins("send state changes to plugins", move |runtime| {
+            runtime.broadcast_state(&changes).map(drop)
+        });
+    }
+
+    fn forward_event_to_plugins(&mut self, event: &Event) {
+        if self.plugin_pump.is_none() {
+            return;
+        }
+        self.forward_module_focus(event);
+        let Some((category, tag, payload)) = map_event_for_plugins(event, self.state.started_at)
+        else {
+            return;
+        };
+        self.submit_to_plugins("broadcast host event to plugins", move |runtime| {
+            runtime
+                .broadcast_event(category, tag.as_deref(), payload)
+                .map(drop)
+        });
+    }
+
+    /// Tell a plugin its module was switched to or away from.
+    fn forward_module_focus(&mut self, event: &Event) {
+        let (id, active) = match event {
+            Event::ModuleActivated { id, .. } => (id, true),
+            Event::ModuleDeactivated { id } => (id, false),
+            _ => return,
+        };
+        let Some(plugin_id) = self
+            .registry
+            .get(id)
+            .and_then(|module| module.downcast_ref::<PluginModule>())
+            .map(|module| module.plugin_id().to_string())
+        else {
+            return;
+        };
+        let params = ModuleFocusParams {
+            module_id: id.clone(),
+            active,
+        };
+        self.submit_to_plugins("send module focus", move |runtime| {
+            match runtime.send_module_focus(&plugin_id, params) {
+                Err(RuntimeError::NotRunning(_)) | Ok(_) => Ok(()),
+                Err(err) => Err(err),
+            }
+        });
+    }
+}
+
+struct AppHost<'a> {
+    state: &'a AppState,
+    registry: &'a mut ModuleRegistry,
+    bus: &'a mut EventBus,
+    console: &'a mut Console,
+    tick_counter: &'a TickCounter,
+    commands: &'a CommandRegistry,
+    telemetry: &'a TelemetryStore,
+    spans: &'a SpanStats,
+    rng: &'a mut Rng,
+    undo: &'a mut UndoStack,
+    event_schemas: &'a mut EventSchemas,
+    /// `[commands]` from `spud.toml`, checked before plugin-invoked commands.
+    command_policy: &'a CommandsConfig,
+    /// Whether there is a console input line to prompt in.
+    interactive: bool,
+}
+
+impl HostBridge for AppHost<'_> {
+    fn state_snapshot(&mut self) -> Result<StateSnapshot> {
+        let active_module = self.registry.active().map(|module| ActiveModule {
+            id: module.id().to_string(),
+            title: module.title().to_string(),
+        });
+
+        let uptime_seconds = Instant::now()
+            .checked_duration_since(self.state.started_at)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+
+        Ok(StateSnapshot {
+            active_module,
+            status_line: self.state.status_line.clone(),
+            uptime_seconds,
+            tps: self.tick_counter.tps(),
+            telemetry: self
+                .telemetry
+                .iter_latest()
+                .map(|(source, key, point)| TelemetryDatum {
+                    source: source.to_string(),
+                    key: key.to_string(),
+                    value: telemetry_value_json(&point.value),
+                })
+                .collect(),
+        })
+    }
+
+    fn telemetry_samples(&mut self) -> Result<Vec<TelemetrySample>> {
+        Ok(self
+            .telemetry
+            .iter_latest()
+            .map(|(source, key, point)| TelemetrySample {
+                source: source.to_string(),
+                key: key.to_string(),
+                value: telemetry_value_json(&point.value),
+                updated_at_ms: point.recorded_at_ms,
+            })
+            .collect())
+    }
+
+    fn list_commands(&mut self) -> Result<Vec<CommandInfo>> {
+        // The core registry only: app commands like `plugins` and `undo`
+        // are not reachable through `spud.host.invoke_command`.
+        Ok(self
+            .commands
+            .commands()
+            .iter()
+            .map(|command| CommandInfo {
+                name: command.name().to_string(),
+                aliases: command
+                    .aliases()
+                    .iter()
+                    .map(|alias| alias.to_string())
+                    .collect(),
+                usage: command.usage().to_string(),
+                description: command.description().to_string(),
+            })
+            .collect())
+    }
+
+    fn invoke_command(&mut self, params: InvokeCommandParams) -> Result<InvokeCommandResult> {
+        // Run exactly the authorized command: arguments are passed through
+        // as words, so `;` and `|` in them are not interpreted.
+        let words: Vec<&str> = std::iter::once(params.command.as_str())
+            .chain(params.args.iter().map(String::as_str))
+            .collect();
+
+        let destructive = self
+            .commands
+            .get(&params.command)
+            .is_some_and(|command| command.destructive(&words[1..]));
+        let gate = guard::check(
+            self.command_policy,
+            self.commands,
+            &words,
+            destructive,
+            false,
+        );
+        let output = if let Gate::Refuse(reason) = gate {
+            CommandOutput::Error(vec![reason])
+        } else {
+            let mut ctx = CommandContext {
+                registry: self.registry,
+                console: self.console,
+                bus: self.bus,
+                tick_counter: self.tick_counter,
+                started_at: self.state.started_at,
+                spans: self.spans,
+                rng: self.rng,
+                undo: self.undo,
+                origin: CommandOrigin::Plugin,
+            };
+            self.commands.execute_words(&words, &mut ctx)
+        };
+
+        let lines = match output.status() {
+            CommandStatus::Ok => output.to_lines(),
+            CommandStatus::Error => output
+                .to_lines()
+                .into_iter()
+                .map(|line| format!("error: {line}"))
+                .collect(),
+            CommandStatus::Quit => {
+                self.bus.publish(Event::Quit);
+                vec!["quit requested".to_string()]
+            }
+        };
+
+        Ok(InvokeCommandResult {
+            lines,
+            is_error: output.is_error(),
+            output: Some(output.to_json()),
+        })
+    }
+
+    fn publish_event(&mut self, params: PublishEventParams) -> Result<PublishEventResult> {
+        if let Err(violations) = self.event_schemas.validate(&params.tag, &params.payload) {
+            let rejection = InvalidEventPayload {
+                tag: params.tag,
+                violations: violations
+                    .into_iter()
+                    .map(|violation| PayloadViolation {
+                        path: violation.path,
+                        message: violation.message,
+                    })
+                    .collect(),
+            };
+            return Err(HostRejection(rejection.to_jsonrpc_error()).into());
+        }
+        let event = parse_span_event(&params.tag, &params.payload).unwrap_or(Event::Custom {
+            tag: params.tag,
+            payload: params.payload,
+        });
+        self.bus.publish(event);
+        Ok(PublishEventResult { accepted: true })
+    }
+
+    fn register_event_schema(
+        &mut self,
+        plugin_id: &str,
+        params: RegisterEventSchemaParams,
+    ) -> Result<RegisterEventSchemaResult> {
+        if let Err(err) = self
+            .event_schemas
+            .register(plugin_id, &params.tag, params.schema)
+        {
+            return Err(HostRejection(JsonRpcError {
+                code: error_code::INVALID_PARAMS,
+                message: format!("{err:#}"),
+                data: None,
+            })
+            .into());
+        }
+        tracing::info!(plugin_id, tag = %params.tag, "plugin registered event schema");
+        Ok(RegisterEventSchemaResult { registered: true })
+    }
+
+    fn console_prompt(
+        &mut self,
+        plugin_id: &str,
+        params: ConsolePromptParams,
+    ) -> Result<ConsolePromptResult> {
+        if !self.interactive {
+            bail!("console prompts are not available when running headless");
+        }
+        if self.console.prompt().is_some() {
+            return Ok(ConsolePromptResult { accepted: false });
+        }
+        self.console.set_prompt(ConsolePrompt {
+            question: params.question,
+            callback_token: params.callback_token,
+            origin: PromptOrigin::Plugin(plugin_id.to_string()),
+        });
+        if !self.console.is_visible() {
+            self.console.toggle(Instant::now());
+        }
+        Ok(ConsolePromptResult { accepted: true })
+    }
+
+    fn update_module(
+        &mut self,
+        plugin_id: &str,
+        module_id: &str,
+        params: ModuleUpdateParams,
+    ) -> Result<ModuleUpdateResult> {
+        let Some(module) = self
+            .registry
+            .get(module_id)
+            .and_then(|module| module.downcast_ref::<PluginModule>())
+            .filter(|module| module.plugin_id() == plugin_id)
+        else {
+            bail!("module {module_id} is not registered for plugin {plugin_id}");
+        };
+        module.update(params);
+        Ok(ModuleUpdateResult {
+            active: self.registry.active_id() == Some(module_id),
+        })
+    }
+}
+
+fn log_severity(level: LogLevel) -> LogSeverity {
+    match level {
+        LogLevel::Trace => LogSeverity::Trace,
+        LogLevel::Debug => LogSeverity::Debug,
+        LogLevel::Info => LogSeverity::Info,
+        LogLevel::Warn => LogSeverity::Warn,
+        LogLevel::Error => LogSeverity::Error,
+    }
+}
+
+fn map_event_for_plugins(
+    event: &Event,
+    started_at: Instant,
+) -> Option<(EventCategory, Option<String>, Value)> {
+    match event {
+        Event::Tick { now } => Some((
+            EventCategory::Tick,
+            None,
+            json!({
+                "uptime_seconds": now
+                    .checked_duration_since(started_at)
+                    .unwrap_or(Duration::ZERO)
+                    .as_secs_f64()
+            }),
+        )),
+        Event::Resize { cols, rows } => Some((
+            EventCategory::Resize,
+            None,
+            json!({
+                "cols": cols,
+                "rows": rows
+            }),
+        )),
+        Event::ModuleActivated { id, reason } => Some((
+            EventCategory::ModuleLifecycle,
+            Some("module.activated".to_string()),
+            json!({ "id": id, "reason": reason.name() }),
+        )),
+        Event::ModuleDeactivated { id } => Some((
+            EventCategory::ModuleLifecycle,
+            Some("module.deactivated".to_string()),
+            json!({ "id": id }),
+        )),
+        Event::AttentionRequested { id, level } => Some((
+            EventCategory::ModuleLifecycle,
+            Some("module.attention".to_string()),
+            json!({ "id": id, "level": level.name() }),
+        )),
+        Event::Telemetry { source, key, value } => Some((
+            EventCategory::Telemetry,
+            None,
+            json!({
+                "source": source,
+                "key": key,
+                "value": telemetry_value_json(value)
+            }),
+        )),
+        Event::Custom { tag, payload } => Some((
+            EventCategory::Custom,
+            Some(tag.clone()),
+            parse_custom_payload(payload),
+        )),
+        Event::Signal { signal } => Some((
+            EventCategory::Custom,
+            Some(SIGNAL_EVENT_TAG.to_string()),
+            json!({ "signal": signal.name() }),
+        )),
+        // Spans are host-side perf data; plugins report their own via
+        // `SPAN_EVENT_TAG` but do not receive them back. Config reloads
+        // and profile changes concern host settings only, and plugin status
+        // is the host's view of other plugins.
+        Event::Key(_)
+        | Event::Span { .. }
+        | Event::PluginStatus { .. }
+        | Event::ConfigReloaded { .. }
+        | Event::ProfileChanged { .. }
+        | Event::Quit => None,
+    }
+}
+
+/// Current wall-clock time in Unix milliseconds.
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// Callback token of the question asked before a destructive command.
+const CONFIRM_TOKEN: &str = "confirm";
+
+/// Event tag signals are forwarded to plugins under.
+const SIGNAL_EVENT_TAG: &str = "spud.signal";
+
+/// Event tag plugins publish to report a latency span.
+const SPAN_EVENT_TAG: &str = "spud.span";
+
+/// Turn a `spud.span` publish with a `{"name", "duration_ms"}` payload into
+/// an [`Event::Span`]. Anything else stays a custom event.
+fn parse_span_event(tag: &str, payload: &str) -> Option<Event> {
+    if tag != SPAN_EVENT_TAG {
+        return None;
+    }
+    let value: Value = serde_json::from_str(payload).ok()?;
+    let name = value.get("name")?.as_str()?;
+    let duration_ms = value.get("duration_ms")?.as_f64()?;
+    let duration = Duration::try_from_secs_f64(duration_ms / 1_000.0).ok()?;
+    Some(Event::Span {
+        name: name.to_string(),
+        duration,
+    })
+}
+
+pub(crate) fn telemetry_value_json(value: &TelemetryValue) -> Value {
+    match value {
+        TelemetryValue::Float(value) => json!(value),
+        TelemetryValue::Int(value) => json!(value),
+        TelemetryValue::Text(value) => json!(value),
+        TelemetryValue::Bool(value) => json!(value),
+        TelemetryValue::DurationMs(ms) => json!({ "duration_ms": ms }),
+        TelemetryValue::Histogram(histogram) => json!({
+            "bounds": histogram.bounds(),
+            "counts": histogram.counts(),
+            "sum": histogram.sum(),
+        }),
+    }
+}
+
+fn parse_custom_payload(payload: &str) -> Value {
+    serde_json::from_str(payload).unwrap_or_else(|_| Value::String(payload.to_string()))
+}
+
+pub(crate) fn setup_terminal() -> Result<Terminal<FrameComposer>> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let composer = FrameComposer::new(stdout);
+    // The app applies `[terminal]` once it runs; attach clients stay on auto
+    let supported = doctor::sync_output_terminal(&|key| std::env::var(key).ok()).is_some();
+    composer.sync().store(supported, Ordering::Relaxed);
+    Ok(Terminal::new(composer)?)
+}
+
+pub(crate) fn restore_terminal(mut terminal: Terminal<FrameComposer>) -> Result<()> {
+    disable_raw_mode()?;
+    // The composer holds the cursor command until the flush that leaving
+    // the alternate screen does
+    terminal.show_cursor()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    Ok(())
+}
+
+/// Columns Shift+Left/Right scroll console tables by.
+const CONSOLE_SIDE_SCROLL: usize = 8;
+
+/// Time per loop iteration spent answering host calls from the plugin pump
+/// thread.
+const PLUGIN_PUMP_BUDGET: Duration = Duration::from_millis(1);
+
+/// Splash key of the plugin discovery step.
+const DISCOVERY_STEP: &str = ":discovery";
+
+/// ID of the module that shows the plugin runtime.
+const PLUGINS_MODULE: &str = "plugins";
+
+/// How often plugin state is republished while the plugins module is shown.
+const PLUGIN_FEED_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Log target of lines dev mode plugins write to stderr.
+const PLUGIN_STDERR_TARGET: &str = "plugin_stderr";
+
+/// Find the plugins under `roots` and apply the `[plugins]` integrity
+/// policy. Runs on the discovery thread.
+fn discover_plugins(roots: &[PathBuf], config: &PluginsConfig) -> Result<PluginRuntime, String> {
+    let mut runtime = PluginRuntime::from_search_roots(roots).map_err(|err| err.to_string())?;
+    let policy = IntegrityPolicy::from_config(config).map_err(|err| format!("{err:#}"))?;
+    runtime.set_integrity_policy(policy);
+
+    let audit_path = spud_core::logging::log_dir().join(AUDIT_FILE_NAME);
+    if let Err(err) = runtime.set_audit_file(&audit_path) {
+        tracing::warn!(error = %err, "plugin audit file unavailable; keeping in-memory audit only");
+    }
+    Ok(runtime)
+}
+
+/// Top-bar status for the active module.
+fn module_status(title: &str) -> String {
+    tr_args("MODULE: {title}", &[("title", &title)])
+}
+
+/// Install the UI catalog for the configured or detected locale. A missing
+/// catalog only warrants a warning when the locale was set in `spud.toml`.
+pub(crate) fn init_locale(config_dir: Option<&Path>, configured: Option<&str>) {
+    let locale = i18n::detect_locale(configured);
+    let dir = config_dir.map(|dir| dir.join(i18n::LOCALES_DIR));
+    match dir.as_deref().map(|dir| i18n::Catalog::load(dir, &locale)) {
+        Some(Ok(Some(catalog))) => {
+            tracing::info!(locale = %catalog.locale(), strings = catalog.len(), "loaded UI catalog");
+            i18n::install(catalog);
+        }
+        Some(Err(err)) => tracing::warn!("UI catalog not loaded: {err:#}"),
+        _ if configured.is_some() && locale != i18n::DEFAULT_LOCALE => {
+            tracing::warn!(%locale, "no UI catalog for locale; using English");
+        }
+        _ => tracing::debug!(%locale, "using English UI strings"),
+    }
+}
+
+/// `E:n W:n` once anything has been logged at warn or above. Red while
+/// errors are unacknowledged (`log ack`), dim afterwards.
+fn log_badge() -> Option<HudSpan> {
+    let (errors, warns, unacked) = logging::with_stats(|stats| {
+        (
+            stats.count(LogLevel::Error),
+            stats.count(LogLevel::Warn),
+            stats.unacknowledged_errors(),
+        )
+    });
+    if errors + warns == 0 {
+        return None;
+    }
+    let style = if unacked > 0 {
+        HudStyle::Critical
+    } else {
+        HudStyle::Dim
+    };
+    Some(HudSpan::new(format!("E:{errors} W:{warns}"), style))
+}
+
+/// Log panics with build info before the default hook prints them, so the
+/// log file doubles as a crash report.
+pub(crate) fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        tracing::error!(version = version::SUMMARY, "panic: {info}");
+        // A module's panic is caught and shown in the shell; printing it
+        // would scribble over the screen
+        if !spud_core::module::isolating() {
+            default_hook(info);
+        }
+    }));
+}
+
+/// Run the event loop without a terminal until a quit event, printing logs
+/// to stderr.
+pub(crate) fn run_headless(app: &mut App) -> Result<()> {
+    tracing::info!("running headless; stop with Ctrl-C");
+    app.signals.stop_on_interrupt();
+    let mut last_tick = Instant::now();
+    loop {
+        if let Ok(mut buf) = app.log_buffer.lock() {
+            for entry in buf.drain(..) {
+                eprintln!("{:5} [{}] {}", entry.level, entry.target, entry.message);
+            }
+        }
+        app.pump_plugin_runtime(PLUGIN_PUMP_BUDGET, &mut Splash::hidden());
+        app.poll_signals();
+        app.poll_control();
+        app.poll_config(Instant::now());
+        app.poll_plugin_dev(Instant::now());
+        app.tick_if_due(&mut last_tick);
+        app.export_frame(Instant::now());
+        app.publish_status(Instant::now());
+        if app.process_events() {
+            return Ok(());
+        }
+        std::thread::sleep(app.profile.tuning().poll_timeout);
+    }
+}
+
+pub(crate) fn run(terminal: &mut Terminal<FrameComposer>, app: &mut App) -> Result<()> {
+    let mut input = InputFilter::default();
+    app.post_draw = terminal.backend().post_draw().clone();
+    app.terminal_sync = terminal.backend().sync().clone();
+    app.apply_sync_output();
+    run_loop(terminal, app, |_, timeout| {
+        Ok(if event::poll(timeout)? {
+            input.accept(event::read()?)
+        } else {
+            None
+        })
+    })
+}
+
+/// Run the shell for `spud attach` clients until a quit event, rendering
+/// into a [`SharedScreen`] and taking input from the control socket.
+pub(crate) fn run_serve(app: &mut App) -> Result<()> {
+    let Some(socket) = app
+        .control
+        .as_ref()
+        .map(|control| control.path().to_path_buf())
+    else {
+        bail!("`spud serve` needs the control socket, which is unavailable");
+    };
+    let screen = SharedScreen::new(screen::DEFAULT_SIZE);
+    let mut terminal = Terminal::new(screen.clone())?;
+    app.screen = Some(screen);
+    app.signals.stop_on_interrupt();
+    tracing::info!(path = %socket.display(), "serving; attach with `spud attach`");
+    eprintln!(
+        "SPUD is serving on {}; attach with `spud attach`, stop with `quit` or Ctrl-C",
+        socket.display()
+    );
+    run_loop(&mut terminal, app, |app, timeout| {
+        let event = app.screen_input.pop_front();
+        if event.is_none() {
+            thread::sleep(timeout);
+        }
+        Ok(event)
+    })
+}
+
+/// The shell loop, drawing on `terminal` and asking `next_input` for at
+/// most one input event per iteration, waiting up to the given time.
+fn run_loop<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mut next_input: impl FnMut(&mut App, Duration) -> Result<Option<CEvent>>,
+) -> Result<()>
+where
+    B::Error: Send + Sync + 'static,
+{
+    let mut last_tick = Instant::now();
+
+    loop {
+        // ── Sync logs from tracing into console ──
+        app.sync_logs();
+        app.pump_plugin_runtime(PLUGIN_PUMP_BUDGET, &mut Splash::hidden());
+
+        // ── Update animation state ──
+        let now = Instant::now();
+        app.console.update(now);
+        app.agent.tick(now);
+        app.export_frame(now);
+        app.publish_status(now);
+
+        // ── Render ──
+        let render_timer = Stopwatch::start("render");
+        let attract = app.idle.is_attract();
+        let bindings = app.active_keybindings();
+        app.keymap.set_module(bindings);
+        let mut render_crash = None;
+        let mut hero_span = None;
+        terminal.draw(|f| {
+            let hud = app.hud_size.unwrap_or(app.settings.hud);
+            let rects = doom_layout(f.area(), hud.height, hud.face_width);
+
+            if let Some(m) = app.registry.active() {
+                let active_id = m.id();
+                let crash = app.registry.crash(active_id);
+                let hud = match crash {
+                    Some(_) => HudContribution::default(),
+                    None => isolate(|| m.hud()).unwrap_or_else(|reason| {
+                        render_crash = Some((active_id, reason));
+                        HudContribution::default()
+                    }),
+                };
+                app.shell_tabs.clear();
+                app.shell_tabs
+                    .extend(app.registry.iter().map(|(id, title)| ModuleTab {
+                        title,
+                        active: id == active_id,
+                        attention: app.registry.attention(id),
+                    }));
+                app.shell_badges.clear();
+                app.shell_badges.extend(log_badge());
+                app.shell_badges
+                    .push(HudSpan::new(app.profile.name(), HudStyle::Dim));
+                let view = ShellView {
+                    module_title: m.title(),
+                    tabs: &app.shell_tabs,
+                    status_line: &app.state.status_line,
+                    badges: &app.shell_badges,
+                    hud_left: &hud.left_lines,
+                    hud_keys: app.keymap.hud_lines(),
+                    hud_right: &hud.right_lines,
+                    // Attract mode shows off the app's agent, whatever the
+                    // module's own
+                    hud_face_lines: m
+                        .agent()
+                        .filter(|_| !attract)
+                        .unwrap_or(&app.agent)
+                        .current_frame_lines(),
+                    face: app.settings.face,
+                    dimmed: attract,
+                };
+
+                render_shell(f, rects, view, |f, hero_area| {
+                    if let Some(reason) = crash {
+                        render_crashed(f, hero_area, active_id, m.title(), reason);
+                    } else if let Some(renderer) = m.as_hero_renderer() {
+                        let hero_timer = Stopwatch::start("hero");
+                        let cache = &mut app.hero_cache;
+                        let post_draw = &app.post_draw;
+                        if let Err(reason) = isolate(|| {
+                            cache.draw(f, active_id, hero_area, renderer);
+                            post_draw.with(|out| renderer.post_draw(hero_area, out));
+                        }) {
+                            render_crash.get_or_insert((active_id, reason));
+                        }
+                        hero_span = Some(hero_timer.finish());
+                    }
+                });
+            }
+
+            // Console overlay on top
+            if app.console.is_visible() {
+                let slowest_span = app.spans.slowest();
+                let slowest_history: Vec<f64> = slowest_span
+                    .as_ref()
+                    .map(|span| {
+                        app.spans
+                            .recent(&span.name, spud_ui::console::SPARKLINE_WIDTH)
+                    })
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|duration| duration.as_secs_f64() * 1_000.0)
+                    .collect();
+                let view = ConsoleView {
+                    console: &app.console,
+                    tps: app.tick_counter.tps(),
+                    slowest_span: slowest_span.as_ref(),
+                    slowest_history: &slowest_history,
+                    fraction: app.console.overlay_fraction(now),
+                    show_cursor: app.console.is_open(),
+                    layout: app.console_layout.unwrap_or(app.settings.console),
+                };
+                render_console(f, f.area(), &view);
+            }
+
+            if app.show_events {
+                render_event_overlay(f, f.area(), app.bus.tap());
+            }
+
+            if app.show_help {
+                let global_keys: Vec<KeyHint> = app
+                    .keymap
+                    .global_hints()
+                    .into_iter()
+                    .chain(
+                        keys::CONSOLE_KEYS
+                            .iter()
+                            .map(|(key, action)| KeyHint::new(*key, tr(action))),
+                    )
+                    .collect();
+                let view = HelpView {
+                    global_keys: &global_keys,
+                    module_title: app.registry.active().map(|m| m.title()),
+                    module_keys: app.keymap.module_hints(),
+                    about: version::about(),
+                };
+                render_help(f, f.area(), &view);
+            }
+        })?;
+        // The composer took its share while flushing; without one (served
+        // screens) nothing writes it
+        app.post_draw.clear();
+        app.bus.publish(render_timer.finish());
+        if let Some(span) = hero_span {
+            app.bus.publish(span);
+        }
+        if let Some((id, reason)) = render_crash {
+            app.registry.mark_crashed(id, reason);
+        }
+
+        // ── Poll → Publish ──
+        let timeout = app.profile.tuning().poll_timeout;
+        match next_input(app, timeout)? {
+            Some(CEvent::Key(key)) => {
+                let global = match app.keymap.lookup(&key) {
+                    Some(KeyTarget::Global(action)) => Some(action),
+                    _ => None,
+                };
+                if app.idle.input(Instant::now()) {
+                    // The key that ends attract mode does nothing else
+                    app.leave_attract(Instant::now());
+                } else if global == Some(keys::CONSOLE) {
+                    // Tilde always toggles the console
+                    app.console.toggle(Instant::now());
+                } else if global == Some(keys::HELP) {
+                    app.show_help = !app.show_help;
+                } else if global == Some(keys::EVENTS) {
+                    app.show_events = !app.show_events;
+                } else if app.show_help {
+                    // Help is modal: Esc closes it, other keys are ignored
+                    if key.code == KeyCode::Esc {
+                        app.show_help = false;
+                    }
+                } else if app.show_events {
+                    // The event overlay is modal too
+                    let tap = app.bus.tap_mut();
+                    match key.code {
+                        KeyCode::Esc => app.show_events = false,
+                        KeyCode::Char(' ') | KeyCode::Char('p') => {
+                            tap.set_paused(!tap.is_paused());
+                        }
+                        KeyCode::Char('c') => tap.clear(),
+                        KeyCode::Up => tap.scroll_up(1),
+                        KeyCode::Down => tap.scroll_down(1),
+                        KeyCode::PageUp => tap.scroll_up(10),
+                        KeyCode::PageDown => tap.scroll_down(10),
+                        _ => {}
+                    }
+                } else if app.console.is_open() && app.console.search().is_some() {
+                    // A history search owns the input line until Enter runs
+                    // its match, Esc cancels, or the cursor moves to edit it
+                    let ctrl = keys::is_ctrl(&key);
+                    match key.code {
+                        KeyCode::Enter => {
+                            app.console.accept_search();
+                            let input = app.console.submit_input();
+                            if app.dispatch_command(&input, true) {
+                                return Ok(());
+                            }
+                        }
+                        KeyCode::Esc => app.console.cancel_search(),
+                        KeyCode::Char('g') if ctrl => app.console.cancel_search(),
+                        KeyCode::Char('r') if ctrl => app.console.search_older(),
+                        KeyCode::Backspace => app.console.search_backspace(),
+                        KeyCode::Left => {
+                            app.console.accept_search();
+                            app.console.cursor_left();
+                        }
+                        KeyCode::Right => app.console.accept_search(),
+                        KeyCode::Char(c) if !ctrl => app.console.search_insert(c),
+                        _ => {}
+                    }
+                    app.console.refresh_hint(&app.commands, APP_COMMANDS);
+                } else if app.console.is_open() {
+                    // Console captures all keys when fully open
+                    match key.code {
+                        KeyCode::Enter => {
+                            let input = app.console.submit_input();
+                            if app.dispatch_command(&input, true) {
+                                return Ok(());
+                            }
+                        }
+                        KeyCode::Backspace => app.console.backspace(),
+                        KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            app.console.scroll_left(CONSOLE_SIDE_SCROLL);
+                        }
+                        KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => {
+                            app.console.scroll_right(CONSOLE_SIDE_SCROLL);
+                        }
+                        KeyCode::Left => app.console.cursor_left(),
+                        KeyCode::Right => app.console.cursor_right(),
+                        KeyCode::PageUp => app.console.scroll_up(10),
+                        KeyCode::PageDown => app.console.scroll_down(10),
+                        KeyCode::Esc => {
+                            // Esc first cancels a pending prompt
+                            if app.console.prompt().is_some() {
+                                app.console.submit_input();
+                                app.cancel_prompt();
+                            } else {
+                                app.console.toggle(Instant::now());
+                            }
+                        }
+                        KeyCode::Char('r') if keys::is_ctrl(&key) => {
+                            app.console.search_older();
+                        }
+                        KeyCode::Char(c) if !keys::is_ctrl(&key) => app.console.insert_char(c),
+                        _ => {}
+                    }
+                    app.console.refresh_hint(&app.commands, APP_COMMANDS);
+                } else {
+                    // Normal mode; the registry turns module keys into
+                    // actions
+                    match global {
+                        Some(keys::QUIT) => {
+                            if app.quit_key() {
+                                app.bus.publish(Event::Quit);
+                            }
+                        }
+                        Some(keys::NEXT_MODULE) => {
+                            for ev in app.registry.cycle_next() {
+                                app.bus.publish(ev);
+                            }
+                        }
+                        Some(keys::BACK) => {
+                            for ev in app.registry.back() {
+                                app.bus.publish(ev);
+                            }
+                        }
+                        None if app.active_crashed()
+                            && app.keymap.lookup(&key)
+                                == Some(KeyTarget::Module(keys::RESTART_MODULE)) =>
+                        {
+                            app.restart_active_module();
+                        }
+                        _ => {
+                            app.bus.publish(Event::Key(key));
+                        }
+                    }
+                }
+            }
+            Some(CEvent::Resize(cols, rows)) => {
+                app.bus.publish(Event::Resize { cols, rows });
+            }
+            _ => {}
+        }
+
+        app.poll_signals();
+        app.poll_control();
+        app.poll_config(Instant::now());
+        app.poll_plugin_dev(Instant::now());
+        app.update_idle(Instant::now());
+        app.tick_if_due(&mut last_tick);
+
+        // ── Drain → Broadcast ──
+        if app.process_events() {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! `spud attach`: use the instance that is already running from another
+//! terminal.
+//!
+//! An instance started with `spud serve` shares its screen: the client
+//! takes over the terminal, draws the frames the instance streams, and
+//! sends keys and resizes back, until `Ctrl-\` detaches or SPUD quits. SPUD
+//! keeps running after the client leaves, so the session survives the
+//! terminal closing.
+//!
+//! Other instances, and any instance when stdin or stdout is not a
+//! terminal, get a line-mode client instead: each line read from stdin is
+//! sent as `spud.control.command` and its output printed, until EOF,
+//! `detach`, or a command that quits SPUD.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event as CEvent, KeyCode, KeyModifiers};
+use ratatui::backend::Backend;
+use ratatui::buffer::Cell;
+
+use crate::app;
+use crate::compose::FrameComposer;
+use crate::control::{Closed, ControlClient, ScreenInput};
+use crate::input::InputFilter;
+use crate::instance::Holder;
+use crate::screen::ScreenFrame;
+
+/// Lines that leave the client without touching SPUD.
+const DETACH: [&str; 2] = ["detach", "exit"];
+/// How long the screen client waits for a key before drawing again.
+const INPUT_POLL: Duration = Duration::from_millis(10);
+
+/// Attach to `holder`: its screen if it serves one, else read commands
+/// from stdin until detached.
+pub(crate) fn run(holder: &Holder) -> Result<()> {
+    let socket = holder.control.as_deref().with_context(|| {
+        format!(
+            "SPUD is running ({}) without a control socket to attach to",
+            holder.describe()
+        )
+    })?;
+    let mut client = ControlClient::connect(socket)?;
+    let info = client.info()?;
+    if info.serving && io::stdin().is_terminal() && io::stdout().is_terminal() {
+        return screen(client);
+    }
+    println!(
+        "attached to SPUD {} (pid {}, up {}s); `detach` or Ctrl-D to leave",
+        info.version, info.pid, info.uptime_secs
+    );
+
+    let mut stdin = io::stdin().lock();
+    let mut line = String::new();
+    loop {
+        print!("spud> ");
+        io::stdout().flush().context("failed to write to stdout")?;
+        line.clear();
+        if stdin.read_line(&mut line).context("failed to read stdin")? == 0 {
+            println!();
+            return Ok(());
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if DETACH.contains(&line) {
+            return Ok(());
+        }
+        let result = match client.command(line) {
+            Err(err) if err.is::<Closed>() => {
+                println!("SPUD has quit");
+                return Ok(());
+            }
+            result => result?,
+        };
+        for output in &result.lines {
+            if result.is_error {
+                eprintln!("{output}");
+            } else {
+                println!("{output}");
+            }
+        }
+        if result.quit {
+            return Ok(());
+        }
+    }
+}
+
+/// Show the instance's screen in this terminal until detached.
+fn screen(client: ControlClient) -> Result<()> {
+    let (cols, rows) = crossterm::terminal::size().context("failed to read the terminal size")?;
+    let (mut input, frames) = client.attach_screen(cols, rows)?;
+    let mut terminal = app::setup_terminal()?;
+    let quit = mirror(terminal.backend_mut(), &mut input, &frames);
+    app::restore_terminal(terminal)?;
+    if quit? {
+        println!("SPUD has quit");
+    }
+    Ok(())
+}
+
+/// Draw frames and forward input until the detach key (`Ok(false)`) or
+/// until SPUD closes the connection (`Ok(true)`).
+fn mirror(
+    backend: &mut FrameComposer,
+    input: &mut ScreenInput,
+    frames: &Receiver<ScreenFrame>,
+) -> Result<bool> {
+    let mut filter = InputFilter::default();
+    loop {
+        loop {
+            match frames.try_recv() {
+                Ok(frame) => draw(backend, &frame)?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(true),
+            }
+        }
+        if !event::poll(INPUT_POLL)? {
+            continue;
+        }
+        let Some(event) = filter.accept(event::read()?) else {
+            continue;
+        };
+        if is_detach(&event) {
+            return Ok(false);
+        }
+        // A failed send means SPUD is gone; the frames say so next
+        let _ = input.send(event);
+    }
+}
+
+/// `Ctrl-\`, which terminals also report as `Ctrl-4`.
+fn is_detach(event: &CEvent) -> bool {
+    matches!(
+        event,
+        CEvent::Key(key)
+            if key.modifiers.contains(KeyModifiers::CONTROL)
+                && matches!(key.code, KeyCode::Char('\\' | '4'))
+    )
+}
+
+/// Apply one frame, leaving out cells beyond this terminal.
+fn draw(backend: &mut FrameComposer, frame: &ScreenFrame) -> io::Result<()> {
+    if frame.clear {
+        backend.clear()?;
+    }
+    let size = backend.size()?;
+    let cells: Vec<(u16, u16, Cell)> = frame
+        .cells
+        .iter()
+        .filter(|cell| cell.x < size.width && cell.y < size.height)
+        .map(|cell| (cell.x, cell.y, cell.to_cell()))
+        .collect();
+    backend.draw(cells.iter().map(|(x, y, cell)| (*x, *y, cell)))?;
+    match frame.cursor {
+        Some(position) => {
+            backend.set_cursor_position(position)?;
+            backend.show_cursor()?;
+        }
+        None => backend.hide_cursor()?,
+    }
+    Backend::flush(backend)
+}
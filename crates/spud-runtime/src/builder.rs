@@ -0,0 +1,348 @@
+//! [`RuntimeBuilder`]: configure and start the SPUD shell.
+
+use std::cell::Cell;
+use std::env;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{bail, Result};
+use spud_config::AppConfig;
+use spud_core::logging;
+use spud_core::module::Module;
+
+use crate::app::{self, App};
+use crate::config::ConfigWatcher;
+use crate::instance::{self, Acquire, IfRunning};
+use crate::splash::Splash;
+use crate::{attach, doctor, version, workspace};
+
+/// Startup options, resolved by the builder and read by [`App::new`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Options {
+    pub plugin_roots: Vec<PathBuf>,
+    /// `None` uses `<platform config dir>/spud`.
+    pub config_dir: Option<PathBuf>,
+    pub initial_module: Option<String>,
+    pub log_level: Option<String>,
+    pub record: Option<PathBuf>,
+    pub seed: Option<u64>,
+    pub headless: bool,
+    /// Render for `spud attach` clients instead of a terminal.
+    pub serve: bool,
+    /// Skip restoring the saved session.
+    pub fresh: bool,
+    /// Workspace to start in; see [`crate::workspace`].
+    pub workspace: Option<String>,
+    /// What to do when an instance already runs; see [`crate::instance`].
+    pub if_running: IfRunning,
+    /// Control socket to listen on, set once the instance lock is held.
+    pub control_socket: Option<PathBuf>,
+}
+
+impl Options {
+    /// The config directory: the one given, else `<platform config dir>/spud`.
+    pub(crate) fn config_dir(&self) -> Option<PathBuf> {
+        self.config_dir
+            .clone()
+            .or_else(spud_config::paths::config_dir)
+    }
+}
+
+/// Makes a fresh instance of a module, at startup, on every workspace
+/// switch, and for `module restart`.
+pub(crate) struct ModuleFactory {
+    make: Box<dyn Fn() -> Box<dyn Module>>,
+    /// ID of the last module built, so `module restart` can pick the
+    /// factory without building every module.
+    id: Cell<Option<&'static str>>,
+}
+
+impl ModuleFactory {
+    pub(crate) fn new(make: impl Fn() -> Box<dyn Module> + 'static) -> Self {
+        Self {
+            make: Box::new(make),
+            id: Cell::new(None),
+        }
+    }
+
+    /// Build a fresh instance.
+    pub(crate) fn build(&self) -> Box<dyn Module> {
+        let module = (self.make)();
+        self.id.set(Some(module.id()));
+        module
+    }
+
+    /// ID of the modules this builds, once it has built one.
+    pub(crate) fn id(&self) -> Option<&'static str> {
+        self.id.get()
+    }
+}
+
+/// Builds and runs the SPUD shell with a chosen set of modules.
+///
+/// Modules are registered in the order given; the first one starts active
+/// unless [`with_initial_module`](Self::with_initial_module) picks another.
+#[derive(Default)]
+pub struct RuntimeBuilder {
+    modules: Vec<ModuleFactory>,
+    options: Options,
+}
+
+impl RuntimeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a module to the shell. `factory` is called again whenever a
+    /// workspace switch rebuilds the modules.
+    pub fn with_module<M: Module + 'static>(mut self, factory: impl Fn() -> M + 'static) -> Self {
+        self.modules
+            .push(ModuleFactory::new(move || Box::new(factory())));
+        self
+    }
+
+    /// Add plugin search roots; each is scanned recursively for
+    /// `plugin.toml`. Without any, the plugin runtime stays off.
+    pub fn with_plugins<P: Into<PathBuf>>(mut self, roots: impl IntoIterator<Item = P>) -> Self {
+        self.options
+            .plugin_roots
+            .extend(roots.into_iter().map(Into::into));
+        self
+    }
+
+    /// Add the plugin roots listed in `SPUD_PLUGIN_DIRS` (a path list using
+    /// the OS separator).
+    pub fn with_env_plugin_dirs(self) -> Self {
+        let roots: Vec<PathBuf> = env::var_os("SPUD_PLUGIN_DIRS")
+            .map(|raw| {
+                env::split_paths(&raw)
+                    .filter(|path| !path.as_os_str().is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.with_plugins(roots)
+    }
+
+    /// Directory holding `spud.toml`, `alerts.toml`, and locale catalogs,
+    /// instead of `<platform config dir>/spud`.
+    pub fn with_config_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.config_dir = Some(dir.into());
+        self
+    }
+
+    /// Start in the named workspace, `<config dir>/workspaces/<name>/`,
+    /// which brings its own settings, module list, and plugin roots.
+    pub fn with_workspace(mut self, name: impl Into<String>) -> Self {
+        self.options.workspace = Some(name.into());
+        self
+    }
+
+    /// Module to activate on startup instead of the first one.
+    pub fn with_initial_module(mut self, id: impl Into<String>) -> Self {
+        self.options.initial_module = Some(id.into());
+        self
+    }
+
+    /// Log filter directive; wins over `log_level` in `spud.toml` at
+    /// startup.
+    pub fn with_log_level(mut self, filter: impl Into<String>) -> Self {
+        self.options.log_level = Some(filter.into());
+        self
+    }
+
+    /// Append every bus event (except ticks and spans) to `path` as JSON
+    /// lines.
+    pub fn with_record(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.record = Some(path.into());
+        self
+    }
+
+    /// Seed for random behaviour, so a run can be reproduced; from the
+    /// clock otherwise.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.options.seed = Some(seed);
+        self
+    }
+
+    /// Run modules, plugins, and alerts without the terminal UI, printing
+    /// logs to stderr.
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.options.headless = headless;
+        self
+    }
+
+    /// Start without restoring the console history, console state, active
+    /// module, and HUD size saved by the last run.
+    pub fn fresh(mut self, fresh: bool) -> Self {
+        self.options.fresh = fresh;
+        self
+    }
+
+    /// What to do when SPUD already runs for this state directory:
+    /// refuse (the default), attach to it, or make it quit and take over.
+    pub fn if_running(mut self, if_running: IfRunning) -> Self {
+        self.options.if_running = if_running;
+        self
+    }
+
+    /// Run the shell without a terminal, streaming its screen to clients
+    /// that `attach`, until it quits. Closing a client's terminal leaves
+    /// the shell running.
+    pub fn serve(mut self) -> Result<()> {
+        self.options.serve = true;
+        self.options.headless = false;
+        self.run()
+    }
+
+    /// Use the instance already running for this state directory: show its
+    /// screen if it was started with [`serve`](Self::serve), else run
+    /// console commands read from stdin.
+    pub fn attach(&self) -> Result<()> {
+        let dir = instance::lock_dir();
+        match instance::running(&dir) {
+            Some(holder) => attach::run(&holder),
+            None => bail!("SPUD is not running for {}", dir.display()),
+        }
+    }
+
+    /// Run the environment and configuration checks behind `spud doctor`,
+    /// print the report, and fail if any check failed.
+    pub fn doctor(&self) -> Result<()> {
+        let workspace = self
+            .options
+            .workspace
+            .as_deref()
+            .map(|name| workspace::load(&self.options, name))
+            .transpose()?;
+        let options = workspace::apply(&self.options, workspace.as_ref());
+        doctor::run(&doctor::Settings::from_options(&options))
+    }
+
+    /// Set up logging and the terminal, then run the shell until it quits.
+    pub fn run(self) -> Result<()> {
+        if self.modules.is_empty() {
+            bail!("no modules registered; add at least one with RuntimeBuilder::with_module");
+        }
+        let mut options = self.options;
+        // Before logging starts: a second instance must not touch the log files
+        let lock_dir = instance::lock_dir();
+        let lock = match instance::acquire(&lock_dir)? {
+            Acquire::Locked(lock) => lock,
+            Acquire::Held(holder) => match options.if_running {
+                IfRunning::Refuse => bail!(
+                    "SPUD is already running ({}) for {}; attach to it or steal it instead",
+                    holder.describe(),
+                    lock_dir.display()
+                ),
+                IfRunning::Attach => return attach::run(&holder),
+                IfRunning::Steal => instance::steal(&lock_dir, &holder)?,
+            },
+        };
+        options.control_socket = lock.control_path();
+        let headless = options.headless;
+        let serve = options.serve;
+        let workspace = options
+            .workspace
+            .as_deref()
+            .map(|name| workspace::load(&options, name))
+            .transpose()?;
+        let config_dir = workspace::apply(&options, workspace.as_ref()).config_dir();
+        let config_watcher = ConfigWatcher::new(config_dir.as_deref(), Instant::now());
+        let loaded = config_watcher.load();
+        let file_log_level = loaded.as_ref().ok().and_then(|c| c.log_level.as_deref());
+        let log_files = loaded.as_ref().map(|c| c.log).unwrap_or_default();
+        let log_buffer =
+            logging::init_with_filter(options.log_level.as_deref().or(file_log_level), &log_files);
+        tracing::info!(version = version::SUMMARY, "SPUD starting up");
+        if let Some(workspace) = &workspace {
+            tracing::info!(workspace = %workspace.name, "workspace active");
+        }
+        app::install_panic_hook();
+        let settings = loaded.unwrap_or_else(|err| {
+            tracing::warn!("using default settings: {err:#}");
+            AppConfig::default()
+        });
+        app::init_locale(config_dir.as_deref(), settings.locale.as_deref());
+
+        if headless {
+            let mut app = App::new(
+                log_buffer,
+                options,
+                workspace,
+                self.modules,
+                settings,
+                config_watcher,
+                &mut Splash::hidden(),
+            )?;
+            return app::run_headless(&mut app);
+        }
+        if serve {
+            let mut app = App::new(
+                log_buffer,
+                options,
+                workspace,
+                self.modules,
+                settings,
+                config_watcher,
+                &mut Splash::hidden(),
+            )?;
+            let res = app::run_serve(&mut app);
+            app.save_session();
+            return res;
+        }
+
+        // The splash shows startup progress until the shell is ready
+        let mut terminal = app::setup_terminal()?;
+        let mut splash = Splash::new(&mut terminal);
+        let started = App::new(
+            log_buffer,
+            options,
+            workspace,
+            self.modules,
+            settings,
+            config_watcher,
+            &mut splash,
+        )
+        .and_then(|mut app| {
+            app.await_splash(&mut splash)?;
+            Ok(app)
+        });
+        drop(splash);
+        let res = started.and_then(|mut app| {
+            let res = app::run(&mut terminal, &mut app);
+            app.save_session();
+            res
+        });
+        app::restore_terminal(terminal)?;
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_options_and_needs_a_module() {
+        let builder = RuntimeBuilder::new()
+            .with_plugins(["a", "b"])
+            .with_plugins([PathBuf::from("c")])
+            .with_config_dir("/etc/spud")
+            .with_initial_module("stats")
+            .with_workspace("work")
+            .with_seed(7)
+            .fresh(true)
+            .headless(true);
+        let options = &builder.options;
+        assert_eq!(options.plugin_roots.len(), 3);
+        assert_eq!(options.config_dir(), Some(PathBuf::from("/etc/spud")));
+        assert_eq!(options.initial_module.as_deref(), Some("stats"));
+        assert_eq!(options.workspace.as_deref(), Some("work"));
+        assert_eq!(options.seed, Some(7));
+        assert!(options.fresh);
+        assert!(options.headless);
+
+        let err = builder.run().unwrap_err();
+        assert!(err.to_string().contains("no modules registered"));
+    }
+}
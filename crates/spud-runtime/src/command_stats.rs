@@ -0,0 +1,99 @@
+//! App-level `stats commands` report: how often each console command ran.
+//!
+//! Lives in the app because the counts belong to the console, which the
+//! stats module cannot see; the dispatcher answers `stats commands` and
+//! hands every other `stats` subcommand to the module. The counts are kept
+//! in `session.json` and never leave this machine.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use spud_core::command::CommandOutput;
+use spud_core::output::{Column, Table};
+use spud_core::usage::CommandUsage;
+
+/// Module whose command this report extends.
+pub(crate) const MODULE: &str = "stats";
+/// Subcommand intercepted by the app console dispatcher.
+pub(crate) const SUBCOMMAND: &str = "commands";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "stats commands [reset]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show how often each console command has run (kept locally)";
+
+/// Run `stats commands` with the arguments after `commands`.
+pub(crate) fn execute(usage: &mut CommandUsage, args: &[&str], now: SystemTime) -> CommandOutput {
+    match args {
+        [] if usage.is_empty() => CommandOutput::Lines(vec!["no commands run yet".into()]),
+        [] => CommandOutput::Table(report(usage, now)),
+        ["reset"] => {
+            usage.clear();
+            CommandOutput::Lines(vec!["command counts cleared".into()])
+        }
+        _ => CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    }
+}
+
+fn report(usage: &CommandUsage, now: SystemTime) -> Table {
+    let now = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let mut table = Table::new(vec![
+        Column::left("COMMAND"),
+        Column::right("RUNS"),
+        Column::right("LAST USED"),
+    ]);
+    for (name, count) in usage.ranked() {
+        table.push_row(vec![
+            name.to_string(),
+            count.count.to_string(),
+            ago(now.saturating_sub(count.last_used)),
+        ]);
+    }
+    table
+}
+
+/// `secs` as a short age: `just now`, `5m ago`, `3h ago`, `2d ago`.
+fn ago(secs: u64) -> String {
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn lists_counts_and_resets() {
+        let mut usage = CommandUsage::default();
+        let now = UNIX_EPOCH + Duration::from_secs(100_000);
+        assert!(matches!(
+            execute(&mut usage, &[], now),
+            CommandOutput::Lines(_)
+        ));
+
+        usage.record("tps", now - Duration::from_secs(7_200));
+        usage.record("stats", now - Duration::from_secs(300));
+        usage.record("stats", now);
+        let CommandOutput::Table(table) = execute(&mut usage, &[], now) else {
+            panic!("expected a table");
+        };
+        assert_eq!(table.rows[0], ["stats", "2", "just now"]);
+        assert_eq!(table.rows[1], ["tps", "1", "2h ago"]);
+
+        assert!(execute(&mut usage, &["bogus"], now).is_error());
+        execute(&mut usage, &["reset"], now);
+        assert!(usage.is_empty());
+    }
+
+    #[test]
+    fn ages_round_down_to_the_unit() {
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(119), "1m ago");
+        assert_eq!(ago(86_400 * 3 + 5), "3d ago");
+    }
+}
@@ -0,0 +1,305 @@
+//! One write per frame.
+//!
+//! Ratatui's crossterm backend writes to stdout as it goes: the cell diff,
+//! then a flush for each cursor command, and anything written after the
+//! draw (inline images through Sixel, iTerm2, or Kitty) goes out on its own.
+//! The terminal can show any of those pieces before the rest arrives.
+//! [`FrameComposer`] keeps all of it in memory until the end of the frame:
+//! the diff and cursor commands, then the [`PostDraw`] output with the
+//! cursor saved and restored around it, sent in one write and one flush.
+//!
+//! With synchronized output on, the frame is also wrapped in DEC mode 2026
+//! begin/end sequences, so a terminal that supports them shows it only once
+//! all of it has arrived, even when it takes more than one read.
+
+use std::io::{self, Stdout, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use ratatui::backend::{Backend, ClearType, CrosstermBackend, WindowSize};
+use ratatui::buffer::Cell;
+use ratatui::layout::{Position, Size};
+
+/// DECSC: save the cursor position and attributes.
+const SAVE_CURSOR: &[u8] = b"\x1b7";
+/// DECRC: restore what [`SAVE_CURSOR`] saved.
+const RESTORE_CURSOR: &[u8] = b"\x1b8";
+/// Begin synchronized update (BSU): set DEC private mode 2026.
+const BEGIN_SYNC: &[u8] = b"\x1b[?2026h";
+/// End synchronized update (ESU).
+const END_SYNC: &[u8] = b"\x1b[?2026l";
+
+/// Escape sequences to write after the cells of the frame being drawn;
+/// clones share the queue.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PostDraw(Arc<Mutex<Vec<u8>>>);
+
+impl PostDraw {
+    /// Append to the queue through `write`.
+    pub(crate) fn with<R>(&self, write: impl FnOnce(&mut Vec<u8>) -> R) -> R {
+        let mut queue = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        write(&mut queue)
+    }
+
+    /// Drop what was queued, when nothing will write it.
+    pub(crate) fn clear(&self) {
+        self.with(Vec::clear);
+    }
+
+    /// Move the queue onto `out`, keeping the queue's allocation.
+    fn drain_into(&self, out: &mut Vec<u8>) {
+        self.with(|queue| {
+            if !queue.is_empty() {
+                out.extend_from_slice(SAVE_CURSOR);
+                out.append(queue);
+                out.extend_from_slice(RESTORE_CURSOR);
+            }
+        });
+    }
+}
+
+/// Ratatui's output for the frame being composed, shared between the
+/// composer and the crossterm backend writing into it. Flushing does
+/// nothing: the composer decides when the frame is done.
+#[derive(Debug, Clone, Default)]
+struct Pending(Arc<Mutex<Vec<u8>>>);
+
+impl Pending {
+    fn lock(&self) -> MutexGuard<'_, Vec<u8>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Write for Pending {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A crossterm backend that sends each frame to `out` in one write when
+/// ratatui flushes it.
+#[derive(Debug)]
+pub(crate) struct FrameComposer<W: Write = Stdout> {
+    backend: CrosstermBackend<Pending>,
+    frame: Pending,
+    post_draw: PostDraw,
+    /// Wrap frames in synchronized updates; shared so the app can switch
+    /// it while ratatui owns the backend.
+    sync: Arc<AtomicBool>,
+    out: W,
+}
+
+impl<W: Write> FrameComposer<W> {
+    pub(crate) fn new(out: W) -> Self {
+        let frame = Pending::default();
+        Self {
+            backend: CrosstermBackend::new(frame.clone()),
+            frame,
+            post_draw: PostDraw::default(),
+            sync: Arc::default(),
+            out,
+        }
+    }
+
+    /// The queue written after each frame's cells.
+    pub(crate) fn post_draw(&self) -> &PostDraw {
+        &self.post_draw
+    }
+
+    /// Switch for synchronized updates, off until set.
+    pub(crate) fn sync(&self) -> &Arc<AtomicBool> {
+        &self.sync
+    }
+
+    /// Write the frame so far and the post-draw queue, then flush `out`.
+    fn send(&mut self) -> io::Result<()> {
+        let mut frame = self.frame.lock();
+        self.post_draw.drain_into(&mut frame);
+        if !frame.is_empty() {
+            if self.sync.load(Ordering::Relaxed) {
+                frame.splice(0..0, BEGIN_SYNC.iter().copied());
+                frame.extend_from_slice(END_SYNC);
+            }
+            let result = self.out.write_all(&frame);
+            frame.clear();
+            result?;
+        }
+        drop(frame);
+        self.out.flush()
+    }
+}
+
+/// Raw writes (`execute!` of mode changes) join the frame being composed.
+impl<W: Write> Write for FrameComposer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.frame.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
+
+impl<W: Write> Backend for FrameComposer<W> {
+    type Error = io::Error;
+
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.backend.draw(content)
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        self.backend.append_lines(n)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.backend.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.backend.show_cursor()
+    }
+
+    /// Sends what is pending first: the terminal answers the position
+    /// query after applying it.
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        self.send()?;
+        self.backend.get_cursor_position()
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.backend.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.backend.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.backend.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        self.backend.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        self.backend.window_size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.send()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+    use ratatui::text::Line;
+    use ratatui::{Terminal, TerminalOptions, Viewport};
+
+    use super::*;
+
+    /// Each write call it gets, and its flushes.
+    #[derive(Default)]
+    struct Writes {
+        calls: Vec<Vec<u8>>,
+        flushes: usize,
+    }
+
+    impl Write for Writes {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.calls.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    fn terminal() -> Terminal<FrameComposer<Writes>> {
+        let options = TerminalOptions {
+            viewport: Viewport::Fixed(Rect::new(0, 0, 10, 2)),
+        };
+        Terminal::with_options(FrameComposer::new(Writes::default()), options).unwrap()
+    }
+
+    #[test]
+    fn frames_and_post_draw_output_go_out_in_one_write() {
+        let mut terminal = terminal();
+        let post_draw = terminal.backend().post_draw().clone();
+        terminal
+            .draw(|f| {
+                f.render_widget(Line::from("hi"), f.area());
+                f.set_cursor_position((1, 1));
+                post_draw.with(|out| out.extend_from_slice(b"\x1b_Gimage\x1b\\"));
+            })
+            .unwrap();
+
+        let writes = &terminal.backend().out;
+        assert_eq!(writes.calls.len(), 1);
+        assert_eq!(writes.flushes, 1);
+        let frame = String::from_utf8_lossy(&writes.calls[0]);
+        let cells = frame.find("hi").unwrap();
+        // The image follows the cells and the cursor, which it leaves where
+        // ratatui put it
+        let cursor = frame.find("\x1b[2;2H").unwrap();
+        let image = frame.find("\x1b7\x1b_Gimage\x1b\\\x1b8").unwrap();
+        assert!(cells < cursor && cursor < image);
+        assert!(frame.ends_with("\x1b8"));
+    }
+
+    #[test]
+    fn synchronized_frames_are_wrapped_whole() {
+        let mut terminal = terminal();
+        terminal.backend().sync().store(true, Ordering::Relaxed);
+        let post_draw = terminal.backend().post_draw().clone();
+        terminal
+            .draw(|f| {
+                f.render_widget(Line::from("hi"), f.area());
+                post_draw.with(|out| out.extend_from_slice(b"image"));
+            })
+            .unwrap();
+        let writes = &terminal.backend().out;
+        assert_eq!(writes.calls.len(), 1);
+        let frame = String::from_utf8_lossy(&writes.calls[0]);
+        assert!(frame.starts_with("\x1b[?2026h"));
+        assert!(frame.ends_with("image\x1b8\x1b[?2026l"));
+    }
+
+    #[test]
+    fn unchanged_frames_only_resend_the_cursor() {
+        let mut terminal = terminal();
+        let draw = |terminal: &mut Terminal<FrameComposer<Writes>>| {
+            terminal
+                .draw(|f| f.render_widget(Line::from("hi"), f.area()))
+                .unwrap();
+        };
+        draw(&mut terminal);
+        let first = terminal.backend().out.calls.len();
+        draw(&mut terminal);
+        let writes = &terminal.backend().out;
+        // Only the cursor, which ratatui hides again every frame
+        assert_eq!(writes.calls.len(), first + 1);
+        assert!(!String::from_utf8_lossy(&writes.calls[first]).contains("hi"));
+        assert!(terminal
+            .backend()
+            .post_draw()
+            .with(|queue| queue.is_empty()));
+    }
+}
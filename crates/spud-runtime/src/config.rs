@@ -0,0 +1,105 @@
+//! Loading and watching `spud.toml`.
+//!
+//! The file is polled rather than watched with OS notifications: a metadata
+//! check once a second is cheap and behaves the same on every platform and
+//! for editors that replace files instead of writing in place.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+use spud_config::{AppConfig, APP_CONFIG_FILE};
+
+/// How often the settings file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What changes when a file is edited, replaced, or removed.
+pub(crate) type Stamp = Option<(Option<SystemTime>, u64)>;
+
+/// Tracks `spud.toml` and reports when it changes.
+pub(crate) struct ConfigWatcher {
+    path: Option<PathBuf>,
+    stamp: Stamp,
+    last_check: Instant,
+}
+
+impl ConfigWatcher {
+    /// Watch `spud.toml` in `config_dir`. With no config dir there is
+    /// nothing to watch and the defaults apply.
+    pub(crate) fn new(config_dir: Option<&Path>, now: Instant) -> Self {
+        let path = config_dir.map(|dir| dir.join(APP_CONFIG_FILE));
+        Self {
+            stamp: path.as_deref().and_then(stamp),
+            path,
+            last_check: now,
+        }
+    }
+
+    /// Load the current settings. A missing file yields the defaults.
+    pub(crate) fn load(&self) -> Result<AppConfig> {
+        match &self.path {
+            Some(path) if path.exists() => AppConfig::from_path(path),
+            _ => Ok(AppConfig::default()),
+        }
+    }
+
+    /// Re-read the file if it changed since the last call, at most once per
+    /// [`CHECK_INTERVAL`]. Returns `None` when nothing changed.
+    pub(crate) fn poll(&mut self, now: Instant) -> Option<Result<AppConfig>> {
+        if now.duration_since(self.last_check) < CHECK_INTERVAL {
+            return None;
+        }
+        self.last_check = now;
+        let current = self.path.as_deref().and_then(stamp);
+        if current == self.stamp {
+            return None;
+        }
+        self.stamp = current;
+        Some(self.load())
+    }
+
+    /// Re-read the file now, regardless of [`CHECK_INTERVAL`] or whether it
+    /// changed.
+    pub(crate) fn reload(&mut self) -> Result<AppConfig> {
+        self.stamp = self.path.as_deref().and_then(stamp);
+        self.load()
+    }
+}
+
+/// The current [`Stamp`] of `path`; `None` when it is missing.
+pub(crate) fn stamp(path: &Path) -> Stamp {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok(), meta.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn poll_reports_edits_and_removal() {
+        let dir = TestDir::new("config-watch");
+        let path = dir.join(APP_CONFIG_FILE);
+        let _ = std::fs::remove_file(&path);
+
+        let start = Instant::now();
+        let mut watcher = ConfigWatcher::new(Some(&dir), start);
+        assert_eq!(watcher.load().unwrap(), AppConfig::default());
+
+        std::fs::write(&path, "tick_interval_ms = 250\n").unwrap();
+        assert!(watcher.poll(start).is_none(), "rate limited");
+        let later = start + CHECK_INTERVAL;
+        let config = watcher.poll(later).unwrap().unwrap();
+        assert_eq!(config.tick_interval_ms, Some(250));
+        assert!(watcher.poll(later + CHECK_INTERVAL).is_none());
+
+        std::fs::write(&path, "tick_interval_ms = 0\n").unwrap();
+        assert!(watcher.poll(later + CHECK_INTERVAL * 2).unwrap().is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        let config = watcher.poll(later + CHECK_INTERVAL * 3).unwrap().unwrap();
+        assert_eq!(config, AppConfig::default());
+    }
+}
@@ -0,0 +1,130 @@
+//! App-level `console` console command.
+//!
+//! Lives in the app because the overlay is drawn by the app loop. Settings
+//! made here override `[console]` in `spud.toml` until that section
+//! changes, and are saved with the session.
+
+use spud_config::ConsoleConfig;
+use spud_core::command::CommandOutput;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "console";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "console [size <pct> | anchor top|bottom | background solid|dim|transparent | slide <ms> | easing <curve> | reset]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show or change the console overlay's size, look, and slide";
+
+/// Run a `console` subcommand. `layout` is the override; `configured` is
+/// the layout from `spud.toml`. The caller applies the slide settings.
+pub(crate) fn execute(
+    layout: &mut Option<ConsoleConfig>,
+    configured: ConsoleConfig,
+    args: &[&str],
+) -> CommandOutput {
+    let mut next = layout.unwrap_or(configured);
+    match args {
+        [] => return describe(*layout, configured),
+        ["reset"] => {
+            *layout = None;
+            return describe(None, configured);
+        }
+        ["size", value] => match value.trim_end_matches('%').parse() {
+            Ok(percent) => next.height = percent,
+            Err(_) => return CommandOutput::Error(vec![format!("invalid size: {value}")]),
+        },
+        ["anchor", value] => match value.parse() {
+            Ok(anchor) => next.anchor = anchor,
+            Err(err) => return CommandOutput::Error(vec![format!("{err}")]),
+        },
+        ["background", value] => match value.parse() {
+            Ok(background) => next.background = background,
+            Err(err) => return CommandOutput::Error(vec![format!("{err}")]),
+        },
+        ["slide", value] => match value.trim_end_matches("ms").parse() {
+            Ok(ms) => next.slide_ms = Some(ms),
+            Err(_) => return CommandOutput::Error(vec![format!("invalid slide length: {value}")]),
+        },
+        ["easing", value] => match value.parse() {
+            Ok(easing) => next.easing = easing,
+            Err(err) => return CommandOutput::Error(vec![format!("{err}")]),
+        },
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    }
+    if let Err(err) = next.validate() {
+        let message = err
+            .to_string()
+            .replace("console.height", "size")
+            .replace("console.slide_ms", "slide");
+        return CommandOutput::Error(vec![message]);
+    }
+    *layout = Some(next);
+    describe(*layout, configured)
+}
+
+fn describe(layout: Option<ConsoleConfig>, configured: ConsoleConfig) -> CommandOutput {
+    let console = layout.unwrap_or(configured);
+    CommandOutput::KeyValue(vec![
+        ("size".into(), format!("{}%", console.height)),
+        ("anchor".into(), console.anchor.to_string()),
+        ("background".into(), console.background.to_string()),
+        (
+            "slide".into(),
+            console
+                .slide_ms
+                .map_or_else(|| "profile".to_string(), |ms| format!("{ms} ms")),
+        ),
+        ("easing".into(), console.easing.to_string()),
+        (
+            "source".into(),
+            if layout.is_some() {
+                "console command"
+            } else {
+                "spud.toml"
+            }
+            .to_string(),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_config::{ConsoleAnchor, ConsoleBackground, Easing};
+
+    #[test]
+    fn changes_validates_and_resets() {
+        let configured = ConsoleConfig::default();
+        let mut layout = None;
+
+        assert!(!execute(&mut layout, configured, &["size", "75%"]).is_error());
+        assert!(!execute(&mut layout, configured, &["anchor", "bottom"]).is_error());
+        assert!(!execute(&mut layout, configured, &["slide", "400ms"]).is_error());
+        assert!(!execute(&mut layout, configured, &["easing", "linear"]).is_error());
+        assert_eq!(
+            layout,
+            Some(ConsoleConfig {
+                height: 75,
+                anchor: ConsoleAnchor::Bottom,
+                background: ConsoleBackground::Solid,
+                slide_ms: Some(400),
+                easing: Easing::Linear,
+            })
+        );
+
+        let output = execute(&mut layout, configured, &["size", "5"]);
+        assert_eq!(
+            output.to_lines(),
+            vec!["size must be between 20 and 100".to_string()]
+        );
+        assert!(execute(&mut layout, configured, &["background", "blur"]).is_error());
+        assert!(execute(&mut layout, configured, &["easing", "bounce"]).is_error());
+        assert_eq!(
+            execute(&mut layout, configured, &["slide", "9000"]).to_lines(),
+            vec!["slide must be between 0 and 2000".to_string()]
+        );
+        assert_eq!(layout.map(|console| console.height), Some(75));
+
+        execute(&mut layout, configured, &["reset"]);
+        assert_eq!(layout, None);
+    }
+}
@@ -0,0 +1,720 @@
+//! Control socket: drive a running SPUD from another process.
+//!
+//! The running instance listens on a Unix socket next to its instance lock
+//! (see [`crate::instance`]) and speaks the same newline-delimited JSON-RPC
+//! 2.0 as plugins, with its own methods:
+//!
+//! | Method                        | Params                 | Result                    |
+//! |-------------------------------|------------------------|---------------------------|
+//! | `spud.control.info`           | `{}`                   | [`InfoResult`]            |
+//! | `spud.control.command`        | [`CommandParams`]      | [`CommandResult`]         |
+//! | `spud.control.quit`           | `{}`                   | `{}`                      |
+//! | `spud.control.attach_screen`  | [`AttachScreenParams`] | `{}`                      |
+//! | `spud.control.input`          | [`InputParams`]        | notification              |
+//! | `spud.control.logs_subscribe` | [`LogSubscribeParams`] | [`LogSubscriptionResult`] |
+//!
+//! After `attach_screen`, an instance started with `spud serve` sends the
+//! client `spud.control.screen` notifications carrying a
+//! [`ScreenFrame`](crate::screen::ScreenFrame) whenever its screen changes,
+//! and the client sends keys and resizes back as `spud.control.input`.
+//!
+//! After `logs_subscribe`, the client gets a `spud.control.log`
+//! notification ([`LogEntryParams`]) for each log entry that passes its
+//! filter, rate-limited like a plugin's `spud.logs.subscribe`, until it
+//! disconnects. Subscribing again on the same connection adds a second
+//! stream.
+//!
+//! Connection threads hand each request to the app as a
+//! [`ControlRequest`]; the app answers from its loop, so commands run on
+//! the main thread like typed ones. Answers and notifications go out
+//! through the connection's [`Outbox`], in the order the app sent them. An
+//! outbox holds at most [`OUTBOX_CAPACITY`] lines, so a client that stops
+//! reading loses lines instead of growing the instance's memory; a lagging
+//! `attach` client gets the whole screen again once it catches up. The
+//! socket is owner-only. Other platforms have no control socket.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossterm::event::Event as CEvent;
+
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use spud_remote::protocol::{
+    error_code, JsonRpcError, LogEntryParams, LogSubscribeParams, LogSubscriptionResult, RequestId,
+};
+use spud_remote::runtime::LogSubscription;
+
+use crate::screen::ScreenFrame;
+
+/// Socket file name in the lock directory.
+pub(crate) const SOCKET_FILE: &str = "control.sock";
+/// How long a client waits for the app to answer a request.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+#[cfg(unix)]
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+#[cfg(not(unix))]
+type Stream = std::net::TcpStream;
+
+/// Control method names.
+pub(crate) mod method {
+    pub(crate) const INFO: &str = "spud.control.info";
+    pub(crate) const COMMAND: &str = "spud.control.command";
+    pub(crate) const QUIT: &str = "spud.control.quit";
+    pub(crate) const ATTACH_SCREEN: &str = "spud.control.attach_screen";
+    pub(crate) const INPUT: &str = "spud.control.input";
+    pub(crate) const LOGS_SUBSCRIBE: &str = "spud.control.logs_subscribe";
+    /// Notification from the instance to an attached screen.
+    pub(crate) const SCREEN: &str = "spud.control.screen";
+    /// Notification from the instance to a log subscriber.
+    pub(crate) const LOG: &str = "spud.control.log";
+}
+
+/// Whether this platform has a control socket.
+pub(crate) fn supported() -> bool {
+    cfg!(unix)
+}
+
+/// Result of `spud.control.info`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct InfoResult {
+    pub(crate) pid: u32,
+    pub(crate) version: String,
+    pub(crate) uptime_secs: u64,
+    pub(crate) active_module: Option<String>,
+    /// Started with `spud serve`, so a client can attach its screen.
+    #[serde(default)]
+    pub(crate) serving: bool,
+}
+
+/// Params of `spud.control.command`: a console line, run as if typed but
+/// without confirmations.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CommandParams {
+    pub(crate) line: String,
+}
+
+/// Result of `spud.control.command`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct CommandResult {
+    pub(crate) lines: Vec<String>,
+    pub(crate) is_error: bool,
+    /// The command quit SPUD; the connection closes next.
+    pub(crate) quit: bool,
+}
+
+/// Params of `spud.control.attach_screen`: the client's window size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AttachScreenParams {
+    pub(crate) cols: u16,
+    pub(crate) rows: u16,
+}
+
+/// Params of `spud.control.input`: a key or resize from an attached
+/// screen, as crossterm reported it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct InputParams {
+    pub(crate) event: CEvent,
+}
+
+/// Most lines an [`Outbox`] holds before it refuses more.
+pub(crate) const OUTBOX_CAPACITY: usize = 256;
+
+/// Lines waiting to be written to one connection.
+#[derive(Debug, Clone)]
+pub(crate) struct Outbox(SyncSender<String>);
+
+/// What became of a line handed to an [`Outbox`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Delivery {
+    Sent,
+    /// The client is [`OUTBOX_CAPACITY`] lines behind; the line was dropped.
+    Full,
+    /// The connection has closed.
+    Closed,
+}
+
+impl Outbox {
+    pub(crate) fn channel() -> (Self, Receiver<String>) {
+        Self::with_capacity(OUTBOX_CAPACITY)
+    }
+
+    fn with_capacity(capacity: usize) -> (Self, Receiver<String>) {
+        let (sender, lines) = mpsc::sync_channel(capacity);
+        (Self(sender), lines)
+    }
+
+    /// Send a notification without waiting for the client.
+    pub(crate) fn notify(&self, method: &str, params: impl Serialize) -> Delivery {
+        let message = json!({"jsonrpc": "2.0", "method": method, "params": params});
+        self.offer(message.to_string())
+    }
+
+    fn respond(&self, id: RequestId, result: Result<Value, JsonRpcError>) {
+        let response = match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(error) => json!({"jsonrpc": "2.0", "id": id, "error": error}),
+        };
+        if self.offer(response.to_string()) == Delivery::Full {
+            tracing::debug!(?id, "control client is not reading; response dropped");
+        }
+    }
+
+    fn offer(&self, line: String) -> Delivery {
+        match self.0.try_send(line) {
+            Ok(()) => Delivery::Sent,
+            Err(TrySendError::Full(_)) => Delivery::Full,
+            Err(TrySendError::Disconnected(_)) => Delivery::Closed,
+        }
+    }
+}
+
+/// One request waiting for the app's answer.
+pub(crate) struct ControlRequest {
+    pub(crate) method: String,
+    params: Value,
+    /// `None` for a notification, which gets no answer.
+    id: Option<RequestId>,
+    outbox: Outbox,
+}
+
+impl ControlRequest {
+    /// The params as `T`, or an invalid-params error.
+    pub(crate) fn params<T: DeserializeOwned>(&self) -> Result<T, JsonRpcError> {
+        serde_json::from_value(self.params.clone()).map_err(|err| JsonRpcError {
+            code: error_code::INVALID_PARAMS,
+            message: format!("invalid params for {}: {err}", self.method),
+            data: None,
+        })
+    }
+
+    /// Answer the request; a connection that went away is ignored.
+    pub(crate) fn reply(self, result: Result<Value, JsonRpcError>) {
+        if let Some(id) = self.id {
+            self.outbox.respond(id, result);
+        }
+    }
+
+    /// The requesting connection, to send it notifications later.
+    pub(crate) fn outbox(&self) -> Outbox {
+        self.outbox.clone()
+    }
+}
+
+/// Connections subscribed with `spud.control.logs_subscribe`.
+#[derive(Debug, Default)]
+pub(crate) struct LogStreams {
+    streams: Vec<(Outbox, LogSubscription)>,
+}
+
+impl LogStreams {
+    /// Subscribe the connection behind `outbox` with `params`.
+    pub(crate) fn subscribe(
+        &mut self,
+        outbox: Outbox,
+        params: &LogSubscribeParams,
+    ) -> Result<LogSubscriptionResult, JsonRpcError> {
+        let subscription = LogSubscription::from_params(params)?;
+        let result = LogSubscriptionResult {
+            subscribed: true,
+            max_per_sec: Some(subscription.rate()),
+        };
+        self.streams.push((outbox, subscription));
+        Ok(result)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// Send each subscriber the entries its filter and rate admit, and
+    /// forget connections that have closed. Entries a lagging subscriber
+    /// has no room for are dropped.
+    pub(crate) fn send(&mut self, entries: &[LogEntryParams], now: Instant) {
+        self.streams.retain_mut(|(outbox, subscription)| {
+            entries
+                .iter()
+                .all(|entry| match subscription.admit(entry, now) {
+                    Some(dropped) => {
+                        let params = LogEntryParams {
+                            dropped,
+                            ..entry.clone()
+                        };
+                        outbox.notify(method::LOG, params) != Delivery::Closed
+                    }
+                    None => true,
+                })
+        });
+    }
+}
+
+/// Error for a method the control socket does not have.
+pub(crate) fn method_not_found(method: &str) -> JsonRpcError {
+    JsonRpcError {
+        code: error_code::METHOD_NOT_FOUND,
+        message: format!("unknown control method: {method}"),
+        data: None,
+    }
+}
+
+/// A request line on the wire.
+#[derive(Deserialize)]
+struct Envelope {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<RequestId>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// The listening socket; removed when dropped.
+pub(crate) struct ControlServer {
+    path: PathBuf,
+    requests: Receiver<ControlRequest>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    /// Listen on `path`, replacing a socket left by an instance that
+    /// crashed. The caller must hold the instance lock.
+    #[cfg(unix)]
+    pub(crate) fn start(path: &Path) -> Result<Self> {
+        use std::os::unix::fs::PermissionsExt;
+        use std::os::unix::net::UnixListener;
+
+        let _ = std::fs::remove_file(path);
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("failed to listen on {}", path.display()))?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("failed to restrict {}", path.display()))?;
+        listener
+            .set_nonblocking(true)
+            .context("failed to configure the control socket")?;
+        let (sender, requests) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        std::thread::Builder::new()
+            .name("spud-control".into())
+            .spawn({
+                let stop = Arc::clone(&stop);
+                move || accept(&listener, &sender, &stop)
+            })
+            .context("failed to start the control socket thread")?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            requests,
+            stop,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn start(_path: &Path) -> Result<Self> {
+        bail!("the control socket needs Unix domain sockets")
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Requests received since the last call, without blocking.
+    pub(crate) fn drain(&self) -> Vec<ControlRequest> {
+        self.requests.try_iter().collect()
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Accept connections until `stop` is set, one thread each.
+#[cfg(unix)]
+fn accept(
+    listener: &std::os::unix::net::UnixListener,
+    requests: &Sender<ControlRequest>,
+    stop: &AtomicBool,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let requests = requests.clone();
+                let spawned = std::thread::Builder::new()
+                    .name("spud-control-conn".into())
+                    .spawn(move || {
+                        if let Err(err) = serve(stream, &requests) {
+                            tracing::debug!("control connection closed: {err}");
+                        }
+                    });
+                if let Err(err) = spawned {
+                    tracing::warn!("control connection refused: {err}");
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(ACCEPT_POLL);
+            }
+            Err(err) => {
+                tracing::debug!("control socket accept failed: {err}");
+                std::thread::sleep(ACCEPT_POLL);
+            }
+        }
+    }
+}
+
+/// Hand one connection's requests to the app in order until it closes.
+fn serve(stream: Stream, requests: &Sender<ControlRequest>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let writer = stream.try_clone()?;
+    let (outbox, lines) = Outbox::channel();
+    std::thread::Builder::new()
+        .name("spud-control-out".into())
+        .spawn(move || write_lines(writer, &lines))?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Envelope>(&line) {
+            Ok(envelope) if envelope.jsonrpc == "2.0" => {
+                let request = ControlRequest {
+                    method: envelope.method,
+                    params: envelope.params,
+                    id: envelope.id,
+                    outbox: outbox.clone(),
+                };
+                if requests.send(request).is_err() {
+                    return Ok(());
+                }
+            }
+            Ok(envelope) => outbox.respond(
+                envelope.id.unwrap_or(RequestId::Null),
+                Err(invalid_request("jsonrpc must be \"2.0\"")),
+            ),
+            Err(err) => outbox.respond(RequestId::Null, Err(invalid_request(&err.to_string()))),
+        }
+    }
+    Ok(())
+}
+
+/// Write a connection's outgoing lines until every [`Outbox`] for it is
+/// dropped or the peer goes away.
+fn write_lines(mut writer: Stream, lines: &Receiver<String>) {
+    for line in lines {
+        if writeln!(writer, "{line}").is_err() {
+            return;
+        }
+    }
+}
+
+fn invalid_request(message: &str) -> JsonRpcError {
+    JsonRpcError {
+        code: error_code::INVALID_REQUEST,
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+/// The instance closed the connection, usually because it quit.
+#[derive(Debug)]
+pub(crate) struct Closed;
+
+impl std::fmt::Display for Closed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SPUD closed the connection")
+    }
+}
+
+impl std::error::Error for Closed {}
+
+/// A connection to a running instance's control socket.
+pub(crate) struct ControlClient {
+    writer: Stream,
+    reader: BufReader<Stream>,
+    next_id: i64,
+}
+
+impl ControlClient {
+    #[cfg(unix)]
+    pub(crate) fn connect(path: &Path) -> Result<Self> {
+        let stream = Stream::connect(path)
+            .with_context(|| format!("failed to connect to {}", path.display()))?;
+        stream
+            .set_read_timeout(Some(REPLY_TIMEOUT))
+            .context("failed to configure the socket")?;
+        let reader = BufReader::new(stream.try_clone().context("failed to clone the socket")?);
+        Ok(Self {
+            writer: stream,
+            reader,
+            next_id: 1,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn connect(_path: &Path) -> Result<Self> {
+        bail!("the control socket needs Unix domain sockets")
+    }
+
+    pub(crate) fn info(&mut self) -> Result<InfoResult> {
+        self.call(method::INFO, json!({}))
+    }
+
+    pub(crate) fn command(&mut self, line: &str) -> Result<CommandResult> {
+        self.call(
+            method::COMMAND,
+            CommandParams {
+                line: line.to_string(),
+            },
+        )
+    }
+
+    /// Ask the instance to quit. It may be gone before it answers, so a
+    /// closed connection counts as done.
+    pub(crate) fn quit(&mut self) -> Result<()> {
+        match self.call::<Value>(method::QUIT, json!({})) {
+            Err(err) if err.is::<Closed>() => Ok(()),
+            result => result.map(drop),
+        }
+    }
+
+    /// Stream the instance's screen: the returned receiver gets each frame
+    /// it sends until it closes the connection.
+    pub(crate) fn attach_screen(
+        mut self,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(ScreenInput, Receiver<ScreenFrame>)> {
+        self.call::<Value>(method::ATTACH_SCREEN, AttachScreenParams { cols, rows })?;
+        let Self { writer, reader, .. } = self;
+        reader
+            .get_ref()
+            .set_read_timeout(None)
+            .context("failed to configure the socket")?;
+        let (sender, frames) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("spud-attach".into())
+            .spawn(move || read_frames(reader, &sender))
+            .context("failed to start the screen thread")?;
+        Ok((ScreenInput { writer }, frames))
+    }
+
+    fn call<R: DeserializeOwned>(&mut self, method: &str, params: impl Serialize) -> Result<R> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        writeln!(self.writer, "{request}").context("failed to send to SPUD")?;
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => return Err(Closed.into()),
+            Ok(_) => {}
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                bail!("SPUD did not answer within {}s", REPLY_TIMEOUT.as_secs())
+            }
+            Err(err) => return Err(err).context("failed to read from SPUD"),
+        }
+        let mut response: Value = serde_json::from_str(&line).context("invalid reply from SPUD")?;
+        if let Some(error) = response.get("error") {
+            let error: JsonRpcError =
+                serde_json::from_value(error.clone()).context("invalid error from SPUD")?;
+            bail!("{}", error.message);
+        }
+        serde_json::from_value(response["result"].take()).context("invalid result from SPUD")
+    }
+}
+
+/// Forward the screen frames read from `reader` until the connection or
+/// the receiver closes.
+fn read_frames(reader: BufReader<Stream>, frames: &Sender<ScreenFrame>) {
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let Ok(mut message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        if message["method"] != method::SCREEN {
+            continue;
+        }
+        match serde_json::from_value(message["params"].take()) {
+            Ok(frame) => {
+                if frames.send(frame).is_err() {
+                    return;
+                }
+            }
+            Err(err) => tracing::debug!("invalid screen frame: {err}"),
+        }
+    }
+}
+
+/// Sends an attached screen's input to the instance.
+pub(crate) struct ScreenInput {
+    writer: Stream,
+}
+
+impl ScreenInput {
+    pub(crate) fn send(&mut self, event: CEvent) -> Result<()> {
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": method::INPUT,
+            "params": InputParams { event },
+        });
+        writeln!(self.writer, "{message}").context("failed to send to SPUD")
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn requests_reach_the_app_and_answers_reach_the_client() {
+        let tmp = TestDir::new("control");
+        let path = tmp.join("control.sock");
+        let server = ControlServer::start(&path).unwrap();
+        let client = std::thread::spawn({
+            let path = path.clone();
+            move || {
+                let mut client = ControlClient::connect(&path).unwrap();
+                let result = client.command("uptime").unwrap();
+                let missing = client.call::<Value>("spud.control.nope", json!({}));
+                let (mut input, frames) = client.attach_screen(80, 24).unwrap();
+                let frame = frames.recv().unwrap();
+                input.send(CEvent::Resize(100, 30)).unwrap();
+                (result, missing.unwrap_err().to_string(), frame)
+            }
+        });
+
+        let mut answered = 0;
+        let mut input = None;
+        while input.is_none() {
+            for request in server.drain() {
+                let result = match request.method.as_str() {
+                    method::COMMAND => {
+                        let params: CommandParams = request.params().unwrap();
+                        Ok(json!(CommandResult {
+                            lines: vec![format!("ran {}", params.line)],
+                            is_error: false,
+                            quit: false,
+                        }))
+                    }
+                    method::ATTACH_SCREEN => {
+                        let params: AttachScreenParams = request.params().unwrap();
+                        assert_eq!((params.cols, params.rows), (80, 24));
+                        let outbox = request.outbox();
+                        request.reply(Ok(json!({})));
+                        // Sent after the answer, as the app's next draw would
+                        assert_eq!(
+                            outbox.notify(method::SCREEN, ScreenFrame::default()),
+                            Delivery::Sent
+                        );
+                        answered += 1;
+                        continue;
+                    }
+                    method::INPUT => {
+                        input = Some(request.params::<InputParams>().unwrap().event);
+                        Ok(json!({}))
+                    }
+                    other => Err(method_not_found(other)),
+                };
+                request.reply(result);
+                answered += 1;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let (result, missing, frame) = client.join().unwrap();
+        assert_eq!(answered, 4);
+        assert_eq!(result.lines, ["ran uptime"]);
+        assert!(missing.contains("unknown control method"));
+        assert_eq!(frame, ScreenFrame::default());
+        assert_eq!(input, Some(CEvent::Resize(100, 30)));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn full_outbox_drops_lines_until_the_client_catches_up() {
+        let (outbox, lines) = Outbox::with_capacity(1);
+        assert_eq!(outbox.notify(method::LOG, "one"), Delivery::Sent);
+        assert_eq!(outbox.notify(method::LOG, "two"), Delivery::Full);
+        assert_eq!(lines.try_iter().count(), 1);
+        assert_eq!(outbox.notify(method::LOG, "three"), Delivery::Sent);
+        drop(lines);
+        assert_eq!(outbox.notify(method::LOG, "four"), Delivery::Closed);
+    }
+
+    #[test]
+    fn log_streams_filter_rate_limit_and_forget_closed_connections() {
+        use spud_remote::protocol::LogSeverity;
+
+        let entry = |level, message: &str| LogEntryParams {
+            level,
+            target: "spud_core".into(),
+            message: message.into(),
+            dropped: 0,
+        };
+        let mut streams = LogStreams::default();
+        let (outbox, lines) = Outbox::channel();
+        let params = LogSubscribeParams {
+            min_level: LogSeverity::Warn,
+            max_per_sec: Some(1),
+            ..LogSubscribeParams::default()
+        };
+        let result = streams.subscribe(outbox.clone(), &params).unwrap();
+        assert_eq!(result.max_per_sec, Some(1));
+        let too_fast = LogSubscribeParams {
+            max_per_sec: Some(0),
+            ..LogSubscribeParams::default()
+        };
+        assert!(streams.subscribe(outbox, &too_fast).is_err());
+
+        let now = Instant::now();
+        streams.send(
+            &[
+                entry(LogSeverity::Info, "quiet"),
+                entry(LogSeverity::Warn, "first"),
+                entry(LogSeverity::Error, "over the rate"),
+            ],
+            now,
+        );
+        streams.send(
+            &[entry(LogSeverity::Warn, "later")],
+            now + Duration::from_secs(1),
+        );
+        let sent: Vec<Value> = lines
+            .try_iter()
+            .map(|line| serde_json::from_str(&line).unwrap())
+            .collect();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0]["method"], method::LOG);
+        assert_eq!(sent[0]["params"]["message"], "first");
+        assert_eq!(sent[1]["params"]["message"], "later");
+        assert_eq!(sent[1]["params"]["dropped"], 1);
+
+        drop(lines);
+        streams.send(
+            &[entry(LogSeverity::Warn, "gone")],
+            now + Duration::from_secs(2),
+        );
+        assert!(streams.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+//! App-level `debug` console command.
+//!
+//! Lives in the app because the overlays it toggles are drawn by the app
+//! loop.
+
+use spud_core::command::CommandOutput;
+use spud_core::tap::EventTap;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "debug";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "debug events [pause|resume|clear]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Toggle or control the recent-events overlay (F12)";
+
+/// Run a `debug` subcommand. `show_events` is the overlay's visibility.
+pub(crate) fn execute(tap: &mut EventTap, show_events: &mut bool, args: &[&str]) -> CommandOutput {
+    let line = match args {
+        ["events"] => {
+            *show_events = !*show_events;
+            if *show_events {
+                "event overlay shown"
+            } else {
+                "event overlay hidden"
+            }
+        }
+        ["events", "pause"] => {
+            tap.set_paused(true);
+            "event capture paused"
+        }
+        ["events", "resume"] => {
+            tap.set_paused(false);
+            "event capture resumed"
+        }
+        ["events", "clear"] => {
+            tap.clear();
+            "event history cleared"
+        }
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    };
+    CommandOutput::Lines(vec![line.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_core::event::Event;
+    use std::time::Instant;
+
+    #[test]
+    fn controls_overlay_and_tap() {
+        let now = Instant::now();
+        let mut tap = EventTap::new(now, 8);
+        let mut shown = false;
+
+        execute(&mut tap, &mut shown, &["events"]);
+        assert!(shown);
+        execute(&mut tap, &mut shown, &["events", "pause"]);
+        assert!(tap.is_paused());
+        execute(&mut tap, &mut shown, &["events", "resume"]);
+        assert!(!tap.is_paused());
+
+        tap.record(&Event::Quit, now);
+        execute(&mut tap, &mut shown, &["events", "clear"]);
+        assert!(tap.entries().is_empty());
+
+        assert!(execute(&mut tap, &mut shown, &[]).is_error());
+        assert!(execute(&mut tap, &mut shown, &["events", "bogus"]).is_error());
+    }
+}
@@ -0,0 +1,432 @@
+//! Diagnostics shared by `spud doctor` and the `doctor` console command.
+//!
+//! Each check reports a status plus, when something is off, a hint the user
+//! can act on. Checks never modify configuration; the log directory probe
+//! writes and removes a single scratch file.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use spud_config::{AlertsConfig, AppConfig, APP_CONFIG_FILE};
+use spud_core::alerts::AlertEngine;
+use spud_core::command::{Column, CommandOutput, Table};
+use spud_core::logging;
+use spud_remote::runtime::PluginRuntime;
+
+use crate::alerts;
+use crate::builder::Options;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "doctor";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "doctor";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Check terminal, config, plugins, and log directory";
+/// Smallest terminal the shell layout is designed for.
+const MIN_TERMINAL_SIZE: (u16, u16) = (80, 24);
+
+/// Where the checks look, resolved once from the runtime options.
+#[derive(Debug, Clone)]
+pub(crate) struct Settings {
+    pub config_dir: Option<PathBuf>,
+    pub plugin_roots: Vec<PathBuf>,
+    pub log_dir: PathBuf,
+}
+
+impl Settings {
+    /// Resolve paths the same way the app does at startup.
+    pub(crate) fn from_options(options: &Options) -> Self {
+        Self {
+            config_dir: options.config_dir(),
+            plugin_roots: options.plugin_roots.clone(),
+            log_dir: logging::log_dir(),
+        }
+    }
+}
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warn => "warn",
+            Self::Fail => "FAIL",
+        }
+    }
+}
+
+/// One diagnostic result.
+#[derive(Debug, Clone)]
+pub(crate) struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// `spud doctor`: print the report and exit non-zero if any check failed.
+pub(crate) fn run(settings: &Settings) -> Result<()> {
+    let output = report(&run_checks(settings));
+    for line in output.to_lines() {
+        println!("{line}");
+    }
+    if output.is_error() {
+        anyhow::bail!("doctor found problems");
+    }
+    Ok(())
+}
+
+/// `doctor` console command.
+pub(crate) fn execute(settings: &Settings, args: &[&str]) -> CommandOutput {
+    if !args.is_empty() {
+        return CommandOutput::Error(vec![format!("usage: {USAGE}")]);
+    }
+    report(&run_checks(settings))
+}
+
+/// Run every check in display order.
+pub(crate) fn run_checks(settings: &Settings) -> Vec<Check> {
+    let env = |key: &str| std::env::var(key).ok();
+    let mut checks = terminal_checks(&env, crossterm::terminal::size().ok());
+    checks.push(settings_check(settings.config_dir.as_deref()));
+    checks.push(alerts_check(settings.config_dir.as_deref()));
+    checks.push(plugins_check(&settings.plugin_roots));
+    checks.push(face_pack_check());
+    checks.push(log_dir_check(&settings.log_dir));
+    checks
+}
+
+/// Render checks as a table, followed by hints. The output is an error if
+/// any check failed.
+pub(crate) fn report(checks: &[Check]) -> CommandOutput {
+    let mut table = Table::new(vec![
+        Column::left("CHECK"),
+        Column::left("STATUS"),
+        Column::left("DETAIL"),
+    ]);
+    let mut hints = Vec::new();
+    for check in checks {
+        table.push_row(vec![
+            check.name.to_string(),
+            check.status.as_str().to_string(),
+            check.detail.clone(),
+        ]);
+        if let Some(hint) = &check.hint {
+            hints.push(format!("  {}: {hint}", check.name));
+        }
+    }
+
+    let mut parts = vec![CommandOutput::Table(table)];
+    if !hints.is_empty() {
+        parts.push(CommandOutput::Lines(hints));
+    }
+    let failed = checks
+        .iter()
+        .filter(|check| check.status == Status::Fail)
+        .count();
+    if failed > 0 {
+        parts.push(CommandOutput::Error(vec![format!(
+            "{failed} check(s) failed"
+        )]));
+    }
+    CommandOutput::Multi(parts)
+}
+
+/// Colour depth, inline graphics protocols, window size, and synchronized
+/// output. `env` reads an
+/// environment variable; `size` is `(columns, rows)` if stdout is a terminal.
+fn terminal_checks(env: &dyn Fn(&str) -> Option<String>, size: Option<(u16, u16)>) -> Vec<Check> {
+    let mut checks = Vec::with_capacity(4);
+
+    let colorterm = env("COLORTERM").unwrap_or_default();
+    checks.push(if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+        Check::ok("truecolor", format!("COLORTERM={colorterm}"))
+    } else if env("WT_SESSION").is_some() {
+        // Windows Terminal supports 24-bit colour but sets no COLORTERM.
+        Check::ok("truecolor", "Windows Terminal")
+    } else {
+        Check::warn(
+            "truecolor",
+            "COLORTERM does not advertise 24-bit colour",
+            "set COLORTERM=truecolor if your terminal supports 24-bit colour",
+        )
+    });
+
+    let protocols = graphics_protocols(env);
+    checks.push(if protocols.is_empty() {
+        Check::ok("graphics", "none detected; faces render as text")
+    } else {
+        Check::ok("graphics", protocols.join(", "))
+    });
+
+    let (min_cols, min_rows) = MIN_TERMINAL_SIZE;
+    checks.push(match size {
+        Some((cols, rows)) if cols >= min_cols && rows >= min_rows => {
+            Check::ok("terminal size", format!("{cols}x{rows}"))
+        }
+        Some((cols, rows)) => Check::warn(
+            "terminal size",
+            format!("{cols}x{rows}"),
+            format!("enlarge the window to at least {min_cols}x{min_rows}"),
+        ),
+        None => Check::warn(
+            "terminal size",
+            "not a terminal",
+            "run spud from an interactive terminal",
+        ),
+    });
+
+    checks.push(match sync_output_terminal(env) {
+        Some(terminal) => Check::ok("sync output", terminal),
+        None => Check::ok(
+            "sync output",
+            "not detected; set [terminal] sync_output = \"on\" to force it",
+        ),
+    });
+
+    checks
+}
+
+/// Name of the terminal if it is one known to support synchronized updates
+/// (DEC mode 2026), guessed from the same variables as
+/// [`graphics_protocols`].
+pub(crate) fn sync_output_terminal(env: &dyn Fn(&str) -> Option<String>) -> Option<&'static str> {
+    let term = env("TERM").unwrap_or_default();
+    let program = env("TERM_PROGRAM").unwrap_or_default();
+    if term.contains("kitty") || env("KITTY_WINDOW_ID").is_some() {
+        return Some("kitty");
+    }
+    match program.as_str() {
+        "WezTerm" => return Some("WezTerm"),
+        "ghostty" => return Some("ghostty"),
+        "iTerm.app" => return Some("iTerm2"),
+        "vscode" => return Some("VS Code"),
+        _ => {}
+    }
+    if term == "foot" || term.starts_with("foot-") {
+        return Some("foot");
+    }
+    if term == "alacritty" {
+        return Some("Alacritty");
+    }
+    if term.starts_with("contour") {
+        return Some("Contour");
+    }
+    env("WT_SESSION").map(|_| "Windows Terminal")
+}
+
+/// Inline image protocols the terminal likely supports, guessed from
+/// `TERM`, `TERM_PROGRAM`, and `KITTY_WINDOW_ID`. The Windows console sets
+/// none of these, so it falls back to text faces.
+pub(crate) fn graphics_protocols(env: &dyn Fn(&str) -> Option<String>) -> Vec<&'static str> {
+    let term = env("TERM").unwrap_or_default();
+    let program = env("TERM_PROGRAM").unwrap_or_default();
+    let mut protocols = Vec::new();
+    if term.contains("kitty")
+        || env("KITTY_WINDOW_ID").is_some()
+        || matches!(program.as_str(), "WezTerm" | "ghostty")
+    {
+        protocols.push("kitty");
+    }
+    if term.contains("sixel")
+        || term == "foot"
+        || term.starts_with("mlterm")
+        || matches!(program.as_str(), "iTerm.app" | "WezTerm")
+    {
+        protocols.push("sixel");
+    }
+    protocols
+}
+
+/// Whether `spud.toml` (if any) parses and passes validation.
+fn settings_check(config_dir: Option<&Path>) -> Check {
+    const CHECK: &str = "settings";
+    let Some(path) = config_dir.map(|dir| dir.join(APP_CONFIG_FILE)) else {
+        return Check::ok(CHECK, "no config directory; using defaults");
+    };
+    if !path.exists() {
+        return Check::ok(CHECK, format!("{} not present", path.display()));
+    }
+    match AppConfig::from_path(&path) {
+        Ok(_) => Check::ok(CHECK, path.display().to_string()),
+        Err(err) => Check::fail(
+            CHECK,
+            format!("{err:#}"),
+            "fix the file; until then the defaults apply",
+        ),
+    }
+}
+
+/// Whether the alert rules file (if any) parses and compiles.
+fn alerts_check(config_dir: Option<&Path>) -> Check {
+    const CHECK: &str = "alerts config";
+    let Some(path) = alerts::config_path(config_dir) else {
+        return Check::ok(CHECK, "no config directory; alerts disabled");
+    };
+    if !path.exists() {
+        return Check::ok(CHECK, format!("{} not present", path.display()));
+    }
+    match AlertsConfig::from_path(&path).and_then(|config| AlertEngine::from_config(&config)) {
+        Ok(engine) => Check::ok(
+            CHECK,
+            format!("{} ({} rules)", path.display(), engine.alerts().len()),
+        ),
+        Err(err) => Check::fail(
+            CHECK,
+            format!("{}: {err:#}", path.display()),
+            "fix the rule file; until then no alerts are evaluated",
+        ),
+    }
+}
+
+/// Whether every plugin manifest under the search roots is valid.
+fn plugins_check(roots: &[PathBuf]) -> Check {
+    const CHECK: &str = "plugins";
+    if roots.is_empty() {
+        return Check::ok(
+            CHECK,
+            "no plugin dirs (use --plugin-dir or SPUD_PLUGIN_DIRS)",
+        );
+    }
+    if let Some(missing) = roots.iter().find(|root| !root.exists()) {
+        return Check::warn(
+            CHECK,
+            format!("{} does not exist", missing.display()),
+            "create the directory or remove it from the search path",
+        );
+    }
+    match PluginRuntime::from_search_roots(roots) {
+        Ok(runtime) => Check::ok(
+            CHECK,
+            format!("{} manifest(s) valid", runtime.plugin_ids().len()),
+        ),
+        Err(err) => Check::fail(
+            CHECK,
+            format!("{err:#}"),
+            "fix or remove the manifest; plugins are disabled until discovery succeeds",
+        ),
+    }
+}
+
+/// Whether the built-in face pack loads.
+fn face_pack_check() -> Check {
+    match spud_agent::Agent::load_default(Instant::now()) {
+        Ok(_) => Check::ok("face pack", "built-in pack loaded"),
+        Err(err) => Check::fail(
+            "face pack",
+            format!("{err:#}"),
+            "the bundled face assets are broken; reinstall spud",
+        ),
+    }
+}
+
+/// Whether log files can be created in `dir`.
+fn log_dir_check(dir: &Path) -> Check {
+    match probe_writable(dir) {
+        Ok(()) => Check::ok("log dir", dir.display().to_string()),
+        Err(err) => Check::fail(
+            "log dir",
+            format!("{err:#}"),
+            "set SPUD_LOG_DIR to a writable directory",
+        ),
+    }
+}
+
+fn probe_writable(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    let probe = dir.join(format!(".spud-doctor-{}", std::process::id()));
+    std::fs::write(&probe, b"ok").with_context(|| format!("cannot write to {}", dir.display()))?;
+    std::fs::remove_file(&probe).with_context(|| format!("cannot clean up {}", probe.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn terminal_checks_read_env_and_size() {
+        let env = |key: &str| match key {
+            "COLORTERM" => Some("truecolor".to_string()),
+            "TERM" => Some("xterm-kitty".to_string()),
+            _ => None,
+        };
+        let checks = terminal_checks(&env, Some((60, 20)));
+        assert_eq!(checks[0].status, Status::Ok);
+        assert_eq!(checks[1].detail, "kitty");
+        assert_eq!(checks[2].status, Status::Warn);
+        assert!(checks[2].hint.as_deref().unwrap().contains("80x24"));
+        assert_eq!(checks[3].detail, "kitty");
+
+        let checks = terminal_checks(&|_| None, None);
+        assert_eq!(checks[0].status, Status::Warn);
+        assert_eq!(checks[2].detail, "not a terminal");
+    }
+
+    #[test]
+    fn windows_terminal_is_truecolor_without_graphics() {
+        let env = |key: &str| match key {
+            "WT_SESSION" => Some("0b7a6b6e".to_string()),
+            _ => None,
+        };
+        let checks = terminal_checks(&env, Some((120, 30)));
+        assert_eq!(checks[0].status, Status::Ok);
+        assert_eq!(checks[0].detail, "Windows Terminal");
+        assert!(graphics_protocols(&env).is_empty());
+        assert_eq!(checks[1].detail, "none detected; faces render as text");
+        assert_eq!(sync_output_terminal(&env), Some("Windows Terminal"));
+        assert_eq!(sync_output_terminal(&|_| None), None);
+    }
+
+    #[test]
+    fn invalid_alerts_file_fails_and_report_is_error() {
+        let dir = TestDir::new("doctor");
+        std::fs::write(dir.join("alerts.toml"), "[[alert]]\nname = ").unwrap();
+        let check = alerts_check(Some(&dir));
+
+        assert_eq!(check.status, Status::Fail);
+        let output = report(&[check, log_dir_check(&std::env::temp_dir())]);
+        assert!(output.is_error());
+        let lines = output.to_lines();
+        assert!(lines[0].contains("CHECK"));
+        assert!(lines.iter().any(|line| line.contains("alerts config: fix")));
+    }
+}
@@ -0,0 +1,118 @@
+//! App-level `dump` console command.
+//!
+//! Writes a pretty JSON snapshot of the running app for attaching to bug
+//! reports. Lives in the app because only the app holds every subsystem at
+//! once.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use spud_config::Profile;
+use spud_core::command::CommandOutput;
+use spud_core::console::ConsoleSnapshot;
+use spud_core::fps::TickSnapshot;
+use spud_core::registry::RegistrySnapshot;
+use spud_core::state::AppStateSnapshot;
+use spud_core::telemetry::SeriesSnapshot;
+use spud_remote::runtime::PluginSessionSnapshot;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "dump";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "dump state [path]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Write runtime state as JSON for bug reports";
+
+/// Everything `dump state` writes.
+#[derive(Debug, Serialize)]
+pub(crate) struct StateDump {
+    pub(crate) version: &'static str,
+    /// Unix milliseconds when the dump was taken.
+    pub(crate) taken_at_ms: u64,
+    /// Random seed; replay with `--seed`.
+    pub(crate) seed: u64,
+    pub(crate) profile: Profile,
+    pub(crate) state: AppStateSnapshot,
+    pub(crate) modules: RegistrySnapshot,
+    pub(crate) console: ConsoleSnapshot,
+    pub(crate) ticks: TickSnapshot,
+    /// Registered plugins; empty when the plugin runtime is disabled.
+    pub(crate) plugins: Vec<PluginSessionSnapshot>,
+    /// Latest sample of each telemetry series.
+    pub(crate) telemetry: Vec<SeriesSnapshot>,
+}
+
+/// Run a `dump` subcommand. Without a path the file goes in `default_dir`
+/// (the log directory), named after the dump time.
+pub(crate) fn execute(dump: &StateDump, default_dir: &Path, args: &[&str]) -> CommandOutput {
+    let path = match args {
+        ["state"] => default_dir.join(format!("spud-state-{}.json", dump.taken_at_ms)),
+        ["state", path] => PathBuf::from(path),
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    };
+    match write(dump, &path) {
+        Ok(()) => CommandOutput::Lines(vec![format!("state written to {}", path.display())]),
+        Err(err) => CommandOutput::Error(vec![format!("{err:#}")]),
+    }
+}
+
+/// Write `dump` to `path` as pretty JSON, creating parent directories.
+pub(crate) fn write(dump: &StateDump, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(dump).context("failed to serialize state")?;
+    fs::write(path, json + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use spud_core::console::Console;
+    use spud_core::fps::TickCounter;
+    use spud_core::registry::ModuleRegistry;
+    use spud_core::state::AppState;
+    use spud_core::telemetry::TelemetryStore;
+    use spud_testkit::TestDir;
+
+    fn sample_dump() -> StateDump {
+        StateDump {
+            version: "test",
+            taken_at_ms: 42,
+            seed: 7,
+            profile: Profile::Battery,
+            state: AppState::new().snapshot(),
+            modules: ModuleRegistry::new().snapshot(),
+            console: Console::default().snapshot(),
+            ticks: TickCounter::default().snapshot(),
+            plugins: Vec::new(),
+            telemetry: TelemetryStore::default().snapshot(),
+        }
+    }
+
+    #[test]
+    fn writes_pretty_json_to_default_dir_and_explicit_path() {
+        let dir = TestDir::new("dump");
+        let dump = sample_dump();
+
+        let output = execute(&dump, &dir, &["state"]);
+        assert!(!output.is_error(), "{:?}", output.to_lines());
+        let raw = fs::read_to_string(dir.join("spud-state-42.json")).unwrap();
+        assert!(raw.contains("\n  \"seed\": 7"));
+        let value: Value = serde_json::from_str(&raw).unwrap();
+        assert_eq!(value["profile"], "battery");
+        assert_eq!(value["console"]["open"], false);
+        assert_eq!(value["ticks"]["total"], 0);
+
+        let explicit = dir.join("nested").join("state.json");
+        let output = execute(&dump, &dir, &["state", explicit.to_str().unwrap()]);
+        assert!(!output.is_error());
+        assert!(explicit.exists());
+
+        assert!(execute(&dump, &dir, &[]).is_error());
+    }
+}
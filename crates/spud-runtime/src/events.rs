@@ -0,0 +1,216 @@
+//! App-level `event` console command.
+//!
+//! `event publish` puts a [`Event::Custom`] on the bus, for exercising
+//! modules and plugins by hand; `event tail` copies published events into
+//! the console until stopped; `event schemas` lists the tags whose payloads
+//! are checked against a registered schema. Lives in the app because it owns the bus and
+//! the console.
+
+use serde_json::Value;
+use spud_core::command::CommandOutput;
+use spud_core::event::Event;
+use spud_core::schema::EventSchemas;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "event";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str =
+    "event publish <tag> <json> | event tail [kind|stop] | event schemas";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Publish a custom bus event, or tail events into the console";
+
+/// Log target of tailed events in the console.
+pub(crate) const TAIL_TARGET: &str = "event";
+
+/// An active `event tail`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct EventTail {
+    /// Only events of this [`Event::kind`]; everything but ticks and spans
+    /// when `None`.
+    kind: Option<&'static str>,
+}
+
+impl EventTail {
+    /// Whether `event` should be shown.
+    pub(crate) fn matches(&self, event: &Event) -> bool {
+        match self.kind {
+            Some(kind) => event.kind() == kind,
+            // Ticks and spans arrive every frame and would bury the rest.
+            None => !matches!(event, Event::Tick { .. } | Event::Span { .. }),
+        }
+    }
+
+    /// Console line for a matching event.
+    pub(crate) fn line(event: &Event) -> String {
+        let preview = event.preview();
+        if preview.is_empty() {
+            event.kind().to_string()
+        } else {
+            format!("{} {preview}", event.kind())
+        }
+    }
+}
+
+/// Run an `event` subcommand. Returns the output and the event to publish,
+/// if any; `tail` is replaced or cleared in place. Published payloads must
+/// match the tag's schema in `schemas`, if it has one.
+pub(crate) fn execute(
+    tail: &mut Option<EventTail>,
+    schemas: &EventSchemas,
+    args: &[&str],
+) -> (CommandOutput, Option<Event>) {
+    match args {
+        ["publish", tag, json @ ..] if !json.is_empty() => {
+            // Unquoted JSON arrives split on spaces; put it back together.
+            let json = json.join(" ");
+            match serde_json::from_str::<Value>(&json) {
+                Ok(payload) => {
+                    if let Err(violations) = schemas.validate(tag, &payload.to_string()) {
+                        let mut lines =
+                            vec![format!("payload for {tag} does not match its schema:")];
+                        lines.extend(violations.iter().map(|violation| format!("  {violation}")));
+                        return (CommandOutput::Error(lines), None);
+                    }
+                    let line = format!("published {tag} {payload}");
+                    let event = Event::Custom {
+                        tag: tag.to_string(),
+                        payload: payload.to_string(),
+                    };
+                    (CommandOutput::Lines(vec![line]), Some(event))
+                }
+                Err(err) => (
+                    CommandOutput::Error(vec![
+                        format!("invalid JSON payload: {err}"),
+                        "quote it in single quotes: event publish <tag> '{...}'".into(),
+                    ]),
+                    None,
+                ),
+            }
+        }
+        ["tail", "stop"] => {
+            let line = if tail.take().is_some() {
+                "event tail stopped"
+            } else {
+                "no event tail running"
+            };
+            (CommandOutput::Lines(vec![line.to_string()]), None)
+        }
+        ["tail"] => {
+            *tail = Some(EventTail { kind: None });
+            let line = "tailing events (except tick and span); `event tail stop` to end";
+            (CommandOutput::Lines(vec![line.to_string()]), None)
+        }
+        ["tail", kind] => match Event::KINDS.into_iter().find(|known| known == kind) {
+            Some(kind) => {
+                *tail = Some(EventTail { kind: Some(kind) });
+                let line = format!("tailing {kind} events; `event tail stop` to end");
+                (CommandOutput::Lines(vec![line]), None)
+            }
+            None => (
+                CommandOutput::Error(vec![format!(
+                    "unknown event kind {kind:?} (expected one of: {})",
+                    Event::KINDS.join(", ")
+                )]),
+                None,
+            ),
+        },
+        ["schemas"] => {
+            let lines = if schemas.is_empty() {
+                vec!["no event schemas registered".to_string()]
+            } else {
+                schemas
+                    .iter()
+                    .map(|(tag, owner)| format!("{tag}  (from {owner})"))
+                    .collect()
+            };
+            (CommandOutput::Lines(lines), None)
+        }
+        _ => (CommandOutput::Error(vec![format!("usage: {USAGE}")]), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn publishes_custom_events_with_json_payloads() {
+        let mut tail = None;
+        let (output, event) = execute(
+            &mut tail,
+            &EventSchemas::new(),
+            &["publish", "demo.ping", "{\"n\":", "1}"],
+        );
+        assert!(!output.is_error());
+        assert!(matches!(
+            event,
+            Some(Event::Custom { tag, payload }) if tag == "demo.ping" && payload == "{\"n\":1}"
+        ));
+
+        let (output, event) = execute(
+            &mut tail,
+            &EventSchemas::new(),
+            &["publish", "demo.ping", "{oops"],
+        );
+        assert!(output.is_error());
+        assert!(event.is_none());
+        assert!(
+            execute(&mut tail, &EventSchemas::new(), &["publish", "demo.ping"])
+                .0
+                .is_error()
+        );
+    }
+
+    #[test]
+    fn publish_checks_registered_schemas() {
+        let mut schemas = EventSchemas::new();
+        schemas
+            .register(
+                "test",
+                "demo.ping",
+                serde_json::json!({"type": "object", "required": ["n"]}),
+            )
+            .unwrap();
+        let mut tail = None;
+        let (output, event) = execute(&mut tail, &schemas, &["publish", "demo.ping", "{}"]);
+        assert!(output.is_error());
+        assert!(event.is_none());
+        assert!(output.to_lines()[1].contains("\"n\""));
+
+        let (output, event) = execute(&mut tail, &schemas, &["publish", "demo.ping", "{\"n\":1}"]);
+        assert!(!output.is_error());
+        assert!(event.is_some());
+
+        let (output, _) = execute(&mut tail, &schemas, &["schemas"]);
+        assert_eq!(output.to_lines(), vec!["demo.ping  (from test)"]);
+    }
+
+    #[test]
+    fn tail_filters_by_kind_until_stopped() {
+        let mut tail = None;
+        execute(&mut tail, &EventSchemas::new(), &["tail"]);
+        let all = tail.clone().unwrap();
+        assert!(all.matches(&Event::Quit));
+        assert!(!all.matches(&Event::Tick {
+            now: Instant::now()
+        }));
+
+        execute(&mut tail, &EventSchemas::new(), &["tail", "custom"]);
+        let custom = Event::Custom {
+            tag: "demo".into(),
+            payload: "{}".into(),
+        };
+        assert!(tail.as_ref().unwrap().matches(&custom));
+        assert!(!tail.as_ref().unwrap().matches(&Event::Quit));
+        assert_eq!(EventTail::line(&custom), "custom demo {}");
+        assert_eq!(EventTail::line(&Event::Quit), "quit");
+
+        assert!(execute(&mut tail, &EventSchemas::new(), &["tail", "bogus"])
+            .0
+            .is_error());
+        assert!(tail.is_some());
+        execute(&mut tail, &EventSchemas::new(), &["tail", "stop"]);
+        assert!(tail.is_none());
+    }
+}
@@ -0,0 +1,324 @@
+//! `[export]`: the agent frame and runtime metrics in a shared file.
+//!
+//! External programs (OBS overlays, status bars) map the file and read it
+//! without talking to SPUD. The file is [`SIZE`] bytes, rewritten in place,
+//! little-endian:
+//!
+//! | Offset | Type      | Field                                                   |
+//! |--------|-----------|---------------------------------------------------------|
+//! | 0      | `[u8; 8]` | magic `SPUDFRM\0`                                       |
+//! | 8      | `u32`     | layout version, 1                                       |
+//! | 12     | `u32`     | file size, 4096                                         |
+//! | 16     | `u64`     | sequence; odd while a write is in progress              |
+//! | 24     | `u64`     | written at, Unix ms                                     |
+//! | 32     | `u64`     | uptime, ms                                              |
+//! | 40     | `f64`     | ticks per second                                        |
+//! | 48     | `f32`     | CPU percent, NaN when unknown                           |
+//! | 52     | `f32`     | memory percent, NaN when unknown                        |
+//! | 56     | `u8`      | mood: 0 neutral, 1 happy, 2 angry, 3 god mode, 4 hurt, 5 thinking |
+//! | 57     | `u8`      | flags: bit 0 set while SPUD is running                  |
+//! | 58     | `u16`     | frame rows                                              |
+//! | 60     | `u16`     | active module id length                                 |
+//! | 62     | `u16`     | status line length                                      |
+//! | 64     | `u16`     | frame text length                                       |
+//! | 128    | 64 bytes  | active module id, UTF-8                                 |
+//! | 192    | 256 bytes | status line, UTF-8                                      |
+//! | 448    | 3648 bytes| frame rows, UTF-8, joined with `\n`                     |
+//!
+//! Text longer than its slot is cut at a character boundary. Readers copy
+//! the file, then check that the sequence is even and unchanged, and try
+//! again otherwise.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use spud_agent::Mood;
+use spud_config::ExportConfig;
+
+/// Size of the export file.
+pub(crate) const SIZE: usize = 4096;
+const MAGIC: &[u8; 8] = b"SPUDFRM\0";
+const LAYOUT_VERSION: u32 = 1;
+const SEQ_OFFSET: u64 = 16;
+const RUNNING: u8 = 1;
+const MODULE_SLOT: (usize, usize) = (128, 64);
+const STATUS_SLOT: (usize, usize) = (192, 256);
+const FRAME_SLOT: (usize, usize) = (448, SIZE - 448);
+
+/// Default export file for the instance locked in `instance_dir` (see
+/// [`crate::instance`]): in `$XDG_RUNTIME_DIR`, which is per user and
+/// usually in memory, else in the cache dir. The name carries a hash of
+/// `instance_dir`, so instances run side by side get a file each.
+pub(crate) fn default_path(instance_dir: Option<&Path>) -> Option<PathBuf> {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute() && dir.is_dir());
+    default_path_in(runtime_dir, spud_config::paths::cache_dir(), instance_dir)
+}
+
+fn default_path_in(
+    runtime_dir: Option<PathBuf>,
+    cache_dir: Option<PathBuf>,
+    instance_dir: Option<&Path>,
+) -> Option<PathBuf> {
+    let name = match instance_dir {
+        Some(dir) => format!(
+            "spud-frame-{:08x}",
+            fnv1a(dir.as_os_str().as_encoded_bytes())
+        ),
+        None => "spud-frame".to_string(),
+    };
+    runtime_dir.or(cache_dir).map(|dir| dir.join(name))
+}
+
+/// 32-bit FNV-1a, stable across builds unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// What one write puts in the file.
+pub(crate) struct FrameSnapshot<'a> {
+    pub(crate) uptime: Duration,
+    pub(crate) tps: f64,
+    pub(crate) cpu_percent: Option<f32>,
+    pub(crate) mem_percent: Option<f32>,
+    pub(crate) mood: Mood,
+    pub(crate) frame: &'a [String],
+    pub(crate) active_module: &'a str,
+    pub(crate) status_line: &'a str,
+}
+
+pub(crate) struct FrameExport {
+    file: File,
+    path: PathBuf,
+    seq: u64,
+    interval: Duration,
+    written_at: Option<Instant>,
+}
+
+impl FrameExport {
+    /// Create or take over the export file `config` names, or the default
+    /// one for `instance_dir`. The file is owner-only.
+    pub(crate) fn open(config: &ExportConfig, instance_dir: Option<&Path>) -> Result<Self> {
+        let path = config
+            .path
+            .clone()
+            .or_else(|| default_path(instance_dir))
+            .context("no cache directory for the export file; set export.path")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let mut options = OpenOptions::new();
+        options.create(true).truncate(false).read(true).write(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        // A file left by an older version may be readable by others
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))
+                .with_context(|| format!("failed to restrict {}", path.display()))?;
+        }
+        file.set_len(SIZE as u64)
+            .with_context(|| format!("failed to size {}", path.display()))?;
+        Ok(Self {
+            file,
+            path,
+            seq: 0,
+            interval: config.interval(),
+            written_at: None,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the write interval has passed since the last write.
+    pub(crate) fn due(&self, now: Instant) -> bool {
+        self.written_at
+            .is_none_or(|at| now.saturating_duration_since(at) >= self.interval)
+    }
+
+    /// Rewrite the file from `snapshot`.
+    pub(crate) fn write(&mut self, snapshot: &FrameSnapshot<'_>, now: Instant) -> Result<()> {
+        self.written_at = Some(now);
+        let body = encode(snapshot, RUNNING);
+        self.publish(&body)
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+
+    /// Write `body` between an odd and an even sequence number, so a
+    /// reader can tell a torn copy.
+    fn publish(&mut self, body: &[u8; SIZE]) -> std::io::Result<()> {
+        self.seq += 1;
+        self.write_at(SEQ_OFFSET, &self.seq.to_le_bytes())?;
+        self.write_at(0, &body[..SEQ_OFFSET as usize])?;
+        self.write_at(SEQ_OFFSET + 8, &body[SEQ_OFFSET as usize + 8..])?;
+        self.seq += 1;
+        self.write_at(SEQ_OFFSET, &self.seq.to_le_bytes())
+    }
+
+    fn write_at(&mut self, offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.write_all(bytes)
+    }
+}
+
+impl Drop for FrameExport {
+    /// Clear the running flag so readers know the frame is stale.
+    fn drop(&mut self) {
+        let mut body = [0u8; SIZE];
+        put_header(&mut body);
+        let _ = self.publish(&body);
+    }
+}
+
+fn put_header(body: &mut [u8; SIZE]) {
+    body[0..8].copy_from_slice(MAGIC);
+    body[8..12].copy_from_slice(&LAYOUT_VERSION.to_le_bytes());
+    body[12..16].copy_from_slice(&(SIZE as u32).to_le_bytes());
+}
+
+/// Lay `snapshot` out as the file's bytes, sequence left zero.
+fn encode(snapshot: &FrameSnapshot<'_>, flags: u8) -> [u8; SIZE] {
+    let mut body = [0u8; SIZE];
+    put_header(&mut body);
+    let written_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0);
+    body[24..32].copy_from_slice(&written_at_ms.to_le_bytes());
+    body[32..40].copy_from_slice(&(snapshot.uptime.as_millis() as u64).to_le_bytes());
+    body[40..48].copy_from_slice(&snapshot.tps.to_le_bytes());
+    body[48..52].copy_from_slice(&snapshot.cpu_percent.unwrap_or(f32::NAN).to_le_bytes());
+    body[52..56].copy_from_slice(&snapshot.mem_percent.unwrap_or(f32::NAN).to_le_bytes());
+    body[56] = snapshot.mood as u8;
+    body[57] = flags;
+    let rows = snapshot.frame.len().min(usize::from(u16::MAX)) as u16;
+    body[58..60].copy_from_slice(&rows.to_le_bytes());
+    let frame = snapshot.frame.join("\n");
+    for (len_at, slot, text) in [
+        (60, MODULE_SLOT, snapshot.active_module),
+        (62, STATUS_SLOT, snapshot.status_line),
+        (64, FRAME_SLOT, frame.as_str()),
+    ] {
+        let text = truncate(text, slot.1);
+        body[slot.0..slot.0 + text.len()].copy_from_slice(text.as_bytes());
+        body[len_at..len_at + 2].copy_from_slice(&(text.len() as u16).to_le_bytes());
+    }
+    body
+}
+
+/// The longest prefix of `text` within `max` bytes that ends on a char
+/// boundary.
+fn truncate(text: &str, max: usize) -> &str {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    fn u16_at(body: &[u8], at: usize) -> usize {
+        usize::from(u16::from_le_bytes([body[at], body[at + 1]]))
+    }
+
+    fn text(body: &[u8], len_at: usize, slot: (usize, usize)) -> &str {
+        std::str::from_utf8(&body[slot.0..slot.0 + u16_at(body, len_at)]).unwrap()
+    }
+
+    #[test]
+    fn writes_the_documented_layout_and_clears_running_on_drop() {
+        let tmp = TestDir::new("frame");
+        let path = tmp.join("frame.shm");
+        let config = ExportConfig {
+            enabled: true,
+            path: Some(path.clone()),
+            interval_ms: 100,
+        };
+        let frame = vec![" o o ".to_string(), "  ∆  ".to_string()];
+        let long_status = "é".repeat(200);
+        let mut export = FrameExport::open(&config, None).unwrap();
+        let now = Instant::now();
+        assert!(export.due(now));
+        export
+            .write(
+                &FrameSnapshot {
+                    uptime: Duration::from_secs(3),
+                    tps: 10.0,
+                    cpu_percent: Some(12.5),
+                    mem_percent: None,
+                    mood: Mood::Angry,
+                    frame: &frame,
+                    active_module: "stats",
+                    status_line: &long_status,
+                },
+                now,
+            )
+            .unwrap();
+        assert!(!export.due(now));
+
+        let body = fs::read(&path).unwrap();
+        assert_eq!(body.len(), SIZE);
+        assert_eq!(&body[0..8], MAGIC);
+        assert_eq!(u64::from_le_bytes(body[16..24].try_into().unwrap()), 2);
+        assert_eq!(u64::from_le_bytes(body[32..40].try_into().unwrap()), 3000);
+        assert_eq!(f64::from_le_bytes(body[40..48].try_into().unwrap()), 10.0);
+        assert_eq!(f32::from_le_bytes(body[48..52].try_into().unwrap()), 12.5);
+        assert!(f32::from_le_bytes(body[52..56].try_into().unwrap()).is_nan());
+        assert_eq!(body[56], 2);
+        assert_eq!(body[57], RUNNING);
+        assert_eq!(u16_at(&body, 58), 2);
+        assert_eq!(text(&body, 60, MODULE_SLOT), "stats");
+        assert_eq!(text(&body, 62, STATUS_SLOT), "é".repeat(128));
+        assert_eq!(text(&body, 64, FRAME_SLOT), " o o \n  ∆  ");
+
+        drop(export);
+        let body = fs::read(&path).unwrap();
+        assert_eq!(body[57], 0);
+        assert_eq!(u64::from_le_bytes(body[16..24].try_into().unwrap()), 4);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn default_path_is_per_user_and_per_instance() {
+        let runtime = Some(PathBuf::from("/run/user/1000"));
+        let cache = Some(PathBuf::from("/home/me/.cache/spud"));
+        let one = default_path_in(runtime.clone(), cache.clone(), Some(Path::new("/a"))).unwrap();
+        let two = default_path_in(runtime.clone(), cache.clone(), Some(Path::new("/b"))).unwrap();
+        assert!(one.starts_with("/run/user/1000"));
+        assert_ne!(one, two);
+        assert_eq!(
+            default_path_in(runtime, cache.clone(), Some(Path::new("/a"))),
+            Some(one)
+        );
+        assert_eq!(
+            default_path_in(None, cache, None),
+            Some(PathBuf::from("/home/me/.cache/spud/spud-frame"))
+        );
+        assert_eq!(default_path_in(None, None, None), None);
+    }
+}
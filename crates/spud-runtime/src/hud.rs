@@ -0,0 +1,85 @@
+//! App-level `hud` console command.
+//!
+//! Lives in the app because the layout is computed by the app loop. Sizes
+//! set here override `[hud]` in `spud.toml` until that section changes, and
+//! are saved with the session.
+
+use spud_config::HudConfig;
+use spud_core::command::CommandOutput;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "hud";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "hud [height <rows> | face <cols> | reset]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show or resize the HUD strip";
+
+/// Run a `hud` subcommand. `size` is the override; `configured` is the size
+/// from `spud.toml`.
+pub(crate) fn execute(
+    size: &mut Option<HudConfig>,
+    configured: HudConfig,
+    args: &[&str],
+) -> CommandOutput {
+    let mut next = size.unwrap_or(configured);
+    match args {
+        [] => return describe(*size, configured),
+        ["reset"] => {
+            *size = None;
+            return describe(None, configured);
+        }
+        ["height", value] => match value.parse() {
+            Ok(rows) => next.height = rows,
+            Err(_) => return CommandOutput::Error(vec![format!("invalid height: {value}")]),
+        },
+        ["face", value] => match value.parse() {
+            Ok(cols) => next.face_width = cols,
+            Err(_) => return CommandOutput::Error(vec![format!("invalid width: {value}")]),
+        },
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    }
+    if let Err(err) = next.validate() {
+        return CommandOutput::Error(vec![err.to_string()]);
+    }
+    *size = Some(next);
+    describe(*size, configured)
+}
+
+fn describe(size: Option<HudConfig>, configured: HudConfig) -> CommandOutput {
+    let hud = size.unwrap_or(configured);
+    CommandOutput::KeyValue(vec![
+        ("height".into(), hud.height.to_string()),
+        ("face width".into(), hud.face_width.to_string()),
+        (
+            "source".into(),
+            if size.is_some() {
+                "hud command"
+            } else {
+                "spud.toml"
+            }
+            .to_string(),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resizes_validates_and_resets() {
+        let configured = HudConfig::default();
+        let mut size = None;
+
+        assert!(!execute(&mut size, configured, &["height", "12"]).is_error());
+        assert_eq!(size.map(|hud| hud.height), Some(12));
+        assert_eq!(size.map(|hud| hud.face_width), Some(configured.face_width));
+
+        assert!(execute(&mut size, configured, &["face", "500"]).is_error());
+        assert!(execute(&mut size, configured, &["face", "wide"]).is_error());
+        assert_eq!(size.map(|hud| hud.face_width), Some(configured.face_width));
+
+        execute(&mut size, configured, &["reset"]);
+        assert_eq!(size, None);
+    }
+}
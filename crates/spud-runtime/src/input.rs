@@ -0,0 +1,60 @@
+//! Terminal input normalization.
+//!
+//! crossterm reports input differently across platforms. On Windows every
+//! key arrives twice, as a press and a release, and dragging the window
+//! edge floods the queue with resizes to the same size. The filter drops
+//! releases and repeated resizes so the rest of the app sees the same
+//! stream everywhere. Key repeats are kept: holding a key should repeat it.
+
+use crossterm::event::{Event as CEvent, KeyEventKind};
+
+/// Drops platform-specific noise from the crossterm event stream.
+#[derive(Debug, Default)]
+pub(crate) struct InputFilter {
+    last_size: Option<(u16, u16)>,
+}
+
+impl InputFilter {
+    /// Return `event` if the app should handle it.
+    pub(crate) fn accept(&mut self, event: CEvent) -> Option<CEvent> {
+        match event {
+            CEvent::Key(key) if key.kind == KeyEventKind::Release => None,
+            CEvent::Resize(cols, rows) => {
+                if self.last_size.replace((cols, rows)) == Some((cols, rows)) {
+                    None
+                } else {
+                    Some(event)
+                }
+            }
+            other => Some(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyEventState, KeyModifiers};
+
+    fn key(kind: KeyEventKind) -> CEvent {
+        CEvent::Key(KeyEvent {
+            code: KeyCode::Char('`'),
+            modifiers: KeyModifiers::NONE,
+            kind,
+            state: KeyEventState::NONE,
+        })
+    }
+
+    #[test]
+    fn drops_key_releases_and_duplicate_resizes() {
+        let mut filter = InputFilter::default();
+        assert!(filter.accept(key(KeyEventKind::Press)).is_some());
+        assert!(filter.accept(key(KeyEventKind::Repeat)).is_some());
+        assert!(filter.accept(key(KeyEventKind::Release)).is_none());
+
+        assert!(filter.accept(CEvent::Resize(80, 24)).is_some());
+        assert!(filter.accept(CEvent::Resize(80, 24)).is_none());
+        assert!(filter.accept(CEvent::Resize(100, 30)).is_some());
+        assert!(filter.accept(CEvent::Resize(80, 24)).is_some());
+    }
+}
@@ -0,0 +1,185 @@
+//! One running SPUD per state directory.
+//!
+//! Two instances sharing a state directory would write the same session,
+//! history, and log files. The first one to start holds an OS lock on
+//! [`LOCK_FILE`] for as long as it runs (released by the OS even after a
+//! crash) and describes itself in [`INFO_FILE`]: its pid and control
+//! socket. A later start finds the lock taken and, depending on
+//! [`IfRunning`], gives up, attaches to the running instance, or asks it to
+//! quit and takes over. Set `SPUD_STATE_DIR` (and `SPUD_LOG_DIR`) to run
+//! instances side by side.
+//!
+//! The lock is keyed on the base state directory, not a workspace's: every
+//! workspace writes the same log files, and a running instance can switch
+//! workspaces, so one instance runs per base state directory whichever
+//! workspace it starts in.
+
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::control::{self, ControlClient};
+
+/// Held locked while an instance runs.
+pub(crate) const LOCK_FILE: &str = "instance.lock";
+/// The running instance's [`Holder`], as JSON. Kept apart from the lock
+/// file because Windows locks block reading too.
+pub(crate) const INFO_FILE: &str = "instance.json";
+/// How long a steal waits for the running instance to let go.
+const STEAL_TIMEOUT: Duration = Duration::from_secs(5);
+const STEAL_POLL: Duration = Duration::from_millis(100);
+
+/// What to do when another instance already runs for the state directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IfRunning {
+    /// Exit with an error naming the running instance.
+    #[default]
+    Refuse,
+    /// Run commands in the running instance instead of starting one.
+    Attach,
+    /// Ask the running instance to quit, then start in its place.
+    Steal,
+}
+
+/// The instance that holds the lock, as it described itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Holder {
+    pub(crate) pid: Option<u32>,
+    /// Control socket; `None` where there is none.
+    pub(crate) control: Option<PathBuf>,
+}
+
+impl Holder {
+    /// `pid 1234` or `unknown pid`, for messages.
+    pub(crate) fn describe(&self) -> String {
+        self.pid
+            .map_or_else(|| "unknown pid".to_string(), |pid| format!("pid {pid}"))
+    }
+}
+
+/// The lock of the running instance; released when dropped.
+#[derive(Debug)]
+pub(crate) struct InstanceLock {
+    _file: File,
+    info: PathBuf,
+}
+
+/// Outcome of [`acquire`].
+#[derive(Debug)]
+pub(crate) enum Acquire {
+    Locked(InstanceLock),
+    /// Another instance holds the lock.
+    Held(Holder),
+}
+
+/// Directory the lock lives in: the base state dir, whatever the
+/// workspace, else the log dir.
+pub(crate) fn lock_dir() -> PathBuf {
+    spud_config::paths::state_dir().unwrap_or_else(spud_config::paths::log_dir)
+}
+
+/// The instance running for `dir`, as it described itself, without
+/// touching the lock. `None` when none is, though an instance that
+/// crashed leaves its description behind.
+pub(crate) fn running(dir: &Path) -> Option<Holder> {
+    let raw = fs::read_to_string(dir.join(INFO_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// Take the instance lock in `dir`, or report who holds it.
+pub(crate) fn acquire(dir: &Path) -> Result<Acquire> {
+    fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let path = dir.join(LOCK_FILE);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let info = dir.join(INFO_FILE);
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            return Ok(Acquire::Held(running(dir).unwrap_or_default()));
+        }
+        Err(TryLockError::Error(err)) => {
+            return Err(err).with_context(|| format!("failed to lock {}", path.display()))
+        }
+    }
+    let holder = Holder {
+        pid: Some(std::process::id()),
+        control: control::supported().then(|| dir.join(control::SOCKET_FILE)),
+    };
+    let json = serde_json::to_string(&holder).context("failed to serialize instance info")?;
+    fs::write(&info, json + "\n").with_context(|| format!("failed to write {}", info.display()))?;
+    Ok(Acquire::Locked(InstanceLock { _file: file, info }))
+}
+
+impl InstanceLock {
+    /// Control socket this instance should listen on.
+    pub(crate) fn control_path(&self) -> Option<PathBuf> {
+        control::supported().then(|| self.info.with_file_name(control::SOCKET_FILE))
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.info);
+    }
+}
+
+/// Ask `holder` to quit over its control socket and take the lock once it
+/// has.
+pub(crate) fn steal(dir: &Path, holder: &Holder) -> Result<InstanceLock> {
+    let Some(socket) = &holder.control else {
+        bail!(
+            "SPUD is already running ({}) and has no control socket to ask it to quit",
+            holder.describe()
+        );
+    };
+    ControlClient::connect(socket)?.quit()?;
+    let deadline = Instant::now() + STEAL_TIMEOUT;
+    loop {
+        if let Acquire::Locked(lock) = acquire(dir)? {
+            return Ok(lock);
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "SPUD ({}) did not quit within {}s",
+                holder.describe(),
+                STEAL_TIMEOUT.as_secs()
+            );
+        }
+        thread::sleep(STEAL_POLL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn second_acquire_sees_the_holder_until_the_lock_drops() {
+        let dir = TestDir::new("instance");
+        let Acquire::Locked(lock) = acquire(&dir).unwrap() else {
+            panic!("first acquire should lock");
+        };
+        let Acquire::Held(holder) = acquire(&dir).unwrap() else {
+            panic!("second acquire should find the lock held");
+        };
+        assert_eq!(holder.pid, Some(std::process::id()));
+        assert_eq!(holder.control, lock.control_path());
+        assert_eq!(holder.describe(), format!("pid {}", std::process::id()));
+        assert_eq!(running(&dir), Some(holder));
+
+        drop(lock);
+        assert!(!dir.join(INFO_FILE).exists());
+        assert_eq!(running(&dir), None);
+        assert!(matches!(acquire(&dir).unwrap(), Acquire::Locked(_)));
+    }
+}
@@ -0,0 +1,62 @@
+//! The app's global keys.
+//!
+//! Declared as [`KeyBinding`]s so the keymap can keep modules off them and
+//! list them in the HUD and the F1 overlay. Keys the console handles while
+//! it is open depend on that mode, so they stay hand-listed.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use spud_core::i18n::tr;
+use spud_core::keymap::{ActionId, KeyBinding, KeyChord};
+
+pub(crate) const NEXT_MODULE: ActionId = "next_module";
+pub(crate) const CONSOLE: ActionId = "console";
+pub(crate) const HELP: ActionId = "help";
+pub(crate) const QUIT: ActionId = "quit";
+pub(crate) const BACK: ActionId = "back";
+pub(crate) const EVENTS: ActionId = "events";
+pub(crate) const RESTART_MODULE: ActionId = "restart_module";
+
+/// Keys that work whatever module is active, in the order they are listed.
+pub(crate) fn global() -> Vec<KeyBinding> {
+    let bind = |chord, action, description| KeyBinding::new(chord, action, tr(description));
+    vec![
+        bind(KeyChord::key(KeyCode::Tab), NEXT_MODULE, "next module"),
+        bind(KeyChord::char('`'), CONSOLE, "toggle console"),
+        bind(KeyChord::char('~'), CONSOLE, "toggle console"),
+        bind(KeyChord::key(KeyCode::F(1)), HELP, "toggle help"),
+        bind(KeyChord::char('q'), QUIT, "quit"),
+        bind(KeyChord::key(KeyCode::Backspace), BACK, "previous module"),
+        bind(
+            KeyChord::key(KeyCode::F(12)),
+            EVENTS,
+            "toggle recent events",
+        ),
+    ]
+}
+
+/// Keys in place of a crashed module's own.
+pub(crate) fn crashed() -> Vec<KeyBinding> {
+    vec![KeyBinding::new(
+        KeyChord::char('r'),
+        RESTART_MODULE,
+        tr("restart module"),
+    )]
+}
+
+/// `(key, action)` for keys the open console handles, listed in the F1
+/// help overlay after the global keys.
+pub(crate) const CONSOLE_KEYS: &[(&str, &str)] = &[
+    ("Enter", "run console command"),
+    ("Right", "accept console suggestion"),
+    ("Ctrl+R", "search console history"),
+    ("PgUp/PgDn", "scroll console"),
+    ("Shift+Left/Right", "scroll console tables sideways"),
+    ("Esc", "close console or overlay"),
+];
+
+/// Whether `key` is a Ctrl shortcut rather than typed text. Windows
+/// reports AltGr as Ctrl+Alt, and AltGr types characters on many
+/// layouts (`@`, `€`, `ł`), so those are text.
+pub(crate) fn is_ctrl(key: &KeyEvent) -> bool {
+    key.modifiers.contains(KeyModifiers::CONTROL) && !key.modifiers.contains(KeyModifiers::ALT)
+}
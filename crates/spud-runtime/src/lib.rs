@@ -0,0 +1,66 @@
+//! The SPUD shell as a library.
+//!
+//! Owns the main loop (sync logs → update animation → render → poll input →
+//! publish events → drain → broadcast → check quit), the app-level console
+//! commands, plugin hosting, alerts, and settings reload. Binaries pick the
+//! modules and plugin roots and hand over with [`RuntimeBuilder::run`]:
+//!
+//! ```no_run
+//! # use spud_core::module::Module;
+//! # struct MyModule;
+//! # impl Module for MyModule {
+//! #     fn id(&self) -> &'static str { "mine" }
+//! #     fn title(&self) -> &'static str { "Mine" }
+//! #     fn as_any(&self) -> &dyn std::any::Any { self }
+//! # }
+//! spud_runtime::RuntimeBuilder::new()
+//!     .with_module(|| MyModule)
+//!     .with_plugins(["./plugins"])
+//!     .run()
+//!     .unwrap();
+//! ```
+//!
+//! The `spud` binary (spud-app) is a thin wrapper that maps its command
+//! line onto the builder.
+
+mod alerts;
+mod app;
+mod attach;
+mod builder;
+mod command_stats;
+mod compose;
+mod config;
+mod console;
+mod control;
+mod debug;
+mod doctor;
+mod dump;
+mod events;
+mod frame_export;
+mod hud;
+mod input;
+mod instance;
+mod keys;
+mod mdns;
+mod module;
+mod plugin_dev;
+mod plugin_module;
+mod plugins;
+mod profile;
+mod record;
+mod redirect;
+mod screen;
+mod session;
+mod signals;
+mod splash;
+mod state_sync;
+mod status_page;
+mod sys;
+mod terminal;
+mod transcript;
+mod undo;
+pub mod version;
+mod workspace;
+
+pub use builder::RuntimeBuilder;
+pub use instance::IfRunning;
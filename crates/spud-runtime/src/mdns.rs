@@ -0,0 +1,150 @@
+//! `[mdns]`: advertise the status page so tools on the LAN can find
+//! running instances.
+//!
+//! Registers a [`SERVICE_TYPE`] service named after the instance, pointing
+//! at the `[http]` status page, with TXT records for the SPUD version and
+//! the JSON path. Browsers see it appear when the page starts and go away
+//! when it stops.
+//!
+//! Needs the `mdns` feature; without it [`Advertiser::start`] fails and the
+//! app logs why.
+#![cfg_attr(not(feature = "mdns"), allow(dead_code))]
+
+use std::net::{IpAddr, SocketAddr};
+
+use anyhow::{bail, Result};
+use spud_config::MdnsConfig;
+
+/// DNS-SD service type of the status page.
+pub(crate) const SERVICE_TYPE: &str = "_spud._tcp.local.";
+/// Path of the machine-readable status, sent as the `path` TXT record.
+const STATUS_PATH: &str = "/status.json";
+
+/// Address to advertise for a server listening on `bind`: `None` for a
+/// wildcard bind, where every interface address is advertised.
+///
+/// Fails for loopback, which nothing else on the LAN could reach.
+pub(crate) fn advertised_ip(bind: SocketAddr) -> Result<Option<IpAddr>> {
+    let ip = bind.ip();
+    if ip.is_loopback() {
+        bail!("the status page only listens on {bind}; set [http] bind to a LAN address");
+    }
+    Ok((!ip.is_unspecified()).then_some(ip))
+}
+
+/// Instance name: `mdns.name`, else `host`.
+pub(crate) fn instance_name(config: &MdnsConfig, host: &str) -> String {
+    config.name.clone().unwrap_or_else(|| host.to_string())
+}
+
+/// TXT records sent with the service.
+fn txt_records() -> [(&'static str, &'static str); 2] {
+    [
+        ("version", env!("CARGO_PKG_VERSION")),
+        ("path", STATUS_PATH),
+    ]
+}
+
+/// A registered service; unregistered on drop.
+pub(crate) struct Advertiser {
+    name: String,
+    #[cfg(feature = "mdns")]
+    daemon: mdns_sd::ServiceDaemon,
+    #[cfg(feature = "mdns")]
+    fullname: String,
+}
+
+impl Advertiser {
+    /// Instance name being advertised.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(not(feature = "mdns"))]
+impl Advertiser {
+    pub(crate) fn start(_config: &MdnsConfig, _addr: SocketAddr) -> Result<Self> {
+        bail!("SPUD was built without the `mdns` feature")
+    }
+}
+
+#[cfg(feature = "mdns")]
+impl Advertiser {
+    /// Advertise the status page listening on `addr`.
+    pub(crate) fn start(config: &MdnsConfig, addr: SocketAddr) -> Result<Self> {
+        use anyhow::Context;
+
+        let ip = advertised_ip(addr)?;
+        let host = sysinfo::System::host_name().unwrap_or_else(|| "spud".to_string());
+        let name = instance_name(config, &host);
+        let host_name = format!("{host}.local.");
+        let info = match ip {
+            Some(ip) => mdns_sd::ServiceInfo::new(
+                SERVICE_TYPE,
+                &name,
+                &host_name,
+                ip,
+                addr.port(),
+                &txt_records()[..],
+            ),
+            None => mdns_sd::ServiceInfo::new(
+                SERVICE_TYPE,
+                &name,
+                &host_name,
+                (),
+                addr.port(),
+                &txt_records()[..],
+            )
+            .map(mdns_sd::ServiceInfo::enable_addr_auto),
+        }
+        .context("invalid mDNS service")?;
+        let fullname = info.get_fullname().to_string();
+        let daemon = mdns_sd::ServiceDaemon::new().context("failed to start mDNS")?;
+        daemon
+            .register(info)
+            .context("failed to register the mDNS service")?;
+        Ok(Self {
+            name,
+            daemon,
+            fullname,
+        })
+    }
+}
+
+#[cfg(feature = "mdns")]
+impl Drop for Advertiser {
+    /// Send the goodbye packets and stop the daemon thread, without
+    /// waiting for either.
+    fn drop(&mut self) {
+        let _ = self.daemon.unregister(&self.fullname);
+        let _ = self.daemon.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advertises_lan_addresses_only() {
+        assert!(advertised_ip("127.0.0.1:7878".parse().unwrap()).is_err());
+        assert!(advertised_ip("[::1]:7878".parse().unwrap()).is_err());
+        assert_eq!(
+            advertised_ip("0.0.0.0:7878".parse().unwrap()).unwrap(),
+            None
+        );
+        assert_eq!(
+            advertised_ip("192.168.1.5:7878".parse().unwrap()).unwrap(),
+            Some("192.168.1.5".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn names_default_to_the_host() {
+        let mut config = MdnsConfig::default();
+        assert_eq!(instance_name(&config, "den"), "den");
+        config.name = Some("kiosk".into());
+        assert_eq!(instance_name(&config, "den"), "kiosk");
+        assert_eq!(txt_records()[1], ("path", "/status.json"));
+    }
+}
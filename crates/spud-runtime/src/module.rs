@@ -0,0 +1,160 @@
+//! App-level `module` console command.
+//!
+//! Lives in the app because a restart builds the module afresh from the
+//! factory it was registered with, which only the app keeps. A module that
+//! panicked stays on screen as an error panel until it is restarted here or
+//! with `r`.
+
+use spud_config::AppConfig;
+use spud_core::{command::CommandOutput, event::Event, registry::ModuleRegistry};
+
+use crate::builder::ModuleFactory;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "module";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "module restart <id>";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str =
+    "Replace a module with a fresh instance, e.g. after it crashed";
+
+/// Run a `module` subcommand. Returns the output and lifecycle events for
+/// the app to publish.
+pub(crate) fn execute(
+    registry: &mut ModuleRegistry,
+    factories: &[ModuleFactory],
+    config: &AppConfig,
+    args: &[&str],
+) -> (CommandOutput, Vec<Event>) {
+    match args {
+        ["restart", id] => match restart(registry, factories, config, id) {
+            Ok(events) => (
+                CommandOutput::Lines(vec![format!("module {id} restarted")]),
+                events,
+            ),
+            Err(err) => (CommandOutput::Error(vec![err]), Vec::new()),
+        },
+        _ => (
+            CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+            Vec::new(),
+        ),
+    }
+}
+
+/// Swap module `id` for a new, configured instance from the factory that
+/// built it. The new instance starts healthy unless configuring it panics,
+/// which leaves the old instance in place.
+pub(crate) fn restart(
+    registry: &mut ModuleRegistry,
+    factories: &[ModuleFactory],
+    config: &AppConfig,
+    id: &str,
+) -> Result<Vec<Event>, String> {
+    if registry.get(id).is_none() {
+        return Err(format!("unknown module: {id}"));
+    }
+    let Some(factory) = factories.iter().find(|factory| factory.id() == Some(id)) else {
+        return Err(format!("module {id} cannot be restarted"));
+    };
+    let mut module = factory.build();
+    spud_core::module::isolate(|| module.configure(config))
+        .map_err(|reason| format!("module {id} panicked while configuring: {reason}"))?;
+    let (_, events) = registry
+        .replace(id, module)
+        .map_err(|err| err.to_string())?;
+    tracing::info!(module = id, "module {id} restarted");
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_core::module::Module;
+
+    struct Counter(u32);
+
+    impl Module for Counter {
+        fn id(&self) -> &'static str {
+            "counter"
+        }
+        fn title(&self) -> &'static str {
+            "Counter"
+        }
+        fn handle_event(&mut self, _ev: &Event) {
+            self.0 += 1;
+            if self.0 > 1 {
+                panic!("counted too far");
+            }
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn restart_replaces_a_crashed_module() {
+        let factories = vec![ModuleFactory::new(|| Box::new(Counter(0)))];
+        let mut registry = ModuleRegistry::new();
+        registry.register(factories[0].build()).unwrap();
+        registry.broadcast(&Event::Quit);
+        registry.broadcast(&Event::Quit);
+        assert_eq!(registry.crash("counter"), Some("counted too far"));
+
+        let config = AppConfig::default();
+        let (output, events) = execute(&mut registry, &factories, &config, &["restart", "counter"]);
+        assert_eq!(output.to_lines(), vec!["module counter restarted"]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(registry.crash("counter"), None);
+
+        let (output, _) = execute(&mut registry, &factories, &config, &["restart", "nope"]);
+        assert!(output.is_error());
+        assert!(execute(&mut registry, &factories, &config, &[])
+            .0
+            .is_error());
+    }
+
+    struct BadConfig;
+
+    impl Module for BadConfig {
+        fn id(&self) -> &'static str {
+            "bad"
+        }
+        fn title(&self) -> &'static str {
+            "Bad"
+        }
+        fn configure(&mut self, _config: &AppConfig) {
+            panic!("bad config");
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn restart_builds_only_the_module_asked_for_and_isolates_configure() {
+        let built = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counted = built.clone();
+        let factories = vec![
+            ModuleFactory::new(move || {
+                counted.set(counted.get() + 1);
+                Box::new(Counter(0))
+            }),
+            ModuleFactory::new(|| Box::new(BadConfig)),
+        ];
+        let mut registry = ModuleRegistry::new();
+        for factory in &factories {
+            registry.register(factory.build()).unwrap();
+        }
+        assert_eq!(built.get(), 1);
+
+        let config = AppConfig::default();
+        let (output, events) = execute(&mut registry, &factories, &config, &["restart", "bad"]);
+        assert_eq!(
+            output.to_lines(),
+            vec!["module bad panicked while configuring: bad config"]
+        );
+        assert!(events.is_empty());
+        assert_eq!(built.get(), 1);
+        assert!(registry.get("bad").is_some());
+    }
+}
@@ -0,0 +1,120 @@
+//! File watching for plugins in dev mode (`plugins dev <plugin_id>`).
+//!
+//! Each watched plugin's manifest and entrypoint are polled like
+//! `spud.toml` (see [`crate::config`]), and a plugin is reported once its
+//! files have changed and then stayed put for a check, so a build still
+//! writing the binary is not started half-written.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::config::{stamp, Stamp};
+
+/// How often watched plugin files are checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tracks the files of plugins in dev mode.
+#[derive(Default)]
+pub(crate) struct DevWatcher {
+    plugins: BTreeMap<String, Watched>,
+    last_check: Option<Instant>,
+}
+
+struct Watched {
+    files: Vec<PathBuf>,
+    stamps: Vec<Stamp>,
+    /// Changed at the last check; reported once the next finds it settled.
+    changed: bool,
+}
+
+impl DevWatcher {
+    /// Watch `files` for `plugin_id`, replacing what was watched for it,
+    /// and take their current state as unchanged.
+    pub(crate) fn watch(&mut self, plugin_id: &str, files: Vec<PathBuf>) {
+        let stamps = files.iter().map(|file| stamp(file)).collect();
+        self.plugins.insert(
+            plugin_id.to_string(),
+            Watched {
+                files,
+                stamps,
+                changed: false,
+            },
+        );
+    }
+
+    /// Stop watching `plugin_id`. Returns whether it was watched.
+    pub(crate) fn unwatch(&mut self, plugin_id: &str) -> bool {
+        self.plugins.remove(plugin_id).is_some()
+    }
+
+    /// Watched plugins in ID order.
+    pub(crate) fn plugin_ids(&self) -> Vec<String> {
+        self.plugins.keys().cloned().collect()
+    }
+
+    /// Files watched for `plugin_id`.
+    pub(crate) fn files(&self, plugin_id: &str) -> Option<&[PathBuf]> {
+        self.plugins
+            .get(plugin_id)
+            .map(|watched| watched.files.as_slice())
+    }
+
+    /// Plugins whose files changed and have since settled, checked at most
+    /// once per [`CHECK_INTERVAL`].
+    pub(crate) fn poll(&mut self, now: Instant) -> Vec<String> {
+        if self
+            .last_check
+            .is_some_and(|last| now.duration_since(last) < CHECK_INTERVAL)
+        {
+            return Vec::new();
+        }
+        self.last_check = Some(now);
+        let mut settled = Vec::new();
+        for (plugin_id, watched) in &mut self.plugins {
+            let current: Vec<Stamp> = watched.files.iter().map(|file| stamp(file)).collect();
+            if current != watched.stamps {
+                watched.stamps = current;
+                watched.changed = true;
+            } else if watched.changed {
+                watched.changed = false;
+                settled.push(plugin_id.clone());
+            }
+        }
+        settled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn reports_a_change_once_it_settles() {
+        let dir = TestDir::new("plugin-dev");
+        let entrypoint = dir.join("plugin.sh");
+        std::fs::write(&entrypoint, "echo one\n").unwrap();
+
+        let start = Instant::now();
+        let mut watcher = DevWatcher::default();
+        watcher.watch("spud.dev", vec![entrypoint.clone()]);
+        assert_eq!(watcher.plugin_ids(), ["spud.dev"]);
+        assert!(watcher.poll(start).is_empty());
+
+        std::fs::write(&entrypoint, "echo one two\n").unwrap();
+        assert!(watcher.poll(start).is_empty(), "rate limited");
+        let later = start + CHECK_INTERVAL;
+        assert!(watcher.poll(later).is_empty(), "still settling");
+        assert_eq!(watcher.poll(later + CHECK_INTERVAL), ["spud.dev"]);
+        assert!(watcher.poll(later + CHECK_INTERVAL * 2).is_empty());
+
+        // Removing the file is a change too
+        std::fs::remove_file(&entrypoint).unwrap();
+        assert!(watcher.poll(later + CHECK_INTERVAL * 3).is_empty());
+        assert_eq!(watcher.poll(later + CHECK_INTERVAL * 4), ["spud.dev"]);
+
+        assert!(watcher.unwatch("spud.dev"));
+        assert!(!watcher.unwatch("spud.dev"));
+    }
+}
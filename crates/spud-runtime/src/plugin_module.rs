@@ -0,0 +1,195 @@
+//! Modules declared by plugins.
+//!
+//! A plugin whose manifest has a `[module]` section gets a [`PluginModule`]
+//! in the registry, so it shows up in the Tab cycle and `modules` like any
+//! other module. The plugin fills the hero and HUD with
+//! `spud.module.update`, and is told with `spud.module.focus` when its
+//! module is switched to or away from. The module outlives plugin restarts
+//! and is removed when plugins are reloaded.
+
+use std::any::Any;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+use spud_config::PluginModuleSpec;
+use spud_core::module::{HeroRenderer, HudContribution, HudLine, HudStyle, Module};
+use spud_remote::protocol::ModuleUpdateParams;
+
+/// Source of hero content versions, shared by every plugin module so a
+/// restarted module never repeats the version of the one it replaced.
+static HERO_VERSION: AtomicU64 = AtomicU64::new(0);
+
+/// Registry module standing in for a plugin's `[module]`.
+pub(crate) struct PluginModule {
+    id: &'static str,
+    title: &'static str,
+    plugin_id: String,
+    view: Mutex<View>,
+}
+
+/// What the plugin last sent.
+struct View {
+    hero: Vec<String>,
+    hud: Vec<String>,
+    /// Changes with `hero`; see [`HeroRenderer::content_version`].
+    hero_version: u64,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            hero: Vec::new(),
+            hud: Vec::new(),
+            hero_version: HERO_VERSION.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl PluginModule {
+    pub(crate) fn new(plugin_id: &str, spec: &PluginModuleSpec) -> Self {
+        // `Module` hands out `&'static str`; one id and title leak per
+        // plugin module registered, which happens once per discovery.
+        Self {
+            id: Box::leak(spec.id.clone().into_boxed_str()),
+            title: Box::leak(spec.title.clone().into_boxed_str()),
+            plugin_id: plugin_id.to_string(),
+            view: Mutex::new(View::default()),
+        }
+    }
+
+    /// Id of the plugin that declared this module.
+    pub(crate) fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    /// Replace the hero and/or HUD lines; a field left out keeps its lines.
+    pub(crate) fn update(&self, params: ModuleUpdateParams) {
+        let Ok(mut view) = self.view.lock() else {
+            return;
+        };
+        if let Some(hero) = params.hero {
+            view.hero = hero;
+            view.hero_version = HERO_VERSION.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(hud) = params.hud {
+            view.hud = hud;
+        }
+    }
+}
+
+impl Module for PluginModule {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn title(&self) -> &'static str {
+        self.title
+    }
+
+    fn hud(&self) -> HudContribution {
+        let left_lines = self
+            .view
+            .lock()
+            .map(|view| {
+                view.hud
+                    .iter()
+                    .map(|line| HudLine::from(line.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        HudContribution {
+            left_lines,
+            right_lines: vec![HudLine::key_value(
+                "PLUGIN:",
+                self.plugin_id.clone(),
+                HudStyle::Dim,
+            )],
+        }
+    }
+
+    fn as_hero_renderer(&self) -> Option<&dyn HeroRenderer> {
+        Some(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl HeroRenderer for PluginModule {
+    fn render_hero(&self, f: &mut Frame, area: Rect) {
+        let lines: Vec<Line> = match self.view.lock() {
+            Ok(view) if !view.hero.is_empty() => view
+                .hero
+                .iter()
+                .map(|line| Line::from(line.clone()))
+                .collect(),
+            _ => vec![Line::styled(
+                format!("waiting for {}", self.plugin_id),
+                Style::default().add_modifier(Modifier::DIM),
+            )],
+        };
+        let p = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::default().borders(Borders::ALL).title(self.title));
+        f.render_widget(p, area);
+    }
+
+    fn content_version(&self) -> Option<u64> {
+        self.view.lock().ok().map(|view| view.hero_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module() -> PluginModule {
+        PluginModule::new(
+            "acme.weather",
+            &PluginModuleSpec {
+                id: "weather".into(),
+                title: "Weather".into(),
+            },
+        )
+    }
+
+    #[test]
+    fn updates_replace_only_the_fields_sent() {
+        let module = module();
+        assert_eq!(module.id(), "weather");
+        assert_eq!(module.title(), "Weather");
+        assert!(module.hud().left_lines.is_empty());
+        let waiting = module.content_version();
+        assert_ne!(waiting, self::module().content_version());
+
+        module.update(ModuleUpdateParams {
+            hero: Some(vec!["sunny".into()]),
+            hud: Some(vec!["21C".into()]),
+        });
+        let sunny = module.content_version();
+        assert_ne!(sunny, waiting);
+        module.update(ModuleUpdateParams {
+            hero: None,
+            hud: Some(vec!["22C".into()]),
+        });
+        assert_eq!(module.content_version(), sunny);
+        module.update(ModuleUpdateParams {
+            hero: Some(vec!["rain".into()]),
+            hud: Some(vec!["21C".into()]),
+        });
+        assert_ne!(module.content_version(), sunny);
+        let view = module.view.lock().unwrap();
+        assert_eq!(view.hero, ["rain"]);
+        assert_eq!(view.hud, ["21C"]);
+        drop(view);
+        assert_eq!(module.hud().left_lines[0].plain_text(), "21C");
+    }
+}
@@ -0,0 +1,319 @@
+//! App-level `plugins` console command.
+//!
+//! Lives in the app rather than `spud-core` because it needs the plugin
+//! runtime, which core commands cannot see. Plugins cannot invoke it through
+//! `spud.host.invoke_command`, which only reaches the core registry.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use spud_core::command::{Column, CommandOutput, Table};
+use spud_remote::audit::{AuditDecision, AuditRecord};
+use spud_remote::metrics::PluginStats;
+use spud_remote::pump::PluginPump;
+use spud_remote::runtime::{HostBridge, PluginRuntime, PluginSessionSnapshot, RuntimeError};
+
+use crate::plugin_dev::DevWatcher;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "plugins";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "plugins list | plugins stats [plugin_id] | plugins audit <plugin_id> | plugins start|stop|restart <plugin_id> | plugins dev [plugin_id [off]]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str =
+    "Show plugin sessions, counters, or audit log; start, stop, or restart a plugin; restart it on file changes";
+/// Subcommands that change a plugin's process; see [`control`].
+pub(crate) const CONTROL: [&str; 3] = ["start", "stop", "restart"];
+/// Subcommand that turns dev mode on and off; see [`dev`].
+pub(crate) const DEV: &str = "dev";
+/// Audit records shown by `plugins audit`.
+const AUDIT_LINES: usize = 20;
+
+/// Whether the subcommand in `args` is destructive: stopping a plugin
+/// drops whatever it was doing.
+pub(crate) fn destructive(args: &[&str]) -> bool {
+    matches!(args, ["stop", ..])
+}
+
+/// Run a `plugins` subcommand.
+pub(crate) fn execute(runtime: Option<&PluginRuntime>, args: &[&str]) -> CommandOutput {
+    let Some(runtime) = runtime else {
+        return CommandOutput::Lines(vec![
+            "plugin runtime disabled (set SPUD_PLUGIN_DIRS)".to_string()
+        ]);
+    };
+
+    match args {
+        ["list"] => list_output(&runtime.sessions()),
+        ["stats"] => stats_output(&runtime.stats()),
+        ["stats", plugin_id] => match runtime.plugin_stats(plugin_id) {
+            Some(stats) => stats_output(&[stats]),
+            None => CommandOutput::Error(vec![format!("unknown plugin: {plugin_id}")]),
+        },
+        ["audit", plugin_id] => {
+            if runtime.plugin_stats(plugin_id).is_none() {
+                return CommandOutput::Error(vec![format!("unknown plugin: {plugin_id}")]);
+            }
+            audit_output(
+                plugin_id,
+                &runtime.audit_log().recent_for(plugin_id, AUDIT_LINES),
+            )
+        }
+        _ => CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    }
+}
+
+/// Run `plugins start|stop|restart <plugin_id>` through the pump. Starts
+/// return before the handshake; its outcome is logged from the pump's
+/// reports.
+pub(crate) fn control(
+    pump: &PluginPump,
+    host: &mut dyn HostBridge,
+    action: &str,
+    plugin_id: &str,
+) -> CommandOutput {
+    let result = match action {
+        "stop" => pump
+            .with_runtime(host, |runtime| {
+                match runtime.plugin_stats(plugin_id).map(|stats| stats.running) {
+                    None => Err(RuntimeError::UnknownPlugin(plugin_id.to_string())),
+                    Some(false) => Err(RuntimeError::NotRunning(plugin_id.to_string())),
+                    Some(true) => runtime.shutdown_plugin(plugin_id),
+                }
+            })
+            .map(|()| format!("stopped {plugin_id}")),
+        "start" => pump
+            .start_plugin(host, plugin_id, false)
+            .map(|()| format!("starting {plugin_id}")),
+        "restart" => pump
+            .start_plugin(host, plugin_id, true)
+            .map(|()| format!("restarting {plugin_id}")),
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    };
+    match result {
+        Ok(line) => CommandOutput::Lines(vec![line]),
+        Err(err) => CommandOutput::Error(vec![err.to_string()]),
+    }
+}
+
+/// Run `plugins dev [plugin_id [off]]`. Dev mode restarts a plugin when
+/// its manifest or entrypoint changes (see [`restart_changed`]) and logs
+/// what it writes to stderr at debug level. Turning it on restarts the
+/// plugin, so its stderr is piped from the start.
+pub(crate) fn dev(
+    pump: &PluginPump,
+    host: &mut dyn HostBridge,
+    watcher: &mut DevWatcher,
+    args: &[&str],
+) -> CommandOutput {
+    match args {
+        [] => {
+            let plugin_ids = watcher.plugin_ids();
+            if plugin_ids.is_empty() {
+                return CommandOutput::Lines(vec!["no plugins in dev mode".to_string()]);
+            }
+            let mut table = Table::new(vec![Column::left("PLUGIN"), Column::left("WATCHING")]);
+            for plugin_id in plugin_ids {
+                let files = watcher.files(&plugin_id).unwrap_or_default();
+                for (i, file) in files.iter().enumerate() {
+                    let plugin = if i == 0 { plugin_id.as_str() } else { "" };
+                    table.push_row(vec![plugin.to_string(), file.display().to_string()]);
+                }
+            }
+            CommandOutput::Table(table)
+        }
+        [plugin_id] => {
+            let result = pump
+                .with_runtime(host, |runtime| {
+                    runtime.set_dev_mode(plugin_id, true)?;
+                    Ok::<_, RuntimeError>(dev_target(runtime, plugin_id))
+                })
+                .and_then(|(files, running)| {
+                    pump.start_plugin(host, plugin_id, running)?;
+                    Ok((files, running))
+                });
+            match result {
+                Ok((files, running)) => {
+                    let count = files.len();
+                    watcher.watch(plugin_id, files);
+                    let verb = if running { "restarting" } else { "starting" };
+                    CommandOutput::Lines(vec![format!(
+                        "dev mode on for {plugin_id}: watching {count} file(s), {verb}"
+                    )])
+                }
+                Err(err) => CommandOutput::Error(vec![err.to_string()]),
+            }
+        }
+        [plugin_id, "off"] => {
+            match pump.with_runtime(host, |runtime| runtime.set_dev_mode(plugin_id, false)) {
+                Ok(()) => {
+                    watcher.unwatch(plugin_id);
+                    CommandOutput::Lines(vec![format!(
+                        "dev mode off for {plugin_id}; stderr stays piped until it restarts"
+                    )])
+                }
+                Err(err) => CommandOutput::Error(vec![err.to_string()]),
+            }
+        }
+        _ => CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    }
+}
+
+/// Restart each dev mode plugin whose files changed since the last check,
+/// with its manifest read again. A manifest that no longer loads is
+/// reported and the running session is left alone.
+pub(crate) fn restart_changed(
+    pump: &PluginPump,
+    host: &mut dyn HostBridge,
+    watcher: &mut DevWatcher,
+    now: Instant,
+) {
+    for plugin_id in watcher.poll(now) {
+        let result = pump
+            .with_runtime(host, |runtime| {
+                runtime.reload_manifest(&plugin_id)?;
+                Ok::<_, RuntimeError>(dev_target(runtime, &plugin_id))
+            })
+            .and_then(|(files, running)| {
+                // The entrypoint may have moved with the manifest
+                watcher.watch(&plugin_id, files);
+                pump.start_plugin(host, &plugin_id, running)
+            });
+        match result {
+            Ok(()) => tracing::info!(plugin_id = %plugin_id, "plugin files changed; restarting"),
+            Err(err) => tracing::warn!(
+                plugin_id = %plugin_id,
+                error = %err,
+                "plugin files changed but it was not restarted"
+            ),
+        }
+    }
+}
+
+/// Files to watch for a dev mode plugin, and whether it is running.
+fn dev_target(runtime: &PluginRuntime, plugin_id: &str) -> (Vec<PathBuf>, bool) {
+    (
+        runtime.plugin_files(plugin_id).unwrap_or_default(),
+        runtime
+            .plugin_stats(plugin_id)
+            .is_some_and(|stats| stats.running),
+    )
+}
+
+fn list_output(sessions: &[PluginSessionSnapshot]) -> CommandOutput {
+    if sessions.is_empty() {
+        return CommandOutput::Lines(vec!["no plugins discovered".to_string()]);
+    }
+
+    let mut table = Table::new(vec![
+        Column::left("PLUGIN"),
+        Column::left("VERSION"),
+        Column::left("STATE"),
+        Column::left("HEALTH"),
+        Column::left("API"),
+    ]);
+    for session in sessions {
+        table.push_row(vec![
+            session.plugin_id.clone(),
+            session.version.clone(),
+            match (session.running, session.dev) {
+                (true, false) => "running",
+                (true, true) => "running (dev)",
+                (false, false) => "stopped",
+                (false, true) => "stopped (dev)",
+            }
+            .to_string(),
+            session
+                .health
+                .map_or_else(|| "-".to_string(), |health| health.to_string()),
+            session
+                .api_version
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+    CommandOutput::Table(table)
+}
+
+fn stats_output(stats: &[PluginStats]) -> CommandOutput {
+    if stats.is_empty() {
+        return CommandOutput::Lines(vec!["no plugins discovered".to_string()]);
+    }
+
+    let mut table = Table::new(vec![
+        Column::left("PLUGIN"),
+        Column::left("UP"),
+        Column::right("REQS"),
+        Column::right("ERRS"),
+        Column::right("SENT"),
+        Column::right("DROP"),
+        Column::right("RST"),
+        Column::right("LAST"),
+        Column::right("WAIT"),
+        Column::right("STARVED"),
+    ]);
+    for entry in stats {
+        table.push_row(vec![
+            entry.plugin_id.clone(),
+            if entry.running { "yes" } else { "no" }.to_string(),
+            entry.requests_handled.to_string(),
+            entry.errors_returned.to_string(),
+            entry.notifications_delivered.to_string(),
+            entry.notifications_dropped.to_string(),
+            entry.restarts.to_string(),
+            format_latency(entry.last_latency),
+            format_latency(entry.max_queue_wait),
+            entry.starved_requests.to_string(),
+        ]);
+    }
+    CommandOutput::Table(table)
+}
+
+fn audit_output(plugin_id: &str, records: &[&AuditRecord]) -> CommandOutput {
+    if records.is_empty() {
+        return CommandOutput::Lines(vec![format!("no audited actions for {plugin_id}")]);
+    }
+
+    let mut table = Table::new(vec![
+        Column::left("TIME"),
+        Column::left("DECISION"),
+        Column::left("ACTION"),
+        Column::left("PARAMS"),
+        Column::left("REASON"),
+    ]);
+    for record in records {
+        let decision = match record.decision {
+            AuditDecision::Allowed => "allow",
+            AuditDecision::Denied => "DENY",
+        };
+        table.push_row(vec![
+            format_utc_time(record.timestamp_ms),
+            decision.to_string(),
+            record.action.as_str().to_string(),
+            record.params.to_string(),
+            record.reason.clone().unwrap_or_default(),
+        ]);
+    }
+    CommandOutput::Table(table)
+}
+
+/// Format a Unix millisecond timestamp as `HH:MM:SS` UTC.
+fn format_utc_time(timestamp_ms: u64) -> String {
+    let secs = (timestamp_ms / 1_000) % 86_400;
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3_600,
+        (secs / 60) % 60,
+        secs % 60
+    )
+}
+
+fn format_latency(latency: Option<Duration>) -> String {
+    match latency {
+        Some(latency) if latency >= Duration::from_millis(1) => {
+            format!("{:.1}ms", latency.as_secs_f64() * 1_000.0)
+        }
+        Some(latency) => format!("{}us", latency.as_micros()),
+        None => "-".to_string(),
+    }
+}
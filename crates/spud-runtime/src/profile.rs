@@ -0,0 +1,67 @@
+//! App-level `profile` console command.
+//!
+//! Lives in the app because the profile tunes the app loop itself (tick and
+//! poll rates) as well as the console and agent animations.
+
+use spud_config::Profile;
+use spud_core::command::CommandOutput;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "profile";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "profile [battery|balanced|performance]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show or switch the power/performance profile";
+
+/// Run the `profile` command. Returns the output and the profile to switch
+/// to, if any.
+pub(crate) fn execute(current: Profile, args: &[&str]) -> (CommandOutput, Option<Profile>) {
+    match args {
+        [] => (CommandOutput::KeyValue(describe(current)), None),
+        [name] => match name.parse::<Profile>() {
+            Ok(profile) => (
+                CommandOutput::Lines(vec![format!("profile: {profile}")]),
+                Some(profile),
+            ),
+            Err(err) => (CommandOutput::Error(vec![err.to_string()]), None),
+        },
+        _ => (CommandOutput::Error(vec![format!("usage: {USAGE}")]), None),
+    }
+}
+
+/// The active profile and its timing values.
+fn describe(profile: Profile) -> Vec<(String, String)> {
+    let tuning = profile.tuning();
+    let ms = |duration: std::time::Duration| format!("{} ms", duration.as_millis());
+    vec![
+        ("profile".into(), profile.to_string()),
+        ("tick".into(), ms(tuning.tick_interval)),
+        ("input poll".into(), ms(tuning.poll_timeout)),
+        ("telemetry".into(), ms(tuning.telemetry_interval)),
+        ("console slide".into(), ms(tuning.slide_duration)),
+        ("face frame".into(), ms(tuning.face_frame_interval)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_and_switches_profiles() {
+        let (output, switch) = execute(Profile::Balanced, &[]);
+        assert_eq!(
+            output.to_lines()[0].split_whitespace().last(),
+            Some("balanced")
+        );
+        assert_eq!(switch, None);
+
+        let (output, switch) = execute(Profile::Balanced, &["battery"]);
+        assert!(!output.is_error());
+        assert_eq!(switch, Some(Profile::Battery));
+
+        let (output, switch) = execute(Profile::Balanced, &["turbo"]);
+        assert!(output.is_error());
+        assert_eq!(switch, None);
+    }
+}
@@ -0,0 +1,149 @@
+//! `--record` support: append bus events to a JSON-lines file.
+//!
+//! Each session starts with a `{"t_ms": 0, "seed": N}` header holding the
+//! RNG seed, so a recording can be replayed with `--seed N`.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use spud_core::event::{Event, PluginStatus};
+
+use crate::app::telemetry_value_json;
+
+/// Writes one `{"t_ms", "event"}` object per recorded event.
+pub(crate) struct EventRecorder {
+    writer: BufWriter<File>,
+    started_at: Instant,
+}
+
+impl EventRecorder {
+    /// Open `path` for appending and write the session header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or written.
+    pub(crate) fn create(path: &Path, seed: u64) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open record file {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", json!({ "t_ms": 0, "seed": seed }))
+            .with_context(|| format!("failed to write record file {}", path.display()))?;
+        Ok(Self {
+            writer,
+            started_at: Instant::now(),
+        })
+    }
+
+    /// Append `event` unless it is a high-frequency tick or span.
+    pub(crate) fn record(&mut self, event: &Event) {
+        let Some(event) = event_json(event) else {
+            return;
+        };
+        let line = json!({
+            "t_ms": self.started_at.elapsed().as_millis() as u64,
+            "event": event,
+        });
+        if let Err(err) = writeln!(self.writer, "{line}") {
+            tracing::warn!(error = %err, "failed to write event record");
+        }
+    }
+
+    /// Flush buffered records to disk.
+    pub(crate) fn flush(&mut self) {
+        if let Err(err) = self.writer.flush() {
+            tracing::warn!(error = %err, "failed to flush event record");
+        }
+    }
+}
+
+impl Drop for EventRecorder {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+fn event_json(event: &Event) -> Option<Value> {
+    Some(match event {
+        Event::Tick { .. } | Event::Span { .. } => return None,
+        Event::Key(key) => json!({
+            "type": "key",
+            "code": format!("{:?}", key.code),
+            "modifiers": format!("{:?}", key.modifiers),
+        }),
+        Event::Resize { cols, rows } => json!({ "type": "resize", "cols": cols, "rows": rows }),
+        Event::ModuleActivated { id, reason } => {
+            json!({ "type": "module_activated", "id": id, "reason": reason.name() })
+        }
+        Event::ModuleDeactivated { id } => json!({ "type": "module_deactivated", "id": id }),
+        Event::AttentionRequested { id, level } => {
+            json!({ "type": "attention_requested", "id": id, "level": level.name() })
+        }
+        Event::Telemetry { source, key, value } => json!({
+            "type": "telemetry",
+            "source": source,
+            "key": key,
+            "value": telemetry_value_json(value),
+        }),
+        Event::ConfigReloaded { changed } => {
+            json!({ "type": "config_reloaded", "changed": changed })
+        }
+        Event::ProfileChanged { profile } => {
+            json!({ "type": "profile_changed", "profile": profile.name() })
+        }
+        Event::Signal { signal } => json!({ "type": "signal", "signal": signal.name() }),
+        Event::PluginStatus { plugin_id, status } => {
+            let mut value = json!({
+                "type": "plugin_status",
+                "plugin_id": plugin_id,
+                "status": status.name(),
+            });
+            if let PluginStatus::Failed(reason) = status {
+                value["reason"] = json!(reason);
+            }
+            value
+        }
+        Event::Custom { tag, payload } => {
+            json!({ "type": "custom", "tag": tag, "payload": payload })
+        }
+        Event::Quit => json!({ "type": "quit" }),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_core::event::ActivationReason;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn records_events_as_json_lines_and_skips_ticks() {
+        let tmp = TestDir::new("record");
+        let path = tmp.join("record.jsonl");
+        {
+            let mut recorder = EventRecorder::create(&path, 7).unwrap();
+            recorder.record(&Event::Tick {
+                now: Instant::now(),
+            });
+            recorder.record(&Event::ModuleActivated {
+                id: "stats".into(),
+                reason: ActivationReason::Switch,
+            });
+        }
+        let contents = std::fs::read_to_string(&path).unwrap();
+
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let header: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["seed"], 7);
+        let value: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(value["event"]["type"], "module_activated");
+        assert_eq!(value["event"]["id"], "stats");
+    }
+}
@@ -0,0 +1,290 @@
+//! `> path` and `>> path` at the end of a console command line.
+//!
+//! `modules > modules.txt` writes the output of the whole line to a file
+//! and shows where it went instead; `>>` appends. Errors are still shown,
+//! and a question or quit at the end of the line still reaches the console.
+//! A relative path resolves under `<data dir>/output` and may not leave it
+//! with `..`; an absolute path is taken as written. Only lines typed in the
+//! console or sent over the control socket are redirected, so plugins and
+//! alert commands cannot write files this way.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+use spud_core::command::CommandOutput;
+
+/// Subdirectory of the data dir that relative paths resolve under.
+const OUTPUT_DIR: &str = "output";
+
+/// Where a command line's output goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Redirect {
+    pub(crate) path: PathBuf,
+    /// `>>`: add to the file rather than replace it.
+    pub(crate) append: bool,
+}
+
+/// Default directory for relative redirect paths.
+pub(crate) fn default_dir() -> Option<PathBuf> {
+    spud_config::paths::data_dir().map(|dir| dir.join(OUTPUT_DIR))
+}
+
+/// Split a trailing `> path` or `>> path` off `input`, resolving relative
+/// paths under `base`. Unless `typed`, the line is returned whole and a
+/// trailing `>` reaches the command as a word.
+///
+/// A `>` inside quotes is text. One followed by more than a single word is
+/// left alone, so `when tps > 5 echo slow` still compares.
+///
+/// # Errors
+///
+/// Returns a message for a missing path, a relative path that climbs out
+/// of `base` or has no `base`, or an empty command.
+pub(crate) fn split<'a>(
+    input: &'a str,
+    typed: bool,
+    base: Option<&Path>,
+) -> Result<(&'a str, Option<Redirect>), String> {
+    if !typed {
+        return Ok((input, None));
+    }
+    let Some(at) = last_unquoted_gt(input) else {
+        return Ok((input, None));
+    };
+    let append = at > 0
+        && input.as_bytes()[at - 1] == b'>'
+        && last_unquoted_gt(&input[..at]) == Some(at - 1);
+    let command = input[..if append { at - 1 } else { at }].trim_end();
+    let Some(target) = single_word(&input[at + 1..]) else {
+        return Ok((input, None));
+    };
+    if target.is_empty() {
+        return Err("missing path after >".to_string());
+    }
+    if command.trim().is_empty() {
+        return Err("nothing to redirect".to_string());
+    }
+    let path = resolve(&target, base)?;
+    Ok((command, Some(Redirect { path, append })))
+}
+
+/// Byte index of the last `>` outside quotes.
+fn last_unquoted_gt(input: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut last = None;
+    for (index, c) in input.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => last = Some(index),
+            _ => {}
+        }
+    }
+    last
+}
+
+/// `text` as one word with its quotes removed: empty for blank text,
+/// `None` for several words or an operator.
+fn single_word(text: &str) -> Option<String> {
+    let text = text.trim();
+    for quote in ['\'', '"'] {
+        if let Some(inner) = text
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return (!inner.contains(quote)).then(|| inner.to_string());
+        }
+    }
+    if text.contains(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '\'' | '"')) {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+fn resolve(target: &str, base: Option<&Path>) -> Result<PathBuf, String> {
+    let path = Path::new(target);
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    let Some(base) = base else {
+        return Err("no data directory for relative paths; give an absolute path".to_string());
+    };
+    if path
+        .components()
+        .any(|part| !matches!(part, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!(
+            "relative paths must stay under {}; give an absolute path to write elsewhere",
+            base.display()
+        ));
+    }
+    Ok(base.join(path))
+}
+
+/// Write `output` as `redirect` says and return what the console shows in
+/// its place: any errors, where the lines went, and a final quit or
+/// question, which is not written.
+pub(crate) fn apply(redirect: &Redirect, output: CommandOutput) -> CommandOutput {
+    let mut parts: Vec<CommandOutput> = output.parts().into_iter().cloned().collect();
+    let last = if matches!(
+        parts.last(),
+        Some(CommandOutput::Quit | CommandOutput::Prompt { .. })
+    ) {
+        parts.pop()
+    } else {
+        None
+    };
+    let lines: Vec<String> = parts.iter().flat_map(CommandOutput::to_lines).collect();
+
+    let mut shown: Vec<CommandOutput> = parts.into_iter().filter(CommandOutput::is_error).collect();
+    shown.push(match write(redirect, &lines) {
+        Ok(()) => CommandOutput::Lines(vec![format!(
+            "{} line(s) {} {}",
+            lines.len(),
+            if redirect.append {
+                "appended to"
+            } else {
+                "written to"
+            },
+            redirect.path.display()
+        )]),
+        Err(err) => CommandOutput::Error(vec![format!("{err:#}")]),
+    });
+    shown.extend(last);
+    match shown.len() {
+        1 => shown.remove(0),
+        _ => CommandOutput::Multi(shown),
+    }
+}
+
+fn write(redirect: &Redirect, lines: &[String]) -> Result<()> {
+    let path = &redirect.path;
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(redirect.append)
+        .truncate(!redirect.append)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let mut text = lines.join("\n");
+    if !lines.is_empty() {
+        text.push('\n');
+    }
+    file.write_all(text.as_bytes())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    fn base() -> PathBuf {
+        PathBuf::from("/data/spud/output")
+    }
+
+    fn split_ok(input: &str) -> (&str, Option<Redirect>) {
+        split(input, true, Some(&base())).unwrap()
+    }
+
+    #[test]
+    fn splits_a_trailing_redirect() {
+        assert_eq!(
+            split_ok("modules | grep stats > mods.txt"),
+            (
+                "modules | grep stats",
+                Some(Redirect {
+                    path: base().join("mods.txt"),
+                    append: false
+                })
+            )
+        );
+        assert_eq!(
+            split_ok("tps >> '/tmp/my tps.log'"),
+            (
+                "tps",
+                Some(Redirect {
+                    path: PathBuf::from("/tmp/my tps.log"),
+                    append: true
+                })
+            )
+        );
+        assert_eq!(
+            split_ok("when tps > 5 echo hi >out/hi.txt").1.unwrap().path,
+            base().join("out/hi.txt")
+        );
+    }
+
+    #[test]
+    fn leaves_quoted_and_comparison_gt_alone() {
+        assert_eq!(split_ok("echo 'a > b'"), ("echo 'a > b'", None));
+        assert_eq!(
+            split_ok("when tps > 5 echo slow"),
+            ("when tps > 5 echo slow", None)
+        );
+        assert_eq!(
+            split_ok("when tps >= 5 echo ok"),
+            ("when tps >= 5 echo ok", None)
+        );
+    }
+
+    #[test]
+    fn rejects_unsafe_or_missing_paths() {
+        assert!(split("tps >", true, Some(&base())).is_err());
+        assert!(split("> out.txt", true, Some(&base())).is_err());
+        let err = split("tps > ../escape.txt", true, Some(&base())).unwrap_err();
+        assert!(err.contains("/data/spud/output"), "{err}");
+        assert!(split("tps > out.txt", true, None).is_err());
+        assert!(split("tps > /tmp/out.txt", true, None).is_ok());
+    }
+
+    #[test]
+    fn only_typed_lines_are_redirected() {
+        assert_eq!(
+            split("tps > /tmp/out.txt", false, Some(&base())),
+            Ok(("tps > /tmp/out.txt", None))
+        );
+        assert_eq!(split("tps >", false, Some(&base())), Ok(("tps >", None)));
+    }
+
+    #[test]
+    fn writes_lines_and_keeps_errors_and_questions_on_the_console() {
+        let dir = TestDir::new("redirect");
+        let redirect = Redirect {
+            path: dir.join("nested").join("out.txt"),
+            append: false,
+        };
+        let output = CommandOutput::Multi(vec![
+            CommandOutput::Lines(vec!["one".into(), "two".into()]),
+            CommandOutput::Error(vec!["bad".into()]),
+            CommandOutput::Prompt {
+                question: "sure?".into(),
+                callback_token: "t".into(),
+            },
+        ]);
+        let shown = apply(&redirect, output).to_lines();
+        assert_eq!(shown[0], "bad");
+        assert!(shown[1].starts_with("3 line(s) written to"), "{shown:?}");
+        assert_eq!(shown[2], "sure?");
+        assert_eq!(
+            fs::read_to_string(&redirect.path).unwrap(),
+            "one\ntwo\nbad\n"
+        );
+
+        let append = Redirect {
+            append: true,
+            ..redirect.clone()
+        };
+        apply(&append, CommandOutput::Lines(vec!["three".into()]));
+        assert_eq!(
+            fs::read_to_string(&redirect.path).unwrap(),
+            "one\ntwo\nbad\nthree\n"
+        );
+    }
+}
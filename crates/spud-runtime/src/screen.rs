@@ -0,0 +1,398 @@
+//! The shell's screen as a stream, for `spud serve`.
+//!
+//! A served instance renders into a [`SharedScreen`] instead of a
+//! terminal. The screen keeps a copy of what is shown and sends what
+//! changed after every draw, as `spud.control.screen` notifications, to
+//! each `spud attach` client on the control socket; a client that joins
+//! gets the whole screen first. The screen takes the size the last client
+//! asked for, so every client sees the layout of the latest window to
+//! attach or resize. A client too far behind to take a change (its
+//! [`Outbox`] is full) skips changes until it has room, then gets the whole
+//! screen again, like one that just joined.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use ratatui::backend::{Backend, ClearType, WindowSize};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::{Position, Rect, Size};
+use ratatui::style::{Color, Modifier};
+use serde::{Deserialize, Serialize};
+
+use crate::control::{self, Delivery, Outbox};
+
+/// Size a served screen starts at, until a client attaches.
+pub(crate) const DEFAULT_SIZE: Size = Size {
+    width: 80,
+    height: 24,
+};
+
+/// One cell of a [`ScreenFrame`]. Colors use ratatui's names (`red`,
+/// `#ff8800`, `42`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct WireCell {
+    pub(crate) x: u16,
+    pub(crate) y: u16,
+    pub(crate) symbol: String,
+    pub(crate) fg: String,
+    pub(crate) bg: String,
+    pub(crate) modifier: u16,
+}
+
+impl WireCell {
+    fn new(x: u16, y: u16, cell: &Cell) -> Self {
+        Self {
+            x,
+            y,
+            symbol: cell.symbol().to_string(),
+            fg: cell.fg.to_string(),
+            bg: cell.bg.to_string(),
+            modifier: cell.modifier.bits(),
+        }
+    }
+
+    /// The ratatui cell; unknown colors fall back to the terminal's own.
+    pub(crate) fn to_cell(&self) -> Cell {
+        let mut cell = Cell::default();
+        cell.set_symbol(&self.symbol);
+        cell.fg = self.fg.parse().unwrap_or(Color::Reset);
+        cell.bg = self.bg.parse().unwrap_or(Color::Reset);
+        cell.modifier = Modifier::from_bits_truncate(self.modifier);
+        cell
+    }
+}
+
+/// Params of a `spud.control.screen` notification.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ScreenFrame {
+    /// Blank the screen before drawing `cells`.
+    pub(crate) clear: bool,
+    pub(crate) cells: Vec<WireCell>,
+    /// Where to show the cursor; hidden when `None`.
+    pub(crate) cursor: Option<(u16, u16)>,
+}
+
+/// The screen a served instance draws on; clones share it.
+#[derive(Clone)]
+pub(crate) struct SharedScreen(Arc<Mutex<Screen>>);
+
+struct Screen {
+    /// What clients show once they have applied every frame sent.
+    mirror: Buffer,
+    /// Changes since the last flush.
+    frame: ScreenFrame,
+    cursor: Position,
+    cursor_visible: bool,
+    cursor_moved: bool,
+    clients: Vec<Outbox>,
+    /// Clients waiting for the whole screen: new ones, and ones that
+    /// missed changes.
+    joining: Vec<Outbox>,
+}
+
+impl SharedScreen {
+    pub(crate) fn new(size: Size) -> Self {
+        Self(Arc::new(Mutex::new(Screen {
+            mirror: Buffer::empty(Rect::new(0, 0, size.width, size.height)),
+            frame: ScreenFrame::default(),
+            cursor: Position::ORIGIN,
+            cursor_visible: false,
+            cursor_moved: false,
+            clients: Vec::new(),
+            joining: Vec::new(),
+        })))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Screen> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Start streaming to a client with a `cols`×`rows` window. It gets the
+    /// whole screen with the next flush.
+    pub(crate) fn attach(&self, client: Outbox, cols: u16, rows: u16) {
+        self.resize(cols, rows);
+        self.lock().joining.push(client);
+    }
+
+    /// Take the size of a client's window; the next draw notices and
+    /// redraws everything.
+    pub(crate) fn resize(&self, cols: u16, rows: u16) {
+        let mut screen = self.lock();
+        let area = Rect::new(0, 0, cols.max(1), rows.max(1));
+        if screen.mirror.area != area {
+            screen.mirror.resize(area);
+        }
+    }
+
+    /// Number of clients being streamed to.
+    pub(crate) fn clients(&self) -> usize {
+        let screen = self.lock();
+        screen.clients.len() + screen.joining.len()
+    }
+}
+
+impl Screen {
+    fn size(&self) -> Size {
+        self.mirror.area.as_size()
+    }
+
+    fn cursor(&self) -> Option<(u16, u16)> {
+        self.cursor_visible
+            .then_some((self.cursor.x, self.cursor.y))
+    }
+
+    /// Blank the mirror cells from index `start` to `end`, and send them
+    /// blank.
+    fn blank(&mut self, start: usize, end: usize) {
+        for index in start..end.min(self.mirror.content.len()) {
+            self.mirror.content[index].reset();
+            let (x, y) = self.mirror.pos_of(index);
+            let cell = WireCell::new(x, y, &self.mirror.content[index]);
+            self.frame.cells.push(cell);
+        }
+    }
+
+    /// The whole screen, for a client that just joined.
+    fn snapshot(&self) -> ScreenFrame {
+        let blank = Cell::default();
+        let cells = self
+            .mirror
+            .content
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| **cell != blank)
+            .map(|(index, cell)| {
+                let (x, y) = self.mirror.pos_of(index);
+                WireCell::new(x, y, cell)
+            })
+            .collect();
+        ScreenFrame {
+            clear: true,
+            cells,
+            cursor: self.cursor(),
+        }
+    }
+}
+
+/// Send `frame` to each client, dropping those that went away and handing
+/// back those with no room for it.
+fn send(clients: &mut Vec<Outbox>, frame: &ScreenFrame) -> Vec<Outbox> {
+    let mut lagging = Vec::new();
+    clients.retain(
+        |client| match client.notify(control::method::SCREEN, frame) {
+            Delivery::Sent => true,
+            Delivery::Full => {
+                lagging.push(client.clone());
+                false
+            }
+            Delivery::Closed => false,
+        },
+    );
+    lagging
+}
+
+impl Backend for SharedScreen {
+    type Error = std::io::Error;
+
+    fn draw<'a, I>(&mut self, content: I) -> std::io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let mut screen = self.lock();
+        for (x, y, cell) in content {
+            if let Some(mirrored) = screen.mirror.cell_mut((x, y)) {
+                *mirrored = cell.clone();
+                screen.frame.cells.push(WireCell::new(x, y, cell));
+            }
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> std::io::Result<()> {
+        let mut screen = self.lock();
+        screen.cursor_moved |= screen.cursor_visible;
+        screen.cursor_visible = false;
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> std::io::Result<()> {
+        let mut screen = self.lock();
+        screen.cursor_moved |= !screen.cursor_visible;
+        screen.cursor_visible = true;
+        Ok(())
+    }
+
+    fn get_cursor_position(&mut self) -> std::io::Result<Position> {
+        Ok(self.lock().cursor)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> std::io::Result<()> {
+        let position = position.into();
+        let mut screen = self.lock();
+        screen.cursor_moved |= screen.cursor != position;
+        screen.cursor = position;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> std::io::Result<()> {
+        let mut screen = self.lock();
+        screen.mirror.reset();
+        screen.frame.cells.clear();
+        screen.frame.clear = true;
+        Ok(())
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> std::io::Result<()> {
+        let mut screen = self.lock();
+        let area = screen.mirror.area;
+        let width = area.width as usize;
+        let end = screen.mirror.content.len();
+        let line = screen.cursor.y.min(area.height.saturating_sub(1)) as usize * width;
+        let cursor = line + screen.cursor.x.min(area.width.saturating_sub(1)) as usize;
+        match clear_type {
+            ClearType::All => {
+                drop(screen);
+                return self.clear();
+            }
+            ClearType::AfterCursor => screen.blank(cursor, end),
+            ClearType::BeforeCursor => screen.blank(0, cursor + 1),
+            ClearType::CurrentLine => screen.blank(line, line + width),
+            ClearType::UntilNewLine => screen.blank(cursor, line + width),
+        }
+        Ok(())
+    }
+
+    fn size(&self) -> std::io::Result<Size> {
+        Ok(self.lock().size())
+    }
+
+    fn window_size(&mut self) -> std::io::Result<WindowSize> {
+        Ok(WindowSize {
+            columns_rows: self.lock().size(),
+            pixels: Size::default(),
+        })
+    }
+
+    /// Send the changes since the last flush to the clients, and the whole
+    /// screen to those that just joined.
+    fn flush(&mut self) -> std::io::Result<()> {
+        let mut screen = self.lock();
+        let screen = &mut *screen;
+        if screen.frame.clear || !screen.frame.cells.is_empty() || screen.cursor_moved {
+            let mut frame = std::mem::take(&mut screen.frame);
+            frame.cursor = screen.cursor();
+            screen.cursor_moved = false;
+            let mut lagging = send(&mut screen.clients, &frame);
+            screen.joining.append(&mut lagging);
+        }
+        if !screen.joining.is_empty() {
+            let snapshot = screen.snapshot();
+            let mut joining = std::mem::take(&mut screen.joining);
+            screen.joining = send(&mut joining, &snapshot);
+            screen.clients.append(&mut joining);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use ratatui::style::Stylize;
+    use ratatui::text::Line;
+    use ratatui::Terminal;
+    use serde_json::Value;
+
+    use super::*;
+
+    fn frames(lines: &mpsc::Receiver<String>) -> Vec<ScreenFrame> {
+        lines
+            .try_iter()
+            .map(|line| {
+                let mut message: Value = serde_json::from_str(&line).unwrap();
+                assert_eq!(message["method"], control::method::SCREEN);
+                serde_json::from_value(message["params"].take()).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clients_get_the_whole_screen_then_changes() {
+        let screen = SharedScreen::new(DEFAULT_SIZE);
+        let mut terminal = Terminal::new(screen.clone()).unwrap();
+        terminal
+            .draw(|f| f.render_widget(Line::from("hi".red()), f.area()))
+            .unwrap();
+
+        let (outbox, lines) = Outbox::channel();
+        screen.attach(outbox, 20, 5);
+        terminal
+            .draw(|f| f.render_widget(Line::from("hi".red()), f.area()))
+            .unwrap();
+        let joined = frames(&lines);
+        // The resize redraws everything for everyone, then the newcomer
+        // gets the snapshot
+        assert_eq!(joined.len(), 1);
+        let snapshot = &joined[0];
+        assert!(snapshot.clear);
+        assert_eq!(snapshot.cells.len(), 2);
+        assert_eq!(snapshot.cells[0].symbol, "h");
+        assert_eq!(snapshot.cells[0].to_cell().fg, Color::Red);
+        assert_eq!(terminal.size().unwrap(), Size::new(20, 5));
+
+        terminal
+            .draw(|f| {
+                f.render_widget(Line::from("ho"), f.area());
+                f.set_cursor_position((1, 2));
+            })
+            .unwrap();
+        let changes = frames(&lines);
+        assert_eq!(changes.len(), 1);
+        assert!(!changes[0].clear);
+        let symbols: Vec<&str> = changes[0].cells.iter().map(|c| c.symbol.as_str()).collect();
+        assert_eq!(symbols, ["h", "o"]);
+        assert_eq!(changes[0].cursor, Some((1, 2)));
+
+        // Nothing changed, nothing sent
+        terminal
+            .draw(|f| {
+                f.render_widget(Line::from("ho"), f.area());
+                f.set_cursor_position((1, 2));
+            })
+            .unwrap();
+        assert!(frames(&lines).is_empty());
+
+        drop(lines);
+        terminal
+            .draw(|f| f.render_widget(Line::from("x"), f.area()))
+            .unwrap();
+        assert_eq!(screen.clients(), 0);
+    }
+
+    #[test]
+    fn lagging_client_gets_the_whole_screen_once_it_has_room() {
+        let screen = SharedScreen::new(Size::new(10, 2));
+        let mut terminal = Terminal::new(screen.clone()).unwrap();
+        let (outbox, lines) = Outbox::channel();
+        screen.attach(outbox, 10, 2);
+        for n in 0..=control::OUTBOX_CAPACITY {
+            terminal
+                .draw(|f| f.render_widget(Line::from(n.to_string()), f.area()))
+                .unwrap();
+        }
+        // The joining snapshot and changes until the outbox filled
+        assert_eq!(frames(&lines).len(), control::OUTBOX_CAPACITY);
+        assert_eq!(screen.clients(), 1);
+
+        terminal
+            .draw(|f| f.render_widget(Line::from("caught up"), f.area()))
+            .unwrap();
+        let resent = frames(&lines);
+        assert_eq!(resent.len(), 1);
+        assert!(resent[0].clear);
+        let text: String = resent[0].cells.iter().map(|c| c.symbol.as_str()).collect();
+        assert_eq!(text, "caughtup");
+    }
+}
@@ -0,0 +1,113 @@
+//! Console and window state kept across restarts.
+//!
+//! Saved as `session.json` in the state dir (the workspace's own when one
+//! is active) when the shell exits or switches workspace, and loaded on the
+//! next start unless `--fresh` is given. Log lines are not saved, so the
+//! restored scroll position is clamped to the lines present at startup.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use spud_config::{ConsoleConfig, HudConfig};
+use spud_core::usage::CommandUsage;
+
+/// File name inside the state dir.
+pub(crate) const SESSION_FILE: &str = "session.json";
+
+/// What is restored on the next start.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Session {
+    pub console_open: bool,
+    /// Lines above the bottom of the console log.
+    pub scroll_offset: usize,
+    /// Submitted console commands, oldest first.
+    pub history: Vec<String>,
+    pub active_module: Option<String>,
+    /// Size set with the `hud` command; `None` follows `spud.toml`.
+    pub hud: Option<HudConfig>,
+    /// Layout set with the `console` command; `None` follows `spud.toml`.
+    pub console: Option<ConsoleConfig>,
+    /// How often each command has run, for `stats commands` and ranking
+    /// completions.
+    pub command_usage: CommandUsage,
+}
+
+impl Session {
+    /// Read the session at `path`; `None` when there is none yet.
+    pub(crate) fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let session = serde_json::from_str(&raw)
+            .with_context(|| format!("invalid session file {}", path.display()))?;
+        Ok(Some(session))
+    }
+
+    /// Write the session to `path`, replacing the old file in one step so a
+    /// crash mid-write leaves the previous session intact.
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, path).with_context(|| format!("failed to replace {}", path.display()))
+    }
+}
+
+/// `session.json` under `state_dir`.
+pub(crate) fn path(state_dir: Option<&Path>) -> Option<PathBuf> {
+    state_dir.map(|dir| dir.join(SESSION_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = TestDir::new("session");
+        let path = path(Some(&dir)).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(Session::load(&path).unwrap(), None);
+
+        let mut session = Session {
+            console_open: true,
+            scroll_offset: 3,
+            history: vec!["tps".into(), "switch stats".into()],
+            active_module: Some("stats".into()),
+            hud: Some(HudConfig {
+                height: 12,
+                face_width: 20,
+            }),
+            console: Some(ConsoleConfig {
+                height: 100,
+                ..ConsoleConfig::default()
+            }),
+            command_usage: CommandUsage::default(),
+        };
+        session
+            .command_usage
+            .record("tps", std::time::SystemTime::now());
+        session.save(&path).unwrap();
+        let loaded = Session::load(&path).unwrap();
+        fs::write(&path, "{\"history\": [\"uptime\"]}").unwrap();
+        let partial = Session::load(&path).unwrap().unwrap();
+        fs::write(&path, "not json").unwrap();
+        let broken = Session::load(&path);
+
+        assert_eq!(loaded, Some(session));
+        assert_eq!(partial.history, vec!["uptime"]);
+        assert!(partial.command_usage.is_empty());
+        assert!(!partial.console_open);
+        assert!(broken.is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! POSIX signal handling.
+//!
+//! Lets external scripts poke a running SPUD without the control socket:
+//! `kill -USR1` reloads `spud.toml`, `kill -USR2` toggles the console, and
+//! `kill -HUP` restarts plugins. After its default action each signal is
+//! published as [`Event::Signal`](spud_core::event::Event::Signal) so
+//! modules and plugins can react too.
+//!
+//! `spud serve` and `--headless` have no terminal to catch Ctrl-C as a key,
+//! so they also [stop on `SIGINT`/`SIGTERM`](SignalListener::stop_on_interrupt):
+//! the first one quits through the normal shutdown path, a second one kills
+//! the process in case that path hangs.
+//!
+//! Handlers only set flags (signal-hook's self-pipe); the loop drains them
+//! once per iteration, so no app code runs in signal context. Platforms
+//! without these signals get a listener that never reports any.
+
+use spud_core::event::Signal;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::sync::Arc;
+
+/// What the app does when a signal arrives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SignalAction {
+    /// Re-read `spud.toml` now instead of waiting for the file watcher.
+    ReloadConfig,
+    /// Open or close the console.
+    ToggleConsole,
+    /// Stop every plugin session and rediscover plugins.
+    ReloadPlugins,
+}
+
+/// Default action for `signal`.
+pub(crate) fn default_action(signal: Signal) -> SignalAction {
+    match signal {
+        Signal::Hup => SignalAction::ReloadPlugins,
+        Signal::Usr1 => SignalAction::ReloadConfig,
+        Signal::Usr2 => SignalAction::ToggleConsole,
+    }
+}
+
+/// Collects signals delivered since the last [`SignalListener::drain`].
+pub(crate) struct SignalListener {
+    #[cfg(unix)]
+    signals: Option<signal_hook::iterator::Signals>,
+    /// Set by `SIGINT`/`SIGTERM` once [`Self::stop_on_interrupt`] ran.
+    #[cfg(unix)]
+    stop: Arc<AtomicBool>,
+}
+
+impl SignalListener {
+    /// Register handlers for every [`Signal`]. Failure is logged and
+    /// leaves a listener that reports nothing.
+    #[cfg(unix)]
+    pub(crate) fn install() -> Self {
+        let numbers = Signal::ALL.map(number);
+        let signals = match signal_hook::iterator::Signals::new(numbers) {
+            Ok(signals) => Some(signals),
+            Err(err) => {
+                tracing::warn!(error = %err, "signal handlers not installed");
+                None
+            }
+        };
+        Self {
+            signals,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Signals are not supported here; the listener reports nothing.
+    #[cfg(not(unix))]
+    pub(crate) fn install() -> Self {
+        Self {}
+    }
+
+    /// Turn `SIGINT` and `SIGTERM` into stop requests instead of letting
+    /// them kill the process. A second one while the first is pending
+    /// still terminates, so a wedged shutdown can be interrupted.
+    #[cfg(unix)]
+    pub(crate) fn stop_on_interrupt(&mut self) {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+        for raw in [SIGINT, SIGTERM] {
+            let registered =
+                signal_hook::flag::register_conditional_shutdown(raw, 1, Arc::clone(&self.stop))
+                    .and_then(|_| signal_hook::flag::register(raw, Arc::clone(&self.stop)));
+            if let Err(err) = registered {
+                tracing::warn!(error = %err, "stop signal handlers not installed");
+            }
+        }
+    }
+
+    /// Signals are not supported here; interrupts keep their default.
+    #[cfg(not(unix))]
+    pub(crate) fn stop_on_interrupt(&mut self) {}
+
+    /// Whether a stop signal arrived since the last call.
+    #[cfg(unix)]
+    pub(crate) fn stop_requested(&self) -> bool {
+        self.stop.swap(false, Ordering::Relaxed)
+    }
+
+    /// Signals are not supported here; never.
+    #[cfg(not(unix))]
+    pub(crate) fn stop_requested(&self) -> bool {
+        false
+    }
+
+    /// Signals received since the last call, without blocking.
+    #[cfg(unix)]
+    pub(crate) fn drain(&mut self) -> Vec<Signal> {
+        let Some(signals) = self.signals.as_mut() else {
+            return Vec::new();
+        };
+        signals
+            .pending()
+            .filter_map(|raw| Signal::ALL.into_iter().find(|s| number(*s) == raw))
+            .collect()
+    }
+
+    /// Signals are not supported here; always empty.
+    #[cfg(not(unix))]
+    pub(crate) fn drain(&mut self) -> Vec<Signal> {
+        Vec::new()
+    }
+}
+
+#[cfg(unix)]
+fn number(signal: Signal) -> i32 {
+    use signal_hook::consts::{SIGHUP, SIGUSR1, SIGUSR2};
+    match signal {
+        Signal::Hup => SIGHUP,
+        Signal::Usr1 => SIGUSR1,
+        Signal::Usr2 => SIGUSR2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings() {
+        assert_eq!(default_action(Signal::Usr1), SignalAction::ReloadConfig);
+        assert_eq!(default_action(Signal::Usr2), SignalAction::ToggleConsole);
+        assert_eq!(default_action(Signal::Hup), SignalAction::ReloadPlugins);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn raised_signal_is_drained_once() {
+        let mut listener = SignalListener::install();
+        signal_hook::low_level::raise(number(Signal::Usr2)).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+        let mut received = Vec::new();
+        while received.is_empty() && std::time::Instant::now() < deadline {
+            received = listener.drain();
+        }
+        assert_eq!(received, vec![Signal::Usr2]);
+        assert!(listener.drain().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn interrupt_becomes_a_stop_request() {
+        let mut listener = SignalListener::install();
+        listener.stop_on_interrupt();
+        signal_hook::low_level::raise(signal_hook::consts::SIGTERM).unwrap();
+        assert!(listener.stop_requested());
+        assert!(!listener.stop_requested());
+    }
+}
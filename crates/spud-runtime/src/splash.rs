@@ -0,0 +1,133 @@
+//! Startup progress shown on the splash screen.
+//!
+//! The app reports each step of startup here (modules registered, plugin
+//! discovery, one line per plugin handshake) and the splash redraws on
+//! every change. Headless runs and later plugin restarts use a hidden
+//! splash, which only keeps the bookkeeping.
+
+use std::time::Duration;
+
+use ratatui::Terminal;
+use spud_ui::splash::{render_splash, SplashStep, SplashView, StepStatus};
+
+use crate::compose::FrameComposer;
+use crate::version;
+
+/// Longest the splash waits for plugin handshakes before the shell comes
+/// up; later ones finish in the background.
+pub(crate) const SPLASH_TIMEOUT: Duration = Duration::from_secs(3);
+/// Time between splash redraws while waiting.
+pub(crate) const SPLASH_FRAME: Duration = Duration::from_millis(80);
+
+pub(crate) struct Splash<'t> {
+    terminal: Option<&'t mut Terminal<FrameComposer>>,
+    steps: Vec<SplashStep>,
+    /// Keys of running steps the splash waits on, with their index.
+    waiting: Vec<(String, usize)>,
+    frame: usize,
+}
+
+impl<'t> Splash<'t> {
+    /// A splash drawn on `terminal`.
+    pub(crate) fn new(terminal: &'t mut Terminal<FrameComposer>) -> Self {
+        let mut splash = Self {
+            terminal: Some(terminal),
+            ..Self::hidden()
+        };
+        splash.draw();
+        splash
+    }
+
+    /// A splash that is never drawn.
+    pub(crate) fn hidden() -> Self {
+        Self {
+            terminal: None,
+            steps: Vec::new(),
+            waiting: Vec::new(),
+            frame: 0,
+        }
+    }
+
+    /// Add a finished step.
+    pub(crate) fn done(&mut self, label: impl Into<String>, detail: impl Into<String>) {
+        self.push(label.into(), StepStatus::Done(detail.into()));
+    }
+
+    /// Add a failed step.
+    pub(crate) fn failed(&mut self, label: impl Into<String>, reason: impl Into<String>) {
+        self.push(label.into(), StepStatus::Failed(reason.into()));
+    }
+
+    /// Add a running step finished later by [`resolve`](Self::resolve)
+    /// with the same `key`.
+    pub(crate) fn wait_on(&mut self, key: impl Into<String>, label: impl Into<String>) {
+        self.waiting.push((key.into(), self.steps.len()));
+        self.push(label.into(), StepStatus::Running);
+    }
+
+    /// Finish the running step for `key`, if there is one.
+    pub(crate) fn resolve(&mut self, key: &str, status: StepStatus) {
+        let Some(pos) = self.waiting.iter().position(|(waiting, _)| waiting == key) else {
+            return;
+        };
+        let (_, index) = self.waiting.remove(pos);
+        self.steps[index].status = status;
+        self.draw();
+    }
+
+    /// Whether a step is still running.
+    pub(crate) fn is_waiting(&self) -> bool {
+        !self.waiting.is_empty()
+    }
+
+    /// Keys of the steps still running.
+    pub(crate) fn waiting_keys(&self) -> impl Iterator<Item = &str> {
+        self.waiting.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Redraw, moving the spinner on.
+    pub(crate) fn draw(&mut self) {
+        let Some(terminal) = self.terminal.as_deref_mut() else {
+            return;
+        };
+        self.frame = self.frame.wrapping_add(1);
+        let view = SplashView {
+            version: version::SUMMARY,
+            steps: &self.steps,
+            frame: self.frame,
+        };
+        if let Err(err) = terminal.draw(|f| render_splash(f, f.area(), &view)) {
+            tracing::debug!("splash not drawn: {err}");
+        }
+    }
+
+    fn push(&mut self, label: String, status: StepStatus) {
+        self.steps.push(SplashStep { label, status });
+        self.draw();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_waiting_steps_by_key() {
+        let mut splash = Splash::hidden();
+        splash.done("Hello module", "");
+        splash.wait_on("demo.a", "plugin demo.a");
+        splash.wait_on("demo.b", "plugin demo.b");
+        assert!(splash.is_waiting());
+
+        splash.resolve("demo.b", StepStatus::Failed("timed out".into()));
+        splash.resolve("demo.b", StepStatus::Done(String::new()));
+        assert_eq!(splash.waiting_keys().collect::<Vec<_>>(), ["demo.a"]);
+        assert_eq!(
+            splash.steps[2].status,
+            StepStatus::Failed("timed out".into())
+        );
+
+        splash.resolve("demo.a", StepStatus::Done("host api 1.3.0".into()));
+        assert!(!splash.is_waiting());
+    }
+}
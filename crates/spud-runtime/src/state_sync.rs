@@ -0,0 +1,109 @@
+//! Host state changes for `spud.state.subscribe` subscribers.
+//!
+//! Every bus event goes through [`StateTracker::observe`], which keeps the
+//! newest value of each telemetry key updated since the last tick. On each
+//! tick the app calls [`StateTracker::take`] with the current active module
+//! and status line, and sends whatever changed as one batch, so a key that
+//! updates many times per tick costs subscribers one entry.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use spud_core::event::Event;
+use spud_remote::protocol::{ActiveModule, StateChange, TelemetryDatum};
+
+use crate::app::telemetry_value_json;
+
+#[derive(Debug, Default)]
+pub(crate) struct StateTracker {
+    active_module: Option<ActiveModule>,
+    status_line: String,
+    /// Telemetry updated since the last [`take`](Self::take), by
+    /// `(source, key)`.
+    telemetry: BTreeMap<(String, String), Value>,
+}
+
+impl StateTracker {
+    /// Note a telemetry update carried by `event`.
+    pub(crate) fn observe(&mut self, event: &Event) {
+        if let Event::Telemetry { source, key, value } = event {
+            self.telemetry
+                .insert((source.clone(), key.clone()), telemetry_value_json(value));
+        }
+    }
+
+    /// What changed since the last call, given the current active module
+    /// and status line.
+    pub(crate) fn take(
+        &mut self,
+        active_module: Option<ActiveModule>,
+        status_line: &str,
+    ) -> Vec<StateChange> {
+        let mut changes = Vec::new();
+        if active_module != self.active_module {
+            self.active_module = active_module.clone();
+            changes.push(StateChange::ActiveModule {
+                module: active_module,
+            });
+        }
+        if status_line != self.status_line {
+            self.status_line = status_line.to_string();
+            changes.push(StateChange::StatusLine {
+                status_line: status_line.to_string(),
+            });
+        }
+        changes.extend(std::mem::take(&mut self.telemetry).into_iter().map(
+            |((source, key), value)| StateChange::Telemetry(TelemetryDatum { source, key, value }),
+        ));
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_core::event::TelemetryValue;
+
+    fn telemetry(key: &str, value: i64) -> Event {
+        Event::Telemetry {
+            source: "stats".into(),
+            key: key.into(),
+            value: TelemetryValue::Int(value),
+        }
+    }
+
+    #[test]
+    fn reports_each_change_once_with_the_newest_telemetry() {
+        let mut tracker = StateTracker::default();
+        let hello = Some(ActiveModule {
+            id: "hello".into(),
+            title: "Hello".into(),
+        });
+        tracker.observe(&telemetry("cpu", 1));
+        tracker.observe(&telemetry("cpu", 2));
+        tracker.observe(&Event::Quit);
+
+        let changes = tracker.take(hello.clone(), "ready");
+        assert_eq!(
+            changes,
+            [
+                StateChange::ActiveModule {
+                    module: hello.clone()
+                },
+                StateChange::StatusLine {
+                    status_line: "ready".into()
+                },
+                StateChange::Telemetry(TelemetryDatum {
+                    source: "stats".into(),
+                    key: "cpu".into(),
+                    value: serde_json::json!(2),
+                }),
+            ]
+        );
+        assert!(tracker.take(hello, "ready").is_empty());
+        assert_eq!(
+            tracker.take(None, "ready"),
+            [StateChange::ActiveModule { module: None }]
+        );
+    }
+}
@@ -0,0 +1,526 @@
+//! `[http]`: a read-only status page for checking on SPUD from elsewhere.
+//!
+//! The app builds a [`StatusSnapshot`] every [`REFRESH`] and hands it to
+//! the [`StatusServer`]; the server thread answers requests from the latest
+//! one and never touches the app. `GET /` is a small HTML page with the
+//! telemetry charts drawn server-side as SVG, reloading itself every few
+//! seconds; `GET /status.json` is the same data as JSON, and `GET /metrics`
+//! the plugin runtime and event bus counters in the Prometheus text format.
+//!
+//! The server needs the `http` feature; without it [`StatusServer::start`]
+//! fails and the app logs why.
+#![cfg_attr(not(feature = "http"), allow(dead_code))]
+
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+use spud_core::bus::{BusStats, KindCounts};
+use spud_remote::health::HealthStatus;
+use spud_remote::metrics::{render_prometheus, PluginStats};
+
+/// How often the app rebuilds the snapshot.
+pub(crate) const REFRESH: Duration = Duration::from_secs(1);
+/// Console log lines shown.
+pub(crate) const LOG_LINES: usize = 50;
+/// Samples per chart.
+pub(crate) const CHART_POINTS: usize = 120;
+/// Seconds between page reloads.
+const PAGE_RELOAD_SECS: u32 = 5;
+const CHART_WIDTH: f64 = 240.0;
+const CHART_HEIGHT: f64 = 48.0;
+
+/// Everything the page shows.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct StatusSnapshot {
+    pub(crate) version: &'static str,
+    pub(crate) uptime_secs: u64,
+    pub(crate) tps: f64,
+    pub(crate) active_module: Option<String>,
+    pub(crate) modules: Vec<ModuleStatus>,
+    /// Empty when the plugin runtime is disabled.
+    pub(crate) plugins: Vec<PluginStatus>,
+    /// Newest last.
+    pub(crate) logs: Vec<LogLine>,
+    pub(crate) charts: Vec<Chart>,
+    /// Served at `/metrics`; empty when the plugin runtime is disabled.
+    #[serde(skip)]
+    pub(crate) plugin_stats: Vec<PluginStats>,
+    /// Served at `/metrics`.
+    #[serde(skip)]
+    pub(crate) bus: Option<BusStats>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ModuleStatus {
+    pub(crate) id: String,
+    pub(crate) title: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct PluginStatus {
+    pub(crate) id: String,
+    pub(crate) version: String,
+    pub(crate) running: bool,
+    /// `None` when not running or health pings are off.
+    pub(crate) health: Option<HealthStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct LogLine {
+    pub(crate) level: String,
+    pub(crate) target: String,
+    pub(crate) message: String,
+}
+
+/// Recent samples of one numeric telemetry series, oldest first.
+#[derive(Debug, Serialize)]
+pub(crate) struct Chart {
+    pub(crate) source: String,
+    pub(crate) key: String,
+    pub(crate) points: Vec<f64>,
+}
+
+/// What to send back for one request.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Reply {
+    pub(crate) status: u16,
+    pub(crate) content_type: &'static str,
+    pub(crate) body: String,
+}
+
+impl Reply {
+    fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{body}\n"),
+        }
+    }
+}
+
+/// Answer `method` on `url` from `snapshot`.
+pub(crate) fn route(method: &str, url: &str, snapshot: &StatusSnapshot) -> Reply {
+    if method != "GET" && method != "HEAD" {
+        return Reply::text(405, "method not allowed");
+    }
+    let path = url.split_once('?').map_or(url, |(path, _)| path);
+    match path {
+        "/" | "/index.html" => Reply {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body: render_html(snapshot),
+        },
+        "/status.json" => match serde_json::to_string_pretty(snapshot) {
+            Ok(json) => Reply {
+                status: 200,
+                content_type: "application/json",
+                body: json + "\n",
+            },
+            Err(err) => Reply::text(500, &format!("failed to serialize status: {err}")),
+        },
+        "/metrics" => Reply {
+            status: 200,
+            content_type: "text/plain; version=0.0.4; charset=utf-8",
+            body: render_prometheus(&snapshot.plugin_stats)
+                + &snapshot.bus.as_ref().map(bus_metrics).unwrap_or_default(),
+        },
+        _ => Reply::text(404, "not found"),
+    }
+}
+
+fn render_html(snapshot: &StatusSnapshot) -> String {
+    let mut html = String::with_capacity(8 * 1024);
+    let _ = write!(
+        html,
+        "<!doctype html>\n<html><head><meta charset=\"utf-8\">\
+         <meta http-equiv=\"refresh\" content=\"{PAGE_RELOAD_SECS}\">\
+         <title>SPUD status</title><style>\
+         body{{font-family:monospace;background:#111;color:#ddd;margin:1.5em}}\
+         h2{{color:#8c8;font-size:1em;margin-top:1.5em}}td,th{{padding:0 1em 0 0;text-align:left}}\
+         .charts{{display:flex;flex-wrap:wrap;gap:1em}}svg{{background:#1b1b1b}}\
+         polyline{{fill:none;stroke:#8c8;stroke-width:1.5}}.dim{{color:#777}}\
+         .WARN{{color:#db6}}.ERROR{{color:#e66}}</style></head><body>\n"
+    );
+    let _ = write!(
+        html,
+        "<h1>SPUD</h1>\n<p>{} &middot; up {} &middot; {:.1} TPS &middot; active: {}</p>\n",
+        escape(snapshot.version),
+        format_uptime(snapshot.uptime_secs),
+        snapshot.tps,
+        escape(snapshot.active_module.as_deref().unwrap_or("none")),
+    );
+
+    html.push_str("<h2>Modules</h2>\n<table>\n");
+    for module in &snapshot.modules {
+        let marker = if snapshot.active_module.as_deref() == Some(module.id.as_str()) {
+            "&#9654;"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            html,
+            "<tr><td>{marker}</td><td>{}</td><td>{}</td></tr>",
+            escape(&module.id),
+            escape(&module.title)
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Plugins</h2>\n");
+    if snapshot.plugins.is_empty() {
+        html.push_str("<p class=\"dim\">none</p>\n");
+    } else {
+        html.push_str(
+            "<table>\n<tr><th>PLUGIN</th><th>VERSION</th><th>STATE</th><th>HEALTH</th></tr>\n",
+        );
+        for plugin in &snapshot.plugins {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&plugin.id),
+                escape(&plugin.version),
+                if plugin.running { "running" } else { "stopped" },
+                plugin
+                    .health
+                    .map_or_else(|| "-".to_string(), |health| health.to_string()),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("<h2>Telemetry</h2>\n<div class=\"charts\">\n");
+    for chart in &snapshot.charts {
+        let latest = chart.points.last().copied().unwrap_or_default();
+        let _ = writeln!(
+            html,
+            "<figure>{}<figcaption>{}.{} {}</figcaption></figure>",
+            sparkline(&chart.points),
+            escape(&chart.source),
+            escape(&chart.key),
+            format_value(latest),
+        );
+    }
+    html.push_str("</div>\n");
+
+    html.push_str("<h2>Recent logs</h2>\n<pre>");
+    for line in &snapshot.logs {
+        let _ = writeln!(
+            html,
+            "<span class=\"{}\">{:5}</span> <span class=\"dim\">{}</span> {}",
+            escape(&line.level),
+            escape(&line.level),
+            escape(&line.target),
+            escape(&line.message),
+        );
+    }
+    html.push_str("</pre>\n</body></html>\n");
+    html
+}
+
+/// Event bus counters in the Prometheus text format.
+fn bus_metrics(stats: &BusStats) -> String {
+    let mut out = String::new();
+    let gauges = [
+        (
+            "spud_bus_queue_depth",
+            "gauge",
+            "Events waiting in the bus queue.",
+            stats.depth as u64,
+        ),
+        (
+            "spud_bus_queue_peak_depth",
+            "gauge",
+            "Deepest the bus queue has been.",
+            stats.peak_depth as u64,
+        ),
+        (
+            "spud_bus_blocked_total",
+            "counter",
+            "Publishes that found the queue full under the block overflow policy.",
+            stats.blocked,
+        ),
+    ];
+    for (name, kind, help, value) in gauges {
+        let _ = writeln!(
+            out,
+            "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}"
+        );
+    }
+    type Field = fn(&KindCounts) -> u64;
+    let families: [(&str, &str, Field); 2] = [
+        (
+            "spud_bus_events_published_total",
+            "Events published on the bus.",
+            |counts| counts.published,
+        ),
+        (
+            "spud_bus_events_dropped_total",
+            "Events dropped because the bus queue was full.",
+            |counts| counts.dropped,
+        ),
+    ];
+    for (name, help, field) in families {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+        for (kind, counts) in &stats.kinds {
+            let _ = writeln!(out, "{name}{{kind=\"{kind}\"}} {}", field(counts));
+        }
+    }
+    out
+}
+
+/// `points` as an SVG polyline scaled to the chart box.
+fn sparkline(points: &[f64]) -> String {
+    let (min, max) = points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        });
+    let span = if max > min { max - min } else { 1.0 };
+    let step = CHART_WIDTH / points.len().saturating_sub(1).max(1) as f64;
+    let mut coords = String::new();
+    for (i, value) in points.iter().enumerate() {
+        let x = i as f64 * step;
+        let y = CHART_HEIGHT - 2.0 - (value - min) / span * (CHART_HEIGHT - 4.0);
+        let _ = write!(coords, "{x:.1},{y:.1} ");
+    }
+    format!(
+        "<svg width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\"><polyline points=\"{}\"/></svg>",
+        coords.trim_end()
+    )
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else {
+        format!("{hours}h {minutes}m {}s", secs % 60)
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+type Latest = Arc<Mutex<Arc<StatusSnapshot>>>;
+
+/// The HTTP server thread and the snapshot it serves.
+pub(crate) struct StatusServer {
+    latest: Latest,
+    addr: SocketAddr,
+    #[cfg(feature = "http")]
+    server: Arc<tiny_http::Server>,
+    #[cfg(feature = "http")]
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl StatusServer {
+    /// Address the server actually listens on.
+    pub(crate) fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Serve `snapshot` from now on.
+    pub(crate) fn publish(&self, snapshot: StatusSnapshot) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Arc::new(snapshot);
+        }
+    }
+}
+
+#[cfg(not(feature = "http"))]
+impl StatusServer {
+    pub(crate) fn start(_bind: SocketAddr) -> Result<Self> {
+        anyhow::bail!("SPUD was built without the `http` feature")
+    }
+}
+
+#[cfg(feature = "http")]
+impl StatusServer {
+    /// Listen on `bind` and answer requests on a `spud-http` thread.
+    pub(crate) fn start(bind: SocketAddr) -> Result<Self> {
+        use anyhow::Context;
+
+        let server = tiny_http::Server::http(bind)
+            .map_err(|err| anyhow::anyhow!("failed to listen on {bind}: {err}"))?;
+        let server = Arc::new(server);
+        let addr = server.server_addr().to_ip().unwrap_or(bind);
+        let latest = Latest::default();
+        let thread = std::thread::Builder::new()
+            .name("spud-http".into())
+            .spawn({
+                let server = Arc::clone(&server);
+                let latest = Arc::clone(&latest);
+                move || serve(&server, &latest)
+            })
+            .context("failed to start the status page thread")?;
+        Ok(Self {
+            latest,
+            addr,
+            server,
+            thread: Some(thread),
+        })
+    }
+}
+
+#[cfg(feature = "http")]
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Answer requests until the server is unblocked.
+#[cfg(feature = "http")]
+fn serve(server: &tiny_http::Server, latest: &Latest) {
+    for request in server.incoming_requests() {
+        let snapshot = match latest.lock() {
+            Ok(latest) => Arc::clone(&latest),
+            Err(_) => return,
+        };
+        let reply = route(request.method().as_str(), request.url(), &snapshot);
+        let header = tiny_http::Header::from_bytes("Content-Type", reply.content_type)
+            .expect("static header is valid");
+        let response = tiny_http::Response::from_string(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header);
+        if let Err(err) = request.respond(response) {
+            tracing::debug!("status page response failed: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> StatusSnapshot {
+        StatusSnapshot {
+            version: "0.1.0",
+            uptime_secs: 3725,
+            tps: 10.0,
+            active_module: Some("stats".into()),
+            modules: vec![ModuleStatus {
+                id: "stats".into(),
+                title: "Stats".into(),
+            }],
+            plugins: vec![PluginStatus {
+                id: "acme.weather".into(),
+                version: "1.2.0".into(),
+                running: true,
+                health: Some(HealthStatus::Healthy),
+            }],
+            logs: vec![LogLine {
+                level: "WARN".into(),
+                target: "spud".into(),
+                message: "<b>loud</b>".into(),
+            }],
+            charts: vec![Chart {
+                source: "stats".into(),
+                key: "cpu".into(),
+                points: vec![10.0, 30.0, 20.0],
+            }],
+            plugin_stats: vec![PluginStats {
+                plugin_id: "acme.weather".into(),
+                running: true,
+                requests_handled: 7,
+                ..PluginStats::default()
+            }],
+            bus: None,
+        }
+    }
+
+    #[test]
+    fn serves_the_page_and_json_and_nothing_else() {
+        let snapshot = snapshot();
+        let page = route("GET", "/?from=bar", &snapshot);
+        assert_eq!(page.status, 200);
+        assert!(page.content_type.starts_with("text/html"));
+        assert!(page.body.contains("up 1h 2m 5s"));
+        assert!(page.body.contains("acme.weather"));
+        assert!(page.body.contains("&lt;b&gt;loud&lt;/b&gt;"));
+        assert!(page
+            .body
+            .contains("<polyline points=\"0.0,46.0 120.0,2.0 240.0,24.0\"/>"));
+
+        let json = route("GET", "/status.json", &snapshot);
+        let value: serde_json::Value = serde_json::from_str(&json.body).unwrap();
+        assert_eq!(value["active_module"], "stats");
+        assert_eq!(value["plugins"][0]["health"], "healthy");
+        assert_eq!(value["charts"][0]["points"][1], 30.0);
+
+        assert_eq!(route("GET", "/admin", &snapshot).status, 404);
+        assert_eq!(route("POST", "/", &snapshot).status, 405);
+    }
+
+    #[test]
+    fn serves_plugin_metrics() {
+        let metrics = route("GET", "/metrics", &snapshot());
+        assert_eq!(metrics.status, 200);
+        assert!(metrics
+            .content_type
+            .starts_with("text/plain; version=0.0.4"));
+        assert!(metrics
+            .body
+            .contains("spud_plugin_requests_total{plugin=\"acme.weather\"} 7"));
+    }
+
+    #[test]
+    fn serves_bus_metrics() {
+        let mut bus = spud_core::bus::EventBus::new();
+        bus.publish(spud_core::event::Event::Quit);
+        let snapshot = StatusSnapshot {
+            bus: Some(bus.stats()),
+            ..snapshot()
+        };
+        let body = route("GET", "/metrics", &snapshot).body;
+        assert!(body.contains("spud_plugin_requests_total{plugin=\"acme.weather\"} 7"));
+        assert!(body.contains("\nspud_bus_queue_depth 1\n"));
+        assert!(body.contains("\nspud_bus_blocked_total 0\n"));
+        assert!(body.contains("spud_bus_events_published_total{kind=\"quit\"} 1"));
+        assert!(body.contains("spud_bus_events_dropped_total{kind=\"quit\"} 0"));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn answers_over_http() {
+        use std::io::{Read, Write};
+
+        let server = StatusServer::start(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        server.publish(snapshot());
+        let mut stream = std::net::TcpStream::connect(server.addr()).unwrap();
+        stream
+            .write_all(b"GET /status.json HTTP/1.1\r\nHost: x\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("\"uptime_secs\": 3725"));
+        drop(server);
+    }
+}
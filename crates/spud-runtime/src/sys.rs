@@ -0,0 +1,44 @@
+//! `sys` console command: a one-shot system summary.
+//!
+//! The figures come from the stats module's latest sample, so `sys` works
+//! whichever module is active.
+
+use spud_core::command::CommandOutput;
+use spud_mod_stats::StatsModule;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "sys";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "sys";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Print a one-shot system summary";
+
+/// Run the `sys` command.
+pub(crate) fn execute(stats: Option<&StatsModule>, args: &[&str]) -> CommandOutput {
+    if !args.is_empty() {
+        return CommandOutput::Error(vec![format!("usage: {USAGE}")]);
+    }
+    match stats {
+        Some(stats) => CommandOutput::KeyValue(stats.system_summary()),
+        None => CommandOutput::Error(vec!["the stats module is not loaded".into()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_stats_module_and_no_args() {
+        assert!(matches!(execute(None, &[]), CommandOutput::Error(_)));
+        let stats = StatsModule::new();
+        assert!(matches!(
+            execute(Some(&stats), &["now"]),
+            CommandOutput::Error(_)
+        ));
+        let CommandOutput::KeyValue(rows) = execute(Some(&stats), &[]) else {
+            panic!("expected key/value output");
+        };
+        assert!(rows.iter().any(|(key, _)| key == "host"));
+    }
+}
@@ -0,0 +1,101 @@
+//! App-level `terminal` console command.
+//!
+//! Lives in the app because the terminal backend belongs to the app loop.
+//! A mode set here overrides `[terminal]` in `spud.toml` until that section
+//! changes. It is not saved with the session: the next run may be in a
+//! different terminal.
+
+use spud_config::SyncOutput;
+use spud_core::command::CommandOutput;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "terminal";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "terminal [sync auto|on|off | reset]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show or force synchronized output (DEC 2026)";
+
+/// Run a `terminal` subcommand. `sync` is the override; `configured` is the
+/// mode from `spud.toml`; `detected` names the terminal if it is known to
+/// support synchronized updates. The caller applies the mode.
+pub(crate) fn execute(
+    sync: &mut Option<SyncOutput>,
+    configured: SyncOutput,
+    detected: Option<&str>,
+    args: &[&str],
+) -> CommandOutput {
+    match args {
+        [] => {}
+        ["reset"] => *sync = None,
+        ["sync", value] => match value.parse() {
+            Ok(mode) => *sync = Some(mode),
+            Err(err) => return CommandOutput::Error(vec![format!("{err}")]),
+        },
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    }
+    describe(*sync, configured, detected)
+}
+
+fn describe(
+    sync: Option<SyncOutput>,
+    configured: SyncOutput,
+    detected: Option<&str>,
+) -> CommandOutput {
+    let mode = sync.unwrap_or(configured);
+    CommandOutput::KeyValue(vec![
+        ("sync".into(), mode.to_string()),
+        (
+            "active".into(),
+            if mode.enabled(detected.is_some()) {
+                "yes"
+            } else {
+                "no"
+            }
+            .to_string(),
+        ),
+        ("detected".into(), detected.unwrap_or("none").to_string()),
+        (
+            "source".into(),
+            if sync.is_some() {
+                "terminal command"
+            } else {
+                "spud.toml"
+            }
+            .to_string(),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(output: &CommandOutput, key: &str) -> String {
+        let CommandOutput::KeyValue(pairs) = output else {
+            panic!("expected key-value output");
+        };
+        pairs.iter().find(|(k, _)| k == key).unwrap().1.clone()
+    }
+
+    #[test]
+    fn forces_and_resets_the_mode() {
+        let mut sync = None;
+        let output = execute(&mut sync, SyncOutput::Auto, None, &[]);
+        assert_eq!(value(&output, "active"), "no");
+        assert_eq!(value(&output, "source"), "spud.toml");
+
+        let output = execute(&mut sync, SyncOutput::Auto, None, &["sync", "on"]);
+        assert_eq!(sync, Some(SyncOutput::On));
+        assert_eq!(value(&output, "active"), "yes");
+        assert_eq!(value(&output, "source"), "terminal command");
+
+        assert!(execute(&mut sync, SyncOutput::Auto, None, &["sync", "maybe"]).is_error());
+        assert!(execute(&mut sync, SyncOutput::Auto, None, &["bogus"]).is_error());
+        assert_eq!(sync, Some(SyncOutput::On));
+
+        let output = execute(&mut sync, SyncOutput::Auto, Some("kitty"), &["reset"]);
+        assert_eq!(sync, None);
+        assert_eq!(value(&output, "active"), "yes");
+        assert_eq!(value(&output, "detected"), "kitty");
+    }
+}
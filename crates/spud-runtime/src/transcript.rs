@@ -0,0 +1,195 @@
+//! Console transcripts and the app-level `transcript` command.
+//!
+//! With `transcript = true` in `spud.toml`, or after `transcript on`, every
+//! console command and its output is appended to one file per session in
+//! the log directory, apart from the tracing logs. The file reads as a
+//! record of what was typed and what came back, so it doubles as
+//! reproduction steps for bug reports:
+//!
+//! ```text
+//! # spud 0.1.0 console transcript (seed 42)
+//! [    3.204s] > switch stats
+//!                switch: stats
+//! [    9.120s] > frobnicate
+//!              ! unknown command: frobnicate
+//! ```
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use spud_core::command::CommandOutput;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "transcript";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "transcript [on|off]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show or toggle the console transcript file";
+
+/// Width of the `[    3.204s] ` timestamp column.
+const STAMP_WIDTH: usize = 13;
+
+/// The session's transcript file, open while transcription is enabled.
+pub(crate) struct Transcript {
+    path: PathBuf,
+    header: String,
+    started_at: Instant,
+    writer: Option<BufWriter<File>>,
+}
+
+impl Transcript {
+    /// A disabled transcript that will write to `path`, starting the file
+    /// with `header` and timing entries from `started_at`.
+    pub(crate) fn new(path: PathBuf, header: String, started_at: Instant) -> Self {
+        Self {
+            path,
+            header,
+            started_at,
+            writer: None,
+        }
+    }
+
+    /// File name for a session started at `session_ms` (Unix milliseconds).
+    pub(crate) fn file_name(session_ms: u64) -> String {
+        format!("transcript-{session_ms}.log")
+    }
+
+    /// Where the transcript is written.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether commands are currently being recorded.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Start or stop recording. Re-enabling appends to the same file.
+    pub(crate) fn set_enabled(&mut self, enabled: bool) -> Result<()> {
+        if !enabled {
+            if let Some(mut writer) = self.writer.take() {
+                writer.flush().ok();
+            }
+            return Ok(());
+        }
+        if self.writer.is_some() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open {}", self.path.display()))?;
+        let is_new = file.metadata().map(|meta| meta.len() == 0).unwrap_or(true);
+        let mut writer = BufWriter::new(file);
+        if is_new {
+            writeln!(writer, "# {}", self.header)
+                .and_then(|()| writer.flush())
+                .with_context(|| format!("failed to write {}", self.path.display()))?;
+        }
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    /// Append `input` and its `output`, if enabled. A write failure stops
+    /// the transcript and is logged rather than failing the command.
+    pub(crate) fn record(&mut self, input: &str, output: &CommandOutput, now: Instant) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        let elapsed = now
+            .checked_duration_since(self.started_at)
+            .unwrap_or(Duration::ZERO);
+        if let Err(err) = write_entry(writer, input, output, elapsed) {
+            tracing::warn!(path = %self.path.display(), error = %err, "transcript stopped");
+            self.writer = None;
+        }
+    }
+}
+
+fn write_entry(
+    writer: &mut impl Write,
+    input: &str,
+    output: &CommandOutput,
+    elapsed: Duration,
+) -> std::io::Result<()> {
+    let stamp = format!("[{:>9.3}s]", elapsed.as_secs_f64());
+    writeln!(writer, "{stamp} > {}", input.trim())?;
+    for part in output.parts() {
+        let marker = if part.is_error() { "!" } else { " " };
+        for line in part.to_lines() {
+            writeln!(writer, "{:STAMP_WIDTH$}{marker} {line}", "")?;
+        }
+    }
+    writer.flush()
+}
+
+/// Run the `transcript` command.
+pub(crate) fn execute(transcript: &mut Transcript, args: &[&str]) -> CommandOutput {
+    let enable = match args {
+        [] => None,
+        ["on"] => Some(true),
+        ["off"] => Some(false),
+        _ => return CommandOutput::Error(vec![format!("usage: {USAGE}")]),
+    };
+    if let Some(enabled) = enable {
+        if let Err(err) = transcript.set_enabled(enabled) {
+            return CommandOutput::Error(vec![format!("{err:#}")]);
+        }
+    }
+    let state = if transcript.is_enabled() { "on" } else { "off" };
+    CommandOutput::Lines(vec![format!(
+        "transcript {state}: {}",
+        transcript.path().display()
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_testkit::TestDir;
+
+    #[test]
+    fn records_commands_only_while_enabled() {
+        let dir = TestDir::new("transcript");
+        let path = dir.join(Transcript::file_name(1));
+        let start = Instant::now();
+        let mut transcript = Transcript::new(path.clone(), "test transcript".into(), start);
+
+        transcript.record("echo hidden", &CommandOutput::Lines(vec![]), start);
+        assert!(!path.exists());
+
+        assert!(!execute(&mut transcript, &["on"]).is_error());
+        transcript.record(
+            "switch stats",
+            &CommandOutput::Lines(vec!["switch: stats".into()]),
+            start + Duration::from_millis(3_204),
+        );
+        execute(&mut transcript, &["off"]);
+        transcript.record("echo off", &CommandOutput::Lines(vec![]), start);
+        execute(&mut transcript, &["on"]);
+        transcript.record(
+            "frobnicate",
+            &CommandOutput::Error(vec!["unknown command: frobnicate".into()]),
+            start + Duration::from_secs(9),
+        );
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            text,
+            "# test transcript\n\
+             [    3.204s] > switch stats\n\
+             \x20              switch: stats\n\
+             [    9.000s] > frobnicate\n\
+             \x20            ! unknown command: frobnicate\n"
+        );
+        assert!(execute(&mut transcript, &["maybe"]).is_error());
+    }
+}
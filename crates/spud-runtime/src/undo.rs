@@ -0,0 +1,93 @@
+//! App-level `undo` console command.
+//!
+//! Lives in the app because most inverses change app state: the `hud`,
+//! `console`, and `terminal` overrides, and plugin sessions. The stack
+//! itself is `spud_core::undo`; this module pops or lists it, and the app
+//! applies what it pops. It is not saved with the session.
+
+use std::time::Instant;
+
+use spud_core::command::CommandOutput;
+use spud_core::output::{Column, Table};
+use spud_core::undo::{UndoEntry, UndoStack};
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "undo";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "undo [list]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str =
+    "Reverse the last clear, setting change, or plugin stop; list what can be undone";
+
+/// Run an `undo` subcommand. A bare `undo` also returns the entry to
+/// reverse; the caller applies it, and reports an error instead of the
+/// output if that fails.
+pub(crate) fn execute(
+    stack: &mut UndoStack,
+    args: &[&str],
+    now: Instant,
+) -> (CommandOutput, Option<UndoEntry>) {
+    match args {
+        [] => match stack.pop(now) {
+            Some(entry) => (
+                CommandOutput::Lines(vec![format!("undone: {}", entry.label)]),
+                Some(entry),
+            ),
+            None => (CommandOutput::Lines(vec!["nothing to undo".into()]), None),
+        },
+        ["list"] => (list(stack, now), None),
+        _ => (CommandOutput::Error(vec![format!("usage: {USAGE}")]), None),
+    }
+}
+
+fn list(stack: &UndoStack, now: Instant) -> CommandOutput {
+    let mut table = Table::new(vec![
+        Column::left("ACTION"),
+        Column::right("AGO"),
+        Column::right("EXPIRES IN"),
+    ]);
+    for entry in stack.entries(now) {
+        table.push_row(vec![
+            entry.label.clone(),
+            format!("{}s", now.duration_since(entry.recorded_at).as_secs()),
+            entry.expires_at.map_or_else(
+                || "-".to_string(),
+                |at| format!("{}s", at.saturating_duration_since(now).as_secs()),
+            ),
+        ]);
+    }
+    if table.rows.is_empty() {
+        return CommandOutput::Lines(vec!["nothing to undo".into()]);
+    }
+    CommandOutput::Table(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_core::undo::{Inverse, CLEAR_GRACE};
+
+    #[test]
+    fn pops_and_lists_entries() {
+        let mut stack = UndoStack::default();
+        let now = Instant::now();
+        assert!(execute(&mut stack, &[], now).1.is_none());
+
+        stack.push(
+            "plugins stop demo",
+            Inverse::Run("plugins start demo".into()),
+            now,
+        );
+        stack.push_expiring("clear", Inverse::RestoreLogs(Vec::new()), now, CLEAR_GRACE);
+        let (CommandOutput::Table(table), None) = execute(&mut stack, &["list"], now) else {
+            panic!("expected a table");
+        };
+        assert_eq!(table.rows[0], ["clear", "0s", "60s"]);
+        assert_eq!(table.rows[1], ["plugins stop demo", "0s", "-"]);
+
+        let (output, entry) = execute(&mut stack, &[], now);
+        assert_eq!(output.to_lines(), ["undone: clear"]);
+        assert_eq!(entry.unwrap().label, "clear");
+        assert!(execute(&mut stack, &["bogus"], now).0.is_error());
+    }
+}
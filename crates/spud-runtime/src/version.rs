@@ -0,0 +1,106 @@
+//! `version` console command and build metadata.
+//!
+//! The git commit, build date, and feature list are embedded by `build.rs`.
+
+use std::sync::OnceLock;
+
+use spud_core::command::CommandOutput;
+use spud_remote::protocol::HOST_API_VERSION;
+
+use crate::doctor;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "version";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "version";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show version and build information";
+
+/// `<version> (<commit> <date>)`, used for `--version` and crash reports.
+pub const SUMMARY: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("SPUD_GIT_COMMIT"),
+    " ",
+    env!("SPUD_BUILD_DATE"),
+    ")"
+);
+
+/// `--version` output: the same facts as the `version` command.
+pub fn long() -> &'static str {
+    static LONG: OnceLock<String> = OnceLock::new();
+    LONG.get_or_init(|| {
+        let mut lines = vec![SUMMARY.to_string()];
+        lines.extend(execute(&[]).to_lines().into_iter().skip(1));
+        lines.join("\n")
+    })
+}
+
+/// The build facts shown in the help overlay.
+pub(crate) fn about() -> Vec<(String, String)> {
+    const KEYS: [&str; 5] = ["spud", "commit", "built", "host api", "graphics"];
+    let env = |key: &str| std::env::var(key).ok();
+    build_info(&doctor::graphics_protocols(&env))
+        .into_iter()
+        .filter(|(key, _)| KEYS.contains(&key.as_str()))
+        .collect()
+}
+
+/// Run the `version` command.
+pub(crate) fn execute(args: &[&str]) -> CommandOutput {
+    if !args.is_empty() {
+        return CommandOutput::Error(vec![format!("usage: {USAGE}")]);
+    }
+    let env = |key: &str| std::env::var(key).ok();
+    CommandOutput::KeyValue(build_info(&doctor::graphics_protocols(&env)))
+}
+
+/// Labelled build facts, in display order.
+fn build_info(graphics: &[&str]) -> Vec<(String, String)> {
+    let graphics = if graphics.is_empty() {
+        "text".to_string()
+    } else {
+        graphics.join(", ")
+    };
+    [
+        ("spud", env!("CARGO_PKG_VERSION")),
+        ("commit", env!("SPUD_GIT_COMMIT")),
+        ("built", env!("SPUD_BUILD_DATE")),
+        ("profile", profile()),
+        ("features", env!("SPUD_FEATURES")),
+        ("host api", HOST_API_VERSION),
+        ("spud-core", spud_core::VERSION),
+        ("spud-ui", spud_ui::VERSION),
+        ("spud-agent", spud_agent::VERSION),
+        ("spud-config", spud_config::VERSION),
+        ("spud-remote", spud_remote::VERSION),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value.to_string()))
+    .chain([("graphics".to_string(), graphics)])
+    .collect()
+}
+
+fn profile() -> &'static str {
+    if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_info_lists_versions_and_graphics() {
+        let info = build_info(&["kitty"]);
+        let get = |key: &str| info.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("spud"), Some(env!("CARGO_PKG_VERSION")));
+        assert_eq!(get("host api"), Some(HOST_API_VERSION));
+        assert_eq!(get("graphics"), Some("kitty"));
+        assert!(SUMMARY.starts_with(env!("CARGO_PKG_VERSION")));
+        assert_eq!(build_info(&[]).last().unwrap().1, "text");
+    }
+}
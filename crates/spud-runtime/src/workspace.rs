@@ -0,0 +1,244 @@
+//! Named workspaces and the app-level `workspace` console command.
+//!
+//! A workspace swaps the config dir for `<config dir>/workspaces/<name>/`
+//! and may narrow the module set and add plugin roots (see
+//! [`spud_config::workspace`]). Switching tears down every module and
+//! plugin session and builds them again, so it lives in the app next to the
+//! code that set them up.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use spud_config::workspace::{self, Workspace};
+use spud_core::command::CommandOutput;
+use spud_core::module::Module;
+use spud_core::registry::ModuleRegistry;
+use spud_core::rng::RngProvider;
+
+use crate::builder::{ModuleFactory, Options};
+use crate::doctor;
+
+/// Command name intercepted by the app console dispatcher.
+pub(crate) const NAME: &str = "workspace";
+/// Usage line shown in the help listing.
+pub(crate) const USAGE: &str = "workspace [list | switch <name> | leave]";
+/// Description shown in the help listing.
+pub(crate) const DESCRIPTION: &str = "Show, list, or switch named workspaces";
+
+/// Where a `workspace` command asked the app to go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Switch {
+    /// Into the named workspace.
+    To(Workspace),
+    /// Back to the plain config dir.
+    Leave,
+}
+
+/// Load workspace `name` from under the base config dir in `options`.
+pub(crate) fn load(options: &Options, name: &str) -> Result<Workspace> {
+    let Some(base) = options.config_dir() else {
+        bail!("no config directory; workspaces live under <config dir>/workspaces");
+    };
+    Workspace::load(&base, name)
+}
+
+/// `options` with the workspace's config dir and extra plugin roots.
+pub(crate) fn apply(options: &Options, workspace: Option<&Workspace>) -> Options {
+    let mut options = options.clone();
+    if let Some(workspace) = workspace {
+        options.config_dir = Some(workspace.dir.clone());
+        options.plugin_roots.extend(workspace.plugin_roots());
+    }
+    options
+}
+
+/// Directory for state kept across runs: per workspace when one is active.
+pub(crate) fn state_dir(workspace: Option<&Workspace>) -> Option<PathBuf> {
+    match workspace {
+        Some(workspace) => workspace.state_dir(),
+        None => spud_config::paths::state_dir(),
+    }
+}
+
+/// Build a registry from `factories`, keeping only and ordering by the
+/// workspace's `modules` list when it has one.
+pub(crate) fn build_registry(
+    rng: RngProvider,
+    factories: &[ModuleFactory],
+    workspace: Option<&Workspace>,
+) -> Result<ModuleRegistry> {
+    let mut modules: Vec<Box<dyn Module>> = factories.iter().map(ModuleFactory::build).collect();
+    if let Some(workspace) = workspace {
+        if let Some(ids) = &workspace.config.modules {
+            let available: Vec<&str> = modules.iter().map(|module| module.id()).collect();
+            let available = available.join(", ");
+            let mut picked = Vec::with_capacity(ids.len());
+            for id in ids {
+                let Some(pos) = modules.iter().position(|module| module.id() == id) else {
+                    bail!(
+                        "workspace {}: unknown or repeated module {id:?} (available: {available})",
+                        workspace.name
+                    );
+                };
+                picked.push(modules.remove(pos));
+            }
+            if picked.is_empty() {
+                bail!("workspace {}: modules list is empty", workspace.name);
+            }
+            modules = picked;
+        }
+    }
+    let mut registry = ModuleRegistry::with_rng(rng);
+    for module in modules {
+        registry.register(module)?;
+    }
+    Ok(registry)
+}
+
+/// Run the `workspace` command. Returns the output and where to switch, if
+/// anywhere; the app does the switch and reports failures.
+pub(crate) fn execute(
+    options: &Options,
+    current: Option<&Workspace>,
+    effective: &doctor::Settings,
+    args: &[&str],
+) -> (CommandOutput, Option<Switch>) {
+    match args {
+        [] => (CommandOutput::KeyValue(describe(current, effective)), None),
+        ["list"] => (list(options.config_dir().as_deref(), current), None),
+        ["switch", name] => {
+            if current.is_some_and(|workspace| workspace.name == *name) {
+                return (
+                    CommandOutput::Lines(vec![format!("already in workspace {name}")]),
+                    None,
+                );
+            }
+            match load(options, name) {
+                Ok(workspace) => (
+                    CommandOutput::Lines(Vec::new()),
+                    Some(Switch::To(workspace)),
+                ),
+                Err(err) => (CommandOutput::Error(vec![format!("{err:#}")]), None),
+            }
+        }
+        ["leave"] => match current {
+            Some(_) => (CommandOutput::Lines(Vec::new()), Some(Switch::Leave)),
+            None => (
+                CommandOutput::Lines(vec!["not in a workspace".to_string()]),
+                None,
+            ),
+        },
+        _ => (CommandOutput::Error(vec![format!("usage: {USAGE}")]), None),
+    }
+}
+
+fn describe(current: Option<&Workspace>, effective: &doctor::Settings) -> Vec<(String, String)> {
+    let path = |dir: Option<&Path>| {
+        dir.map(|dir| dir.display().to_string())
+            .unwrap_or_else(|| "(none)".to_string())
+    };
+    let modules = current
+        .and_then(|workspace| workspace.config.modules.as_ref())
+        .map(|ids| ids.join(", "))
+        .unwrap_or_else(|| "all".to_string());
+    vec![
+        (
+            "workspace".into(),
+            current.map_or_else(|| "(none)".to_string(), |w| w.name.clone()),
+        ),
+        ("config dir".into(), path(effective.config_dir.as_deref())),
+        ("state dir".into(), path(state_dir(current).as_deref())),
+        ("modules".into(), modules),
+        (
+            "plugin roots".into(),
+            effective.plugin_roots.len().to_string(),
+        ),
+    ]
+}
+
+fn list(base: Option<&Path>, current: Option<&Workspace>) -> CommandOutput {
+    let names = base.map(workspace::list).unwrap_or_default();
+    if names.is_empty() {
+        return CommandOutput::Lines(vec![
+            "no workspaces (create <config dir>/workspaces/<name>/)".to_string(),
+        ]);
+    }
+    CommandOutput::Lines(
+        names
+            .into_iter()
+            .map(|name| {
+                let marker = if current.is_some_and(|w| w.name == name) {
+                    "*"
+                } else {
+                    " "
+                };
+                format!("{marker} {name}")
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use spud_config::workspace::{WORKSPACES_DIR, WORKSPACE_FILE};
+    use spud_testkit::TestDir;
+
+    use super::*;
+
+    struct Named(&'static str);
+
+    impl Module for Named {
+        fn id(&self) -> &'static str {
+            self.0
+        }
+        fn title(&self) -> &'static str {
+            self.0
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn switches_and_filters_modules() {
+        let root = TestDir::new("workspace-cmd");
+        let work = root.join(WORKSPACES_DIR).join("work");
+        fs::create_dir_all(&work).unwrap();
+        fs::write(work.join(WORKSPACE_FILE), "modules = [\"b\"]\n").unwrap();
+        let options = Options {
+            config_dir: Some(root.to_path_buf()),
+            ..Options::default()
+        };
+        let effective = doctor::Settings::from_options(&options);
+
+        let (output, switch) = execute(&options, None, &effective, &["switch", "work"]);
+        let (list_output, _) = execute(&options, None, &effective, &["list"]);
+        let (missing, none) = execute(&options, None, &effective, &["switch", "home"]);
+
+        assert!(!output.is_error());
+        let Some(Switch::To(workspace)) = switch else {
+            panic!("expected a switch, got {switch:?}");
+        };
+        assert_eq!(list_output.to_lines(), vec!["  work"]);
+        assert!(missing.is_error());
+        assert_eq!(none, None);
+
+        let applied = apply(&options, Some(&workspace));
+        assert_eq!(applied.config_dir, Some(work));
+
+        let factories: Vec<ModuleFactory> = vec![
+            ModuleFactory::new(|| Box::new(Named("a"))),
+            ModuleFactory::new(|| Box::new(Named("b"))),
+        ];
+        let rng = RngProvider::new(1);
+        let registry = build_registry(rng, &factories, Some(&workspace)).unwrap();
+        assert_eq!(registry.list(), vec![("b", "b")]);
+        assert_eq!(build_registry(rng, &factories, None).unwrap().len(), 2);
+
+        let (output, switch) = execute(&options, Some(&workspace), &effective, &["leave"]);
+        assert!(!output.is_error());
+        assert_eq!(switch, Some(Switch::Leave));
+    }
+}
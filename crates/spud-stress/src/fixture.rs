@@ -0,0 +1,169 @@
+//! Chatty fixture plugins.
+//!
+//! The fixtures are this binary again, started by the host through the
+//! hidden `plugin` subcommand. Each one publishes a burst of custom events
+//! on every tick, reads telemetry back, and switches the active module
+//! every few ticks, so the plugin pump, permission checks, and module
+//! activation all run under load.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use spud_plugin_sdk::{EventCategory, GetTelemetryParams, Plugin};
+
+/// Tag of the custom events the fixtures publish.
+const EVENT_TAG: &str = "stress.chatter";
+/// Custom events each fixture publishes per tick.
+const EVENTS_PER_TICK: u64 = 20;
+/// Ticks between module switches.
+const SWITCH_EVERY: u64 = 5;
+/// Ticks between telemetry reads.
+const TELEMETRY_EVERY: u64 = 10;
+
+/// Id of fixture `n`.
+pub(crate) fn plugin_id(n: usize) -> String {
+    format!("stress.chatty{n}")
+}
+
+/// Write one `plugin.toml` per fixture under `root`, each starting `exe`
+/// in plugin mode and switching between `modules`.
+pub(crate) fn write_plugins(root: &Path, count: usize, exe: &Path, modules: &[&str]) -> Result<()> {
+    for n in 0..count {
+        let id = plugin_id(n);
+        let dir = root.join(&id);
+        fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+        fs::write(dir.join("plugin.toml"), manifest(&id, exe, modules))
+            .with_context(|| format!("writing the manifest of {id}"))?;
+    }
+    Ok(())
+}
+
+fn manifest(id: &str, exe: &Path, modules: &[&str]) -> String {
+    // JSON string escapes are valid in TOML basic strings.
+    let quote = |text: &str| json!(text).to_string();
+    let args = json!(["plugin", "--id", id, "--modules", modules.join(",")]);
+    format!(
+        r#"id = {id}
+name = "Stress fixture"
+version = "0.1.0"
+
+[runtime]
+entrypoint = {entrypoint}
+args = {args}
+
+[compatibility]
+host_api = "^1.0.0"
+
+[permissions]
+commands = ["switch"]
+event_tags = [{tag}]
+subscriptions = ["tick", "telemetry", "custom"]
+
+[health]
+ping_interval_ms = 1000
+"#,
+        id = quote(id),
+        tag = quote(EVENT_TAG),
+        entrypoint = quote(&exe.to_string_lossy()),
+    )
+}
+
+/// Run as fixture `id` over stdio until the host closes the pipe.
+pub(crate) fn run_plugin(id: &str, modules: &[String]) -> Result<()> {
+    let modules = modules.to_vec();
+    let mut ticks: u64 = 0;
+    Plugin::new(id, "0.1.0")
+        .on(EventCategory::Tick, move |client, _| {
+            ticks += 1;
+            for n in 0..EVENTS_PER_TICK {
+                let payload = json!({ "tick": ticks, "n": n }).to_string();
+                client.publish_event(EVENT_TAG, &payload)?;
+            }
+            if ticks.is_multiple_of(TELEMETRY_EVERY) {
+                client.get_telemetry(&GetTelemetryParams::default())?;
+            }
+            if ticks.is_multiple_of(SWITCH_EVERY) && !modules.is_empty() {
+                let target = &modules[(ticks / SWITCH_EVERY) as usize % modules.len()];
+                client.invoke_command("switch", &[target])?;
+            }
+            Ok(())
+        })
+        .on(EventCategory::Telemetry, |_, _| Ok(()))
+        .on(EventCategory::Custom, |_, _| Ok(()))
+        .run_stdio()
+        .with_context(|| format!("fixture plugin {id}"))
+}
+
+/// Scratch directory for the run's config, plugin manifests, logs, and
+/// state, removed on drop.
+pub(crate) struct Scratch {
+    path: PathBuf,
+}
+
+impl Scratch {
+    /// Create the directory and point `SPUD_LOG_DIR` and `SPUD_STATE_DIR`
+    /// into it, so the run neither writes the user's logs and session nor
+    /// takes the instance lock of a SPUD they have open. Call before any
+    /// thread starts.
+    pub(crate) fn new() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("spud-stress-{}", std::process::id()));
+        fs::create_dir_all(path.join("config"))
+            .with_context(|| format!("creating {}", path.display()))?;
+        std::env::set_var("SPUD_LOG_DIR", path.join("logs"));
+        std::env::set_var("SPUD_STATE_DIR", path.join("state"));
+        Ok(Self { path })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Empty config directory, so the user's `spud.toml` is not read.
+    pub(crate) fn config_dir(&self) -> PathBuf {
+        self.path.join("config")
+    }
+
+    pub(crate) fn plugin_root(&self) -> PathBuf {
+        self.path.join("plugins")
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        remove_scratch(&self.path);
+    }
+}
+
+/// Remove a scratch directory when the process exits without dropping
+/// its [`Scratch`].
+pub(crate) fn remove_scratch(path: &Path) {
+    let _ = fs::remove_dir_all(path);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_config::PluginManifest;
+
+    #[test]
+    fn manifests_are_valid_and_start_this_binary() {
+        let exe = Path::new("/opt/spud \"x\"/spud-stress");
+        let raw = manifest("stress.chatty0", exe, &["hello", "stress"]);
+        let manifest = PluginManifest::from_toml_str(&raw).unwrap();
+        assert_eq!(manifest.id, "stress.chatty0");
+        assert_eq!(manifest.runtime.entrypoint, exe.to_string_lossy());
+        assert_eq!(
+            manifest.runtime.args,
+            [
+                "plugin",
+                "--id",
+                "stress.chatty0",
+                "--modules",
+                "hello,stress"
+            ]
+        );
+        assert_eq!(manifest.permissions.commands, ["switch"]);
+    }
+}
@@ -0,0 +1,228 @@
+//! The load module: synthetic telemetry and log volume from inside the
+//! runtime, the progress the watchdog and the final checks read, and the
+//! end of the run.
+
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use spud_core::event::{Event, Histogram, PluginStatus, TelemetryValue};
+use spud_core::module::Module;
+
+/// Module id, also a target for the fixture plugins' `switch` commands.
+pub(crate) const ID: &str = "stress";
+
+/// How much load the module makes.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LoadSettings {
+    /// How long to run before publishing `Quit`.
+    pub duration: Duration,
+    pub logs_per_sec: u64,
+    /// Telemetry events published per tick.
+    pub telemetry_per_tick: usize,
+    /// Distinct telemetry keys the events rotate through.
+    pub telemetry_keys: usize,
+}
+
+/// What the load module saw, shared with the watchdog and `main`.
+#[derive(Debug, Default)]
+pub(crate) struct Progress {
+    /// Ticks seen; the watchdog's heartbeat.
+    pub ticks: AtomicU64,
+    /// Plugins that reached `Running`.
+    pub plugins_up: Mutex<BTreeSet<String>>,
+    /// `<plugin>: <status>` for plugins that failed or exited mid-run.
+    pub plugins_lost: Mutex<Vec<String>>,
+}
+
+pub(crate) struct LoadModule {
+    settings: LoadSettings,
+    progress: Arc<Progress>,
+    started: Option<Instant>,
+    logs_sent: u64,
+    samples_sent: usize,
+    latency: Histogram,
+    pending: Vec<Event>,
+    quitting: bool,
+}
+
+impl LoadModule {
+    pub(crate) fn new(settings: LoadSettings, progress: Arc<Progress>) -> Self {
+        Self {
+            settings,
+            progress,
+            started: None,
+            logs_sent: 0,
+            samples_sent: 0,
+            latency: Histogram::new([1.0, 5.0, 25.0, 100.0]),
+            pending: Vec::new(),
+            quitting: false,
+        }
+    }
+
+    fn on_tick(&mut self, now: Instant) {
+        self.progress.ticks.fetch_add(1, Ordering::Relaxed);
+        let started = *self.started.get_or_insert(now);
+        let elapsed = now.checked_duration_since(started).unwrap_or_default();
+
+        let due = logs_due(self.settings.logs_per_sec, elapsed, self.logs_sent);
+        for _ in 0..due {
+            self.logs_sent += 1;
+            tracing::info!(target: "spud_stress", n = self.logs_sent, "synthetic log line");
+        }
+
+        for _ in 0..self.settings.telemetry_per_tick {
+            let n = self.samples_sent;
+            self.samples_sent = self.samples_sent.wrapping_add(1);
+            self.latency.observe((n % 120) as f64);
+            let value = match n % 4 {
+                0 => TelemetryValue::Float(n as f64 / 7.0),
+                1 => TelemetryValue::Int(n as i64),
+                2 => TelemetryValue::Bool(n % 8 == 2),
+                _ => TelemetryValue::Histogram(self.latency.clone()),
+            };
+            self.pending.push(Event::Telemetry {
+                source: ID.to_string(),
+                key: format!("k{}", n % self.settings.telemetry_keys.max(1)),
+                value,
+            });
+        }
+
+        if elapsed >= self.settings.duration && !self.quitting {
+            self.quitting = true;
+            tracing::info!(
+                target: "spud_stress",
+                logs = self.logs_sent,
+                samples = self.samples_sent,
+                "stress run finished"
+            );
+            self.pending.push(Event::Quit);
+        }
+    }
+
+    fn on_plugin_status(&mut self, plugin_id: &str, status: &PluginStatus) {
+        match status {
+            PluginStatus::Starting => {}
+            PluginStatus::Running => {
+                if let Ok(mut up) = self.progress.plugins_up.lock() {
+                    up.insert(plugin_id.to_string());
+                }
+            }
+            PluginStatus::Failed(reason) => self.lose(format!("{plugin_id}: failed: {reason}")),
+            PluginStatus::Exited => self.lose(format!("{plugin_id}: exited")),
+        }
+    }
+
+    fn lose(&self, what: String) {
+        tracing::error!(target: "spud_stress", "fixture plugin lost: {what}");
+        if let Ok(mut lost) = self.progress.plugins_lost.lock() {
+            lost.push(what);
+        }
+    }
+}
+
+/// Log lines still owed after `elapsed` at `per_sec`, when `sent` went out
+/// already. Capped at one second's worth, so a stalled loop does not come
+/// back to a burst.
+fn logs_due(per_sec: u64, elapsed: Duration, sent: u64) -> u64 {
+    let owed = (per_sec as f64 * elapsed.as_secs_f64()) as u64;
+    owed.saturating_sub(sent).min(per_sec)
+}
+
+impl Module for LoadModule {
+    fn id(&self) -> &'static str {
+        ID
+    }
+
+    fn title(&self) -> &'static str {
+        "Stress"
+    }
+
+    fn handle_event(&mut self, ev: &Event) {
+        match ev {
+            Event::Tick { now } => self.on_tick(*now),
+            Event::PluginStatus { plugin_id, status } => self.on_plugin_status(plugin_id, status),
+            _ => {}
+        }
+    }
+
+    fn drain_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.pending)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logs_keep_pace_without_bursting() {
+        assert_eq!(logs_due(1_000, Duration::from_millis(250), 0), 250);
+        assert_eq!(logs_due(1_000, Duration::from_millis(250), 250), 0);
+        assert_eq!(logs_due(1_000, Duration::from_secs(30), 10), 1_000);
+    }
+
+    #[test]
+    fn publishes_telemetry_and_quits_once_after_the_duration() {
+        let progress = Arc::new(Progress::default());
+        let settings = LoadSettings {
+            duration: Duration::from_secs(1),
+            logs_per_sec: 0,
+            telemetry_per_tick: 8,
+            telemetry_keys: 3,
+        };
+        let mut module = LoadModule::new(settings, progress.clone());
+        let start = Instant::now();
+
+        module.handle_event(&Event::Tick { now: start });
+        let events = module.drain_events();
+        assert_eq!(events.len(), 8);
+        assert!(events
+            .iter()
+            .all(|ev| matches!(ev, Event::Telemetry { key, .. } if ["k0", "k1", "k2"].contains(&key.as_str()))));
+
+        for later in [1, 2] {
+            module.handle_event(&Event::Tick {
+                now: start + Duration::from_secs(later),
+            });
+        }
+        let quits = module
+            .drain_events()
+            .iter()
+            .filter(|ev| matches!(ev, Event::Quit))
+            .count();
+        assert_eq!(quits, 1);
+        assert_eq!(progress.ticks.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn records_plugins_that_come_up_and_that_are_lost() {
+        let progress = Arc::new(Progress::default());
+        let settings = LoadSettings {
+            duration: Duration::from_secs(60),
+            logs_per_sec: 0,
+            telemetry_per_tick: 0,
+            telemetry_keys: 1,
+        };
+        let mut module = LoadModule::new(settings, progress.clone());
+        for (plugin_id, status) in [
+            ("a", PluginStatus::Starting),
+            ("a", PluginStatus::Running),
+            ("b", PluginStatus::Running),
+            ("b", PluginStatus::Exited),
+        ] {
+            module.handle_event(&Event::PluginStatus {
+                plugin_id: plugin_id.into(),
+                status,
+            });
+        }
+        assert_eq!(progress.plugins_up.lock().unwrap().len(), 2);
+        assert_eq!(*progress.plugins_lost.lock().unwrap(), ["b: exited"]);
+    }
+}
@@ -0,0 +1,203 @@
+//! `spud-stress`: a soak test for the SPUD runtime.
+//!
+//! Runs the headless runtime with the first-party modules, a load module
+//! that floods it with telemetry and log lines, and several chatty fixture
+//! plugins that publish events and switch modules, for a fixed duration.
+//! Fails if the main loop stops ticking (see [`watch`]), a fixture plugin
+//! fails or exits, or resident memory grows by more than `--max-growth-mb`
+//! after the warmup.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand};
+use spud_mod_hello::HelloModule;
+use spud_mod_plugins::PluginsModule;
+use spud_mod_stats::StatsModule;
+use spud_runtime::RuntimeBuilder;
+
+mod fixture;
+mod load;
+mod watch;
+
+use load::{LoadModule, LoadSettings, Progress};
+use watch::{WatchSettings, Watchdog};
+
+/// Modules the fixture plugins switch between.
+const MODULES: [&str; 4] = ["hello", "stats", "plugins", load::ID];
+
+/// Soak-test the SPUD runtime under synthetic load.
+#[derive(Debug, Parser)]
+#[command(name = "spud-stress")]
+struct Cli {
+    /// How long to run, e.g. `90s` or `10m`
+    #[arg(long, default_value = "2m", value_parser = duration)]
+    duration: Duration,
+
+    /// Chatty fixture plugins to start
+    #[arg(long, default_value_t = 3)]
+    plugins: usize,
+
+    /// Log lines the load module writes per second
+    #[arg(long, default_value_t = 2_000)]
+    logs_per_sec: u64,
+
+    /// Telemetry events the load module publishes per tick
+    #[arg(long, default_value_t = 200)]
+    telemetry_per_tick: usize,
+
+    /// Distinct telemetry keys the events rotate through
+    #[arg(long, default_value_t = 500)]
+    telemetry_keys: usize,
+
+    /// Most resident memory may grow after the warmup, in MiB
+    #[arg(long, default_value_t = 64)]
+    max_growth_mb: u64,
+
+    /// Time without a tick after which the run counts as deadlocked
+    #[arg(long, default_value = "10s", value_parser = duration)]
+    stall_timeout: Duration,
+
+    /// Seed for random behaviour, so a failing run can be repeated
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    #[command(subcommand)]
+    command: Option<StressCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+enum StressCommand {
+    /// Run as a fixture plugin; started by the host, not by hand
+    #[command(hide = true)]
+    Plugin {
+        #[arg(long)]
+        id: String,
+        /// Comma-separated module ids to switch between
+        #[arg(long, value_delimiter = ',')]
+        modules: Vec<String>,
+    },
+}
+
+fn duration(raw: &str) -> Result<Duration, String> {
+    spud_config::alerts::parse_duration(raw).map_err(|err| err.to_string())
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if let Some(StressCommand::Plugin { id, modules }) = &cli.command {
+        return fixture::run_plugin(id, modules);
+    }
+
+    let scratch = fixture::Scratch::new()?;
+    let exe: PathBuf = std::env::current_exe().context("locating the spud-stress binary")?;
+    fixture::write_plugins(&scratch.plugin_root(), cli.plugins, &exe, &MODULES)?;
+
+    let settings = LoadSettings {
+        duration: cli.duration,
+        logs_per_sec: cli.logs_per_sec,
+        telemetry_per_tick: cli.telemetry_per_tick,
+        telemetry_keys: cli.telemetry_keys,
+    };
+    let progress = Arc::new(Progress::default());
+    let watchdog = Watchdog::start(
+        WatchSettings {
+            interval: Duration::from_secs(1),
+            warmup: (cli.duration / 5).min(Duration::from_secs(30)),
+            stall_timeout: cli.stall_timeout,
+        },
+        progress.clone(),
+        {
+            let path = scratch.path().to_path_buf();
+            move || fixture::remove_scratch(&path)
+        },
+    );
+
+    let run = RuntimeBuilder::new()
+        .with_module(HelloModule::new)
+        .with_module(StatsModule::new)
+        .with_module(PluginsModule::new)
+        .with_module({
+            let progress = progress.clone();
+            move || LoadModule::new(settings, progress.clone())
+        })
+        .with_config_dir(scratch.config_dir())
+        .with_plugins([scratch.plugin_root()])
+        .with_seed(cli.seed)
+        .fresh(true)
+        .headless(true)
+        .run();
+    let report = watchdog.finish();
+    run.context("runtime stopped with an error")?;
+
+    const MIB: u64 = 1024 * 1024;
+    println!(
+        "spud-stress: {} ticks in {:?}; resident memory {} MiB at baseline, {} MiB peak, {} MiB growth over {} samples",
+        report.ticks,
+        cli.duration,
+        report.baseline.unwrap_or(0) / MIB,
+        report.peak / MIB,
+        report.growth() / MIB,
+        report.samples,
+    );
+    if report.ticks == 0 {
+        bail!("the runtime never ticked");
+    }
+    let lost = progress
+        .plugins_lost
+        .lock()
+        .map(|lost| lost.clone())
+        .unwrap_or_default();
+    if !lost.is_empty() {
+        bail!("fixture plugins lost mid-run: {}", lost.join("; "));
+    }
+    let up = progress
+        .plugins_up
+        .lock()
+        .map(|up| up.len())
+        .unwrap_or_default();
+    if up < cli.plugins {
+        bail!("only {up} of {} fixture plugins started", cli.plugins);
+    }
+    if report.growth() > cli.max_growth_mb * MIB {
+        bail!(
+            "resident memory grew by {} MiB, over the {} MiB limit",
+            report.growth() / MIB,
+            cli.max_growth_mb
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_fixture_mode() {
+        let cli = Cli::try_parse_from([
+            "spud-stress",
+            "plugin",
+            "--id",
+            "stress.chatty0",
+            "--modules",
+            "hello,stress",
+        ])
+        .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(StressCommand::Plugin { ref id, ref modules })
+                if id == "stress.chatty0" && modules == &["hello", "stress"]
+        ));
+        let cli = Cli::try_parse_from(["spud-stress", "--duration", "30s"]).unwrap();
+        assert_eq!(cli.duration, Duration::from_secs(30));
+    }
+}
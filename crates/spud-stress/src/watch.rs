@@ -0,0 +1,155 @@
+//! Watchdog: memory samples and deadlock detection.
+//!
+//! A background thread samples the process's resident memory once per
+//! `interval` and checks the load module's tick count. When the
+//! heartbeat stops moving for `stall_timeout` the main loop is taken to be
+//! deadlocked and the process exits with status 2, as the runtime can no
+//! longer be asked to stop. Exiting skips destructors, so the watchdog runs
+//! an `on_stall` cleanup first.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::load::Progress;
+
+/// Exit status for a run that stopped ticking.
+pub(crate) const STALLED: i32 = 2;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WatchSettings {
+    /// Time between samples.
+    pub interval: Duration,
+    /// Samples before this are left out of the baseline, so startup
+    /// allocations and warm caches do not count as growth.
+    pub warmup: Duration,
+    pub stall_timeout: Duration,
+}
+
+/// Memory seen over a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct MemoryReport {
+    /// Resident bytes at the first sample after the warmup.
+    pub baseline: Option<u64>,
+    /// Most resident bytes at any sample after the warmup.
+    pub peak: u64,
+    pub samples: u64,
+    /// Ticks the load module saw.
+    pub ticks: u64,
+}
+
+impl MemoryReport {
+    fn record(&mut self, rss: u64) {
+        self.samples += 1;
+        self.baseline.get_or_insert(rss);
+        self.peak = self.peak.max(rss);
+    }
+
+    /// Growth from the baseline to the peak, in bytes.
+    pub(crate) fn growth(&self) -> u64 {
+        self.baseline
+            .map_or(0, |baseline| self.peak.saturating_sub(baseline))
+    }
+}
+
+pub(crate) struct Watchdog {
+    done: Arc<AtomicBool>,
+    progress: Arc<Progress>,
+    thread: JoinHandle<MemoryReport>,
+}
+
+impl Watchdog {
+    /// Start watching. `on_stall` runs before the process exits on a stall.
+    pub(crate) fn start(
+        settings: WatchSettings,
+        progress: Arc<Progress>,
+        on_stall: impl FnOnce() + Send + 'static,
+    ) -> Self {
+        let done = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let done = done.clone();
+            let progress = progress.clone();
+            thread::Builder::new()
+                .name("spud-stress-watchdog".into())
+                .spawn(move || watch(settings, &done, &progress, on_stall))
+                .expect("watchdog thread starts")
+        };
+        Self {
+            done,
+            progress,
+            thread,
+        }
+    }
+
+    /// Stop sampling and return what was seen.
+    pub(crate) fn finish(self) -> MemoryReport {
+        self.done.store(true, Ordering::Relaxed);
+        let mut report = self.thread.join().unwrap_or_default();
+        report.ticks = self.progress.ticks.load(Ordering::Relaxed);
+        report
+    }
+}
+
+fn watch(
+    settings: WatchSettings,
+    done: &AtomicBool,
+    progress: &Progress,
+    on_stall: impl FnOnce(),
+) -> MemoryReport {
+    let pid = Pid::from_u32(std::process::id());
+    let mut sys = System::new();
+    let started = Instant::now();
+    let mut report = MemoryReport::default();
+    let mut last_beat = (progress.ticks.load(Ordering::Relaxed), started);
+
+    while !done.load(Ordering::Relaxed) {
+        thread::sleep(settings.interval);
+        let now = Instant::now();
+
+        let beat = progress.ticks.load(Ordering::Relaxed);
+        if beat != last_beat.0 {
+            last_beat = (beat, now);
+        } else if now
+            .checked_duration_since(last_beat.1)
+            .is_some_and(|quiet| quiet >= settings.stall_timeout)
+            && !done.load(Ordering::Relaxed)
+        {
+            eprintln!(
+                "spud-stress: no tick for {:?} after {beat} ticks; the main loop looks deadlocked",
+                settings.stall_timeout
+            );
+            on_stall();
+            std::process::exit(STALLED);
+        }
+
+        if now.checked_duration_since(started).unwrap_or_default() < settings.warmup {
+            continue;
+        }
+        sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), false);
+        if let Some(process) = sys.process(pid) {
+            report.record(process.memory());
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn growth_is_measured_from_the_first_sample() {
+        let mut report = MemoryReport::default();
+        assert_eq!(report.growth(), 0);
+        for rss in [100, 90, 160, 120] {
+            report.record(rss);
+        }
+        assert_eq!(report.baseline, Some(100));
+        assert_eq!(report.peak, 160);
+        assert_eq!(report.growth(), 60);
+        assert_eq!(report.samples, 4);
+    }
+}
@@ -0,0 +1,150 @@
+//! Test helpers for SPUD's rendering snapshots and file fixtures.
+//!
+//! [`render`] draws into a ratatui `TestBackend` and returns the screen as
+//! plain text, one line per row, ready for `insta::assert_snapshot!`.
+//! [`normalize`] masks values that change from run to run (TPS, span
+//! durations, uptime, load averages, clock times), so snapshots only change
+//! when the layout does. [`TestDir`] is a scratch directory for tests that
+//! touch the filesystem.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ratatui::{backend::TestBackend, buffer::Buffer, Frame, Terminal};
+use regex::Regex;
+
+/// Draw a `width`×`height` frame with `draw` and return it as text.
+pub fn render(width: u16, height: u16, draw: impl FnOnce(&mut Frame)) -> String {
+    let mut terminal =
+        Terminal::new(TestBackend::new(width, height)).expect("test backend never fails");
+    terminal.draw(draw).expect("test backend never fails");
+    buffer_text(terminal.backend().buffer())
+}
+
+/// The symbols of `buffer`, one line per row, with trailing spaces trimmed.
+pub fn buffer_text(buffer: &Buffer) -> String {
+    (0..buffer.area.height)
+        .map(|y| {
+            let row: String = (0..buffer.area.width)
+                .map(|x| buffer[(buffer.area.x + x, buffer.area.y + y)].symbol())
+                .collect();
+            row.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Patterns for values that change from run to run.
+static DYNAMIC: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    [
+        r"TPS: \d+(\.\d+)?",
+        r"\bup (\d+[dhms] ?)*\d+[dhms]",
+        r"\bload \d+\.\d+ \d+\.\d+ \d+\.\d+",
+        r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?",
+        r"\b\d{2}:\d{2}:\d{2}(\.\d+)?\b",
+        r"\b\d+(\.\d+)?(us|ms|s)\b",
+    ]
+    .into_iter()
+    .map(|pattern| Regex::new(pattern).expect("normalize patterns are valid"))
+    .collect()
+});
+
+/// Mask run-to-run values in rendered `text`: every digit of a TPS reading,
+/// uptime, load averages, timestamp, clock time, or duration (as printed by
+/// `spud_core::spans::format_duration`) becomes `#`. Widths are kept, so
+/// borders stay where they were drawn.
+pub fn normalize(text: &str) -> String {
+    DYNAMIC.iter().fold(text.to_string(), |text, pattern| {
+        pattern
+            .replace_all(&text, |caps: &regex::Captures<'_>| {
+                caps[0].replace(|c: char| c.is_ascii_digit(), "#")
+            })
+            .into_owned()
+    })
+}
+
+static TEST_DIR_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Unique temporary directory, removed on drop, so it is cleaned up even
+/// when an assert fails.
+pub struct TestDir {
+    pub path: PathBuf,
+}
+
+impl TestDir {
+    /// Create `spud-<name>-…` under the system temp dir. The name is made
+    /// unique per process, time, and call, so tests can run in parallel.
+    pub fn new(name: &str) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos());
+        let counter = TEST_DIR_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "spud-{name}-{}-{nanos}-{counter}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&path).expect("temp dir is writable");
+        Self { path }
+    }
+}
+
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::widgets::Paragraph;
+
+    #[test]
+    fn renders_rows_without_trailing_spaces() {
+        let text = render(8, 3, |f| {
+            f.render_widget(Paragraph::new("ab\n\ncd"), f.area());
+        });
+        assert_eq!(text, "ab\n\ncd");
+    }
+
+    #[test]
+    fn normalize_masks_dynamic_values() {
+        let text = "CONSOLE  TPS: 59.8  SLOW: tick 12.3ms  SYSTEM STATS · up 3d 4h 5m · \
+                    load 0.52 1.10 0.98  at 2026-10-16T08:15:42Z, 08:15:42, 850us, 1.20s";
+        assert_eq!(
+            normalize(text),
+            "CONSOLE  TPS: ##.#  SLOW: tick ##.#ms  SYSTEM STATS · up #d #h #m · \
+             load #.## #.## #.##  at ####-##-##T##:##:##Z, ##:##:##, ###us, #.##s"
+        );
+    }
+
+    #[test]
+    fn test_dirs_are_unique_and_removed_on_drop() {
+        let first = TestDir::new("testkit");
+        let second = TestDir::new("testkit");
+        assert_ne!(first.path, second.path);
+        fs::write(first.join("file"), "x").unwrap();
+
+        let path = first.path.clone();
+        drop(first);
+        assert!(!path.exists());
+        assert!(second.path.is_dir());
+    }
+}
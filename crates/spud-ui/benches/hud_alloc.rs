@@ -0,0 +1,201 @@
+//! Heap allocations per frame spent building the shell's [`ShellView`].
+//!
+//! Draws the same shell two ways: copying every label, key hint, and agent
+//! face line into fresh collections each frame, as the app used to, and
+//! borrowing them with reused buffers, as it does now. Prints both counts
+//! and fails if borrowing stops saving allocations.
+//!
+//! ```sh
+//! cargo bench -p spud-ui --bench hud_alloc
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ratatui::{backend::TestBackend, Terminal};
+use spud_config::FaceConfig;
+use spud_core::keymap::{KeyBinding, KeyChord, Keymap};
+use spud_core::module::{HudContribution, HudLine, HudSpan, HudStyle};
+use spud_ui::layout::doom_layout;
+use spud_ui::shell::{render_shell, ModuleTab, ShellView};
+
+/// Counts every allocation and reallocation.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const FRAMES: usize = 1_000;
+const MODULES: [(&str, &str); 3] = [
+    ("hello", "Hello"),
+    ("stats", "Stats"),
+    ("plugins", "Plugins"),
+];
+
+/// What the app has on hand when it draws a frame.
+struct Scene {
+    keymap: Keymap,
+    face: Vec<String>,
+    status: String,
+}
+
+impl Scene {
+    fn new() -> Self {
+        let mut keymap = Keymap::new(vec![
+            KeyBinding::new(KeyChord::char('`'), "console", "console"),
+            KeyBinding::new(KeyChord::char('q'), "quit", "quit"),
+        ]);
+        keymap.set_module(vec![KeyBinding::new(
+            KeyChord::char('h'),
+            "heatmap",
+            "toggle per-core heatmap",
+        )]);
+        Self {
+            keymap,
+            face: vec![
+                " .---. ".to_string(),
+                "( o o )".to_string(),
+                " \\ - / ".to_string(),
+            ],
+            status: "MODULE: Stats".to_string(),
+        }
+    }
+
+    /// A module's HUD with one computed value and fixed labels, built with
+    /// owned strings or borrowed ones.
+    fn module_hud(frame: usize, owned: bool) -> HudContribution {
+        let label = |text: &'static str| -> HudLine {
+            if owned {
+                HudLine::styled(text.to_string(), HudStyle::Dim)
+            } else {
+                HudLine::styled(text, HudStyle::Dim)
+            }
+        };
+        HudContribution {
+            left_lines: vec![HudLine::key_value(
+                "CPU:",
+                format!("{}%", frame % 100),
+                HudStyle::Normal,
+            )],
+            right_lines: vec![label("HMR: (planned)"), label("IMG: (planned)")],
+        }
+    }
+}
+
+/// Allocations per frame, drawing with `draw` for [`FRAMES`] frames.
+fn measure(mut draw: impl FnMut(&mut Terminal<TestBackend>, usize)) -> f64 {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    // Warm up buffers that live across frames
+    draw(&mut terminal, 0);
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for frame in 0..FRAMES {
+        draw(&mut terminal, frame);
+    }
+    (ALLOCATIONS.load(Ordering::Relaxed) - before) as f64 / FRAMES as f64
+}
+
+fn main() {
+    let scene = Scene::new();
+
+    let owned = measure(|terminal, frame| {
+        let hud = Scene::module_hud(frame, true);
+        terminal
+            .draw(|f| {
+                let tabs: Vec<ModuleTab> = MODULES
+                    .iter()
+                    .map(|(id, title)| ModuleTab {
+                        title,
+                        active: *id == "stats",
+                        attention: None,
+                    })
+                    .collect();
+                let badges = vec![HudSpan::new("balanced".to_string(), HudStyle::Dim)];
+                let hud_left: Vec<HudLine> = hud
+                    .left_lines
+                    .iter()
+                    .cloned()
+                    .chain(
+                        scene
+                            .keymap
+                            .module_hints()
+                            .into_iter()
+                            .chain(scene.keymap.global_hints())
+                            .map(|hint| HudLine::from(format!("{}: {}", hint.key, hint.action))),
+                    )
+                    .collect();
+                let face = scene.face.to_vec();
+                let view = ShellView {
+                    module_title: "Stats",
+                    tabs: &tabs,
+                    status_line: &scene.status,
+                    badges: &badges,
+                    hud_left: &hud_left,
+                    hud_keys: &[],
+                    hud_right: &hud.right_lines,
+                    hud_face_lines: &face,
+                    face: FaceConfig::default(),
+                    dimmed: false,
+                };
+                render_shell(f, doom_layout(f.area(), 9, 18), view, |_, _| {});
+            })
+            .unwrap();
+    });
+
+    let mut tabs = Vec::new();
+    let mut badges = Vec::new();
+    let borrowed = measure(|terminal, frame| {
+        let hud = Scene::module_hud(frame, false);
+        terminal
+            .draw(|f| {
+                tabs.clear();
+                tabs.extend(MODULES.iter().map(|(id, title)| ModuleTab {
+                    title,
+                    active: *id == "stats",
+                    attention: None,
+                }));
+                badges.clear();
+                badges.push(HudSpan::new("balanced", HudStyle::Dim));
+                let view = ShellView {
+                    module_title: "Stats",
+                    tabs: &tabs,
+                    status_line: &scene.status,
+                    badges: &badges,
+                    hud_left: &hud.left_lines,
+                    hud_keys: scene.keymap.hud_lines(),
+                    hud_right: &hud.right_lines,
+                    hud_face_lines: &scene.face,
+                    face: FaceConfig::default(),
+                    dimmed: false,
+                };
+                render_shell(f, doom_layout(f.area(), 9, 18), view, |_, _| {});
+            })
+            .unwrap();
+    });
+
+    println!("shell view, allocations per frame over {FRAMES} frames");
+    println!("  owned     {owned:8.1}");
+    println!("  borrowed  {borrowed:8.1}");
+    println!("  saved     {:8.1}", owned - borrowed);
+    assert!(
+        borrowed < owned,
+        "borrowing the shell view no longer saves allocations"
+    );
+}
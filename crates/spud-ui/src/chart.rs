@@ -0,0 +1,438 @@
+//! Chart widgets shared by modules and overlays.
+//!
+//! Each widget takes a slice of samples and a [`ChartTheme`]: the colour of
+//! normal values, the warning and critical colours past the theme's bands,
+//! and the track drawn where there is no data. Charts read history newest
+//! last and right-align it, so the latest sample is always at the right
+//! edge whatever the width.
+//!
+//! - [`Sparkline`]: one row, one column per sample, in eighth-height bars.
+//! - [`AreaChart`]: a filled area over several rows, drawn in a
+//!   [`GraphicsMode`] for finer resolution.
+//! - [`BarChart`]: labelled horizontal bars, one per row.
+//! - [`Gauge`]: a single value as a line gauge with a label.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{LineGauge, Widget},
+};
+use spud_config::{GaugeStyle, Thresholds};
+use unicode_width::UnicodeWidthStr;
+
+use crate::pixels::{render_pixels, GraphicsMode};
+
+/// Sparkline glyphs by height in eighths, lowest first.
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Partial glyphs for horizontal bars by width in eighths, 1 to 7.
+const PARTIAL_BARS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Colours of a chart and the value bands they apply to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartTheme {
+    /// Values below `warn`.
+    pub normal: Color,
+    pub warn_color: Color,
+    pub crit_color: Color,
+    /// Empty parts of gauges and bars.
+    pub track: Color,
+    /// Values at or above this use `warn_color`.
+    pub warn: f64,
+    /// Values at or above this use `crit_color`.
+    pub crit: f64,
+}
+
+impl ChartTheme {
+    /// A theme drawing every value in `normal`.
+    pub fn new(normal: Color) -> Self {
+        Self {
+            normal,
+            warn_color: Color::Yellow,
+            crit_color: Color::Red,
+            track: Color::DarkGray,
+            warn: f64::INFINITY,
+            crit: f64::INFINITY,
+        }
+    }
+
+    /// Warn and go critical at percentages from `[modules.*]` thresholds.
+    pub fn thresholds(self, thresholds: Thresholds) -> Self {
+        self.bands(f64::from(thresholds.warn), f64::from(thresholds.crit))
+    }
+
+    /// Warn from `warn` and go critical from `crit`, in the data's units.
+    pub fn bands(self, warn: f64, crit: f64) -> Self {
+        Self { warn, crit, ..self }
+    }
+
+    /// Colour for `value`.
+    pub fn color(&self, value: f64) -> Color {
+        if value >= self.crit {
+            self.crit_color
+        } else if value >= self.warn {
+            self.warn_color
+        } else {
+            self.normal
+        }
+    }
+}
+
+impl Default for ChartTheme {
+    fn default() -> Self {
+        Self::new(Color::Green)
+    }
+}
+
+/// Upper end of the value axis: `max` if given, else the largest sample.
+/// Never zero, so an all-zero series draws as a floor.
+fn axis_max(data: &[f64], max: Option<f64>) -> f64 {
+    let max = max.unwrap_or_else(|| data.iter().copied().fold(0.0, f64::max));
+    if max > 0.0 {
+        max
+    } else {
+        1.0
+    }
+}
+
+/// `value` as a fraction of `max`, clamped to `0.0..=1.0`; NaN is empty.
+fn fraction(value: f64, max: f64) -> f64 {
+    if value.is_nan() {
+        0.0
+    } else {
+        (value / max).clamp(0.0, 1.0)
+    }
+}
+
+/// The last `width` samples, newest at the right edge.
+fn tail(data: &[f64], width: usize) -> &[f64] {
+    &data[data.len().saturating_sub(width)..]
+}
+
+/// One row of bars, one column per recent sample.
+#[derive(Debug, Clone, Copy)]
+pub struct Sparkline<'a> {
+    data: &'a [f64],
+    max: Option<f64>,
+    theme: ChartTheme,
+}
+
+impl<'a> Sparkline<'a> {
+    pub fn new(data: &'a [f64], theme: ChartTheme) -> Self {
+        Self {
+            data,
+            max: None,
+            theme,
+        }
+    }
+
+    /// Fix the top of the scale, e.g. 100 for percentages; by default it
+    /// is the largest sample shown.
+    pub fn max(self, max: f64) -> Self {
+        Self {
+            max: Some(max),
+            ..self
+        }
+    }
+
+    /// The last `width` samples as spans, for embedding in a line of text.
+    /// Every sample gets at least the lowest bar, so a quiet series still
+    /// shows as a floor.
+    pub fn spans(&self, width: usize) -> Vec<Span<'static>> {
+        let data = tail(self.data, width);
+        let max = axis_max(data, self.max);
+        data.iter()
+            .map(|&value| {
+                let eighths = (fraction(value, max) * 8.0).round() as usize;
+                let glyph = BARS[eighths.clamp(1, BARS.len()) - 1];
+                Span::styled(
+                    glyph.to_string(),
+                    Style::default().fg(self.theme.color(value)),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Widget for Sparkline<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = usize::from(area.width);
+        let spans = self.spans(width);
+        let pad = width - spans.len();
+        let row = Rect {
+            x: area.x + pad as u16,
+            width: spans.len() as u16,
+            height: area.height.min(1),
+            ..area
+        };
+        Line::from(spans).render(row, buf);
+    }
+}
+
+/// A filled area under recent samples, one pixel column per sample.
+#[derive(Debug, Clone, Copy)]
+pub struct AreaChart<'a> {
+    data: &'a [f64],
+    max: Option<f64>,
+    theme: ChartTheme,
+    mode: GraphicsMode,
+}
+
+impl<'a> AreaChart<'a> {
+    /// An area chart drawn in quadrant blocks.
+    pub fn new(data: &'a [f64], theme: ChartTheme) -> Self {
+        Self {
+            data,
+            max: None,
+            theme,
+            mode: GraphicsMode::Quadrant,
+        }
+    }
+
+    /// Fix the top of the scale; by default it is the largest sample shown.
+    pub fn max(self, max: f64) -> Self {
+        Self {
+            max: Some(max),
+            ..self
+        }
+    }
+
+    /// Draw in `mode`; braille fits the most samples in a cell.
+    pub fn mode(self, mode: GraphicsMode) -> Self {
+        Self { mode, ..self }
+    }
+}
+
+impl Widget for AreaChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (cell_width, cell_height) = self.mode.cell_pixels();
+        let width = usize::from(area.width) * cell_width;
+        let height = usize::from(area.height) * cell_height;
+        let data = tail(self.data, width);
+        let max = axis_max(data, self.max);
+        // Pixel rows filled in each column, counted from the bottom
+        let offset = width - data.len();
+        let filled: Vec<usize> = data
+            .iter()
+            .map(|&value| (fraction(value, max) * height as f64).round() as usize)
+            .collect();
+        let lines = render_pixels(
+            width,
+            height,
+            |x, y| {
+                let column = x.checked_sub(offset)?;
+                (height - y <= filled[column]).then(|| self.theme.color(data[column]))
+            },
+            self.mode,
+        );
+        for (row, line) in lines.into_iter().enumerate() {
+            let row = Rect {
+                y: area.y + row as u16,
+                height: 1,
+                ..area
+            };
+            line.render(row, buf);
+        }
+    }
+}
+
+/// Labelled horizontal bars, one row each, scaled to the largest value.
+#[derive(Debug, Clone, Copy)]
+pub struct BarChart<'a> {
+    bars: &'a [(&'a str, f64)],
+    max: Option<f64>,
+    theme: ChartTheme,
+}
+
+impl<'a> BarChart<'a> {
+    pub fn new(bars: &'a [(&'a str, f64)], theme: ChartTheme) -> Self {
+        Self {
+            bars,
+            max: None,
+            theme,
+        }
+    }
+
+    /// Fix the full-width value; by default it is the largest bar.
+    pub fn max(self, max: f64) -> Self {
+        Self {
+            max: Some(max),
+            ..self
+        }
+    }
+}
+
+impl Widget for BarChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let values: Vec<f64> = self.bars.iter().map(|(_, value)| *value).collect();
+        let max = axis_max(&values, self.max);
+        let label_width = self
+            .bars
+            .iter()
+            .map(|(label, _)| label.width())
+            .max()
+            .unwrap_or(0);
+        let bar_width = usize::from(area.width).saturating_sub(label_width + 1);
+        for (row, &(label, value)) in self.bars.iter().take(area.height.into()).enumerate() {
+            let eighths = (fraction(value, max) * (bar_width * 8) as f64).round() as usize;
+            let mut bar = "█".repeat(eighths / 8);
+            if let Some(partial) = (eighths % 8).checked_sub(1) {
+                bar.push(PARTIAL_BARS[partial]);
+            }
+            let rest = bar_width - bar.chars().count();
+            let line = Line::from(vec![
+                Span::raw(format!("{label:<label_width$} ")),
+                Span::styled(bar, Style::default().fg(self.theme.color(value))),
+                Span::styled(
+                    symbols::line::NORMAL.horizontal.repeat(rest),
+                    Style::default().fg(self.theme.track),
+                ),
+            ]);
+            let row = Rect {
+                y: area.y + row as u16,
+                height: 1,
+                ..area
+            };
+            line.render(row, buf);
+        }
+    }
+}
+
+/// One percentage as a labelled line gauge.
+#[derive(Debug, Clone)]
+pub struct Gauge<'a> {
+    percent: f64,
+    label: Line<'a>,
+    style: GaugeStyle,
+    theme: ChartTheme,
+}
+
+impl<'a> Gauge<'a> {
+    /// A gauge at `percent` (0–100, clamped; NaN shows empty).
+    pub fn new(percent: f64, label: impl Into<Line<'a>>, theme: ChartTheme) -> Self {
+        Self {
+            percent,
+            label: label.into(),
+            style: GaugeStyle::default(),
+            theme,
+        }
+    }
+
+    /// Draw the line in `style`.
+    pub fn style(self, style: GaugeStyle) -> Self {
+        Self { style, ..self }
+    }
+}
+
+impl Widget for Gauge<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let symbol = match self.style {
+            GaugeStyle::Normal => symbols::line::NORMAL.horizontal,
+            GaugeStyle::Thick => symbols::line::THICK.horizontal,
+            GaugeStyle::Double => symbols::line::DOUBLE.horizontal,
+        };
+        LineGauge::default()
+            .ratio(fraction(self.percent, 100.0))
+            .label(self.label)
+            .filled_symbol(symbol)
+            .unfilled_symbol(symbol)
+            .filled_style(Style::default().fg(self.theme.color(self.percent)))
+            .unfilled_style(Style::default().fg(self.theme.track))
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::{Constraint, Layout};
+
+    use super::*;
+
+    /// Rising then falling load.
+    const SAMPLES: [f64; 12] = [
+        0.0, 10.0, 25.0, 40.0, 55.0, 70.0, 85.0, 100.0, 80.0, 50.0, 30.0, 5.0,
+    ];
+
+    fn render(width: u16, height: u16, widget: impl Widget) -> String {
+        spud_testkit::render(width, height, |f| f.render_widget(widget, f.area()))
+    }
+
+    #[test]
+    fn theme_colours_values_by_band() {
+        let theme = ChartTheme::new(Color::Cyan).thresholds(Thresholds::new(60, 85));
+        assert_eq!(theme.color(59.9), Color::Cyan);
+        assert_eq!(theme.color(60.0), Color::Yellow);
+        assert_eq!(theme.color(85.0), Color::Red);
+        assert_eq!(ChartTheme::new(Color::Cyan).color(1e9), Color::Cyan);
+    }
+
+    #[test]
+    fn sparkline_golden() {
+        let theme = ChartTheme::default().bands(60.0, 85.0);
+        let line = Sparkline::new(&SAMPLES, theme).max(100.0);
+        insta::assert_snapshot!("sparkline", render(16, 1, line));
+        // Narrower than the data: the newest samples are kept
+        insta::assert_snapshot!("sparkline_narrow", render(5, 1, line));
+    }
+
+    #[test]
+    fn sparkline_spans_colour_each_sample() {
+        let theme = ChartTheme::default().bands(60.0, 85.0);
+        let spans = Sparkline::new(&SAMPLES, theme).max(100.0).spans(4);
+        let colors: Vec<_> = spans.iter().map(|span| span.style.fg).collect();
+        assert_eq!(
+            colors,
+            [Color::Yellow, Color::Green, Color::Green, Color::Green].map(Some)
+        );
+        // Without a fixed max the largest sample fills the row
+        let spans = Sparkline::new(&[1.0, 2.0], theme).spans(2);
+        assert_eq!(spans[1].content, "█");
+        assert_eq!(Sparkline::new(&[], theme).spans(4).len(), 0);
+        assert_eq!(
+            Sparkline::new(&[0.0, f64::NAN], theme).spans(2)[1].content,
+            "▁"
+        );
+    }
+
+    #[test]
+    fn area_chart_golden() {
+        let theme = ChartTheme::default();
+        let chart = AreaChart::new(&SAMPLES, theme).max(100.0);
+        insta::assert_snapshot!("area_quadrant", render(8, 4, chart));
+        let chart = chart.mode(GraphicsMode::Braille);
+        insta::assert_snapshot!("area_braille", render(8, 3, chart));
+    }
+
+    #[test]
+    fn bar_chart_golden() {
+        let bars = [("render", 12.0), ("io", 3.5), ("plugins", 7.25)];
+        let chart = BarChart::new(&bars, ChartTheme::default());
+        insta::assert_snapshot!("bars", render(24, 4, chart));
+    }
+
+    #[test]
+    fn gauge_golden() {
+        let theme = ChartTheme::default().thresholds(Thresholds::new(60, 85));
+        let text = spud_testkit::render(30, 3, |f| {
+            let rows = Layout::vertical([Constraint::Length(1); 3]).split(f.area());
+            f.render_widget(Gauge::new(42.0, "CPU", theme), rows[0]);
+            let thick = Gauge::new(90.0, "MEM", theme).style(GaugeStyle::Thick);
+            f.render_widget(thick, rows[1]);
+            f.render_widget(Gauge::new(f64::NAN, "SWP", theme), rows[2]);
+        });
+        insta::assert_snapshot!("gauges", text);
+    }
+
+    #[test]
+    fn gauge_fills_in_the_band_colour() {
+        let theme = ChartTheme::default().thresholds(Thresholds::new(60, 85));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 1));
+        Gauge::new(42.0, "CPU", theme).render(buf.area, &mut buf);
+        assert_eq!(buf[(5, 0)].fg, Color::Green);
+        assert_eq!(buf[(28, 0)].fg, Color::DarkGray);
+        Gauge::new(90.0, "MEM", theme).render(buf.area, &mut buf);
+        assert_eq!(buf[(5, 0)].fg, Color::Red);
+    }
+}
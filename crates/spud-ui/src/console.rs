@@ -8,24 +8,60 @@ use ratatui::{
 
 use unicode_width::UnicodeWidthStr;
 
-use spud_core::console::Console;
+use spud_config::{ConsoleAnchor, ConsoleBackground, ConsoleConfig};
+use spud_core::console::{
+    is_tabular, Console, InputHint, INPUT_TARGET, KEY_VALUE_TARGET, TABLE_HEADER_TARGET,
+};
+use spud_core::i18n::{tr, tr_args};
 use spud_core::logging::LogLevel;
+use spud_core::spans::{format_duration, SpanSummary};
+
+use crate::chart::{ChartTheme, Sparkline};
+use crate::width::{column_boundary, columns, truncate_end, truncate_middle};
 
-/// Render the drop-down console overlay covering the top half of the screen.
+/// Samples in the title bar's sparkline of the slowest span.
+pub const SPARKLINE_WIDTH: usize = 12;
+/// Widest a span name or log target gets before its middle is cut.
+const MAX_NAME_WIDTH: usize = 24;
+/// One frame at 60 Hz; spans slower than this show as warnings, and
+/// slower than two frames as critical.
+const FRAME_MS: f64 = 1000.0 / 60.0;
+
+/// Data needed to draw the console overlay.
+pub struct ConsoleView<'a> {
+    pub console: &'a Console,
+    /// Current ticks per second, shown in the title bar.
+    pub tps: f64,
+    /// Slowest recent span, shown in the title bar.
+    pub slowest_span: Option<&'a SpanSummary>,
+    /// Recent durations of the slowest span in milliseconds, oldest first,
+    /// drawn as a sparkline after it.
+    pub slowest_history: &'a [f64],
+    /// How far the overlay has slid in, 0.0 (hidden) to 1.0 (open).
+    pub fraction: f64,
+    /// Place the terminal cursor in the input line.
+    pub show_cursor: bool,
+    /// Height, anchor edge, and log background.
+    pub layout: ConsoleConfig,
+}
+
+/// Render the drop-down console overlay, sliding in from the top or bottom
+/// edge of `area` and covering `layout.height` percent of it when open.
 ///
 /// The overlay consists of three bands:
-/// 1. **Title bar** — shows `CONSOLE` label, current TPS, and close hint.
-/// 2. **Log area** — colour-coded log entries with scroll support.
-/// 3. **Input line** — single-line command input with cursor.
-pub fn render_console(
-    f: &mut Frame,
-    area: Rect,
-    console: &Console,
-    tps: f64,
-    fraction: f64,
-    show_cursor: bool,
-) {
-    let max_height = area.height / 2;
+/// 1. **Title bar** — shows `CONSOLE` label, current TPS, the slowest recent
+///    span (if any), and close hint.
+/// 2. **Log area** — colour-coded log entries with scroll support, over a
+///    solid, dimmed, or see-through background. Tables and key-value lists
+///    are not wrapped but clipped to the width, shifted by the console's
+///    sideways scroll, with `‹`/`›` where they continue.
+/// 3. **Input line** — single-line command input with cursor, followed by a
+///    dim inline suggestion. Input naming an unknown command is shown in red.
+///    While a prompt is pending its question replaces the `> ` marker.
+pub fn render_console(f: &mut Frame, area: Rect, view: &ConsoleView) {
+    let console = view.console;
+    let fraction = view.fraction;
+    let max_height = (u32::from(area.height) * u32::from(view.layout.height.min(100)) / 100) as u16;
     let mut overlay_height = ((max_height as f64) * fraction).round() as u16;
     // Need at least 3 rows for title + log + input; clamp during animation,
     // skip entirely when fully hidden.
@@ -36,15 +72,36 @@ pub fn render_console(
             return;
         }
     }
+    let overlay_height = overlay_height.min(area.height);
     let overlay = Rect {
         x: area.x,
-        y: area.y,
+        y: match view.layout.anchor {
+            ConsoleAnchor::Top => area.y,
+            ConsoleAnchor::Bottom => area.bottom() - overlay_height,
+        },
         width: area.width,
         height: overlay_height,
     };
 
-    // Clear the area behind the overlay
-    f.render_widget(Clear, overlay);
+    // Clear the area behind the overlay, or with a see-through background
+    // leave the shell under the log.
+    let log_style = match view.layout.background {
+        ConsoleBackground::Solid => {
+            f.render_widget(Clear, overlay);
+            Style::default().bg(Color::Black)
+        }
+        ConsoleBackground::Dim => {
+            f.buffer_mut().set_style(
+                overlay,
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .bg(Color::Black)
+                    .remove_modifier(Modifier::all()),
+            );
+            Style::default()
+        }
+        ConsoleBackground::Transparent => Style::default(),
+    };
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -55,18 +112,36 @@ pub fn render_console(
         ])
         .split(overlay);
 
-    // Title bar with TPS
-    let title = Line::from(vec![
+    // Title bar with TPS and the slowest recent span
+    let mut title_spans = vec![
         Span::styled(
-            " CONSOLE ",
+            format!(" {} ", tr("CONSOLE")),
             Style::default()
                 .fg(Color::Black)
                 .bg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         ),
-        Span::raw(format!("  TPS: {:.1}  ", tps)),
-        Span::styled("~ to close", Style::default().fg(Color::DarkGray)),
-    ]);
+        Span::raw(format!("  TPS: {:.1}  ", view.tps)),
+    ];
+    if let Some(span) = view.slowest_span {
+        title_spans.push(Span::raw(format!(
+            "SLOW: {} {} ",
+            truncate_middle(&span.name, MAX_NAME_WIDTH),
+            format_duration(span.avg)
+        )));
+        let theme = ChartTheme::new(Color::Cyan).bands(FRAME_MS, 2.0 * FRAME_MS);
+        title_spans.extend(Sparkline::new(view.slowest_history, theme).spans(SPARKLINE_WIDTH));
+        title_spans.push(Span::raw("  "));
+    }
+    if console.h_scroll() > 0 {
+        let column = tr_args("COL: +{column}", &[("column", &console.h_scroll())]);
+        title_spans.push(Span::raw(format!("{column}  ")));
+    }
+    title_spans.push(Span::styled(
+        tr("~ to close"),
+        Style::default().fg(Color::DarkGray),
+    ));
+    let title = Line::from(title_spans);
     f.render_widget(
         Paragraph::new(title).style(Style::default().bg(Color::DarkGray).fg(Color::White)),
         chunks[0],
@@ -77,6 +152,8 @@ pub fn render_console(
     let visible_height = chunks[1].height as usize;
     let total = log_lines.len();
     let scroll_offset = console.scroll_offset();
+    // Inside the left and right borders
+    let log_width = usize::from(chunks[1].width.saturating_sub(2));
 
     // Scroll from top: show bottom by default, scroll_offset moves viewport up.
     // Entry count approximates row count — exact when lines don't wrap,
@@ -95,7 +172,25 @@ pub fn render_console(
                 LogLevel::Debug => Color::Cyan,
                 LogLevel::Trace => Color::DarkGray,
             };
-            Line::from(vec![
+            if is_tabular(&entry.target) {
+                return tabular_line(&entry.target, &entry.message, console.h_scroll(), log_width);
+            }
+            if entry.target == INPUT_TARGET {
+                let mut spans = vec![Span::styled(
+                    " > ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )];
+                spans.extend(highlight_command(&entry.message));
+                return Line::from(spans);
+            }
+            let message_style = if entry.level == LogLevel::Error {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut spans = vec![
                 Span::styled(
                     format!(" {:5} ", entry.level),
                     Style::default()
@@ -103,17 +198,24 @@ pub fn render_console(
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
-                    format!("[{}] ", entry.target),
+                    format!("[{}] ", truncate_middle(&entry.target, MAX_NAME_WIDTH)),
                     Style::default().fg(Color::DarkGray),
                 ),
-                Span::raw(&entry.message),
-            ])
+                Span::styled(&entry.message, message_style),
+            ];
+            if entry.count > 1 {
+                spans.push(Span::styled(
+                    format!(" (x{})", entry.count),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            Line::from(spans)
         })
         .collect();
 
     let log_block = Block::default()
         .borders(Borders::LEFT | Borders::RIGHT)
-        .style(Style::default().bg(Color::Black));
+        .style(log_style);
 
     f.render_widget(
         Paragraph::new(lines)
@@ -123,32 +225,562 @@ pub fn render_console(
         chunks[1],
     );
 
-    // Input line
-    let input_line = Line::from(vec![
-        Span::styled(
+    // Input line with inline hint
+    let search = console.search();
+    let marker = match (search, console.prompt()) {
+        (Some(search), _) => Span::styled(
+            format!(
+                "({}reverse-i-search)`{}': ",
+                if search.failed() { "failed " } else { "" },
+                search.query()
+            ),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        // A long question leaves at least half the line for the answer
+        (None, Some(prompt)) => Span::styled(
+            format!(
+                "{} ",
+                truncate_end(&prompt.question, usize::from(chunks[2].width / 2))
+            ),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        (None, None) => Span::styled(
             "> ",
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         ),
-        Span::raw(&console.input_buffer),
-    ]);
+    };
+    let marker_width = marker.width();
+    let input_width = usize::from(chunks[2].width).saturating_sub(marker_width);
+    if let Some(search) = search {
+        // The match stands in for the input, query highlighted and the
+        // cursor at its start
+        let mut spans = vec![marker];
+        let mut cursor_col = 0;
+        if let Some((entry, at)) = console.search_match() {
+            let end = at + search.query().len();
+            let (before, found, after) = if !search.failed() && entry.get(at..end).is_some() {
+                (&entry[..at], &entry[at..end], &entry[end..])
+            } else {
+                (entry, "", "")
+            };
+            cursor_col = before.width();
+            spans.push(Span::raw(before));
+            spans.push(Span::styled(
+                found,
+                Style::default().add_modifier(Modifier::UNDERLINED),
+            ));
+            spans.push(Span::raw(after));
+        }
+        f.render_widget(
+            Paragraph::new(Line::from(spans))
+                .style(Style::default().bg(Color::Black).fg(Color::White)),
+            chunks[2],
+        );
+        if view.show_cursor {
+            let max_col = usize::from(chunks[2].width).saturating_sub(1);
+            let col = (marker_width + cursor_col).min(max_col) as u16;
+            f.set_cursor_position((chunks[2].x + col, chunks[2].y));
+        }
+        return;
+    }
+    let cursor_col = console
+        .input_buffer
+        .get(..console.cursor_pos)
+        .map(|s| s.width())
+        .unwrap_or(0);
+    // Input too wide for the line scrolls sideways to keep the cursor in
+    // view, without the hint. It starts on a whole grapheme, so the first
+    // cell is never half of a wide char
+    let overflows = console.input_buffer.width() >= input_width;
+    let input_scroll = if overflows {
+        column_boundary(
+            &console.input_buffer,
+            cursor_col.saturating_sub(input_width.saturating_sub(1)),
+        )
+    } else {
+        0
+    };
+    let mut input_spans = vec![marker];
+    match console.hint() {
+        hint if overflows => {
+            let style = if hint == Some(&InputHint::Unknown) {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            };
+            let visible = columns(
+                &console.input_buffer,
+                input_scroll,
+                input_scroll + input_width,
+            );
+            input_spans.push(Span::styled(visible, style));
+        }
+        Some(InputHint::Suggest { completion, usage }) => {
+            input_spans.push(Span::raw(&console.input_buffer));
+            input_spans.push(Span::styled(
+                completion.as_str(),
+                Style::default().fg(Color::DarkGray),
+            ));
+            if let Some(usage) = usage {
+                input_spans.push(Span::styled(
+                    format!("  {usage}"),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                ));
+            }
+        }
+        Some(InputHint::Unknown) => input_spans.push(Span::styled(
+            &console.input_buffer,
+            Style::default().fg(Color::Red),
+        )),
+        None => input_spans.push(Span::raw(&console.input_buffer)),
+    }
+    let input_line = Line::from(input_spans);
     f.render_widget(
         Paragraph::new(input_line).style(Style::default().bg(Color::Black).fg(Color::White)),
         chunks[2],
     );
 
     // Position cursor in the input field only when fully open
-    if show_cursor {
-        let display_col = console
-            .input_buffer
-            .get(..console.cursor_pos)
-            .map(|s| s.width())
-            .unwrap_or(0);
-        let max_col = chunks[2].width.saturating_sub(2) as usize;
-        f.set_cursor_position((
-            chunks[2].x + 2 + display_col.min(max_col) as u16,
-            chunks[2].y,
-        ));
+    if view.show_cursor {
+        let max_col = usize::from(chunks[2].width).saturating_sub(1);
+        let col = (marker_width + cursor_col - input_scroll).min(max_col) as u16;
+        f.set_cursor_position((chunks[2].x + col, chunks[2].y));
+    }
+}
+
+/// A table or key-value line, styled by `target` and clipped to `width`
+/// columns starting `skip` columns in.
+fn tabular_line(target: &str, message: &str, skip: usize, width: usize) -> Line<'static> {
+    let text = Style::default().fg(Color::White);
+    let segments = match target {
+        TABLE_HEADER_TARGET => vec![(
+            message,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )],
+        // Keys are padded to a shared width, so the value starts after the
+        // first double space past the indent
+        KEY_VALUE_TARGET => match message.get(2..).and_then(|rest| rest.find("  ")) {
+            Some(end) => vec![
+                (&message[..end + 2], Style::default().fg(Color::DarkGray)),
+                (&message[end + 2..], text),
+            ],
+            None => vec![(message, text)],
+        },
+        _ => vec![(message, text)],
+    };
+    Line::from(clip_columns(&segments, skip, width))
+}
+
+/// The part of `segments` between columns `skip` and `skip + width`, with
+/// a `‹` in the first column when text is cut off on the left and a `›` in
+/// the last when it continues on the right.
+fn clip_columns(segments: &[(&str, Style)], skip: usize, width: usize) -> Vec<Span<'static>> {
+    let total: usize = segments.iter().map(|(text, _)| text.width()).sum();
+    let more_left = skip > 0 && total > 0;
+    let more_right = total > skip + width;
+    let start = skip + usize::from(more_left);
+    let room = width.saturating_sub(usize::from(more_left) + usize::from(more_right));
+    let marker = Style::default().fg(Color::DarkGray);
+
+    let mut spans = Vec::new();
+    if more_left && width > 0 {
+        spans.push(Span::styled("‹", marker));
+    }
+    let end = start + room;
+    let mut column = 0;
+    let mut used = 0;
+    for (text, style) in segments {
+        let kept = columns(
+            text,
+            start.saturating_sub(column),
+            end.saturating_sub(column),
+        );
+        column += text.width();
+        if !kept.is_empty() {
+            used += kept.width();
+            spans.push(Span::styled(kept, *style));
+        }
+    }
+    if more_right && width > 1 {
+        spans.push(Span::raw(" ".repeat(room - used)));
+        spans.push(Span::styled("›", marker));
+    }
+    spans
+}
+
+/// Syntactic role of a piece of an echoed command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandToken {
+    /// The command name (first word).
+    Name,
+    /// A word starting with `-`.
+    Flag,
+    /// A single- or double-quoted string, quotes included.
+    Quoted,
+    /// Any other argument.
+    Arg,
+    /// Whitespace between words.
+    Space,
+}
+
+/// Split a command line into highlightable pieces, preserving all text.
+///
+/// An unterminated quote runs to the end of the line.
+pub fn command_tokens(input: &str) -> Vec<(CommandToken, &str)> {
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    let mut seen_word = false;
+    while let Some(first) = rest.chars().next() {
+        let (kind, len) = if first.is_whitespace() {
+            let len = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            (CommandToken::Space, len)
+        } else if first == '"' || first == '\'' {
+            let len = rest[1..]
+                .find(first)
+                .map(|end| end + 2)
+                .unwrap_or(rest.len());
+            (CommandToken::Quoted, len)
+        } else {
+            let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let kind = if !seen_word {
+                CommandToken::Name
+            } else if first == '-' {
+                CommandToken::Flag
+            } else {
+                CommandToken::Arg
+            };
+            (kind, len)
+        };
+        if kind != CommandToken::Space {
+            seen_word = true;
+        }
+        tokens.push((kind, &rest[..len]));
+        rest = &rest[len..];
+    }
+    tokens
+}
+
+/// Style an echoed command line: bold name, cyan flags, green strings.
+fn highlight_command(input: &str) -> Vec<Span<'_>> {
+    command_tokens(input)
+        .into_iter()
+        .map(|(kind, text)| {
+            let style = match kind {
+                CommandToken::Name => Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+                CommandToken::Flag => Style::default().fg(Color::Cyan),
+                CommandToken::Quoted => Style::default().fg(Color::Green),
+                CommandToken::Arg => Style::default().fg(Color::White),
+                CommandToken::Space => Style::default(),
+            };
+            Span::styled(text, style)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_core::console::{ConsolePrompt, PromptOrigin};
+
+    #[test]
+    fn command_tokens_classify_words() {
+        use CommandToken::*;
+        assert_eq!(
+            command_tokens("echo -n \"hi there\" x"),
+            vec![
+                (Name, "echo"),
+                (Space, " "),
+                (Flag, "-n"),
+                (Space, " "),
+                (Quoted, "\"hi there\""),
+                (Space, " "),
+                (Arg, "x"),
+            ]
+        );
+    }
+
+    /// Rows of a 20x12 screen filled with `#` after drawing a fully open
+    /// `console` with `layout` over it.
+    fn render_rows(console: &Console, layout: ConsoleConfig) -> Vec<String> {
+        use ratatui::{backend::TestBackend, widgets::Paragraph, Terminal};
+
+        let mut terminal = Terminal::new(TestBackend::new(20, 12)).unwrap();
+        terminal
+            .draw(|f| {
+                let fill = vec![Line::from("#".repeat(20)); 12];
+                f.render_widget(Paragraph::new(fill), f.area());
+                let view = ConsoleView {
+                    console,
+                    tps: 10.0,
+                    slowest_span: None,
+                    slowest_history: &[],
+                    fraction: 1.0,
+                    show_cursor: false,
+                    layout,
+                };
+                render_console(f, f.area(), &view);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn layout_sets_height_edge_and_background() {
+        let console = Console::default();
+        let rows = render_rows(&console, ConsoleConfig::default());
+        assert!(rows[0].contains("CONSOLE"));
+        assert_eq!(rows[3], format!("│{}│", " ".repeat(18)));
+        assert!(rows[5].starts_with("> "));
+        assert_eq!(rows[6], "#".repeat(20));
+
+        let rows = render_rows(
+            &console,
+            ConsoleConfig {
+                height: 75,
+                anchor: ConsoleAnchor::Bottom,
+                background: ConsoleBackground::Transparent,
+                ..ConsoleConfig::default()
+            },
+        );
+        assert_eq!(rows[2], "#".repeat(20));
+        assert!(rows[3].contains("CONSOLE"));
+        assert_eq!(rows[6], format!("│{}│", "#".repeat(18)));
+        assert!(rows[11].starts_with("> "));
+    }
+
+    #[test]
+    fn pending_prompt_replaces_input_marker() {
+        let mut console = Console::default();
+        console.set_prompt(ConsolePrompt {
+            question: "Name?".into(),
+            callback_token: "name".into(),
+            origin: PromptOrigin::Command("greet".into()),
+        });
+        console.insert_char('x');
+        let rows = render_rows(&console, ConsoleConfig::default());
+        assert!(rows[5].starts_with("Name? x"));
+    }
+
+    #[test]
+    fn tables_clip_to_the_width_and_scroll_sideways() {
+        use spud_core::output::{Column, CommandOutput, Table};
+
+        let mut table = Table::new(vec![Column::left("COMMAND"), Column::left("DESCRIPTION")]);
+        table.push_row(vec!["console".into(), "Show or change the overlay".into()]);
+        let mut console = Console::default();
+        console.push_output(&CommandOutput::Table(table));
+
+        let rows = render_rows(&console, ConsoleConfig::default());
+        assert_eq!(rows[1], "│  COMMAND  DESCRI›│");
+        assert_eq!(rows[2], "│  console  Show o›│");
+
+        console.scroll_right(10);
+        let rows = render_rows(&console, ConsoleConfig::default());
+        assert_eq!(rows[1], "│‹DESCRIPTION      │");
+        assert_eq!(rows[2], "│‹Show or change t›│");
+    }
+
+    #[test]
+    fn long_targets_and_questions_are_cut() {
+        use spud_core::logging::LogEntry;
+
+        let mut console = Console::default();
+        console.push_log(LogEntry {
+            level: LogLevel::Info,
+            target: "plugin com.example.weather-radar".into(),
+            message: "up".into(),
+            count: 1,
+        });
+        console.set_prompt(ConsolePrompt {
+            question: "Overwrite the existing export file?".into(),
+            callback_token: "overwrite".into(),
+            origin: PromptOrigin::Command("export".into()),
+        });
+        let text = spud_testkit::render(60, 12, |f| {
+            let view = ConsoleView {
+                console: &console,
+                tps: 10.0,
+                slowest_span: None,
+                slowest_history: &[],
+                fraction: 1.0,
+                show_cursor: false,
+                layout: ConsoleConfig::default(),
+            };
+            render_console(f, f.area(), &view);
+        });
+        assert!(text.contains("[plugin com.e…ather-radar] up"));
+        assert!(text.contains("\nOverwrite the existing export…\n"));
+    }
+
+    #[test]
+    fn reverse_search_shows_the_query_and_match() {
+        let mut console = Console::default();
+        console.restore_history(["stats export".to_string(), "tps".to_string()]);
+        console.search_older();
+        "port".chars().for_each(|ch| console.search_insert(ch));
+        let render = |console: &Console| {
+            spud_testkit::render(50, 12, |f| {
+                let view = ConsoleView {
+                    console,
+                    tps: 10.0,
+                    slowest_span: None,
+                    slowest_history: &[],
+                    fraction: 1.0,
+                    show_cursor: false,
+                    layout: ConsoleConfig::default(),
+                };
+                render_console(f, f.area(), &view);
+            })
+        };
+        assert!(render(&console).contains("\n(reverse-i-search)`port': stats export"));
+        console.search_insert('x');
+        assert!(render(&console).contains("\n(failed reverse-i-search)`portx': stats export"));
+    }
+
+    #[test]
+    fn wide_text_keeps_its_columns() {
+        let segments = [("日本語 ok", Style::default())];
+        let text = |spans: Vec<Span<'_>>| -> String {
+            spans.iter().map(|span| span.content.as_ref()).collect()
+        };
+        // Scrolled past "日" and half of "本", which leaves a space
+        assert_eq!(text(clip_columns(&segments, 2, 6)), "‹ 語 ›");
+        assert_eq!(text(clip_columns(&segments, 0, 5)), "日本›");
+    }
+
+    #[test]
+    fn long_input_scrolls_to_keep_the_cursor_in_view() {
+        let mut console = Console::default();
+        for ch in "echo 日本語のテキスト".chars() {
+            console.insert_char(ch);
+        }
+        let rows = render_rows(&console, ConsoleConfig::default());
+        // 21 cells of input in 18: scrolled 4 in, with the cursor in the
+        // last column. Rows list wide chars with their blank second cell
+        assert_eq!(rows[5], ">  日 本 語 の テ キ ス ト  ");
+    }
+
+    #[test]
+    fn cursor_sits_after_wide_and_composed_text() {
+        use ratatui::{backend::TestBackend, layout::Position, Terminal};
+
+        let cursor = |console: &Console| {
+            let mut terminal = Terminal::new(TestBackend::new(20, 12)).unwrap();
+            terminal
+                .draw(|f| {
+                    let view = ConsoleView {
+                        console,
+                        tps: 10.0,
+                        slowest_span: None,
+                        slowest_history: &[],
+                        fraction: 1.0,
+                        show_cursor: true,
+                        layout: ConsoleConfig::default(),
+                    };
+                    render_console(f, f.area(), &view);
+                })
+                .unwrap();
+            let rows = render_rows(console, ConsoleConfig::default());
+            (terminal.get_cursor_position().unwrap(), rows[5].clone())
+        };
+        let mut console = Console::default();
+        for ch in "日本e\u{301}".chars() {
+            console.insert_char(ch);
+        }
+        // "> " then two wide chars and one accented letter
+        assert_eq!(cursor(&console).0, Position::new(7, 5));
+        console.cursor_left();
+        assert_eq!(cursor(&console).0, Position::new(6, 5));
+        console.cursor_left();
+        assert_eq!(cursor(&console).0, Position::new(4, 5));
+
+        // Scrolled input starts on a whole wide char, never half of one
+        let mut console = Console::default();
+        for ch in "日本語のテキストを入力".chars() {
+            console.insert_char(ch);
+        }
+        let (position, row) = cursor(&console);
+        assert_eq!(position, Position::new(18, 5));
+        assert_eq!(row, "> の テ キ ス ト を 入 力   ");
+    }
+
+    #[test]
+    fn console_snapshots_across_widths_and_fractions() {
+        use spud_core::logging::LogEntry;
+        use std::time::Duration;
+
+        let mut console = Console::default();
+        for (level, message) in [
+            (LogLevel::Info, "module stats configured"),
+            (
+                LogLevel::Warn,
+                "plugin demo.slow took 1.20s to answer a ping",
+            ),
+            (LogLevel::Error, "plugin demo.bad: handshake timed out"),
+        ] {
+            console.push_log(LogEntry {
+                level,
+                target: "spud".into(),
+                message: message.into(),
+                count: 1,
+            });
+        }
+        console.insert_char('h');
+        let slowest = SpanSummary {
+            name: "render".into(),
+            count: 120,
+            avg: Duration::from_micros(850),
+            max: Duration::from_micros(12_300),
+            last: Duration::from_micros(900),
+        };
+        let history = [0.8, 0.9, 0.7, 12.3, 0.9, 0.85, 0.9];
+
+        for (width, fraction) in [(40, 1.0), (80, 1.0), (80, 0.5), (120, 0.25)] {
+            let text = spud_testkit::render(width, 16, |f| {
+                let view = ConsoleView {
+                    console: &console,
+                    tps: 59.7,
+                    slowest_span: Some(&slowest),
+                    slowest_history: &history,
+                    fraction,
+                    show_cursor: false,
+                    layout: ConsoleConfig::default(),
+                };
+                render_console(f, f.area(), &view);
+            });
+            insta::assert_snapshot!(
+                format!("console_{width}x16_{}", (fraction * 100.0) as u32),
+                spud_testkit::normalize(&text)
+            );
+        }
+    }
+
+    #[test]
+    fn command_tokens_keep_unterminated_quote() {
+        let tokens = command_tokens("  say 'oops");
+        assert_eq!(tokens[0], (CommandToken::Space, "  "));
+        assert_eq!(tokens[1], (CommandToken::Name, "say"));
+        assert_eq!(tokens[3], (CommandToken::Quoted, "'oops"));
     }
 }
@@ -0,0 +1,150 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use spud_core::i18n::{tr, tr_args};
+use spud_core::tap::{EventTap, TappedEvent};
+
+/// Width of the kind column, long enough for `module_deactivated`.
+const KIND_WIDTH: usize = 18;
+
+/// Render the event debug overlay as a modal over the centre of `area`.
+///
+/// The newest events are at the bottom; scrolling moves the window back
+/// through the tap's history.
+pub fn render_event_overlay(f: &mut Frame, area: Rect, tap: &EventTap) {
+    let width = (area.width * 4 / 5).max(area.width.min(40));
+    let height = (area.height * 3 / 5).max(area.height.min(8));
+    let modal = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let title = if tap.is_paused() {
+        format!(
+            " {} ",
+            tr_args("EVENTS — PAUSED, {count} new", &[("count", &tap.missed())])
+        )
+    } else {
+        format!(" {} ", tr("EVENTS"))
+    };
+    let rows = height.saturating_sub(2) as usize;
+
+    f.render_widget(Clear, modal);
+    f.render_widget(
+        Paragraph::new(event_lines(tap, rows)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .title_bottom(
+                    Line::from(format!(
+                        " {} ",
+                        tr("Space pause · ↑↓/PgUp/PgDn scroll · c clear · F12/Esc close")
+                    ))
+                    .alignment(Alignment::Center),
+                ),
+        ),
+        modal,
+    );
+}
+
+/// The `rows` events visible at the tap's scroll position, oldest first.
+fn event_lines(tap: &EventTap, rows: usize) -> Vec<Line<'static>> {
+    let entries = tap.entries();
+    if entries.is_empty() {
+        return vec![Line::styled(
+            format!(" {}", tr("no events yet")),
+            Style::default().fg(Color::DarkGray),
+        )];
+    }
+    let end = entries.len() - tap.scroll().min(entries.len());
+    let start = end.saturating_sub(rows);
+    entries.range(start..end).map(event_line).collect()
+}
+
+fn event_line(event: &TappedEvent) -> Line<'static> {
+    let secs = event.at.as_secs_f64();
+    Line::from(vec![
+        Span::styled(
+            format!(" {secs:>9.3}s  "),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            format!("{:KIND_WIDTH$}  ", event.kind),
+            Style::default()
+                .fg(kind_color(event.kind))
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(event.preview.clone()),
+    ])
+}
+
+fn kind_color(kind: &str) -> Color {
+    match kind {
+        "key" | "resize" => Color::Cyan,
+        "module_activated" | "module_deactivated" | "config_reloaded" => Color::Green,
+        "attention_requested" => Color::Yellow,
+        "telemetry" => Color::Blue,
+        "quit" => Color::Red,
+        _ => Color::Magenta,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spud_core::event::{ActivationReason, Event};
+    use std::time::{Duration, Instant};
+
+    fn text_of(line: &Line<'_>) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn shows_latest_rows_and_follows_scroll() {
+        let origin = Instant::now();
+        let mut tap = EventTap::new(origin, 16);
+        for id in ["a", "b", "c"] {
+            tap.record(
+                &Event::ModuleActivated {
+                    id: id.into(),
+                    reason: ActivationReason::Cycle,
+                },
+                origin + Duration::from_millis(250),
+            );
+        }
+        let text: Vec<String> = event_lines(&tap, 2).iter().map(text_of).collect();
+        assert_eq!(
+            text,
+            vec![
+                "     0.250s  module_activated    b (cycle)",
+                "     0.250s  module_activated    c (cycle)",
+            ]
+        );
+
+        tap.scroll_up(1);
+        let text: Vec<String> = event_lines(&tap, 2).iter().map(text_of).collect();
+        assert!(text[0].ends_with(" a (cycle)") && text[1].ends_with(" b (cycle)"));
+    }
+
+    #[test]
+    fn modal_fits_tiny_terminal() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut tap = EventTap::new(Instant::now(), 4);
+        tap.set_paused(true);
+        let mut terminal = Terminal::new(TestBackend::new(10, 4)).unwrap();
+        terminal
+            .draw(|f| render_event_overlay(f, f.area(), &tap))
+            .unwrap();
+    }
+}
@@ -1,79 +1,309 @@
+use std::cell::RefCell;
+use std::f32::consts::PI;
+
 use ratatui::{
-    style::{Color, Style},
-    text::{Line, Span, Text},
+    layout::Size,
+    style::Color,
+    text::{Line, Text},
 };
+use spud_config::{FaceConfig, FaceFilter, FaceScale};
+
+use crate::pixels::render_pixels;
+
+/// Most scaled faces kept by [`build_face_text`]; enough for every frame of
+/// an animated face at one panel size.
+const CACHED_FACES: usize = 16;
+
+/// A pixel-art face drawn for one panel size and config.
+struct ScaledFace {
+    lines: Vec<String>,
+    area: Size,
+    config: FaceConfig,
+    text: Vec<Line<'static>>,
+}
 
-/// Build display text for the agent face.
+thread_local! {
+    /// Recently drawn faces, newest last. The face is drawn every frame
+    /// but changes only when its animation frame, the panel, or `[face]`
+    /// does, so resampling is done once per combination.
+    static SCALED_FACES: RefCell<Vec<ScaledFace>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Build display text for the agent face in a panel of `area` cells.
 ///
 /// Supports two input modes:
 /// - Plain text lines (fallback behavior).
-/// - Pixel-art rows encoded with palette keys (Claude-style sprite rendering).
-pub fn build_face_text(lines: &[String]) -> Text<'static> {
-    if let Some(pixel_rows) = parse_pixel_rows(lines) {
-        render_pixel_rows(&pixel_rows)
+/// - Pixel-art rows encoded with palette keys (Claude-style sprite rendering),
+///   drawn in `config.graphics` and sized to the panel as `config` says.
+///   The result is cached per frame lines, panel size, and `config`.
+pub fn build_face_text<'a>(lines: &'a [String], area: Size, config: &FaceConfig) -> Text<'a> {
+    let cached = SCALED_FACES.with_borrow(|faces| {
+        faces
+            .iter()
+            .find(|face| face.area == area && face.config == *config && face.lines == lines)
+            .map(|face| face.text.clone())
+    });
+    if let Some(text) = cached {
+        return Text::from(text);
+    }
+    if let Some(text) = scale_face(lines, area, config) {
+        SCALED_FACES.with_borrow_mut(|faces| {
+            if faces.len() == CACHED_FACES {
+                faces.remove(0);
+            }
+            faces.push(ScaledFace {
+                lines: lines.to_vec(),
+                area,
+                config: *config,
+                text: text.clone(),
+            });
+        });
+        Text::from(text)
     } else {
         Text::from(
             lines
                 .iter()
-                .cloned()
-                .map(Line::from)
-                .collect::<Vec<Line<'static>>>(),
+                .map(|line| Line::from(line.as_str()))
+                .collect::<Vec<Line<'_>>>(),
         )
     }
 }
 
-fn parse_pixel_rows(lines: &[String]) -> Option<Vec<Vec<char>>> {
-    if lines.is_empty() {
-        return None;
+/// The face drawn from palette-key rows, or `None` if `lines` are not
+/// pixel art.
+fn scale_face(lines: &[String], area: Size, config: &FaceConfig) -> Option<Vec<Line<'static>>> {
+    let sprite = Sprite::parse(lines)?;
+    let (cell_width, cell_height) = config.graphics.cell_pixels();
+    let panel = (
+        usize::from(area.width) * cell_width,
+        usize::from(area.height) * cell_height,
+    );
+    let size = target_size((sprite.width, sprite.height), panel, config);
+    let sprite = sprite.resize(size, config);
+    let mut text = render_pixels(
+        sprite.width,
+        sprite.height,
+        |x, y| sprite.color(x, y),
+        config.graphics,
+    );
+    if config.letterbox && config.scale != FaceScale::None {
+        let pad = usize::from(area.height).saturating_sub(text.len()) / 2;
+        text.splice(0..0, (0..pad).map(|_| Line::default()));
     }
+    Some(text)
+}
+
+/// Premultiplied RGBA, each channel in `0.0..=1.0`.
+type Pixel = [f32; 4];
 
-    let mut rows = Vec::with_capacity(lines.len());
-    for line in lines {
-        let mut row = Vec::with_capacity(line.chars().count());
-        for ch in line.chars() {
-            if palette_color(ch).is_none() && ch != '.' {
-                return None;
+/// A face as pixels, row by row.
+#[derive(Debug, Clone, PartialEq)]
+struct Sprite {
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+}
+
+impl Sprite {
+    /// The sprite drawn by palette-key rows; `None` if any character is not
+    /// a palette key. Short rows are padded with transparent pixels.
+    fn parse(lines: &[String]) -> Option<Self> {
+        if lines.is_empty() {
+            return None;
+        }
+        let width = lines.iter().map(|line| line.chars().count()).max()?;
+        let mut pixels = Vec::with_capacity(width * lines.len());
+        for line in lines {
+            for ch in line.chars() {
+                pixels.push(match palette_color(ch) {
+                    Some(Color::Rgb(r, g, b)) => [channel(r), channel(g), channel(b), 1.0],
+                    _ if ch == '.' => [0.0; 4],
+                    _ => return None,
+                });
             }
-            row.push(ch);
+            let padding = width - line.chars().count();
+            pixels.extend(std::iter::repeat_n([0.0; 4], padding));
         }
-        rows.push(row);
+        Some(Self {
+            width,
+            height: lines.len(),
+            pixels,
+        })
     }
 
-    Some(rows)
-}
+    /// Color of the pixel at `(x, y)`; `None` where it is mostly
+    /// transparent or outside the sprite.
+    fn color(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let [r, g, b, a] = self.pixels[y * self.width + x];
+        (a >= 0.5).then(|| {
+            let unmultiply = |c: f32| ((c / a).clamp(0.0, 1.0) * 255.0).round() as u8;
+            Color::Rgb(unmultiply(r), unmultiply(g), unmultiply(b))
+        })
+    }
 
-fn render_pixel_rows(rows: &[Vec<char>]) -> Text<'static> {
-    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
-    let mut rendered = Vec::new();
-    let mut y = 0usize;
-
-    while y < rows.len() {
-        let top = &rows[y];
-        let bottom = rows.get(y + 1);
-        let mut spans = Vec::with_capacity(width);
-
-        for x in 0..width {
-            let top_color = top.get(x).copied().and_then(palette_color);
-            let bottom_color = bottom
-                .and_then(|row| row.get(x).copied())
-                .and_then(palette_color);
-
-            let (glyph, style) = match (top_color, bottom_color) {
-                (Some(top), Some(bottom)) if top == bottom => ('█', Style::default().fg(top)),
-                (Some(top), Some(bottom)) => ('▀', Style::default().fg(top).bg(bottom)),
-                (Some(top), None) => ('▀', Style::default().fg(top)),
-                (None, Some(bottom)) => ('▄', Style::default().fg(bottom)),
-                (None, None) => (' ', Style::default()),
+    /// The sprite at `size`. Whole-multiple enlargements under
+    /// [`FaceScale::Integer`] repeat pixels; everything else goes through
+    /// the configured filter.
+    fn resize(self, size: (usize, usize), config: &FaceConfig) -> Self {
+        let (width, height) = size;
+        if (width, height) == (self.width, self.height) {
+            return self;
+        }
+        let filter =
+            if config.scale == FaceScale::Integer && width >= self.width && height >= self.height {
+                FaceFilter::Nearest
+            } else {
+                config.filter
             };
+        self.resample_rows(width, filter)
+            .transpose()
+            .resample_rows(height, filter)
+            .transpose()
+    }
 
-            spans.push(Span::styled(glyph.to_string(), style));
+    /// Each row resampled to `width` pixels.
+    fn resample_rows(&self, width: usize, filter: FaceFilter) -> Self {
+        let taps = taps(self.width, width, filter);
+        let mut pixels = Vec::with_capacity(width * self.height);
+        for row in self.pixels.chunks_exact(self.width) {
+            for sample in &taps {
+                let mut pixel = [0.0; 4];
+                for &(index, weight) in sample {
+                    for (out, value) in pixel.iter_mut().zip(row[index]) {
+                        *out += value * weight;
+                    }
+                }
+                // Lanczos lobes overshoot; keep colour within alpha
+                let alpha = pixel[3].clamp(0.0, 1.0);
+                pixel = pixel.map(|c| c.clamp(0.0, alpha));
+                pixel[3] = alpha;
+                pixels.push(pixel);
+            }
+        }
+        Self {
+            width,
+            height: self.height,
+            pixels,
         }
+    }
+
+    fn transpose(self) -> Self {
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for x in 0..self.width {
+            pixels.extend((0..self.height).map(|y| self.pixels[y * self.width + x]));
+        }
+        Self {
+            width: self.height,
+            height: self.width,
+            pixels,
+        }
+    }
+}
+
+fn channel(value: u8) -> f32 {
+    f32::from(value) / 255.0
+}
+
+/// Size of a `source`-sized face in a `panel` (both in pixels, width then
+/// height) under `config`.
+fn target_size(
+    source: (usize, usize),
+    panel: (usize, usize),
+    config: &FaceConfig,
+) -> (usize, usize) {
+    let ((width, height), (panel_width, panel_height)) = (source, panel);
+    if config.scale == FaceScale::None
+        || width == 0
+        || height == 0
+        || panel_width == 0
+        || panel_height == 0
+    {
+        return source;
+    }
+    let mut x = panel_width as f64 / width as f64;
+    let mut y = panel_height as f64 / height as f64;
+    if config.letterbox {
+        x = x.min(y);
+        y = x;
+    }
+    if config.scale == FaceScale::Integer {
+        x = whole_step(x);
+        y = whole_step(y);
+    }
+    let scaled = |len: usize, factor: f64, max: usize| {
+        ((len as f64 * factor).round() as usize).clamp(1, max)
+    };
+    (
+        scaled(width, x, panel_width),
+        scaled(height, y, panel_height),
+    )
+}
+
+/// The largest whole multiple or whole fraction not above `factor`.
+fn whole_step(factor: f64) -> f64 {
+    if factor >= 1.0 {
+        factor.floor()
+    } else {
+        1.0 / (1.0 / factor).ceil()
+    }
+}
 
-        rendered.push(Line::from(spans));
-        y += 2;
+/// For each of `out` samples across `len` source pixels, the source pixels
+/// it blends and their weights, which add up to one.
+fn taps(len: usize, out: usize, filter: FaceFilter) -> Vec<Vec<(usize, f32)>> {
+    let ratio = len as f32 / out as f32;
+    // Shrinking widens the kernel so every source pixel counts
+    let stretch = ratio.max(1.0);
+    let support = filter_support(filter) * stretch;
+    (0..out)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * ratio;
+            let nearest = vec![((center as usize).min(len - 1), 1.0)];
+            if filter == FaceFilter::Nearest {
+                return nearest;
+            }
+            let start = (center - support).floor().max(0.0) as usize;
+            let end = ((center + support).ceil() as usize).min(len);
+            let mut sample: Vec<(usize, f32)> = (start..end)
+                .map(|j| (j, kernel(filter, (j as f32 + 0.5 - center) / stretch)))
+                .filter(|(_, weight)| *weight != 0.0)
+                .collect();
+            let total: f32 = sample.iter().map(|(_, weight)| weight).sum();
+            if total.abs() < f32::EPSILON {
+                return nearest;
+            }
+            for (_, weight) in &mut sample {
+                *weight /= total;
+            }
+            sample
+        })
+        .collect()
+}
+
+/// Half-width of the filter's kernel, in source pixels at 1:1.
+fn filter_support(filter: FaceFilter) -> f32 {
+    match filter {
+        FaceFilter::Nearest => 0.5,
+        FaceFilter::Bilinear => 1.0,
+        FaceFilter::Lanczos => 3.0,
     }
+}
 
-    Text::from(rendered)
+fn kernel(filter: FaceFilter, x: f32) -> f32 {
+    let x = x.abs();
+    match filter {
+        FaceFilter::Nearest => f32::from(u8::from(x < 0.5)),
+        FaceFilter::Bilinear => (1.0 - x).max(0.0),
+        FaceFilter::Lanczos if x < f32::EPSILON => 1.0,
+        FaceFilter::Lanczos if x < 3.0 => {
+            let px = PI * x;
+            3.0 * px.sin() * (px / 3.0).sin() / (px * px)
+        }
+        FaceFilter::Lanczos => 0.0,
+    }
 }
 
 fn palette_color(ch: char) -> Option<Color> {
@@ -90,24 +320,154 @@ fn palette_color(ch: char) -> Option<Color> {
 
 #[cfg(test)]
 mod tests {
+    use spud_config::GraphicsMode;
+
     use super::*;
 
+    const PANEL: Size = Size {
+        width: 16,
+        height: 7,
+    };
+
+    fn rows(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| (*line).to_string()).collect()
+    }
+
+    fn config(scale: FaceScale, filter: FaceFilter) -> FaceConfig {
+        FaceConfig {
+            scale,
+            filter,
+            ..FaceConfig::default()
+        }
+    }
+
     #[test]
     fn plain_text_fallback_for_non_palette_lines() {
-        let lines = vec!["hello".to_string(), "world".to_string()];
-        let text = build_face_text(&lines);
+        let lines = rows(&["hello", "world"]);
+        let text = build_face_text(&lines, PANEL, &FaceConfig::default());
         assert_eq!(text.lines.len(), 2);
     }
 
     #[test]
     fn pixel_rows_are_packed_to_half_height() {
-        let lines = vec![
-            "OO".to_string(),
-            "OO".to_string(),
-            "kk".to_string(),
-            "..".to_string(),
-        ];
-        let text = build_face_text(&lines);
+        let lines = rows(&["OO", "OO", "kk", ".."]);
+        let text = build_face_text(&lines, PANEL, &config(FaceScale::None, FaceFilter::Lanczos));
         assert_eq!(text.lines.len(), 2);
     }
+
+    #[test]
+    fn sizes_follow_the_scale_mode() {
+        let fit = config(FaceScale::Fit, FaceFilter::Lanczos);
+        let integer = config(FaceScale::Integer, FaceFilter::Lanczos);
+        let none = config(FaceScale::None, FaceFilter::Lanczos);
+        // The built-in 16x12 faces fill the default panel as they are
+        assert_eq!(target_size((16, 12), (16, 14), &integer), (16, 12));
+        assert_eq!(target_size((16, 12), (16, 14), &fit), (16, 12));
+        // Larger panels enlarge by whole steps unless fitting
+        assert_eq!(target_size((16, 12), (38, 30), &integer), (32, 24));
+        assert_eq!(target_size((16, 12), (38, 30), &fit), (38, 29));
+        // Large faces shrink by whole fractions
+        assert_eq!(target_size((128, 128), (16, 14), &integer), (13, 13));
+        assert_eq!(target_size((128, 128), (16, 14), &fit), (14, 14));
+        assert_eq!(target_size((128, 128), (16, 14), &none), (128, 128));
+        // Without letterboxing each axis fills the panel
+        let stretch = FaceConfig {
+            letterbox: false,
+            ..fit
+        };
+        assert_eq!(target_size((128, 128), (16, 14), &stretch), (16, 14));
+    }
+
+    #[test]
+    fn whole_enlargements_repeat_pixels() {
+        let sprite = Sprite::parse(&rows(&["Ok", ".h"])).unwrap();
+        let big = sprite.resize((4, 4), &config(FaceScale::Integer, FaceFilter::Lanczos));
+        assert_eq!(big.color(0, 0), big.color(1, 1));
+        assert_eq!(big.color(2, 0), palette_color('k'));
+        assert_eq!(big.color(1, 3), None);
+        assert_eq!(big.color(3, 3), palette_color('h'));
+    }
+
+    #[test]
+    fn shrinking_blends_instead_of_dropping_pixels() {
+        // One dark column in four: nearest drops or keeps it whole, the
+        // smoothing filters mix it into its neighbour
+        let lines = rows(&["OkOO", "OkOO"]);
+        let sprite = Sprite::parse(&lines).unwrap();
+        let shrink = |filter| {
+            sprite
+                .clone()
+                .resize((2, 2), &config(FaceScale::Fit, filter))
+                .color(0, 0)
+        };
+        let Some(Color::Rgb(r, ..)) = shrink(FaceFilter::Bilinear) else {
+            panic!("bilinear pixel should be opaque");
+        };
+        assert!(r > 10 && r < 255);
+        let Some(Color::Rgb(r, ..)) = shrink(FaceFilter::Lanczos) else {
+            panic!("lanczos pixel should be opaque");
+        };
+        assert!(r > 10 && r < 255);
+        assert!(matches!(
+            shrink(FaceFilter::Nearest),
+            Some(Color::Rgb(255, ..) | Color::Rgb(10, ..))
+        ));
+    }
+
+    #[test]
+    fn finer_graphics_modes_draw_the_face_larger() {
+        let lines = rows(&["OOOO", "OkkO", "OOOO", "O..O"]);
+        let braille = FaceConfig {
+            graphics: GraphicsMode::Braille,
+            ..FaceConfig::default()
+        };
+        // The panel is 32x28 braille dots: the 4x4 face grows 7 times to
+        // 28x28, 14 cells by 7 rows
+        let text = build_face_text(&lines, PANEL, &braille);
+        assert_eq!(text.lines.len(), 7);
+        assert_eq!(text.lines[0].spans.len(), 14);
+        // Half blocks give 16x14 pixels: it grows 3 times, 12 cells wide
+        let text = build_face_text(&lines, PANEL, &FaceConfig::default());
+        assert_eq!(text.lines[0].spans.len(), 12);
+    }
+
+    #[test]
+    fn scaled_faces_are_cached_per_frame_panel_and_config() {
+        let cached = || SCALED_FACES.with_borrow(Vec::len);
+        let lines = rows(&["Ok", "kO"]);
+        let first = build_face_text(&lines, PANEL, &FaceConfig::default());
+        assert_eq!(cached(), 1);
+        assert_eq!(
+            build_face_text(&lines, PANEL, &FaceConfig::default()),
+            first
+        );
+        assert_eq!(cached(), 1);
+        let nearest = config(FaceScale::Fit, FaceFilter::Nearest);
+        build_face_text(&lines, PANEL, &nearest);
+        build_face_text(&lines, Size::new(8, 4), &nearest);
+        build_face_text(&rows(&["kO", "Ok"]), PANEL, &nearest);
+        assert_eq!(cached(), 4);
+        // Plain text is not cached
+        build_face_text(&rows(&["(o_o)"]), PANEL, &nearest);
+        assert_eq!(cached(), 4);
+        for n in 0..CACHED_FACES {
+            build_face_text(&lines, Size::new(4, n as u16 + 1), &nearest);
+        }
+        assert_eq!(cached(), CACHED_FACES);
+    }
+
+    #[test]
+    fn letterboxing_centres_the_face_vertically() {
+        let lines = rows(&["OO", "OO"]);
+        let text = build_face_text(&lines, PANEL, &config(FaceScale::None, FaceFilter::Lanczos));
+        assert_eq!(text.lines.len(), 1);
+        let text = build_face_text(&lines, PANEL, &FaceConfig::default());
+        // 2x2 grows to 14x14 pixels, 7 rows: the whole panel
+        assert_eq!(text.lines.len(), 7);
+        let wide = rows(&["OOOOOOOO", "OOOOOOOO"]);
+        let text = build_face_text(&wide, PANEL, &FaceConfig::default());
+        // 8x2 grows to 16x4: two rows, padded by two above
+        assert_eq!(text.lines.len(), 4);
+        assert!(text.lines[0].spans.is_empty());
+    }
 }
@@ -0,0 +1,177 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use spud_core::i18n::tr;
+use spud_core::module::KeyHint;
+use unicode_width::UnicodeWidthStr;
+
+/// Content of the help overlay.
+pub struct HelpView<'a> {
+    /// Keys that work regardless of the active module.
+    pub global_keys: &'a [KeyHint],
+    /// Title of the active module, if any.
+    pub module_title: Option<&'a str>,
+    /// Keys handled by the active module.
+    pub module_keys: Vec<KeyHint>,
+    /// Labelled version and build facts.
+    pub about: Vec<(String, String)>,
+}
+
+/// Render the help overlay as a centred modal over `area`.
+///
+/// The modal sizes itself to its content, shrinking to fit small terminals.
+pub fn render_help(f: &mut Frame, area: Rect, view: &HelpView<'_>) {
+    let lines = help_lines(view);
+    let content_width = lines.iter().map(Line::width).max().unwrap_or(0) as u16;
+    let width = (content_width + 4).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let modal = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, modal);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", tr("HELP")))
+                .title_alignment(Alignment::Center)
+                .title_bottom(
+                    Line::from(format!(" {} ", tr("F1/Esc to close"))).alignment(Alignment::Center),
+                ),
+        ),
+        modal,
+    );
+}
+
+/// Build the overlay text: global keys, module keys, then build info.
+fn help_lines(view: &HelpView<'_>) -> Vec<Line<'static>> {
+    let key_width = view
+        .global_keys
+        .iter()
+        .chain(&view.module_keys)
+        .map(|hint| hint.key.width())
+        .chain(view.about.iter().map(|(label, _)| label.width()))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![heading(tr("KEYS"))];
+    lines.extend(
+        view.global_keys
+            .iter()
+            .map(|hint| key_line(hint, key_width)),
+    );
+
+    lines.push(Line::default());
+    let title = view.module_title.unwrap_or(tr("no module"));
+    lines.push(heading(&title.to_uppercase()));
+    if view.module_keys.is_empty() {
+        lines.push(Line::styled(
+            format!(" {}", tr("no module-specific keys")),
+            Style::default().fg(Color::DarkGray),
+        ));
+    } else {
+        lines.extend(
+            view.module_keys
+                .iter()
+                .map(|hint| key_line(hint, key_width)),
+        );
+    }
+
+    if !view.about.is_empty() {
+        lines.push(Line::default());
+        lines.push(heading(tr("ABOUT")));
+        lines.extend(view.about.iter().map(|(label, value)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" {label:key_width$}  "),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(value.clone()),
+            ])
+        }));
+    }
+    lines
+}
+
+fn heading(text: &str) -> Line<'static> {
+    Line::styled(
+        format!(" {text}"),
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+fn key_line(hint: &KeyHint, key_width: usize) -> Line<'static> {
+    let pad = " ".repeat(key_width.saturating_sub(hint.key.width()));
+    Line::from(vec![
+        Span::styled(
+            format!(" {}{pad}  ", hint.key),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(hint.action.clone()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(line: &Line<'_>) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn lists_sections_with_aligned_keys() {
+        let global = [
+            KeyHint::new("F1", "help"),
+            KeyHint::new("Tab", "next module"),
+        ];
+        let view = HelpView {
+            global_keys: &global,
+            module_title: Some("Stats"),
+            module_keys: vec![KeyHint::new("r", "reset")],
+            about: vec![("version".into(), "0.1.0".into())],
+        };
+        let text: Vec<String> = help_lines(&view).iter().map(text_of).collect();
+        assert_eq!(
+            text,
+            vec![
+                " KEYS",
+                " F1       help",
+                " Tab      next module",
+                "",
+                " STATS",
+                " r        reset",
+                "",
+                " ABOUT",
+                " version  0.1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn modal_fits_tiny_terminal() {
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let view = HelpView {
+            global_keys: &[KeyHint::new("q", "quit")],
+            module_title: None,
+            module_keys: Vec::new(),
+            about: Vec::new(),
+        };
+        let mut terminal = Terminal::new(TestBackend::new(10, 4)).unwrap();
+        terminal.draw(|f| render_help(f, f.area(), &view)).unwrap();
+    }
+}
@@ -1,11 +1,20 @@
 //! TUI rendering layer for SPUD.
 //!
-//! Provides the Doom-style layout, shell chrome, and console overlay widgets.
-//! All rendering uses [`ratatui`] — this crate owns the visual presentation
-//! while [`spud_core`] owns the state.
+//! Provides the Doom-style layout, shell chrome, the console, help, and
+//! event debug overlays, and the startup splash. All rendering uses [`ratatui`] — this crate owns the
+//! visual presentation while [`spud_core`] owns the state.
 
+pub mod chart;
 pub mod console;
+pub mod events;
 pub mod face;
+pub mod help;
 pub mod layout;
+pub mod pixels;
 pub mod renderer;
 pub mod shell;
+pub mod splash;
+pub mod width;
+
+/// Version of this crate, as reported by `spud version`.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
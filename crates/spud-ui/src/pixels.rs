@@ -0,0 +1,230 @@
+//! Pixel grids drawn with character cells, for faces and charts.
+//!
+//! Each [`GraphicsMode`] packs a block of pixels into one cell: half blocks
+//! take 1×2 and give each pixel its own colour; quadrant blocks take 2×2
+//! and braille dots 2×4. A cell has only a foreground and a background
+//! colour, so those two split each block between its two most different
+//! colours and draw each side in its average.
+
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+pub use spud_config::GraphicsMode;
+
+/// Quadrant glyphs by mask: bit 0 top left, 1 top right, 2 bottom left,
+/// 3 bottom right.
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+/// Braille dot bit of each pixel in a 2×4 block, row by row.
+const BRAILLE_DOTS: [u8; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+
+/// Draw a `width`×`height` grid of pixels, where `pixel(x, y)` is `None`
+/// for transparent, as lines of cells in `mode`.
+pub fn render_pixels(
+    width: usize,
+    height: usize,
+    pixel: impl Fn(usize, usize) -> Option<Color>,
+    mode: GraphicsMode,
+) -> Vec<Line<'static>> {
+    let (cell_width, cell_height) = mode.cell_pixels();
+    let mut lines = Vec::with_capacity(height.div_ceil(cell_height));
+    let mut block = Vec::with_capacity(cell_width * cell_height);
+    for top in (0..height).step_by(cell_height) {
+        let mut spans = Vec::with_capacity(width.div_ceil(cell_width));
+        for left in (0..width).step_by(cell_width) {
+            block.clear();
+            for y in top..top + cell_height {
+                for x in left..left + cell_width {
+                    block.push((x < width && y < height).then(|| pixel(x, y)).flatten());
+                }
+            }
+            let (glyph, style) = match mode {
+                GraphicsMode::HalfBlock => half_block(block[0], block[1]),
+                GraphicsMode::Quadrant => {
+                    let (mask, style) = split(&block);
+                    (QUADRANTS[usize::from(mask)], style)
+                }
+                GraphicsMode::Braille => {
+                    let (mask, style) = split(&block);
+                    let bits = BRAILLE_DOTS
+                        .iter()
+                        .enumerate()
+                        .filter(|(index, _)| mask & (1 << index) != 0)
+                        .fold(0, |bits, (_, dot)| bits | dot);
+                    (braille(bits), style)
+                }
+            };
+            spans.push(Span::styled(glyph.to_string(), style));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+/// The braille pattern with `dots` raised; blank braille shows as a space.
+fn braille(dots: u8) -> char {
+    if dots == 0 {
+        return ' ';
+    }
+    char::from_u32(0x2800 + u32::from(dots)).unwrap_or(' ')
+}
+
+fn half_block(top: Option<Color>, bottom: Option<Color>) -> (char, Style) {
+    match (top, bottom) {
+        (Some(top), Some(bottom)) if top == bottom => ('█', Style::default().fg(top)),
+        (Some(top), Some(bottom)) => ('▀', Style::default().fg(top).bg(bottom)),
+        (Some(top), None) => ('▀', Style::default().fg(top)),
+        (None, Some(bottom)) => ('▄', Style::default().fg(bottom)),
+        (None, None) => (' ', Style::default()),
+    }
+}
+
+/// Split a block into the foreground pixels (the returned mask, bit `i`
+/// for pixel `i`) and the rest, with the style drawing both. Transparent
+/// pixels are always background; between two colours the smaller group is
+/// the foreground.
+fn split(block: &[Option<Color>]) -> (u8, Style) {
+    // The two most different pixels seed the groups
+    let mut seeds = (block[0], block[0]);
+    let mut widest = 0;
+    for (i, a) in block.iter().enumerate() {
+        for b in &block[i + 1..] {
+            let d = distance(*a, *b);
+            if d > widest {
+                widest = d;
+                seeds = (*a, *b);
+            }
+        }
+    }
+    let mut first = 0u8;
+    for (i, pixel) in block.iter().enumerate() {
+        if distance(*pixel, seeds.0) <= distance(*pixel, seeds.1) {
+            first |= 1 << i;
+        }
+    }
+    let all = u8::MAX >> (8 - block.len());
+    let second = all & !first;
+    let (fg, bg) = match seeds {
+        (None, None) => return (0, Style::default()),
+        (Some(_), None) => (first, second),
+        (None, Some(_)) => (second, first),
+        _ if widest == 0 || first.count_ones() <= second.count_ones() => (first, second),
+        _ => (second, first),
+    };
+    let mut style = Style::default();
+    if let Some(color) = average(block, fg) {
+        style = style.fg(color);
+    }
+    if let Some(color) = average(block, bg) {
+        style = style.bg(color);
+    }
+    (fg, style)
+}
+
+/// Squared RGB distance; transparent and named colours are far from
+/// anything but themselves.
+fn distance(a: Option<Color>, b: Option<Color>) -> u32 {
+    match (a, b) {
+        (Some(Color::Rgb(r1, g1, b1)), Some(Color::Rgb(r2, g2, b2))) => {
+            [(r1, r2), (g1, g2), (b1, b2)]
+                .into_iter()
+                .map(|(x, y)| u32::from(x.abs_diff(y)).pow(2))
+                .sum()
+        }
+        _ if a == b => 0,
+        _ => u32::MAX,
+    }
+}
+
+/// Average colour of the opaque pixels in `mask`; the first of them when
+/// any is not RGB.
+fn average(block: &[Option<Color>], mask: u8) -> Option<Color> {
+    let colors = block
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| mask & (1 << i) != 0)
+        .filter_map(|(_, pixel)| *pixel);
+    let mut sum = [0u32; 3];
+    let mut count = 0;
+    let mut first = None;
+    for color in colors {
+        first.get_or_insert(color);
+        let Color::Rgb(r, g, b) = color else {
+            return first;
+        };
+        for (total, channel) in sum.iter_mut().zip([r, g, b]) {
+            *total += u32::from(channel);
+        }
+        count += 1;
+    }
+    first?;
+    let [r, g, b] = sum.map(|total| (total / count) as u8);
+    Some(Color::Rgb(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORANGE: Color = Color::Rgb(255, 141, 92);
+    const DARK: Color = Color::Rgb(10, 14, 30);
+
+    fn glyphs(lines: &[Line<'_>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// A diagonal line across an 8×8 grid.
+    fn diagonal(x: usize, y: usize) -> Option<Color> {
+        (x == y).then_some(Color::Green)
+    }
+
+    #[test]
+    fn modes_pack_more_pixels_per_cell() {
+        let half = render_pixels(8, 8, diagonal, GraphicsMode::HalfBlock);
+        assert_eq!(glyphs(&half)[0], "▀▄      ");
+        let quadrant = render_pixels(8, 8, diagonal, GraphicsMode::Quadrant);
+        assert_eq!(glyphs(&quadrant), ["▚   ", " ▚  ", "  ▚ ", "   ▚"]);
+        let braille = render_pixels(8, 8, diagonal, GraphicsMode::Braille);
+        assert_eq!(glyphs(&braille), ["⠑⢄  ", "  ⠑⢄"]);
+        assert_eq!(braille[0].spans[0].style.fg, Some(Color::Green));
+        assert_eq!(braille[0].spans[0].style.bg, None);
+    }
+
+    #[test]
+    fn two_colour_blocks_use_fg_and_bg() {
+        // A dark eye pixel in an orange quadrant block
+        let block = [Some(DARK), Some(ORANGE), Some(ORANGE), Some(ORANGE)];
+        let (mask, style) = split(&block);
+        assert_eq!(QUADRANTS[usize::from(mask)], '▘');
+        assert_eq!(style.fg, Some(DARK));
+        assert_eq!(style.bg, Some(ORANGE));
+
+        // Solid blocks are all foreground, empty ones blank
+        let (mask, style) = split(&[Some(ORANGE); 4]);
+        assert_eq!((mask, style.fg, style.bg), (0b1111, Some(ORANGE), None));
+        assert_eq!(split(&[None; 8]), (0, Style::default()));
+
+        // Transparent pixels stay background even when they are fewer
+        let block = [Some(ORANGE), Some(ORANGE), Some(DARK), None];
+        let (mask, style) = split(&block);
+        assert_eq!(mask, 0b0111);
+        assert_eq!(style.bg, None);
+        assert!(matches!(style.fg, Some(Color::Rgb(r, ..)) if r > 10 && r < 255));
+    }
+
+    #[test]
+    fn partial_blocks_at_the_edges_are_transparent() {
+        let lines = render_pixels(3, 3, |_, _| Some(ORANGE), GraphicsMode::Quadrant);
+        assert_eq!(glyphs(&lines), ["█▌", "▀▘"]);
+    }
+}
@@ -1,14 +1,166 @@
-use ratatui::{layout::Rect, Frame};
-
-/// Trait for modules that render content in the hero (main) area.
-///
-/// Implement this alongside [`spud_core::module::Module`] to provide
-/// hero-area rendering. The app wires renderers to modules at registration
-/// time via [`std::any::Any`] downcasting — no rendering types leak into
-/// spud-core.
-pub trait HeroRenderer {
-    /// Render the hero (main content) area of the screen.
-    ///
-    /// Called each frame when this module is active.
-    fn render_hero(&self, f: &mut Frame, area: Rect);
+//! Hero-area rendering contract.
+//!
+//! [`HeroRenderer`] lives in `spud-core` so the module registry can look up a
+//! module's renderer directly; it is re-exported here for existing imports.
+//! A module that crashed gets [`render_crashed`] in its place, and
+//! [`HeroCache`] skips re-rendering heroes that did not change.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+use spud_core::i18n::tr_args;
+
+pub use spud_core::module::HeroRenderer;
+
+/// Fill the hero area with the error panel of a crashed module: what
+/// happened, the panic message, and how to restart it.
+pub fn render_crashed(f: &mut Frame, area: Rect, id: &str, title: &str, reason: &str) {
+    let red = Style::default().fg(Color::Red);
+    let lines = vec![
+        Line::from(""),
+        Line::styled(
+            tr_args(
+                "module {title} crashed — press r to restart",
+                &[("title", &title)],
+            ),
+            red.add_modifier(Modifier::BOLD),
+        ),
+        Line::from(""),
+        Line::styled(reason.to_string(), Style::default().fg(Color::DarkGray)),
+        Line::from(""),
+        Line::styled(
+            tr_args("or run: module restart {id}", &[("id", &id)]),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ];
+    f.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(red)
+                    .title(format!(" {title} ")),
+            ),
+        area,
+    );
+}
+
+/// The cells of the last hero rendered by a module that reports a
+/// [`HeroRenderer::content_version`], reused while its version, id, and
+/// area hold.
+#[derive(Debug, Default)]
+pub struct HeroCache {
+    /// Module id, area, and content version of `cells`.
+    key: Option<(&'static str, Rect, u64)>,
+    cells: Buffer,
+}
+
+impl HeroCache {
+    /// Draw module `id`'s hero into `area`: copied from the last render
+    /// when nothing changed, else rendered and kept if the module has a
+    /// version. Returns whether [`HeroRenderer::render_hero`] ran.
+    pub fn draw(
+        &mut self,
+        f: &mut Frame,
+        id: &'static str,
+        area: Rect,
+        renderer: &dyn HeroRenderer,
+    ) -> bool {
+        let key = renderer
+            .content_version()
+            .map(|version| (id, area, version));
+        if key.is_some() && key == self.key {
+            f.buffer_mut().merge(&self.cells);
+            return false;
+        }
+        renderer.render_hero(f, area);
+        self.key = key;
+        if key.is_some() {
+            let frame = f.buffer_mut();
+            self.cells = Buffer {
+                area,
+                content: area.positions().map(|pos| frame[pos].clone()).collect(),
+            };
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn crash_panel_names_the_module_and_the_panic() {
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        terminal
+            .draw(|f| render_crashed(f, f.area(), "stats", "System Stats", "index out of bounds"))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let text: String = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(text.contains("module System Stats crashed — press r to restart"));
+        assert!(text.contains("index out of bounds"));
+        assert!(text.contains("module restart stats"));
+    }
+
+    struct Counted {
+        renders: std::cell::Cell<u32>,
+        version: Option<u64>,
+    }
+
+    impl HeroRenderer for Counted {
+        fn render_hero(&self, f: &mut Frame, area: Rect) {
+            self.renders.set(self.renders.get() + 1);
+            f.render_widget(Line::from(format!("render {}", self.renders.get())), area);
+        }
+
+        fn content_version(&self) -> Option<u64> {
+            self.version
+        }
+    }
+
+    #[test]
+    fn cached_heroes_render_once_per_version_and_area() {
+        let mut terminal = Terminal::new(TestBackend::new(20, 4)).unwrap();
+        let mut cache = HeroCache::default();
+        let mut hero = Counted {
+            renders: std::cell::Cell::new(0),
+            version: Some(1),
+        };
+        let mut draw = |hero: &Counted, cache: &mut HeroCache, width: u16| {
+            let mut rendered = false;
+            terminal
+                .draw(|f| rendered = cache.draw(f, "hello", Rect::new(0, 1, width, 2), hero))
+                .unwrap();
+            let line: String = (0..8)
+                .map(|x| terminal.backend().buffer()[(x, 1)].symbol().to_string())
+                .collect();
+            (rendered, line)
+        };
+
+        assert_eq!(draw(&hero, &mut cache, 20), (true, "render 1".into()));
+        // Reused, not blanked: the frame buffer starts empty every draw
+        assert_eq!(draw(&hero, &mut cache, 20), (false, "render 1".into()));
+        assert_eq!(draw(&hero, &mut cache, 10), (true, "render 2".into()));
+        hero.version = Some(2);
+        assert_eq!(draw(&hero, &mut cache, 10), (true, "render 3".into()));
+        hero.version = None;
+        assert_eq!(draw(&hero, &mut cache, 10), (true, "render 4".into()));
+        assert_eq!(draw(&hero, &mut cache, 10), (true, "render 5".into()));
+    }
 }
@@ -1,32 +1,62 @@
 use ratatui::{
-    layout::{Alignment, Rect},
-    style::Style,
-    text::{Line, Text},
+    layout::{Alignment, Rect, Size},
+    style::{Color, Modifier, Style},
+    text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
+use spud_config::FaceConfig;
+use spud_core::event::AttentionLevel;
+use spud_core::i18n::tr;
+use spud_core::module::{HudLine, HudSpan, HudStyle};
+use spud_core::registry::Attention;
+use unicode_width::UnicodeWidthStr;
 
 use crate::face::build_face_text;
 use crate::layout::DoomRects;
+use crate::width::{take_width, truncate_end, truncate_middle, ELLIPSIS};
 
 /// Data passed to [`render_shell`] to populate the shell chrome.
 ///
 /// The shell view carries the text content for the top bar, HUD panels, and
-/// delegates hero-area rendering to the caller via a closure.
+/// delegates hero-area rendering to the caller via a closure. It is built
+/// every frame, so it borrows everything instead of copying it.
 pub struct ShellView<'a> {
-    /// Title of the active module, shown in the top bar.
+    /// Title of the active module, shown in the top bar when there are no
+    /// `tabs`.
     pub module_title: &'a str,
+    /// Every registered module in order, shown as tabs in the top bar.
+    pub tabs: &'a [ModuleTab<'a>],
     /// Status text displayed alongside the module title.
     pub status_line: &'a str,
+    /// Short indicators right-aligned in the top bar (e.g. log counts).
+    pub badges: &'a [HudSpan],
     /// Lines rendered in the left HUD column.
-    pub hud_left: Vec<String>,
+    pub hud_left: &'a [HudLine],
+    /// Key hints rendered in the left HUD column after `hud_left`.
+    pub hud_keys: &'a [HudLine],
     /// Lines rendered in the right HUD column.
-    pub hud_right: Vec<String>,
+    pub hud_right: &'a [HudLine],
     /// Optional text lines rendered in the HUD centre panel.
     ///
     /// Plain lines render as text, while palette-encoded lines render as a
     /// pixel sprite.
-    pub hud_face_lines: Vec<String>,
+    pub hud_face_lines: &'a [String],
+    /// How pixel faces are fitted into the centre panel.
+    pub face: FaceConfig,
+    /// Grey out the top bar and HUD columns, leaving the agent face lit
+    /// (attract mode).
+    pub dimmed: bool,
+}
+
+/// One module's tab in the top bar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleTab<'a> {
+    pub title: &'a str,
+    /// Whether this is the active module.
+    pub active: bool,
+    /// Badge for a background module that asked for attention.
+    pub attention: Option<Attention>,
 }
 
 /// Render the full Doom-style shell: top bar, hero area, and HUD panels.
@@ -40,49 +70,446 @@ pub fn render_shell(
     view: ShellView<'_>,
     hero: impl FnOnce(&mut Frame, Rect),
 ) {
-    let top = Paragraph::new(Line::from(format!(
-        "SPUD | {} | {}",
-        view.module_title, view.status_line
-    )))
-    .style(Style::default())
-    .block(Block::default().borders(Borders::BOTTOM));
+    let badges = badge_line(view.badges);
+    // The status line stops short of the badges
+    let room = usize::from(rects.top.width).saturating_sub(match badges.width() {
+        0 => 0,
+        width => width + 1,
+    });
+    let top = Paragraph::new(top_line(&view, room))
+        .style(Style::default())
+        .block(Block::default().borders(Borders::BOTTOM));
     f.render_widget(top, rects.top);
+    if !view.badges.is_empty() {
+        let badge_area = Rect {
+            height: rects.top.height.saturating_sub(1),
+            ..rects.top
+        };
+        f.render_widget(
+            Paragraph::new(badges).alignment(Alignment::Right),
+            badge_area,
+        );
+    }
 
     hero(f, rects.hero);
 
     f.render_widget(
-        Block::default().borders(Borders::ALL).title("HUD"),
+        Block::default().borders(Borders::ALL).title(tr("HUD")),
         rects.hud,
     );
 
-    let left_text = Text::from(
-        view.hud_left
-            .into_iter()
-            .map(Line::from)
-            .collect::<Vec<_>>(),
+    let left_text = hud_text(
+        view.hud_left.iter().chain(view.hud_keys),
+        rects.hud_left.width.saturating_sub(2),
     );
     let left =
-        Paragraph::new(left_text).block(Block::default().borders(Borders::ALL).title("LEFT"));
+        Paragraph::new(left_text).block(Block::default().borders(Borders::ALL).title(tr("LEFT")));
     f.render_widget(left, rects.hud_left);
 
     let face = if view.hud_face_lines.is_empty() {
-        Paragraph::new(Line::from("[ FACE ]"))
-            .block(Block::default().borders(Borders::ALL).title("AGENT"))
+        Paragraph::new(Line::from(tr("[ FACE ]")))
+            .block(Block::default().borders(Borders::ALL).title(tr("AGENT")))
     } else {
-        let face_text = build_face_text(&view.hud_face_lines);
+        let panel = Size::new(
+            rects.hud_face.width.saturating_sub(2),
+            rects.hud_face.height.saturating_sub(2),
+        );
+        let face_text = build_face_text(view.hud_face_lines, panel, &view.face);
         Paragraph::new(face_text)
             .alignment(Alignment::Center)
-            .block(Block::default().borders(Borders::ALL).title("AGENT"))
+            .block(Block::default().borders(Borders::ALL).title(tr("AGENT")))
     };
     f.render_widget(face, rects.hud_face);
 
-    let right_text = Text::from(
-        view.hud_right
-            .into_iter()
-            .map(Line::from)
-            .collect::<Vec<_>>(),
-    );
+    let right_text = hud_text(view.hud_right, rects.hud_right.width.saturating_sub(2));
     let right =
-        Paragraph::new(right_text).block(Block::default().borders(Borders::ALL).title("RIGHT"));
+        Paragraph::new(right_text).block(Block::default().borders(Borders::ALL).title(tr("RIGHT")));
     f.render_widget(right, rects.hud_right);
+
+    if view.dimmed {
+        let dim = Style::default()
+            .fg(Color::DarkGray)
+            .remove_modifier(Modifier::all());
+        for area in [rects.top, rects.hud_left, rects.hud_right] {
+            f.buffer_mut().set_style(area, dim);
+        }
+    }
+}
+
+/// Map a semantic HUD style to a terminal style.
+pub fn hud_style(style: HudStyle) -> Style {
+    match style {
+        HudStyle::Normal => Style::default(),
+        HudStyle::Dim => Style::default().fg(Color::DarkGray),
+        HudStyle::Emphasis => Style::default().add_modifier(Modifier::BOLD),
+        HudStyle::Good => Style::default().fg(Color::Green),
+        HudStyle::Warn => Style::default().fg(Color::Yellow),
+        HudStyle::Critical => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
+
+/// `SPUD | tabs | status` in `width` cells, with the active tab bracketed
+/// and a colored `●count` after each module waiting for attention. Tab
+/// titles are cut to [`MAX_TAB_WIDTH`] and the status line to what is left.
+fn top_line<'a>(view: &ShellView<'a>, width: usize) -> Line<'a> {
+    let mut spans = vec![Span::raw("SPUD | ")];
+    if view.tabs.is_empty() {
+        spans.push(Span::raw(truncate_end(view.module_title, MAX_TAB_WIDTH)));
+    }
+    for (idx, tab) in view.tabs.iter().enumerate() {
+        if idx > 0 {
+            spans.push(Span::raw(" "));
+        }
+        // Plugin modules are titled by id, which differ at the end
+        let title = truncate_middle(tab.title, MAX_TAB_WIDTH);
+        if tab.active {
+            spans.push(Span::styled(
+                format!("[{title}]"),
+                hud_style(HudStyle::Emphasis),
+            ));
+        } else {
+            spans.push(Span::styled(title, hud_style(HudStyle::Dim)));
+        }
+        if let Some(badge) = tab.attention {
+            spans.push(Span::styled(
+                format!("●{}", badge.count),
+                attention_style(badge.level),
+            ));
+        }
+    }
+    spans.push(Span::raw(" | "));
+    let used: usize = spans.iter().map(Span::width).sum();
+    spans.push(Span::raw(truncate_end(
+        view.status_line,
+        width.saturating_sub(used),
+    )));
+    Line::from(spans)
+}
+
+/// Widest a module title gets in the top bar.
+const MAX_TAB_WIDTH: usize = 16;
+
+/// Map an attention level to its badge style.
+pub fn attention_style(level: AttentionLevel) -> Style {
+    hud_style(match level {
+        AttentionLevel::Info => HudStyle::Good,
+        AttentionLevel::Warn => HudStyle::Warn,
+        AttentionLevel::Alert => HudStyle::Critical,
+    })
+}
+
+/// Badges separated by a space, each in its own style.
+fn badge_line(badges: &[HudSpan]) -> Line<'_> {
+    let mut spans = Vec::with_capacity(badges.len() * 2);
+    for (idx, badge) in badges.iter().enumerate() {
+        if idx > 0 {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(badge.text.as_ref(), hud_style(badge.style)));
+    }
+    Line::from(spans)
+}
+
+fn hud_text<'a>(lines: impl IntoIterator<Item = &'a HudLine>, width: u16) -> Text<'a> {
+    Text::from(
+        lines
+            .into_iter()
+            .map(|line| hud_line(line, width as usize))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Lay out one HUD line for a column `width` cells wide.
+///
+/// Key/value lines pad the value flush right; gauges give the bar whatever
+/// width remains after the label and value (at least [`MIN_GAUGE_WIDTH`]).
+fn hud_line(line: &HudLine, width: usize) -> Line<'_> {
+    match line {
+        HudLine::Spans(spans) => Line::from(clip_spans(spans, width)),
+        HudLine::KeyValue { key, value, style } => {
+            // The value is the reading; a long key gives way to it
+            let value = truncate_end(value, width);
+            let value_width = value.width();
+            let key = truncate_end(key, width.saturating_sub(value_width + 1));
+            let pad = width.saturating_sub(key.width() + value_width).max(1);
+            Line::from(vec![
+                Span::raw(key),
+                Span::raw(" ".repeat(pad)),
+                Span::styled(value, hud_style(*style)),
+            ])
+        }
+        HudLine::Gauge {
+            label,
+            ratio,
+            value,
+            style,
+        } => {
+            // label, space, [bar], space, value; the label is cut first
+            let value_width = value.width();
+            let label = truncate_end(
+                label,
+                width.saturating_sub(value_width + 4 + MIN_GAUGE_WIDTH),
+            );
+            let bar_width = width
+                .saturating_sub(label.width() + value_width + 4)
+                .max(MIN_GAUGE_WIDTH);
+            let filled = ((*ratio * bar_width as f32).round() as usize).min(bar_width);
+            let style = hud_style(*style);
+            Line::from(vec![
+                Span::raw(label),
+                Span::raw(" ["),
+                Span::styled("█".repeat(filled), style),
+                Span::styled("░".repeat(bar_width - filled), hud_style(HudStyle::Dim)),
+                Span::raw("] "),
+                Span::styled(value.as_ref(), style),
+            ])
+        }
+    }
+}
+
+/// Styled spans cut to `width` cells, with an ellipsis where they were cut.
+fn clip_spans(spans: &[HudSpan], width: usize) -> Vec<Span<'_>> {
+    let total: usize = spans.iter().map(|span| span.text.width()).sum();
+    let mut room = width;
+    let mut clipped = Vec::with_capacity(spans.len());
+    for span in spans {
+        let style = hud_style(span.style);
+        let span_width = span.text.width();
+        if total <= width || span_width < room {
+            clipped.push(Span::styled(span.text.as_ref(), style));
+            room = room.saturating_sub(span_width);
+        } else {
+            // Always room for the ellipsis: earlier spans left at least a cell
+            let (kept, _) = take_width(&span.text, room.saturating_sub(1));
+            clipped.push(Span::styled(format!("{kept}{ELLIPSIS}"), style));
+            break;
+        }
+    }
+    clipped
+}
+
+/// Narrowest bar drawn for a HUD gauge, even when the column is cramped.
+const MIN_GAUGE_WIDTH: usize = 3;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(line: &Line<'_>) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn badges_keep_their_styles() {
+        let badges = [
+            HudSpan::new("E:1 W:2", HudStyle::Critical),
+            HudSpan::new("REC", HudStyle::Dim),
+        ];
+        let line = badge_line(&badges);
+        assert_eq!(text_of(&line), "E:1 W:2 REC");
+        assert_eq!(line.spans[0].style, hud_style(HudStyle::Critical));
+        assert_eq!(line.spans[2].style, hud_style(HudStyle::Dim));
+    }
+
+    #[test]
+    fn top_line_shows_tabs_and_attention_badges() {
+        let view = ShellView {
+            module_title: "Hello",
+            tabs: &[
+                ModuleTab {
+                    title: "Hello",
+                    active: true,
+                    attention: None,
+                },
+                ModuleTab {
+                    title: "Net",
+                    active: false,
+                    attention: Some(Attention {
+                        level: AttentionLevel::Alert,
+                        count: 3,
+                    }),
+                },
+            ],
+            status_line: "ok",
+            badges: &[],
+            hud_left: &[],
+            hud_keys: &[],
+            hud_right: &[],
+            hud_face_lines: &[],
+            face: FaceConfig::default(),
+            dimmed: false,
+        };
+        let line = top_line(&view, 80);
+        assert_eq!(text_of(&line), "SPUD | [Hello] Net●3 | ok");
+        assert_eq!(line.spans[4].style, attention_style(AttentionLevel::Alert));
+
+        let bare = ShellView { tabs: &[], ..view };
+        assert_eq!(text_of(&top_line(&bare, 80)), "SPUD | Hello | ok");
+    }
+
+    #[test]
+    fn top_line_cuts_long_titles_and_status() {
+        let tabs = [ModuleTab {
+            title: "com.example.weather-radar",
+            active: true,
+            attention: None,
+        }];
+        let view = ShellView {
+            module_title: "com.example.weather-radar",
+            tabs: &tabs,
+            status_line: "fetching radar tiles for the whole region",
+            badges: &[],
+            hud_left: &[],
+            hud_keys: &[],
+            hud_right: &[],
+            hud_face_lines: &[],
+            face: FaceConfig::default(),
+            dimmed: false,
+        };
+        let text = text_of(&top_line(&view, 40));
+        assert_eq!(text, "SPUD | [com.exam…r-radar] | fetching ra…");
+        assert_eq!(text.width(), 40);
+        // No room left: the status line is dropped, not pushed off-screen
+        assert_eq!(
+            text_of(&top_line(&view, 20)),
+            "SPUD | [com.exam…r-radar] | "
+        );
+    }
+
+    #[test]
+    fn dimmed_shell_greys_out_all_but_the_face() {
+        use crate::layout::doom_layout;
+        use ratatui::{backend::TestBackend, Terminal};
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        let mut rects = None;
+        terminal
+            .draw(|f| {
+                let layout = doom_layout(f.area(), 9, 18);
+                rects = Some(layout);
+                let view = ShellView {
+                    module_title: "Hello",
+                    tabs: &[],
+                    status_line: "ok",
+                    badges: &[],
+                    hud_left: &[HudLine::key_value("CPU", "5%", HudStyle::Critical)],
+                    hud_keys: &[],
+                    hud_right: &[],
+                    hud_face_lines: &["(o_o)".to_string()],
+                    face: FaceConfig::default(),
+                    dimmed: true,
+                };
+                render_shell(f, layout, view, |_, _| {});
+            })
+            .unwrap();
+        let rects = rects.unwrap();
+        let buffer = terminal.backend().buffer();
+        let left = rects.hud_left;
+        assert_eq!(buffer[(left.x + 1, left.y + 1)].fg, Color::DarkGray);
+        assert!(buffer[(left.x + 1, left.y + 1)].modifier.is_empty());
+        assert_eq!(buffer[(rects.top.x, rects.top.y)].fg, Color::DarkGray);
+        let face = rects.hud_face;
+        assert_ne!(buffer[(face.x, face.y)].fg, Color::DarkGray);
+    }
+
+    #[test]
+    fn key_value_right_aligns_value() {
+        let kv = HudLine::key_value("RSS", "12 MiB", HudStyle::Good);
+        let line = hud_line(&kv, 16);
+        let text = text_of(&line);
+        assert_eq!(text, "RSS       12 MiB");
+        assert_eq!(line.spans[2].style, hud_style(HudStyle::Good));
+    }
+
+    #[test]
+    fn gauge_fills_remaining_width() {
+        let gauge = HudLine::gauge("CPU", 0.5, "50%", HudStyle::Warn);
+        let line = hud_line(&gauge, 18);
+        let text = text_of(&line);
+        assert_eq!(text.width(), 18);
+        assert_eq!(text, "CPU [████░░░░] 50%");
+    }
+
+    #[test]
+    fn gauge_keeps_minimum_bar_when_cramped() {
+        let gauge = HudLine::gauge("MEM", 1.0, "100.0%", HudStyle::Critical);
+        let line = hud_line(&gauge, 4);
+        assert_eq!(text_of(&line), " [███] 100.0%");
+    }
+
+    #[test]
+    fn wide_text_is_measured_in_cells() {
+        // Each ideograph takes two cells
+        let temp = HudLine::key_value("温度", "61°C", HudStyle::Normal);
+        assert_eq!(text_of(&hud_line(&temp, 12)), "温度    61°C");
+        // Too wide: the key gives way to the value, never the panel edge
+        let mem = HudLine::key_value("メモリ使用量", "8 GiB", HudStyle::Normal);
+        assert_eq!(text_of(&hud_line(&mem, 12)), "メモ…  8 GiB");
+        let gauge = HudLine::gauge("🔋 バッテリー", 0.5, "50%", HudStyle::Good);
+        let text = text_of(&hud_line(&gauge, 16));
+        assert_eq!(text, "🔋 バ… [██░] 50%");
+        assert_eq!(text.width(), 16);
+    }
+
+    #[test]
+    fn long_hud_spans_end_in_an_ellipsis() {
+        let line = HudLine::Spans(vec![
+            HudSpan::new("plugin ", HudStyle::Dim),
+            HudSpan::new("com.example.weather", HudStyle::Good),
+        ]);
+        let clipped = hud_line(&line, 12);
+        assert_eq!(text_of(&clipped), "plugin com.…");
+        assert_eq!(clipped.spans[1].style, hud_style(HudStyle::Good));
+        assert_eq!(text_of(&hud_line(&line, 40)), "plugin com.example.weather");
+    }
+
+    #[test]
+    fn shell_snapshot() {
+        use crate::layout::doom_layout;
+
+        let text = spud_testkit::render(72, 20, |f| {
+            let rects = doom_layout(f.area(), 7, 14);
+            let view = ShellView {
+                module_title: "Stats",
+                tabs: &[
+                    ModuleTab {
+                        title: "Hello",
+                        active: false,
+                        attention: None,
+                    },
+                    ModuleTab {
+                        title: "Stats",
+                        active: true,
+                        attention: None,
+                    },
+                    ModuleTab {
+                        title: "Plugins",
+                        active: false,
+                        attention: Some(Attention {
+                            level: AttentionLevel::Warn,
+                            count: 2,
+                        }),
+                    },
+                ],
+                status_line: "Switched to Stats",
+                badges: &[HudSpan::new("E:1", HudStyle::Critical)],
+                hud_left: &[HudLine::key_value("CPU", "12%", HudStyle::Normal)],
+                hud_keys: &[
+                    HudLine::from("h: toggle per-core heatmap"),
+                    HudLine::from("Tab: next module"),
+                ],
+                hud_right: &[HudLine::key_value("TPS", "60.0", HudStyle::Dim)],
+                hud_face_lines: &["(o_o)".to_string()],
+                face: FaceConfig::default(),
+                dimmed: false,
+            };
+            render_shell(f, rects, view, |f, area| {
+                f.render_widget(Paragraph::new("hero"), area);
+            });
+        });
+        insta::assert_snapshot!(spud_testkit::normalize(&text));
+    }
 }
@@ -0,0 +1,192 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+/// The logo, drawn when the terminal is wide enough.
+const LOGO: [&str; 6] = [
+    "███████╗██████╗ ██╗   ██╗██████╗ ",
+    "██╔════╝██╔══██╗██║   ██║██╔══██╗",
+    "███████╗██████╔╝██║   ██║██║  ██║",
+    "╚════██║██╔═══╝ ██║   ██║██║  ██║",
+    "███████║██║     ╚██████╔╝██████╔╝",
+    "╚══════╝╚═╝      ╚═════╝ ╚═════╝ ",
+];
+
+/// Spinner shown next to running steps, one frame per redraw.
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Content of the startup splash.
+pub struct SplashView<'a> {
+    /// Version line under the logo.
+    pub version: &'a str,
+    /// Startup steps in the order they began.
+    pub steps: &'a [SplashStep],
+    /// Redraw count, which animates the spinner.
+    pub frame: usize,
+}
+
+/// One line of startup progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplashStep {
+    pub label: String,
+    pub status: StepStatus,
+}
+
+/// How far a startup step got.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepStatus {
+    Running,
+    /// Finished, with optional detail such as a plugin's API version.
+    Done(String),
+    /// Failed, with the reason.
+    Failed(String),
+}
+
+/// Render the splash over the whole of `area`: the logo and version,
+/// centred, above the steps. When the steps do not fit, the newest are
+/// shown.
+pub fn render_splash(f: &mut Frame, area: Rect, view: &SplashView<'_>) {
+    f.render_widget(Clear, area);
+
+    let logo_width = LOGO[0].width() as u16;
+    let mut lines: Vec<Line<'static>> = if area.width >= logo_width && area.height > 12 {
+        LOGO.iter()
+            .map(|row| Line::styled(*row, Style::default().fg(Color::Yellow)))
+            .collect()
+    } else {
+        vec![Line::styled(
+            "SPUD",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]
+    };
+    lines.push(Line::styled(
+        view.version.to_string(),
+        Style::default().fg(Color::DarkGray),
+    ));
+    lines.push(Line::default());
+    let header = lines.len() as u16;
+
+    let room = usize::from(area.height.saturating_sub(header));
+    let skipped = view.steps.len().saturating_sub(room);
+    let steps: Vec<Line<'static>> = view.steps[skipped..]
+        .iter()
+        .map(|step| step_line(step, view.frame))
+        .collect();
+    let steps_width = steps.iter().map(Line::width).max().unwrap_or(0) as u16;
+
+    let height = (header + steps.len() as u16).min(area.height);
+    let top = area.y + (area.height - height) / 2;
+    f.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        Rect {
+            y: top,
+            height: header.min(area.height),
+            ..area
+        },
+    );
+    let width = steps_width.min(area.width);
+    f.render_widget(
+        Paragraph::new(steps),
+        Rect {
+            x: area.x + (area.width - width) / 2,
+            y: top + header.min(height),
+            width,
+            height: height - header.min(height),
+        },
+    );
+}
+
+/// `<mark> <label>  <detail>`, coloured by status.
+fn step_line(step: &SplashStep, frame: usize) -> Line<'static> {
+    let (mark, color, detail) = match &step.status {
+        StepStatus::Running => (SPINNER[frame % SPINNER.len()], Color::Cyan, ""),
+        StepStatus::Done(detail) => ("✓", Color::Green, detail.as_str()),
+        StepStatus::Failed(reason) => ("✗", Color::Red, reason.as_str()),
+    };
+    let mut spans = vec![
+        Span::styled(mark, Style::default().fg(color)),
+        Span::raw(format!(" {}", step.label)),
+    ];
+    if !detail.is_empty() {
+        let style = match step.status {
+            StepStatus::Failed(_) => Style::default().fg(Color::Red),
+            _ => Style::default().fg(Color::DarkGray),
+        };
+        spans.push(Span::styled(format!("  {detail}"), style));
+    }
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    fn render(width: u16, height: u16, steps: &[SplashStep]) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| {
+                let view = SplashView {
+                    version: "0.1.0 (abc1234 2026-10-16)",
+                    steps,
+                    frame: 1,
+                };
+                render_splash(f, f.area(), &view);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn step(label: &str, status: StepStatus) -> SplashStep {
+        SplashStep {
+            label: label.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn shows_logo_version_and_step_statuses() {
+        let steps = [
+            step("Hello module", StepStatus::Done(String::new())),
+            step("plugin demo.ok", StepStatus::Done("host api 1.3.0".into())),
+            step("plugin demo.slow", StepStatus::Running),
+            step(
+                "plugin demo.bad",
+                StepStatus::Failed("handshake timed out".into()),
+            ),
+        ];
+        let text = render(60, 20, &steps);
+        assert!(text.contains(LOGO[0].trim_end()));
+        assert!(text.contains("0.1.0 (abc1234 2026-10-16)"));
+        assert!(text.contains("✓ plugin demo.ok  host api 1.3.0"));
+        assert!(text.contains("⠙ plugin demo.slow"));
+        assert!(text.contains("✗ plugin demo.bad  handshake timed out"));
+    }
+
+    #[test]
+    fn small_terminals_get_a_plain_title_and_the_newest_steps() {
+        let steps: Vec<SplashStep> = (0..10)
+            .map(|n| step(&format!("step {n}"), StepStatus::Running))
+            .collect();
+        let text = render(24, 6, &steps);
+        assert!(text.contains("SPUD"));
+        assert!(!text.contains('█'));
+        assert!(text.contains("step 9"));
+        assert!(!text.contains("step 6"));
+    }
+}
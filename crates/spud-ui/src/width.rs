@@ -0,0 +1,200 @@
+//! Display width of text in terminal cells.
+//!
+//! A `char` is not a cell: CJK ideographs and most emoji take two, combining
+//! marks and zero-width joiners none. Layout code measures and cuts text
+//! with these helpers instead of counting chars or bytes, so wide text
+//! lines up with narrow text and stops at the panel edge. Text is cut
+//! between grapheme clusters, so an accent stays on its letter.
+//!
+//! Text that does not fit is cut with an ellipsis: at the end for titles
+//! and messages ([`truncate_end`]), in the middle for ids and paths, whose
+//! ends tell them apart ([`truncate_middle`]).
+
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Marks where truncated text was cut.
+pub const ELLIPSIS: char = '…';
+
+/// Cells `text` takes on screen.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Cells `ch` takes on screen; control characters take none.
+pub fn char_width(ch: char) -> usize {
+    ch.width().unwrap_or(0)
+}
+
+/// Each grapheme cluster of `text` with its byte offset and width.
+pub fn graphemes(text: &str) -> impl DoubleEndedIterator<Item = (usize, &str, usize)> {
+    text.grapheme_indices(true)
+        .map(|(index, grapheme)| (index, grapheme, grapheme.width()))
+}
+
+/// The longest prefix of `text` that fits in `max` cells, and its width.
+/// A wide char that would straddle the edge is left out.
+pub fn take_width(text: &str, max: usize) -> (&str, usize) {
+    let mut used = 0;
+    for (index, _, cells) in graphemes(text) {
+        if used + cells > max {
+            return (&text[..index], used);
+        }
+        used += cells;
+    }
+    (text, used)
+}
+
+/// The longest suffix of `text` that fits in `max` cells, and its width.
+pub fn take_width_end(text: &str, max: usize) -> (&str, usize) {
+    let mut used = 0;
+    for (index, grapheme, cells) in graphemes(text).rev() {
+        if used + cells > max {
+            return (&text[index + grapheme.len()..], used);
+        }
+        used += cells;
+    }
+    (text, used)
+}
+
+/// The first column at or after `column` where a grapheme cluster of
+/// `text` starts, or its width if none does.
+pub fn column_boundary(text: &str, column: usize) -> usize {
+    let mut start = 0;
+    for (_, _, cells) in graphemes(text) {
+        if start >= column {
+            break;
+        }
+        start += cells;
+    }
+    start
+}
+
+/// `text` if it fits in `width` cells, else as much of its start as fits
+/// with an ellipsis after it.
+pub fn truncate_end(text: &str, width: usize) -> Cow<'_, str> {
+    if text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+    let Some(room) = width.checked_sub(1) else {
+        return Cow::Borrowed("");
+    };
+    let (kept, _) = take_width(text, room);
+    Cow::Owned(format!("{kept}{ELLIPSIS}"))
+}
+
+/// `text` if it fits in `width` cells, else its start and end around an
+/// ellipsis: `com.example.…-plugin`.
+pub fn truncate_middle(text: &str, width: usize) -> Cow<'_, str> {
+    if text.width() <= width {
+        return Cow::Borrowed(text);
+    }
+    let Some(room) = width.checked_sub(1) else {
+        return Cow::Borrowed("");
+    };
+    let (head, head_width) = take_width(text, room.div_ceil(2));
+    let (tail, _) = take_width_end(text, room - head_width);
+    Cow::Owned(format!("{head}{ELLIPSIS}{tail}"))
+}
+
+/// `text` cut to `width` cells and padded with spaces to exactly that.
+pub fn fit_width(text: &str, width: usize) -> String {
+    let (kept, used) = take_width(text, width);
+    let mut fitted = String::with_capacity(kept.len() + width - used);
+    fitted.push_str(kept);
+    fitted.extend(std::iter::repeat_n(' ', width - used));
+    fitted
+}
+
+/// The cells of `text` from column `start` up to (not including) `end`.
+/// Half of a wide char cut by either edge shows as a space, so what
+/// follows stays in its column.
+pub fn columns(text: &str, start: usize, end: usize) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    for (_, grapheme, cells) in graphemes(text) {
+        if column >= end {
+            break;
+        }
+        let next = column + cells;
+        if column >= start && next <= end {
+            out.push_str(grapheme);
+        } else if next > start {
+            // Straddles an edge: keep the cells inside it
+            let inside = next.min(end) - column.max(start);
+            out.extend(std::iter::repeat_n(' ', inside));
+        }
+        column = next;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_chars_take_two_cells() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("日本"), 4);
+        assert_eq!(width("e\u{301}"), 1);
+        assert_eq!(char_width('\u{7}'), 0);
+    }
+
+    #[test]
+    fn take_width_stops_before_a_straddling_char() {
+        assert_eq!(take_width("日本語", 5), ("日本", 4));
+        assert_eq!(take_width("日本語", 6), ("日本語", 6));
+        assert_eq!(take_width("ab", 0), ("", 0));
+        assert_eq!(take_width_end("日本語", 5), ("本語", 4));
+        assert_eq!(fit_width("日本語", 5), "日本 ");
+        assert_eq!(fit_width("ok", 4), "ok  ");
+    }
+
+    #[test]
+    fn truncation_marks_the_cut() {
+        assert_eq!(truncate_end("status", 6), "status");
+        assert!(matches!(truncate_end("status", 6), Cow::Borrowed(_)));
+        assert_eq!(truncate_end("status line", 7), "status…");
+        assert_eq!(truncate_end("日本語", 4), "日…");
+        assert_eq!(truncate_end("abc", 0), "");
+        assert_eq!(truncate_end("abc", 1), "…");
+
+        assert_eq!(truncate_middle("com.example.plugin", 11), "com.e…lugin");
+        assert_eq!(
+            truncate_middle("/home/me/a/b/spud.toml", 12),
+            "/home/….toml"
+        );
+        assert_eq!(truncate_middle("日本語テキスト", 7), "日…スト");
+        for width in 0..12 {
+            assert!(truncate_middle("plugin.日本.example", width).width() <= width);
+            assert!(truncate_end("plugin.日本.example", width).width() <= width);
+        }
+    }
+
+    #[test]
+    fn columns_keep_cells_in_place() {
+        assert_eq!(columns("abcdef", 2, 4), "cd");
+        // "日" covers columns 0-1, "本" 2-3
+        assert_eq!(columns("日本x", 1, 5), " 本x");
+        assert_eq!(columns("日本x", 0, 3), "日 ");
+        assert_eq!(columns("abc", 5, 8), "");
+        // An accent is never split from its letter
+        assert_eq!(columns("ae\u{301}b", 1, 2), "e\u{301}");
+    }
+
+    #[test]
+    fn graphemes_are_measured_whole() {
+        let text = "e\u{301}日本";
+        let cells: Vec<_> = graphemes(text).map(|(i, _, cells)| (i, cells)).collect();
+        assert_eq!(cells, [(0, 1), (3, 2), (6, 2)]);
+        assert_eq!(take_width(text, 2), ("e\u{301}", 1));
+        assert_eq!(take_width_end("ae\u{301}", 1), ("e\u{301}", 1));
+        // Column 2 is the second half of "日": the next boundary is 3
+        assert_eq!(column_boundary(text, 2), 3);
+        assert_eq!(column_boundary(text, 3), 3);
+        assert_eq!(column_boundary(text, 9), 5);
+    }
+}